@@ -0,0 +1,116 @@
+//! The about screen: which build is running and where its data and config
+//! actually live, read from the database and the file system each time.
+
+use anyhow::Result;
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Cell, Row, Table},
+    Frame,
+};
+use std::{fs, path::Path};
+
+use crate::config;
+use crate::data::format_size;
+use crate::migrations;
+use crate::{dates, App, Database, Screen};
+
+impl Database {
+    /// Dates of the first and latest sets not in the trash.
+    pub fn workout_date_range(&self) -> Result<Option<(String, String)>> {
+        Ok(self.conn.query_row(
+            "SELECT MIN(timestamp), MAX(timestamp) FROM workouts WHERE deleted_at IS NULL",
+            [],
+            |row| {
+                let first: Option<String> = row.get(0)?;
+                let last: Option<String> = row.get(1)?;
+                Ok(first.zip(last))
+            },
+        )?)
+    }
+}
+
+/// A path as it would be found from anywhere, for files that exist.
+fn absolute(path: &Path) -> String {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()).display().to_string()
+}
+
+impl App {
+    /// Opens the about screen; Esc goes back to wherever it was opened from.
+    pub(crate) fn open_about(&mut self, from_settings: bool) {
+        self.screen = Screen::About;
+        self.about_from_settings = from_settings;
+        self.message = None;
+    }
+
+    pub(crate) fn handle_about_input(&mut self, key: KeyCode) -> Result<bool> {
+        if key == KeyCode::Esc {
+            self.screen = if self.about_from_settings { Screen::Settings } else { Screen::Main };
+        }
+        Ok(false)
+    }
+
+    /// Label and value of every row on the about screen.
+    fn about_rows(&self) -> Result<Vec<(&str, String)>> {
+        let text = &self.text;
+        let path = self.db.file_path();
+        let size = fs::metadata(&path).map_or_else(|_| text.get("about.missing").to_string(), |meta| format_size(meta.len()));
+        let counts = self.db.row_counts()?;
+        let sets = counts.iter().find(|(table, _)| table == "workouts").map_or(0, |(_, count)| *count);
+        let total: i64 = counts.iter().map(|(_, count)| count).sum();
+        let dates = |timestamp: &str| match dates::parse_timestamp(timestamp) {
+            Some(at) => self.display_date(at.date()),
+            None => timestamp.to_string(),
+        };
+        let (first, latest) = match self.db.workout_date_range()? {
+            Some((first, latest)) => (dates(&first), dates(&latest)),
+            None => (text.get("about.none").to_string(), text.get("about.none").to_string()),
+        };
+        let config_path = Path::new(config::CONFIG_PATH);
+        let config_file = if config_path.exists() {
+            absolute(config_path)
+        } else {
+            text.format("about.no_config", &[("path", &config_path.display())])
+        };
+        let schema = text.format(
+            "about.schema_of",
+            &[("version", &migrations::schema_version(&self.db.conn)?), ("latest", &migrations::latest_version())],
+        );
+        let backend = if self.db.jsonl.is_some() { "JSON lines" } else { "SQLite" };
+        let storage = if self.db.is_encrypted() { format!("{backend}, {}", text.get("about.encrypted")) } else { backend.to_string() };
+        Ok(vec![
+            (text.get("about.version"), env!("CARGO_PKG_VERSION").to_string()),
+            (
+                text.get("about.data_file"),
+                if path.as_os_str().is_empty() { text.get("about.in_memory").to_string() } else { absolute(&path) },
+            ),
+            (text.get("about.storage"), storage),
+            (text.get("about.file_size"), size),
+            (text.get("about.rows"), text.format("about.rows_of", &[("total", &total), ("sets", &sets)])),
+            (text.get("about.first_set"), first),
+            (text.get("about.latest_set"), latest),
+            (text.get("about.profile"), self.profiles.active.clone()),
+            (text.get("about.schema"), schema),
+            (text.get("about.config_file"), config_file),
+        ])
+    }
+}
+
+pub fn render_about_screen(f: &mut Frame, area: Rect, app: &App) {
+    let rows: Vec<Row> = match app.about_rows() {
+        Ok(rows) => rows
+            .into_iter()
+            .map(|(label, value)| {
+                Row::new(vec![
+                    Cell::from(label).style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+                    Cell::from(value),
+                ])
+            })
+            .collect(),
+        Err(e) => vec![Row::new(vec![Cell::from(format!("{e:#}")).style(Style::default().fg(Color::Red))])],
+    };
+    let table = Table::new(rows, [Constraint::Length(16), Constraint::Min(0)])
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("about.title")));
+    f.render_widget(table, area);
+}
@@ -0,0 +1,248 @@
+//! AMRAP mode: a countdown for "as many reps as possible", tapping a key
+//! after each mini-set. When time runs out the tally goes into the add form
+//! for the exercise it was started from.
+
+use anyhow::Result;
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use std::time::Duration;
+
+use crate::exercises::render_dialog;
+use crate::input::NumericInput;
+use crate::notify::NotifyEvent;
+use crate::stats::format_duration;
+use crate::timer::{format_remaining, TimerState};
+use crate::{AddField, App, Screen};
+
+const DEFAULT_MINUTES: u32 = 10;
+/// Pace is left out until then, since the first few taps make it wild.
+const PACE_AFTER: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+pub struct Countdown {
+    clock: TimerState,
+    /// Reps added by each tap, so the last one can be taken back.
+    taps: Vec<i32>,
+    per_tap: i32,
+}
+
+impl Countdown {
+    fn new(length: Duration, per_tap: i32) -> Self {
+        Self {
+            clock: TimerState::countdown(length),
+            taps: Vec::new(),
+            per_tap,
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.clock.elapsed()
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.clock.remaining().unwrap_or_default()
+    }
+
+    pub fn paused(&self) -> bool {
+        self.clock.is_paused()
+    }
+
+    pub fn reps(&self) -> i32 {
+        self.taps.iter().sum()
+    }
+
+    /// Reps per minute so far, once enough time has gone by.
+    pub fn pace(&self) -> Option<f64> {
+        let elapsed = self.elapsed();
+        (elapsed >= PACE_AFTER).then(|| self.reps() as f64 / (elapsed.as_secs_f64() / 60.0))
+    }
+}
+
+#[derive(Debug)]
+pub enum Amrap {
+    /// Choosing the length and how many reps a tap counts for.
+    Setup {
+        minutes: NumericInput,
+        per_tap: NumericInput,
+        /// Editing `per_tap` rather than `minutes`.
+        on_reps: bool,
+    },
+    Running {
+        countdown: Countdown,
+        /// Asking whether to stop without logging anything.
+        confirm_abort: bool,
+    },
+}
+
+#[derive(Debug)]
+pub struct AmrapState {
+    pub exercise: String,
+    pub mode: Amrap,
+}
+
+impl App {
+    /// Starts setting up a countdown for the exercise picked on the add
+    /// screen, which has to be counted in reps.
+    pub(crate) fn open_amrap(&mut self) {
+        let Some(exercise) = self.picker_exercises().get(self.selected_exercise).map(|e| (*e).clone()) else {
+            return;
+        };
+        if exercise.unit != "reps" {
+            self.message = Some(self.text.get("amrap.reps_only").to_string());
+            return;
+        }
+        let mut minutes = NumericInput::default();
+        minutes.set(DEFAULT_MINUTES);
+        let mut per_tap = NumericInput::default();
+        per_tap.set(1);
+        self.amrap = Some(AmrapState {
+            exercise: exercise.name,
+            mode: Amrap::Setup { minutes, per_tap, on_reps: false },
+        });
+        self.screen = Screen::Amrap;
+        self.message = None;
+    }
+
+    pub(crate) fn handle_amrap_input(&mut self, key: KeyCode) -> Result<bool> {
+        let Some(state) = &mut self.amrap else {
+            self.screen = Screen::AddWorkout;
+            return Ok(false);
+        };
+        match &mut state.mode {
+            Amrap::Setup { minutes, per_tap, on_reps } => match key {
+                KeyCode::Esc => self.close_amrap(),
+                KeyCode::Tab | KeyCode::Up | KeyCode::Down => *on_reps = !*on_reps,
+                KeyCode::Enter => {
+                    let length = minutes.as_str().parse::<u64>().ok().filter(|m| *m > 0);
+                    let reps = per_tap.as_str().parse::<i32>().ok().filter(|r| *r > 0);
+                    match length.zip(reps) {
+                        Some((length, reps)) => {
+                            state.mode = Amrap::Running {
+                                countdown: Countdown::new(Duration::from_secs(length * 60), reps),
+                                confirm_abort: false,
+                            };
+                            self.message = None;
+                        }
+                        None => self.message = Some(self.text.get("amrap.invalid").to_string()),
+                    }
+                }
+                _ => {
+                    if *on_reps { per_tap.handle_key(key) } else { minutes.handle_key(key) };
+                }
+            },
+            Amrap::Running { confirm_abort: confirm @ true, .. } => match key {
+                KeyCode::Char('y') => self.close_amrap(),
+                KeyCode::Char('n') | KeyCode::Esc => *confirm = false,
+                _ => {}
+            },
+            Amrap::Running { countdown, confirm_abort } => match key {
+                KeyCode::Enter => countdown.taps.push(countdown.per_tap),
+                KeyCode::Backspace => {
+                    countdown.taps.pop();
+                }
+                KeyCode::Esc if countdown.clock.asks_before_cancel() => *confirm_abort = true,
+                KeyCode::Esc => self.close_amrap(),
+                key => {
+                    countdown.clock.handle_key(key);
+                }
+            },
+        }
+        Ok(false)
+    }
+
+    /// Back to the add screen without logging anything.
+    fn close_amrap(&mut self) {
+        self.amrap = None;
+        self.screen = Screen::AddWorkout;
+        self.message = None;
+    }
+
+    /// Runs on every tick; when the countdown ends it alerts and moves the
+    /// tally into the add form, leaving the set to be saved from there.
+    pub(crate) fn check_amrap(&mut self) {
+        let Some(AmrapState { exercise, mode: Amrap::Running { countdown, .. } }) = &self.amrap else {
+            return;
+        };
+        if !countdown.clock.is_finished() {
+            return;
+        }
+        let (exercise, reps, length) = (exercise.clone(), countdown.reps(), countdown.elapsed());
+        self.amrap = None;
+        let amount = self.format_count(&exercise, reps as i64);
+        let message = self.text.format(
+            "amrap.done",
+            &[("amount", &amount), ("exercise", &exercise), ("time", &format_duration(length.as_secs() as i64))],
+        );
+        self.notify(NotifyEvent::TimerDone, &message);
+        if let Some(index) = self.picker_exercises().iter().position(|e| e.name == exercise) {
+            self.selected_exercise = index;
+        }
+        self.input_count.set(reps);
+        self.add_field = AddField::Count;
+        self.screen = Screen::AddWorkout;
+        self.message = Some(message);
+    }
+}
+
+pub fn render_amrap_screen(f: &mut Frame, area: Rect, app: &App) {
+    let Some(state) = &app.amrap else {
+        return;
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(7), Constraint::Min(0), Constraint::Length(3)])
+        .split(area);
+    let title = app.text.format("amrap.title", &[("exercise", &state.exercise)]);
+
+    match &state.mode {
+        Amrap::Setup { minutes, per_tap, on_reps } => {
+            let field = |label: &str, input: &NumericInput, active: bool| {
+                let marker = if active { "▶" } else { " " };
+                format!("{marker} {label}: {}", input.as_str())
+            };
+            let lines = [
+                field(app.text.get("amrap.minutes"), minutes, !on_reps),
+                field(app.text.get("amrap.per_tap"), per_tap, *on_reps),
+                String::new(),
+                app.text.get("amrap.setup_keys").to_string(),
+            ];
+            let setup = Paragraph::new(lines.join("\n")).block(Block::default().borders(Borders::ALL).title(title));
+            f.render_widget(setup, chunks[0]);
+        }
+        Amrap::Running { countdown, confirm_abort } => {
+            let color = if countdown.paused() { Color::Yellow } else { Color::Green };
+            let mut clock = app.text.format(
+                "amrap.clock",
+                &[("remaining", &format_remaining(countdown.remaining())), ("elapsed", &format_duration(countdown.elapsed().as_secs() as i64))],
+            );
+            if countdown.paused() {
+                clock = format!("{clock}  {}", app.text.get("timer.paused"));
+            }
+            let reps = app.text.format(
+                "amrap.tally",
+                &[("reps", &countdown.reps()), ("taps", &countdown.taps.len()), ("pace", &countdown.pace().map_or("-".to_string(), |pace| format!("{pace:.1}")))],
+            );
+            let lines = ["", clock.as_str(), "", reps.as_str()];
+            let running = Paragraph::new(lines.join("\n"))
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(color).add_modifier(Modifier::BOLD))
+                .block(Block::default().borders(Borders::ALL).title(title));
+            f.render_widget(running, chunks[0]);
+            if *confirm_abort {
+                render_dialog(f, app.text.get("amrap.abort_title"), app.text.get("amrap.abort"));
+            }
+        }
+    }
+
+    if let Some(msg) = &app.message {
+        let message = Paragraph::new(msg.as_str())
+            .style(Style::default().fg(Color::Red))
+            .block(Block::default().borders(Borders::ALL).title(app.text.get("common.status")));
+        f.render_widget(message, chunks[2]);
+    }
+}
@@ -0,0 +1,199 @@
+//! Anonymized lists of sets, for sharing raw data publicly: timestamps are
+//! cut to the day or every date is moved by the same random number of days,
+//! and exercises can be renamed to "exercise_1", "exercise_2" and so on.
+//! Counts, units, weights and RPE stay, as does the order of the sets.
+//! Notes and entry times are never exported; UUIDs are left out, as
+//! they'd tie the sets to the ones in the database.
+
+use chrono::{Duration, NaiveDate};
+use clap::ValueEnum;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+
+use crate::dates;
+use crate::sets_export::SetRow;
+
+/// Shifts are at least this many days, so no date stays close to the real one.
+const MIN_SHIFT_DAYS: i64 = 365;
+const MAX_SHIFT_DAYS: i64 = 3 * 365;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum DateMode {
+    /// Keep only the day of each set.
+    Truncate,
+    /// Move every date by the same random number of days, keeping times.
+    Shift,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Anonymize {
+    pub dates: DateMode,
+    pub rename_exercises: bool,
+    /// Days added to every date when shifting, picked once per export so
+    /// the gaps between sets stay as they were.
+    pub shift_days: i64,
+}
+
+impl Anonymize {
+    /// Settings for one export, with a fresh random shift.
+    pub fn new(dates: DateMode, rename_exercises: bool) -> Self {
+        let random = std::collections::hash_map::RandomState::new().build_hasher().finish();
+        let days = MIN_SHIFT_DAYS + (random % (MAX_SHIFT_DAYS - MIN_SHIFT_DAYS + 1) as u64) as i64;
+        // Either way round, so the shift can't be told from the direction
+        let shift_days = if random >> 63 == 0 { days } else { -days };
+        Self { dates, rename_exercises, shift_days }
+    }
+
+    /// A day of the export's range as it appears in the file.
+    pub fn date(&self, date: NaiveDate) -> NaiveDate {
+        match self.dates {
+            DateMode::Truncate => date,
+            DateMode::Shift => date + Duration::days(self.shift_days),
+        }
+    }
+
+    fn timestamp(&self, stored: &str) -> String {
+        let Some(at) = dates::parse_timestamp(stored) else {
+            // Nothing to keep from a timestamp that can't be read
+            return String::new();
+        };
+        match self.dates {
+            DateMode::Truncate => at.date().to_string(),
+            DateMode::Shift => (at + Duration::days(self.shift_days)).format(dates::TIMESTAMP_FORMAT).to_string(),
+        }
+    }
+
+    /// Generic labels for exercise names, numbered in the order of
+    /// `exercises` (the export's filter) and then of first appearance.
+    fn labels(&self, exercises: &[String], sets: &[SetRow]) -> HashMap<String, String> {
+        let mut labels = HashMap::new();
+        for name in exercises.iter().chain(sets.iter().map(|set| &set.exercise)) {
+            if !labels.contains_key(name) {
+                labels.insert(name.clone(), format!("exercise_{}", labels.len() + 1));
+            }
+        }
+        labels
+    }
+
+    /// Rewrites `sets` in place and returns the filter's exercises as
+    /// they should be named in the file.
+    pub fn apply(&self, sets: &mut [SetRow], exercises: &[String]) -> Vec<String> {
+        let labels = self.rename_exercises.then(|| self.labels(exercises, sets));
+        for set in sets.iter_mut() {
+            set.timestamp = self.timestamp(&set.timestamp);
+            set.uuid = None;
+            if let Some(label) = labels.as_ref().and_then(|labels| labels.get(&set.exercise)) {
+                set.exercise = label.clone();
+            }
+        }
+        match &labels {
+            Some(labels) => exercises.iter().map(|name| labels[name].clone()).collect(),
+            None => exercises.to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::ExportOptions;
+    use crate::Database;
+
+    const SECRET: &str = "Knee rehab (Dr. Smith)";
+
+    fn logged() -> Database {
+        let db = Database::new(":memory:").unwrap();
+        db.add_exercise(SECRET).unwrap();
+        for (timestamp, exercise, count, weight) in [
+            ("2024-05-01 06:13:27", SECRET, 12, Some(7.5)),
+            ("2024-05-01 06:20:41", "squats", 20, None),
+            ("2024-05-03 21:47:09", SECRET, 15, Some(10.0)),
+            ("2024-05-04 07:05:00", "push-ups", 30, None),
+        ] {
+            db.conn
+                .execute(
+                    "INSERT INTO workouts (exercise_type, count, timestamp, weight) VALUES (?1, ?2, ?3, ?4)",
+                    rusqlite::params![exercise, count, timestamp, weight],
+                )
+                .unwrap();
+        }
+        db
+    }
+
+    fn options(anonymize: Anonymize, exercises: &[&str]) -> ExportOptions {
+        ExportOptions {
+            from: NaiveDate::from_ymd_opt(2024, 5, 1),
+            to: NaiveDate::from_ymd_opt(2024, 5, 4),
+            exercises: exercises.iter().map(|name| name.to_string()).collect(),
+            anonymize: Some(anonymize),
+            ..ExportOptions::default()
+        }
+    }
+
+    fn db_uuids(db: &Database) -> Vec<String> {
+        let mut stmt = db.conn.prepare("SELECT uuid FROM workouts").unwrap();
+        stmt.query_map([], |row| row.get(0)).unwrap().map(Result::unwrap).collect()
+    }
+
+    /// The CSV, JSON and Markdown exports, one after the other.
+    fn export_all(db: &Database, options: &ExportOptions) -> String {
+        let mut out = Vec::new();
+        db.write_sets_csv(&mut out, options).unwrap();
+        db.write_sets_json(&mut out, options).unwrap();
+        db.write_sets_markdown(&mut out, options).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn truncating_leaves_no_times_or_names() {
+        let anonymize = Anonymize { dates: DateMode::Truncate, rename_exercises: true, shift_days: 0 };
+        let db = logged();
+        let text = export_all(&db, &options(anonymize, &[SECRET, "squats"]));
+        for secret in ["Knee", "Smith", "squats", "06:13", "13:27", "06:20", "21:47", "07:05"] {
+            assert!(!text.contains(secret), "{secret:?} leaked:\n{text}");
+        }
+        assert!(text.starts_with("# exercises: exercise_1, exercise_2; from 2024-05-01 to 2024-05-04\n"));
+        assert!(text.contains("\n2024-05-01,exercise_1,12,reps,,7.5,\n2024-05-01,exercise_2,20,reps,,,\n2024-05-03,exercise_1,15,reps,,10,\n"));
+        assert!(text.contains("\"timestamp\": \"2024-05-03\""));
+        assert!(db_uuids(&db).iter().all(|uuid| !text.contains(uuid.as_str())), "UUIDs leaked:\n{text}");
+        assert!(!text.contains("\"uuid\""));
+    }
+
+    #[test]
+    fn shifting_keeps_gaps_and_times_but_not_dates() {
+        let anonymize = Anonymize { dates: DateMode::Shift, rename_exercises: false, shift_days: -400 };
+        let db = logged();
+        let options = options(anonymize, &[]);
+        let text = export_all(&db, &options);
+        assert!(!text.contains("2024-05"), "real dates leaked:\n{text}");
+        assert!(text.contains("# Workouts 2023-03-28 – 2023-03-31"));
+
+        let mut sets = db.export_sets(&options).unwrap();
+        let real = sets.clone();
+        options.anonymize.as_ref().unwrap().apply(&mut sets, &[]);
+        let at = |set: &SetRow| dates::parse_timestamp(&set.timestamp).unwrap();
+        for (shifted, real) in sets.iter().zip(&real) {
+            assert_eq!(at(real) - at(shifted), Duration::days(400));
+            assert_eq!((shifted.count, shifted.weight_kg, &shifted.exercise), (real.count, real.weight_kg, &real.exercise));
+        }
+    }
+
+    #[test]
+    fn one_export_uses_one_mapping() {
+        let db = logged();
+        let options = options(Anonymize::new(DateMode::Shift, true), &[]);
+        assert_eq!(export_all(&db, &options), export_all(&db, &options));
+        let mut sets = db.export_sets(&options).unwrap();
+        options.anonymize.as_ref().unwrap().apply(&mut sets, &[]);
+        let names: Vec<&str> = sets.iter().map(|set| set.exercise.as_str()).collect();
+        assert_eq!(names, ["exercise_1", "exercise_2", "exercise_1", "exercise_3"]);
+    }
+
+    #[test]
+    fn random_shifts_are_large() {
+        for _ in 0..50 {
+            let days = Anonymize::new(DateMode::Shift, false).shift_days.abs();
+            assert!((MIN_SHIFT_DAYS..=MAX_SHIFT_DAYS).contains(&days));
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use anyhow::Result;
+use chrono::{Duration, FixedOffset, Local, NaiveDateTime, Offset, TimeZone, Utc};
+use std::{fmt::Write as _, io::Write};
+
+use crate::config::AppleHealthConfig;
+use crate::csv_import::csv_field;
+use crate::export::ExportOptions;
+use crate::{dates, units, Database};
+
+/// Time a set without a duration of its own is assumed to take.
+const SET_SECONDS: i64 = 60;
+const STRENGTH: &str = "TraditionalStrengthTraining";
+const CORE: &str = "CoreTraining";
+
+pub const CSV_HEADER: &str = "Start Date,End Date,Workout Type,Duration (min),Distance (km),Notes";
+
+/// A logged set with what the mapping needs to know about its exercise.
+#[derive(Debug, Clone)]
+pub struct HealthEntry {
+    pub at: NaiveDateTime,
+    pub exercise: String,
+    pub unit: String,
+    pub category: Option<String>,
+    pub count: i64,
+}
+
+impl HealthEntry {
+    fn end(&self) -> NaiveDateTime {
+        let seconds = if self.unit == units::SECONDS { self.count.max(1) } else { SET_SECONDS };
+        self.at + Duration::seconds(seconds)
+    }
+}
+
+/// One row of the import file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HealthWorkout {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub activity: String,
+    pub meters: i64,
+    pub notes: String,
+}
+
+/// HealthKit activity for an exercise: the configured one, else a default
+/// for rep exercises. Timed and distance exercises have no safe default.
+fn activity(entry: &HealthEntry, config: &AppleHealthConfig) -> Option<String> {
+    if let Some(activity) = config.activities.get(&entry.exercise) {
+        return Some(activity.clone());
+    }
+    if entry.unit == units::SECONDS || entry.unit == units::DISTANCE {
+        return None;
+    }
+    Some(match entry.category.as_deref() {
+        Some("core") => CORE,
+        _ => STRENGTH,
+    }
+    .to_string())
+}
+
+/// Maps sets (oldest first) to workouts, one per set or one per session
+/// as configured. Returns the workouts and the number of sets skipped
+/// because their exercise has no activity.
+pub fn build_workouts(entries: &[HealthEntry], config: &AppleHealthConfig) -> (Vec<HealthWorkout>, usize) {
+    let mut mapped: Vec<(&HealthEntry, String)> = Vec::new();
+    let mut skipped = 0;
+    for entry in entries {
+        match activity(entry, config) {
+            Some(activity) => mapped.push((entry, activity)),
+            None => skipped += 1,
+        }
+    }
+
+    let gap = Duration::minutes(config.session_gap_minutes as i64);
+    let mut groups: Vec<Vec<(&HealthEntry, String)>> = Vec::new();
+    for (entry, activity) in mapped {
+        match groups.last_mut() {
+            Some(group)
+                if config.sessions && group.last().is_some_and(|(last, _)| entry.at - last.end() <= gap) =>
+            {
+                group.push((entry, activity))
+            }
+            _ => groups.push(vec![(entry, activity)]),
+        }
+    }
+
+    let workouts = groups
+        .into_iter()
+        .map(|group| {
+            // The activity most of the session's sets map to, earliest on a tie
+            let mut counts: Vec<(&str, usize)> = Vec::new();
+            let mut totals: Vec<(&str, &str, i64)> = Vec::new();
+            for (entry, activity) in &group {
+                match counts.iter_mut().find(|(a, _)| a == activity) {
+                    Some((_, n)) => *n += 1,
+                    None => counts.push((activity, 1)),
+                }
+                match totals.iter_mut().find(|(exercise, ..)| *exercise == entry.exercise) {
+                    Some((_, _, total)) => *total += entry.count,
+                    None => totals.push((&entry.exercise, &entry.unit, entry.count)),
+                }
+            }
+            let best = counts.iter().map(|(_, n)| *n).max().unwrap_or(0);
+            let activity = counts.iter().find(|(_, n)| *n == best).map_or(STRENGTH, |(a, _)| a);
+            let notes: Vec<String> = totals
+                .iter()
+                .map(|(exercise, unit, total)| {
+                    format!("{exercise} {}", units::format_amount(*total, unit, units::DistanceUnit::Km))
+                })
+                .collect();
+            HealthWorkout {
+                start: group[0].0.at,
+                end: group.iter().map(|(entry, _)| entry.end()).max().unwrap_or(group[0].0.at),
+                activity: activity.to_string(),
+                meters: group
+                    .iter()
+                    .filter(|(entry, _)| entry.unit == units::DISTANCE)
+                    .map(|(entry, _)| entry.count)
+                    .sum(),
+                notes: notes.join(", "),
+            }
+        })
+        .collect();
+    (workouts, skipped)
+}
+
+/// The import CSV. Times are written as "2024-05-01 08:00:00 +0200", the
+/// way Health's own export writes them, with `offset` giving the UTC
+/// offset in effect at each local time.
+pub fn render_csv(workouts: &[HealthWorkout], offset: impl Fn(NaiveDateTime) -> FixedOffset) -> String {
+    let time = |at: NaiveDateTime| format!("{} {}", at.format(dates::TIMESTAMP_FORMAT), offset(at).to_string().replace(':', ""));
+    let mut out = String::new();
+    let _ = writeln!(out, "{CSV_HEADER}");
+    for workout in workouts {
+        let minutes = (workout.end - workout.start).num_seconds() as f64 / 60.0;
+        let distance = match workout.meters {
+            0 => String::new(),
+            meters => format!("{:.3}", meters as f64 / 1000.0),
+        };
+        let _ = writeln!(
+            out,
+            "{},{},{},{minutes:.1},{distance},{}",
+            time(workout.start),
+            time(workout.end),
+            csv_field(&workout.activity),
+            csv_field(&workout.notes)
+        );
+    }
+    out
+}
+
+impl Database {
+    /// Sets between the option's dates with their exercise's unit and
+    /// category, oldest first.
+    pub fn health_entries(&self, options: &ExportOptions) -> Result<Vec<HealthEntry>> {
+        let (from, to) = options.range();
+        let mut stmt = self.conn.prepare(
+            "SELECT w.timestamp, w.exercise_type, COALESCE(e.unit, 'reps'), e.category, w.count
+             FROM workouts w
+             LEFT JOIN exercises e ON e.name = w.exercise_type
+             WHERE substr(w.timestamp, 1, 10) BETWEEN ?1 AND ?2 AND w.deleted_at IS NULL
+             ORDER BY w.timestamp ASC, w.id ASC",
+        )?;
+        let rows = stmt
+            .query_map([from.to_string(), to.to_string()], |row| {
+                Ok((row.get::<_, String>(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(timestamp, exercise, unit, category, count)| {
+                Some(HealthEntry {
+                    at: dates::parse_timestamp(&timestamp)?,
+                    exercise,
+                    unit,
+                    category,
+                    count,
+                })
+            })
+            .collect())
+    }
+
+    /// Writes the Apple Health import CSV and returns how many sets were
+    /// skipped for lack of an activity mapping.
+    pub fn write_apple_health(&self, out: &mut impl Write, options: &ExportOptions) -> Result<usize> {
+        let entries = self.health_entries(options)?;
+        let (workouts, skipped) = build_workouts(&entries, &options.apple_health);
+        let csv = render_csv(&workouts, |at| {
+            Local
+                .offset_from_local_datetime(&at)
+                .earliest()
+                .map_or_else(|| Utc.fix(), |offset| offset.fix())
+        });
+        out.write_all(csv.as_bytes())?;
+        Ok(skipped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(at: &str, exercise: &str, unit: &str, category: Option<&str>, count: i64) -> HealthEntry {
+        HealthEntry {
+            at: dates::parse_timestamp(at).unwrap(),
+            exercise: exercise.to_string(),
+            unit: unit.to_string(),
+            category: category.map(str::to_string),
+            count,
+        }
+    }
+
+    fn fixture() -> Vec<HealthEntry> {
+        vec![
+            entry("2024-05-01 08:00:00", "squats", "reps", Some("legs"), 20),
+            entry("2024-05-01 08:05:00", "push-ups", "reps", Some("push"), 15),
+            entry("2024-05-01 08:10:00", "plank", units::SECONDS, Some("core"), 90),
+            entry("2024-05-01 08:20:00", "squats", "reps", Some("legs"), 20),
+            entry("2024-05-01 18:00:00", "running", units::DISTANCE, None, 5200),
+            entry("2024-05-01 19:00:00", "sit-ups", "reps", Some("core"), 30),
+        ]
+    }
+
+    fn plus_two(_: NaiveDateTime) -> FixedOffset {
+        FixedOffset::east_opt(2 * 3600).unwrap()
+    }
+
+    #[test]
+    fn sets_group_into_sessions_and_unmapped_units_are_skipped() {
+        let config = AppleHealthConfig::default();
+        let (workouts, skipped) = build_workouts(&fixture(), &config);
+        // plank (seconds) and running (distance) have no mapping
+        assert_eq!(skipped, 2);
+        let csv = render_csv(&workouts, plus_two);
+        assert_eq!(
+            csv,
+            format!(
+                "{CSV_HEADER}\n\
+                 2024-05-01 08:00:00 +0200,2024-05-01 08:21:00 +0200,TraditionalStrengthTraining,21.0,,\"squats 40, push-ups 15\"\n\
+                 2024-05-01 19:00:00 +0200,2024-05-01 19:01:00 +0200,CoreTraining,1.0,,sit-ups 30\n"
+            )
+        );
+    }
+
+    #[test]
+    fn configured_activities_map_custom_exercises() {
+        let mut config = AppleHealthConfig::default();
+        config.activities.insert("running".to_string(), "Running".to_string());
+        config.activities.insert("plank".to_string(), "CoreTraining".to_string());
+        config.sessions = false;
+        let (workouts, skipped) = build_workouts(&fixture(), &config);
+        assert_eq!(skipped, 0);
+        assert_eq!(workouts.len(), 6);
+        let csv = render_csv(&workouts, plus_two);
+        assert!(csv.contains("2024-05-01 08:10:00 +0200,2024-05-01 08:11:30 +0200,CoreTraining,1.5,,plank 90s\n"));
+        assert!(csv.contains("2024-05-01 18:00:00 +0200,2024-05-01 18:01:00 +0200,Running,1.0,5.200,running 5.20 km\n"));
+    }
+
+    #[test]
+    fn session_gap_is_configurable() {
+        let config = AppleHealthConfig {
+            session_gap_minutes: 5,
+            ..AppleHealthConfig::default()
+        };
+        let (workouts, _) = build_workouts(&fixture(), &config);
+        let starts: Vec<String> = workouts.iter().map(|w| w.start.format("%H:%M").to_string()).collect();
+        assert_eq!(starts, ["08:00", "08:20", "19:00"]);
+    }
+
+    #[test]
+    fn fields_are_quoted_when_needed() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
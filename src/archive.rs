@@ -0,0 +1,184 @@
+//! Archiving old sets: everything logged before a cutoff day moves into a
+//! second database file next to the data file, with the same schema, so
+//! the live one stays small. The archive is a normal tracker database, so
+//! merging it brings the sets back. The stats screen can attach it
+//! read-only to count the archived sets in again.
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use rusqlite::{params_from_iter, OpenFlags};
+use std::path::{Path, PathBuf};
+
+use crate::backup::check_database_file;
+use crate::config::BackupConfig;
+use crate::encryption;
+use crate::Database;
+
+/// Schema name the archive is attached under, both for moving sets into it
+/// and for reading it on the stats screen.
+const SCHEMA: &str = "archive";
+
+/// Outcome of moving sets into the archive.
+#[derive(Debug, Clone)]
+pub struct ArchiveReport {
+    pub moved: usize,
+    /// Backup taken before anything was moved.
+    pub backup: PathBuf,
+    pub path: PathBuf,
+}
+
+/// An SQLite URI for `path`, escaping what a URI would read differently.
+fn file_uri(path: &Path, query: &str) -> Result<String> {
+    let path = path.to_str().context("archive path is not valid UTF-8")?;
+    let escaped = path.replace('%', "%25").replace('?', "%3f").replace('#', "%23");
+    Ok(format!("file:{escaped}?{query}"))
+}
+
+impl Database {
+    /// The archive file: `<data file name>.archive.db` next to it.
+    pub fn archive_path(&self) -> PathBuf {
+        let db_path = self.file_path();
+        let stem = db_path.file_stem().and_then(|s| s.to_str()).unwrap_or("fitness_tracker");
+        db_path.with_file_name(format!("{stem}.archive.db"))
+    }
+
+    pub fn has_archive(&self) -> bool {
+        self.archive_path().is_file()
+    }
+
+    fn attach_statement(&self, path: &Path) -> String {
+        // An attached file shares the main database's key unless told otherwise
+        if self.is_encrypted() && !encryption::is_encrypted(path) {
+            format!("ATTACH DATABASE ?1 AS {SCHEMA} KEY ''")
+        } else {
+            format!("ATTACH DATABASE ?1 AS {SCHEMA}")
+        }
+    }
+
+    /// Sets logged before `cutoff`, trashed ones included.
+    pub fn count_before(&self, cutoff: NaiveDate) -> Result<i64> {
+        Ok(self.conn.query_row(
+            "SELECT COUNT(*) FROM workouts WHERE substr(timestamp, 1, 10) < ?1",
+            [cutoff.to_string()],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Moves every set logged before `cutoff` into the archive, creating it
+    /// on first use, along with the definitions of all exercises. A backup
+    /// is taken first. Copying and deleting happen in one transaction, so an
+    /// interrupted archive leaves the live database as it was; sets already
+    /// in the archive (matched by UUID) are only removed here.
+    pub fn archive_before(&mut self, cutoff: NaiveDate, config: &BackupConfig) -> Result<ArchiveReport> {
+        let backup = self.backup(config)?;
+        let path = self.archive_path();
+        if path.exists() {
+            check_database_file(&path, self.passphrase.as_ref())?;
+        }
+        // Creates the file, or brings an older archive up to this schema
+        Database::with_connection(encryption::open_connection(&path, OpenFlags::default(), self.passphrase.as_ref())?)?;
+
+        self.detach_archive()?;
+        let path_str = path.to_str().context("archive path is not valid UTF-8")?;
+        self.conn.execute(&self.attach_statement(&path), [path_str])?;
+        let cutoff = cutoff.to_string();
+        let moved = (|| {
+            let tx = self.conn.transaction()?;
+            // Every column, so one added by a later migration isn't left behind;
+            // both sides are on the same schema by now. Replacing keeps the
+            // archive's copy of an exercise it already has, like the built-ins
+            // every new database starts with, up to date
+            let columns: String = tx.query_row(
+                r#"SELECT group_concat('"' || replace(name, '"', '""') || '"', ', ') FROM pragma_table_info('exercises', 'main')"#,
+                [],
+                |row| row.get(0),
+            )?;
+            tx.execute(
+                &format!("INSERT OR REPLACE INTO {SCHEMA}.exercises ({columns}) SELECT {columns} FROM main.exercises"),
+                [],
+            )?;
+            tx.execute(
+                &format!(
+                    "INSERT OR IGNORE INTO {SCHEMA}.workouts (exercise_type, count, timestamp, rpe, uuid, deleted_at, recorded_at, weight, notes)
+                     SELECT exercise_type, count, timestamp, rpe, uuid, deleted_at, recorded_at, weight, notes
+                     FROM main.workouts
+                     WHERE substr(timestamp, 1, 10) < ?1
+                     ORDER BY timestamp, id"
+                ),
+                [&cutoff],
+            )?;
+            let uuids = {
+                let mut stmt = tx.prepare("SELECT uuid FROM main.workouts WHERE substr(timestamp, 1, 10) < ?1 AND uuid IS NOT NULL")?;
+                stmt.query_map([&cutoff], |row| row.get::<_, String>(0))?.collect::<Result<Vec<_>, _>>()?
+            };
+            let moved = tx.execute("DELETE FROM main.workouts WHERE substr(timestamp, 1, 10) < ?1", [&cutoff])?;
+            tx.commit()?;
+            Ok::<_, anyhow::Error>((moved, uuids))
+        })();
+        self.conn.execute(&format!("DETACH DATABASE {SCHEMA}"), [])?;
+        let (moved, uuids) = moved?;
+        if moved > 0 {
+            let summary = format!("{moved} sets before {cutoff} to {}", path.display());
+            self.log_change(None, "archive", None, Some(&summary))?;
+            // The journal would otherwise offer them back as missing
+            self.journal_purged(&uuids);
+        }
+        Ok(ArchiveReport { moved, backup, path })
+    }
+
+    /// Whether the archive is attached for the stats.
+    pub fn archive_attached(&self) -> bool {
+        self.conn
+            .query_row("SELECT EXISTS(SELECT 1 FROM pragma_database_list WHERE name = ?1)", [SCHEMA], |row| row.get(0))
+            .unwrap_or(false)
+    }
+
+    /// Attaches the archive read-only, so the stats count its sets too.
+    pub fn attach_archive(&self) -> Result<()> {
+        let path = self.archive_path();
+        check_database_file(&path, self.passphrase.as_ref())?;
+        self.conn
+            .execute(&self.attach_statement(&path), [file_uri(&path, "mode=ro")?])
+            .with_context(|| format!("failed to open {}", path.display()))?;
+        Ok(())
+    }
+
+    pub fn detach_archive(&self) -> Result<()> {
+        if self.archive_attached() {
+            self.conn.execute(&format!("DETACH DATABASE {SCHEMA}"), [])?;
+        }
+        Ok(())
+    }
+
+    /// What the stats read sets from in place of `workouts`: the live sets,
+    /// plus while the archive is attached the archived ones that weren't
+    /// merged back since. With a tag to filter on, only the live sets with
+    /// that tag, as archived sets leave their tags behind; the query binds
+    /// the tag as `?1`.
+    pub(crate) fn stats_workouts(&self, tag: Option<&str>) -> String {
+        if tag.is_some() {
+            "(SELECT exercise_type, count, timestamp, rpe, weight, deleted_at FROM main.workouts
+              WHERE id IN (SELECT wt.workout_id FROM workout_tags wt JOIN tags t ON t.id = wt.tag_id WHERE t.name = ?1))"
+                .to_string()
+        } else if self.archive_attached() {
+            "(SELECT exercise_type, count, timestamp, rpe, weight, deleted_at FROM main.workouts
+              UNION ALL
+              SELECT exercise_type, count, timestamp, rpe, weight, deleted_at FROM archive.workouts
+              WHERE uuid NOT IN (SELECT uuid FROM main.workouts WHERE uuid IS NOT NULL))"
+                .to_string()
+        } else {
+            "workouts".to_string()
+        }
+    }
+
+    /// Day of the first set the stats read, with sets tagged `tag` only
+    /// when given.
+    pub fn first_stats_day(&self, tag: Option<&str>) -> Result<Option<NaiveDate>> {
+        let first: Option<String> = self.conn.query_row(
+            &format!("SELECT MIN(day) FROM {}", self.stats_daily_totals(tag)),
+            params_from_iter(tag),
+            |row| row.get(0),
+        )?;
+        Ok(first.and_then(|day| NaiveDate::parse_from_str(&day, "%Y-%m-%d").ok()))
+    }
+}
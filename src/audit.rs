@@ -0,0 +1,153 @@
+use anyhow::Result;
+use chrono::Local;
+use ratatui::{
+    style::{Color, Style},
+    widgets::{Block, Borders, Clear, List, ListItem},
+    Frame,
+};
+use rusqlite::{params, OptionalExtension};
+
+use crate::{centered_rect, dates, App, Database};
+
+/// Oldest entries beyond this are dropped as new ones are written.
+pub(crate) const MAX_ENTRIES: i64 = 10_000;
+
+/// One recorded change. Bulk operations leave a single entry without a row
+/// UUID that describes the whole operation.
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub at: String,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+/// How a bulk entry names the sets it covers: how many, and the span of
+/// their timestamps.
+pub fn describe_sets<'a>(timestamps: impl IntoIterator<Item = &'a str>) -> String {
+    let timestamps: Vec<&str> = timestamps.into_iter().collect();
+    let first = timestamps.iter().min().copied().unwrap_or_default();
+    let last = timestamps.iter().max().copied().unwrap_or_default();
+    format!("{} sets from {first} to {last}", timestamps.len())
+}
+
+impl Database {
+    /// Records a change to the set with `uuid`, or with `None` a summary of a
+    /// bulk operation. Runs inside the caller's transaction, if any.
+    pub fn log_change(&self, uuid: Option<&str>, field: &str, old: Option<&str>, new: Option<&str>) -> Result<()> {
+        let at = Local::now().format(dates::TIMESTAMP_FORMAT).to_string();
+        self.conn.execute(
+            "INSERT INTO audit_log (row_uuid, field, old_value, new_value, at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![uuid, field, old, new, at],
+        )?;
+        self.conn.execute(
+            "DELETE FROM audit_log WHERE id <= (SELECT MAX(id) FROM audit_log) - ?1",
+            [MAX_ENTRIES],
+        )?;
+        Ok(())
+    }
+
+    pub fn workout_uuid(&self, id: i64) -> Result<Option<String>> {
+        let uuid = self
+            .conn
+            .prepare_cached("SELECT uuid FROM workouts WHERE id = ?1")?
+            .query_row([id], |row| row.get(0))
+            .optional()?;
+        Ok(uuid.flatten())
+    }
+
+    /// When a set was performed and when it was typed in. Rows inserted
+    /// without going through `add_workout` may lack the latter.
+    pub fn workout_times(&self, id: i64) -> Result<Option<(String, Option<String>)>> {
+        let times = self
+            .conn
+            .query_row("SELECT timestamp, recorded_at FROM workouts WHERE id = ?1", [id], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .optional()?;
+        Ok(times)
+    }
+
+    /// Changes recorded for one set, oldest first.
+    pub fn get_audit_log(&self, id: i64) -> Result<Vec<AuditEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT at, field, old_value, new_value FROM audit_log
+             WHERE row_uuid = (SELECT uuid FROM workouts WHERE id = ?1)
+             ORDER BY id ASC",
+        )?;
+        let entries = stmt
+            .query_map([id], |row| {
+                Ok(AuditEntry {
+                    at: row.get(0)?,
+                    field: row.get(1)?,
+                    old_value: row.get(2)?,
+                    new_value: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+
+    pub fn count_audit_log(&self) -> Result<i64> {
+        Ok(self.conn.query_row("SELECT COUNT(*) FROM audit_log", [], |row| row.get(0))?)
+    }
+
+    pub fn clear_audit_log(&self) -> Result<usize> {
+        Ok(self.conn.execute("DELETE FROM audit_log", [])?)
+    }
+}
+
+impl App {
+    /// "count: 20 → 25", with the field name translated where there's a
+    /// catalog entry for it.
+    fn audit_line(&self, entry: &AuditEntry) -> String {
+        let at = self.audit_time(&entry.at);
+        let field = match entry.field.as_str() {
+            "created" => self.text.get("audit.created"),
+            "count" => self.text.get("audit.count"),
+            "deleted_at" => self.text.get("audit.deleted_at"),
+            "exercise_type" => self.text.get("audit.exercise"),
+            other => other,
+        };
+        let none = self.text.get("audit.none");
+        format!(
+            "{at}  {field}: {} → {}",
+            entry.old_value.as_deref().unwrap_or(none),
+            entry.new_value.as_deref().unwrap_or(none)
+        )
+    }
+
+    fn audit_time(&self, timestamp: &str) -> String {
+        dates::parse_timestamp(timestamp)
+            .map(|at| {
+                let time = dates::format_time(at.time(), self.config.ui.time_format, false);
+                format!("{} {}", self.display_date(at.date()), time)
+            })
+            .unwrap_or_else(|| timestamp.to_string())
+    }
+}
+
+/// Popup over the history day view with the highlighted set's changes.
+pub fn render_audit_popup(f: &mut Frame, app: &App, id: i64) {
+    let entries = app.db.get_audit_log(id).unwrap_or_default();
+    let mut items = Vec::new();
+    if let Ok(Some((performed, recorded))) = app.db.workout_times(id) {
+        let recorded = recorded.map_or_else(|| app.text.get("audit.none").to_string(), |at| app.audit_time(&at));
+        let times = app.text.format(
+            "audit.times",
+            &[("performed", &app.audit_time(&performed)), ("recorded", &recorded)],
+        );
+        items.push(ListItem::new(times).style(Style::default().fg(Color::Yellow)));
+    }
+    if entries.is_empty() {
+        items.push(ListItem::new(app.text.get("audit.empty")));
+    } else {
+        items.extend(entries.iter().map(|entry| ListItem::new(app.audit_line(entry))));
+    }
+    let area = centered_rect(70, items.len() as u16 + 2, f.size());
+    let list = List::new(items)
+        .style(Style::default().fg(Color::Cyan))
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("audit.title")));
+    f.render_widget(Clear, area);
+    f.render_widget(list, area);
+}
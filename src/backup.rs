@@ -0,0 +1,176 @@
+use anyhow::{ensure, Context, Result};
+use chrono::{Local, NaiveDateTime};
+use rusqlite::{backup::Backup, OpenFlags};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::config::BackupConfig;
+use crate::encryption::{self, Passphrase};
+use crate::{migrations, Database};
+
+/// Timestamp in a backup's file name. The milliseconds keep two backups
+/// taken in the same second, like a restore's snapshot and the backup
+/// before it, from getting the same name.
+const STAMP: &str = "%Y%m%d-%H%M%S-%3f";
+/// The timestamp of backups named before milliseconds were added.
+const OLD_STAMP: &str = "%Y%m%d-%H%M%S";
+
+/// When a backup was taken, read from the timestamp in its file name.
+pub(crate) fn backup_time(path: &Path) -> Option<NaiveDateTime> {
+    let stem = path.file_stem()?.to_str()?;
+    let stamp = |len: usize| stem.get(stem.len().checked_sub(len)?..);
+    stamp(19)
+        .and_then(|s| NaiveDateTime::parse_from_str(s, STAMP).ok())
+        .or_else(|| NaiveDateTime::parse_from_str(stamp(15)?, OLD_STAMP).ok())
+}
+
+impl Database {
+    /// Rows changed through this connection since it was opened; compared
+    /// at exit to skip backing up an untouched database.
+    pub fn total_changes(&self) -> Result<i64> {
+        Ok(self.conn.query_row("SELECT total_changes()", [], |row| row.get(0))?)
+    }
+
+    /// Folder backups go to; relative paths are resolved next to the
+    /// database file.
+    fn backup_dir(&self, config: &BackupConfig) -> PathBuf {
+        let db_path = self.file_path();
+        db_path.parent().unwrap_or(Path::new("")).join(&config.dir)
+    }
+
+    /// File name prefix shared by every backup of this database.
+    fn backup_prefix(&self) -> String {
+        let db_path = self.file_path();
+        let stem = db_path.file_stem().and_then(|s| s.to_str()).unwrap_or("fitness_tracker");
+        format!("{stem}-")
+    }
+
+    /// Writes a timestamped copy of the database and prunes the oldest
+    /// copies beyond `config.keep`. `VACUUM INTO` gives a consistent
+    /// snapshot even with a WAL journal, which a plain file copy doesn't,
+    /// and encrypts the copy with the database's own key.
+    pub fn backup(&self, config: &BackupConfig) -> Result<PathBuf> {
+        let path = self.write_backup(config)?;
+        self.prune_backups(config)?;
+        Ok(path)
+    }
+
+    fn write_backup(&self, config: &BackupConfig) -> Result<PathBuf> {
+        let dir = self.backup_dir(config);
+        fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        let name = format!("{}{}.db", self.backup_prefix(), Local::now().format(STAMP));
+        let path = dir.join(name);
+        let target = path.to_str().context("backup path is not valid UTF-8")?;
+        self.conn
+            .execute("VACUUM INTO ?1", [target])
+            .with_context(|| format!("failed to back up to {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Existing backups, oldest first by the timestamp in their names.
+    /// Files that merely share the prefix aren't backups and are left out.
+    pub fn list_backups(&self, config: &BackupConfig) -> Result<Vec<PathBuf>> {
+        let dir = self.backup_dir(config);
+        let prefix = self.backup_prefix();
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e).with_context(|| format!("failed to read {}", dir.display())),
+        };
+        let mut backups = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+            if name.starts_with(&prefix) && name.ends_with(".db") && backup_time(&path).is_some() {
+                backups.push(path);
+            }
+        }
+        backups.sort_by_key(|path| backup_time(path));
+        Ok(backups)
+    }
+
+    /// Replaces the live database with `backup`. The current database is
+    /// snapshotted first so the restore itself can be undone; the snapshot's
+    /// path is returned. Older backups are migrated up to the current schema.
+    pub fn restore(&mut self, backup: &Path, config: &BackupConfig) -> Result<PathBuf> {
+        check_database_file(backup, self.passphrase.as_ref())?;
+        let snapshot = self.write_backup(config)?;
+        let source = encryption::open_connection(backup, OpenFlags::SQLITE_OPEN_READ_ONLY, self.passphrase.as_ref())?;
+        Backup::new(&source, &mut self.conn)
+            .and_then(|copy| copy.run_to_completion(100, Duration::ZERO, None))
+            .with_context(|| format!("failed to restore {}", backup.display()))?;
+        migrations::migrate(&mut self.conn)?;
+        // The backup API copies pages without counting any changes
+        self.mark_unsaved();
+        // Prune only now, so the backup being restored can't be the one removed
+        self.prune_backups(config)?;
+        Ok(snapshot)
+    }
+
+    /// Deletes everything, leaving the database as it is on a first run:
+    /// every table is dropped and created again, and the file is vacuumed so
+    /// nothing stays readable in its free pages. A last backup is taken
+    /// first and its path returned. The journal hears about every set, so
+    /// none is offered back for replay.
+    pub fn wipe(&mut self, config: &BackupConfig) -> Result<PathBuf> {
+        let backup = self.backup(config)?;
+        self.detach_archive()?;
+        let tx = self.conn.transaction()?;
+        let uuids = {
+            let mut stmt = tx.prepare("SELECT uuid FROM workouts WHERE uuid IS NOT NULL")?;
+            stmt.query_map([], |row| row.get::<_, String>(0))?.collect::<Result<Vec<_>, _>>()?
+        };
+        let tables = {
+            let mut stmt = tx.prepare("SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name")?;
+            stmt.query_map([], |row| row.get::<_, String>(0))?.collect::<Result<Vec<_>, _>>()?
+        };
+        for table in &tables {
+            // The notes index takes its own tables with it
+            tx.execute(&format!("DROP TABLE IF EXISTS \"{}\"", table.replace('"', "\"\"")), [])?;
+        }
+        tx.pragma_update(None, "user_version", 0)?;
+        tx.commit()?;
+        Database::create_tables(&mut self.conn)?;
+        self.conn.execute("VACUUM", [])?;
+        // Dropping tables isn't counted as a change either
+        self.mark_unsaved();
+        self.journal_purged(&uuids);
+        Ok(backup)
+    }
+
+    fn prune_backups(&self, config: &BackupConfig) -> Result<()> {
+        let backups = self.list_backups(config)?;
+        let excess = backups.len().saturating_sub(config.keep.max(1));
+        for old in &backups[..excess] {
+            fs::remove_file(old).with_context(|| format!("failed to remove {}", old.display()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Rejects files that aren't intact databases of this app, or that a newer
+/// version wrote with a schema this one doesn't know. Encrypted files are
+/// opened with `passphrase`.
+pub(crate) fn check_database_file(path: &Path, passphrase: Option<&Passphrase>) -> Result<()> {
+    let conn = encryption::open_connection(path, OpenFlags::SQLITE_OPEN_READ_ONLY, passphrase)?;
+    let integrity: String = conn
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .context("not a readable database")?;
+    ensure!(integrity == "ok", "database is corrupt: {integrity}");
+    let has_workouts: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'workouts')",
+        [],
+        |row| row.get(0),
+    )?;
+    ensure!(has_workouts, "not a fitness tracker database");
+    let version = migrations::schema_version(&conn)?;
+    let latest = migrations::latest_version();
+    ensure!(
+        version <= latest,
+        "file is from a newer version (schema {version}, this version knows up to {latest})"
+    );
+    Ok(())
+}
@@ -0,0 +1,226 @@
+//! Deleting every set in a date range at once, optionally of one exercise,
+//! to clean up after a botched import. The sets go to the trash in one
+//! transaction, so the whole batch can be undone from the data screen for
+//! the rest of the session, or restored one by one from the trash later.
+
+use anyhow::Result;
+use chrono::{Local, NaiveDate};
+use crossterm::event::KeyCode;
+use ratatui::Frame;
+use rusqlite::{params, OptionalExtension};
+
+use crate::audit::describe_sets;
+use crate::exercises::{normalize_name, render_dialog};
+use crate::input::TextInput;
+use crate::{dates, App, Database};
+
+/// The range and exercise being typed on the data screen.
+#[derive(Debug)]
+pub struct BulkDeleteForm {
+    pub from: TextInput,
+    pub to: TextInput,
+    /// Empty for every exercise.
+    pub exercise: TextInput,
+    /// Index of the focused field, in the order above.
+    pub field: usize,
+}
+
+impl Default for BulkDeleteForm {
+    fn default() -> Self {
+        let today = Local::now().date_naive().to_string();
+        Self {
+            from: TextInput::with_text(&today),
+            to: TextInput::with_text(&today),
+            exercise: TextInput::default(),
+            field: 0,
+        }
+    }
+}
+
+impl BulkDeleteForm {
+    const FIELDS: usize = 3;
+
+    fn focused(&mut self) -> &mut TextInput {
+        match self.field {
+            0 => &mut self.from,
+            1 => &mut self.to,
+            _ => &mut self.exercise,
+        }
+    }
+}
+
+/// Sets matching a filled-in form, waiting for confirmation.
+#[derive(Debug, Clone)]
+pub struct BulkDelete {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub exercise: Option<String>,
+    pub ids: Vec<i64>,
+}
+
+impl Database {
+    /// Sets outside the trash logged from `from` to `to`, both included,
+    /// of `exercise` if given.
+    pub fn sets_between(&self, from: NaiveDate, to: NaiveDate, exercise: Option<&str>) -> Result<Vec<i64>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id FROM workouts
+             WHERE substr(timestamp, 1, 10) BETWEEN ?1 AND ?2 AND (?3 IS NULL OR exercise_type = ?3)
+               AND deleted_at IS NULL
+             ORDER BY id",
+        )?;
+        let ids = stmt
+            .query_map(params![from.to_string(), to.to_string(), exercise], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ids)
+    }
+
+    /// Moves the sets to the trash in one transaction, with one audit entry
+    /// for all of them. Returns how many moved.
+    pub fn trash_workouts(&self, ids: &[i64]) -> Result<usize> {
+        let now = Local::now().format(dates::TIMESTAMP_FORMAT).to_string();
+        let tx = self.conn.unchecked_transaction()?;
+        let mut moved = Vec::new();
+        {
+            let mut trash =
+                tx.prepare("UPDATE workouts SET deleted_at = ?2 WHERE id = ?1 AND deleted_at IS NULL RETURNING timestamp")?;
+            for &id in ids {
+                if let Some(timestamp) = trash.query_row(params![id, now], |row| row.get::<_, String>(0)).optional()? {
+                    moved.push(timestamp);
+                }
+            }
+        }
+        if !moved.is_empty() {
+            self.log_change(None, "bulk_trash", None, Some(&describe_sets(moved.iter().map(String::as_str))))?;
+        }
+        tx.commit()?;
+        Ok(moved.len())
+    }
+
+    /// Takes the sets back out of the trash in one transaction, with one
+    /// audit entry for all of them; any purged since are gone for good.
+    /// Returns how many came back.
+    pub fn untrash_workouts(&self, ids: &[i64]) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut restored = Vec::new();
+        {
+            let mut restore =
+                tx.prepare("UPDATE workouts SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL RETURNING timestamp")?;
+            for &id in ids {
+                if let Some(timestamp) = restore.query_row([id], |row| row.get::<_, String>(0)).optional()? {
+                    restored.push(timestamp);
+                }
+            }
+        }
+        if !restored.is_empty() {
+            self.log_change(None, "bulk_restore", None, Some(&describe_sets(restored.iter().map(String::as_str))))?;
+        }
+        tx.commit()?;
+        Ok(restored.len())
+    }
+}
+
+impl App {
+    pub(crate) fn handle_bulk_delete_input(&mut self, key: KeyCode) -> Result<bool> {
+        let Some(form) = &mut self.data_screen.bulk_delete else {
+            return Ok(false);
+        };
+        match key {
+            KeyCode::Esc => self.data_screen.bulk_delete = None,
+            KeyCode::Up | KeyCode::BackTab => form.field = (form.field + BulkDeleteForm::FIELDS - 1) % BulkDeleteForm::FIELDS,
+            KeyCode::Down | KeyCode::Tab => form.field = (form.field + 1) % BulkDeleteForm::FIELDS,
+            KeyCode::Enter if form.field + 1 < BulkDeleteForm::FIELDS => form.field += 1,
+            KeyCode::Enter => self.find_bulk_delete()?,
+            _ => {
+                form.focused().handle_key(key);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Checks the form and asks to confirm what it matches; a mistake only
+    /// produces a status message and leaves the form open.
+    fn find_bulk_delete(&mut self) -> Result<()> {
+        let Some(form) = &self.data_screen.bulk_delete else {
+            return Ok(());
+        };
+        let (Some(from), Some(to)) = (dates::parse_date(form.from.as_str().trim()), dates::parse_date(form.to.as_str().trim())) else {
+            self.message = Some(self.text.get("data.bulk_bad_date").to_string());
+            return Ok(());
+        };
+        if from > to {
+            self.message = Some(self.text.get("data.bulk_backwards").to_string());
+            return Ok(());
+        }
+        let typed = form.exercise.as_str().trim();
+        let exercise = if typed.is_empty() {
+            None
+        } else {
+            // Sets of a name that was never added as an exercise still match
+            Some(self.db.resolve_exercise(typed)?.unwrap_or_else(|| normalize_name(typed)))
+        };
+        let ids = self.db.sets_between(from, to, exercise.as_deref())?;
+        if ids.is_empty() {
+            self.message = Some(self.text.get("data.bulk_nothing").to_string());
+            return Ok(());
+        }
+        self.data_screen.bulk_delete = None;
+        self.data_screen.confirm_bulk_delete = Some(BulkDelete { from, to, exercise, ids });
+        Ok(())
+    }
+
+    pub(crate) fn bulk_delete(&mut self, delete: BulkDelete) {
+        self.message = Some(match self.db.trash_workouts(&delete.ids) {
+            Ok(moved) => {
+                self.reset_cached_state();
+                self.data_screen.last_bulk_delete = Some(delete.ids);
+                self.text.format("data.bulk_deleted", &[("count", &moved)])
+            }
+            Err(e) => self.text.format("data.bulk_failed", &[("error", &format!("{e:#}"))]),
+        });
+    }
+
+    pub(crate) fn undo_bulk_delete(&mut self) {
+        let Some(ids) = self.data_screen.last_bulk_delete.take() else {
+            return;
+        };
+        self.message = Some(match self.db.untrash_workouts(&ids) {
+            Ok(restored) => {
+                self.reset_cached_state();
+                self.text.format("data.bulk_undone", &[("count", &restored)])
+            }
+            Err(e) => {
+                self.data_screen.last_bulk_delete = Some(ids);
+                self.text.format("data.bulk_failed", &[("error", &format!("{e:#}"))])
+            }
+        });
+    }
+}
+
+pub fn render_bulk_delete_dialog(f: &mut Frame, app: &App) {
+    if let Some(form) = &app.data_screen.bulk_delete {
+        let cursor = |field: usize| if form.field == field { "▏" } else { "" };
+        render_dialog(
+            f,
+            app.text.get("data.bulk_title"),
+            &app.text.format(
+                "data.bulk_field",
+                &[
+                    ("from", &format!("{}{}", form.from.as_str(), cursor(0))),
+                    ("to", &format!("{}{}", form.to.as_str(), cursor(1))),
+                    ("exercise", &format!("{}{}", form.exercise.as_str(), cursor(2))),
+                ],
+            ),
+        );
+    }
+    if let Some(delete) = &app.data_screen.confirm_bulk_delete {
+        let exercise = delete.exercise.clone().unwrap_or_else(|| app.text.get("data.bulk_all_exercises").to_string());
+        render_dialog(
+            f,
+            app.text.get("data.bulk_title"),
+            &app.text.format(
+                "data.bulk_confirm",
+                &[("count", &delete.ids.len()), ("from", &delete.from), ("to", &delete.to), ("exercise", &exercise)],
+            ),
+        );
+    }
+}
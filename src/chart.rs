@@ -0,0 +1,196 @@
+//! Chart of daily rep totals on the stats screen. Rest days make the raw
+//! line spiky, so a moving average can be laid over it; `moving_average`
+//! is kept separate for any other chart that wants the same smoothing.
+//! The one-line mini charts elsewhere scale their block glyphs with `spark`.
+
+use chrono::{Duration, NaiveDate};
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::{Color, Style},
+    symbols::Marker,
+    text::Span,
+    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType},
+    Frame,
+};
+
+use crate::stats::PeriodStats;
+use crate::App;
+
+/// Days in the chart, ending today.
+const CHART_DAYS: i64 = 30;
+/// Days in each moving average.
+pub const AVERAGE_DAYS: usize = 7;
+/// Block glyphs from smallest to biggest, for charts a line high.
+pub const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// The glyph for `value` on a scale from `low` to `high`, or the middle one
+/// when there's no range to scale over.
+pub fn spark(value: f64, low: f64, high: f64) -> char {
+    if high <= low {
+        return SPARKS[SPARKS.len() / 2];
+    }
+    let scaled = ((value - low) / (high - low)).clamp(0.0, 1.0);
+    SPARKS[(scaled * (SPARKS.len() - 1) as f64).round() as usize]
+}
+
+/// A glyph per set sized against the day's biggest, so 5×20 and a single
+/// 100 look different. Sets past `width` glyphs are cut to an ellipsis.
+pub fn set_bars(sets: &[i64], width: usize) -> String {
+    let high = sets.iter().copied().max().unwrap_or(0) as f64;
+    let bars = sets.iter().map(|set| spark(*set as f64, 0.0, high));
+    if sets.len() <= width {
+        return bars.collect();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    bars.take(width - 1).chain(std::iter::once('…')).collect()
+}
+
+/// The mean of each value with the `window - 1` before it. Values near the
+/// start have fewer before them, so their means are over what there is.
+pub fn moving_average(values: &[f64], window: usize) -> Vec<f64> {
+    let window = window.max(1);
+    let mut sum = 0.0;
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            sum += value;
+            if i >= window {
+                sum -= values[i - window];
+            }
+            sum / (i + 1).min(window) as f64
+        })
+        .collect()
+}
+
+/// Reps per day from `from` through `to`, with zeros for rest days.
+fn daily_reps(daily: &[PeriodStats], from: NaiveDate, to: NaiveDate) -> Vec<f64> {
+    std::iter::successors(Some(from), |day| Some(*day + Duration::days(1)))
+        .take_while(|day| *day <= to)
+        .map(|day| daily.iter().find(|d| d.start == day).map_or(0.0, |d| d.reps as f64))
+        .collect()
+}
+
+/// `daily` must cover every day from `covered_from`, which is as far back
+/// as the average can look for the first days in the chart.
+pub fn render_daily_chart(f: &mut Frame, area: Rect, daily: &[PeriodStats], covered_from: NaiveDate, today: NaiveDate, app: &App) {
+    let first = today - Duration::days(CHART_DAYS - 1);
+    let lead_in = (first - Duration::days(AVERAGE_DAYS as i64 - 1)).max(covered_from);
+    let values = daily_reps(daily, lead_in, today);
+    let skip = (first - lead_in).num_days().max(0) as usize;
+    let points = |values: &[f64]| -> Vec<(f64, f64)> {
+        values.iter().skip(skip).enumerate().map(|(i, value)| (i as f64, *value)).collect()
+    };
+    let raw = points(&values);
+    let average = points(&moving_average(&values, AVERAGE_DAYS));
+
+    let mut datasets = vec![Dataset::default()
+        .name(app.text.get("chart.reps"))
+        .marker(Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(Color::Cyan))
+        .data(&raw)];
+    if app.stats_average {
+        datasets.push(
+            Dataset::default()
+                .name(app.text.format("chart.average", &[("days", &AVERAGE_DAYS)]))
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(Color::Yellow))
+                .data(&average),
+        );
+    }
+
+    let top = raw.iter().map(|(_, value)| *value).fold(0.0, f64::max).max(1.0);
+    let last = (CHART_DAYS - 1) as f64;
+    let chart = Chart::new(datasets)
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("chart.title")))
+        .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)))
+        .x_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::DarkGray))
+                .bounds([0.0, last])
+                .labels(vec![
+                    Span::raw(app.display_date(first)),
+                    Span::raw(app.display_date(today - Duration::days(CHART_DAYS / 2))),
+                    Span::raw(app.display_date(today)),
+                ]),
+        )
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::DarkGray))
+                .bounds([0.0, top])
+                .labels(vec![Span::raw("0"), Span::raw(format!("{:.0}", top / 2.0)), Span::raw(format!("{top:.0}"))]),
+        );
+    f.render_widget(chart, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn averages_over_the_window() {
+        let values = [7.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 14.0, 0.0];
+        let average = moving_average(&values, 7);
+        assert_eq!(average[6], 1.0);
+        // The first day drops out as the eighth comes in
+        assert_eq!(average[7], 2.0);
+        assert_eq!(average[8], 2.0);
+    }
+
+    #[test]
+    fn the_start_uses_partial_windows() {
+        assert_eq!(moving_average(&[10.0, 20.0, 30.0, 40.0], 3), vec![10.0, 15.0, 20.0, 30.0]);
+    }
+
+    #[test]
+    fn rest_days_count_as_zero() {
+        assert_eq!(moving_average(&[10.0, 0.0, 10.0, 0.0], 2), vec![10.0, 5.0, 5.0, 5.0]);
+        assert_eq!(moving_average(&[0.0, 0.0], 7), vec![0.0, 0.0]);
+    }
+
+    #[test]
+    fn short_windows_and_empty_input() {
+        assert_eq!(moving_average(&[3.0, 5.0], 1), vec![3.0, 5.0]);
+        assert_eq!(moving_average(&[3.0, 5.0], 0), vec![3.0, 5.0]);
+        assert_eq!(moving_average(&[], 7), Vec::<f64>::new());
+    }
+
+    #[test]
+    fn sparks_scale_between_low_and_high() {
+        assert_eq!(spark(0.0, 0.0, 70.0), '▁');
+        assert_eq!(spark(30.0, 0.0, 70.0), '▄');
+        assert_eq!(spark(70.0, 0.0, 70.0), '█');
+        // Outside the range sticks to the ends
+        assert_eq!(spark(-5.0, 0.0, 70.0), '▁');
+        assert_eq!(spark(90.0, 0.0, 70.0), '█');
+        assert_eq!(spark(5.0, 5.0, 5.0), '▅');
+    }
+
+    #[test]
+    fn set_bars_size_each_set_against_the_biggest() {
+        assert_eq!(set_bars(&[20, 20, 20, 20, 20], 10), "█████");
+        assert_eq!(set_bars(&[100], 10), "█");
+        assert_eq!(set_bars(&[20, 10, 15, 5], 10), "█▅▆▃");
+        assert_eq!(set_bars(&[], 10), "");
+    }
+
+    #[test]
+    fn set_bars_end_in_an_ellipsis_past_the_width() {
+        assert_eq!(set_bars(&[10; 6], 4), "███…");
+        assert_eq!(set_bars(&[10; 4], 4), "████");
+        assert_eq!(set_bars(&[10; 2], 1), "…");
+        assert_eq!(set_bars(&[10; 2], 0), "");
+    }
+
+    #[test]
+    fn daily_reps_fill_rest_days_with_zeros() {
+        let day = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        let mut logged = PeriodStats::empty(day("2024-05-07"));
+        logged.reps = 30;
+        assert_eq!(daily_reps(&[logged], day("2024-05-06"), day("2024-05-08")), vec![0.0, 30.0, 0.0]);
+    }
+}
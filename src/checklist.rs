@@ -0,0 +1,194 @@
+//! Planned against done, the same way for every kind of plan: each planned
+//! set is checked off by a logged set of its exercise, the one closest in
+//! amount, and is then done (✓) or short of the plan (✗); the rest are
+//! pending. Sets beyond the plan count as extras and never push completion
+//! past 100%. The main screen shows the checklist while a plan is active.
+
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use crate::{exercises, App};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ItemState {
+    Done,
+    Short,
+    Pending,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChecklistItem {
+    pub exercise: String,
+    pub planned: i32,
+    /// Amount of the set that checked it off.
+    pub done: Option<i32>,
+}
+
+impl ChecklistItem {
+    pub fn state(&self) -> ItemState {
+        match self.done {
+            None => ItemState::Pending,
+            Some(done) if done >= self.planned => ItemState::Done,
+            Some(_) => ItemState::Short,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Checklist {
+    pub items: Vec<ChecklistItem>,
+    /// Sets logged with nothing planned left to check off.
+    pub extra: usize,
+}
+
+impl Checklist {
+    /// Checks `planned` sets off with `done` ones, both as exercise and
+    /// amount; each done set takes the open planned set of its exercise
+    /// closest in amount, the earlier one on a tie.
+    pub fn build(planned: &[(String, i32)], done: &[(String, i32)]) -> Self {
+        let mut checklist = Self {
+            items: planned
+                .iter()
+                .map(|(exercise, planned)| ChecklistItem { exercise: exercise.clone(), planned: *planned, done: None })
+                .collect(),
+            extra: 0,
+        };
+        for (exercise, count) in done {
+            let closest = checklist
+                .items
+                .iter_mut()
+                .filter(|item| item.exercise == *exercise && item.done.is_none())
+                .min_by_key(|item| (item.planned - count).abs());
+            match closest {
+                Some(item) => item.done = Some(*count),
+                None => checklist.extra += 1,
+            }
+        }
+        checklist
+    }
+
+    /// Share of the planned sets checked off, 0 to 100.
+    pub fn percent(&self) -> u32 {
+        if self.items.is_empty() {
+            return 100;
+        }
+        let checked = self.items.iter().filter(|item| item.state() != ItemState::Pending).count();
+        (checked * 100 / self.items.len()) as u32
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.items.iter().all(|item| item.state() != ItemState::Pending)
+    }
+
+    /// The planned sets of one exercise, in plan order.
+    pub fn planned<'a>(&'a self, exercise: &'a str) -> impl Iterator<Item = &'a ChecklistItem> + 'a {
+        self.items.iter().filter(move |item| item.exercise == exercise)
+    }
+
+    /// Exercises in the order they first appear in the plan.
+    fn exercises(&self) -> Vec<&str> {
+        let mut exercises: Vec<&str> = Vec::new();
+        for item in &self.items {
+            if !exercises.contains(&item.exercise.as_str()) {
+                exercises.push(&item.exercise);
+            }
+        }
+        exercises
+    }
+}
+
+/// Rows needed to show `checklist`, borders included.
+pub fn height(checklist: &Checklist, max_rows: usize) -> u16 {
+    checklist.exercises().len().min(max_rows) as u16 + 2
+}
+
+/// A line per exercise of the plan, each planned set marked with its state.
+pub fn render_checklist(f: &mut Frame, area: Rect, app: &App, title: &str, checklist: &Checklist) {
+    let lines: Vec<Line> = checklist
+        .exercises()
+        .into_iter()
+        .map(|exercise| {
+            let mut spans = vec![Span::styled(
+                format!("{}: ", exercises::display_name(exercise)),
+                Style::default().fg(app.exercise_color(exercise)),
+            )];
+            for item in checklist.planned(exercise) {
+                let planned = app.format_count(exercise, item.planned as i64);
+                let (text, color) = match (item.state(), item.done) {
+                    (ItemState::Done, _) => (format!("✓{planned}"), Color::Green),
+                    (ItemState::Short, Some(done)) => (format!("✗{}/{planned}", app.format_count(exercise, done as i64)), Color::Red),
+                    _ => (format!("·{planned}"), Color::DarkGray),
+                };
+                spans.push(Span::styled(text, Style::default().fg(color)));
+                spans.push(Span::raw(" "));
+            }
+            Line::from(spans)
+        })
+        .collect();
+
+    let percent = checklist.percent();
+    let mut summary = app.text.format("checklist.percent", &[("percent", &percent)]);
+    if checklist.extra > 0 {
+        summary.push_str(&app.text.format("checklist.extra", &[("count", &checklist.extra)]));
+    }
+    let color = if checklist.is_complete() { Color::Green } else { Color::Yellow };
+    let block = Block::default().borders(Borders::ALL).title(Line::from(vec![
+        Span::raw(format!("{title} ")),
+        Span::styled(summary, Style::default().fg(color).add_modifier(Modifier::BOLD)),
+    ]));
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sets(sets: &[(&str, i32)]) -> Vec<(String, i32)> {
+        sets.iter().map(|(exercise, count)| (exercise.to_string(), *count)).collect()
+    }
+
+    fn states(checklist: &Checklist) -> Vec<ItemState> {
+        checklist.items.iter().map(ChecklistItem::state).collect()
+    }
+
+    #[test]
+    fn partial_completion_leaves_the_rest_pending() {
+        let plan = sets(&[("squats", 20), ("squats", 18), ("push-ups", 15)]);
+        let checklist = Checklist::build(&plan, &sets(&[("squats", 20), ("push-ups", 12)]));
+        assert_eq!(states(&checklist), vec![ItemState::Done, ItemState::Pending, ItemState::Short]);
+        assert_eq!(checklist.percent(), 66);
+        assert!(!checklist.is_complete());
+        assert_eq!(checklist.extra, 0);
+    }
+
+    #[test]
+    fn sets_check_off_the_closest_planned_amount() {
+        let plan = sets(&[("squats", 20), ("squats", 18), ("squats", 12)]);
+        let checklist = Checklist::build(&plan, &sets(&[("squats", 13), ("squats", 19)]));
+        let done: Vec<Option<i32>> = checklist.items.iter().map(|item| item.done).collect();
+        assert_eq!(done, vec![Some(19), None, Some(13)]);
+    }
+
+    #[test]
+    fn over_completion_counts_extras_but_caps_at_the_plan() {
+        let plan = sets(&[("squats", 20), ("squats", 20)]);
+        let done = sets(&[("squats", 25), ("squats", 22), ("squats", 20), ("lunges", 10)]);
+        let checklist = Checklist::build(&plan, &done);
+        assert_eq!(states(&checklist), vec![ItemState::Done, ItemState::Done]);
+        assert_eq!(checklist.extra, 2);
+        assert_eq!(checklist.percent(), 100);
+        assert!(checklist.is_complete());
+    }
+
+    #[test]
+    fn nothing_logged_is_nothing_checked() {
+        let checklist = Checklist::build(&sets(&[("squats", 20)]), &[]);
+        assert_eq!(checklist.percent(), 0);
+        assert_eq!(states(&checklist), vec![ItemState::Pending]);
+    }
+}
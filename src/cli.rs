@@ -0,0 +1,249 @@
+use chrono::NaiveDate;
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+
+use crate::anonymize::DateMode;
+use crate::csv_import::CsvDialect;
+use crate::export::ExportFormat;
+use crate::list::{self, ListFormat};
+use crate::store::Backend;
+use crate::today::TodayFormat;
+
+/// Terminal workout tracker.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Start without mouse capture so the terminal's own text selection works
+    /// (toggle at runtime with F2).
+    #[arg(long)]
+    pub no_mouse: bool,
+
+    /// Storage backend, overriding [storage] in the config.
+    #[arg(long, value_enum)]
+    pub backend: Option<Backend>,
+
+    /// Data file, overriding [storage] in the config.
+    #[arg(long)]
+    pub db: Option<PathBuf>,
+
+    /// Pick the data file from the recently opened ones, or type another,
+    /// before starting.
+    #[arg(long, conflicts_with = "db")]
+    pub choose_db: bool,
+
+    /// Open this profile's data instead of asking at startup.
+    #[arg(long)]
+    pub profile: Option<String>,
+
+    /// Never check for a newer release, whatever the config says.
+    #[arg(long)]
+    pub no_update_check: bool,
+
+    /// Screen to open on ("main", "add", "history" or "stats"), overriding
+    /// both the config and the restored state.
+    #[arg(long)]
+    pub screen: Option<String>,
+
+    /// Show the first-run setup again, even if it was finished before.
+    #[arg(long)]
+    pub onboarding: bool,
+
+    /// Print today's totals, goal progress and streak on one line and exit,
+    /// for a status line or prompt. Works while the app has the file open.
+    #[arg(long, conflicts_with = "choose_db")]
+    pub today: bool,
+
+    /// How --today prints its summary.
+    #[arg(long, value_enum, default_value = "text", requires = "today")]
+    pub format: TodayFormat,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Write the data to a file and exit without starting the UI.
+    Export {
+        #[arg(long, value_enum)]
+        format: ExportFormat,
+        /// Output file; defaults to a timestamped name in the current folder.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Also export sets that are in the trash.
+        #[arg(long)]
+        include_deleted: bool,
+        /// First day for every format but sql (YYYY-MM-DD); defaults to 30
+        /// days before the last.
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// Last day for every format but sql; defaults to today.
+        #[arg(long)]
+        to: Option<NaiveDate>,
+        /// Only this exercise's sets in csv, json and markdown; repeat for more.
+        #[arg(long = "exercise")]
+        exercises: Vec<String>,
+        /// Anonymize csv, json and markdown: keep only the day of each set,
+        /// or shift every date by the same random number of days.
+        #[arg(long, value_enum)]
+        anonymize: Option<DateMode>,
+        /// With --anonymize, rename exercises to exercise_1, exercise_2 and
+        /// so on.
+        #[arg(long, requires = "anonymize")]
+        rename_exercises: bool,
+        #[command(flatten)]
+        csv: CsvArgs,
+    },
+    /// Print sets to stdout for scripts, oldest first, as JSON lines or TSV.
+    /// Amounts are as stored (reps, seconds or meters) and weights in kg.
+    List {
+        /// First day (YYYY-MM-DD).
+        #[arg(long, value_parser = list::parse_day)]
+        from: Option<NaiveDate>,
+        /// Last day (YYYY-MM-DD).
+        #[arg(long, value_parser = list::parse_day)]
+        to: Option<NaiveDate>,
+        /// Only this exercise's sets; repeat for more.
+        #[arg(long = "exercise")]
+        exercises: Vec<String>,
+        #[arg(long, value_enum, default_value = "json")]
+        format: ListFormat,
+    },
+    /// Write a Markdown summary of one week and exit.
+    Report {
+        /// ISO week such as 2024-W18; defaults to the current week.
+        #[arg(long)]
+        week: Option<String>,
+        /// Output file; defaults to a name with the week in the current folder.
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+    /// Import sets from a Strong app CSV export.
+    ImportStrong {
+        path: PathBuf,
+        /// Where rows that can't be imported are written; defaults to
+        /// <file>.rejects.csv next to the export.
+        #[arg(long)]
+        rejects: Option<PathBuf>,
+        /// Import after the preview without asking.
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Import sets from a FitNotes CSV export.
+    ImportFitnotes {
+        path: PathBuf,
+        /// Where rows that can't be imported are written; defaults to
+        /// <file>.rejects.csv next to the export.
+        #[arg(long)]
+        rejects: Option<PathBuf>,
+        /// Import after the preview without asking.
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Import sets from this app's JSON export of sets; sets already here
+    /// (by UUID) are skipped.
+    ImportJson {
+        path: PathBuf,
+        /// Where sets that can't be imported are written; defaults to
+        /// <file>.rejects.csv next to the export.
+        #[arg(long)]
+        rejects: Option<PathBuf>,
+        /// Import after the preview without asking.
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Import sets from any CSV with a header row, mapping its columns on
+    /// the terminal.
+    ImportCsv {
+        path: PathBuf,
+        /// Use this saved column mapping instead of asking.
+        #[arg(long)]
+        mapping: Option<String>,
+        #[command(flatten)]
+        csv: CsvArgs,
+        /// Where rows that can't be imported are written; defaults to
+        /// <file>.rejects.csv next to the file.
+        #[arg(long)]
+        rejects: Option<PathBuf>,
+        /// Import after the preview without asking.
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Copy workouts missing here from another tracker database file.
+    Merge {
+        path: PathBuf,
+    },
+    /// Write exercise definitions, goals, tags and settings, but no sets,
+    /// to a TOML file (JSON if it ends in .json) and exit.
+    ExportConfig {
+        /// Output file; defaults to fitness_tracker-setup.toml in the
+        /// current folder.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Merge a file from export-config in, keeping what's here wherever
+    /// both differ, and list those differences.
+    ImportConfig {
+        path: PathBuf,
+    },
+    /// Move sets logged before a day into <data file>.archive.db, after
+    /// taking a backup. Merge that file to bring them back.
+    Archive {
+        /// First day to keep (YYYY-MM-DD); every set before it is moved.
+        before: NaiveDate,
+    },
+}
+
+impl Command {
+    /// Whether the command changes the data, and so has to hold the same
+    /// lock as the UI while it runs.
+    pub fn writes(&self) -> bool {
+        !matches!(self, Command::Export { .. } | Command::List { .. } | Command::Report { .. } | Command::ExportConfig { .. })
+    }
+}
+
+/// CSV dialect flags, each overriding the same option under [csv] in the
+/// config.
+#[derive(Debug, Args)]
+pub struct CsvArgs {
+    /// Field separator for CSV; "tab" for tabs.
+    #[arg(long, value_parser = parse_separator)]
+    pub delimiter: Option<char>,
+    /// Quote character for CSV fields.
+    #[arg(long)]
+    pub quote: Option<char>,
+    /// CSV has a header row.
+    #[arg(long, conflicts_with = "no_header")]
+    pub header: bool,
+    /// CSV has no header row.
+    #[arg(long)]
+    pub no_header: bool,
+    /// Timestamp format for CSV, e.g. "%d.%m.%Y %H:%M:%S".
+    #[arg(long)]
+    pub timestamp_format: Option<String>,
+    /// Decimal separator for weights in CSV: "." or ",".
+    #[arg(long)]
+    pub decimal: Option<char>,
+}
+
+impl CsvArgs {
+    /// `dialect` with the flags that were given applied.
+    pub fn apply(&self, dialect: &CsvDialect) -> CsvDialect {
+        CsvDialect {
+            delimiter: self.delimiter.unwrap_or(dialect.delimiter),
+            quote: self.quote.unwrap_or(dialect.quote),
+            header: if self.header || self.no_header { self.header } else { dialect.header },
+            timestamp_format: self.timestamp_format.clone().unwrap_or_else(|| dialect.timestamp_format.clone()),
+            decimal: self.decimal.unwrap_or(dialect.decimal),
+        }
+    }
+}
+
+fn parse_separator(text: &str) -> Result<char, String> {
+    let mut chars = text.chars();
+    match (text, chars.next(), chars.next()) {
+        ("tab", _, _) => Ok('\t'),
+        (_, Some(c), None) => Ok(c),
+        _ => Err("expected a single character or \"tab\"".to_string()),
+    }
+}
@@ -0,0 +1,125 @@
+use std::{
+    env, fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use crate::units::{self, DistanceUnit};
+use crate::{App, WorkoutRecord};
+
+const SUMMARY_FILE: &str = "fitness-tracker-summary.txt";
+
+/// Where copied text ended up.
+pub enum Copied {
+    /// The system clipboard.
+    #[cfg_attr(not(feature = "clipboard"), allow(dead_code))]
+    Clipboard,
+    /// Handed to the terminal with OSC 52, which can't confirm it worked.
+    Terminal,
+    /// No clipboard could be reached, so the text was saved here.
+    File(PathBuf),
+}
+
+/// The system clipboard, kept open for the app's lifetime: on X11 and
+/// Wayland the copied text is only available while its owner is alive.
+#[derive(Default)]
+pub struct Clipboard {
+    #[cfg(feature = "clipboard")]
+    system: Option<arboard::Clipboard>,
+}
+
+impl Clipboard {
+    /// Copies `text` to the system clipboard when built with it, else to
+    /// the terminal over SSH, else to a file in the temp directory.
+    pub fn copy(&mut self, text: &str) -> io::Result<Copied> {
+        #[cfg(feature = "clipboard")]
+        {
+            if self.system.is_none() {
+                self.system = arboard::Clipboard::new().ok();
+            }
+            if let Some(system) = &mut self.system
+                && system.set_text(text).is_ok()
+            {
+                return Ok(Copied::Clipboard);
+            }
+        }
+        if env::var_os("SSH_TTY").is_some() || env::var_os("SSH_CONNECTION").is_some() {
+            let mut stdout = io::stdout();
+            let sent = write!(stdout, "\x1b]52;c;{}\x07", base64(text.as_bytes())).and_then(|_| stdout.flush());
+            if sent.is_ok() {
+                return Ok(Copied::Terminal);
+            }
+        }
+        let path = env::temp_dir().join(SUMMARY_FILE);
+        fs::write(&path, text)?;
+        Ok(Copied::File(path))
+    }
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// A one-line summary of a day's sets: each exercise in the order it was
+/// first done, with its sets and their total, e.g.
+/// "2024-05-03 — squats 3×20 (60), push-ups 15+12 (27)".
+pub fn day_summary(date: &str, sets: &[(&str, &str, i64)], distance: DistanceUnit) -> String {
+    let mut exercises: Vec<(&str, &str, Vec<i64>)> = Vec::new();
+    for (exercise, unit, count) in sets {
+        match exercises.iter_mut().find(|(name, ..)| name == exercise) {
+            Some((_, _, counts)) => counts.push(*count),
+            None => exercises.push((exercise, unit, vec![*count])),
+        }
+    }
+    let parts: Vec<String> = exercises
+        .iter()
+        .map(|(exercise, unit, counts)| {
+            let amount = |count: i64| units::format_amount(count, unit, distance);
+            let total = amount(counts.iter().sum());
+            match counts.as_slice() {
+                [single] => format!("{exercise} {}", amount(*single)),
+                [first, rest @ ..] if rest.iter().all(|c| c == first) => {
+                    format!("{exercise} {}×{} ({total})", counts.len(), amount(*first))
+                }
+                _ => {
+                    let sets: Vec<String> = counts.iter().map(|c| amount(*c)).collect();
+                    format!("{exercise} {} ({total})", sets.join("+"))
+                }
+            }
+        })
+        .collect();
+    format!("{date} — {}", parts.join(", "))
+}
+
+impl App {
+    /// Copies the summary of `records`, logged on `date`, and says where it went.
+    pub(crate) fn copy_day_summary(&mut self, date: &str, records: &[WorkoutRecord]) {
+        if records.is_empty() {
+            self.message = Some(self.text.get("clipboard.nothing").to_string());
+            return;
+        }
+        let sets: Vec<(&str, &str, i64)> = records
+            .iter()
+            .map(|r| (r.exercise_type.as_str(), self.exercise_unit(&r.exercise_type), r.count as i64))
+            .collect();
+        let summary = day_summary(date, &sets, self.config.units.distance);
+        self.message = Some(match self.clipboard.copy(&summary) {
+            Ok(Copied::Clipboard) => self.text.format("clipboard.copied", &[("text", &summary)]),
+            Ok(Copied::Terminal) => self.text.format("clipboard.sent", &[("text", &summary)]),
+            Ok(Copied::File(path)) => self.text.format("clipboard.saved", &[("path", &path.display())]),
+            Err(error) => self.text.format("clipboard.failed", &[("error", &error)]),
+        });
+    }
+}
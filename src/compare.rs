@@ -0,0 +1,351 @@
+//! Friendly competition: two profiles' weekly sets, streaks and goal
+//! completion over the same weeks, side by side.
+
+use anyhow::Result;
+use chrono::{Duration, Local, NaiveDate};
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, Wrap},
+    Frame,
+};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, HashMap},
+};
+
+use crate::dates::{self, week_start, WeekStart};
+use crate::encryption;
+use crate::store::Backend;
+use crate::units;
+use crate::{App, Database, Screen};
+
+/// Ranges offered, in weeks up to and including this one.
+const RANGES: [i64; 4] = [4, 8, 12, 26];
+const BAR_WIDTH: usize = 20;
+const COLORS: [Color; 2] = [Color::Cyan, Color::Magenta];
+
+/// One profile's numbers over the compared range.
+#[derive(Debug, Clone)]
+pub struct ProfileSummary {
+    pub name: String,
+    /// Sets per week, oldest first, with an entry for every week.
+    pub weekly_sets: Vec<i64>,
+    /// Amount per exercise with its unit, in first-logged order.
+    pub exercises: Vec<(String, String, i64)>,
+    pub active_days: usize,
+    /// Days in a row with sets up to today, or up to yesterday while today
+    /// is still empty.
+    pub current_streak: usize,
+    pub longest_streak: usize,
+    /// Days an exercise's daily goal was reached...
+    pub goals_met: i64,
+    /// ...out of the days in the range times the number of goals.
+    pub goals_possible: i64,
+}
+
+impl ProfileSummary {
+    pub fn sets(&self) -> i64 {
+        self.weekly_sets.iter().sum()
+    }
+}
+
+impl Database {
+    /// `name`'s numbers for the weeks from the one containing `from` up to `today`.
+    pub fn profile_summary(&self, name: &str, from: NaiveDate, today: NaiveDate, week: WeekStart) -> Result<ProfileSummary> {
+        let first_week = week_start(from, week);
+        let mut stmt = self.conn.prepare(
+            "SELECT d.day, d.exercise_type, COALESCE(e.unit, ?3), d.total, d.sets
+             FROM daily_totals d
+             LEFT JOIN exercises e ON e.name = d.exercise_type
+             WHERE d.day >= ?1 AND d.day <= ?2
+             ORDER BY d.first_at",
+        )?;
+        let day = |date: NaiveDate| date.format("%Y-%m-%d").to_string();
+        let rows = stmt
+            .query_map(rusqlite::params![day(first_week), day(today), "reps"], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, i64>(4)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let weeks = (week_start(today, week) - first_week).num_weeks() as usize + 1;
+        let goals = self.get_goals()?;
+        let mut summary = ProfileSummary {
+            name: name.to_string(),
+            weekly_sets: vec![0; weeks],
+            exercises: Vec::new(),
+            active_days: 0,
+            current_streak: 0,
+            longest_streak: 0,
+            goals_met: 0,
+            goals_possible: goals.len() as i64 * ((today - first_week).num_days() + 1),
+        };
+        let mut days = BTreeSet::new();
+        for (date, exercise, unit, total, sets) in rows {
+            let Some(date) = dates::parse_date(&date) else {
+                continue;
+            };
+            days.insert(date);
+            summary.weekly_sets[((date - first_week).num_days() / 7) as usize] += sets;
+            match summary.exercises.iter_mut().find(|(name, ..)| *name == exercise) {
+                Some((.., amount)) => *amount += total,
+                None => summary.exercises.push((exercise.clone(), unit, total)),
+            }
+            if goals.get(&exercise).is_some_and(|target| total >= *target) {
+                summary.goals_met += 1;
+            }
+        }
+        let days: Vec<NaiveDate> = days.into_iter().collect();
+        summary.active_days = days.len();
+        (summary.current_streak, summary.longest_streak) = streaks(&days, today);
+        Ok(summary)
+    }
+}
+
+/// Current and longest run of consecutive days in `days` (sorted, unique).
+pub fn streaks(days: &[NaiveDate], today: NaiveDate) -> (usize, usize) {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for day in days {
+        run = match previous {
+            Some(previous) if *day - previous == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(*day);
+    }
+    let current = match previous {
+        Some(last) if last == today || last == today - Duration::days(1) => run,
+        _ => 0,
+    };
+    (current, longest)
+}
+
+/// Exercises both profiles logged, with each one's amount, then those only
+/// the first or only the second did.
+#[allow(clippy::type_complexity)]
+pub fn shared_exercises<'a>(
+    a: &'a ProfileSummary,
+    b: &'a ProfileSummary,
+) -> (Vec<(&'a str, &'a str, i64, i64)>, Vec<&'a str>, Vec<&'a str>) {
+    let b_amounts: HashMap<&str, i64> = b.exercises.iter().map(|(name, _, amount)| (name.as_str(), *amount)).collect();
+    let mut shared = Vec::new();
+    let mut only_a = Vec::new();
+    for (name, unit, amount) in &a.exercises {
+        match b_amounts.get(name.as_str()) {
+            Some(other) => shared.push((name.as_str(), unit.as_str(), *amount, *other)),
+            None => only_a.push(name.as_str()),
+        }
+    }
+    let only_b = b
+        .exercises
+        .iter()
+        .map(|(name, ..)| name.as_str())
+        .filter(|name| !a.exercises.iter().any(|(other, ..)| other == name))
+        .collect();
+    (shared, only_a, only_b)
+}
+
+pub struct CompareState {
+    /// The profile being compared with the open one.
+    pub other: String,
+    pub other_db: Database,
+    /// Index into `RANGES`.
+    pub range: usize,
+}
+
+impl App {
+    /// Opens the comparison between the open profile and `other`.
+    pub(crate) fn open_compare_screen(&mut self, other: &str) -> Result<()> {
+        let profiles = &self.profiles.profiles;
+        let path = profiles.path(other);
+        if profiles.backend == Backend::Sqlite && encryption::is_encrypted(&path) {
+            self.message = Some(self.text.format("profiles.encrypted", &[("name", &other)]));
+            return Ok(());
+        }
+        self.compare = Some(CompareState {
+            other: other.to_string(),
+            other_db: Database::open(profiles.backend, &path)?,
+            range: 1,
+        });
+        self.screen = Screen::Compare;
+        Ok(())
+    }
+
+    pub(crate) fn handle_compare_input(&mut self, key: KeyCode) -> Result<bool> {
+        let Some(state) = &mut self.compare else {
+            self.screen = Screen::Profiles;
+            return Ok(false);
+        };
+        match key {
+            KeyCode::Esc => {
+                self.compare = None;
+                self.screen = Screen::Profiles;
+            }
+            KeyCode::Left => state.range = (state.range + RANGES.len() - 1) % RANGES.len(),
+            KeyCode::Right => state.range = (state.range + 1) % RANGES.len(),
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    /// Both profiles' summaries, the open one first.
+    fn compare_summaries(&self, state: &CompareState) -> Result<[ProfileSummary; 2]> {
+        let today = Local::now().date_naive();
+        let week = self.config.week_starts_on;
+        let from = today - Duration::weeks(RANGES[state.range] - 1);
+        Ok([
+            self.db.profile_summary(&self.profiles.active, from, today, week)?,
+            state.other_db.profile_summary(&state.other, from, today, week)?,
+        ])
+    }
+}
+
+pub fn render_compare_screen(f: &mut Frame, area: Rect, app: &App) {
+    let Some(state) = &app.compare else {
+        return;
+    };
+    let summaries = match app.compare_summaries(state) {
+        Ok(summaries) => summaries,
+        Err(e) => {
+            let error = Paragraph::new(format!("{e:#}")).block(Block::default().borders(Borders::ALL));
+            f.render_widget(error, area);
+            return;
+        }
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(8), Constraint::Min(0)])
+        .split(area);
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[1]);
+
+    let weeks = RANGES[state.range];
+    let title = app.text.format(
+        "compare.title",
+        &[("a", &summaries[0].name), ("b", &summaries[1].name), ("weeks", &weeks)],
+    );
+    f.render_widget(summary_table(&summaries, app).block(Block::default().borders(Borders::ALL).title(title)), chunks[0]);
+    render_weekly_bars(f, bottom[0], &summaries, app);
+    render_exercises(f, bottom[1], &summaries, app);
+}
+
+/// Totals, streaks and goals in paired columns, the better of each pair
+/// highlighted.
+fn summary_table<'a>(summaries: &[ProfileSummary; 2], app: &'a App) -> Table<'a> {
+    let [a, b] = summaries;
+    let row = |label: &str, values: [i64; 2], cells: [String; 2]| {
+        let better = Style::default().fg(Color::Green).add_modifier(Modifier::BOLD);
+        let [mut first, mut second] = cells.map(Cell::from);
+        match values[0].cmp(&values[1]) {
+            Ordering::Greater => first = first.style(better),
+            Ordering::Less => second = second.style(better),
+            Ordering::Equal => {}
+        }
+        Row::new(vec![Cell::from(app.text.get(label).to_string()), first, second])
+    };
+    let count = |label: &str, values: [i64; 2]| row(label, values, values.map(|v| v.to_string()));
+    let goals = |s: &ProfileSummary| match s.goals_possible {
+        0 => "-".to_string(),
+        possible => format!("{}/{possible} ({}%)", s.goals_met, s.goals_met * 100 / possible),
+    };
+    // Compared as a rate, so a profile with more goals isn't ahead just for that
+    let goal_rate = |s: &ProfileSummary| s.goals_met * 1000 / s.goals_possible.max(1);
+    let rows = vec![
+        count("compare.sets", [a.sets(), b.sets()]),
+        count("compare.active_days", [a.active_days as i64, b.active_days as i64]),
+        count("compare.current_streak", [a.current_streak as i64, b.current_streak as i64]),
+        count("compare.longest_streak", [a.longest_streak as i64, b.longest_streak as i64]),
+        row("compare.goals", [goal_rate(a), goal_rate(b)], [goals(a), goals(b)]),
+    ];
+    Table::new(rows, [Constraint::Length(18), Constraint::Length(18), Constraint::Min(0)]).header(
+        Row::new(vec![String::new(), a.name.clone(), b.name.clone()])
+            .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+    )
+}
+
+/// Sets per week as a pair of bars, newest week first, scaled to the
+/// busiest week of either profile.
+fn render_weekly_bars(f: &mut Frame, area: Rect, summaries: &[ProfileSummary; 2], app: &App) {
+    let block = Block::default().borders(Borders::ALL).title(app.text.get("compare.weekly"));
+    let today = Local::now().date_naive();
+    let this_week = week_start(today, app.config.week_starts_on);
+    let most = summaries.iter().flat_map(|s| s.weekly_sets.iter().copied()).max().unwrap_or(0);
+    if most == 0 {
+        f.render_widget(Paragraph::new(app.text.get("compare.no_sets")).block(block), area);
+        return;
+    }
+    let name_width = summaries.iter().map(|s| s.name.chars().count()).max().unwrap_or(0);
+    let weeks = summaries[0].weekly_sets.len();
+    let mut lines = Vec::new();
+    for i in (0..weeks).rev() {
+        let start = this_week - Duration::weeks((weeks - 1 - i) as i64);
+        lines.push(Line::from(Span::styled(app.display_date(start), Style::default().fg(Color::Yellow))));
+        for (summary, color) in summaries.iter().zip(COLORS) {
+            let sets = summary.weekly_sets.get(i).copied().unwrap_or(0);
+            let bar = "█".repeat((sets as usize * BAR_WIDTH).div_ceil(most as usize));
+            lines.push(Line::from(vec![
+                Span::raw(format!("  {:name_width$} ", summary.name)),
+                Span::styled(bar, Style::default().fg(color)),
+                Span::raw(format!(" {sets}")),
+            ]));
+        }
+    }
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Amounts for the exercises both logged, and which ones only one of them did.
+fn render_exercises(f: &mut Frame, area: Rect, summaries: &[ProfileSummary; 2], app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(5)])
+        .split(area);
+    let distance = app.config.units.distance;
+    let (shared, only_a, only_b) = shared_exercises(&summaries[0], &summaries[1]);
+    let rows: Vec<Row> = if shared.is_empty() {
+        vec![Row::new(vec![app.text.get("compare.nothing_shared").to_string()])]
+    } else {
+        shared
+            .iter()
+            .map(|(name, unit, a, b)| {
+                Row::new(vec![
+                    name.to_string(),
+                    units::format_amount(*a, unit, distance),
+                    units::format_amount(*b, unit, distance),
+                ])
+            })
+            .collect()
+    };
+    let table = Table::new(rows, [Constraint::Percentage(40), Constraint::Percentage(30), Constraint::Min(0)])
+        .header(
+            Row::new(vec![
+                app.text.get("common.exercise").to_string(),
+                summaries[0].name.clone(),
+                summaries[1].name.clone(),
+            ])
+            .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("compare.shared")));
+    f.render_widget(table, chunks[0]);
+
+    let list = |names: &[&str]| if names.is_empty() { "-".to_string() } else { names.join(", ") };
+    let lines = vec![
+        Line::from(app.text.format("compare.only", &[("name", &summaries[0].name), ("exercises", &list(&only_a))])),
+        Line::from(app.text.format("compare.only", &[("name", &summaries[1].name), ("exercises", &list(&only_b))])),
+    ];
+    let others = Paragraph::new(lines)
+        .wrap(Wrap { trim: true })
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("compare.not_shared")));
+    f.render_widget(others, chunks[1]);
+}
@@ -0,0 +1,423 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use crate::csv_import::CsvDialect;
+use crate::dates::{DateFormat, TimeFormat, WeekStart};
+use crate::notify::Alert;
+use crate::store::Backend;
+use crate::units::{DistanceUnit, WeightUnit};
+
+pub const CONFIG_PATH: &str = "config.toml";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// First day of the week for every weekly total ("monday" or "sunday").
+    pub week_starts_on: WeekStart,
+    /// UI language code ("en" or "uk"); anything else falls back to English.
+    pub language: String,
+    pub apple_health: AppleHealthConfig,
+    pub backup: BackupConfig,
+    pub csv: CsvDialect,
+    pub csv_import: ImportConfig,
+    pub fitnotes_import: ImportConfig,
+    pub goals: GoalsConfig,
+    pub journal: JournalConfig,
+    pub measurements: MeasurementsConfig,
+    pub mood: MoodConfig,
+    pub notify: NotifyConfig,
+    pub plates: PlatesConfig,
+    pub progression: ProgressionConfig,
+    pub reminders: RemindersConfig,
+    pub storage: StorageConfig,
+    pub strong_import: ImportConfig,
+    pub timer: TimerConfig,
+    pub trash: TrashConfig,
+    pub trends: TrendsConfig,
+    pub ui: UiConfig,
+    pub units: UnitsConfig,
+    pub updates: UpdatesConfig,
+    pub webhook: WebhookConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AppleHealthConfig {
+    /// One workout per session of sets instead of one per set.
+    pub sessions: bool,
+    /// Sets further apart than this start a new session.
+    pub session_gap_minutes: u32,
+    /// HealthKit activity type by exercise name, e.g.
+    /// `running = "Running"`. Rep exercises without an entry count as
+    /// TraditionalStrengthTraining (CoreTraining for the core category);
+    /// timed and distance exercises without one are skipped.
+    pub activities: HashMap<String, String>,
+}
+
+impl Default for AppleHealthConfig {
+    fn default() -> Self {
+        Self {
+            sessions: true,
+            session_gap_minutes: 30,
+            activities: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BackupConfig {
+    /// Back up the database on a clean exit when the session changed it.
+    pub on_exit: bool,
+    /// Also back up when the app starts.
+    pub on_startup: bool,
+    /// Number of backups to keep; older ones are deleted.
+    pub keep: usize,
+    /// Backup folder, relative to the database file unless absolute.
+    pub dir: String,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            on_exit: true,
+            on_startup: false,
+            keep: 7,
+            dir: "backups".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct GoalsConfig {
+    /// How far above the recent daily average a suggested goal is, in percent.
+    pub progression_percent: u32,
+    /// Show the pace needed for the nearest deadline goal in the status bar.
+    pub deadline_in_status: bool,
+}
+
+impl Default for GoalsConfig {
+    fn default() -> Self {
+        Self { progression_percent: 10, deadline_in_status: false }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PlatesConfig {
+    /// Weight of the empty bar, in the `[units] weight` unit.
+    pub bar: f64,
+    /// Plates on hand, in the same unit; any number of pairs of each.
+    pub sizes: Vec<f64>,
+}
+
+impl Default for PlatesConfig {
+    fn default() -> Self {
+        Self {
+            bar: 20.0,
+            sizes: vec![25.0, 20.0, 15.0, 10.0, 5.0, 2.5, 1.25],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ProgressionConfig {
+    /// How far below a week's prescription counts as a bad miss, in percent.
+    pub miss_percent: u32,
+    /// How much a bad miss takes off the next week's prescription, in
+    /// percent; 0 holds it instead.
+    pub deload_percent: u32,
+}
+
+impl Default for ProgressionConfig {
+    fn default() -> Self {
+        Self {
+            miss_percent: 20,
+            deload_percent: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RemindersConfig {
+    /// Global off switch for reminder banners.
+    pub enabled: bool,
+    /// Times of day ("HH:MM") to check that something was logged since the
+    /// previous one. Unparseable entries are ignored.
+    pub times: Vec<String>,
+    /// Time of day ("HH:MM") after which a daily goal that isn't met yet
+    /// shows a banner until it's met or dismissed; empty turns it off.
+    pub goal_after: String,
+}
+
+impl Default for RemindersConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            times: Vec::new(),
+            goal_after: "20:00".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TrashConfig {
+    /// Deleted sets are purged for good this many days after deletion, on
+    /// startup; 0 keeps them forever.
+    pub purge_after_days: u32,
+}
+
+impl Default for TrashConfig {
+    fn default() -> Self {
+        Self { purge_after_days: 30 }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TrendsConfig {
+    /// Changes from the previous period within this many percent show as
+    /// steady on the stats screen.
+    pub tolerance_percent: u32,
+    /// Weeks in a row of rising volume before an easier week is suggested;
+    /// 0 turns the hint off.
+    pub deload_weeks: u32,
+    /// How much a week's volume has to beat the one before to count as
+    /// rising, in percent.
+    pub deload_rise_percent: u32,
+}
+
+impl Default for TrendsConfig {
+    fn default() -> Self {
+        Self {
+            tolerance_percent: 5,
+            deload_weeks: 4,
+            deload_rise_percent: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ImportConfig {
+    /// Unit of the export's weight column ("kg" or "lbs") when its header
+    /// doesn't say.
+    pub weight_unit: WeightUnit,
+    /// This tracker's exercise by the other app's exercise name, e.g.
+    /// `"Squat (Barbell)" = "squats"`.
+    pub exercises: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct JournalConfig {
+    /// Append every logged set to a plain-text CSV journal as it's saved.
+    pub enabled: bool,
+    /// Journal file, relative to the database file unless absolute.
+    pub path: String,
+}
+
+impl Default for JournalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: "fitness_tracker.journal.csv".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// "sqlite", or "jsonl" for a plain-text file that diffs well in git.
+    pub backend: Backend,
+    /// Data file; defaults to fitness_tracker.db or fitness_tracker.jsonl.
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TimerConfig {
+    /// Rest countdown started after each logged set; 0 turns it off. Rests
+    /// longer than this show in red in the history and stats.
+    pub rest_seconds: u64,
+}
+
+impl Default for TimerConfig {
+    fn default() -> Self {
+        Self { rest_seconds: 90 }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct NotifyConfig {
+    /// Master switch for every bell and flash.
+    pub enabled: bool,
+    /// Alert when the rest timer runs out ("off", "bell", "flash" or "both").
+    pub timer: Alert,
+    /// Alert when a daily goal is reached.
+    pub goal: Alert,
+    /// Also send a desktop notification for any alert that isn't "off".
+    /// Only has an effect in builds with the `desktop-notify` feature.
+    pub desktop: bool,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            timer: Alert::Both,
+            goal: Alert::Flash,
+            desktop: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct UnitsConfig {
+    /// Unit used to enter and display distance exercises ("km" or "miles").
+    pub distance: DistanceUnit,
+    /// Unit used to enter and display weights ("kg" or "lbs").
+    pub weight: WeightUnit,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct UpdatesConfig {
+    /// Look for a newer release on GitHub once a day and mention it in the
+    /// status bar. Needs a build with the `update-check` feature.
+    pub check: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    /// POST a small JSON payload to `url` after every logged set. Needs a
+    /// build with the `webhook` feature.
+    pub enabled: bool,
+    /// Where the payload goes, like "http://dashboard.local/hooks/fitness".
+    pub url: String,
+    /// Sent as the Authorization header when set, like "Bearer abc123".
+    pub authorization: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct UiConfig {
+    /// Render without any colors (also enabled by the NO_COLOR variable).
+    pub monochrome: bool,
+    /// Display style for dates ("iso", "dmy", "mdy", "short" or "relative").
+    pub date_format: DateFormat,
+    /// Clock style for displayed times ("24h" or "12h").
+    pub time_format: TimeFormat,
+    /// Capture the mouse on startup. Turn off to keep the terminal's own
+    /// text selection; F2 toggles it while running.
+    pub mouse: bool,
+    /// Exercise selected on the add screen when the app starts.
+    pub default_exercise: Option<String>,
+    /// Screen the app opens on: "main", "add", "history" or "stats".
+    /// Anything else opens the main screen with a warning.
+    pub startup_screen: String,
+    /// Open where the last session was quit from instead, with the same
+    /// exercise or day selected.
+    pub restore_state: bool,
+    /// Print a recap of the session's sets, goals and bests after quitting.
+    pub exit_summary: bool,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            monochrome: false,
+            date_format: DateFormat::default(),
+            time_format: TimeFormat::default(),
+            mouse: true,
+            default_exercise: None,
+            startup_screen: "main".to_string(),
+            restore_state: false,
+            exit_summary: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MeasurementsConfig {
+    pub metrics: Vec<MetricDef>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MetricDef {
+    pub name: String,
+    pub unit: String,
+}
+
+impl Default for MeasurementsConfig {
+    fn default() -> Self {
+        let metric = |name: &str, unit: &str| MetricDef {
+            name: name.to_string(),
+            unit: unit.to_string(),
+        };
+        Self {
+            metrics: vec![
+                metric("weight", "kg"),
+                metric("waist", "cm"),
+                metric("chest", "cm"),
+                metric("arm", "cm"),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MoodConfig {
+    /// Ask for a daily mood check-in when the app opens.
+    pub prompt: bool,
+}
+
+impl Default for MoodConfig {
+    fn default() -> Self {
+        Self { prompt: true }
+    }
+}
+
+impl Config {
+    /// Loads the config file, falling back to defaults when it doesn't exist.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                toml::from_str(&text).with_context(|| format!("invalid config in {}", path.display()))
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("failed to read {}", path.display())),
+        }
+    }
+}
+
+/// Points `[storage] path` in the config file at `data`, creating the file
+/// if needed and leaving the rest of it, comments included, untouched.
+pub fn save_storage_path(path: impl AsRef<Path>, data: &Path) -> Result<()> {
+    let path = path.as_ref();
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e).with_context(|| format!("failed to read {}", path.display())),
+    };
+    let mut doc: toml_edit::DocumentMut = text.parse().with_context(|| format!("invalid config in {}", path.display()))?;
+    doc["storage"]["path"] = toml_edit::value(data.to_string_lossy().as_ref());
+    fs::write(path, doc.to_string()).with_context(|| format!("failed to write {}", path.display()))
+}
@@ -0,0 +1,667 @@
+//! Shared plumbing for importing sets from other apps' CSV exports. Each
+//! importer only maps its columns onto `ImportSet`s; reading the CSV,
+//! exercise mapping, dedupe, the transaction and the summary live here.
+
+use anyhow::{bail, Context, Result};
+use chrono::{Local, NaiveDateTime};
+use rusqlite::params;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, BufRead, IsTerminal, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::config::ImportConfig;
+use crate::exercises::{Exercise, CATEGORIES};
+use crate::units::{self, DistanceUnit, WeightUnit};
+use crate::{dates, Database};
+
+/// One set read from another app's export, before it's mapped to an
+/// exercise here.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportSet {
+    /// Line the record starts on, for the summary.
+    pub line: usize,
+    /// The record as it appeared in the file, for the rejects file.
+    pub raw: String,
+    pub at: NaiveDateTime,
+    /// Exercise name in the other app.
+    pub exercise: String,
+    /// Category given by the other app, used for new exercises.
+    pub category: Option<String>,
+    pub reps: Option<i64>,
+    /// Kilograms.
+    pub weight: Option<f64>,
+    pub seconds: Option<i64>,
+    /// In the configured distance unit, like typed-in distances.
+    pub distance: Option<f64>,
+    pub rpe: Option<f64>,
+    /// The set's UUID when the file is one of ours, which makes it the
+    /// same set as the one here with that UUID.
+    pub uuid: Option<String>,
+}
+
+/// A record that couldn't be imported and why.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reject {
+    pub line: usize,
+    pub raw: String,
+    pub reason: String,
+}
+
+/// A parsed export: its header line and delimiter (for the rejects file),
+/// the sets and the rows that didn't parse.
+#[derive(Debug, Clone)]
+pub struct ImportFile {
+    pub header: String,
+    pub delimiter: char,
+    pub sets: Vec<ImportSet>,
+    pub rejects: Vec<Reject>,
+}
+
+/// Sets and total amount imported for one exercise.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedExercise {
+    pub name: String,
+    pub unit: String,
+    pub sets: usize,
+    pub total: i64,
+}
+
+/// What became of one parsed set.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RowOutcome {
+    New,
+    Duplicate,
+    Rejected(String),
+}
+
+/// A parsed set as it was (or would be) imported, for the preview.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportRow {
+    pub line: usize,
+    pub at: NaiveDateTime,
+    /// Exercise name here, after mapping.
+    pub exercise: String,
+    pub unit: String,
+    pub count: Option<i32>,
+    pub outcome: RowOutcome,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    pub exercises: Vec<ImportedExercise>,
+    /// Sets already in the database with the same exercise, amount and time.
+    pub duplicates: usize,
+    pub rejects: Vec<Reject>,
+    /// Every parsed set in file order.
+    pub rows: Vec<ImportRow>,
+}
+
+impl ImportReport {
+    pub fn added(&self) -> usize {
+        self.exercises.iter().map(|e| e.sets).sum()
+    }
+
+    /// First and last time among the parsed sets.
+    pub fn date_range(&self) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        let first = self.rows.iter().map(|row| row.at).min()?;
+        let last = self.rows.iter().map(|row| row.at).max()?;
+        Some((first, last))
+    }
+}
+
+/// A CSV file split into its header columns and records, each record with
+/// its starting line and raw text.
+pub struct CsvTable {
+    pub header: String,
+    pub delimiter: char,
+    pub columns: Vec<String>,
+    pub records: Vec<(usize, String, Vec<String>)>,
+}
+
+impl CsvTable {
+    /// Index of the first column whose name starts with `prefix`, ignoring
+    /// case, so "Weight" finds "Weight (kgs)".
+    pub fn column(&self, prefix: &str) -> Option<usize> {
+        self.columns
+            .iter()
+            .position(|c| c.trim().to_lowercase().starts_with(&prefix.to_lowercase()))
+    }
+}
+
+/// How CSV files are written and read, from `[csv]` in the config.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct CsvDialect {
+    /// Between fields: usually "," or ";", or "\t" for tabs.
+    pub delimiter: char,
+    /// Around fields that contain the delimiter, a quote or a line break.
+    pub quote: char,
+    /// The first row names the columns.
+    pub header: bool,
+    /// chrono format of timestamps, e.g. "%d.%m.%Y %H:%M:%S".
+    pub timestamp_format: String,
+    /// Decimal separator for weights: "." or ",". Reading accepts either.
+    pub decimal: char,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            quote: '"',
+            header: true,
+            timestamp_format: dates::TIMESTAMP_FORMAT.to_string(),
+            decimal: '.',
+        }
+    }
+}
+
+impl CsvDialect {
+    /// Fails on settings that would make the file unreadable.
+    pub fn check(&self) -> Result<()> {
+        if self.delimiter == self.quote {
+            bail!("the delimiter and the quote must differ");
+        }
+        if [self.delimiter, self.quote].iter().any(|c| matches!(c, '\n' | '\r')) {
+            bail!("the delimiter and the quote can't be line breaks");
+        }
+        if !matches!(self.decimal, '.' | ',') {
+            bail!("the decimal separator must be \".\" or \",\"");
+        }
+        if self.timestamp_format.trim().is_empty() {
+            bail!("the timestamp format is empty");
+        }
+        Ok(())
+    }
+
+    /// Quotes a field when it needs it.
+    pub fn field(&self, text: &str) -> String {
+        if text.contains([self.delimiter, self.quote, '\n', '\r']) {
+            let quote = self.quote.to_string();
+            format!("{quote}{}{quote}", text.replace(self.quote, &quote.repeat(2)))
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// A number with the dialect's decimal separator, quoted if that's
+    /// also the delimiter.
+    pub fn number(&self, value: f64) -> String {
+        self.field(&value.to_string().replace('.', &self.decimal.to_string()))
+    }
+
+    /// A stored timestamp in the dialect's format; one that doesn't parse
+    /// is written as it is.
+    pub fn timestamp(&self, stored: &str) -> String {
+        match dates::parse_timestamp(stored) {
+            Some(at) => at.format(&self.timestamp_format).to_string(),
+            None => stored.to_string(),
+        }
+    }
+
+    /// A row of already quoted fields.
+    pub fn join(&self, fields: &[String]) -> String {
+        fields.join(&self.delimiter.to_string())
+    }
+}
+
+/// Splits CSV text into records of fields, honoring quotes (which may
+/// span lines). Comma or semicolon, whichever the header uses more.
+pub fn read_csv(text: &str) -> Option<CsvTable> {
+    let header = text.trim_start_matches('\u{feff}').lines().find(|line| !line.starts_with("# ")).unwrap_or_default();
+    let delimiter = if header.matches(';').count() > header.matches(',').count() { ';' } else { ',' };
+    read_csv_dialect(text, &CsvDialect { delimiter, ..CsvDialect::default() })
+}
+
+/// Splits CSV text as written in `dialect`, after any leading "# " note
+/// lines. Without a header row the columns are called "Column 1",
+/// "Column 2" and so on.
+pub fn read_csv_dialect(text: &str, dialect: &CsvDialect) -> Option<CsvTable> {
+    let mut text = text.trim_start_matches('\u{feff}');
+    let (delimiter, quote) = (dialect.delimiter, dialect.quote);
+    // Notes such as the exercise filter of an export
+    let mut line = 1;
+    while text.starts_with("# ") {
+        text = text.split_once('\n').map_or("", |(_, rest)| rest);
+        line += 1;
+    }
+
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut raw = String::new();
+    let mut start = line;
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c == quote && quoted && chars.peek() == Some(&quote) => {
+                chars.next();
+                field.push(quote);
+                raw.push(quote);
+                raw.push(quote);
+                continue;
+            }
+            c if c == quote => quoted = !quoted,
+            c if c == delimiter && !quoted => fields.push(std::mem::take(&mut field)),
+            '\r' if !quoted => continue,
+            '\n' if !quoted => {
+                fields.push(std::mem::take(&mut field));
+                if fields.iter().any(|f| !f.is_empty()) {
+                    records.push((start, std::mem::take(&mut raw), std::mem::take(&mut fields)));
+                }
+                fields.clear();
+                raw.clear();
+                line += 1;
+                start = line;
+                continue;
+            }
+            c => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+        raw.push(c);
+    }
+    fields.push(field);
+    if fields.iter().any(|f| !f.is_empty()) {
+        records.push((start, raw, fields));
+    }
+
+    if !dialect.header {
+        let width = records.iter().map(|(_, _, fields)| fields.len()).max()?;
+        let columns: Vec<String> = (1..=width).map(|n| format!("Column {n}")).collect();
+        return Some(CsvTable {
+            header: dialect.join(&columns),
+            delimiter,
+            columns,
+            records,
+        });
+    }
+    let header = text.lines().next().unwrap_or_default().to_string();
+    let mut records = records.into_iter();
+    let (_, _, columns) = records.next()?;
+    Some(CsvTable {
+        header,
+        delimiter,
+        columns,
+        records: records.collect(),
+    })
+}
+
+/// Quotes a CSV field when it needs it.
+pub fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// A positive number, `None` for an empty or zero field. Accepts a
+/// decimal comma, as some locales export them.
+pub fn parse_number(text: &str) -> Result<Option<f64>, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    match text.replace(',', ".").parse::<f64>() {
+        Ok(value) if value.is_finite() && value >= 0.0 => Ok((value > 0.0).then_some(value)),
+        _ => Err(format!("not a number: {text:?}")),
+    }
+}
+
+/// A UUID in the form sets are given them, lowercased; `None` for an empty
+/// field.
+pub fn parse_uuid(text: &str) -> Result<Option<String>, String> {
+    let text = text.trim().to_lowercase();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    let valid = text.len() == 36
+        && text.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        });
+    if !valid {
+        return Err(format!("not a UUID: {text:?}"));
+    }
+    Ok(Some(text))
+}
+
+pub fn to_kg(weight: f64, unit: WeightUnit) -> f64 {
+    weight * unit.kg_per_unit()
+}
+
+pub fn read_file(path: &Path) -> Result<String> {
+    fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))
+}
+
+/// This tracker's exercise for a name from another app: the configured
+/// mapping, else an existing exercise with the same name ignoring case.
+pub fn known_exercise(name: &str, exercises: &[Exercise], config: &ImportConfig) -> Option<String> {
+    config.exercises.get(name).cloned().or_else(|| {
+        exercises
+            .iter()
+            .find(|e| e.name.eq_ignore_ascii_case(name))
+            .map(|e| e.name.clone())
+    })
+}
+
+/// Exercise names for every name in `sets`. Names without a mapping are
+/// asked about on the terminal when there is one, and otherwise imported
+/// under their own name as new exercises.
+pub fn map_exercises(
+    sets: &[ImportSet],
+    exercises: &[Exercise],
+    config: &ImportConfig,
+    source: &str,
+) -> Result<HashMap<String, String>> {
+    let interactive = io::stdin().is_terminal();
+    let mut mapping = HashMap::new();
+    for set in sets {
+        if mapping.contains_key(&set.exercise) {
+            continue;
+        }
+        let name = match known_exercise(&set.exercise, exercises, config) {
+            Some(name) => name,
+            None if interactive => {
+                print!("{source} exercise \"{}\" has no match. Import as [Enter keeps the name]: ", set.exercise);
+                io::stdout().flush()?;
+                let mut answer = String::new();
+                io::stdin().lock().read_line(&mut answer)?;
+                match answer.trim() {
+                    "" => set.exercise.clone(),
+                    answer => known_exercise(answer, exercises, config).unwrap_or_else(|| answer.to_string()),
+                }
+            }
+            None => set.exercise.clone(),
+        };
+        mapping.insert(set.exercise.clone(), name);
+    }
+    Ok(mapping)
+}
+
+/// Unit a new exercise gets from the first set logged for it.
+fn infer_unit(set: &ImportSet) -> &'static str {
+    if set.reps.is_some() {
+        "reps"
+    } else if set.distance.is_some() {
+        units::DISTANCE
+    } else if set.seconds.is_some() {
+        units::SECONDS
+    } else {
+        "reps"
+    }
+}
+
+/// The stored amount of a set in `unit`, or why there isn't one.
+fn amount(set: &ImportSet, unit: &str, distance: DistanceUnit) -> Result<i32, String> {
+    let value = match unit {
+        units::SECONDS => set.seconds.map(|s| s.to_string()),
+        units::DISTANCE => set.distance.map(|d| d.to_string()),
+        _ => set.reps.map(|r| r.to_string()),
+    };
+    value
+        .and_then(|value| units::parse_amount(&value, unit, distance))
+        .ok_or_else(|| format!("no {unit} value for {}", set.exercise))
+}
+
+impl Database {
+    /// Adds the sets under their mapped exercise names in one transaction.
+    /// Sets whose amount doesn't fit the exercise's unit are rejected, and
+    /// sets already logged are skipped, so importing twice is harmless: a
+    /// set with a UUID is already logged when any set here has it, even one
+    /// edited or trashed since, and one without when a set has the same
+    /// exercise, amount and time.
+    pub fn import_sets(
+        &self,
+        sets: &[ImportSet],
+        mapping: &HashMap<String, String>,
+        distance: DistanceUnit,
+        source: &str,
+    ) -> Result<ImportReport> {
+        self.run_import(sets, mapping, distance, source, true)
+    }
+
+    /// What `import_sets` would do, without writing anything: the same
+    /// import runs and its transaction is rolled back.
+    pub fn preview_import(&self, sets: &[ImportSet], mapping: &HashMap<String, String>, distance: DistanceUnit) -> Result<ImportReport> {
+        self.run_import(sets, mapping, distance, "preview", false)
+    }
+
+    fn run_import(
+        &self,
+        sets: &[ImportSet],
+        mapping: &HashMap<String, String>,
+        distance: DistanceUnit,
+        source: &str,
+        commit: bool,
+    ) -> Result<ImportReport> {
+        let exercises = self.get_exercises()?;
+        let mut report = ImportReport::default();
+        let recorded_at = Local::now().format(dates::TIMESTAMP_FORMAT).to_string();
+        let last_id = self.last_workout_id()?;
+        let tx = self.conn.unchecked_transaction()?;
+        for set in sets {
+            let name = mapping.get(&set.exercise).unwrap_or(&set.exercise);
+            let unit = match exercises.iter().find(|e| e.name == *name) {
+                Some(exercise) => exercise.unit.clone(),
+                None => match report.exercises.iter().find(|e| e.name == *name) {
+                    Some(imported) => imported.unit.clone(),
+                    None => infer_unit(set).to_string(),
+                },
+            };
+            let mut row = ImportRow {
+                line: set.line,
+                at: set.at,
+                exercise: name.clone(),
+                unit: unit.clone(),
+                count: None,
+                outcome: RowOutcome::New,
+            };
+            let count = match amount(set, &unit, distance) {
+                Ok(count) => count,
+                Err(reason) => {
+                    row.outcome = RowOutcome::Rejected(reason.clone());
+                    report.rows.push(row);
+                    report.rejects.push(Reject {
+                        line: set.line,
+                        raw: set.raw.clone(),
+                        reason,
+                    });
+                    continue;
+                }
+            };
+            row.count = Some(count);
+            let rpe = set.rpe.map(|r| r.round() as i32).filter(|r| (1..=10).contains(r));
+            let timestamp = set.at.format(dates::TIMESTAMP_FORMAT).to_string();
+
+            let exists: bool = match &set.uuid {
+                Some(uuid) => tx.query_row("SELECT EXISTS (SELECT 1 FROM workouts WHERE uuid = ?1)", [uuid], |row| row.get(0))?,
+                None => tx.query_row(
+                    "SELECT EXISTS (SELECT 1 FROM workouts
+                     WHERE exercise_type = ?1 AND count = ?2 AND timestamp = ?3 AND deleted_at IS NULL)",
+                    params![name, count, timestamp],
+                    |row| row.get(0),
+                )?,
+            };
+            if exists {
+                row.outcome = RowOutcome::Duplicate;
+                report.rows.push(row);
+                report.duplicates += 1;
+                continue;
+            }
+            report.rows.push(row);
+            let category = set
+                .category
+                .as_deref()
+                .map(str::to_lowercase)
+                .filter(|c| CATEGORIES.contains(&c.as_str()));
+            tx.execute(
+                "INSERT OR IGNORE INTO exercises (name, position, unit, category) VALUES (?1, 100, ?2, ?3)",
+                params![name, unit, category],
+            )?;
+            // Without a UUID the insert trigger gives the set a new one
+            tx.execute(
+                "INSERT INTO workouts (exercise_type, count, timestamp, rpe, weight, recorded_at, uuid)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![name, count, timestamp, rpe, set.weight, recorded_at, set.uuid],
+            )?;
+            match report.exercises.iter_mut().find(|e| e.name == *name) {
+                Some(imported) => {
+                    imported.sets += 1;
+                    imported.total += count as i64;
+                }
+                None => report.exercises.push(ImportedExercise {
+                    name: name.clone(),
+                    unit,
+                    sets: 1,
+                    total: count as i64,
+                }),
+            }
+        }
+        if !commit {
+            // Dropping the transaction rolls it back
+            return Ok(report);
+        }
+        let added = report.added();
+        if added > 0 {
+            self.log_change(None, "import", None, Some(&format!("{added} sets from {source}")))?;
+        }
+        tx.commit()?;
+        self.journal_inserted_after(last_id)?;
+        Ok(report)
+    }
+}
+
+/// Writes rejected records under the original header with the reason in
+/// an extra column, so they can be fixed and imported again.
+pub fn write_rejects(path: &Path, file: &ImportFile, rejects: &[Reject]) -> Result<()> {
+    let mut out = format!("{}{}Reject Reason\n", file.header, file.delimiter);
+    for reject in rejects {
+        out.push_str(&format!(
+            "{}{}\"line {}: {}\"\n",
+            reject.raw,
+            file.delimiter,
+            reject.line,
+            reject.reason.replace('"', "\"\"")
+        ));
+    }
+    fs::write(path, out).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Sample rows shown in the preview.
+const PREVIEW_ROWS: usize = 5;
+
+/// What an import would do, printed before asking to go ahead.
+fn print_preview(file: &ImportFile, preview: &ImportReport, distance: DistanceUnit, path: &Path) {
+    let rejected = file.rejects.len() + preview.rejects.len();
+    println!("Preview of {}", path.display());
+    println!("  Rows parsed: {}", file.sets.len() + file.rejects.len());
+    println!("  New: {}  Duplicates: {}  Invalid: {rejected}", preview.added(), preview.duplicates);
+    if let Some((first, last)) = preview.date_range() {
+        println!("  Dates: {} – {}", first.date(), last.date());
+    }
+    for imported in &preview.exercises {
+        let total = units::format_amount(imported.total, &imported.unit, distance);
+        println!("  {}: {} new, {} total", imported.name, imported.sets, total);
+    }
+    if preview.rows.is_empty() {
+        return;
+    }
+    let cells: Vec<[String; 5]> = preview
+        .rows
+        .iter()
+        .take(PREVIEW_ROWS)
+        .map(|row| {
+            let outcome = match &row.outcome {
+                RowOutcome::New => "new".to_string(),
+                RowOutcome::Duplicate => "duplicate".to_string(),
+                RowOutcome::Rejected(reason) => format!("invalid: {reason}"),
+            };
+            let amount = row.count.map_or("-".to_string(), |count| units::format_amount(count as i64, &row.unit, distance));
+            [row.line.to_string(), row.at.format("%Y-%m-%d %H:%M").to_string(), row.exercise.clone(), amount, outcome]
+        })
+        .collect();
+    let header = ["Line", "Time", "Exercise", "Amount", "Status"];
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| cells.iter().map(|row| row[i].chars().count()).chain([header[i].len()]).max().unwrap_or(0))
+        .collect();
+    let line = |fields: &[&str]| {
+        let padded: Vec<String> = fields.iter().zip(&widths).map(|(field, width)| format!("{field:width$}")).collect();
+        println!("  {}", padded.join("  ").trim_end());
+    };
+    println!();
+    line(&header);
+    for row in &cells {
+        line(&row.each_ref().map(String::as_str));
+    }
+    if preview.rows.len() > PREVIEW_ROWS {
+        println!("  … and {} more", preview.rows.len() - PREVIEW_ROWS);
+    }
+    println!();
+}
+
+/// Asks on the terminal whether to go ahead; anything but yes cancels, and
+/// without a terminal only `--yes` goes ahead.
+fn confirm(added: usize) -> Result<bool> {
+    if !io::stdin().is_terminal() {
+        println!("Not importing without a terminal to confirm on; pass --yes to import anyway");
+        return Ok(false);
+    }
+    print!("Import {added} new sets? [y/N] ");
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// Maps and previews a parsed export for the import commands, then, once
+/// confirmed (or with `yes`), imports it and reports a line per exercise,
+/// the duplicate count, and rejected rows written to `rejects` (default
+/// `<file>.rejects.csv`). Cancelling leaves the database untouched.
+#[allow(clippy::too_many_arguments)]
+pub fn run(
+    db: &Database,
+    file: &ImportFile,
+    config: &ImportConfig,
+    distance: DistanceUnit,
+    source: &str,
+    path: &Path,
+    rejects: Option<PathBuf>,
+    yes: bool,
+) -> Result<()> {
+    let mapping = map_exercises(&file.sets, &db.get_exercises()?, config, source)?;
+    let preview = db.preview_import(&file.sets, &mapping, distance)?;
+    print_preview(file, &preview, distance, path);
+    let report = if preview.added() == 0 {
+        println!("Nothing new to import");
+        preview
+    } else if yes || confirm(preview.added())? {
+        db.import_sets(&file.sets, &mapping, distance, source)?
+    } else {
+        println!("Cancelled; nothing was imported");
+        return Ok(());
+    };
+    for imported in &report.exercises {
+        let total = units::format_amount(imported.total, &imported.unit, distance);
+        println!("{}: {} sets, {} total", imported.name, imported.sets, total);
+    }
+    if report.duplicates > 0 {
+        println!("{} sets were already present", report.duplicates);
+    }
+    let rejected: Vec<_> = file.rejects.iter().chain(&report.rejects).cloned().collect();
+    if !rejected.is_empty() {
+        let rejects = rejects.unwrap_or_else(|| path.with_extension("rejects.csv"));
+        write_rejects(&rejects, file, &rejected)?;
+        eprintln!("Warning: {} rows could not be imported; see {}", rejected.len(), rejects.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,176 @@
+//! Per day and exercise totals, kept in the `daily_totals` table so the
+//! stats, heatmaps and sparklines read one row per day instead of every
+//! set. Triggers on `workouts` (migration 22) keep it current through
+//! every insert, edit, trash, restore and purge, whichever code path makes
+//! them; trashed sets don't count. The Data screen can rebuild it from
+//! scratch should it ever drift.
+
+use anyhow::Result;
+
+use crate::Database;
+
+/// The `daily_totals` columns worked out from the sets in `source`.
+fn aggregate(source: &str) -> String {
+    format!(
+        "SELECT substr(timestamp, 1, 10) AS day, exercise_type, COUNT(*) AS sets, SUM(count) AS total,
+                MAX(count) AS best, MIN(timestamp) AS first_at, COALESCE(SUM(weight * count), 0) AS volume,
+                SUM(CASE WHEN weight IS NULL THEN 0 ELSE count END) AS weighted_reps,
+                COALESCE(SUM(rpe), 0) AS rpe_sum, COUNT(rpe) AS rpe_sets
+         FROM {source} WHERE deleted_at IS NULL
+         GROUP BY day, exercise_type"
+    )
+}
+
+impl Database {
+    /// What the stats read per-day totals from in place of `daily_totals`:
+    /// the table itself, or the same columns worked out on the fly while
+    /// `stats_workouts` adds archived sets or filters by tag.
+    pub(crate) fn stats_daily_totals(&self, tag: Option<&str>) -> String {
+        match self.stats_workouts(tag).as_str() {
+            "workouts" => "daily_totals".to_string(),
+            source => format!("({})", aggregate(source)),
+        }
+    }
+
+    /// Works the summary out again from every set; returns its row count.
+    pub fn rebuild_daily_totals(&self) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM daily_totals", [])?;
+        let rows = tx.execute(&format!("INSERT INTO daily_totals {}", aggregate("workouts")), [])?;
+        tx.commit()?;
+        Ok(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{NewWorkout, WorkoutStore};
+
+    /// Small deterministic generator, so a failing sequence can be replayed.
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self, below: u64) -> u64 {
+            self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (self.0 >> 33) % below
+        }
+    }
+
+    type Row = (String, String, i64, i64, i64, String, f64, i64, i64, i64);
+
+    fn rows(db: &Database, sql: &str) -> Vec<Row> {
+        let mut stmt = db.conn.prepare(sql).unwrap();
+        stmt.query_map([], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+                row.get(5)?,
+                row.get(6)?,
+                row.get(7)?,
+                row.get(8)?,
+                row.get(9)?,
+            ))
+        })
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+    }
+
+    fn assert_matches_sets(db: &Database, step: usize) {
+        let summary = rows(db, "SELECT * FROM daily_totals ORDER BY day, exercise_type");
+        let truth = rows(db, &format!("{} ORDER BY day, exercise_type", aggregate("workouts")));
+        assert_eq!(summary.len(), truth.len(), "step {step}");
+        for (kept, expected) in summary.iter().zip(&truth) {
+            // Volume is a float added and taken away again, so it may drift a hair
+            assert!((kept.6 - expected.6).abs() < 1e-6, "step {step}: {kept:?} vs {expected:?}");
+            let (mut kept, mut expected) = (kept.clone(), expected.clone());
+            (kept.6, expected.6) = (0.0, 0.0);
+            assert_eq!(kept, expected, "step {step}");
+        }
+    }
+
+    fn random_set(rng: &mut Lcg) -> NewWorkout {
+        let exercises = ["squats", "push-ups", "plank"];
+        NewWorkout {
+            exercise_type: exercises[rng.next(3) as usize].to_string(),
+            count: rng.next(40) as i32 + 1,
+            rpe: (rng.next(2) == 0).then(|| rng.next(10) as i32 + 1),
+            weight: (rng.next(3) == 0).then(|| (rng.next(200) as f64) * 0.5),
+            timestamp: format!("2024-05-{:02} {:02}:{:02}:00", rng.next(6) + 1, rng.next(24), rng.next(60)),
+        }
+    }
+
+    fn random_id(db: &Database, rng: &mut Lcg) -> Option<i64> {
+        let ids: Vec<i64> =
+            db.conn.prepare("SELECT id FROM workouts").unwrap().query_map([], |row| row.get(0)).unwrap().map(Result::unwrap).collect();
+        (!ids.is_empty()).then(|| ids[rng.next(ids.len() as u64) as usize])
+    }
+
+    #[test]
+    fn the_summary_matches_the_sets_through_random_changes() {
+        let db = Database::new(":memory:").unwrap();
+        let mut rng = Lcg(0x5eed);
+        for step in 0..600 {
+            match rng.next(8) {
+                0 | 1 => {
+                    let set = random_set(&mut rng);
+                    db.add_workouts(&[set]).unwrap();
+                }
+                // An import, many sets at once
+                2 => {
+                    let sets: Vec<_> = (0..rng.next(20) + 1).map(|_| random_set(&mut rng)).collect();
+                    db.add_workouts(&sets).unwrap();
+                }
+                3 => {
+                    if let Some(id) = random_id(&db, &mut rng) {
+                        db.update_workout_count(id, rng.next(40) as i32 + 1).unwrap();
+                    }
+                }
+                // Moved to another day, or another exercise
+                4 => {
+                    if let Some(id) = random_id(&db, &mut rng) {
+                        let set = random_set(&mut rng);
+                        db.conn
+                            .execute(
+                                "UPDATE workouts SET timestamp = ?1, exercise_type = ?2, weight = ?3 WHERE id = ?4",
+                                rusqlite::params![set.timestamp, set.exercise_type, set.weight, id],
+                            )
+                            .unwrap();
+                    }
+                }
+                5 => {
+                    if let Some(id) = random_id(&db, &mut rng) {
+                        db.delete_workout(id).unwrap();
+                    }
+                }
+                6 => {
+                    if let Some(id) = random_id(&db, &mut rng) {
+                        db.restore_workout(id).unwrap();
+                    }
+                }
+                // Purged for good
+                _ => {
+                    if let Some(id) = random_id(&db, &mut rng) {
+                        db.conn.execute("DELETE FROM workouts WHERE id = ?1", [id]).unwrap();
+                    }
+                }
+            }
+            assert_matches_sets(&db, step);
+        }
+    }
+
+    #[test]
+    fn a_rebuild_repairs_a_drifted_summary() {
+        let db = Database::new(":memory:").unwrap();
+        db.add_workout("squats", 20, None, None).unwrap();
+        db.add_workout("squats", 30, Some(8), Some(40.0)).unwrap();
+        db.conn.execute("UPDATE daily_totals SET total = 999", []).unwrap();
+        db.conn.execute("INSERT INTO daily_totals VALUES ('2020-01-01', 'plank', 1, 1, 1, '2020-01-01 08:00:00', 0, 0, 0, 0)", []).unwrap();
+        assert_eq!(db.rebuild_daily_totals().unwrap(), 1);
+        assert_matches_sets(&db, 0);
+    }
+}
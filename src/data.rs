@@ -0,0 +1,440 @@
+use anyhow::Result;
+use chrono::{Datelike, Local, NaiveDate};
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Paragraph, Row, Table},
+    Frame,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::backup::backup_time;
+use crate::bulk_delete::{self, BulkDelete, BulkDeleteForm};
+use crate::encryption::{self, PassphraseForm};
+use crate::exercises::render_dialog;
+use crate::input::TextInput;
+use crate::setup_file;
+use crate::{dates, App, Screen};
+
+#[derive(Debug, Default)]
+pub struct DataState {
+    /// Index into the backups, newest first.
+    pub selected: usize,
+    pub confirm_restore: bool,
+    /// Size and row counts shown while asking to confirm a vacuum.
+    pub confirm_vacuum: Option<String>,
+    /// Path of the database file to merge in, while it's being typed.
+    pub merge_path: Option<TextInput>,
+    /// Where to write the setup file, while it's being typed.
+    pub setup_export: Option<TextInput>,
+    /// Setup file to import, while its path is being typed.
+    pub setup_import: Option<TextInput>,
+    /// New passphrase, while it's being typed.
+    pub passphrase: Option<PassphraseForm>,
+    /// Day to archive the sets before, while it's being typed.
+    pub archive_cutoff: Option<TextInput>,
+    /// That day and how many sets it would move, while asking to confirm.
+    pub confirm_archive: Option<(NaiveDate, i64)>,
+    /// Confirmation phrase for deleting everything, while it's being typed.
+    pub wipe: Option<TextInput>,
+    /// Range of sets to delete, while it's being typed.
+    pub bulk_delete: Option<BulkDeleteForm>,
+    /// The sets it matched, while asking to confirm.
+    pub confirm_bulk_delete: Option<BulkDelete>,
+    /// Sets the last bulk delete moved to the trash, until it's undone.
+    pub last_bulk_delete: Option<Vec<i64>>,
+}
+
+/// What has to be typed to delete everything, so no stray key can.
+const WIPE_PHRASE: &str = "DELETE";
+
+pub(crate) fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{bytes} B"),
+        1024..=1_048_575 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+impl App {
+    /// Backups, newest first, as the data screen lists them.
+    fn backups(&self) -> Result<Vec<PathBuf>> {
+        let mut backups = self.db.list_backups(&self.config.backup)?;
+        backups.reverse();
+        Ok(backups)
+    }
+
+    pub(crate) fn handle_data_input(&mut self, key: KeyCode) -> Result<bool> {
+        let backups = self.backups()?;
+
+        if self.data_screen.confirm_restore {
+            self.data_screen.confirm_restore = false;
+            if key == KeyCode::Char('y')
+                && let Some(backup) = backups.get(self.data_screen.selected)
+            {
+                self.restore_backup(backup);
+            }
+            return Ok(false);
+        }
+
+        if self.data_screen.confirm_vacuum.take().is_some() {
+            if key == KeyCode::Char('y') {
+                self.start_vacuum()?;
+            }
+            return Ok(false);
+        }
+
+        if let Some((cutoff, _)) = self.data_screen.confirm_archive.take() {
+            if key == KeyCode::Char('y') {
+                self.archive_sets(cutoff);
+            }
+            return Ok(false);
+        }
+
+        if let Some(input) = &mut self.data_screen.archive_cutoff {
+            match key {
+                KeyCode::Esc => self.data_screen.archive_cutoff = None,
+                KeyCode::Enter => match dates::parse_date(input.as_str().trim()) {
+                    Some(cutoff) => {
+                        self.data_screen.archive_cutoff = None;
+                        let count = self.db.count_before(cutoff)?;
+                        if count == 0 {
+                            self.message = Some(self.text.format("data.archive_nothing", &[("date", &cutoff)]));
+                        } else {
+                            self.data_screen.confirm_archive = Some((cutoff, count));
+                        }
+                    }
+                    None => self.message = Some(self.text.get("data.archive_bad_date").to_string()),
+                },
+                _ => {
+                    input.handle_key(key);
+                }
+            }
+            return Ok(false);
+        }
+
+        if let Some(delete) = self.data_screen.confirm_bulk_delete.take() {
+            if key == KeyCode::Char('y') {
+                self.bulk_delete(delete);
+            }
+            return Ok(false);
+        }
+
+        if self.data_screen.bulk_delete.is_some() {
+            return self.handle_bulk_delete_input(key);
+        }
+
+        if let Some(input) = &mut self.data_screen.wipe {
+            match key {
+                KeyCode::Esc => self.data_screen.wipe = None,
+                KeyCode::Enter => {
+                    let confirmed = input.as_str().trim() == WIPE_PHRASE;
+                    self.data_screen.wipe = None;
+                    if confirmed {
+                        self.wipe_data()?;
+                    } else {
+                        self.message = Some(self.text.format("data.wipe_mismatch", &[("phrase", &WIPE_PHRASE)]));
+                    }
+                }
+                _ => {
+                    input.handle_key(key);
+                }
+            }
+            return Ok(false);
+        }
+
+        if self.data_screen.passphrase.is_some() {
+            return self.handle_passphrase_input(key);
+        }
+
+        if let Some(input) = &mut self.data_screen.merge_path {
+            match key {
+                KeyCode::Esc => self.data_screen.merge_path = None,
+                KeyCode::Enter => {
+                    let path = PathBuf::from(input.as_str().trim());
+                    self.data_screen.merge_path = None;
+                    self.merge_database(&path)?;
+                }
+                _ => {
+                    input.handle_key(key);
+                }
+            }
+            return Ok(false);
+        }
+
+        if let Some(input) = &mut self.data_screen.setup_export {
+            match key {
+                KeyCode::Esc => self.data_screen.setup_export = None,
+                KeyCode::Enter => {
+                    let path = PathBuf::from(input.as_str().trim());
+                    self.data_screen.setup_export = None;
+                    self.export_setup(&path);
+                }
+                _ => {
+                    input.handle_key(key);
+                }
+            }
+            return Ok(false);
+        }
+
+        if let Some(input) = &mut self.data_screen.setup_import {
+            match key {
+                KeyCode::Esc => self.data_screen.setup_import = None,
+                KeyCode::Enter => {
+                    let path = PathBuf::from(input.as_str().trim());
+                    self.data_screen.setup_import = None;
+                    self.import_setup(&path)?;
+                }
+                _ => {
+                    input.handle_key(key);
+                }
+            }
+            return Ok(false);
+        }
+
+        match key {
+            KeyCode::Esc => self.screen = Screen::Main,
+            KeyCode::Up => self.data_screen.selected = self.data_screen.selected.saturating_sub(1),
+            KeyCode::Down if self.data_screen.selected + 1 < backups.len() => self.data_screen.selected += 1,
+            KeyCode::Char('b') => {
+                self.message = Some(match self.db.backup(&self.config.backup) {
+                    Ok(path) => self.text.format("data.backed_up", &[("path", &path.display())]),
+                    Err(e) => self.text.format("data.backup_failed", &[("error", &format!("{e:#}"))]),
+                });
+                self.data_screen.selected = 0;
+            }
+            KeyCode::Char('r') if !backups.is_empty() => self.data_screen.confirm_restore = true,
+            KeyCode::Char('x') => self.open_export(),
+            KeyCode::Char('c') => self.open_health_screen()?,
+            KeyCode::Char('t') => self.open_trash(),
+            KeyCode::Char('m') => self.data_screen.merge_path = Some(TextInput::default()),
+            KeyCode::Char('e') => {
+                let path = setup_file::default_path();
+                self.data_screen.setup_export = Some(TextInput::with_text(&path.to_string_lossy()));
+            }
+            KeyCode::Char('i') => self.data_screen.setup_import = Some(TextInput::default()),
+            KeyCode::Char('a') => {
+                let today = Local::now().date_naive();
+                let new_year = today.with_ordinal(1).unwrap_or(today);
+                self.data_screen.archive_cutoff = Some(TextInput::with_text(&new_year.to_string()));
+            }
+            KeyCode::Char('k') => self.data_screen.passphrase = Some(PassphraseForm::default()),
+            KeyCode::Char('d') => self.data_screen.bulk_delete = Some(BulkDeleteForm::default()),
+            KeyCode::Char('u') if self.data_screen.last_bulk_delete.is_some() => self.undo_bulk_delete(),
+            KeyCode::Char('w') => self.data_screen.wipe = Some(TextInput::default()),
+            KeyCode::Char('v') if self.vacuum.is_none() => {
+                self.data_screen.confirm_vacuum = Some(self.database_summary()?);
+            }
+            KeyCode::Char('s') => {
+                self.message = Some(match self.db.rebuild_daily_totals() {
+                    Ok(rows) => self.text.format("data.totals_rebuilt", &[("rows", &rows)]),
+                    Err(e) => self.text.format("data.totals_failed", &[("error", &format!("{e:#}"))]),
+                });
+                self.forget_history_days();
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    /// Merges another database file in; a file that isn't a tracker
+    /// database only produces a status message.
+    fn merge_database(&mut self, path: &Path) -> Result<()> {
+        self.message = Some(match self.db.merge_from(path) {
+            Ok(report) => {
+                self.reload_exercises()?;
+                self.text.format(
+                    "data.merged",
+                    &[("added", &report.added), ("skipped", &report.skipped), ("path", &path.display())],
+                )
+            }
+            Err(e) => self.text.format("data.merge_failed", &[("error", &format!("{e:#}"))]),
+        });
+        Ok(())
+    }
+
+    /// Moves the sets before `cutoff` into the archive file. Like a
+    /// restore, any failure only ends up in the status line.
+    fn archive_sets(&mut self, cutoff: NaiveDate) {
+        self.message = Some(match self.db.archive_before(cutoff, &self.config.backup) {
+            Ok(report) => {
+                self.reset_cached_state();
+                self.text.format(
+                    "data.archived",
+                    &[("moved", &report.moved), ("path", &report.path.display()), ("backup", &report.backup.display())],
+                )
+            }
+            Err(e) => self.text.format("data.archive_failed", &[("error", &format!("{e:#}"))]),
+        });
+        self.data_screen.selected = 0;
+    }
+
+    /// Deletes everything in this profile and starts over as on a first
+    /// run, onboarding included. A failure only ends up in the status line.
+    fn wipe_data(&mut self) -> Result<()> {
+        match self.db.wipe(&self.config.backup) {
+            Ok(backup) => {
+                self.exercises = self.db.get_exercises()?;
+                self.apply_settings()?;
+                self.reset_cached_state();
+                self.data_screen = DataState::default();
+                self.screen = Screen::Main;
+                self.mood_prompt = self.should_prompt_mood()?;
+                self.start_onboarding(false)?;
+                // The main screen drops statuses, so this one is a toast
+                self.show_toast(self.text.format("data.wiped", &[("backup", &backup.display())]));
+            }
+            Err(e) => self.message = Some(self.text.format("data.wipe_failed", &[("error", &format!("{e:#}"))])),
+        }
+        Ok(())
+    }
+
+    /// Restores `backup` and reloads everything cached from the database.
+    /// Failures (a corrupt file, a newer schema) only end up in the status
+    /// line; the live database is untouched unless the restore succeeded.
+    fn restore_backup(&mut self, backup: &Path) {
+        let restored = self
+            .db
+            .restore(backup, &self.config.backup)
+            .and_then(|snapshot| Ok((snapshot, self.db.get_exercises()?)));
+        self.message = Some(match restored {
+            Ok((snapshot, exercises)) => {
+                self.exercises = exercises;
+                self.reset_cached_state();
+                self.text.format("data.restored", &[("snapshot", &snapshot.display())])
+            }
+            Err(e) => self.text.format("data.restore_failed", &[("error", &format!("{e:#}"))]),
+        });
+        self.data_screen.selected = 0;
+    }
+}
+
+pub fn render_data_screen(f: &mut Frame, area: Rect, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(3)])
+        .split(area);
+
+    let backups = app.backups().unwrap_or_default();
+    let rows: Vec<Row> = backups
+        .iter()
+        .enumerate()
+        .map(|(i, path)| {
+            let taken = backup_time(path).map_or_else(
+                || path.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+                |at| {
+                    let time = dates::format_time(at.time(), app.config.ui.time_format, true);
+                    format!("{} {}", app.display_date(at.date()), time)
+                },
+            );
+            let size = fs::metadata(path).map(|m| format_size(m.len())).unwrap_or_default();
+            let mut style = Style::default();
+            if i == app.data_screen.selected {
+                style = style.add_modifier(Modifier::BOLD | Modifier::REVERSED);
+            }
+            Row::new(vec![taken, size]).style(style)
+        })
+        .collect();
+
+    let title = app.text.format("data.title", &[("count", &backups.len())]);
+    let table = Table::new(rows, [Constraint::Percentage(60), Constraint::Min(0)])
+        .header(
+            Row::new(vec![app.text.get("data.taken"), app.text.get("data.size")])
+                .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(table, chunks[0]);
+
+    let status = app.message.as_deref().unwrap_or_else(|| {
+        if backups.is_empty() {
+            app.text.get("data.empty")
+        } else {
+            ""
+        }
+    });
+    let status = Paragraph::new(status)
+        .style(Style::default().fg(Color::Green))
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("common.status")));
+    f.render_widget(status, chunks[1]);
+
+    if app.data_screen.confirm_restore
+        && let Some(backup) = backups.get(app.data_screen.selected)
+    {
+        let name = backup.file_name().unwrap_or_default().to_string_lossy();
+        render_dialog(
+            f,
+            app.text.get("data.confirm_title"),
+            &app.text.format("data.confirm_restore", &[("backup", &name)]),
+        );
+    }
+
+    if let Some(summary) = &app.data_screen.confirm_vacuum {
+        render_dialog(
+            f,
+            app.text.get("data.vacuum_title"),
+            &app.text.format("data.vacuum_confirm", &[("summary", summary)]),
+        );
+    }
+
+    if let Some(input) = &app.data_screen.merge_path {
+        render_dialog(
+            f,
+            app.text.get("data.merge_title"),
+            &app.text.format("data.merge_field", &[("input", &input.as_str())]),
+        );
+    }
+
+    if let Some(input) = &app.data_screen.setup_export {
+        render_dialog(
+            f,
+            app.text.get("setup_file.export_title"),
+            &app.text.format("setup_file.export_field", &[("input", &input.as_str())]),
+        );
+    }
+
+    if let Some(input) = &app.data_screen.setup_import {
+        render_dialog(
+            f,
+            app.text.get("setup_file.import_title"),
+            &app.text.format("setup_file.import_field", &[("input", &input.as_str())]),
+        );
+    }
+
+    if let Some(input) = &app.data_screen.archive_cutoff {
+        render_dialog(
+            f,
+            app.text.get("data.archive_title"),
+            &app.text.format(
+                "data.archive_field",
+                &[("input", &input.as_str()), ("path", &app.db.archive_path().display())],
+            ),
+        );
+    }
+
+    if let Some((cutoff, count)) = &app.data_screen.confirm_archive {
+        render_dialog(
+            f,
+            app.text.get("data.archive_title"),
+            &app.text.format(
+                "data.archive_confirm",
+                &[("count", count), ("date", cutoff), ("path", &app.db.archive_path().display())],
+            ),
+        );
+    }
+
+    if let Some(input) = &app.data_screen.wipe {
+        render_dialog(
+            f,
+            app.text.get("data.wipe_title"),
+            &app.text.format("data.wipe_field", &[("phrase", &WIPE_PHRASE), ("input", &input.as_str())]),
+        );
+    }
+
+    bulk_delete::render_bulk_delete_dialog(f, app);
+    encryption::render_passphrase_dialog(f, app);
+
+}
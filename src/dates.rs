@@ -0,0 +1,195 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use serde::Deserialize;
+
+use crate::i18n::Messages;
+
+/// Storage format of workout timestamps; display formatting never changes it.
+pub const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// How dates are shown on screen. Storage and exports always stay ISO.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateFormat {
+    /// 2024-05-03
+    #[default]
+    Iso,
+    /// 03.05.2024
+    Dmy,
+    /// 05/03/2024
+    Mdy,
+    /// Fri, May 3
+    Short,
+    /// Today, Yesterday or the weekday for the last week, ISO before that
+    Relative,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+pub enum TimeFormat {
+    #[default]
+    #[serde(rename = "24h")]
+    H24,
+    #[serde(rename = "12h")]
+    H12,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WeekStart {
+    #[default]
+    Monday,
+    Sunday,
+}
+
+impl WeekStart {
+    fn weekday(self) -> Weekday {
+        match self {
+            WeekStart::Monday => Weekday::Mon,
+            WeekStart::Sunday => Weekday::Sun,
+        }
+    }
+}
+
+/// First day of the week containing `date`. Every weekly grouping goes
+/// through this so the boundary is the same on every screen.
+pub fn week_start(date: NaiveDate, start: WeekStart) -> NaiveDate {
+    let offset = date.weekday().days_since(start.weekday());
+    date - Duration::days(offset as i64)
+}
+
+pub fn parse_date(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+pub fn parse_timestamp(timestamp: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()
+}
+
+/// Time of day in the configured clock style. All rendered times go
+/// through here rather than formatting `%H:%M` themselves.
+pub fn format_time(time: NaiveTime, format: TimeFormat, seconds: bool) -> String {
+    let pattern = match (format, seconds) {
+        (TimeFormat::H24, true) => "%H:%M:%S",
+        (TimeFormat::H24, false) => "%H:%M",
+        (TimeFormat::H12, true) => "%-I:%M:%S %P",
+        (TimeFormat::H12, false) => "%-I:%M %P",
+    };
+    time.format(pattern).to_string()
+}
+
+/// A date in the configured display style. `today` anchors the relative
+/// style and decides whether the short style needs a year.
+pub fn format_date(date: NaiveDate, format: DateFormat, today: NaiveDate, text: &Messages) -> String {
+    match format {
+        DateFormat::Iso => date.format("%Y-%m-%d").to_string(),
+        DateFormat::Dmy => date.format("%d.%m.%Y").to_string(),
+        DateFormat::Mdy => date.format("%m/%d/%Y").to_string(),
+        DateFormat::Short if date.year() == today.year() => date.format("%a, %b %-d").to_string(),
+        DateFormat::Short => date.format("%a, %b %-d %Y").to_string(),
+        DateFormat::Relative => match (today - date).num_days() {
+            0 => text.get("date.today").to_string(),
+            1 => text.get("date.yesterday").to_string(),
+            2..=6 => weekday_name(date.weekday(), text).to_string(),
+            _ => date.format("%Y-%m-%d").to_string(),
+        },
+    }
+}
+
+/// "today at 14:05", "yesterday at 9:30 am", or the date for anything older.
+pub fn format_relative(
+    at: NaiveDateTime,
+    now: NaiveDateTime,
+    date: DateFormat,
+    time: TimeFormat,
+    text: &Messages,
+) -> String {
+    let clock = format_time(at.time(), time, false);
+    match (now.date() - at.date()).num_days() {
+        0 => text.format("date.today_at", &[("time", &clock)]),
+        1 => text.format("date.yesterday_at", &[("time", &clock)]),
+        _ => {
+            let day = format_date(at.date(), date, now.date(), text);
+            text.format("date.on_at", &[("date", &day), ("time", &clock)])
+        }
+    }
+}
+
+pub(crate) fn weekday_name(day: Weekday, text: &Messages) -> &'static str {
+    let key = ["weekday.0", "weekday.1", "weekday.2", "weekday.3", "weekday.4", "weekday.5", "weekday.6"]
+        [day.num_days_from_monday() as usize];
+    text.get(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn time(s: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(s, "%H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn midnight_and_noon_in_both_clock_styles() {
+        assert_eq!(format_time(time("00:00:00"), TimeFormat::H24, true), "00:00:00");
+        assert_eq!(format_time(time("00:00:00"), TimeFormat::H12, true), "12:00:00 am");
+        assert_eq!(format_time(time("12:00:00"), TimeFormat::H24, false), "12:00");
+        assert_eq!(format_time(time("12:00:00"), TimeFormat::H12, false), "12:00 pm");
+        assert_eq!(format_time(time("23:59:59"), TimeFormat::H12, true), "11:59:59 pm");
+    }
+
+    #[test]
+    fn relative_time_uses_the_clock_style() {
+        let now = parse_timestamp("2024-05-06 09:00:00").unwrap();
+        let today = parse_timestamp("2024-05-06 00:15:00").unwrap();
+        let yesterday = parse_timestamp("2024-05-05 12:30:00").unwrap();
+        let (iso, en) = (DateFormat::Iso, &Messages::default());
+        assert_eq!(format_relative(today, now, iso, TimeFormat::H12, en), "today at 12:15 am");
+        assert_eq!(format_relative(yesterday, now, iso, TimeFormat::H24, en), "yesterday at 12:30");
+        assert_eq!(format_relative(yesterday, now, iso, TimeFormat::H12, en), "yesterday at 12:30 pm");
+    }
+
+    #[test]
+    fn display_date_formats() {
+        let (today, en) = (date("2024-05-06"), &Messages::default());
+        let friday = date("2024-05-03");
+        assert_eq!(format_date(friday, DateFormat::Iso, today, en), "2024-05-03");
+        assert_eq!(format_date(friday, DateFormat::Dmy, today, en), "03.05.2024");
+        assert_eq!(format_date(friday, DateFormat::Mdy, today, en), "05/03/2024");
+        assert_eq!(format_date(friday, DateFormat::Short, today, en), "Fri, May 3");
+        assert_eq!(format_date(date("2023-05-03"), DateFormat::Short, today, en), "Wed, May 3 2023");
+    }
+
+    #[test]
+    fn relative_dates_fall_back_to_iso_after_a_week() {
+        let (today, en) = (date("2024-05-06"), &Messages::default());
+        assert_eq!(format_date(today, DateFormat::Relative, today, en), "Today");
+        assert_eq!(format_date(date("2024-05-05"), DateFormat::Relative, today, en), "Yesterday");
+        assert_eq!(format_date(date("2024-05-03"), DateFormat::Relative, today, en), "Friday");
+        assert_eq!(format_date(date("2024-04-29"), DateFormat::Relative, today, en), "2024-04-29");
+    }
+
+    // 2024-05-05 is a Sunday, 2024-05-06 the following Monday.
+
+    #[test]
+    fn monday_weeks_split_between_sunday_and_monday() {
+        assert_eq!(week_start(date("2024-05-05"), WeekStart::Monday), date("2024-04-29"));
+        assert_eq!(week_start(date("2024-05-06"), WeekStart::Monday), date("2024-05-06"));
+        assert_eq!(week_start(date("2024-05-12"), WeekStart::Monday), date("2024-05-06"));
+    }
+
+    #[test]
+    fn sunday_weeks_split_between_saturday_and_sunday() {
+        assert_eq!(week_start(date("2024-05-04"), WeekStart::Sunday), date("2024-04-28"));
+        assert_eq!(week_start(date("2024-05-05"), WeekStart::Sunday), date("2024-05-05"));
+        assert_eq!(week_start(date("2024-05-06"), WeekStart::Sunday), date("2024-05-05"));
+    }
+
+    #[test]
+    fn week_start_crosses_month_and_year_boundaries() {
+        assert_eq!(week_start(date("2025-01-01"), WeekStart::Monday), date("2024-12-30"));
+        assert_eq!(week_start(date("2025-01-01"), WeekStart::Sunday), date("2024-12-29"));
+    }
+}
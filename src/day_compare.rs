@@ -0,0 +1,144 @@
+//! Two history days side by side: mark one date, pick another, and each
+//! exercise's sets and totals on both are listed with the change between
+//! them. Exercises done on only one of the days get an empty column.
+
+use anyhow::Result;
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    text::Span,
+    widgets::{Block, Borders, Cell, Row, Table},
+    Frame,
+};
+use std::cmp::Ordering;
+
+use crate::store::WorkoutStore;
+use crate::{App, WorkoutRecord};
+
+/// One exercise's sets on the two compared days.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExerciseComparison {
+    pub exercise: String,
+    pub first: Vec<i32>,
+    pub second: Vec<i32>,
+}
+
+impl ExerciseComparison {
+    pub fn first_total(&self) -> i64 {
+        self.first.iter().map(|count| *count as i64).sum()
+    }
+
+    pub fn second_total(&self) -> i64 {
+        self.second.iter().map(|count| *count as i64).sum()
+    }
+
+    /// How far the second day's total is from the first's.
+    pub fn delta(&self) -> i64 {
+        self.second_total() - self.first_total()
+    }
+}
+
+/// A row per exercise done on either day, in the order they were first
+/// done on the first day and then the second.
+pub fn compare_days(first: &[WorkoutRecord], second: &[WorkoutRecord]) -> Vec<ExerciseComparison> {
+    let mut rows: Vec<ExerciseComparison> = Vec::new();
+    for (records, on_second) in [(first, false), (second, true)] {
+        for record in records {
+            let index = match rows.iter().position(|row| row.exercise == record.exercise_type) {
+                Some(index) => index,
+                None => {
+                    rows.push(ExerciseComparison { exercise: record.exercise_type.clone(), first: Vec::new(), second: Vec::new() });
+                    rows.len() - 1
+                }
+            };
+            let sets = if on_second { &mut rows[index].second } else { &mut rows[index].first };
+            sets.push(record.count);
+        }
+    }
+    rows
+}
+
+impl App {
+    /// Marks the highlighted date to compare against, or unmarks it.
+    pub(crate) fn toggle_history_mark(&mut self, date: String) {
+        if self.history_mark.as_deref() == Some(date.as_str()) {
+            self.history_mark = None;
+            self.message = None;
+        } else {
+            self.message = Some(self.text.format("day_compare.marked", &[("date", &self.display_date_str(&date))]));
+            self.history_mark = Some(date);
+        }
+    }
+
+    /// Opens the comparison of the marked date with `date`.
+    pub(crate) fn open_day_compare(&mut self, date: String) {
+        match &self.history_mark {
+            None => self.message = Some(self.text.get("day_compare.no_mark").to_string()),
+            Some(mark) if *mark == date => self.message = Some(self.text.get("day_compare.same_date").to_string()),
+            Some(mark) => {
+                self.history_compare = Some((mark.clone(), date));
+                self.message = None;
+            }
+        }
+    }
+
+    pub(crate) fn handle_day_compare_input(&mut self, key: KeyCode) -> Result<bool> {
+        if key == KeyCode::Esc {
+            self.history_compare = None;
+            self.history_mark = None;
+        }
+        Ok(false)
+    }
+}
+
+/// `+n`/`-n` in the exercise's unit, or nothing when unchanged.
+fn format_delta(app: &App, exercise: &str, delta: i64) -> String {
+    let amount = app.format_count(exercise, delta.abs());
+    match delta.cmp(&0) {
+        Ordering::Greater => format!("+{amount}"),
+        Ordering::Less => format!("-{amount}"),
+        Ordering::Equal => String::new(),
+    }
+}
+
+/// Sets joined up with their total, or a dash when there were none.
+fn format_sets(app: &App, exercise: &str, sets: &[i32]) -> String {
+    if sets.is_empty() {
+        return "-".to_string();
+    }
+    let counts: Vec<String> = sets.iter().map(|count| app.format_count(exercise, *count as i64)).collect();
+    let total = app.format_count(exercise, sets.iter().map(|count| *count as i64).sum());
+    if sets.len() == 1 { total } else { format!("{} = {total}", counts.join(", ")) }
+}
+
+pub fn render_day_compare(f: &mut Frame, area: Rect, app: &App, first: &str, second: &str) {
+    let first_records = app.db.get_workouts_by_date(first).unwrap_or_default();
+    let second_records = app.db.get_workouts_by_date(second).unwrap_or_default();
+    let rows: Vec<Row> = compare_days(&first_records, &second_records)
+        .iter()
+        .map(|row| {
+            let delta = row.delta();
+            let color = match delta.cmp(&0) {
+                Ordering::Greater => Color::Green,
+                Ordering::Less => Color::Red,
+                Ordering::Equal => Color::DarkGray,
+            };
+            Row::new(vec![
+                Cell::from(Span::styled(row.exercise.clone(), Style::default().fg(app.exercise_color(&row.exercise)))),
+                Cell::from(format_sets(app, &row.exercise, &row.first)),
+                Cell::from(format_sets(app, &row.exercise, &row.second)),
+                Cell::from(Span::styled(format_delta(app, &row.exercise, delta), Style::default().fg(color))),
+            ])
+        })
+        .collect();
+    let (first, second) = (app.display_date_str(first), app.display_date_str(second));
+    let table = Table::new(rows, [Constraint::Percentage(25), Constraint::Percentage(30), Constraint::Percentage(30), Constraint::Percentage(15)])
+        .header(
+            Row::new(vec![app.text.get("day_compare.exercise"), first.as_str(), second.as_str(), app.text.get("day_compare.change")])
+                .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::default().borders(Borders::ALL).title(app.text.format("day_compare.title", &[("first", &first), ("second", &second)])));
+    f.render_widget(table, area);
+}
+
@@ -0,0 +1,117 @@
+//! A past day's sets copied as today's plan, for "do what I did last
+//! Tuesday". Today's sets check it off as a `Checklist`, and the main
+//! screen shows the sets still open as grayed placeholders. The plan is
+//! kept in its own table so it survives a restart during the day; one from
+//! an earlier day is ignored.
+
+use anyhow::Result;
+use chrono::Local;
+use rusqlite::params;
+
+use crate::checklist::Checklist;
+use crate::store::WorkoutStore;
+use crate::{App, Database, WorkoutRecord};
+
+impl Database {
+    /// The planned sets for `date` as exercise and amount, in the order
+    /// they were done on the copied day.
+    pub fn day_plan(&self, date: &str) -> Result<Vec<(String, i32)>> {
+        let mut stmt = self.conn.prepare("SELECT exercise, count FROM day_plan WHERE date = ?1 ORDER BY position")?;
+        let sets = stmt.query_map([date], |row| Ok((row.get(0)?, row.get(1)?)))?.collect::<Result<Vec<_>, _>>()?;
+        Ok(sets)
+    }
+
+    /// Replaces the plan, including any left over from an earlier day.
+    pub fn set_day_plan(&self, date: &str, sets: &[(String, i32)]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM day_plan", [])?;
+        for (position, (exercise, count)) in sets.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO day_plan (date, position, exercise, count) VALUES (?1, ?2, ?3, ?4)",
+                params![date, position as i64, exercise, count],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn clear_day_plan(&self) -> Result<()> {
+        self.conn.execute("DELETE FROM day_plan", [])?;
+        Ok(())
+    }
+}
+
+fn today() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
+
+impl App {
+    /// 'y' on a history day: its sets become today's plan.
+    pub(crate) fn plan_from_day(&mut self, date: &str) -> Result<()> {
+        let sets: Vec<(String, i32)> =
+            self.db.get_workouts_by_date(date)?.into_iter().map(|w| (w.exercise_type, w.count)).collect();
+        if sets.is_empty() {
+            return Ok(());
+        }
+        self.db.set_day_plan(&today(), &sets)?;
+        let day = self.display_date_str(date);
+        self.message = Some(self.text.format("day_plan.copied", &[("sets", &sets.len()), ("date", &day)]));
+        Ok(())
+    }
+
+    pub(crate) fn clear_day_plan(&mut self) -> Result<()> {
+        if !self.db.day_plan(&today())?.is_empty() {
+            self.db.clear_day_plan()?;
+            self.message = Some(self.text.get("day_plan.cleared").to_string());
+        }
+        Ok(())
+    }
+
+    /// Today's plan checked off by today's sets, if there is a plan.
+    pub(crate) fn day_checklist(&self, today_workouts: &[WorkoutRecord]) -> Option<Checklist> {
+        let plan = self.db.day_plan(&today()).unwrap_or_default();
+        if plan.is_empty() {
+            return None;
+        }
+        let done: Vec<(String, i32)> = today_workouts.iter().map(|w| (w.exercise_type.clone(), w.count)).collect();
+        Some(Checklist::build(&plan, &done))
+    }
+
+    /// After a set is logged: a toast when it was the one that completed
+    /// today's plan.
+    pub(crate) fn check_day_plan(&mut self) -> Result<()> {
+        let today_workouts = self.db.get_today_workouts()?;
+        let Some(checklist) = self.day_checklist(&today_workouts) else {
+            return Ok(());
+        };
+        let before = self.day_checklist(&today_workouts[..today_workouts.len().saturating_sub(1)]);
+        if checklist.is_complete() && !before.is_some_and(|before| before.is_complete()) {
+            self.show_toast(self.text.format("day_plan.complete", &[("sets", &checklist.items.len())]));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn planned(db: &Database) -> Vec<String> {
+        db.day_plan(&today()).unwrap().into_iter().map(|(exercise, _)| exercise).collect()
+    }
+
+    #[test]
+    fn the_plan_follows_renamed_merged_and_deleted_exercises() {
+        let db = Database::new(":memory:").unwrap();
+        for name in ["dips", "ring dips", "wall sits"] {
+            db.add_exercise(name).unwrap();
+        }
+        let plan = ["dips", "ring dips", "wall sits", "squats"].map(|name| (name.to_string(), 10));
+        db.set_day_plan(&today(), &plan).unwrap();
+
+        db.rename_exercise("dips", "bench dips").unwrap();
+        db.merge_exercise("ring dips", "bench dips").unwrap();
+        db.delete_exercise("wall sits").unwrap();
+        assert_eq!(planned(&db), ["bench dips", "bench dips", "squats"]);
+    }
+}
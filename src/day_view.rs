@@ -0,0 +1,133 @@
+//! Rows of the history's day view: the sets in the order they were logged,
+//! or grouped by exercise with a subtotal under each, and a total line
+//! either way. Grouping only reorders the records already fetched, and the
+//! highlighted entry indexes the reordered list, so editing and deleting
+//! still act on the set that's shown.
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::units;
+use crate::{App, Database, WorkoutRecord};
+
+/// Most bars a date in the history list gets, one per set.
+pub const MAX_SET_BARS: usize = 24;
+
+pub enum DayRow {
+    /// An exercise's name above its sets.
+    Header(String),
+    /// A set, by index into the records.
+    Set(usize),
+    /// Sum of the records in the range, under an exercise's sets.
+    Subtotal(Range<usize>),
+    Total,
+}
+
+/// The records grouped by exercise, exercises in the order of their first
+/// set and sets in the order they were logged.
+pub fn grouped(records: Vec<WorkoutRecord>) -> Vec<WorkoutRecord> {
+    let mut exercises: Vec<String> = Vec::new();
+    for record in &records {
+        if !exercises.contains(&record.exercise_type) {
+            exercises.push(record.exercise_type.clone());
+        }
+    }
+    let mut records = records;
+    // Stable, so each exercise's sets keep their order
+    records.sort_by_key(|record| exercises.iter().position(|name| *name == record.exercise_type));
+    records
+}
+
+/// Rows for `records`, which are already grouped when `grouped` is set.
+pub fn rows(records: &[WorkoutRecord], grouped: bool) -> Vec<DayRow> {
+    let mut rows = Vec::new();
+    if grouped {
+        let mut start = 0;
+        while start < records.len() {
+            let exercise = &records[start].exercise_type;
+            let end = records[start..].iter().position(|r| r.exercise_type != *exercise).map_or(records.len(), |n| start + n);
+            rows.push(DayRow::Header(exercise.clone()));
+            rows.extend((start..end).map(DayRow::Set));
+            rows.push(DayRow::Subtotal(start..end));
+            start = end;
+        }
+    } else {
+        rows.extend((0..records.len()).map(DayRow::Set));
+    }
+    if !records.is_empty() {
+        rows.push(DayRow::Total);
+    }
+    rows
+}
+
+/// The row showing the set at `entry`.
+pub fn row_of(rows: &[DayRow], entry: usize) -> Option<usize> {
+    rows.iter().position(|row| matches!(row, DayRow::Set(i) if *i == entry))
+}
+
+/// The set shown at `row`, or else the closest one below it, or above.
+pub fn entry_near(rows: &[DayRow], row: usize) -> Option<usize> {
+    let set = |row: &DayRow| match row {
+        DayRow::Set(i) => Some(*i),
+        _ => None,
+    };
+    rows.iter().skip(row).find_map(set).or_else(|| rows.iter().take(row).rev().find_map(set))
+}
+
+impl App {
+    /// The amounts of `records` added up per unit, reps first.
+    fn amount_totals(&self, records: &[WorkoutRecord]) -> String {
+        let mut totals: Vec<(&str, i64)> = Vec::new();
+        for record in records {
+            let unit = self.exercise_unit(&record.exercise_type);
+            match totals.iter_mut().find(|(u, _)| *u == unit) {
+                Some((_, total)) => *total += record.count as i64,
+                None => totals.push((unit, record.count as i64)),
+            }
+        }
+        totals.sort_by_key(|(unit, _)| *unit == units::SECONDS || *unit == units::DISTANCE);
+        totals
+            .iter()
+            .map(|(unit, total)| match *unit {
+                units::SECONDS | units::DISTANCE => units::format_amount(*total, unit, self.config.units.distance),
+                _ => self.text.format("history.reps", &[("reps", total)]),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Text of a subtotal or total row, from its message `key`.
+    pub(crate) fn day_total(&self, key: &str, records: &[WorkoutRecord]) -> String {
+        self.text.format(key, &[("sets", &records.len()), ("amounts", &self.amount_totals(records))])
+    }
+}
+
+impl Database {
+    /// Each day's set amounts in the order they were logged.
+    pub fn day_set_amounts(&self) -> Result<HashMap<String, Vec<i64>>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT substr(timestamp, 1, 10), count FROM workouts
+             WHERE deleted_at IS NULL
+             ORDER BY timestamp, id",
+        )?;
+        let mut days: HashMap<String, Vec<i64>> = HashMap::new();
+        for row in stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))? {
+            let (day, count) = row?;
+            days.entry(day).or_default().push(count);
+        }
+        Ok(days)
+    }
+}
+
+impl App {
+    /// The day's set amounts for its bars in the date list, all days read
+    /// at once on first use like the heat.
+    pub(crate) fn day_sets(&self, date: &str) -> Vec<i64> {
+        let mut sets = self.history_sets.borrow_mut();
+        if sets.is_none() {
+            *sets = Some(self.db.day_set_amounts().unwrap_or_default());
+        }
+        sets.as_ref().and_then(|sets| sets.get(date).cloned()).unwrap_or_default()
+    }
+}
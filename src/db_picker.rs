@@ -0,0 +1,221 @@
+//! Choosing the data file at startup, for people keeping more than one.
+//! Every file opened in the UI is remembered in recent_databases.json next
+//! to config.toml, and the picker lists those, newest first, with when
+//! they were last used and how many sets they hold. It shows up with
+//! `--choose-db`, or when the configured file is missing but others were
+//! used before. A file that can't be opened only shows why on the picker.
+
+use anyhow::{bail, Result};
+use chrono::Local;
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::backup::check_database_file;
+use crate::config::CONFIG_PATH;
+use crate::exercises::render_dialog;
+use crate::i18n::Messages;
+use crate::input::TextInput;
+use crate::store::Backend;
+use crate::{before_ui, centered_rect, encryption, Database};
+
+const RECENT_FILE: &str = "recent_databases.json";
+/// Files remembered; older ones drop off.
+const MAX_RECENT: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentDatabase {
+    pub path: PathBuf,
+    pub backend: Backend,
+    /// `YYYY-MM-DD HH:MM:SS`, local time.
+    pub last_used: String,
+}
+
+fn recent_path() -> PathBuf {
+    Path::new(CONFIG_PATH).with_file_name(RECENT_FILE)
+}
+
+/// The remembered files, most recently used first; none if the list
+/// is missing or unreadable.
+pub fn recent() -> Vec<RecentDatabase> {
+    fs::read_to_string(recent_path()).ok().and_then(|text| serde_json::from_str(&text).ok()).unwrap_or_default()
+}
+
+/// Moves `path` to the top of the recent files.
+pub fn remember(path: &Path, backend: Backend) -> Result<()> {
+    let full = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut list = recent();
+    list.retain(|entry| entry.path != full);
+    list.insert(0, RecentDatabase { path: full, backend, last_used: Local::now().format("%Y-%m-%d %H:%M:%S").to_string() });
+    list.truncate(MAX_RECENT);
+    fs::write(recent_path(), serde_json::to_string_pretty(&list)?)?;
+    Ok(())
+}
+
+/// The backend a typed file name suggests, if its extension tells.
+fn backend_for(path: &Path) -> Option<Backend> {
+    match path.extension()?.to_str()? {
+        "jsonl" => Some(Backend::Jsonl),
+        "db" | "sqlite" | "sqlite3" => Some(Backend::Sqlite),
+        _ => None,
+    }
+}
+
+/// Sets in the file, or what stands in the way of counting them.
+fn describe(entry: &RecentDatabase, text: &Messages) -> String {
+    if !entry.path.exists() {
+        return text.get("db_picker.missing").to_string();
+    }
+    let count = match entry.backend {
+        Backend::Jsonl => Database::load_jsonl(&entry.path).and_then(|db| db.count_sets()).ok(),
+        Backend::Sqlite if encryption::is_encrypted(&entry.path) => return text.get("db_picker.encrypted").to_string(),
+        Backend::Sqlite => Connection::open_with_flags(&entry.path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .and_then(|conn| conn.query_row("SELECT COUNT(*) FROM workouts WHERE deleted_at IS NULL", [], |row| row.get(0)))
+            .ok(),
+    };
+    match count {
+        Some(count) => text.format("db_picker.sets", &[("count", &count)]),
+        None => text.get("db_picker.unreadable").to_string(),
+    }
+}
+
+/// Why `path` can't be opened as a `backend` data file, if it can't. A
+/// file that doesn't exist yet is fine: it's created.
+fn check(path: &Path, backend: Backend) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    match backend {
+        Backend::Jsonl => Database::load_jsonl(path).map(|_| ()),
+        // The passphrase is asked for after picking
+        Backend::Sqlite if encryption::is_encrypted(path) => Ok(()),
+        Backend::Sqlite => check_database_file(path, None),
+    }
+}
+
+impl Database {
+    fn count_sets(&self) -> Result<i64> {
+        Ok(self.conn.query_row("SELECT COUNT(*) FROM workouts WHERE deleted_at IS NULL", [], |row| row.get(0))?)
+    }
+}
+
+struct Picker {
+    entries: Vec<RecentDatabase>,
+    /// Set counts, worked out once.
+    details: Vec<String>,
+    selected: usize,
+    /// A path being typed instead.
+    typing: Option<TextInput>,
+    error: Option<String>,
+}
+
+/// Asks which data file to open, on a screen shown before the main UI,
+/// starting from `current`. Esc quits.
+pub fn prompt_database(current: &Path, backend: Backend, text: &Messages) -> Result<(PathBuf, Backend)> {
+    let entries = recent();
+    let details = entries.iter().map(|entry| describe(entry, text)).collect();
+    let mut picker = Picker { entries, details, selected: 0, typing: None, error: None };
+    if picker.entries.is_empty() {
+        picker.typing = Some(TextInput::with_text(&current.to_string_lossy()));
+    }
+    before_ui(|terminal| loop {
+        terminal.draw(|f| render_picker(f, &picker, text))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        let chosen = if let Some(input) = &mut picker.typing {
+            match key.code {
+                KeyCode::Esc if picker.entries.is_empty() => bail!("no data file picked"),
+                KeyCode::Esc => {
+                    picker.typing = None;
+                    None
+                }
+                KeyCode::Enter if !input.as_str().trim().is_empty() => {
+                    let path = PathBuf::from(input.as_str().trim());
+                    let backend = backend_for(&path).unwrap_or(backend);
+                    Some((path, backend))
+                }
+                code => {
+                    input.handle_key(code);
+                    None
+                }
+            }
+        } else {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => bail!("no data file picked"),
+                KeyCode::Up => {
+                    picker.selected = picker.selected.saturating_sub(1);
+                    None
+                }
+                KeyCode::Down if picker.selected + 1 < picker.entries.len() => {
+                    picker.selected += 1;
+                    None
+                }
+                KeyCode::Char('n') => {
+                    picker.typing = Some(TextInput::default());
+                    None
+                }
+                KeyCode::Enter => picker.entries.get(picker.selected).map(|entry| (entry.path.clone(), entry.backend)),
+                _ => None,
+            }
+        };
+        let Some((path, backend)) = chosen else {
+            continue;
+        };
+        let opened = if picker.typing.is_none() && !path.exists() {
+            Err(anyhow::anyhow!("{} no longer exists", path.display()))
+        } else {
+            check(&path, backend)
+        };
+        match opened {
+            Ok(()) => return Ok((path, backend)),
+            Err(e) => picker.error = Some(text.format("db_picker.failed", &[("path", &path.display()), ("error", &format!("{e:#}"))])),
+        }
+    })
+}
+
+fn render_picker(f: &mut Frame, picker: &Picker, text: &Messages) {
+    let area = centered_rect(76, picker.entries.len() as u16 + 7, f.size());
+    f.render_widget(Clear, area);
+    let block = Block::default().borders(Borders::ALL).title(text.get("db_picker.title"));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(2), Constraint::Length(1)])
+        .split(inner);
+
+    let width = picker.entries.iter().map(|entry| entry.path.display().to_string().chars().count()).max().unwrap_or(0);
+    let items: Vec<ListItem> = picker
+        .entries
+        .iter()
+        .zip(&picker.details)
+        .map(|(entry, detail)| {
+            ListItem::new(vec![Line::from(vec![
+                Span::raw(format!("{:width$}", entry.path.display().to_string())),
+                Span::styled(format!("  {}  {detail}", entry.last_used), Style::default().fg(Color::DarkGray)),
+            ])])
+        })
+        .collect();
+    let list = List::new(items).highlight_style(Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED));
+    f.render_stateful_widget(list, chunks[0], &mut ListState::default().with_selected(Some(picker.selected)));
+    if let Some(error) = &picker.error {
+        f.render_widget(Paragraph::new(error.as_str()).wrap(Wrap { trim: true }).style(Style::default().fg(Color::Red)), chunks[1]);
+    }
+    let hint = Paragraph::new(text.get("db_picker.hint")).style(Style::default().fg(Color::DarkGray));
+    f.render_widget(hint, chunks[2]);
+
+    if let Some(input) = &picker.typing {
+        let error = picker.error.as_deref().unwrap_or_default();
+        render_dialog(f, text.get("db_picker.other"), &text.format("db_picker.field", &[("input", &input.as_str()), ("error", &error)]));
+    }
+}
@@ -0,0 +1,235 @@
+//! Notices when something other than this session changes the data file,
+//! like a sync tool dropping a newer copy in its place or a second
+//! instance logging a set. The file is looked at from the tick loop: a new
+//! inode means it was replaced, and for SQLite `PRAGMA data_version` moves
+//! when another connection commits. A JSON-lines file is rewritten on every
+//! save, so its own saves are told apart by `saved_changes`. A banner then
+//! offers to reopen the file; a file that's gone stays on the banner until
+//! it's back, and F5 tries again.
+
+use anyhow::Result;
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::Line,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::encryption;
+use crate::exercises::ExercisesMode;
+use crate::store::Backend;
+use crate::{App, Database, Screen};
+
+/// How often the file is looked at.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DbChange {
+    /// Written or replaced by something else.
+    Changed,
+    /// No longer there.
+    Missing,
+}
+
+/// What identifies the file on disk. The modification time and size only
+/// count for JSON-lines files: SQLite's own writes move them too.
+#[derive(Debug, Clone, PartialEq)]
+struct Stamp {
+    id: Option<u64>,
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+fn stamp(path: &Path, jsonl: bool) -> Option<Stamp> {
+    let meta = fs::metadata(path).ok()?;
+    #[cfg(unix)]
+    let id = Some(std::os::unix::fs::MetadataExt::ino(&meta));
+    #[cfg(not(unix))]
+    let id = meta
+        .created()
+        .ok()
+        .and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|since| since.as_nanos() as u64);
+    Some(if jsonl {
+        Stamp { id, modified: meta.modified().ok(), len: meta.len() }
+    } else {
+        Stamp { id, modified: None, len: 0 }
+    })
+}
+
+#[derive(Debug)]
+pub struct DbWatch {
+    path: PathBuf,
+    jsonl: bool,
+    stamp: Option<Stamp>,
+    data_version: Option<i64>,
+    saves: i64,
+    checked: Instant,
+    /// What was noticed, until the file is reopened.
+    pub change: Option<DbChange>,
+    /// Whether F5 was pressed once over unsaved input and the next one reloads.
+    pub confirm: bool,
+}
+
+impl DbWatch {
+    pub fn new(db: &Database) -> Self {
+        let path = db.file_path();
+        let jsonl = db.jsonl.is_some();
+        Self {
+            stamp: stamp(&path, jsonl),
+            path,
+            jsonl,
+            data_version: db.data_version(),
+            saves: db.saved_changes.get(),
+            checked: Instant::now(),
+            change: None,
+            confirm: false,
+        }
+    }
+
+    /// What changed since the file was opened or last saved from here, if
+    /// anything. In-memory databases have no file to watch.
+    fn check(&mut self, db: &Database) -> Option<DbChange> {
+        if self.path.as_os_str().is_empty() || self.checked.elapsed() < POLL_INTERVAL {
+            return None;
+        }
+        self.checked = Instant::now();
+        let Some(stamp) = stamp(&self.path, self.jsonl) else {
+            return Some(DbChange::Missing);
+        };
+        if self.jsonl && db.saved_changes.get() != self.saves {
+            self.stamp = Some(stamp);
+            self.saves = db.saved_changes.get();
+            return None;
+        }
+        (self.stamp.as_ref() != Some(&stamp) || db.data_version() != self.data_version).then_some(DbChange::Changed)
+    }
+}
+
+impl Database {
+    /// SQLite's count of commits made by other connections; `None` for
+    /// JSON-lines files, which are only ever written from here.
+    fn data_version(&self) -> Option<i64> {
+        if self.jsonl.is_some() {
+            return None;
+        }
+        self.conn.query_row("PRAGMA data_version", [], |row| row.get(0)).ok()
+    }
+}
+
+impl App {
+    pub(crate) fn check_database(&mut self) {
+        // The vacuum writes through a connection of its own
+        if self.vacuum.is_some() {
+            return;
+        }
+        // Another profile or a moved file is watched from scratch
+        if self.db.file_path() != self.db_watch.path {
+            self.watch_database();
+            return;
+        }
+        if self.db_watch.change == Some(DbChange::Changed) {
+            return;
+        }
+        match self.db_watch.check(&self.db) {
+            Some(change) => self.db_watch.change = Some(change),
+            // A file that came back is a changed one
+            None if self.db_watch.change == Some(DbChange::Missing) => self.db_watch.change = Some(DbChange::Changed),
+            None => {}
+        }
+    }
+
+    /// Starts over from the file as it is now, after this session replaced
+    /// it itself.
+    pub(crate) fn watch_database(&mut self) {
+        self.db_watch = DbWatch::new(&self.db);
+    }
+
+    /// Whether something typed but not saved yet would be lost by reopening
+    /// the file.
+    fn has_pending_input(&self) -> bool {
+        let data = &self.data_screen;
+        matches!(self.screen, Screen::AddWorkout)
+            || self.history_edit.is_some()
+            || self.history_note.is_some()
+            || self.exercise_detail.goal.is_some()
+            || self.goals_screen.editing.is_some()
+            || self.goals_screen.deadline.is_some()
+            || !matches!(self.exercises_screen.mode, ExercisesMode::Browse)
+            || !self.measurements.value.as_str().is_empty()
+            || self.settings_screen.editing.is_some()
+            || self.palette.as_ref().is_some_and(|palette| palette.prompt.is_some())
+            || data.merge_path.is_some()
+            || data.setup_export.is_some()
+            || data.setup_import.is_some()
+            || data.passphrase.is_some()
+            || data.archive_cutoff.is_some()
+            || data.wipe.is_some()
+            || data.bulk_delete.is_some()
+    }
+
+    /// F5 on the banner: reopens the file, after a warning when that would
+    /// throw away unsaved input.
+    pub(crate) fn handle_reload_key(&mut self) {
+        // Reopening a file that's gone would start an empty one
+        if self.db_watch.change == Some(DbChange::Missing) && !self.db_watch.path.exists() {
+            return;
+        }
+        if !self.db_watch.confirm && self.has_pending_input() {
+            self.db_watch.confirm = true;
+            return;
+        }
+        if let Err(e) = self.reload_database() {
+            self.db_watch.confirm = false;
+            self.show_warning(self.text.format("db_watch.reload_failed", &[("error", &format!("{e:#}"))]));
+        }
+    }
+
+    /// Opens the data file again and drops everything read from the old one.
+    fn reload_database(&mut self) -> Result<()> {
+        let path = self.db_watch.path.clone();
+        let backend = if self.db_watch.jsonl { Backend::Jsonl } else { Backend::Sqlite };
+        let mut db = match &self.db.passphrase {
+            Some(passphrase) if encryption::is_encrypted(&path) => Database::unlock(&path, passphrase.clone(), self.read_only)?
+                .ok_or_else(|| anyhow::anyhow!("the passphrase no longer unlocks {}", path.display()))?,
+            _ if self.read_only => Database::open_read_only(backend, &path)?,
+            _ => Database::open(backend, &path)?,
+        };
+        if self.config.journal.enabled && !self.read_only {
+            let journal = self.profiles.profiles.journal_path(&self.profiles.active, &db, &self.config.journal);
+            db.start_journal(journal);
+        }
+        self.db = db;
+        self.exercises = self.db.get_exercises()?;
+        self.apply_settings()?;
+        self.reset_cached_state();
+        self.mood_prompt = self.should_prompt_mood()?;
+        self.screen = Screen::Main;
+        self.palette = None;
+        self.history_edit = None;
+        self.history_note = None;
+        self.exercise_detail = Default::default();
+        self.settings_screen = Default::default();
+        self.data_screen = Default::default();
+        self.watch_database();
+        self.show_toast(self.text.get("db_watch.reloaded").to_string());
+        Ok(())
+    }
+}
+
+/// The banner line, while there's something to say.
+pub fn banner_line(app: &App) -> Option<Line<'static>> {
+    let text = match app.db_watch.change? {
+        DbChange::Missing => app.text.format("db_watch.missing", &[("path", &app.db_watch.path.display())]),
+        DbChange::Changed if app.db_watch.confirm => app.text.get("db_watch.confirm").to_string(),
+        DbChange::Changed => app.text.get("db_watch.changed").to_string(),
+    };
+    let style = match app.db_watch.change? {
+        DbChange::Missing => Style::default().fg(Color::White).bg(Color::Red),
+        DbChange::Changed => Style::default().fg(Color::Black).bg(Color::LightMagenta),
+    };
+    Some(Line::styled(format!("⟳ {text}"), style.add_modifier(Modifier::BOLD)))
+}
@@ -0,0 +1,374 @@
+//! Totals to reach by a date, like 500 push-ups by the end of the month,
+//! next to the daily goals. Sets from the day a goal is set up to its
+//! deadline count toward it, and the goals screen shows each as a gauge
+//! with the daily pace still needed against the pace so far. Once the
+//! deadline has passed the goal is closed as achieved or missed with its
+//! final total and listed under the past ones.
+
+use anyhow::Result;
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use rusqlite::params;
+
+use crate::dates;
+use crate::exercises;
+use crate::goals::progress_bar;
+use crate::input::{DateInput, NumericInput};
+use crate::{units, App, Database};
+
+/// Past goals listed at most.
+const HISTORY_ROWS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    Achieved,
+    Missed,
+}
+
+impl Outcome {
+    fn parse(text: &str) -> Option<Self> {
+        match text {
+            "achieved" => Some(Self::Achieved),
+            "missed" => Some(Self::Missed),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Achieved => "achieved",
+            Self::Missed => "missed",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DeadlineGoal {
+    pub id: i64,
+    pub exercise: String,
+    pub target: i64,
+    pub start: NaiveDate,
+    pub deadline: NaiveDate,
+    /// Set once the deadline has passed.
+    pub outcome: Option<Outcome>,
+    /// Amount logged toward it, frozen when it closed.
+    pub total: i64,
+}
+
+/// Daily amounts for a running goal, today counting as a day left.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pace {
+    pub needed: i64,
+    pub days_left: i64,
+    pub current: i64,
+}
+
+impl DeadlineGoal {
+    pub fn reached(&self) -> bool {
+        self.total >= self.target
+    }
+
+    pub fn pace(&self, today: NaiveDate) -> Pace {
+        let days_left = ((self.deadline - today).num_days() + 1).max(1);
+        let elapsed = ((today - self.start).num_days() + 1).max(1);
+        let remaining = (self.target - self.total).max(0);
+        Pace {
+            needed: (remaining + days_left - 1) / days_left,
+            days_left,
+            current: (self.total as f64 / elapsed as f64).round() as i64,
+        }
+    }
+}
+
+/// The new goal being typed on the goals screen.
+#[derive(Debug, Clone)]
+pub struct DeadlineForm {
+    pub target: NumericInput,
+    pub deadline: DateInput,
+    pub on_deadline: bool,
+}
+
+/// Last day of `date`'s month, the deadline a new goal starts with.
+fn month_end(date: NaiveDate) -> NaiveDate {
+    let (year, month) = if date.month() == 12 { (date.year() + 1, 1) } else { (date.year(), date.month() + 1) };
+    NaiveDate::from_ymd_opt(year, month, 1).map_or(date, |first| first - Duration::days(1))
+}
+
+// Sets of the goal's exercise between its start and its deadline
+const TOTAL: &str = "(SELECT COALESCE(SUM(d.total), 0) FROM daily_totals d
+     WHERE d.exercise_type = g.exercise AND d.day BETWEEN g.start_date AND g.deadline)";
+
+impl Database {
+    /// Running goals by deadline, then closed ones, latest first.
+    pub fn deadline_goals(&self) -> Result<Vec<DeadlineGoal>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT g.id, g.exercise, g.target, g.start_date, g.deadline, g.outcome, COALESCE(g.final_total, {TOTAL})
+             FROM deadline_goals g
+             ORDER BY g.outcome IS NOT NULL, CASE WHEN g.outcome IS NULL THEN g.deadline END, g.deadline DESC, g.id"
+        ))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, Option<String>>(5)?,
+                    row.get::<_, i64>(6)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(id, exercise, target, start, deadline, outcome, total)| {
+                Some(DeadlineGoal {
+                    id,
+                    exercise,
+                    target,
+                    start: dates::parse_date(&start)?,
+                    deadline: dates::parse_date(&deadline)?,
+                    outcome: outcome.as_deref().and_then(Outcome::parse),
+                    total,
+                })
+            })
+            .collect())
+    }
+
+    /// Sets up a goal for `exercise`, replacing the one it already had running.
+    pub fn set_deadline_goal(&self, exercise: &str, target: i64, start: NaiveDate, deadline: NaiveDate) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM deadline_goals WHERE exercise = ?1 AND outcome IS NULL", [exercise])?;
+        tx.execute(
+            "INSERT INTO deadline_goals (exercise, target, start_date, deadline) VALUES (?1, ?2, ?3, ?4)",
+            params![exercise, target, start.to_string(), deadline.to_string()],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn remove_deadline_goal(&self, exercise: &str) -> Result<bool> {
+        let removed = self.conn.execute("DELETE FROM deadline_goals WHERE exercise = ?1 AND outcome IS NULL", [exercise])?;
+        Ok(removed > 0)
+    }
+
+    /// Closes the running goals whose deadline is before `today`, keeping
+    /// their final total. Returns the ones closed.
+    pub fn close_deadline_goals(&self, today: NaiveDate) -> Result<Vec<DeadlineGoal>> {
+        let expired: Vec<DeadlineGoal> = self
+            .deadline_goals()?
+            .into_iter()
+            .filter(|goal| goal.outcome.is_none() && goal.deadline < today)
+            .collect();
+        let tx = self.conn.unchecked_transaction()?;
+        let mut closed = Vec::new();
+        for mut goal in expired {
+            let outcome = if goal.reached() { Outcome::Achieved } else { Outcome::Missed };
+            tx.execute(
+                "UPDATE deadline_goals SET outcome = ?2, final_total = ?3 WHERE id = ?1",
+                params![goal.id, outcome.as_str(), goal.total],
+            )?;
+            goal.outcome = Some(outcome);
+            closed.push(goal);
+        }
+        tx.commit()?;
+        Ok(closed)
+    }
+}
+
+impl App {
+    /// 'n' on the goals screen.
+    pub(crate) fn open_deadline_form(&mut self, exercise: &str) -> Result<()> {
+        let today = Local::now().date_naive();
+        let running = self.db.deadline_goals()?.into_iter().find(|goal| goal.exercise == exercise && goal.outcome.is_none());
+        let mut target = NumericInput::default();
+        target.set_decimal(units::is_decimal(self.exercise_unit(exercise)));
+        let deadline = match &running {
+            Some(goal) => {
+                target.set(units::input_text(goal.target, self.exercise_unit(exercise), self.config.units.distance));
+                goal.deadline
+            }
+            None => month_end(today),
+        };
+        self.goals_screen.deadline = Some(DeadlineForm { target, deadline: DateInput::with_date(deadline), on_deadline: false });
+        Ok(())
+    }
+
+    pub(crate) fn handle_deadline_form_input(&mut self, key: KeyCode, exercise: &str) -> Result<()> {
+        let Some(mut form) = self.goals_screen.deadline.take() else {
+            return Ok(());
+        };
+        match key {
+            KeyCode::Esc => {
+                self.message = None;
+                return Ok(());
+            }
+            KeyCode::Tab | KeyCode::Up | KeyCode::Down => form.on_deadline = !form.on_deadline,
+            KeyCode::Enter => {
+                let today = Local::now().date_naive();
+                let target = units::parse_amount(form.target.as_str(), self.exercise_unit(exercise), self.config.units.distance);
+                match (target, form.deadline.value()) {
+                    (Some(target), Some(deadline)) if target > 0 && deadline >= today => {
+                        self.db.set_deadline_goal(exercise, target as i64, today, deadline)?;
+                        self.message = None;
+                        return Ok(());
+                    }
+                    _ => self.message = Some(self.text.get("goals.deadline_invalid").to_string()),
+                }
+            }
+            _ if form.on_deadline => {
+                form.deadline.handle_key(key);
+            }
+            _ => {
+                form.target.handle_key(key);
+            }
+        }
+        self.goals_screen.deadline = Some(form);
+        Ok(())
+    }
+
+    /// "needs 18/day for the remaining 11 days — current pace 14/day".
+    pub(crate) fn pace_line(&self, goal: &DeadlineGoal, today: NaiveDate) -> String {
+        let pace = goal.pace(today);
+        let current = self.format_count(&goal.exercise, pace.current);
+        if goal.reached() {
+            return self.text.format("goals.pace_reached", &[("pace", &current)]);
+        }
+        self.text.format(
+            "goals.pace",
+            &[("needed", &self.format_count(&goal.exercise, pace.needed)), ("days", &pace.days_left), ("pace", &current)],
+        )
+    }
+
+    /// The nearest running deadline not reached yet, for the status bar.
+    pub(crate) fn deadline_status(&self) -> Option<String> {
+        if !self.config.goals.deadline_in_status {
+            return None;
+        }
+        let today = Local::now().date_naive();
+        let goal = self
+            .db
+            .deadline_goals()
+            .ok()?
+            .into_iter()
+            .find(|goal| goal.outcome.is_none() && goal.deadline >= today && !goal.reached())?;
+        let pace = goal.pace(today);
+        Some(self.text.format(
+            "status.deadline",
+            &[
+                ("exercise", &exercises::display_name(&goal.exercise)),
+                ("needed", &self.format_count(&goal.exercise, pace.needed)),
+                ("days", &pace.days_left),
+            ],
+        ))
+    }
+}
+
+/// Rows needed for the running goals and for the past ones, borders
+/// included; 0 when there are none.
+pub fn heights(goals: &[DeadlineGoal]) -> (u16, u16) {
+    let running = goals.iter().filter(|goal| goal.outcome.is_none()).count();
+    let past = goals.iter().filter(|goal| goal.outcome.is_some()).count().min(HISTORY_ROWS);
+    let height = |rows: usize| if rows == 0 { 0 } else { rows as u16 + 2 };
+    (height(running * 2), height(past))
+}
+
+pub fn render_running(f: &mut Frame, area: Rect, app: &App, goals: &[DeadlineGoal]) {
+    let today = Local::now().date_naive();
+    let mut lines = Vec::new();
+    for goal in goals.iter().filter(|goal| goal.outcome.is_none()) {
+        let color = if goal.reached() { Color::Green } else { app.exercise_color(&goal.exercise) };
+        lines.push(Line::from(vec![
+            Span::styled(
+                app.text.format(
+                    "goals.deadline_row",
+                    &[
+                        ("exercise", &exercises::display_name(&goal.exercise)),
+                        ("total", &app.format_count(&goal.exercise, goal.total)),
+                        ("target", &app.format_count(&goal.exercise, goal.target)),
+                        ("date", &app.display_date(goal.deadline)),
+                    ],
+                ),
+                Style::default().fg(color),
+            ),
+            Span::raw("  "),
+            Span::styled(progress_bar(goal.total, goal.target), Style::default().fg(color)),
+        ]));
+        lines.push(Line::from(Span::styled(format!("  {}", app.pace_line(goal, today)), Style::default().fg(Color::DarkGray))));
+    }
+    let block = Block::default().borders(Borders::ALL).title(app.text.get("goals.deadlines"));
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+pub fn render_past(f: &mut Frame, area: Rect, app: &App, goals: &[DeadlineGoal]) {
+    let lines: Vec<Line> = goals
+        .iter()
+        .filter_map(|goal| Some((goal, goal.outcome?)))
+        .take(HISTORY_ROWS)
+        .map(|(goal, outcome)| {
+            let (key, color) = match outcome {
+                Outcome::Achieved => ("goals.achieved", Color::Green),
+                Outcome::Missed => ("goals.missed", Color::Red),
+            };
+            Line::from(vec![
+                Span::styled(format!("{}  ", app.text.get(key)), Style::default().fg(color)),
+                Span::raw(app.text.format(
+                    "goals.deadline_row",
+                    &[
+                        ("exercise", &exercises::display_name(&goal.exercise)),
+                        ("total", &app.format_count(&goal.exercise, goal.total)),
+                        ("target", &app.format_count(&goal.exercise, goal.target)),
+                        ("date", &app.display_date(goal.deadline)),
+                    ],
+                )),
+            ])
+        })
+        .collect();
+    let block = Block::default().borders(Borders::ALL).title(app.text.get("goals.past_deadlines"));
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::WorkoutStore;
+
+    fn goals(db: &Database) -> Vec<(String, i64, i64, bool)> {
+        let goals = db.deadline_goals().unwrap().into_iter();
+        goals.map(|goal| (goal.exercise, goal.target, goal.total, goal.outcome.is_none())).collect()
+    }
+
+    #[test]
+    fn goals_follow_renamed_and_merged_exercises() {
+        let db = Database::new(":memory:").unwrap();
+        let today = Local::now().date_naive();
+        let deadline = today + Duration::days(10);
+        db.add_exercise("dips").unwrap();
+        db.set_deadline_goal("dips", 500, today, deadline).unwrap();
+        db.add_workout("dips", 20, None, None).unwrap();
+        db.rename_exercise("dips", "bench dips").unwrap();
+        db.add_workout("bench dips", 15, None, None).unwrap();
+        assert_eq!(goals(&db), [("bench dips".to_string(), 500, 35, true)]);
+
+        // The target's running goal wins; a closed one is kept as history
+        db.add_exercise("ring dips").unwrap();
+        db.set_deadline_goal("ring dips", 100, today, deadline).unwrap();
+        db.conn
+            .execute(
+                "INSERT INTO deadline_goals (exercise, target, start_date, deadline, outcome, final_total)
+                 VALUES ('ring dips', 50, '2024-05-01', '2024-05-31', 'achieved', 60)",
+                [],
+            )
+            .unwrap();
+        db.add_workout("ring dips", 10, None, None).unwrap();
+        db.merge_exercise("ring dips", "bench dips").unwrap();
+        assert_eq!(goals(&db), [("bench dips".to_string(), 500, 45, true), ("bench dips".to_string(), 50, 60, false)]);
+    }
+}
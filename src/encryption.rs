@@ -0,0 +1,258 @@
+//! Optional encryption of the SQLite database with SQLCipher, behind the
+//! `encryption` feature. An encrypted file is unlocked with a passphrase
+//! typed on a screen of its own before the main UI starts. The passphrase
+//! is only ever kept in memory: it never goes to the config, the audit log
+//! or an error message.
+
+use anyhow::{bail, ensure, Result};
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::Frame;
+use rusqlite::{Connection, OpenFlags};
+use std::{fmt, fs::File, io::Read, path::Path};
+
+use crate::exercises::render_dialog;
+use crate::i18n::Messages;
+use crate::input::TextInput;
+use crate::{before_ui, App, Database};
+
+/// How every plain SQLite file starts; SQLCipher files start with random salt.
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+const NO_SUPPORT: &str = "this build can't open encrypted databases; rebuild it with `--features encryption`";
+
+/// A database passphrase. Its `Debug` output leaves the passphrase out, so
+/// it can't end up in a log by accident.
+#[derive(Clone)]
+pub struct Passphrase(#[cfg_attr(not(feature = "encryption"), allow(dead_code))] String);
+
+impl fmt::Debug for Passphrase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Passphrase(..)")
+    }
+}
+
+/// The passphrase being typed on the data screen, entered twice to catch typos.
+#[derive(Debug, Default)]
+pub struct PassphraseForm {
+    pub new: TextInput,
+    pub repeat: TextInput,
+    /// Whether the second field has focus.
+    pub repeating: bool,
+}
+
+/// Whether the file at `path` exists but isn't a plain SQLite database,
+/// which for this app's files means SQLCipher encrypted it.
+pub fn is_encrypted(path: &Path) -> bool {
+    let mut header = [0; 16];
+    match File::open(path).and_then(|mut file| file.read_exact(&mut header)) {
+        Ok(()) => &header != SQLITE_HEADER,
+        Err(_) => false,
+    }
+}
+
+/// Opens the database file at `path`, unlocking it with `passphrase` when
+/// it's encrypted.
+pub(crate) fn open_connection(path: &Path, flags: OpenFlags, passphrase: Option<&Passphrase>) -> Result<Connection> {
+    let conn = Connection::open_with_flags(path, flags)?;
+    if is_encrypted(path) {
+        let Some(passphrase) = passphrase else {
+            bail!("{} is encrypted", path.display());
+        };
+        ensure!(unlock_connection(&conn, passphrase)?, "wrong passphrase for {}", path.display());
+    }
+    Ok(conn)
+}
+
+/// Gives the connection its key; false when the key doesn't decrypt the file.
+#[cfg(feature = "encryption")]
+fn unlock_connection(conn: &Connection, passphrase: &Passphrase) -> Result<bool> {
+    conn.pragma_update(None, "key", &passphrase.0)?;
+    // The key is only checked once a page is read
+    match conn.query_row("SELECT count(*) FROM sqlite_master", [], |_| Ok(())) {
+        Ok(()) => Ok(true),
+        Err(e) if e.sqlite_error_code() == Some(rusqlite::ErrorCode::NotADatabase) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(not(feature = "encryption"))]
+fn unlock_connection(_conn: &Connection, _passphrase: &Passphrase) -> Result<bool> {
+    bail!(NO_SUPPORT)
+}
+
+impl Database {
+    /// Opens the encrypted database at `path`, or `None` when the
+    /// passphrase is wrong. With `read_only`, as `open_read_only` would.
+    pub fn unlock(path: &Path, passphrase: Passphrase, read_only: bool) -> Result<Option<Self>> {
+        let conn = if read_only {
+            Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)?
+        } else {
+            Connection::open(path)?
+        };
+        if !unlock_connection(&conn, &passphrase)? {
+            return Ok(None);
+        }
+        let mut db = if read_only { Database::with_read_only_connection(conn, path)? } else { Database::with_connection(conn)? };
+        db.passphrase = Some(passphrase);
+        Ok(Some(db))
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.passphrase.is_some()
+    }
+
+    /// Changes the passphrase of an encrypted database, or encrypts a plain
+    /// one. A plain file is exported to an encrypted copy that then replaces
+    /// it, so an interrupted export leaves the original as it was.
+    #[cfg(feature = "encryption")]
+    pub fn set_passphrase(&mut self, passphrase: Passphrase) -> Result<()> {
+        use anyhow::Context;
+        use rusqlite::DatabaseName;
+
+        ensure!(self.jsonl.is_none(), "only SQLite databases can be encrypted");
+        ensure!(!passphrase.0.is_empty(), "the passphrase can't be empty");
+        if self.passphrase.is_some() {
+            self.conn.pragma_update(None, "rekey", &passphrase.0)?;
+            self.passphrase = Some(passphrase);
+            return Ok(());
+        }
+
+        let path = self.file_path();
+        let temp = path.with_extension("encrypting");
+        let _ = std::fs::remove_file(&temp);
+        let temp_str = temp.to_str().context("database path is not valid UTF-8")?;
+        self.conn.execute("ATTACH DATABASE ?1 AS encrypted KEY ?2", [temp_str, &passphrase.0])?;
+        let exported = self
+            .conn
+            .query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()))
+            .and_then(|_| {
+                // The export copies tables but not the schema version
+                let version: i64 = self.conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+                self.conn.pragma_update(Some(DatabaseName::Attached("encrypted")), "user_version", version)
+            });
+        self.conn.execute("DETACH DATABASE encrypted", [])?;
+        if let Err(e) = exported {
+            let _ = std::fs::remove_file(&temp);
+            return Err(e).context("failed to write the encrypted copy");
+        }
+
+        // Close the plain file before the encrypted copy takes its place
+        self.conn = Connection::open_in_memory()?;
+        let replaced = std::fs::rename(&temp, &path);
+        self.conn = Connection::open(&path)?;
+        if let Err(e) = replaced {
+            let _ = std::fs::remove_file(&temp);
+            return Err(e).with_context(|| format!("failed to replace {}", path.display()));
+        }
+        ensure!(unlock_connection(&self.conn, &passphrase)?, "the encrypted copy didn't open");
+        self.passphrase = Some(passphrase);
+        Ok(())
+    }
+
+    #[cfg(not(feature = "encryption"))]
+    pub fn set_passphrase(&mut self, _passphrase: Passphrase) -> Result<()> {
+        bail!(NO_SUPPORT)
+    }
+}
+
+/// Asks for the passphrase of the encrypted database at `path` until it
+/// opens, read-only if `read_only`, on a screen shown before the main UI.
+/// Esc gives up.
+pub fn prompt_unlock(path: &Path, read_only: bool, text: &Messages) -> Result<Database> {
+    ensure!(cfg!(feature = "encryption"), "{} is encrypted, but {NO_SUPPORT}", path.display());
+    before_ui(|terminal| {
+        let mut input = TextInput::default();
+        let mut wrong = 0;
+        loop {
+            terminal.draw(|f| render_unlock_screen(f, path, text, &input, wrong))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            match key.code {
+                KeyCode::Esc => bail!("no passphrase given for {}", path.display()),
+                KeyCode::Enter => match Database::unlock(path, Passphrase(input.as_str().to_string()), read_only)? {
+                    Some(db) => return Ok(db),
+                    None => {
+                        wrong += 1;
+                        input = TextInput::default();
+                    }
+                },
+                code => {
+                    input.handle_key(code);
+                }
+            }
+        }
+    })
+}
+
+fn render_unlock_screen(f: &mut Frame, path: &Path, text: &Messages, input: &TextInput, wrong: usize) {
+    let hint = match wrong {
+        0 => text.get("unlock.hint").to_string(),
+        count => text.format("unlock.wrong", &[("count", &count)]),
+    };
+    render_dialog(
+        f,
+        text.get("unlock.title"),
+        &text.format("unlock.field", &[("path", &path.display()), ("input", &mask(input)), ("hint", &hint)]),
+    );
+}
+
+/// A typed passphrase as dots.
+fn mask(input: &TextInput) -> String {
+    "•".repeat(input.as_str().chars().count())
+}
+
+impl App {
+    pub(crate) fn handle_passphrase_input(&mut self, key: KeyCode) -> Result<bool> {
+        let Some(form) = &mut self.data_screen.passphrase else {
+            return Ok(false);
+        };
+        match key {
+            KeyCode::Esc => self.data_screen.passphrase = None,
+            KeyCode::Enter if !form.repeating => form.repeating = true,
+            KeyCode::Enter => {
+                let (new, repeat) = (form.new.as_str().to_string(), form.repeat.as_str().to_string());
+                if new != repeat {
+                    *form = PassphraseForm::default();
+                    self.message = Some(self.text.get("data.passphrase_mismatch").to_string());
+                    return Ok(false);
+                }
+                self.data_screen.passphrase = None;
+                let was_encrypted = self.db.is_encrypted();
+                self.message = Some(match self.db.set_passphrase(Passphrase(new)) {
+                    Ok(()) if was_encrypted => self.text.get("data.passphrase_changed").to_string(),
+                    Ok(()) => self.text.get("data.encrypted").to_string(),
+                    Err(e) => self.text.format("data.passphrase_failed", &[("error", &format!("{e:#}"))]),
+                });
+                // The encrypted copy took the plain file's place
+                self.watch_database();
+            }
+            _ if form.repeating => {
+                form.repeat.handle_key(key);
+            }
+            _ => {
+                form.new.handle_key(key);
+            }
+        }
+        Ok(false)
+    }
+}
+
+pub fn render_passphrase_dialog(f: &mut Frame, app: &App) {
+    let Some(form) = &app.data_screen.passphrase else {
+        return;
+    };
+    let title = if app.db.is_encrypted() {
+        "data.passphrase_change_title"
+    } else {
+        "data.passphrase_title"
+    };
+    let cursor = |focused: bool| if focused { "▏" } else { "" };
+    let new = format!("{}{}", mask(&form.new), cursor(!form.repeating));
+    let repeat = format!("{}{}", mask(&form.repeat), cursor(form.repeating));
+    render_dialog(
+        f,
+        app.text.get(title),
+        &app.text.format("data.passphrase_field", &[("new", &new), ("repeat", &repeat)]),
+    );
+}
@@ -0,0 +1,275 @@
+//! One exercise on a page of its own, opened with Enter on the exercises
+//! screen or 'i' on the add screen's picker: lifetime figures, the last 30
+//! days as a sparkline, its settings, and its most recent sets across all
+//! days. The figures are gathered when the page opens; its daily goal and
+//! color can be changed from here.
+
+use anyhow::Result;
+use chrono::{Duration, Local, NaiveDate};
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        block::{Position, Title},
+        Block, Borders, List, ListItem, ListState, Paragraph, Wrap,
+    },
+    Frame,
+};
+use rusqlite::params;
+
+use crate::compare::streaks;
+use crate::exercises::{next_color, render_dialog};
+use crate::input::NumericInput;
+use crate::strength::sparkline;
+use crate::{dates, units, App, Database, Screen};
+
+/// Days the sparkline covers, today included.
+pub const SPARK_DAYS: i64 = 30;
+/// Sets listed, newest first.
+pub const RECENT_SETS: usize = 100;
+
+#[derive(Debug, Clone, Default)]
+pub struct ExerciseSummary {
+    pub total: i64,
+    pub sets: i64,
+    /// The day with the highest total, and that total.
+    pub best_day: Option<(NaiveDate, i64)>,
+    /// The day of the biggest single set, and its amount.
+    pub best_set: Option<(NaiveDate, i64)>,
+    pub current_streak: usize,
+    /// Total per day over the last `SPARK_DAYS` days, oldest first.
+    pub daily: Vec<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RecentSet {
+    pub timestamp: String,
+    pub count: i32,
+    /// Kilograms.
+    pub weight: Option<f64>,
+}
+
+#[derive(Default)]
+pub struct ExerciseDetailState {
+    pub exercise: String,
+    pub summary: ExerciseSummary,
+    pub recent: Vec<RecentSet>,
+    /// Highlighted row of the recent sets.
+    pub selected: usize,
+    /// Daily goal being typed.
+    pub goal: Option<NumericInput>,
+    /// Screen Esc returns to.
+    pub back: Option<Screen>,
+}
+
+impl Database {
+    pub fn exercise_summary(&self, exercise: &str, today: NaiveDate) -> Result<ExerciseSummary> {
+        let mut stmt = self.conn.prepare(
+            "SELECT day, total, best, sets FROM daily_totals
+             WHERE exercise_type = ?1
+             ORDER BY day",
+        )?;
+        let rows = stmt
+            .query_map([exercise], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?, row.get::<_, i64>(3)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let first = today - Duration::days(SPARK_DAYS - 1);
+        let mut summary = ExerciseSummary { daily: vec![0; SPARK_DAYS as usize], ..ExerciseSummary::default() };
+        let mut days = Vec::new();
+        for (day, total, best, sets) in rows {
+            let Some(day) = dates::parse_date(&day) else {
+                continue;
+            };
+            summary.total += total;
+            summary.sets += sets;
+            if summary.best_day.is_none_or(|(_, most)| total > most) {
+                summary.best_day = Some((day, total));
+            }
+            if summary.best_set.is_none_or(|(_, most)| best > most) {
+                summary.best_set = Some((day, best));
+            }
+            if (first..=today).contains(&day) {
+                summary.daily[(day - first).num_days() as usize] += total;
+            }
+            days.push(day);
+        }
+        summary.current_streak = streaks(&days, today).0;
+        Ok(summary)
+    }
+
+    pub fn recent_sets(&self, exercise: &str, limit: usize) -> Result<Vec<RecentSet>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT timestamp, count, weight FROM workouts
+             WHERE exercise_type = ?1 AND deleted_at IS NULL
+             ORDER BY timestamp DESC, id DESC
+             LIMIT ?2",
+        )?;
+        let sets = stmt
+            .query_map(params![exercise, limit as i64], |row| {
+                Ok(RecentSet { timestamp: row.get(0)?, count: row.get(1)?, weight: row.get(2)? })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(sets)
+    }
+}
+
+impl App {
+    pub(crate) fn open_exercise_detail(&mut self, exercise: &str, back: Screen) -> Result<()> {
+        let today = Local::now().date_naive();
+        self.exercise_detail = ExerciseDetailState {
+            exercise: exercise.to_string(),
+            summary: self.db.exercise_summary(exercise, today)?,
+            recent: self.db.recent_sets(exercise, RECENT_SETS)?,
+            selected: 0,
+            goal: None,
+            back: Some(back),
+        };
+        self.screen = Screen::ExerciseDetail;
+        self.message = None;
+        Ok(())
+    }
+
+    pub(crate) fn handle_exercise_detail_input(&mut self, key: KeyCode) -> Result<bool> {
+        let Some(exercise) = self.exercises.iter().find(|e| e.name == self.exercise_detail.exercise).cloned() else {
+            self.screen = self.exercise_detail.back.take().unwrap_or(Screen::Main);
+            return Ok(false);
+        };
+        let state = &mut self.exercise_detail;
+        if let Some(input) = &mut state.goal {
+            match key {
+                KeyCode::Esc => state.goal = None,
+                KeyCode::Enter => {
+                    let text = input.as_str().to_string();
+                    state.goal = None;
+                    let target = units::parse_amount(&text, &exercise.unit, self.config.units.distance);
+                    if text.is_empty() || target.is_some() {
+                        self.db.set_goal(&exercise.name, target)?;
+                    }
+                }
+                _ => {
+                    input.handle_key(key);
+                }
+            }
+            return Ok(false);
+        }
+        match key {
+            KeyCode::Esc => {
+                self.screen = state.back.take().unwrap_or(Screen::Main);
+                self.message = None;
+            }
+            KeyCode::Up => state.selected = state.selected.saturating_sub(1),
+            KeyCode::Down if state.selected + 1 < state.recent.len() => state.selected += 1,
+            KeyCode::Char('g') => {
+                let mut input = NumericInput::default();
+                input.set_decimal(units::is_decimal(&exercise.unit));
+                if let Some(target) = self.db.get_goals()?.get(&exercise.name) {
+                    input.set(units::input_text(*target, &exercise.unit, self.config.units.distance));
+                }
+                self.exercise_detail.goal = Some(input);
+            }
+            KeyCode::Char('o') => {
+                self.db.set_exercise_color(&exercise.name, next_color(exercise.color.as_deref()))?;
+                self.reload_exercises()?;
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+}
+
+pub fn render_exercise_detail_screen(f: &mut Frame, area: Rect, app: &App) {
+    let state = &app.exercise_detail;
+    let Some(exercise) = app.exercises.iter().find(|e| e.name == state.exercise) else {
+        return;
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+        .split(area);
+
+    let name = exercise.name.as_str();
+    let amount = |count: i64| app.format_count(name, count);
+    let summary = &state.summary;
+    let heading = |key: &str| Line::from(Span::styled(app.text.get(key).to_string(), Style::default().add_modifier(Modifier::BOLD)));
+    let mut lines = Vec::new();
+    if summary.sets == 0 {
+        lines.push(Line::from(app.text.get("exercise_detail.none")));
+    } else {
+        lines.push(Line::from(app.text.format("exercise_detail.total", &[("amount", &amount(summary.total)), ("sets", &summary.sets)])));
+        for (key, best) in [("exercise_detail.best_day", summary.best_day), ("exercise_detail.best_set", summary.best_set)] {
+            if let Some((day, best)) = best {
+                lines.push(Line::from(app.text.format(key, &[("amount", &amount(best)), ("date", &app.display_date(day))])));
+            }
+        }
+        lines.push(Line::from(app.text.format("exercise_detail.streak", &[("days", &summary.current_streak)])));
+    }
+    let daily: Vec<Option<f64>> = summary.daily.iter().map(|total| (*total > 0).then_some(*total as f64)).collect();
+    lines.push(Line::from(vec![
+        Span::raw(app.text.format("exercise_detail.last_days", &[("days", &SPARK_DAYS)])),
+        Span::styled(sparkline(&daily), Style::default().fg(app.exercise_color(name))),
+    ]));
+
+    lines.push(Line::from(""));
+    lines.push(heading("exercise_detail.settings"));
+    let none = app.text.get("exercise_detail.unset");
+    let default = exercise.default_count.map(|count| amount(count as i64)).unwrap_or_else(|| none.to_string());
+    let template = exercise.template.map(|t| t.text(&exercise.unit, app.config.units.distance)).unwrap_or_else(|| none.to_string());
+    let goal = app.db.get_goals().unwrap_or_default().get(name).map(|target| amount(*target)).unwrap_or_else(|| none.to_string());
+    let color = exercise.color.clone().unwrap_or_else(|| app.text.get("exercises.auto_color").to_string());
+    lines.push(Line::from(app.text.format("exercise_detail.default", &[("amount", &default)])));
+    lines.push(Line::from(app.text.format("exercises.template_line", &[("template", &template)])));
+    lines.push(Line::from(app.text.format("exercise_detail.goal", &[("amount", &goal)])));
+    lines.push(Line::from(vec![
+        Span::raw(app.text.get("exercise_detail.color")),
+        Span::styled(color, Style::default().fg(app.exercise_color(name))),
+    ]));
+    if let Some(plan) = &exercise.plan {
+        lines.push(Line::from(format!("{} · {}", app.rule_label(plan.rule), app.plan_line(exercise).unwrap_or_default())));
+    }
+    lines.push(Line::from(""));
+    match app.one_rep_max_lines(exercise) {
+        Ok(estimate) => lines.extend(estimate.into_iter().map(Line::from)),
+        Err(e) => lines.push(Line::from(format!("{e:#}"))),
+    }
+
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .title(Span::styled(exercise.display_name(), Style::default().fg(app.exercise_color(name))));
+    if let Some(message) = &app.message {
+        block = block.title(Title::from(message.as_str()).position(Position::Bottom));
+    }
+    f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }).block(block), chunks[0]);
+
+    let unit = app.config.units.weight;
+    let items: Vec<ListItem> = state
+        .recent
+        .iter()
+        .map(|set| {
+            let when = dates::parse_timestamp(&set.timestamp)
+                .map(|at| format!("{} {}", app.display_date(at.date()), dates::format_time(at.time(), app.config.ui.time_format, false)))
+                .unwrap_or_else(|| set.timestamp.clone());
+            let mut text = format!("{when}  {}", amount(set.count as i64));
+            if let Some(weight) = set.weight {
+                text.push_str(&format!(" ({})", units::format_weight(weight, unit)));
+            }
+            ListItem::new(text)
+        })
+        .collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("exercise_detail.recent")))
+        .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::REVERSED));
+    let mut list_state = ListState::default().with_selected((!state.recent.is_empty()).then_some(state.selected));
+    f.render_stateful_widget(list, chunks[1], &mut list_state);
+
+    if let Some(input) = &state.goal {
+        render_dialog(
+            f,
+            &app.text.format("goals.edit", &[("exercise", &exercise.name)]),
+            &app.text.format("goals.edit_field", &[("input", &input.as_str())]),
+        );
+    }
+}
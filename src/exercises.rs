@@ -0,0 +1,1020 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Cell, Clear, List, ListItem, Paragraph, Row, Table, Wrap},
+    Frame,
+};
+use rusqlite::{params, OptionalExtension};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::input::{NumericInput, TextInput};
+use crate::progression::{Plan, Rule};
+use crate::template::Template;
+use crate::units;
+use crate::{centered_rect, App, Database, HistoryFilter, Screen};
+
+pub const CATEGORIES: &[&str] = &["legs", "push", "pull", "core"];
+pub const OTHER_CATEGORY: &str = "other";
+pub const UNITS: &[&str] = &["reps", units::SECONDS, units::DISTANCE];
+
+/// Colors offered on the management screen and used for the name-hash fallback.
+const PALETTE: &[&str] = &[
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "lightred",
+    "lightgreen",
+    "lightyellow",
+    "lightblue",
+    "lightmagenta",
+    "lightcyan",
+];
+
+#[derive(Debug, Clone)]
+pub struct Exercise {
+    pub name: String,
+    pub category: Option<String>,
+    pub color: Option<String>,
+    pub archived: bool,
+    /// Listed first in the picker, marked with a star.
+    pub favorite: bool,
+    pub unit: String,
+    pub default_count: Option<i32>,
+    pub plan: Option<Plan>,
+    /// Planned sets walked through by a guided add.
+    pub template: Option<Template>,
+}
+
+impl Exercise {
+    /// Uncategorized exercises are rolled up under "other".
+    pub fn category(&self) -> &str {
+        self.category.as_deref().unwrap_or(OTHER_CATEGORY)
+    }
+
+    pub fn display_name(&self) -> String {
+        display_name(&self.name)
+    }
+}
+
+/// The display name, starred for a favorite.
+pub fn favorite_name(exercise: &Exercise) -> String {
+    if exercise.favorite { format!("★ {}", exercise.display_name()) } else { exercise.display_name() }
+}
+
+pub fn display_name(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Deterministic palette pick from an FNV-1a hash of the name, so an
+/// exercise keeps the same color across runs and machines.
+fn fallback_color(name: &str) -> Color {
+    let hash = name
+        .bytes()
+        .fold(0xcbf29ce484222325u64, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3));
+    Color::from_str(PALETTE[(hash % PALETTE.len() as u64) as usize]).unwrap_or(Color::White)
+}
+
+fn next_unit(current: &str) -> &'static str {
+    let i = UNITS.iter().position(|u| *u == current).unwrap_or(0);
+    UNITS[(i + 1) % UNITS.len()]
+}
+
+/// Cycles the explicit color through the palette, then back to unset.
+pub(crate) fn next_color(current: Option<&str>) -> Option<&'static str> {
+    match current.and_then(|c| PALETTE.iter().position(|known| *known == c)) {
+        None => PALETTE.first().copied(),
+        Some(i) => PALETTE.get(i + 1).copied(),
+    }
+}
+
+/// Cycles none -> legs -> push -> pull -> core -> none.
+fn next_category(current: Option<&str>) -> Option<&'static str> {
+    match current.and_then(|c| CATEGORIES.iter().position(|known| *known == c)) {
+        None => CATEGORIES.first().copied(),
+        Some(i) => CATEGORIES.get(i + 1).copied(),
+    }
+}
+
+impl Database {
+    pub fn get_exercises(&self) -> Result<Vec<Exercise>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT name, category, color, archived, unit, default_count, progression, progression_base, progression_start,
+                    favorite, template_sets, template_count
+             FROM exercises
+             ORDER BY position ASC, name ASC",
+        )?;
+        let exercises = stmt
+            .query_map([], |row| {
+                Ok(Exercise {
+                    name: row.get(0)?,
+                    category: row.get(1)?,
+                    color: row.get(2)?,
+                    archived: row.get(3)?,
+                    favorite: row.get(9)?,
+                    unit: row.get(4)?,
+                    default_count: row.get(5)?,
+                    plan: Plan::from_columns(row.get(6)?, row.get(7)?, row.get(8)?),
+                    template: Template::from_columns(row.get(10)?, row.get(11)?),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(exercises)
+    }
+
+    pub fn set_exercise_category(&self, name: &str, category: Option<&str>) -> Result<()> {
+        self.conn.execute(
+            "UPDATE exercises SET category = ?2 WHERE name = ?1",
+            params![name, category],
+        )?;
+        Ok(())
+    }
+
+    pub fn set_exercise_color(&self, name: &str, color: Option<&str>) -> Result<()> {
+        self.conn.execute(
+            "UPDATE exercises SET color = ?2 WHERE name = ?1",
+            params![name, color],
+        )?;
+        Ok(())
+    }
+
+    /// Adds an exercise at the end of the picker order.
+    pub fn add_exercise(&self, name: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO exercises (name, position)
+             VALUES (?1, (SELECT COALESCE(MAX(position), 0) + 1 FROM exercises))",
+            [name],
+        )?;
+        Ok(())
+    }
+
+    /// Only used for exercises without logged rows; callers must check first.
+    pub fn delete_exercise(&self, name: &str) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM exercise_aliases WHERE exercise = ?1", [name])?;
+        tx.execute("DELETE FROM goals WHERE exercise = ?1", [name])?;
+        tx.execute("DELETE FROM deadline_goals WHERE exercise = ?1", [name])?;
+        tx.execute("DELETE FROM day_plan WHERE exercise = ?1", [name])?;
+        tx.execute("DELETE FROM exercises WHERE name = ?1", [name])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn set_exercise_unit(&self, name: &str, unit: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE exercises SET unit = ?2 WHERE name = ?1",
+            params![name, unit],
+        )?;
+        Ok(())
+    }
+
+    pub fn set_exercise_default_count(&self, name: &str, count: Option<i32>) -> Result<()> {
+        self.conn.execute(
+            "UPDATE exercises SET default_count = ?2 WHERE name = ?1",
+            params![name, count],
+        )?;
+        Ok(())
+    }
+
+    /// Rewrites positions so `names` appear in the given order.
+    pub fn reorder_exercises(&self, names: &[String]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        for (position, name) in names.iter().enumerate() {
+            tx.execute(
+                "UPDATE exercises SET position = ?2 WHERE name = ?1",
+                params![name, position as i64],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Lifetime logged total per exercise name.
+    pub fn get_exercise_totals(&self) -> Result<HashMap<String, i64>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT exercise_type, SUM(total) FROM daily_totals
+             GROUP BY exercise_type",
+        )?;
+        let totals = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        Ok(totals)
+    }
+
+    pub fn set_exercise_archived(&self, name: &str, archived: bool) -> Result<()> {
+        self.conn.execute(
+            "UPDATE exercises SET archived = ?2 WHERE name = ?1",
+            params![name, archived],
+        )?;
+        Ok(())
+    }
+
+    pub fn set_exercise_favorite(&self, name: &str, favorite: bool) -> Result<()> {
+        self.conn.execute("UPDATE exercises SET favorite = ?2 WHERE name = ?1", params![name, favorite])?;
+        Ok(())
+    }
+
+    /// When each exercise was last done, for ordering the picker.
+    pub fn last_used_exercises(&self) -> Result<HashMap<String, String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT exercise_type, MAX(timestamp) FROM workouts WHERE deleted_at IS NULL GROUP BY exercise_type",
+        )?;
+        let last_used = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?.collect::<Result<HashMap<_, _>, _>>()?;
+        Ok(last_used)
+    }
+
+    pub fn count_workouts_for(&self, name: &str) -> Result<i64> {
+        let count = self.conn.query_row(
+            "SELECT COUNT(*) FROM workouts WHERE exercise_type = ?1",
+            [name],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
+    /// Renames an exercise and every workout logged under it. The old name
+    /// is kept as an alias so it still resolves at import time.
+    pub fn rename_exercise(&self, from: &str, to: &str) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let rows = tx.execute(
+            "UPDATE workouts SET exercise_type = ?2 WHERE exercise_type = ?1",
+            params![from, to],
+        )?;
+        tx.execute("UPDATE exercises SET name = ?2 WHERE name = ?1", params![from, to])?;
+        tx.execute("UPDATE goals SET exercise = ?2 WHERE exercise = ?1", params![from, to])?;
+        tx.execute("UPDATE deadline_goals SET exercise = ?2 WHERE exercise = ?1", params![from, to])?;
+        tx.execute("UPDATE day_plan SET exercise = ?2 WHERE exercise = ?1", params![from, to])?;
+        tx.execute(
+            "UPDATE exercise_aliases SET exercise = ?2 WHERE exercise = ?1",
+            params![from, to],
+        )?;
+        tx.execute("DELETE FROM exercise_aliases WHERE alias = ?1", [to])?;
+        tx.execute(
+            "INSERT OR REPLACE INTO exercise_aliases (alias, exercise) VALUES (?1, ?2)",
+            params![from, to],
+        )?;
+        self.log_change(None, "exercise_type", Some(from), Some(&format!("{to} (renamed, {rows} sets)")))?;
+        tx.commit()?;
+        Ok(rows)
+    }
+
+    /// Re-points all of `from`'s workouts and aliases at `into`, then removes
+    /// `from`, leaving its name behind as an alias.
+    pub fn merge_exercise(&self, from: &str, into: &str) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let rows = tx.execute(
+            "UPDATE workouts SET exercise_type = ?2 WHERE exercise_type = ?1",
+            params![from, into],
+        )?;
+        tx.execute("DELETE FROM exercises WHERE name = ?1", [from])?;
+        // The target's own goal wins; `from`'s only carries over if it had none
+        tx.execute("UPDATE OR IGNORE goals SET exercise = ?2 WHERE exercise = ?1", params![from, into])?;
+        tx.execute("DELETE FROM goals WHERE exercise = ?1", [from])?;
+        // Likewise for a running deadline goal; closed ones all carry over
+        tx.execute(
+            "DELETE FROM deadline_goals WHERE exercise = ?1 AND outcome IS NULL
+               AND EXISTS (SELECT 1 FROM deadline_goals WHERE exercise = ?2 AND outcome IS NULL)",
+            params![from, into],
+        )?;
+        tx.execute("UPDATE deadline_goals SET exercise = ?2 WHERE exercise = ?1", params![from, into])?;
+        tx.execute("UPDATE day_plan SET exercise = ?2 WHERE exercise = ?1", params![from, into])?;
+        tx.execute(
+            "UPDATE exercise_aliases SET exercise = ?2 WHERE exercise = ?1",
+            params![from, into],
+        )?;
+        tx.execute(
+            "INSERT OR REPLACE INTO exercise_aliases (alias, exercise) VALUES (?1, ?2)",
+            params![from, into],
+        )?;
+        self.log_change(None, "exercise_type", Some(from), Some(&format!("{into} (merged, {rows} sets)")))?;
+        tx.commit()?;
+        Ok(rows)
+    }
+
+    pub fn add_exercise_alias(&self, alias: &str, exercise: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO exercise_aliases (alias, exercise) VALUES (?1, ?2)",
+            params![alias, exercise],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_exercise_aliases(&self) -> Result<Vec<(String, String)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT alias, exercise FROM exercise_aliases ORDER BY alias ASC")?;
+        let aliases = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(aliases)
+    }
+
+    /// Maps a typed or imported name to its canonical exercise, if any.
+    pub fn resolve_exercise(&self, name: &str) -> Result<Option<String>> {
+        let name = normalize_name(name);
+        let exercise = self
+            .conn
+            .query_row(
+                "SELECT name FROM exercises WHERE name = ?1
+                 UNION ALL
+                 SELECT exercise FROM exercise_aliases WHERE alias = ?1
+                 LIMIT 1",
+                [name],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(exercise)
+    }
+
+    /// Per-day rep totals grouped by exercise category from `since` onwards.
+    /// Timed and distance exercises are left out since their amounts aren't reps.
+    /// Archived sets count while the archive is attached; with `tag`, only
+    /// sets carrying it count.
+    pub fn get_category_totals_since(&self, since: NaiveDate, tag: Option<&str>) -> Result<Vec<(NaiveDate, String, i64)>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT d.day, COALESCE(e.category, ?3), SUM(d.total)
+             FROM {} d
+             LEFT JOIN exercises e ON e.name = d.exercise_type
+             WHERE d.day >= ?2
+               AND COALESCE(e.unit, 'reps') NOT IN (?4, ?5)
+             GROUP BY d.day, 2
+             ORDER BY d.day ASC",
+            self.stats_daily_totals(tag)
+        ))?;
+        let rows = stmt
+            .query_map(
+                params![tag, since.format("%Y-%m-%d").to_string(), OTHER_CATEGORY, units::SECONDS, units::DISTANCE],
+                |row| Ok((row.get::<_, String>(0)?, row.get(1)?, row.get(2)?)),
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(day, category, total)| {
+                Some((NaiveDate::parse_from_str(&day, "%Y-%m-%d").ok()?, category, total))
+            })
+            .collect())
+    }
+}
+
+/// Exercise names are stored trimmed and lowercase.
+pub fn normalize_name(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+pub enum PendingChange {
+    Rename { from: String, to: String, rows: i64 },
+    Merge { from: String, into: String, rows: i64 },
+    Delete { name: String },
+}
+
+pub enum ExercisesMode {
+    Browse,
+    Create(TextInput),
+    DefaultCount(NumericInput),
+    Plan(TextInput),
+    Template(TextInput),
+    Rename(TextInput),
+    Alias(TextInput),
+    MergeTarget(usize),
+    Confirm(PendingChange),
+}
+
+pub struct ExercisesState {
+    pub selected: usize,
+    pub show_archived: bool,
+    pub mode: ExercisesMode,
+}
+
+impl Default for ExercisesState {
+    fn default() -> Self {
+        Self {
+            selected: 0,
+            show_archived: false,
+            mode: ExercisesMode::Browse,
+        }
+    }
+}
+
+impl App {
+    /// The exercise's configured color, or a stable one derived from its name.
+    pub(crate) fn exercise_color(&self, name: &str) -> Color {
+        self.exercises
+            .iter()
+            .find(|e| e.name == name)
+            .and_then(|e| e.color.as_deref())
+            .and_then(|c| Color::from_str(c).ok())
+            .unwrap_or_else(|| fallback_color(name))
+    }
+
+    pub(crate) fn exercise_unit(&self, name: &str) -> &str {
+        self.exercises
+            .iter()
+            .find(|e| e.name == name)
+            .map(|e| e.unit.as_str())
+            .unwrap_or("reps")
+    }
+
+    /// A stored count for display in the exercise's unit.
+    pub(crate) fn format_count(&self, name: &str, count: i64) -> String {
+        units::format_amount(count, self.exercise_unit(name), self.config.units.distance)
+    }
+
+    /// Exercises offered on the add screen; archived ones are left out.
+    /// The exercises offered on the add screen: favorites in the management
+    /// screen's order, then the rest by when they were last done, most
+    /// recent first, and never-done ones in the management screen's order.
+    pub(crate) fn picker_exercises(&self) -> Vec<&Exercise> {
+        let mut exercises: Vec<&Exercise> = self.exercises.iter().filter(|e| !e.archived).collect();
+        // Stable, so ties keep the management screen's order
+        exercises.sort_by(|a, b| match (a.favorite, b.favorite) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => self.last_used.get(&b.name).cmp(&self.last_used.get(&a.name)),
+        });
+        exercises
+    }
+
+    /// Stars or unstars an exercise, keeping the add screen on the same one.
+    pub(crate) fn toggle_favorite(&mut self, exercise: &Exercise) -> Result<()> {
+        let picked = self.picker_exercises().get(self.selected_exercise).map(|e| e.name.clone());
+        self.db.set_exercise_favorite(&exercise.name, !exercise.favorite)?;
+        self.reload_exercises()?;
+        if let Some(i) = picked.and_then(|name| self.picker_exercises().iter().position(|e| e.name == name)) {
+            self.selected_exercise = i;
+        }
+        let key = if exercise.favorite { "exercises.unstarred" } else { "exercises.starred" };
+        self.message = Some(self.text.format(key, &[("exercise", &exercise.name)]));
+        Ok(())
+    }
+
+    /// Rows shown on the management screen, honoring the archived toggle.
+    pub(crate) fn managed_exercises(&self) -> Vec<&Exercise> {
+        self.exercises
+            .iter()
+            .filter(|e| self.exercises_screen.show_archived || !e.archived)
+            .collect()
+    }
+
+    fn selected_managed_exercise(&self) -> Option<Exercise> {
+        self.managed_exercises()
+            .get(self.exercises_screen.selected)
+            .map(|e| (*e).clone())
+    }
+
+    pub(crate) fn reload_exercises(&mut self) -> Result<()> {
+        self.exercises = self.db.get_exercises()?;
+        self.selected_exercise = self.selected_exercise.min(self.picker_exercises().len().saturating_sub(1));
+        self.exercises_screen.selected =
+            self.exercises_screen.selected.min(self.managed_exercises().len().saturating_sub(1));
+        Ok(())
+    }
+
+    pub(crate) fn handle_exercises_input(&mut self, key: KeyCode) -> Result<bool> {
+        let mode = std::mem::replace(&mut self.exercises_screen.mode, ExercisesMode::Browse);
+        let mode = match mode {
+            ExercisesMode::Browse => return self.handle_exercises_browse_input(key),
+            ExercisesMode::Create(mut input) => {
+                self.exercises_screen.mode = match key {
+                    KeyCode::Esc => ExercisesMode::Browse,
+                    KeyCode::Enter => {
+                        self.create_exercise(input.as_str())?;
+                        ExercisesMode::Browse
+                    }
+                    _ => {
+                        input.handle_key(key);
+                        ExercisesMode::Create(input)
+                    }
+                };
+                return Ok(false);
+            }
+            other => other,
+        };
+        let Some(selected) = self.selected_managed_exercise() else {
+            return Ok(false);
+        };
+
+        let next_mode = match mode {
+            ExercisesMode::Browse | ExercisesMode::Create(_) => ExercisesMode::Browse,
+            ExercisesMode::DefaultCount(mut input) => match key {
+                KeyCode::Esc => ExercisesMode::Browse,
+                KeyCode::Enter => {
+                    let count = units::parse_amount(input.as_str(), &selected.unit, self.config.units.distance);
+                    self.db.set_exercise_default_count(&selected.name, count)?;
+                    self.reload_exercises()?;
+                    ExercisesMode::Browse
+                }
+                _ => {
+                    input.handle_key(key);
+                    ExercisesMode::DefaultCount(input)
+                }
+            },
+            ExercisesMode::Plan(mut input) => match key {
+                KeyCode::Esc => ExercisesMode::Browse,
+                KeyCode::Enter => {
+                    let text = input.as_str().trim().to_string();
+                    if text.is_empty() {
+                        if selected.plan.is_some() {
+                            self.db.set_exercise_plan(&selected.name, None)?;
+                            self.reload_exercises()?;
+                            self.message = Some(self.text.format("exercises.plan_cleared", &[("exercise", &selected.name)]));
+                        }
+                        ExercisesMode::Browse
+                    } else if let Some(rule) = Rule::parse(&text) {
+                        let key = if self.start_plan(&selected.name, rule)? { "exercises.plan_started" } else { "exercises.plan_no_history" };
+                        self.message = Some(self.text.format(key, &[("exercise", &selected.name)]));
+                        ExercisesMode::Browse
+                    } else {
+                        self.message = Some(self.text.format("exercises.plan_invalid", &[("rule", &text)]));
+                        ExercisesMode::Plan(input)
+                    }
+                }
+                _ => {
+                    input.handle_key(key);
+                    ExercisesMode::Plan(input)
+                }
+            },
+            ExercisesMode::Template(mut input) => match key {
+                KeyCode::Esc => ExercisesMode::Browse,
+                KeyCode::Enter => {
+                    let text = input.as_str().trim().to_string();
+                    let distance = self.config.units.distance;
+                    if text.is_empty() {
+                        if selected.template.is_some() {
+                            self.db.set_exercise_template(&selected.name, None)?;
+                            self.reload_exercises()?;
+                            self.message = Some(self.text.format("exercises.template_cleared", &[("exercise", &selected.name)]));
+                        }
+                        ExercisesMode::Browse
+                    } else if let Some(template) = Template::parse(&text, &selected.unit, distance) {
+                        self.db.set_exercise_template(&selected.name, Some(template))?;
+                        self.reload_exercises()?;
+                        let scheme = template.text(&selected.unit, distance);
+                        self.message = Some(self.text.format("exercises.template_set", &[("exercise", &selected.name), ("template", &scheme)]));
+                        ExercisesMode::Browse
+                    } else {
+                        self.message = Some(self.text.format("exercises.template_invalid", &[("template", &text)]));
+                        ExercisesMode::Template(input)
+                    }
+                }
+                _ => {
+                    input.handle_key(key);
+                    ExercisesMode::Template(input)
+                }
+            },
+            ExercisesMode::Rename(mut input) => match key {
+                KeyCode::Esc => ExercisesMode::Browse,
+                KeyCode::Enter => self.prepare_rename(&selected, input.as_str())?,
+                _ => {
+                    input.handle_key(key);
+                    ExercisesMode::Rename(input)
+                }
+            },
+            ExercisesMode::Alias(mut input) => match key {
+                KeyCode::Esc => ExercisesMode::Browse,
+                KeyCode::Enter => {
+                    let alias = normalize_name(input.as_str());
+                    if alias.is_empty() {
+                        self.message = Some(self.text.get("exercises.alias_empty").to_string());
+                    } else if let Some(existing) = self.db.resolve_exercise(&alias)? {
+                        self.message = Some(
+                            self.text.format("exercises.alias_taken", &[("alias", &alias), ("exercise", &existing)]),
+                        );
+                    } else {
+                        self.db.add_exercise_alias(&alias, &selected.name)?;
+                        self.message = Some(
+                            self.text.format("exercises.alias_added", &[("alias", &alias), ("exercise", &selected.name)]),
+                        );
+                    }
+                    ExercisesMode::Browse
+                }
+                _ => {
+                    input.handle_key(key);
+                    ExercisesMode::Alias(input)
+                }
+            },
+            ExercisesMode::MergeTarget(target) => {
+                let candidates = self.merge_candidates(&selected.name);
+                match key {
+                    KeyCode::Esc => ExercisesMode::Browse,
+                    KeyCode::Up => ExercisesMode::MergeTarget(target.saturating_sub(1)),
+                    KeyCode::Down => {
+                        ExercisesMode::MergeTarget((target + 1).min(candidates.len().saturating_sub(1)))
+                    }
+                    KeyCode::Enter => match candidates.get(target) {
+                        Some(into) => ExercisesMode::Confirm(PendingChange::Merge {
+                            from: selected.name.clone(),
+                            into: into.clone(),
+                            rows: self.db.count_workouts_for(&selected.name)?,
+                        }),
+                        None => ExercisesMode::Browse,
+                    },
+                    _ => ExercisesMode::MergeTarget(target),
+                }
+            }
+            ExercisesMode::Confirm(change) => {
+                if key == KeyCode::Char('y') {
+                    self.apply_change(change)?;
+                }
+                ExercisesMode::Browse
+            }
+        };
+        self.exercises_screen.mode = next_mode;
+        Ok(false)
+    }
+
+    fn merge_candidates(&self, from: &str) -> Vec<String> {
+        self.exercises
+            .iter()
+            .filter(|e| e.name != from)
+            .map(|e| e.name.clone())
+            .collect()
+    }
+
+    pub(crate) fn create_exercise(&mut self, name: &str) -> Result<()> {
+        let name = normalize_name(name);
+        if name.is_empty() {
+            return Ok(());
+        }
+        if let Some(existing) = self.db.resolve_exercise(&name)? {
+            self.message = Some(self.text.format("exercises.exists", &[("name", &name), ("exercise", &existing)]));
+            return Ok(());
+        }
+        self.db.add_exercise(&name)?;
+        self.message = Some(self.text.format("exercises.created", &[("exercise", &name)]));
+        self.reload_exercises()?;
+        // Jump to the new row so it can be edited straight away
+        if let Some(i) = self.managed_exercises().iter().position(|e| e.name == name) {
+            self.exercises_screen.selected = i;
+        }
+        Ok(())
+    }
+
+    /// Swaps the selected exercise with its neighbor in the visible list.
+    fn move_exercise(&mut self, up: bool) -> Result<()> {
+        let visible: Vec<String> = self.managed_exercises().iter().map(|e| e.name.clone()).collect();
+        let from = self.exercises_screen.selected;
+        let to = if up { from.checked_sub(1) } else { Some(from + 1) };
+        let Some(to) = to.filter(|to| *to < visible.len()) else {
+            return Ok(());
+        };
+
+        let mut order: Vec<String> = self.exercises.iter().map(|e| e.name.clone()).collect();
+        let a = order.iter().position(|n| *n == visible[from]);
+        let b = order.iter().position(|n| *n == visible[to]);
+        if let (Some(a), Some(b)) = (a, b) {
+            order.swap(a, b);
+            self.db.reorder_exercises(&order)?;
+            self.exercises_screen.selected = to;
+            self.reload_exercises()?;
+        }
+        Ok(())
+    }
+
+    fn prepare_rename(&mut self, exercise: &Exercise, new_name: &str) -> Result<ExercisesMode> {
+        let to = normalize_name(new_name);
+        if to.is_empty() || to == exercise.name {
+            return Ok(ExercisesMode::Browse);
+        }
+        if self.exercises.iter().any(|e| e.name == to) {
+            self.message = Some(self.text.format("exercises.rename_taken", &[("name", &to)]));
+            return Ok(ExercisesMode::Browse);
+        }
+        Ok(ExercisesMode::Confirm(PendingChange::Rename {
+            from: exercise.name.clone(),
+            to,
+            rows: self.db.count_workouts_for(&exercise.name)?,
+        }))
+    }
+
+    fn apply_change(&mut self, change: PendingChange) -> Result<()> {
+        self.message = Some(match change {
+            PendingChange::Rename { from, to, .. } => {
+                let rows = self.db.rename_exercise(&from, &to)?;
+                self.text.format("exercises.renamed", &[("from", &from), ("to", &to), ("rows", &rows)])
+            }
+            PendingChange::Merge { from, into, .. } => {
+                let rows = self.db.merge_exercise(&from, &into)?;
+                self.text.format("exercises.merged", &[("from", &from), ("into", &into), ("rows", &rows)])
+            }
+            PendingChange::Delete { name } => {
+                self.db.delete_exercise(&name)?;
+                self.text.format("exercises.deleted", &[("exercise", &name)])
+            }
+        });
+        // The filter may name an exercise that no longer exists
+        self.history_filter = HistoryFilter::All;
+        self.reload_exercises()?;
+        Ok(())
+    }
+
+    fn handle_exercises_browse_input(&mut self, key: KeyCode) -> Result<bool> {
+        match key {
+            KeyCode::Esc => self.screen = Screen::Main,
+            KeyCode::Up => {
+                self.exercises_screen.selected = self.exercises_screen.selected.saturating_sub(1);
+            }
+            KeyCode::Down if self.exercises_screen.selected + 1 < self.managed_exercises().len() => {
+                self.exercises_screen.selected += 1;
+            }
+            KeyCode::Enter => {
+                if let Some(exercise) = self.selected_managed_exercise() {
+                    self.open_exercise_detail(&exercise.name, Screen::Exercises)?;
+                }
+            }
+            KeyCode::Char('c') => {
+                if let Some(exercise) = self.selected_managed_exercise() {
+                    let category = next_category(exercise.category.as_deref());
+                    self.db.set_exercise_category(&exercise.name, category)?;
+                    self.reload_exercises()?;
+                }
+            }
+            KeyCode::Char('o') => {
+                if let Some(exercise) = self.selected_managed_exercise() {
+                    let color = next_color(exercise.color.as_deref());
+                    self.db.set_exercise_color(&exercise.name, color)?;
+                    self.reload_exercises()?;
+                }
+            }
+            KeyCode::Char('r') => {
+                if let Some(exercise) = self.selected_managed_exercise() {
+                    self.exercises_screen.mode = ExercisesMode::Rename(TextInput::with_text(&exercise.name));
+                }
+            }
+            KeyCode::Char('a') => {
+                self.exercises_screen.mode = ExercisesMode::Alias(TextInput::default());
+            }
+            KeyCode::Char('m') if self.exercises.len() > 1 => {
+                self.exercises_screen.mode = ExercisesMode::MergeTarget(0);
+            }
+            KeyCode::Char('x') => {
+                if let Some(exercise) = self.selected_managed_exercise() {
+                    self.db.set_exercise_archived(&exercise.name, !exercise.archived)?;
+                    let key = if exercise.archived { "exercises.restored" } else { "exercises.archived_msg" };
+                    self.message = Some(self.text.format(key, &[("exercise", &exercise.name)]));
+                    self.reload_exercises()?;
+                }
+            }
+            KeyCode::Char('f') => {
+                if let Some(exercise) = self.selected_managed_exercise() {
+                    self.toggle_favorite(&exercise)?;
+                }
+            }
+            KeyCode::Char('v') => {
+                self.exercises_screen.show_archived = !self.exercises_screen.show_archived;
+                self.reload_exercises()?;
+            }
+            KeyCode::Char('n') => {
+                self.exercises_screen.mode = ExercisesMode::Create(TextInput::default());
+            }
+            KeyCode::Char('u') => {
+                if let Some(exercise) = self.selected_managed_exercise() {
+                    self.db.set_exercise_unit(&exercise.name, next_unit(&exercise.unit))?;
+                    self.reload_exercises()?;
+                }
+            }
+            KeyCode::Char('d') => {
+                if let Some(exercise) = self.selected_managed_exercise() {
+                    let mut input = NumericInput::default();
+                    input.set_decimal(units::is_decimal(&exercise.unit));
+                    if let Some(count) = exercise.default_count {
+                        input.set(units::input_text(count as i64, &exercise.unit, self.config.units.distance));
+                    }
+                    self.exercises_screen.mode = ExercisesMode::DefaultCount(input);
+                }
+            }
+            KeyCode::Char('p') => {
+                if let Some(exercise) = self.selected_managed_exercise() {
+                    let rule = exercise.plan.map(|plan| plan.rule.text()).unwrap_or_default();
+                    self.exercises_screen.mode = ExercisesMode::Plan(TextInput::with_text(&rule));
+                }
+            }
+            KeyCode::Char('t') => {
+                if let Some(exercise) = self.selected_managed_exercise() {
+                    let distance = self.config.units.distance;
+                    let scheme = exercise.template.map(|t| t.text(&exercise.unit, distance)).unwrap_or_default();
+                    self.exercises_screen.mode = ExercisesMode::Template(TextInput::with_text(&scheme));
+                }
+            }
+            KeyCode::Char('K') => self.move_exercise(true)?,
+            KeyCode::Char('J') => self.move_exercise(false)?,
+            KeyCode::Delete => {
+                if let Some(exercise) = self.selected_managed_exercise() {
+                    let rows = self.db.count_workouts_for(&exercise.name)?;
+                    if rows > 0 {
+                        self.message = Some(
+                            self.text.format("exercises.delete_refused", &[("exercise", &exercise.name), ("rows", &rows)]),
+                        );
+                    } else {
+                        self.exercises_screen.mode =
+                            ExercisesMode::Confirm(PendingChange::Delete { name: exercise.name });
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+}
+
+pub fn render_exercises_screen(f: &mut Frame, area: Rect, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(3)])
+        .split(area);
+
+    let aliases = app.db.get_exercise_aliases().unwrap_or_default();
+    let totals = app.db.get_exercise_totals().unwrap_or_default();
+    let rows: Vec<Row> = app
+        .managed_exercises()
+        .into_iter()
+        .enumerate()
+        .map(|(i, exercise)| {
+            let mut style = Style::default();
+            if exercise.archived {
+                style = style.add_modifier(Modifier::DIM);
+            }
+            if i == app.exercises_screen.selected {
+                style = style.add_modifier(Modifier::BOLD | Modifier::REVERSED);
+            }
+            let color = exercise
+                .color
+                .clone()
+                .unwrap_or_else(|| app.text.get("exercises.auto_color").to_string());
+            let exercise_aliases: Vec<&str> = aliases
+                .iter()
+                .filter(|(_, target)| *target == exercise.name)
+                .map(|(alias, _)| alias.as_str())
+                .collect();
+            Row::new(vec![
+                Cell::from(favorite_name(exercise))
+                    .style(Style::default().fg(app.exercise_color(&exercise.name))),
+                Cell::from(exercise.unit.clone()),
+                Cell::from(exercise.category().to_string()),
+                Cell::from(color),
+                Cell::from(
+                    exercise
+                        .default_count
+                        .map(|c| app.format_count(&exercise.name, c as i64))
+                        .unwrap_or_default(),
+                ),
+                Cell::from(app.format_count(&exercise.name, totals.get(&exercise.name).copied().unwrap_or(0))),
+                Cell::from(if exercise.archived { app.text.get("exercises.yes") } else { "" }),
+                Cell::from(exercise_aliases.join(", ")),
+            ])
+            .style(style)
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Percentage(20),
+        Constraint::Percentage(9),
+        Constraint::Percentage(9),
+        Constraint::Percentage(12),
+        Constraint::Percentage(9),
+        Constraint::Percentage(9),
+        Constraint::Percentage(9),
+        Constraint::Percentage(23),
+    ];
+    let title = app.text.get(if app.exercises_screen.show_archived {
+        "exercises.title_archived"
+    } else {
+        "exercises.title"
+    });
+    let table = Table::new(rows, widths)
+        .header(
+            Row::new(
+                [
+                    "common.exercise",
+                    "exercises.unit",
+                    "exercises.category",
+                    "exercises.color",
+                    "exercises.default",
+                    "common.total",
+                    "exercises.archived",
+                    "exercises.aliases",
+                ]
+                .map(|key| app.text.get(key)),
+            )
+                .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(table, chunks[0]);
+
+    if let Some(msg) = &app.message {
+        let message = Paragraph::new(msg.as_str())
+            .style(Style::default().fg(Color::Green))
+            .block(Block::default().borders(Borders::ALL).title(app.text.get("common.status")));
+        f.render_widget(message, chunks[1]);
+    }
+
+    if let ExercisesMode::Create(input) = &app.exercises_screen.mode {
+        render_dialog(
+            f,
+            app.text.get("exercises.new"),
+            &app.text.format("exercises.name_field", &[("input", &input.as_str())]),
+        );
+        return;
+    }
+    let Some(selected) = app.selected_managed_exercise() else {
+        return;
+    };
+    match &app.exercises_screen.mode {
+        ExercisesMode::Browse | ExercisesMode::Create(_) => {}
+        ExercisesMode::DefaultCount(input) => {
+            render_dialog(
+                f,
+                &app.text.format("exercises.default_for", &[("exercise", &selected.name)]),
+                &app.text.format("exercises.default_field", &[("input", &input.as_str())]),
+            );
+        }
+        ExercisesMode::Plan(input) => {
+            let current = match &selected.plan {
+                Some(plan) => app.rule_label(plan.rule),
+                None => app.text.get("exercises.plan_none").to_string(),
+            };
+            render_dialog(
+                f,
+                &app.text.format("exercises.plan_for", &[("exercise", &selected.name)]),
+                &app.text.format("exercises.plan_field", &[("input", &input.as_str()), ("current", &current)]),
+            );
+        }
+        ExercisesMode::Template(input) => {
+            render_dialog(
+                f,
+                &app.text.format("exercises.template_for", &[("exercise", &selected.name)]),
+                &app.text.format("exercises.template_field", &[("input", &input.as_str())]),
+            );
+        }
+        ExercisesMode::Rename(input) => {
+            render_dialog(
+                f,
+                &app.text.format("exercises.rename", &[("exercise", &selected.name)]),
+                &app.text.format("exercises.new_name_field", &[("input", &input.as_str())]),
+            );
+        }
+        ExercisesMode::Alias(input) => {
+            render_dialog(
+                f,
+                &app.text.format("exercises.alias_for", &[("exercise", &selected.name)]),
+                &app.text.format("exercises.alias_field", &[("input", &input.as_str())]),
+            );
+        }
+        ExercisesMode::MergeTarget(target) => {
+            let candidates = app.merge_candidates(&selected.name);
+            let items: Vec<ListItem> = candidates
+                .iter()
+                .enumerate()
+                .map(|(i, name)| {
+                    let style = if i == *target {
+                        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default()
+                    };
+                    ListItem::new(name.as_str()).style(style)
+                })
+                .collect();
+            let area = centered_rect(40, candidates.len() as u16 + 2, f.size());
+            let list = List::new(items).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(app.text.format("exercises.merge_into", &[("exercise", &selected.name)])),
+            );
+            f.render_widget(Clear, area);
+            f.render_widget(list, area);
+        }
+        ExercisesMode::Confirm(change) => {
+            let text = match change {
+                PendingChange::Rename { from, to, rows } => {
+                    app.text.format("exercises.confirm_rename", &[("from", from), ("to", to), ("rows", rows)])
+                }
+                PendingChange::Merge { from, into, rows } => {
+                    app.text.format("exercises.confirm_merge", &[("from", from), ("into", into), ("rows", rows)])
+                }
+                PendingChange::Delete { name } => app.text.format("exercises.confirm_delete", &[("exercise", name)]),
+            };
+            render_dialog(f, app.text.get("exercises.confirm"), &text);
+        }
+    }
+}
+
+pub(crate) fn render_dialog(f: &mut Frame, title: &str, text: &str) {
+    let area = centered_rect(60, 7, f.size());
+    let dialog = Paragraph::new(text.to_string())
+        .style(Style::default().fg(Color::Cyan))
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title(title.to_string()));
+    f.render_widget(Clear, area);
+    f.render_widget(dialog, area);
+}
@@ -0,0 +1,296 @@
+use anyhow::{Context, Result};
+use chrono::{Duration, Local, NaiveDate};
+use clap::ValueEnum;
+use rusqlite::types::ValueRef;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::anonymize::Anonymize;
+use crate::config::{AppleHealthConfig, Config};
+use crate::csv_import::CsvDialect;
+use crate::dates::WeekStart;
+use crate::i18n::Messages;
+use crate::units::{DistanceUnit, WeightUnit};
+use crate::{migrations, sets_export, Database};
+
+/// Formats offered by `fitness-tracker export` and the data screen.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ExportFormat {
+    /// Every set in a date range, one per line; measurements go to a .measurements.csv next to it
+    Csv,
+    /// Every set in a date range as an array of objects; measurements go to a .measurements.json next to it
+    Json,
+    /// Schema and INSERT statements for every table, like `sqlite3 .dump`
+    Sql,
+    /// Self-contained page with summary tables and charts for a date range
+    Html,
+    /// iCalendar file with an all-day event per workout day in a date range
+    Ics,
+    /// CSV of workouts for Apple Health import apps, over a date range
+    AppleHealth,
+    /// Table of every set in a date range
+    Markdown,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 7] = [
+        ExportFormat::Csv,
+        ExportFormat::Json,
+        ExportFormat::Sql,
+        ExportFormat::Markdown,
+        ExportFormat::Html,
+        ExportFormat::Ics,
+        ExportFormat::AppleHealth,
+    ];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Sql => "sql",
+            ExportFormat::Html => "html",
+            ExportFormat::Ics => "ics",
+            ExportFormat::AppleHealth => "csv",
+            ExportFormat::Markdown => "md",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Json => "JSON",
+            ExportFormat::Sql => "SQL",
+            ExportFormat::Html => "HTML",
+            ExportFormat::Ics => "iCalendar",
+            ExportFormat::AppleHealth => "Apple Health CSV",
+            ExportFormat::Markdown => "Markdown",
+        }
+    }
+
+    /// Whether the export covers a date range rather than the whole database.
+    pub fn uses_range(self) -> bool {
+        !matches!(self, ExportFormat::Sql)
+    }
+
+    /// The lists of sets can be limited to some exercises.
+    pub fn filters_exercises(self) -> bool {
+        matches!(self, ExportFormat::Csv | ExportFormat::Json | ExportFormat::Markdown)
+    }
+
+    /// Timestamped file name used when no output path is given, naming the
+    /// exercises an export is limited to.
+    pub fn default_path(self, exercises: &[String]) -> PathBuf {
+        let names: String = exercises.iter().map(|name| format!("-{}", file_name_part(name))).collect();
+        PathBuf::from(format!(
+            "fitness_tracker{names}-{}.{}",
+            Local::now().format("%Y%m%d-%H%M%S"),
+            self.extension()
+        ))
+    }
+}
+
+/// An exercise name made safe for a file name: lowercase letters and
+/// digits, with a dash for each run of anything else.
+fn file_name_part(name: &str) -> String {
+    let mut part = String::new();
+    for c in name.to_lowercase().chars() {
+        if c.is_alphanumeric() {
+            part.push(c);
+        } else if !part.is_empty() && !part.ends_with('-') {
+            part.push('-');
+        }
+    }
+    part.trim_end_matches('-').to_string()
+}
+
+/// Days covered by a ranged export when no start date is given.
+pub const DEFAULT_RANGE_DAYS: i64 = 30;
+
+/// What goes into an export besides the format itself.
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// Include sets that are in the trash, in the SQL dump and the lists of
+    /// sets. Reports and calendars never do.
+    pub include_deleted: bool,
+    /// First and last day for ranged formats; the SQL dump always has
+    /// everything.
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    /// Labels, units and week boundaries for reports.
+    pub text: Messages,
+    pub distance: DistanceUnit,
+    pub weight: WeightUnit,
+    pub week_starts_on: WeekStart,
+    pub apple_health: AppleHealthConfig,
+    /// Separators, quoting and timestamps of the CSV export.
+    pub csv: CsvDialect,
+    /// Exercises the lists of sets are limited to; empty is all of them.
+    pub exercises: Vec<String>,
+    /// Anonymize the lists of sets.
+    pub anonymize: Option<Anonymize>,
+    /// Only these sets in the lists of sets, for exporting a selection.
+    pub sets: Option<Vec<i64>>,
+}
+
+/// What an export left out, for a warning after it's written, and where
+/// else it wrote.
+#[derive(Debug, Clone, Default)]
+pub struct ExportSummary {
+    /// Sets the format has no way to represent.
+    pub skipped: usize,
+    /// The companion file with the range's measurements, when there were any.
+    pub measurements: Option<PathBuf>,
+}
+
+impl ExportOptions {
+    /// Options with the report settings taken from the config.
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            text: Messages::for_language(&config.language),
+            distance: config.units.distance,
+            weight: config.units.weight,
+            week_starts_on: config.week_starts_on,
+            apple_health: config.apple_health.clone(),
+            csv: config.csv.clone(),
+            ..Self::default()
+        }
+    }
+
+    /// Report range: up to today, starting `DEFAULT_RANGE_DAYS` before the
+    /// end unless a start is given.
+    /// Whether a CSV or JSON export has the measurements too: not when it's
+    /// limited to some sets, or anonymized for sharing.
+    fn includes_measurements(&self) -> bool {
+        self.exercises.is_empty() && self.sets.is_none() && self.anonymize.is_none()
+    }
+
+    pub fn range(&self) -> (NaiveDate, NaiveDate) {
+        let to = self.to.unwrap_or_else(|| Local::now().date_naive());
+        let from = self.from.unwrap_or(to - Duration::days(DEFAULT_RANGE_DAYS - 1));
+        (from, to)
+    }
+}
+
+/// Double-quoted SQL identifier.
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// SQL literal that reads back as exactly the same value.
+fn sql_literal(value: ValueRef) -> String {
+    match value {
+        ValueRef::Null => "NULL".to_string(),
+        ValueRef::Integer(i) => i.to_string(),
+        // Debug formatting is the shortest text that round-trips the float
+        ValueRef::Real(f) if f.is_finite() => format!("{f:?}"),
+        ValueRef::Real(f) if f > 0.0 => "1e999".to_string(),
+        ValueRef::Real(_) => "-1e999".to_string(),
+        ValueRef::Text(text) => format!("'{}'", String::from_utf8_lossy(text).replace('\'', "''")),
+        ValueRef::Blob(bytes) => {
+            let hex: String = bytes.iter().map(|b| format!("{b:02X}")).collect();
+            format!("X'{hex}'")
+        }
+    }
+}
+
+impl Database {
+    /// Writes the whole database as SQL text that rebuilds it in an empty
+    /// database, including the schema version. Trashed sets are left out
+    /// unless `options` asks for them.
+    pub fn dump_sql(&self, out: &mut impl Write, options: &ExportOptions) -> Result<()> {
+        writeln!(out, "PRAGMA foreign_keys=OFF;")?;
+        writeln!(out, "BEGIN TRANSACTION;")?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT name, sql FROM sqlite_master
+             WHERE type = 'table' AND sql IS NOT NULL
+             ORDER BY name",
+        )?;
+        let tables = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        for (name, sql) in &tables {
+            if name == "sqlite_sequence" {
+                writeln!(out, "DELETE FROM sqlite_sequence;")?;
+            } else if name.starts_with("sqlite_") || migrations::is_notes_index(name) {
+                continue;
+            } else {
+                writeln!(out, "{sql};")?;
+            }
+            let filter = match name.as_str() {
+                "workouts" if !options.include_deleted => "WHERE deleted_at IS NULL",
+                _ => "",
+            };
+            self.dump_rows(name, filter, out)?;
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT name, sql FROM sqlite_master
+             WHERE type IN ('index', 'trigger', 'view') AND sql IS NOT NULL
+             ORDER BY type = 'view', name",
+        )?;
+        // The notes index is built again when the dump is opened
+        for row in stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))? {
+            let (name, sql) = row?;
+            if !migrations::is_notes_index(&name) {
+                writeln!(out, "{sql};")?;
+            }
+        }
+
+        writeln!(out, "PRAGMA user_version={};", migrations::schema_version(&self.conn)?)?;
+        writeln!(out, "COMMIT;")?;
+        Ok(())
+    }
+
+    fn dump_rows(&self, table: &str, filter: &str, out: &mut impl Write) -> Result<()> {
+        let table = quote_ident(table);
+        let mut stmt = self.conn.prepare(&format!("SELECT * FROM {table} {filter}"))?;
+        let columns = stmt.column_count();
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let values = (0..columns)
+                .map(|i| Ok(sql_literal(row.get_ref(i)?)))
+                .collect::<Result<Vec<_>>>()?;
+            writeln!(out, "INSERT INTO {table} VALUES({});", values.join(","))?;
+        }
+        Ok(())
+    }
+
+    /// Writes an export in `format` to `path`.
+    pub fn export(&self, format: ExportFormat, path: &Path, options: &ExportOptions) -> Result<ExportSummary> {
+        let file = File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+        let mut out = BufWriter::new(file);
+        let mut summary = ExportSummary::default();
+        match format {
+            ExportFormat::Csv => self.write_sets_csv(&mut out, options)?,
+            ExportFormat::Json => self.write_sets_json(&mut out, options)?,
+            ExportFormat::Sql => self.dump_sql(&mut out, options)?,
+            ExportFormat::Markdown => self.write_sets_markdown(&mut out, options)?,
+            ExportFormat::Html => self.write_html(&mut out, options)?,
+            ExportFormat::Ics => self.write_ics(&mut out, options)?,
+            ExportFormat::AppleHealth => summary.skipped = self.write_apple_health(&mut out, options)?,
+        }
+        out.flush()?;
+        if matches!(format, ExportFormat::Csv | ExportFormat::Json) && options.includes_measurements() {
+            let measurements = self.export_measurements(options)?;
+            if !measurements.is_empty() {
+                let path = sets_export::measurements_path(path);
+                let file = File::create(&path).with_context(|| format!("failed to create {}", path.display()))?;
+                let mut out = BufWriter::new(file);
+                match format {
+                    ExportFormat::Csv => self.write_measurements_csv(&mut out, &measurements, options)?,
+                    _ => {
+                        serde_json::to_writer_pretty(&mut out, &measurements)?;
+                        writeln!(out)?;
+                    }
+                }
+                out.flush()?;
+                summary.measurements = Some(path);
+            }
+        }
+        Ok(summary)
+    }
+}
@@ -0,0 +1,562 @@
+//! The export screen, opened from the data screen: a format from the list,
+//! a range from presets or typed dates, the exercises to limit the lists of
+//! sets to and whether to anonymize them, the CSV dialect when exporting
+//! CSV, and an output path, with a count of the sets that will go in. Choices
+//! stay as they were for the next export until the app is closed. Opened
+//! with sets picked in the history, it exports just those as a list.
+
+use anyhow::Result;
+use chrono::{Datelike, Local, NaiveDate};
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+use std::path::{Path, PathBuf};
+
+use crate::anonymize::{Anonymize, DateMode};
+use crate::csv_import::CsvDialect;
+use crate::dates::{self, week_start};
+use crate::export::{ExportFormat, ExportOptions};
+use crate::input::TextInput;
+use crate::{App, Screen};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RangePreset {
+    Today,
+    ThisWeek,
+    ThisMonth,
+    All,
+    Custom,
+}
+
+impl RangePreset {
+    pub const ALL: [RangePreset; 5] = [RangePreset::Today, RangePreset::ThisWeek, RangePreset::ThisMonth, RangePreset::All, RangePreset::Custom];
+
+    fn label_key(self) -> &'static str {
+        match self {
+            RangePreset::Today => "export.today",
+            RangePreset::ThisWeek => "export.this_week",
+            RangePreset::ThisMonth => "export.this_month",
+            RangePreset::All => "export.all",
+            RangePreset::Custom => "export.custom",
+        }
+    }
+}
+
+/// The part of the screen keys go to.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Field {
+    #[default]
+    Format,
+    Range,
+    From,
+    To,
+    Exercises,
+    Anonymize,
+    Delimiter,
+    Quote,
+    Header,
+    Decimal,
+    TimestampFormat,
+    Path,
+}
+
+/// Anonymization choices: off, or how dates change and whether exercises
+/// get generic names.
+const ANONYMIZE: [Option<(DateMode, bool)>; 5] = [
+    None,
+    Some((DateMode::Truncate, false)),
+    Some((DateMode::Truncate, true)),
+    Some((DateMode::Shift, false)),
+    Some((DateMode::Shift, true)),
+];
+
+/// Choices offered for the CSV dialect's characters.
+const DELIMITERS: [char; 4] = [',', ';', '\t', '|'];
+const QUOTES: [char; 2] = ['"', '\''];
+const DECIMALS: [char; 2] = ['.', ','];
+
+/// The choice after (or before) `current`; one that isn't offered moves to
+/// the first.
+fn cycle(choices: &[char], current: char, forward: bool) -> char {
+    match choices.iter().position(|c| *c == current) {
+        Some(at) if forward => choices[(at + 1) % choices.len()],
+        Some(at) => choices[(at + choices.len() - 1) % choices.len()],
+        None => choices[0],
+    }
+}
+
+/// A separator as shown on the screen.
+fn show_char(c: char) -> String {
+    match c {
+        '\t' => "tab".to_string(),
+        ' ' => "space".to_string(),
+        c => c.to_string(),
+    }
+}
+
+#[derive(Debug)]
+pub struct ExportDialog {
+    /// Index into `ExportFormat::ALL`.
+    pub format: usize,
+    /// Index into `RangePreset::ALL`.
+    pub preset: usize,
+    /// Typed dates for the custom range.
+    pub from: TextInput,
+    pub to: TextInput,
+    pub path: TextInput,
+    /// The path was typed rather than filled in, so it's kept when the
+    /// format changes.
+    pub path_typed: bool,
+    /// Exercises picked for the lists of sets, in list order; none is all.
+    pub exercises: Vec<String>,
+    /// Highlighted exercise while picking.
+    pub exercise_cursor: usize,
+    /// Index into `ANONYMIZE`.
+    pub anonymize: usize,
+    /// CSV dialect, starting from the config; the timestamp format is
+    /// typed into `timestamp_format`.
+    pub csv: CsvDialect,
+    pub timestamp_format: TextInput,
+    pub field: Field,
+    /// What stopped the last export, shown under the fields.
+    pub error: Option<String>,
+    /// Sets picked in the history, exported instead of a range.
+    pub selection: Option<ExportSelection>,
+}
+
+/// Picked sets to export and the days they span.
+#[derive(Debug, Clone)]
+pub struct ExportSelection {
+    pub sets: Vec<i64>,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl ExportDialog {
+    pub fn new(csv: &CsvDialect) -> Self {
+        let today = Local::now().date_naive().to_string();
+        Self {
+            format: 0,
+            preset: 3,
+            from: TextInput::with_text(&today),
+            to: TextInput::with_text(&today),
+            path: TextInput::default(),
+            path_typed: false,
+            exercises: Vec::new(),
+            exercise_cursor: 0,
+            anonymize: 0,
+            csv: csv.clone(),
+            timestamp_format: TextInput::with_text(&csv.timestamp_format),
+            field: Field::Format,
+            error: None,
+            selection: None,
+        }
+    }
+
+    fn format(&self) -> ExportFormat {
+        ExportFormat::ALL[self.format]
+    }
+
+    fn preset(&self) -> RangePreset {
+        RangePreset::ALL[self.preset]
+    }
+
+    /// Fields that take keys for the chosen format and range, in order.
+    fn fields(&self) -> Vec<Field> {
+        let mut fields = vec![Field::Format];
+        if self.selection.is_some() {
+            // The picked sets stand in for the range and the exercises
+            fields.push(Field::Anonymize);
+        } else {
+            if self.format().uses_range() {
+                fields.push(Field::Range);
+                if self.preset() == RangePreset::Custom {
+                    fields.extend([Field::From, Field::To]);
+                }
+            }
+            if self.format().filters_exercises() {
+                fields.extend([Field::Exercises, Field::Anonymize]);
+            }
+        }
+        if self.format() == ExportFormat::Csv {
+            fields.extend([Field::Delimiter, Field::Quote, Field::Header, Field::Decimal, Field::TimestampFormat]);
+        }
+        fields.push(Field::Path);
+        fields
+    }
+
+    /// The next format in the list, or the previous one; picked sets only
+    /// go to the lists of sets.
+    fn step_format(&mut self, forward: bool) {
+        let count = ExportFormat::ALL.len();
+        loop {
+            self.format = if forward { (self.format + 1) % count } else { (self.format + count - 1) % count };
+            if self.selection.is_none() || self.format().filters_exercises() {
+                break;
+            }
+        }
+        self.fill_default_path();
+    }
+
+    fn move_field(&mut self, forward: bool) {
+        let fields = self.fields();
+        let at = fields.iter().position(|field| *field == self.field).unwrap_or(0);
+        let next = if forward { (at + 1) % fields.len() } else { (at + fields.len() - 1) % fields.len() };
+        self.field = fields[next];
+    }
+
+    fn fill_default_path(&mut self) {
+        if !self.path_typed {
+            // Generic names would be given away by real ones in the file name
+            let named = if self.renames_exercises() { &[] } else { self.exercise_filter() };
+            self.path = TextInput::with_text(&self.format().default_path(named).to_string_lossy());
+        }
+    }
+
+    /// The picked exercises, if the format can be limited to them.
+    fn exercise_filter(&self) -> &[String] {
+        if self.format().filters_exercises() && self.selection.is_none() { &self.exercises } else { &[] }
+    }
+
+    fn renames_exercises(&self) -> bool {
+        self.format().filters_exercises() && ANONYMIZE[self.anonymize].is_some_and(|(_, rename)| rename)
+    }
+
+    /// Anonymization for one export, with its own random shift.
+    fn anonymization(&self) -> Option<Anonymize> {
+        let (dates, rename) = ANONYMIZE[self.anonymize]?;
+        self.format().filters_exercises().then(|| Anonymize::new(dates, rename))
+    }
+
+    /// Picks or unpicks `name`, keeping the picks in `names` order.
+    fn toggle_exercise(&mut self, name: &str, names: &[String]) {
+        match self.exercises.iter().position(|picked| picked == name) {
+            Some(at) => {
+                self.exercises.remove(at);
+            }
+            None => {
+                self.exercises.push(name.to_string());
+                self.exercises.sort_by_key(|picked| names.iter().position(|name| name == picked));
+            }
+        }
+        self.fill_default_path();
+    }
+
+    fn csv_dialect(&self) -> CsvDialect {
+        CsvDialect {
+            timestamp_format: self.timestamp_format.as_str().to_string(),
+            ..self.csv.clone()
+        }
+    }
+}
+
+/// Parses a typed date, naming the field in the error.
+fn typed_date(input: &TextInput, field: &str, app: &App) -> Result<NaiveDate, String> {
+    dates::parse_date(input.as_str().trim()).ok_or_else(|| app.text.format("export.bad_date", &[("field", &field)]))
+}
+
+/// Why `path` can't be written to, when that can be told up front.
+fn path_problem(path: &Path, app: &App) -> Option<String> {
+    if path.as_os_str().is_empty() {
+        return Some(app.text.get("export.no_path").to_string());
+    }
+    if path.is_dir() {
+        return Some(app.text.format("export.path_is_folder", &[("path", &path.display())]));
+    }
+    match path.parent() {
+        Some(folder) if !folder.as_os_str().is_empty() && !folder.is_dir() => {
+            Some(app.text.format("export.no_folder", &[("folder", &folder.display())]))
+        }
+        _ => None,
+    }
+}
+
+impl App {
+    pub(crate) fn open_export(&mut self) {
+        self.export_dialog.selection = None;
+        self.export_back = None;
+        self.show_export();
+    }
+
+    /// The export screen limited to `selection`, with Esc going back to
+    /// `back`.
+    pub(crate) fn open_export_selection(&mut self, selection: ExportSelection, back: Screen) {
+        self.export_dialog.selection = Some(selection);
+        self.export_back = Some(back);
+        if !self.export_dialog.format().filters_exercises() {
+            self.export_dialog.format = 0;
+        }
+        self.show_export();
+    }
+
+    fn show_export(&mut self) {
+        self.export_dialog.error = None;
+        self.export_dialog.fill_default_path();
+        if !self.export_dialog.fields().contains(&self.export_dialog.field) {
+            self.export_dialog.field = Field::Format;
+        }
+        self.screen = Screen::Export;
+        self.message = None;
+    }
+
+    /// First and last day of the chosen range, or what's wrong with the
+    /// typed dates.
+    fn export_range(&self) -> Result<(NaiveDate, NaiveDate), String> {
+        let today = Local::now().date_naive();
+        let dialog = &self.export_dialog;
+        Ok(match dialog.preset() {
+            RangePreset::Today => (today, today),
+            RangePreset::ThisWeek => (week_start(today, self.config.week_starts_on), today),
+            RangePreset::ThisMonth => (today.with_day(1).unwrap_or(today), today),
+            RangePreset::All => {
+                let first = self.db.workout_date_range().ok().flatten().and_then(|(first, _)| dates::parse_timestamp(&first));
+                (first.map_or(today, |first| first.date().min(today)), today)
+            }
+            RangePreset::Custom => {
+                let from = typed_date(&dialog.from, self.text.get("export.from"), self)?;
+                let to = typed_date(&dialog.to, self.text.get("export.to"), self)?;
+                if from > to {
+                    return Err(self.text.get("export.backwards").to_string());
+                }
+                (from, to)
+            }
+        })
+    }
+
+    fn export_options(&self) -> Result<ExportOptions, String> {
+        let (from, to) = match &self.export_dialog.selection {
+            Some(selection) => (selection.from, selection.to),
+            None => self.export_range()?,
+        };
+        Ok(ExportOptions {
+            from: Some(from),
+            to: Some(to),
+            csv: self.export_dialog.csv_dialect(),
+            exercises: self.export_dialog.exercise_filter().to_vec(),
+            anonymize: self.export_dialog.anonymization(),
+            sets: self.export_dialog.selection.as_ref().map(|selection| selection.sets.clone()),
+            ..ExportOptions::from_config(&self.config)
+        })
+    }
+
+    pub(crate) fn handle_export_input(&mut self, key: KeyCode) -> Result<bool> {
+        let names: Vec<String> = self.exercises.iter().map(|exercise| exercise.name.clone()).collect();
+        let dialog = &mut self.export_dialog;
+        match (dialog.field, key) {
+            (_, KeyCode::Esc) => {
+                self.screen = self.export_back.take().unwrap_or(Screen::Data);
+                return Ok(false);
+            }
+            (_, KeyCode::Tab) => dialog.move_field(true),
+            (_, KeyCode::BackTab) => dialog.move_field(false),
+            (_, KeyCode::Enter) => {
+                self.run_export();
+                return Ok(false);
+            }
+            (Field::Format, KeyCode::Up | KeyCode::Left) => dialog.step_format(false),
+            (Field::Format, KeyCode::Down | KeyCode::Right) => dialog.step_format(true),
+            (Field::Range, KeyCode::Left) => dialog.preset = (dialog.preset + RangePreset::ALL.len() - 1) % RangePreset::ALL.len(),
+            (Field::Range, KeyCode::Right) => dialog.preset = (dialog.preset + 1) % RangePreset::ALL.len(),
+            (Field::Anonymize, KeyCode::Left | KeyCode::Right) => {
+                dialog.anonymize = if key == KeyCode::Right {
+                    (dialog.anonymize + 1) % ANONYMIZE.len()
+                } else {
+                    (dialog.anonymize + ANONYMIZE.len() - 1) % ANONYMIZE.len()
+                };
+                dialog.fill_default_path();
+            }
+            (Field::Exercises, KeyCode::Left) => dialog.exercise_cursor = dialog.exercise_cursor.saturating_sub(1),
+            (Field::Exercises, KeyCode::Right) => dialog.exercise_cursor = (dialog.exercise_cursor + 1).min(names.len().saturating_sub(1)),
+            (Field::Exercises, KeyCode::Char(' ')) => {
+                if let Some(name) = names.get(dialog.exercise_cursor) {
+                    dialog.toggle_exercise(name, &names);
+                }
+            }
+            (Field::Delimiter, KeyCode::Left | KeyCode::Right) => dialog.csv.delimiter = cycle(&DELIMITERS, dialog.csv.delimiter, key == KeyCode::Right),
+            (Field::Quote, KeyCode::Left | KeyCode::Right) => dialog.csv.quote = cycle(&QUOTES, dialog.csv.quote, key == KeyCode::Right),
+            (Field::Decimal, KeyCode::Left | KeyCode::Right) => dialog.csv.decimal = cycle(&DECIMALS, dialog.csv.decimal, key == KeyCode::Right),
+            (Field::Header, KeyCode::Left | KeyCode::Right | KeyCode::Char(' ')) => dialog.csv.header = !dialog.csv.header,
+            (_, KeyCode::Up) => dialog.move_field(false),
+            (_, KeyCode::Down) => dialog.move_field(true),
+            (Field::From, _) => {
+                dialog.from.handle_key(key);
+            }
+            (Field::To, _) => {
+                dialog.to.handle_key(key);
+            }
+            (Field::TimestampFormat, _) => {
+                dialog.timestamp_format.handle_key(key);
+            }
+            (Field::Path, _) => dialog.path_typed |= dialog.path.handle_key(key),
+            _ => {}
+        }
+        self.export_dialog.error = None;
+        Ok(false)
+    }
+
+    /// Writes the export; problems stay on the screen, success goes back
+    /// to the data screen with the path in the status line.
+    fn run_export(&mut self) {
+        let format = self.export_dialog.format();
+        let path = PathBuf::from(self.export_dialog.path.as_str().trim());
+        let options = match self.export_options() {
+            Ok(options) => options,
+            Err(error) => {
+                self.export_dialog.error = Some(error);
+                return;
+            }
+        };
+        if let Some(problem) = path_problem(&path, self) {
+            self.export_dialog.error = Some(problem);
+            return;
+        }
+        if format == ExportFormat::Csv
+            && let Err(e) = options.csv.check()
+        {
+            self.export_dialog.error = Some(self.text.format("export.bad_dialect", &[("error", &e)]));
+            return;
+        }
+        match self.db.export(format, &path, &options) {
+            Ok(summary) => {
+                self.message = Some(if summary.skipped > 0 {
+                    self.text.format("data.exported_skipped", &[("path", &path.display()), ("skipped", &summary.skipped)])
+                } else if let Some(measurements) = &summary.measurements {
+                    self.text.format("data.exported_measurements", &[("path", &path.display()), ("measurements", &measurements.display())])
+                } else {
+                    self.text.format("data.exported", &[("path", &path.display())])
+                });
+                // A fresh timestamp for next time, unless the path was typed
+                self.export_dialog.fill_default_path();
+                self.screen = self.export_back.take().unwrap_or(Screen::Data);
+            }
+            Err(e) => self.export_dialog.error = Some(self.text.format("data.export_failed", &[("error", &format!("{e:#}"))])),
+        }
+    }
+}
+
+pub fn render_export_screen(f: &mut Frame, area: Rect, app: &App) {
+    let dialog = &app.export_dialog;
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(24), Constraint::Min(0)])
+        .split(area);
+    let focused = |field: Field| {
+        if dialog.field == field { Style::default().fg(Color::Yellow) } else { Style::default() }
+    };
+
+    let formats: Vec<ListItem> = ExportFormat::ALL
+        .iter()
+        .map(|format| {
+            // Picked sets only go to the lists of sets
+            let style = if dialog.selection.is_some() && !format.filters_exercises() { Style::default().fg(Color::DarkGray) } else { Style::default() };
+            ListItem::new(format.label()).style(style)
+        })
+        .collect();
+    let list = List::new(formats)
+        .block(Block::default().borders(Borders::ALL).border_style(focused(Field::Format)).title(app.text.get("export.format")))
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED));
+    f.render_stateful_widget(list, chunks[0], &mut ListState::default().with_selected(Some(dialog.format)));
+
+    let marker = |field: Field| if dialog.field == field { "▶ " } else { "  " };
+    let format = dialog.format();
+    let mut lines = Vec::new();
+    if let Some(selection) = &dialog.selection {
+        lines.push(Line::styled(
+            format!("  {}", app.text.format("export.selection", &[("count", &selection.sets.len())])),
+            Style::default().fg(Color::Cyan),
+        ));
+    } else if format.uses_range() {
+        let mut presets = vec![Span::raw(format!("{}{}: ", marker(Field::Range), app.text.get("export.range")))];
+        for (i, preset) in RangePreset::ALL.iter().enumerate() {
+            let style = if i == dialog.preset { Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD | Modifier::REVERSED) } else { Style::default() };
+            presets.push(Span::styled(app.text.get(preset.label_key()).to_string(), style));
+            presets.push(Span::raw(" "));
+        }
+        lines.push(Line::from(presets).style(focused(Field::Range)));
+        if dialog.preset() == RangePreset::Custom {
+            for (field, label, input) in [(Field::From, "export.from", &dialog.from), (Field::To, "export.to", &dialog.to)] {
+                let cursor = if dialog.field == field { "▏" } else { "" };
+                lines.push(Line::styled(format!("{}{}: {}{cursor}", marker(field), app.text.get(label), input.as_str()), focused(field)));
+            }
+        }
+    } else {
+        lines.push(Line::styled(format!("  {}", app.text.get("export.whole_database")), Style::default().fg(Color::DarkGray)));
+    }
+    if format.filters_exercises() {
+        if dialog.selection.is_none() {
+            let mut exercises = vec![Span::raw(format!("{}{}: ", marker(Field::Exercises), app.text.get("export.exercises")))];
+            if dialog.exercises.is_empty() {
+                exercises.push(Span::styled(app.text.get("export.all_exercises"), Style::default().fg(Color::DarkGray)));
+                exercises.push(Span::raw(" "));
+            }
+            for (i, exercise) in app.exercises.iter().enumerate() {
+                let mut style = if dialog.exercises.contains(&exercise.name) {
+                    Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD | Modifier::REVERSED)
+                } else {
+                    Style::default()
+                };
+                if dialog.field == Field::Exercises && i == dialog.exercise_cursor {
+                    style = style.add_modifier(Modifier::UNDERLINED);
+                }
+                exercises.push(Span::styled(exercise.name.clone(), style));
+                exercises.push(Span::raw(" "));
+            }
+            lines.push(Line::from(exercises).style(focused(Field::Exercises)));
+        }
+        let anonymize = match ANONYMIZE[dialog.anonymize] {
+            None => app.text.get("export.anonymize_off").to_string(),
+            Some((dates, rename)) => {
+                let dates = app.text.get(if dates == DateMode::Truncate { "export.anonymize_truncate" } else { "export.anonymize_shift" });
+                if rename { app.text.format("export.anonymize_rename", &[("dates", &dates)]) } else { dates.to_string() }
+            }
+        };
+        lines.push(Line::styled(
+            format!("{}{}: {anonymize}", marker(Field::Anonymize), app.text.get("export.anonymize")),
+            focused(Field::Anonymize),
+        ));
+    }
+    if format == ExportFormat::Csv {
+        let header = app.text.get(if dialog.csv.header { "export.header_on" } else { "export.header_off" });
+        for (field, label, value) in [
+            (Field::Delimiter, "export.delimiter", show_char(dialog.csv.delimiter)),
+            (Field::Quote, "export.quote", show_char(dialog.csv.quote)),
+            (Field::Header, "export.header", header.to_string()),
+            (Field::Decimal, "export.decimal", show_char(dialog.csv.decimal)),
+        ] {
+            lines.push(Line::styled(format!("{}{}: {value}", marker(field), app.text.get(label)), focused(field)));
+        }
+        let cursor = if dialog.field == Field::TimestampFormat { "▏" } else { "" };
+        lines.push(Line::styled(
+            format!("{}{}: {}{cursor}", marker(Field::TimestampFormat), app.text.get("export.timestamp_format"), dialog.timestamp_format.as_str()),
+            focused(Field::TimestampFormat),
+        ));
+    }
+    let cursor = if dialog.field == Field::Path { "▏" } else { "" };
+    lines.push(Line::styled(
+        format!("{}{}: {}{cursor}", marker(Field::Path), app.text.get("export.path"), dialog.path.as_str()),
+        focused(Field::Path),
+    ));
+    lines.push(Line::raw(""));
+    match app.export_options() {
+        Ok(options) => {
+            let count = app.db.count_export_sets(format, &options).unwrap_or(0);
+            let mut preview = app.text.format("export.preview", &[("count", &count)]);
+            if format.uses_range() {
+                let (from, to) = options.range();
+                preview = format!("{preview} ({} – {})", app.display_date(from), app.display_date(to));
+            }
+            lines.push(Line::styled(preview, Style::default().fg(Color::Green)));
+        }
+        Err(error) => lines.push(Line::styled(error, Style::default().fg(Color::Red))),
+    }
+    if let Some(error) = &dialog.error {
+        lines.push(Line::styled(error.as_str(), Style::default().fg(Color::Red)));
+    }
+    let fields = Paragraph::new(lines).wrap(Wrap { trim: false }).block(Block::default().borders(Borders::ALL).title(app.text.get("export.title")));
+    f.render_widget(fields, chunks[1]);
+}
@@ -0,0 +1,263 @@
+use anyhow::{bail, Context, Result};
+use chrono::{Duration, NaiveDateTime};
+use std::path::Path;
+
+use crate::csv_import::{self, ImportFile, ImportSet, Reject};
+use crate::dates;
+use crate::units::{DistanceUnit, WeightUnit};
+
+/// FitNotes only records the day of a set, so sets are placed at noon and
+/// a second apart in file order, which keeps them sorted and makes the
+/// same file import to the same timestamps every time.
+const SET_HOUR: u32 = 12;
+
+/// This tracker's category for a FitNotes one, by the muscles it trains.
+fn category(name: &str) -> Option<&'static str> {
+    match name.trim().to_lowercase().as_str() {
+        "legs" => Some("legs"),
+        "chest" | "shoulders" | "triceps" => Some("push"),
+        "back" | "biceps" => Some("pull"),
+        "abs" | "core" => Some("core"),
+        _ => None,
+    }
+}
+
+/// Meters per FitNotes distance unit.
+fn meters_per(unit: &str) -> Option<f64> {
+    match unit.trim().to_lowercase().as_str() {
+        "m" => Some(1.0),
+        "km" => Some(1000.0),
+        "ft" => Some(0.3048),
+        "mi" | "miles" => Some(1609.344),
+        _ => None,
+    }
+}
+
+/// Seconds in a FitNotes duration: "h:mm:ss", "mm:ss" or plain seconds.
+fn parse_duration(text: &str) -> Result<Option<i64>, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    let mut seconds = 0;
+    for part in text.split(':') {
+        let value: i64 = part.trim().parse().map_err(|_| format!("not a duration: {text:?}"))?;
+        seconds = seconds * 60 + value;
+    }
+    Ok((seconds > 0).then_some(seconds))
+}
+
+/// Parses a FitNotes CSV export. The weight unit comes from the weight
+/// column's header ("Weight (kgs)" or "Weight (lbs)") when it says, else
+/// from `weight_unit`; distances use the row's distance unit when there is
+/// one and are converted to `distance`.
+pub fn parse_fitnotes_csv(text: &str, weight_unit: WeightUnit, distance: DistanceUnit) -> Result<ImportFile> {
+    let Some(table) = csv_import::read_csv(text) else {
+        bail!("the file is empty");
+    };
+    let (Some(date), Some(exercise)) = (table.column("Date"), table.column("Exercise")) else {
+        bail!("not a FitNotes export: the header needs \"Date\" and \"Exercise\" columns");
+    };
+    let (category_col, weight, reps, distance_col, distance_unit, time) = (
+        table.column("Category"),
+        table.column("Weight"),
+        table.column("Reps"),
+        table.column("Distance"),
+        table.column("Distance Unit"),
+        table.column("Time"),
+    );
+    if reps.is_none() && time.is_none() && distance_col.is_none() {
+        bail!("not a FitNotes export: no Reps, Distance or Time column");
+    }
+    let weight_unit = match weight.map(|i| table.columns[i].to_lowercase()) {
+        Some(header) if header.contains("lbs") => WeightUnit::Lbs,
+        Some(header) if header.contains("kg") => WeightUnit::Kg,
+        _ => weight_unit,
+    };
+
+    let mut file = ImportFile {
+        header: table.header,
+        delimiter: table.delimiter,
+        sets: Vec::new(),
+        rejects: Vec::new(),
+    };
+    let mut day: Option<(NaiveDateTime, i64)> = None;
+    for (line, raw, fields) in table.records {
+        let field = |index: Option<usize>| index.and_then(|i| fields.get(i)).map_or("", |f| f.trim());
+        let number = |index: Option<usize>| csv_import::parse_number(field(index));
+        let parsed = (|| {
+            let noon = dates::parse_date(field(Some(date)))
+                .and_then(|d| d.and_hms_opt(SET_HOUR, 0, 0))
+                .ok_or_else(|| format!("unreadable date {:?}", field(Some(date))))?;
+            let name = field(Some(exercise));
+            if name.is_empty() {
+                return Err("no exercise name".to_string());
+            }
+            let meters = match number(distance_col)? {
+                Some(value) => {
+                    let unit = field(distance_unit);
+                    let per = if unit.is_empty() {
+                        distance.meters_per_unit()
+                    } else {
+                        meters_per(unit).ok_or_else(|| format!("unknown distance unit {unit:?}"))?
+                    };
+                    Some(value * per)
+                }
+                None => None,
+            };
+            Ok(ImportSet {
+                line,
+                raw: raw.clone(),
+                at: noon,
+                exercise: name.to_string(),
+                category: category(field(category_col)).map(str::to_string),
+                reps: number(reps)?.map(|r| r.round() as i64),
+                weight: number(weight)?.map(|w| csv_import::to_kg(w, weight_unit)),
+                seconds: parse_duration(field(time))?,
+                distance: meters.map(|m| m / distance.meters_per_unit()),
+                rpe: None,
+                uuid: None,
+            })
+        })();
+        match parsed {
+            Ok(mut set) => {
+                let index = match day {
+                    Some((noon, index)) if noon == set.at => index + 1,
+                    _ => 0,
+                };
+                day = Some((set.at, index));
+                set.at += Duration::seconds(index);
+                file.sets.push(set);
+            }
+            Err(reason) => file.rejects.push(Reject { line, raw, reason }),
+        }
+    }
+    Ok(file)
+}
+
+pub fn read_fitnotes_csv(path: &Path, weight_unit: WeightUnit, distance: DistanceUnit) -> Result<ImportFile> {
+    let text = csv_import::read_file(path)?;
+    parse_fitnotes_csv(&text, weight_unit, distance).with_context(|| format!("failed to import {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units;
+    use crate::Database;
+    use std::collections::HashMap;
+
+    const EXPORT: &str = "Date,Exercise,Category,Weight (kgs),Reps,Distance,Distance Unit,Time,Comment
+2024-05-01,Barbell Squat,Legs,60.0,10,,,,
+2024-05-01,Barbell Squat,Legs,62.5,8,,,,\"felt heavy, stopped\"
+2024-05-01,Plank,Abs,,,,,0:01:30,
+2024-05-01,Running,Cardio,,,5.2,km,0:28:00,
+2024-05-02,Pull Up,Back,,12,,,,
+2024-05-02,Rowing,Cardio,,,2000,m,,
+yesterday,Pull Up,Back,,12,,,,
+2024-05-03,Pull Up,Back,,ten,,,,
+";
+
+    fn at(s: &str) -> NaiveDateTime {
+        dates::parse_timestamp(s).unwrap()
+    }
+
+    #[test]
+    fn parses_sets_with_units_and_order_within_a_day() {
+        let file = parse_fitnotes_csv(EXPORT, WeightUnit::Kg, DistanceUnit::Km).unwrap();
+        assert_eq!(file.sets.len(), 6);
+        let times: Vec<NaiveDateTime> = file.sets.iter().map(|s| s.at).collect();
+        assert_eq!(
+            times,
+            [
+                at("2024-05-01 12:00:00"),
+                at("2024-05-01 12:00:01"),
+                at("2024-05-01 12:00:02"),
+                at("2024-05-01 12:00:03"),
+                at("2024-05-02 12:00:00"),
+                at("2024-05-02 12:00:01"),
+            ]
+        );
+        assert_eq!(file.sets[1].weight, Some(62.5));
+        assert_eq!(file.sets[1].reps, Some(8));
+        assert_eq!(file.sets[1].category.as_deref(), Some("legs"));
+        assert_eq!(file.sets[2].seconds, Some(90));
+        assert_eq!(file.sets[2].category.as_deref(), Some("core"));
+        assert_eq!(file.sets[3].distance, Some(5.2));
+        assert_eq!(file.sets[3].category, None);
+        assert_eq!(file.sets[5].distance, Some(2.0));
+        let reasons: Vec<(usize, &str)> = file.rejects.iter().map(|r| (r.line, r.reason.as_str())).collect();
+        assert_eq!(reasons, [(8, "unreadable date \"yesterday\""), (9, "not a number: \"ten\"")]);
+    }
+
+    #[test]
+    fn weight_unit_comes_from_the_header() {
+        let text = "Date,Exercise,Category,Weight (lbs),Reps\n2024-05-01,Deadlift,Back,100,5\n";
+        let file = parse_fitnotes_csv(text, WeightUnit::Kg, DistanceUnit::Km).unwrap();
+        assert!((file.sets[0].weight.unwrap() - 45.359237).abs() < 1e-9);
+        let text = "Date,Exercise,Weight,Reps\n2024-05-01,Deadlift,100,5\n";
+        let file = parse_fitnotes_csv(text, WeightUnit::Lbs, DistanceUnit::Km).unwrap();
+        assert!((file.sets[0].weight.unwrap() - 45.359237).abs() < 1e-9);
+    }
+
+    #[test]
+    fn distances_convert_to_the_configured_unit() {
+        let text = "Date,Exercise,Distance,Distance Unit\n2024-05-01,Running,1,mi\n2024-05-01,Running,3,\n";
+        let file = parse_fitnotes_csv(text, WeightUnit::Kg, DistanceUnit::Miles).unwrap();
+        assert_eq!(file.sets[0].distance, Some(1.0));
+        assert_eq!(file.sets[1].distance, Some(3.0));
+        let text = "Date,Exercise,Distance,Distance Unit\n2024-05-01,Running,1,furlong\n";
+        let file = parse_fitnotes_csv(text, WeightUnit::Kg, DistanceUnit::Km).unwrap();
+        assert_eq!(file.rejects[0].reason, "unknown distance unit \"furlong\"");
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("0:01:30"), Ok(Some(90)));
+        assert_eq!(parse_duration("02:05"), Ok(Some(125)));
+        assert_eq!(parse_duration("45"), Ok(Some(45)));
+        assert_eq!(parse_duration("0:00:00"), Ok(None));
+        assert_eq!(parse_duration(""), Ok(None));
+        assert!(parse_duration("1:xx").is_err());
+    }
+
+    #[test]
+    fn other_csv_files_are_refused() {
+        assert!(parse_fitnotes_csv("Start Date,End Date\n", WeightUnit::Kg, DistanceUnit::Km).is_err());
+        assert!(parse_fitnotes_csv("Date,Exercise,Comment\n", WeightUnit::Kg, DistanceUnit::Km).is_err());
+        assert!(parse_fitnotes_csv("", WeightUnit::Kg, DistanceUnit::Km).is_err());
+    }
+
+    #[test]
+    fn imports_with_categories_and_skips_duplicates() {
+        let db = Database::new(":memory:").unwrap();
+        let file = parse_fitnotes_csv(EXPORT, WeightUnit::Kg, DistanceUnit::Km).unwrap();
+        let mut mapping: HashMap<String, String> = file.sets.iter().map(|s| (s.exercise.clone(), s.exercise.clone())).collect();
+        mapping.insert("Barbell Squat".to_string(), "squats".to_string());
+        let report = db.import_sets(&file.sets, &mapping, DistanceUnit::Km, "FitNotes").unwrap();
+        let summary: Vec<(&str, &str, usize, i64)> = report
+            .exercises
+            .iter()
+            .map(|e| (e.name.as_str(), e.unit.as_str(), e.sets, e.total))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("squats", "reps", 2, 18),
+                ("Plank", units::SECONDS, 1, 90),
+                ("Running", units::DISTANCE, 1, 5200),
+                ("Pull Up", "reps", 1, 12),
+                ("Rowing", units::DISTANCE, 1, 2000),
+            ]
+        );
+        let category: Option<String> = db
+            .conn
+            .query_row("SELECT category FROM exercises WHERE name = 'Pull Up'", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(category.as_deref(), Some("pull"));
+
+        let again = db.import_sets(&file.sets, &mapping, DistanceUnit::Km, "FitNotes").unwrap();
+        assert!(again.exercises.is_empty());
+        assert_eq!(again.duplicates, 6);
+    }
+}
@@ -0,0 +1,495 @@
+//! Imports from any CSV, such as a spreadsheet kept by hand: each field is
+//! mapped to one of the file's columns on the terminal, and the mapping can
+//! be saved under a name to pick with one key next time. The file is read
+//! in the configured CSV dialect.
+
+use anyhow::{bail, Context, Result};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{self, BufRead, IsTerminal, Write},
+    path::Path,
+};
+
+use crate::csv_import::{self, CsvDialect, CsvTable, ImportFile, ImportSet, Reject};
+use crate::units::{DistanceUnit, WeightUnit};
+use crate::Database;
+
+/// Sets whose timestamp has no time of day are placed at this hour.
+const SET_HOUR: u32 = 12;
+
+/// Which column each field comes from, by header name so a saved mapping
+/// still fits when the columns move around.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnMapping {
+    /// `None` when every row is `fixed_exercise`.
+    pub exercise: Option<String>,
+    pub fixed_exercise: Option<String>,
+    /// Amount as the CSV export writes it: reps, seconds or meters,
+    /// whichever the exercise counts.
+    pub count: String,
+    pub timestamp: String,
+    /// chrono format of the timestamp column; a date-only format puts the
+    /// sets at noon.
+    pub timestamp_format: String,
+    pub weight: Option<String>,
+    #[serde(default)]
+    pub rpe: Option<String>,
+    /// Keeps the identity of sets exported from here, so they're matched
+    /// to the same sets rather than by their values.
+    #[serde(default)]
+    pub uuid: Option<String>,
+}
+
+/// A timestamp in `format`, or a date in it at noon, with whether it was
+/// only a date.
+fn parse_timestamp(text: &str, format: &str) -> Option<(NaiveDateTime, bool)> {
+    match NaiveDateTime::parse_from_str(text, format) {
+        Ok(at) => Some((at, false)),
+        Err(_) => Some((NaiveDate::parse_from_str(text, format).ok()?.and_hms_opt(SET_HOUR, 0, 0)?, true)),
+    }
+}
+
+/// Parses `table` with `mapping`. Fails only when a mapped column isn't in
+/// the header; sets on the same date without a time are spread a second
+/// apart in file order, so equal sets aren't taken for duplicates of each
+/// other.
+pub fn parse_mapped_csv(table: CsvTable, mapping: &ColumnMapping, weight_unit: WeightUnit, distance: DistanceUnit) -> Result<ImportFile> {
+    let column = |name: &str| {
+        table
+            .columns
+            .iter()
+            .position(|c| c.trim() == name)
+            .with_context(|| format!("the file has no {name:?} column"))
+    };
+    let exercise = mapping.exercise.as_deref().map(column).transpose()?;
+    let (count, timestamp) = (column(&mapping.count)?, column(&mapping.timestamp)?);
+    let weight = mapping.weight.as_deref().map(column).transpose()?;
+    let rpe = mapping.rpe.as_deref().map(column).transpose()?;
+    let uuid = mapping.uuid.as_deref().map(column).transpose()?;
+    let weight_unit = match weight.map(|i| table.columns[i].to_lowercase()) {
+        Some(header) if header.contains("lbs") => WeightUnit::Lbs,
+        Some(header) if header.contains("kg") => WeightUnit::Kg,
+        _ => weight_unit,
+    };
+
+    let mut file = ImportFile {
+        header: table.header,
+        delimiter: table.delimiter,
+        sets: Vec::new(),
+        rejects: Vec::new(),
+    };
+    let mut previous: Option<(NaiveDateTime, i64)> = None;
+    for (line, raw, fields) in table.records {
+        let field = |index: Option<usize>| index.and_then(|i| fields.get(i)).map_or("", |f| f.trim());
+        let parsed = (|| {
+            let (at, date_only) = parse_timestamp(field(Some(timestamp)), &mapping.timestamp_format)
+                .ok_or_else(|| format!("unreadable timestamp {:?}", field(Some(timestamp))))?;
+            let name = match exercise {
+                Some(_) => field(exercise),
+                None => mapping.fixed_exercise.as_deref().unwrap_or_default(),
+            };
+            if name.is_empty() {
+                return Err("no exercise name".to_string());
+            }
+            // The exercise's unit decides which one is used
+            let amount = csv_import::parse_number(field(Some(count)))?;
+            let set = ImportSet {
+                line,
+                raw: raw.clone(),
+                at,
+                exercise: name.to_string(),
+                category: None,
+                reps: amount.map(|a| a.round() as i64),
+                weight: csv_import::parse_number(field(weight))?.map(|w| csv_import::to_kg(w, weight_unit)),
+                seconds: amount.map(|a| a.round() as i64),
+                distance: amount.map(|meters| meters / distance.meters_per_unit()),
+                rpe: csv_import::parse_number(field(rpe))?,
+                uuid: csv_import::parse_uuid(field(uuid))?,
+            };
+            Ok((set, date_only))
+        })();
+        match parsed {
+            Ok((mut set, date_only)) => {
+                if date_only {
+                    let index = match previous {
+                        Some((at, index)) if at == set.at => index + 1,
+                        _ => 0,
+                    };
+                    previous = Some((set.at, index));
+                    set.at += Duration::seconds(index);
+                }
+                file.sets.push(set);
+            }
+            Err(reason) => file.rejects.push(Reject { line, raw, reason }),
+        }
+    }
+    Ok(file)
+}
+
+impl Database {
+    /// Saved column mappings by name, alphabetically.
+    pub fn csv_mappings(&self) -> Result<Vec<(String, ColumnMapping)>> {
+        let mut stmt = self.conn.prepare("SELECT name, mapping FROM csv_mappings ORDER BY name")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        // A mapping that no longer parses is left out rather than failing the import
+        Ok(rows
+            .into_iter()
+            .filter_map(|(name, json)| Some((name, serde_json::from_str(&json).ok()?)))
+            .collect())
+    }
+
+    pub fn csv_mapping(&self, name: &str) -> Result<Option<ColumnMapping>> {
+        let json: Option<String> = self
+            .conn
+            .query_row("SELECT mapping FROM csv_mappings WHERE name = ?1", [name], |row| row.get(0))
+            .optional()?;
+        json.map(|json| serde_json::from_str(&json).with_context(|| format!("saved mapping {name:?} is unreadable")))
+            .transpose()
+    }
+
+    pub fn save_csv_mapping(&self, name: &str, mapping: &ColumnMapping) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO csv_mappings (name, mapping) VALUES (?1, ?2)
+             ON CONFLICT(name) DO UPDATE SET mapping = excluded.mapping",
+            params![name, serde_json::to_string(mapping)?],
+        )?;
+        Ok(())
+    }
+}
+
+fn ask(prompt: &str) -> Result<String> {
+    print!("{prompt}");
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(answer.trim().to_string())
+}
+
+/// Asks for a column by its number in the list. Enter takes `default`
+/// (a guess from the header names), or skips the field when `optional`
+/// and there's no guess.
+fn ask_column(table: &CsvTable, field: &str, default: Option<usize>, optional: bool) -> Result<Option<usize>> {
+    let hint = match (default, optional) {
+        (Some(i), _) => format!("Enter for {}", i + 1),
+        (None, true) => "Enter for none".to_string(),
+        (None, false) => "number".to_string(),
+    };
+    loop {
+        let answer = ask(&format!("{field} column [{hint}]: "))?;
+        if answer.is_empty() && (default.is_some() || optional) {
+            return Ok(default);
+        }
+        match answer.parse::<usize>() {
+            Ok(n) if (1..=table.columns.len()).contains(&n) => return Ok(Some(n - 1)),
+            _ => println!("  Pick a column from 1 to {}", table.columns.len()),
+        }
+    }
+}
+
+/// Walks through each field on the terminal and offers to save the result.
+fn ask_mapping(db: &Database, table: &CsvTable, timestamp_format: &str) -> Result<ColumnMapping> {
+    println!("Columns:");
+    let sample = table.records.first().map(|(_, _, fields)| fields);
+    for (i, column) in table.columns.iter().enumerate() {
+        match sample.and_then(|fields| fields.get(i)).filter(|value| !value.trim().is_empty()) {
+            Some(value) => println!("  {}) {}  (e.g. {})", i + 1, column.trim(), value.trim()),
+            None => println!("  {}) {}", i + 1, column.trim()),
+        }
+    }
+    let name = |index: Option<usize>| index.map(|i| table.columns[i].trim().to_string());
+    let guess = |prefixes: &[&str]| prefixes.iter().find_map(|prefix| table.column(prefix));
+
+    let exercise = name(ask_column(table, "Exercise", guess(&["exercise"]), true)?);
+    let fixed_exercise = match exercise {
+        Some(_) => None,
+        None => loop {
+            let answer = ask("No exercise column; exercise for every row: ")?;
+            if !answer.is_empty() {
+                break Some(answer);
+            }
+        },
+    };
+    let count = name(ask_column(table, "Count", guess(&["count", "reps"]), false)?).unwrap_or_default();
+    let timestamp = name(ask_column(table, "Timestamp", guess(&["timestamp", "date", "time"]), false)?).unwrap_or_default();
+    let format = ask(&format!("Timestamp format, e.g. %d.%m.%Y for dates only [Enter for {timestamp_format}]: "))?;
+    let weight = name(ask_column(table, "Weight", guess(&["weight"]), true)?);
+    let rpe = name(ask_column(table, "RPE", guess(&["rpe"]), true)?);
+    let uuid = name(ask_column(table, "UUID", guess(&["uuid"]), true)?);
+    let mapping = ColumnMapping {
+        exercise,
+        fixed_exercise,
+        count,
+        timestamp,
+        timestamp_format: if format.is_empty() { timestamp_format.to_string() } else { format },
+        weight,
+        rpe,
+        uuid,
+    };
+    let save_as = ask("Save this mapping as [Enter to skip]: ")?;
+    if !save_as.is_empty() {
+        db.save_csv_mapping(&save_as, &mapping)?;
+        println!("Saved; next time pick it from the list or pass --mapping {save_as:?}");
+    }
+    Ok(mapping)
+}
+
+/// The mapping to import with: the one named, a saved one picked by its
+/// number, or a new one asked for field by field.
+fn choose_mapping(db: &Database, table: &CsvTable, named: Option<&str>, timestamp_format: &str) -> Result<ColumnMapping> {
+    if let Some(name) = named {
+        return db.csv_mapping(name)?.with_context(|| format!("no saved mapping named {name:?}"));
+    }
+    if !io::stdin().is_terminal() {
+        bail!("no terminal to map the columns on; pass --mapping with a saved one");
+    }
+    let saved = db.csv_mappings()?;
+    if saved.is_empty() {
+        return ask_mapping(db, table, timestamp_format);
+    }
+    println!("Saved mappings:");
+    for (i, (name, _)) in saved.iter().enumerate() {
+        println!("  {}) {name}", i + 1);
+    }
+    let answer = ask("Use a saved mapping [number, Enter for a new one]: ")?;
+    match answer.parse::<usize>().ok().and_then(|n| n.checked_sub(1)).and_then(|i| saved.get(i)) {
+        Some((_, mapping)) => Ok(mapping.clone()),
+        None => ask_mapping(db, table, timestamp_format),
+    }
+}
+
+/// Reads a CSV in `dialect` and maps its columns for the import-csv
+/// command. A `timestamp_format` given on the command line wins over the
+/// mapping's.
+pub fn read_mapped_csv(
+    db: &Database,
+    path: &Path,
+    mapping: Option<&str>,
+    dialect: &CsvDialect,
+    timestamp_format: Option<&str>,
+    weight_unit: WeightUnit,
+    distance: DistanceUnit,
+) -> Result<ImportFile> {
+    dialect.check()?;
+    let text = csv_import::read_file(path)?;
+    let Some(table) = csv_import::read_csv_dialect(&text, dialect) else {
+        bail!("failed to import {}: the file is empty", path.display());
+    };
+    let mut mapping = choose_mapping(db, &table, mapping, &dialect.timestamp_format)?;
+    if let Some(format) = timestamp_format {
+        mapping.timestamp_format = format.to_string();
+    }
+    parse_mapped_csv(table, &mapping, weight_unit, distance).with_context(|| format!("failed to import {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::ExportOptions;
+    use crate::sets_export::SetRow;
+    use crate::units;
+    use chrono::NaiveDate;
+    use std::collections::HashMap;
+
+    const FARMERS: &str = "Farmer's walk; \"heavy\", 1.5";
+
+    fn with_exercises() -> Database {
+        let db = Database::new(":memory:").unwrap();
+        for (name, unit) in [("plank", units::SECONDS), ("running", units::DISTANCE), (FARMERS, "reps")] {
+            db.add_exercise(name).unwrap();
+            db.set_exercise_unit(name, unit).unwrap();
+        }
+        db
+    }
+
+    fn logged() -> Database {
+        let db = with_exercises();
+        for (timestamp, exercise, count, rpe, weight) in [
+            ("2024-05-01 08:00:00", "squats", 10, Some(8), Some(62.5)),
+            ("2024-05-01 08:02:30", "squats", 8, None, Some(100.25)),
+            ("2024-05-01 08:10:00", "plank", 90, None, None),
+            ("2024-05-02 18:00:00", "running", 5210, Some(6), None),
+            ("2024-05-03 07:00:05", FARMERS, 40, Some(9), Some(0.1)),
+            ("2024-05-03 07:01:00", "push-ups", 20, None, None),
+        ] {
+            db.conn
+                .execute(
+                    "INSERT INTO workouts (exercise_type, count, timestamp, rpe, weight) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![exercise, count, timestamp, rpe, weight],
+                )
+                .unwrap();
+        }
+        db
+    }
+
+    fn options(dialect: &CsvDialect) -> ExportOptions {
+        ExportOptions {
+            from: NaiveDate::from_ymd_opt(2024, 5, 1),
+            to: NaiveDate::from_ymd_opt(2024, 5, 3),
+            csv: dialect.clone(),
+            ..ExportOptions::default()
+        }
+    }
+
+    /// Exports in `dialect`, imports the file into a fresh database with
+    /// the same exercises, and returns both sides.
+    fn round_trip(dialect: &CsvDialect) -> (String, Vec<SetRow>, Vec<SetRow>) {
+        let source = logged();
+        let mut out = Vec::new();
+        source.write_sets_csv(&mut out, &options(dialect)).unwrap();
+        let text = String::from_utf8(out).unwrap();
+
+        let table = csv_import::read_csv_dialect(&text, dialect).unwrap();
+        let column = |name: &str, index: usize| if dialect.header { name.to_string() } else { format!("Column {index}") };
+        let mapping = ColumnMapping {
+            exercise: Some(column("exercise", 2)),
+            fixed_exercise: None,
+            count: column("count", 3),
+            timestamp: column("timestamp", 1),
+            timestamp_format: dialect.timestamp_format.clone(),
+            weight: Some(column("weight_kg", 6)),
+            rpe: Some(column("rpe", 5)),
+            uuid: Some(column("uuid", 7)),
+        };
+        let file = parse_mapped_csv(table, &mapping, WeightUnit::Kg, DistanceUnit::Km).unwrap();
+        assert!(file.rejects.is_empty(), "{:?}", file.rejects);
+
+        let target = with_exercises();
+        let report = target.import_sets(&file.sets, &HashMap::new(), DistanceUnit::Km, "CSV").unwrap();
+        assert!(report.rejects.is_empty(), "{:?}", report.rejects);
+        let options = options(&CsvDialect::default());
+        (text, source.export_sets(&options).unwrap(), target.export_sets(&options).unwrap())
+    }
+
+    #[test]
+    fn every_dialect_round_trips() {
+        let dialects = [
+            CsvDialect::default(),
+            CsvDialect {
+                delimiter: ';',
+                decimal: ',',
+                timestamp_format: "%d.%m.%Y %H:%M:%S".to_string(),
+                ..CsvDialect::default()
+            },
+            CsvDialect {
+                delimiter: '\t',
+                quote: '\'',
+                header: false,
+                ..CsvDialect::default()
+            },
+            CsvDialect {
+                decimal: ',',
+                timestamp_format: "%Y-%m-%dT%H:%M:%S".to_string(),
+                ..CsvDialect::default()
+            },
+            CsvDialect {
+                delimiter: '|',
+                header: false,
+                timestamp_format: "%m/%d/%Y %I:%M:%S %p".to_string(),
+                ..CsvDialect::default()
+            },
+        ];
+        for dialect in &dialects {
+            let (text, exported, imported) = round_trip(dialect);
+            assert_eq!(exported.len(), 6);
+            assert_eq!(imported, exported, "{dialect:?}\n{text}");
+        }
+    }
+
+    #[test]
+    fn uuids_keep_the_identity_of_sets() {
+        let source = logged();
+        let mut out = Vec::new();
+        source.write_sets_csv(&mut out, &options(&CsvDialect::default())).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let mapping = ColumnMapping {
+            exercise: Some("exercise".to_string()),
+            fixed_exercise: None,
+            count: "count".to_string(),
+            timestamp: "timestamp".to_string(),
+            timestamp_format: CsvDialect::default().timestamp_format,
+            weight: Some("weight_kg".to_string()),
+            rpe: Some("rpe".to_string()),
+            uuid: Some("uuid".to_string()),
+        };
+        let import = |db: &Database, mapping: &ColumnMapping| {
+            let file = parse_mapped_csv(csv_import::read_csv(&text).unwrap(), mapping, WeightUnit::Kg, DistanceUnit::Km).unwrap();
+            db.import_sets(&file.sets, &HashMap::new(), DistanceUnit::Km, "CSV").unwrap()
+        };
+
+        // An edited set is still the same set
+        let target = with_exercises();
+        assert_eq!(import(&target, &mapping).added(), 6);
+        target.conn.execute("UPDATE workouts SET count = 12 WHERE count = 10", []).unwrap();
+        let again = import(&target, &mapping);
+        assert_eq!((again.added(), again.duplicates), (0, 6));
+
+        // Without the column, sets are matched by their values and get new UUIDs
+        let without = ColumnMapping { uuid: None, ..mapping.clone() };
+        let fresh = with_exercises();
+        import(&fresh, &without);
+        let uuids = |db: &Database| db.export_sets(&options(&CsvDialect::default())).unwrap().into_iter().map(|set| set.uuid).collect::<Vec<_>>();
+        assert!(uuids(&fresh).iter().all(|uuid| uuid.is_some() && !uuids(&source).contains(uuid)));
+        assert_eq!(import(&target, &without).added(), 1, "only the edited set no longer matches");
+
+        let bad = text.replacen(",reps,8,62.5,", ",reps,8,62.5,not-a-uuid", 1);
+        let file = parse_mapped_csv(csv_import::read_csv(&bad).unwrap(), &mapping, WeightUnit::Kg, DistanceUnit::Km).unwrap();
+        assert_eq!(file.rejects.len(), 1);
+    }
+
+    #[test]
+    fn exports_in_the_dialect() {
+        let dialect = CsvDialect {
+            delimiter: ';',
+            decimal: ',',
+            timestamp_format: "%d.%m.%Y %H:%M".to_string(),
+            ..CsvDialect::default()
+        };
+        let write = |dialect: &CsvDialect| {
+            let mut out = Vec::new();
+            logged().write_sets_csv(&mut out, &options(dialect)).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let text = write(&dialect);
+        let lines: Vec<&str> = text.lines().collect();
+        // Leaves out the UUIDs, which differ with every database
+        let lines: Vec<&str> = lines.iter().map(|line| line.rsplit_once(';').unwrap().0).collect();
+        assert_eq!(lines[0], "timestamp;exercise;count;unit;rpe;weight_kg");
+        assert_eq!(lines[2], "01.05.2024 08:02;squats;8;reps;;100,25");
+        assert_eq!(lines[5], "03.05.2024 07:00;\"Farmer's walk; \"\"heavy\"\", 1.5\";40;reps;9;0,1");
+        assert!(text.starts_with("timestamp;exercise;count;unit;rpe;weight_kg;uuid\n"));
+        let headless = write(&CsvDialect { header: false, ..dialect });
+        assert!(headless.starts_with("01.05.2024 08:00;squats;10;reps;8;62,5;"));
+    }
+
+    #[test]
+    fn a_comma_decimal_is_quoted_in_comma_separated_files() {
+        let dialect = CsvDialect { decimal: ',', ..CsvDialect::default() };
+        assert_eq!(dialect.number(62.5), "\"62,5\"");
+        assert_eq!(dialect.number(60.0), "60");
+        assert!(CsvDialect { quote: ',', ..CsvDialect::default() }.check().is_err());
+        assert!(CsvDialect { decimal: ';', ..CsvDialect::default() }.check().is_err());
+    }
+
+    #[test]
+    fn dates_without_times_are_spread_over_noon() {
+        let text = "day,what,n\n01.05.2024,squats,10\n01.05.2024,squats,10\n02.05.2024,squats,12\n";
+        let table = csv_import::read_csv(text).unwrap();
+        let mapping = ColumnMapping {
+            exercise: Some("what".to_string()),
+            fixed_exercise: None,
+            count: "n".to_string(),
+            timestamp: "day".to_string(),
+            timestamp_format: "%d.%m.%Y".to_string(),
+            weight: None,
+            rpe: None,
+            uuid: None,
+        };
+        let file = parse_mapped_csv(table, &mapping, WeightUnit::Kg, DistanceUnit::Km).unwrap();
+        let times: Vec<String> = file.sets.iter().map(|set| set.at.to_string()).collect();
+        assert_eq!(times, ["2024-05-01 12:00:00", "2024-05-01 12:00:01", "2024-05-02 12:00:00"]);
+    }
+}
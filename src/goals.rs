@@ -0,0 +1,413 @@
+use anyhow::Result;
+use chrono::{Duration, Local, NaiveDate};
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{
+        block::{Position, Title},
+        Block, Borders, Cell, Row, Table,
+    },
+    Frame,
+};
+use rusqlite::params;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::deadline_goals::{self, DeadlineForm};
+use crate::exercises::render_dialog;
+use crate::input::NumericInput;
+use crate::notify::NotifyEvent;
+use crate::units;
+use crate::{App, Database, Screen};
+
+const BAR_WIDTH: usize = 20;
+
+/// Days before today a suggested goal is based on.
+const SUGGEST_WINDOW_DAYS: i64 = 28;
+/// Fewer days with sets than this in the window give no suggestion.
+const SUGGEST_MIN_DAYS: usize = 7;
+
+#[derive(Debug, Default)]
+pub struct GoalsState {
+    pub selected: usize,
+    /// Daily target being typed for the selected exercise.
+    pub editing: Option<NumericInput>,
+    /// Suggested target for the named exercise, waiting for confirmation;
+    /// `None` inside when there's too little history to suggest one.
+    pub suggestion: Option<(String, Option<GoalSuggestion>)>,
+    /// Deadline goal being set up for the selected exercise.
+    pub deadline: Option<DeadlineForm>,
+}
+
+/// A daily target proposed from recent history.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoalSuggestion {
+    /// Average total on the days with sets.
+    pub average: f64,
+    pub active_days: usize,
+    pub target: i64,
+}
+
+/// Proposes a daily target from per-day totals: the average over the days
+/// with sets among the `SUGGEST_WINDOW_DAYS` before `today`, raised by
+/// `progression_percent`. Today doesn't count since it isn't over, and too
+/// few active days give no suggestion at all.
+pub fn suggest_target(days: &[(NaiveDate, i64)], today: NaiveDate, progression_percent: u32) -> Option<GoalSuggestion> {
+    let from = today - Duration::days(SUGGEST_WINDOW_DAYS);
+    let mut totals: BTreeMap<NaiveDate, i64> = BTreeMap::new();
+    for (date, total) in days.iter().filter(|(date, _)| (from..today).contains(date)) {
+        *totals.entry(*date).or_default() += total;
+    }
+    totals.retain(|_, total| *total > 0);
+    if totals.len() < SUGGEST_MIN_DAYS {
+        return None;
+    }
+    let average = totals.values().sum::<i64>() as f64 / totals.len() as f64;
+    let target = (average * (1.0 + progression_percent as f64 / 100.0)).round() as i64;
+    Some(GoalSuggestion {
+        average,
+        active_days: totals.len(),
+        target: target.max(1),
+    })
+}
+
+impl Database {
+    /// Daily targets by exercise, in the exercise's stored unit.
+    pub fn get_goals(&self) -> Result<HashMap<String, i64>> {
+        let mut stmt = self.conn.prepare("SELECT exercise, daily_target FROM goals")?;
+        let goals = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        Ok(goals)
+    }
+
+    /// Sets or, with `None`, removes an exercise's daily target.
+    pub fn set_goal(&self, exercise: &str, target: Option<i32>) -> Result<()> {
+        match target {
+            Some(target) => self.conn.execute(
+                "INSERT OR REPLACE INTO goals (exercise, daily_target) VALUES (?1, ?2)",
+                params![exercise, target],
+            )?,
+            None => self.conn.execute("DELETE FROM goals WHERE exercise = ?1", [exercise])?,
+        };
+        Ok(())
+    }
+
+    /// Total per day for one exercise from `since` onwards.
+    pub fn exercise_daily_totals(&self, exercise: &str, since: NaiveDate) -> Result<Vec<(NaiveDate, i64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT day, total FROM daily_totals
+             WHERE exercise_type = ?1 AND day >= ?2",
+        )?;
+        let rows = stmt
+            .query_map(params![exercise, since.format("%Y-%m-%d").to_string()], |row| {
+                Ok((row.get::<_, String>(0)?, row.get(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(day, total)| Some((NaiveDate::parse_from_str(&day, "%Y-%m-%d").ok()?, total)))
+            .collect())
+    }
+
+    /// Amount logged today per exercise.
+    pub fn get_today_totals(&self) -> Result<HashMap<String, i64>> {
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        let mut stmt = self.conn.prepare(
+            "SELECT exercise_type, total FROM daily_totals
+             WHERE day = ?1",
+        )?;
+        let totals = stmt
+            .query_map([today], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        Ok(totals)
+    }
+}
+
+/// Text progress bar with a percentage, capped at full.
+pub(crate) fn progress_bar(total: i64, target: i64) -> String {
+    let ratio = (total as f64 / target as f64).clamp(0.0, 1.0);
+    let filled = (ratio * BAR_WIDTH as f64).round() as usize;
+    format!(
+        "{}{} {:>3}%",
+        "█".repeat(filled),
+        "░".repeat(BAR_WIDTH - filled),
+        (total * 100 / target).min(999)
+    )
+}
+
+impl App {
+    /// Called after a set is saved; announces the goal when this set is the
+    /// one that crossed it.
+    pub(crate) fn check_goal(&mut self, exercise: &str, added: i64) -> Result<()> {
+        let Some(target) = self.db.get_goals()?.get(exercise).copied() else {
+            return Ok(());
+        };
+        let total = self.db.get_today_totals()?.get(exercise).copied().unwrap_or(0);
+        if total >= target && total - added < target {
+            let target = self.format_count(exercise, target);
+            let message = self.text.format("goals.reached", &[("exercise", &exercise), ("target", &target)]);
+            self.notify(NotifyEvent::GoalReached, &message);
+            self.message = Some(message);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn open_goals(&mut self) -> Result<()> {
+        self.screen = Screen::Goals;
+        self.goals_screen = GoalsState::default();
+        let closed = self.db.close_deadline_goals(Local::now().date_naive())?;
+        self.message = (!closed.is_empty()).then(|| self.text.format("goals.deadlines_closed", &[("count", &closed.len())]));
+        Ok(())
+    }
+
+    pub(crate) fn handle_goals_input(&mut self, key: KeyCode) -> Result<bool> {
+        let exercises: Vec<_> = self.picker_exercises().into_iter().cloned().collect();
+        if self.goals_screen.deadline.is_some() {
+            if let Some(exercise) = exercises.get(self.goals_screen.selected) {
+                self.handle_deadline_form_input(key, &exercise.name)?;
+            }
+            return Ok(false);
+        }
+        let state = &mut self.goals_screen;
+
+        if let Some((exercise, suggestion)) = state.suggestion.take() {
+            if key == KeyCode::Char('y')
+                && let Some(suggestion) = suggestion
+            {
+                self.db.set_goal(&exercise, Some(suggestion.target as i32))?;
+            }
+            return Ok(false);
+        }
+
+        if let Some(input) = &mut state.editing {
+            match key {
+                KeyCode::Esc => state.editing = None,
+                KeyCode::Enter => {
+                    let text = input.as_str().to_string();
+                    state.editing = None;
+                    let Some(exercise) = exercises.get(state.selected) else {
+                        return Ok(false);
+                    };
+                    let target = units::parse_amount(&text, &exercise.unit, self.config.units.distance);
+                    if text.is_empty() || target.is_some() {
+                        self.db.set_goal(&exercise.name, target)?;
+                    }
+                }
+                _ => {
+                    input.handle_key(key);
+                }
+            }
+            return Ok(false);
+        }
+
+        match key {
+            KeyCode::Esc => {
+                self.screen = Screen::Main;
+                self.message = None;
+            }
+            KeyCode::Up => state.selected = state.selected.saturating_sub(1),
+            KeyCode::Down if state.selected + 1 < exercises.len() => state.selected += 1,
+            KeyCode::Enter => {
+                if let Some(exercise) = exercises.get(state.selected) {
+                    let mut input = NumericInput::default();
+                    input.set_decimal(units::is_decimal(&exercise.unit));
+                    if let Some(target) = self.db.get_goals()?.get(&exercise.name) {
+                        input.set(units::input_text(*target, &exercise.unit, self.config.units.distance));
+                    }
+                    self.goals_screen.editing = Some(input);
+                }
+            }
+            KeyCode::Char('s') => {
+                if let Some(exercise) = exercises.get(state.selected) {
+                    let today = Local::now().date_naive();
+                    let days = self.db.exercise_daily_totals(&exercise.name, today - Duration::days(SUGGEST_WINDOW_DAYS))?;
+                    let suggestion = suggest_target(&days, today, self.config.goals.progression_percent);
+                    self.goals_screen.suggestion = Some((exercise.name.clone(), suggestion));
+                }
+            }
+            KeyCode::Char('n') => {
+                if let Some(exercise) = exercises.get(state.selected) {
+                    self.open_deadline_form(&exercise.name)?;
+                }
+            }
+            KeyCode::Char('x') => {
+                if let Some(exercise) = exercises.get(state.selected)
+                    && self.db.remove_deadline_goal(&exercise.name)?
+                {
+                    self.message = Some(self.text.format("goals.deadline_removed", &[("exercise", &exercise.name)]));
+                }
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+}
+
+pub fn render_goals_screen(f: &mut Frame, area: Rect, app: &App) {
+    let goals = app.db.get_goals().unwrap_or_default();
+    let totals = app.db.get_today_totals().unwrap_or_default();
+    let exercises = app.picker_exercises();
+    let deadlines = app.db.deadline_goals().unwrap_or_default();
+    let (running_height, past_height) = deadline_goals::heights(&deadlines);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(running_height), Constraint::Length(past_height)])
+        .split(area);
+
+    let rows: Vec<Row> = exercises
+        .iter()
+        .enumerate()
+        .map(|(i, exercise)| {
+            let total = totals.get(&exercise.name).copied().unwrap_or(0);
+            let goal = goals.get(&exercise.name).copied();
+            let mut style = Style::default();
+            if i == app.goals_screen.selected {
+                style = style.add_modifier(Modifier::BOLD | Modifier::REVERSED);
+            }
+            let color = match goal {
+                Some(target) if total >= target => Color::Green,
+                _ => app.exercise_color(&exercise.name),
+            };
+            Row::new(vec![
+                Cell::from(exercise.display_name()).style(Style::default().fg(color)),
+                Cell::from(app.format_count(&exercise.name, total)),
+                Cell::from(goal.map(|t| app.format_count(&exercise.name, t)).unwrap_or_else(|| "-".to_string())),
+                Cell::from(goal.map(|t| progress_bar(total, t)).unwrap_or_default()).style(Style::default().fg(color)),
+            ])
+            .style(style)
+        })
+        .collect();
+
+    let mut block = Block::default().borders(Borders::ALL).title(app.text.get("goals.title"));
+    if let Some(message) = &app.message {
+        block = block.title(Title::from(message.as_str()).position(Position::Bottom));
+    }
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(25),
+            Constraint::Percentage(15),
+            Constraint::Percentage(15),
+            Constraint::Min(0),
+        ],
+    )
+    .header(
+        Row::new(vec![
+            app.text.get("common.exercise"),
+            app.text.get("date.today"),
+            app.text.get("goals.daily_goal"),
+            app.text.get("goals.progress"),
+        ])
+        .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+    )
+    .block(block);
+    f.render_widget(table, chunks[0]);
+    if running_height > 0 {
+        deadline_goals::render_running(f, chunks[1], app, &deadlines);
+    }
+    if past_height > 0 {
+        deadline_goals::render_past(f, chunks[2], app, &deadlines);
+    }
+
+    if let (Some(input), Some(exercise)) = (&app.goals_screen.editing, exercises.get(app.goals_screen.selected)) {
+        render_dialog(
+            f,
+            &app.text.format("goals.edit", &[("exercise", &exercise.name)]),
+            &app.text.format("goals.edit_field", &[("input", &input.as_str())]),
+        );
+    }
+
+    if let (Some(form), Some(exercise)) = (&app.goals_screen.deadline, exercises.get(app.goals_screen.selected)) {
+        let cursor = |focused: bool| if focused { "▏" } else { "" };
+        render_dialog(
+            f,
+            &app.text.format("goals.deadline_title", &[("exercise", &exercise.name)]),
+            &app.text.format(
+                "goals.deadline_field",
+                &[
+                    ("target", &format!("{}{}", form.target.as_str(), cursor(!form.on_deadline))),
+                    ("deadline", &format!("{}{}", form.deadline.as_str(), cursor(form.on_deadline))),
+                ],
+            ),
+        );
+    }
+
+    if let Some((exercise, suggestion)) = &app.goals_screen.suggestion {
+        let title = app.text.format("goals.suggest_title", &[("exercise", exercise)]);
+        let text = match suggestion {
+            Some(suggestion) => app.text.format(
+                "goals.suggestion",
+                &[
+                    ("average", &app.format_count(exercise, suggestion.average.round() as i64)),
+                    ("days", &suggestion.active_days),
+                    ("target", &app.format_count(exercise, suggestion.target)),
+                ],
+            ),
+            None => app.text.format("goals.no_suggestion", &[("days", &SUGGEST_MIN_DAYS)]),
+        };
+        render_dialog(f, &title, &text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    /// `totals` on consecutive days ending the day before 2024-05-29.
+    fn history(totals: &[i64]) -> Vec<(NaiveDate, i64)> {
+        let today = date("2024-05-29");
+        totals
+            .iter()
+            .enumerate()
+            .map(|(i, total)| (today - Duration::days(totals.len() as i64 - i as i64), *total))
+            .collect()
+    }
+
+    #[test]
+    fn suggests_the_active_day_average_plus_progression() {
+        let days = history(&[80, 90, 86, 0, 86, 84, 88, 88]);
+        let suggestion = suggest_target(&days, date("2024-05-29"), 10).unwrap();
+        assert_eq!(suggestion.active_days, 7);
+        assert_eq!(suggestion.average, 86.0);
+        assert_eq!(suggestion.target, 95);
+    }
+
+    #[test]
+    fn no_suggestion_with_fewer_than_seven_active_days() {
+        let days = history(&[50, 0, 50, 50, 0, 50, 50, 50, 0]);
+        assert_eq!(suggest_target(&days, date("2024-05-29"), 10), None);
+        assert_eq!(suggest_target(&[], date("2024-05-29"), 10), None);
+    }
+
+    #[test]
+    fn ignores_today_and_days_before_the_window() {
+        let mut days = history(&[10; 7]);
+        days.push((date("2024-05-29"), 1000));
+        days.push((date("2024-05-01"), 1000));
+        days.push((date("2024-04-01"), 1000));
+        let suggestion = suggest_target(&days, date("2024-05-29"), 0).unwrap();
+        // Seven days of 10 and the first day of the window
+        assert_eq!(suggestion.active_days, 8);
+        assert_eq!(suggestion.target, 134);
+    }
+
+    #[test]
+    fn adds_up_several_entries_for_the_same_day() {
+        let mut days = history(&[20; 7]);
+        days.push(days[0]);
+        let suggestion = suggest_target(&days, date("2024-05-29"), 0).unwrap();
+        assert_eq!(suggestion.active_days, 7);
+        assert_eq!(suggestion.target, 23);
+    }
+
+    #[test]
+    fn never_suggests_less_than_one() {
+        let days = history(&[1; 7]);
+        assert_eq!(suggest_target(&days, date("2024-05-29"), 0).unwrap().target, 1);
+    }
+}
@@ -0,0 +1,241 @@
+use anyhow::Result;
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, List, ListItem, Row, Table},
+    Frame,
+};
+
+use crate::exercises::render_dialog;
+use crate::{dates, App, Database, Screen};
+
+/// One health check and the rows it flagged, already formatted for display.
+#[derive(Debug, Clone)]
+pub struct Check {
+    /// Catalog key of the check's name.
+    pub key: &'static str,
+    pub rows: Vec<String>,
+    /// Whether `f` offers a fix for the flagged rows.
+    pub fixable: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct HealthState {
+    pub checks: Vec<Check>,
+    pub selected: usize,
+    /// Show the rows flagged by the selected check.
+    pub expanded: bool,
+    pub confirm_fix: bool,
+    /// Entry count shown while confirming that the audit log be cleared.
+    pub confirm_clear_log: Option<i64>,
+}
+
+/// "#12  2024-05-03 07:30:00  push-ups ×20", the line used for a flagged set.
+fn workout_line(row: &rusqlite::Row) -> rusqlite::Result<String> {
+    let id: i64 = row.get(0)?;
+    let exercise: String = row.get(1)?;
+    let count: i64 = row.get(2)?;
+    let timestamp: String = row.get(3)?;
+    Ok(format!("#{id}  {timestamp}  {exercise} ×{count}"))
+}
+
+impl Database {
+    fn pragma_lines(&self, sql: &str) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let columns = stmt.column_count();
+        let lines = stmt
+            .query_map([], |row| {
+                (0..columns)
+                    .map(|i| row.get_ref(i).map(|v| format!("{v:?}")))
+                    .collect::<rusqlite::Result<Vec<_>>>()
+                    .map(|values| values.join(" "))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(lines)
+    }
+
+    fn workout_lines(&self, condition: &str) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, exercise_type, count, timestamp FROM workouts
+             WHERE deleted_at IS NULL AND {condition}
+             ORDER BY timestamp, id"
+        ))?;
+        let lines = stmt.query_map([], workout_line)?.collect::<Result<Vec<_>, _>>()?;
+        Ok(lines)
+    }
+
+    /// Runs SQLite's own checks plus the app-level ones. Every check is
+    /// read-only.
+    pub fn health_checks(&self) -> Result<Vec<Check>> {
+        let integrity: Vec<String> = self
+            .conn
+            .prepare("PRAGMA integrity_check")?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?
+            .into_iter()
+            .filter(|line| line != "ok")
+            .collect();
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, exercise_type, count, timestamp FROM workouts WHERE deleted_at IS NULL",
+        )?;
+        let bad_timestamps = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(3)?, workout_line(row)?)))?
+            .filter_map(|row| match row {
+                Ok((timestamp, _)) if dates::parse_timestamp(&timestamp).is_some() => None,
+                other => Some(other.map(|(_, line)| line)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let check = |key, rows, fixable| Check { key, rows, fixable };
+        Ok(vec![
+            check("health.integrity", integrity, false),
+            check("health.foreign_keys", self.pragma_lines("PRAGMA foreign_key_check")?, false),
+            check("health.non_positive", self.workout_lines("count <= 0")?, false),
+            check("health.bad_timestamps", bad_timestamps, false),
+            check(
+                "health.unknown_exercises",
+                self.workout_lines("exercise_type NOT IN (SELECT name FROM exercises)")?,
+                true,
+            ),
+            check(
+                "health.duplicates",
+                self.workout_lines(
+                    "(exercise_type, timestamp) IN (
+                        SELECT exercise_type, timestamp FROM workouts WHERE deleted_at IS NULL
+                        GROUP BY exercise_type, timestamp HAVING COUNT(*) > 1
+                    )",
+                )?,
+                false,
+            ),
+        ])
+    }
+
+    /// Registers every logged exercise name missing from the exercises
+    /// table, the same way the exercises migration seeded them.
+    fn add_missing_exercises(&self) -> Result<usize> {
+        let added = self.conn.execute(
+            "INSERT OR IGNORE INTO exercises (name, position)
+             SELECT DISTINCT exercise_type, 100 FROM workouts
+             WHERE exercise_type NOT IN (SELECT name FROM exercises)",
+            [],
+        )?;
+        Ok(added)
+    }
+}
+
+impl App {
+    pub(crate) fn open_health_screen(&mut self) -> Result<()> {
+        self.health = HealthState {
+            checks: self.db.health_checks()?,
+            ..HealthState::default()
+        };
+        self.screen = Screen::Health;
+        self.message = None;
+        Ok(())
+    }
+
+    pub(crate) fn handle_health_input(&mut self, key: KeyCode) -> Result<bool> {
+        if self.health.confirm_fix {
+            self.health.confirm_fix = false;
+            if key == KeyCode::Char('y') {
+                let added = self.db.add_missing_exercises()?;
+                self.exercises = self.db.get_exercises()?;
+                self.health.checks = self.db.health_checks()?;
+                self.message = Some(self.text.format("health.fixed", &[("count", &added)]));
+            }
+            return Ok(false);
+        }
+        if self.health.confirm_clear_log.take().is_some() {
+            if key == KeyCode::Char('y') {
+                let cleared = self.db.clear_audit_log()?;
+                self.message = Some(self.text.format("health.log_cleared", &[("count", &cleared)]));
+            }
+            return Ok(false);
+        }
+
+        let state = &mut self.health;
+        let fixable = state.checks.get(state.selected).is_some_and(|c| c.fixable && !c.rows.is_empty());
+        match key {
+            KeyCode::Esc => self.screen = Screen::Data,
+            KeyCode::Up => {
+                state.selected = state.selected.saturating_sub(1);
+                self.message = None;
+            }
+            KeyCode::Down if state.selected + 1 < state.checks.len() => {
+                state.selected += 1;
+                self.message = None;
+            }
+            KeyCode::Enter => state.expanded = !state.expanded,
+            KeyCode::Char('f') if fixable => state.confirm_fix = true,
+            KeyCode::Char('r') => {
+                state.checks = self.db.health_checks()?;
+                self.message = None;
+            }
+            KeyCode::Char('l') => state.confirm_clear_log = Some(self.db.count_audit_log()?),
+            _ => {}
+        }
+        Ok(false)
+    }
+}
+
+pub fn render_health_screen(f: &mut Frame, area: Rect, app: &App) {
+    let state = &app.health;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(state.checks.len() as u16 + 3), Constraint::Min(0)])
+        .split(area);
+
+    let rows: Vec<Row> = state
+        .checks
+        .iter()
+        .enumerate()
+        .map(|(i, check)| {
+            let (result, color) = if check.rows.is_empty() {
+                (app.text.get("health.ok").to_string(), Color::Green)
+            } else {
+                (check.rows.len().to_string(), Color::Red)
+            };
+            let mut style = Style::default().fg(color);
+            if i == state.selected {
+                style = style.add_modifier(Modifier::BOLD | Modifier::REVERSED);
+            }
+            Row::new(vec![app.text.get(check.key).to_string(), result]).style(style)
+        })
+        .collect();
+    let table = Table::new(rows, [Constraint::Percentage(70), Constraint::Min(0)])
+        .header(
+            Row::new(vec![app.text.get("health.check"), app.text.get("health.found")])
+                .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("health.title")));
+    f.render_widget(table, chunks[0]);
+
+    let Some(check) = state.checks.get(state.selected) else {
+        return;
+    };
+    let lines: Vec<ListItem> = match (&app.message, state.expanded) {
+        (Some(message), _) => vec![ListItem::new(message.as_str()).style(Style::default().fg(Color::Green))],
+        (None, true) => check.rows.iter().map(|row| ListItem::new(row.as_str())).collect(),
+        (None, false) if check.fixable && !check.rows.is_empty() => vec![ListItem::new(app.text.get("health.fix_hint"))],
+        (None, false) => Vec::new(),
+    };
+    let list = List::new(lines).block(Block::default().borders(Borders::ALL).title(app.text.get(check.key)));
+    f.render_widget(list, chunks[1]);
+
+    if state.confirm_fix {
+        render_dialog(
+            f,
+            app.text.get("health.fix_title"),
+            &app.text.format("health.fix_confirm", &[("count", &check.rows.len())]),
+        );
+    }
+    if let Some(count) = state.confirm_clear_log {
+        render_dialog(
+            f,
+            app.text.get("health.clear_log_title"),
+            &app.text.format("health.clear_log_confirm", &[("count", &count)]),
+        );
+    }
+}
@@ -0,0 +1,105 @@
+//! How hard each day in the history was next to recent ones, for coloring
+//! the date list from dim to bright. A day scores the sum of its totals
+//! per exercise, each relative to that exercise's average day over the
+//! last 90 days, so reps, seconds and distance mix fairly. The scores of
+//! the last 90 days are split into quintiles and every day falls into one
+//! of the five buckets. Worked out once per visit to the history from one
+//! aggregate query, and again after sets were deleted or imported.
+
+use anyhow::Result;
+use chrono::{Duration, Local, NaiveDate};
+use ratatui::style::{Color, Modifier, Style};
+use std::collections::HashMap;
+
+use crate::{dates, App, Database};
+
+/// Days back the averages and quintiles are taken over.
+const WINDOW_DAYS: i64 = 90;
+pub const BUCKETS: u8 = 5;
+
+impl Database {
+    /// Total per day and exercise over all days, as (day, exercise, total).
+    pub fn day_exercise_totals(&self) -> Result<Vec<(String, String, i64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT day, exercise_type, total FROM daily_totals",
+        )?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+}
+
+/// Bucket per day, 0 for the lightest fifth of recent days up to
+/// `BUCKETS - 1` for the hardest.
+pub fn buckets(totals: &[(String, String, i64)], today: NaiveDate) -> HashMap<String, u8> {
+    let since = today - Duration::days(WINDOW_DAYS);
+    let recent = |day: &str| dates::parse_date(day).is_some_and(|day| day > since);
+
+    let mut sums: HashMap<&str, (i64, i64)> = HashMap::new();
+    for (_, exercise, total) in totals.iter().filter(|(day, ..)| recent(day)) {
+        let (sum, days) = sums.entry(exercise).or_default();
+        *sum += total;
+        *days += 1;
+    }
+    let mut scores: HashMap<&str, f64> = HashMap::new();
+    for (day, exercise, total) in totals {
+        // Without recent days to compare with, a day of it is an ordinary one
+        let average = match sums.get(exercise.as_str()) {
+            Some((sum, days)) if *sum > 0 => *sum as f64 / *days as f64,
+            _ => 0.0,
+        };
+        let score = if average > 0.0 { *total as f64 / average } else { 1.0 };
+        *scores.entry(day).or_default() += score;
+    }
+
+    let mut window: Vec<f64> = scores.iter().filter(|(day, _)| recent(day)).map(|(_, score)| *score).collect();
+    if window.is_empty() {
+        window = scores.values().copied().collect();
+    }
+    window.sort_by(f64::total_cmp);
+    // Ties take the middle of their ranks, so a run of equal days doesn't
+    // land at the top
+    let bucket = |score: f64| {
+        let below = window.partition_point(|s| *s < score);
+        let equal = window[below..].partition_point(|s| *s <= score);
+        let rank = (below as f64 + equal as f64 / 2.0) / window.len() as f64;
+        ((rank * BUCKETS as f64) as u8).min(BUCKETS - 1)
+    };
+    scores.into_iter().map(|(day, score)| (day.to_string(), bucket(score))).collect()
+}
+
+/// Text color of a bucket, dim to bright.
+pub fn style(bucket: u8) -> Style {
+    match bucket {
+        0 => Style::default().fg(Color::DarkGray),
+        1 => Style::default().fg(Color::Gray),
+        2 => Style::default().fg(Color::White),
+        3 => Style::default().fg(Color::LightGreen),
+        _ => Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD),
+    }
+}
+
+/// Marker standing in for the color in monochrome mode.
+pub fn glyph(bucket: u8) -> char {
+    match bucket {
+        0 => ' ',
+        1 => '▁',
+        2 => '▃',
+        3 => '▅',
+        _ => '▇',
+    }
+}
+
+impl App {
+    /// The day's bucket, working all of them out on first use after
+    /// `forget_history_days`.
+    pub(crate) fn heat_bucket(&self, date: &str) -> Option<u8> {
+        let mut heat = self.history_heat.borrow_mut();
+        if heat.is_none() {
+            let totals = self.db.day_exercise_totals().unwrap_or_default();
+            *heat = Some(buckets(&totals, Local::now().date_naive()));
+        }
+        heat.as_ref().and_then(|heat| heat.get(date).copied())
+    }
+}
@@ -0,0 +1,365 @@
+use anyhow::Result;
+use chrono::{Duration, NaiveDate};
+use std::{fmt::Write as _, io::Write};
+
+use crate::dates::WeekStart;
+use crate::export::ExportOptions;
+use crate::i18n::Messages;
+use crate::stats::{format_duration, weekly, PeriodStats};
+use crate::units::{self, DistanceUnit, WeightUnit};
+use crate::Database;
+
+/// Ranges longer than this are charted and tabled by week instead of by
+/// day, which keeps multi-year reports a few hundred KB at most.
+const MAX_DAILY_DAYS: i64 = 120;
+const CHART_WIDTH: f64 = 720.0;
+const CHART_HEIGHT: f64 = 160.0;
+
+const STYLE: &str = "body{font-family:sans-serif;max-width:760px;margin:2em auto;color:#222}\
+table{border-collapse:collapse;margin:1em 0}th,td{padding:4px 10px;border-bottom:1px solid #ddd}\
+th{text-align:left;background:#f4f4f4}td.n{text-align:right}svg{background:#fafafa}rect{fill:#4a90d9}";
+
+/// Everything the HTML report shows for a date range.
+#[derive(Debug, Clone)]
+pub struct HtmlReport {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Days with sets, oldest first.
+    pub days: Vec<PeriodStats>,
+    pub weeks: Vec<PeriodStats>,
+    /// Reps per category over the whole range.
+    pub categories: Vec<(String, i64)>,
+}
+
+impl HtmlReport {
+    fn by_week(&self) -> bool {
+        (self.to - self.from).num_days() >= MAX_DAILY_DAYS
+    }
+}
+
+impl Database {
+    /// Gathers the report through the same queries as the stats screen.
+    pub fn html_report(&self, from: NaiveDate, to: NaiveDate, week: WeekStart) -> Result<HtmlReport> {
+        let mut days: Vec<PeriodStats> = self
+            .get_daily_stats(from, None)?
+            .into_iter()
+            .filter(|d| d.start <= to)
+            .collect();
+        for (date, mood) in self.get_moods_since(from)? {
+            if let Some(day) = days.iter_mut().find(|d| d.start == date) {
+                day.mood_sum += mood as i64;
+                day.mood_days += 1;
+            }
+        }
+        let weeks = weekly(&days, week);
+
+        let mut categories: Vec<(String, i64)> = Vec::new();
+        for (_, category, total) in self.get_category_totals_since(from, None)?.into_iter().filter(|(day, ..)| *day <= to) {
+            match categories.iter_mut().find(|(name, _)| *name == category) {
+                Some((_, sum)) => *sum += total,
+                None => categories.push((category, total)),
+            }
+        }
+        categories.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        Ok(HtmlReport { from, to, days, weeks, categories })
+    }
+
+    pub fn write_html(&self, out: &mut impl Write, options: &ExportOptions) -> Result<()> {
+        let (from, to) = options.range();
+        let report = self.html_report(from, to, options.week_starts_on)?;
+        out.write_all(render_html(&report, &options.text, options.distance, options.weight).as_bytes())?;
+        Ok(())
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn average(value: Option<f64>) -> String {
+    value.map_or_else(|| "-".to_string(), |avg| format!("{avg:.1}"))
+}
+
+/// Bar chart of one measure, with the value of each bar as its tooltip.
+/// Returns nothing when every bar would be zero.
+fn bar_chart(title: &str, periods: &[PeriodStats], value: impl Fn(&PeriodStats) -> i64, label: impl Fn(i64) -> String) -> String {
+    let max = periods.iter().map(&value).max().unwrap_or(0);
+    if max <= 0 {
+        return String::new();
+    }
+    let slot = CHART_WIDTH / periods.len() as f64;
+    let mut svg = format!(
+        "<h3>{}</h3>\n<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{CHART_WIDTH}\" height=\"{CHART_HEIGHT}\" \
+         viewBox=\"0 0 {CHART_WIDTH} {CHART_HEIGHT}\" role=\"img\">\n",
+        escape(title)
+    );
+    for (i, period) in periods.iter().enumerate() {
+        let amount = value(period);
+        if amount <= 0 {
+            continue;
+        }
+        let height = (amount as f64 / max as f64 * (CHART_HEIGHT - 4.0)).max(1.0);
+        let _ = writeln!(
+            svg,
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\"><title>{}: {}</title></rect>",
+            i as f64 * slot + slot * 0.1,
+            CHART_HEIGHT - height,
+            (slot * 0.8).max(0.5),
+            height,
+            period.start,
+            escape(&label(amount))
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Volume load for a cell, starred when only some reps had a weight.
+fn volume(stats: &PeriodStats, weight: WeightUnit) -> String {
+    if stats.volume <= 0.0 {
+        return "-".to_string();
+    }
+    let volume = units::format_volume(stats.volume, weight);
+    if stats.partial_volume() { format!("{volume}*") } else { volume }
+}
+
+fn period_table(out: &mut String, heading: &str, period: &str, stats: &[PeriodStats], text: &Messages, distance: DistanceUnit, weight: WeightUnit) {
+    let _ = writeln!(out, "<h2>{}</h2>\n<table>", escape(heading));
+    let _ = write!(out, "<tr><th>{}</th>", escape(period));
+    let _ = write!(out, "<th>{}</th><th>{}</th>", escape(text.get("stats.sets")), escape(text.get("stats.reps")));
+    let _ = write!(out, "<th>{}</th>", escape(&text.format("stats.volume", &[("unit", &weight.label())])));
+    for key in ["stats.time", "stats.distance", "stats.avg_rpe", "stats.mood"] {
+        let _ = write!(out, "<th>{}</th>", escape(text.get(key)));
+    }
+    out.push_str("</tr>\n");
+    for s in stats {
+        let _ = writeln!(
+            out,
+            "<tr><td>{}</td><td class=\"n\">{}</td><td class=\"n\">{}</td><td class=\"n\">{}</td><td class=\"n\">{}</td><td class=\"n\">{}</td><td class=\"n\">{}</td><td class=\"n\">{}</td></tr>",
+            s.start,
+            s.sets,
+            s.reps,
+            volume(s, weight),
+            if s.seconds > 0 { format_duration(s.seconds) } else { "-".to_string() },
+            if s.meters > 0 { escape(&units::format_amount(s.meters, units::DISTANCE, distance)) } else { "-".to_string() },
+            average(s.avg_rpe()),
+            average(s.avg_mood())
+        );
+    }
+    out.push_str("</table>\n");
+    if stats.iter().any(PeriodStats::partial_volume) {
+        let _ = writeln!(out, "<p>{}</p>", escape(text.get("stats.weighted_only")));
+    }
+}
+
+/// The report as a single XHTML-compatible page with inline CSS and SVG,
+/// so it opens anywhere without fetching anything.
+pub fn render_html(report: &HtmlReport, text: &Messages, distance: DistanceUnit, weight: WeightUnit) -> String {
+    let title = text.format("html.title", &[("from", &report.from), ("to", &report.to)]);
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\" />\n<title>{0}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>{0}</h1>",
+        escape(&title)
+    );
+
+    // Summary over the whole range
+    let total = report.days.iter().fold(
+        PeriodStats::empty(report.from),
+        |mut total, day| {
+            total.add(day);
+            total
+        },
+    );
+    let volume_title = text.format("stats.volume", &[("unit", &weight.label())]);
+    let summary = [
+        (text.get("html.active_days"), report.days.len().to_string()),
+        (text.get("stats.sets"), total.sets.to_string()),
+        (text.get("stats.reps"), total.reps.to_string()),
+        (volume_title.as_str(), volume(&total, weight)),
+        (text.get("stats.time"), format_duration(total.seconds)),
+        (text.get("stats.distance"), units::format_amount(total.meters, units::DISTANCE, distance)),
+        (text.get("stats.avg_rpe"), average(total.avg_rpe())),
+        (text.get("stats.mood"), average(total.avg_mood())),
+    ];
+    let _ = writeln!(out, "<h2>{}</h2>\n<table>", escape(text.get("html.summary")));
+    for (label, value) in summary {
+        let _ = writeln!(out, "<tr><th>{}</th><td class=\"n\">{}</td></tr>", escape(label), escape(&value));
+    }
+    out.push_str("</table>\n");
+
+    if report.days.is_empty() {
+        let _ = writeln!(out, "<p>{}</p>", escape(text.get("html.empty")));
+    } else {
+        // Every day (or week) of the range gets a slot, so gaps show as gaps
+        let (logged, step, mut date) = if report.by_week() {
+            (&report.weeks, 7, report.weeks[0].start)
+        } else {
+            (&report.days, 1, report.from)
+        };
+        let mut periods = Vec::new();
+        while date <= report.to {
+            periods.push(
+                logged
+                    .iter()
+                    .find(|p| p.start == date)
+                    .cloned()
+                    .unwrap_or_else(|| PeriodStats::empty(date)),
+            );
+            date += Duration::days(step);
+        }
+        let chart_key = if report.by_week() { "html.weekly_chart" } else { "html.daily_chart" };
+        let _ = writeln!(out, "<h2>{}</h2>", escape(text.get(chart_key)));
+        out.push_str(&bar_chart(text.get("stats.reps"), &periods, |p| p.reps, |n| n.to_string()));
+        out.push_str(&bar_chart(&volume_title, &periods, |p| (p.volume / weight.kg_per_unit()).round() as i64, |n| n.to_string()));
+        out.push_str(&bar_chart(text.get("stats.time"), &periods, |p| p.seconds, format_duration));
+        out.push_str(&bar_chart(text.get("stats.distance"), &periods, |p| p.meters, |m| {
+            units::format_amount(m, units::DISTANCE, distance)
+        }));
+
+        if !report.categories.is_empty() {
+            let _ = writeln!(
+                out,
+                "<h2>{}</h2>\n<table>\n<tr><th>{}</th><th>{}</th></tr>",
+                escape(text.get("html.by_category")),
+                escape(text.get("html.category")),
+                escape(text.get("stats.reps"))
+            );
+            for (category, reps) in &report.categories {
+                let _ = writeln!(out, "<tr><td>{}</td><td class=\"n\">{reps}</td></tr>", escape(category));
+            }
+            out.push_str("</table>\n");
+        }
+
+        let weeks: Vec<PeriodStats> = report.weeks.iter().rev().cloned().collect();
+        period_table(&mut out, text.get("html.weekly"), text.get("stats.week_of"), &weeks, text, distance, weight);
+        if !report.by_week() {
+            let days: Vec<PeriodStats> = report.days.iter().rev().cloned().collect();
+            period_table(&mut out, text.get("html.daily"), text.get("common.date"), &days, text, distance, weight);
+        }
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::params;
+
+    fn date(s: &str) -> NaiveDate {
+        crate::dates::parse_date(s).unwrap()
+    }
+
+    fn seeded() -> Database {
+        let db = Database::new(":memory:").unwrap();
+        let sets = [
+            ("push-ups", 20, "2024-05-01 08:00:00", Some(7)),
+            ("push-ups", 25, "2024-05-01 18:00:00", Some(8)),
+            ("squats", 40, "2024-05-03 08:00:00", None),
+            // outside the range on both sides
+            ("squats", 99, "2024-04-30 08:00:00", None),
+            ("squats", 99, "2024-05-08 08:00:00", None),
+        ];
+        for (exercise, count, timestamp, rpe) in sets {
+            db.conn
+                .execute(
+                    "INSERT INTO workouts (exercise_type, count, timestamp, rpe) VALUES (?1, ?2, ?3, ?4)",
+                    params![exercise, count, timestamp, rpe],
+                )
+                .unwrap();
+        }
+        db.conn
+            .execute(
+                "INSERT INTO workouts (exercise_type, count, timestamp, deleted_at) VALUES ('squats', 500, '2024-05-02 08:00:00', '2024-05-02 09:00:00')",
+                [],
+            )
+            .unwrap();
+        db
+    }
+
+    fn html(from: &str, to: &str) -> String {
+        let report = seeded().html_report(date(from), date(to), WeekStart::Monday).unwrap();
+        render_html(&report, &Messages::default(), DistanceUnit::Km, WeightUnit::Kg)
+    }
+
+    /// Checks that every element is closed in order, the way an XML parser
+    /// would, and that no stray `<` or `&` made it into the text.
+    fn assert_well_formed(html: &str) {
+        let mut open: Vec<&str> = Vec::new();
+        let mut rest = html;
+        while let Some(start) = rest.find('<') {
+            let text = &rest[..start];
+            assert!(!text.contains('>'), "stray '>' in {text:?}");
+            for (i, _) in text.match_indices('&') {
+                let entity = &text[i..text[i..].find(';').map_or(text.len(), |end| i + end + 1)];
+                assert!(["&amp;", "&lt;", "&gt;", "&quot;"].contains(&entity), "bad entity {entity:?}");
+            }
+            let end = start + rest[start..].find('>').expect("unclosed tag");
+            let tag = &rest[start + 1..end];
+            rest = &rest[end + 1..];
+            if tag.starts_with('!') || tag.ends_with('/') {
+                continue;
+            }
+            let name = tag.trim_start_matches('/').split_whitespace().next().unwrap();
+            if tag.starts_with('/') {
+                assert_eq!(open.pop(), Some(name), "mismatched </{name}>");
+            } else {
+                open.push(name);
+            }
+        }
+        assert!(open.is_empty(), "unclosed elements: {open:?}");
+    }
+
+    #[test]
+    fn report_is_well_formed() {
+        assert_well_formed(&html("2024-05-01", "2024-05-07"));
+        assert_well_formed(&html("2023-01-01", "2024-12-31"));
+        assert_well_formed(&html("2020-01-01", "2020-01-31"));
+    }
+
+    #[test]
+    fn summary_covers_only_the_range() {
+        let html = html("2024-05-01", "2024-05-07");
+        assert!(html.contains("<title>Workout report 2024-05-01 – 2024-05-07</title>"));
+        assert!(html.contains("<tr><th>Active days</th><td class=\"n\">2</td></tr>"));
+        assert!(html.contains("<tr><th>Sets</th><td class=\"n\">3</td></tr>"));
+        assert!(html.contains("<tr><th>Reps</th><td class=\"n\">85</td></tr>"));
+        assert!(html.contains("<tr><th>Avg RPE</th><td class=\"n\">7.5</td></tr>"));
+    }
+
+    #[test]
+    fn daily_chart_has_a_bar_per_active_day() {
+        let html = html("2024-05-01", "2024-05-07");
+        assert_eq!(html.matches("<rect ").count(), 2);
+        assert!(html.contains("<title>2024-05-01: 45</title>"));
+        assert!(html.contains("<title>2024-05-03: 40</title>"));
+        assert!(html.contains("<tr><td>2024-05-03</td><td class=\"n\">1</td><td class=\"n\">40</td>"));
+    }
+
+    #[test]
+    fn long_ranges_switch_to_weekly_bars() {
+        let html = html("2024-01-01", "2024-12-31");
+        assert!(html.contains("Weekly totals"));
+        assert!(!html.contains("Daily totals"));
+        assert!(html.contains("<title>2024-04-29: 184</title>"));
+        assert!(html.contains("<title>2024-05-06: 99</title>"));
+    }
+
+    #[test]
+    fn empty_range_still_renders() {
+        let html = html("2020-01-01", "2020-01-31");
+        assert!(html.contains("No sets in this range."));
+        assert!(!html.contains("<svg"));
+    }
+
+    #[test]
+    fn text_is_escaped() {
+        assert_eq!(escape("<b>\"a&b\"</b>"), "&lt;b&gt;&quot;a&amp;b&quot;&lt;/b&gt;");
+    }
+}
@@ -0,0 +1,1006 @@
+//! UI string catalog. Every label, help line and message shown on screen is
+//! looked up here by key so translations live in one place. Templates use
+//! `{name}` placeholders filled in by `Messages::format`.
+
+use std::fmt::Display;
+
+type Catalog = &'static [(&'static str, &'static str)];
+
+/// Strings for the configured language. Keys missing from a translation
+/// fall back to English, and keys missing from English show the key itself.
+#[derive(Debug, Clone, Copy)]
+pub struct Messages {
+    catalog: Catalog,
+}
+
+impl Default for Messages {
+    fn default() -> Self {
+        Self { catalog: EN }
+    }
+}
+
+impl Messages {
+    /// Catalog for a language code such as "en" or "uk". Unknown codes get English.
+    pub fn for_language(code: &str) -> Self {
+        let catalog = match code.to_lowercase().as_str() {
+            "uk" | "ua" => UK,
+            _ => EN,
+        };
+        Self { catalog }
+    }
+
+    pub fn get<'a>(&self, key: &'a str) -> &'a str {
+        lookup(self.catalog, key)
+            .or_else(|| lookup(EN, key))
+            .unwrap_or(key)
+    }
+
+    /// Fills `{name}` placeholders in the template stored under `key`.
+    pub fn format(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        let mut text = self.get(key).to_string();
+        for (name, value) in args {
+            text = text.replace(&format!("{{{}}}", name), &value.to_string());
+        }
+        text
+    }
+}
+
+fn lookup(catalog: Catalog, key: &str) -> Option<&'static str> {
+    catalog.iter().find(|(k, _)| *k == key).map(|(_, text)| *text)
+}
+
+const EN: Catalog = &[
+    ("common.status", "Status"),
+    ("common.date", "Date"),
+    ("common.exercise", "Exercise"),
+    ("common.total", "Total"),
+    // Dates
+    ("date.today", "Today"),
+    ("date.yesterday", "Yesterday"),
+    ("date.today_at", "today at {time}"),
+    ("date.yesterday_at", "yesterday at {time}"),
+    ("date.on_at", "{date} at {time}"),
+    ("weekday.0", "Monday"),
+    ("weekday.1", "Tuesday"),
+    ("weekday.2", "Wednesday"),
+    ("weekday.3", "Thursday"),
+    ("weekday.4", "Friday"),
+    ("weekday.5", "Saturday"),
+    ("weekday.6", "Sunday"),
+    // Help bar
+    ("help.main", "[a] Add Workout  [h] History  [m] Measurements  [t] Stats  [g] Goals  [e] Exercises  [d] Data  [p] Profiles  [o] Settings  [i] About  [s] Stopwatch  [S] Quick stats  [w] Week  [l] Calendar  [c] Copy today  [x] Clear plan  [:] Commands  [q] Quit"),
+    (
+        "help.main_rest",
+        "[Space] Pause Rest  [+] +15 s  [-] -15 s  [Esc] Cancel Rest  [a] Add Workout  [h] History  [m] Measurements  [t] Stats  [g] Goals  [e] Exercises  [d] Data  [p] Profiles  [o] Settings  [i] About  [s] Stopwatch  [S] Quick stats  [w] Week  [l] Calendar  [c] Copy today  [x] Clear plan  [:] Commands  [q] Quit",
+    ),
+    (
+        "help.add",
+        "[Tab] Switch Exercise  [↑/↓] Count/Weight/RPE/Tags  [g] Guided  [i] Info  [*] Favorite  [p] Plates  [a] AMRAP  [Enter] Save  [Esc] Back",
+    ),
+    (
+        "help.add_rest",
+        "[Space] Pause Rest  [+] +15 s  [-] -15 s  [Tab] Switch Exercise  [↑/↓] Count/Weight/RPE/Tags  [g] Guided  [i] Info  [*] Favorite  [p] Plates  [a] AMRAP  [Enter] Save  [Esc] Back",
+    ),
+    ("help.history", "[↑/↓] Navigate  [Enter] Select  [m] Mark  [c] Compare  [f] Filter  [/] Search  [Esc] Back"),
+    ("help.history_compare", "[Esc] Back"),
+    (
+        "help.history_day",
+        "[↑/↓] Navigate  [←/→] Day  [e] Edit  [N] Note  [l] Changes  [c] Copy  [y] Plan today  [Space] Select  [a] All  [r] Reassign  \
+         [x] Export  [Del] Delete  [g] Group  [f] Filter  [/] Search  [Esc] Back",
+    ),
+    ("help.measurements", "[←/→] Metric  [Tab] Value/Date  [Enter] Save  [Esc] Back"),
+    ("help.stats", "[c] Chart/Table  [a] Average  [w] Weekdays  [n] Weeks  [i] Archive  [f] Tag  [r] Weekly Report  [Esc] Back"),
+    ("help.report", "[←/→] Week  [Enter] Save Markdown  [Esc] Back"),
+    ("help.week", "[←/→] Week  [Tab] Day  [Enter] Open Day  [Esc] Back"),
+    ("help.exercise_detail", "[↑/↓] Recent Sets  [g] Daily Goal  [o] Color  [Esc] Back"),
+    ("help.search", "[Type] Search  [↑/↓] Navigate  [Enter] Open Day  [Tab] Results  [Esc] Back"),
+    (
+        "help.search_results",
+        "[↑/↓] Navigate  [Space] Select  [a] All  [r] Reassign  [x] Export  [Del] Delete  [Enter] Open Day  [Tab] Search  [Esc] Back",
+    ),
+    ("help.month", "[Arrows] Day  [PgUp/PgDn] Month  [Enter] Open Day  [Esc] Back"),
+    ("help.goals", "[↑/↓] Navigate  [Enter] Set Goal  [s] Suggest  [n] Deadline Goal  [x] Drop Deadline  [Esc] Back"),
+    ("help.export", "[Tab] Next Field  [↑/↓] Format  [←/→] Change  [Space] Pick Exercise  [Enter] Export  [Esc] Back"),
+    ("help.data", "[↑/↓] Navigate  [b] Back Up Now  [r] Restore  [x] Export  [m] Merge  [e] Export Config  [i] Import Config  [c] Health Check  [v] Vacuum  [s] Rebuild Totals  [d] Delete Range  [u] Undo Delete  [a] Archive  [w] Delete All  [k] Passphrase  [t] Trash  [Esc] Back"),
+    ("help.trash", "[↑/↓] Navigate  [r] Restore  [p] Purge  [Esc] Back"),
+    ("help.profiles", "[↑/↓] Navigate  [Enter] Switch  [c] Compare  [n] New  [r] Rename  [Del] Delete  [Esc] Back"),
+    ("help.compare", "[←/→] Range  [Esc] Back"),
+    ("help.settings", "[↑/↓] Navigate  [Enter] Change  [r] Reset  [R] Reset All  [w] Test Webhook  [t] Tags  [i] About  [Esc] Back"),
+    ("help.tags", "[↑/↓] Navigate  [r] Rename  [d] Delete  [Esc] Back"),
+    ("help.about", "[Esc] Back"),
+    ("help.stopwatch", "[Space] Start/Pause  [l] Lap  [+] +15 s  [-] -15 s  [Enter] Finish  [Esc] Back"),
+    ("help.amrap_setup", "[Tab] Minutes/Reps  [Enter] Start  [Esc] Back"),
+    ("help.amrap", "[Enter] Mini-set Done  [Backspace] Undo  [Space] Pause  [+] +15 s  [-] -15 s  [Esc] Abort"),
+    ("help.health", "[↑/↓] Navigate  [Enter] Show Rows  [f] Fix  [r] Re-run  [l] Clear Audit Log  [Esc] Back"),
+    (
+        "help.exercises",
+        "[↑/↓] Navigate  [Enter] Details  [n] New  [r] Rename  [u] Unit  [c] Category  [o] Color  [d] Default  \
+         [p] Plan  [t] Sets  [J/K] Move  [f] Favorite  [x] Archive  [v] Show Archived  [m] Merge  [a] Alias  [Del] Delete  [Esc] Back",
+    ),
+    ("ui.too_small", "Window too small ({size}) — need at least {min}"),
+    ("status.rest", "rest {time}"),
+    ("status.update", "v{version} is out [F4]"),
+    ("status.deadline", "{exercise} {needed}/day, {days}d left"),
+    ("status.mouse_on", "[F2] mouse on"),
+    ("status.mouse_off", "[F2] mouse off"),
+    ("status.profile", "● {name}"),
+    // Main screen
+    ("main.welcome", "Welcome"),
+    ("main.title", "🏋️  Fitness Tracker"),
+    ("main.last_set", "last set {when}"),
+    ("main.summary", "Workout Summary"),
+    ("main.empty", "No workouts yet! Press 'a' to add your first workout."),
+    ("main.today_row", "{exercise} Today"),
+    ("main.today_planned_row", "{exercise} Today {progress}"),
+    ("main.day_plan", "Today's Plan"),
+    ("main.last_row", "{exercise} Last"),
+    ("main.dated_row", "{exercise} ({date})"),
+    ("main.plan", "This Week's Plan"),
+    // Add workout
+    ("add.exercise_type", "Exercise Type"),
+    ("add.switch", "{exercise} (Tab to switch)"),
+    ("add.no_exercises", "No exercises defined — press [e] on the main screen to add one"),
+    ("add.distance", "Distance in {unit} (Enter to save)"),
+    ("add.count_in", "Count in {unit} (Enter to save)"),
+    ("add.count", "Count (Enter to save)"),
+    ("add.weight", "Weight in {unit} (optional)"),
+    ("add.weight_invalid", "Weight must be a positive number"),
+    ("add.rpe", "RPE 1-10 (optional)"),
+    ("add.tags", "Tags, comma-separated (optional)"),
+    ("add.tags_complete", "Tags — [Tab] {tag}"),
+    ("add.rpe_range", "RPE must be between 1 and 10"),
+    ("add.added", "Added {amount} {exercise}!"),
+    ("add.plan", "Plan: {plan}"),
+    ("add.template", "Sets: {template} — [g] Guided"),
+    // Guided adds
+    ("guided.progress", "Guided: set {progress} — [Esc] Stop"),
+    ("guided.finished", "Done: all {sets} sets of {exercise} logged"),
+    ("guided.stopped", "Stopped guided {exercise} after {progress} sets — those stay logged"),
+    ("guided.no_template", "{exercise} has no sets planned — press [t] on the exercises screen"),
+    // Plate calculator
+    ("plates.title", "Plate Calculator"),
+    ("plates.target", "Target: {input} {unit}  (bar {bar} {unit})"),
+    ("plates.per_side", "Per side: {plates}"),
+    ("plates.bar_only", "nothing, just the bar"),
+    ("plates.exact", "Loads exactly {total} {unit}"),
+    ("plates.nearest", "Can't load that exactly; the closest is {total} {unit}"),
+    ("plates.below_bar", "That's less than the bar, which alone is {total} {unit}"),
+    ("plates.no_sizes", "No plate sizes set in [plates] in config.toml"),
+    ("plates.enter_target", "Type the weight to load"),
+    ("plates.keys", "[Enter] Use this weight  [Esc] Close"),
+    // History
+    ("history.day", "Workouts on {date}"),
+    ("history.title", "Workout History (Enter to view) — filter: {filter}"),
+    ("history.filter_all", "all"),
+    ("history.filter_category", "category: {category}"),
+    ("history.filter_exercise", "exercise: {exercise}"),
+    ("history.filter_tag", "tag: #{tag}"),
+    ("history.deleted", "Moved to the trash (restore it from Data → Trash)"),
+    ("history.edit", "Edit {exercise}"),
+    ("history.edit_field", "Amount: {input}\n\n[Enter] Save  [Esc] Cancel"),
+    ("history.note", "Note on {exercise}"),
+    ("history.note_field", "Note: {input}\n\n[Enter] Save (empty removes it)  [Esc] Cancel"),
+    ("history.invalid_amount", "Not a valid amount — nothing changed"),
+    ("history.no_earlier", "No earlier workouts"),
+    ("history.no_later", "No later workouts"),
+    ("history.no_sets", "No workouts that day"),
+    ("history.reps", "{reps} reps"),
+    ("history.subtotal", "= {amounts} in {sets} sets"),
+    ("history.total", "Total: {amounts} in {sets} sets"),
+    // Day comparison
+    ("day_compare.mark", "◆ marked"),
+    ("day_compare.marked", "Marked {date} — pick another date and press c"),
+    ("day_compare.no_mark", "Mark a date with m first"),
+    ("day_compare.same_date", "Pick a different date than the marked one"),
+    ("day_compare.title", "{first} vs {second}"),
+    ("day_compare.exercise", "Exercise"),
+    ("day_compare.change", "Change"),
+    // Measurements
+    ("measurements.metric", "Metric (←/→ to switch)"),
+    ("measurements.no_metrics", "No metrics configured. Add some under [measurements] in config.toml."),
+    ("measurements.value", "Value ({unit})"),
+    ("measurements.value_column", "Value"),
+    ("measurements.change", "Change"),
+    ("measurements.summary", "Summary"),
+    ("measurements.first_latest", "First {first} ({first_date})  Latest {latest} ({latest_date})  Δ {change}"),
+    ("measurements.none", "No {metric} measurements yet"),
+    ("measurements.history", "{metric} history"),
+    ("measurements.recorded", "Recorded {metric} {value}"),
+    ("measurements.bad_date", "Date must be YYYY-MM-DD"),
+    ("measurements.bad_value", "Enter a positive value"),
+    // Stats
+    ("stats.daily", "Daily (last 14 days)"),
+    ("stats.weekly", "Weekly (last 8 weeks)"),
+    ("stats.week_of", "Week of"),
+    ("stats.by_category", "Weekly reps by category"),
+    ("stats.sets", "Sets"),
+    ("stats.reps", "Reps"),
+    ("stats.time", "Time"),
+    ("stats.distance", "Distance"),
+    ("stats.avg_rpe", "Avg RPE"),
+    ("stats.mood", "Mood"),
+    ("stats.volume", "Volume ({unit})"),
+    ("stats.weighted_only", "* volume from weighted sets only"),
+    ("stats.monthly", "Monthly (last 6 months)"),
+    ("stats.month", "Month"),
+    ("stats.deload", "Volume up {weeks} weeks running — consider an easier week"),
+    ("stats.archive_excluded", "archive not included [i]"),
+    ("stats.archive_included", "archive included [i]"),
+    ("stats.archive_failed", "Could not open the archive: {error}"),
+    ("stats.tag", "Only #{tag}"),
+    // Daily chart
+    ("chart.title", "Daily reps (last 30 days)"),
+    ("chart.reps", "reps"),
+    ("chart.average", "{days}-day average"),
+    // Weekday patterns
+    ("weekdays.title", "Weekdays (last {weeks} weeks)"),
+    ("weekdays.title_all", "Weekdays (all time)"),
+    ("weekdays.day", "Day"),
+    ("weekdays.trained", "Trained"),
+    ("weekdays.average", "Avg reps"),
+    ("weekdays.days", "{days}×"),
+    // Rest between sets
+    ("rest.title", "Rest (last 8 weeks)"),
+    ("rest.average", "Avg"),
+    ("rest.count", "Rests"),
+    ("rest.before", "rest {rest}"),
+    // Goals and timers
+    ("goals.title", "Daily goals"),
+    ("goals.daily_goal", "Goal"),
+    ("goals.progress", "Progress"),
+    ("goals.edit", "Daily goal for {exercise}"),
+    ("goals.edit_field", "Target: {input}\n\nLeave empty to clear"),
+    ("goals.reached", "Goal reached: {target} {exercise} today!"),
+    ("goals.suggest_title", "Suggested goal for {exercise}"),
+    ("goals.suggestion", "Average {average}/day over the last 4 weeks ({days} days with sets)\n→ suggested {target}\n\nUse it? [y/n]"),
+    ("goals.deadlines", "Deadline goals"),
+    ("goals.past_deadlines", "Past deadline goals"),
+    ("goals.deadline_row", "{exercise}: {total} / {target} by {date}"),
+    ("goals.pace", "needs {needed}/day for the remaining {days} days — current pace {pace}/day"),
+    ("goals.pace_reached", "reached — {pace}/day so far"),
+    ("goals.achieved", "✓ achieved"),
+    ("goals.missed", "✗ missed"),
+    ("goals.deadline_title", "Deadline goal for {exercise}"),
+    ("goals.deadline_field", "Total: {target}\nBy:    {deadline}\n\n[Tab] Switch  [Enter] Save  [Esc] Cancel"),
+    ("goals.deadline_invalid", "A deadline goal needs a total above zero and a date from today on (YYYY-MM-DD)"),
+    ("goals.deadline_removed", "Dropped the deadline goal for {exercise}"),
+    ("goals.deadlines_closed", "{count} deadline goals ended and moved to the past ones"),
+    ("goals.no_suggestion", "Not enough history yet: a suggestion needs at least {days} days with sets in the last 4 weeks."),
+    // Data management
+    ("data.title", "Backups ({count})"),
+    ("data.taken", "Taken"),
+    ("data.size", "Size"),
+    ("data.empty", "No backups yet. Press 'b' to create one."),
+    ("data.backed_up", "Backed up to {path}"),
+    ("data.backup_failed", "Backup failed: {error}"),
+    ("data.confirm_title", "Restore backup"),
+    (
+        "data.confirm_restore",
+        "Replace ALL current data with {backup}?\nEverything logged since then will be gone from the live database.\nA snapshot is taken first.  [y] Restore  [any key] Cancel",
+    ),
+    ("data.restored", "Restored. Previous data saved to {snapshot}"),
+    ("data.restore_failed", "Restore failed, nothing changed: {error}"),
+    ("data.exported", "Exported to {path}"),
+    ("data.exported_measurements", "Exported to {path}, measurements to {measurements}"),
+    ("data.exported_skipped", "Exported to {path}; skipped {skipped} sets with no activity type (see [apple_health] in config.toml)"),
+    ("data.export_failed", "Export failed: {error}"),
+    // Export
+    ("export.title", "Export"),
+    ("export.format", "Format"),
+    ("export.range", "Range"),
+    ("export.today", "Today"),
+    ("export.this_week", "This week"),
+    ("export.this_month", "This month"),
+    ("export.all", "All"),
+    ("export.custom", "Custom"),
+    ("export.from", "From"),
+    ("export.to", "To"),
+    ("export.path", "Save to"),
+    ("export.whole_database", "The SQL dump always has the whole database"),
+    ("export.selection", "The {count} sets picked in the history"),
+    ("export.preview", "{count} sets will be exported"),
+    ("export.bad_date", "{field} must be a date like 2024-05-01"),
+    ("export.backwards", "From must not be after To"),
+    ("export.no_path", "Type a file to save to"),
+    ("export.path_is_folder", "{path} is a folder; add a file name"),
+    ("export.no_folder", "Folder {folder} doesn't exist"),
+    ("export.exercises", "Exercises"),
+    ("export.all_exercises", "all"),
+    ("export.anonymize", "Anonymize"),
+    ("export.anonymize_off", "off"),
+    ("export.anonymize_truncate", "dates only, no times"),
+    ("export.anonymize_shift", "dates shifted at random"),
+    ("export.anonymize_rename", "{dates}, generic exercise names"),
+    ("export.delimiter", "Delimiter"),
+    ("export.quote", "Quote"),
+    ("export.header", "Header row"),
+    ("export.header_on", "yes"),
+    ("export.header_off", "no"),
+    ("export.decimal", "Decimal separator"),
+    ("export.timestamp_format", "Timestamp format"),
+    ("export.bad_dialect", "CSV settings don't work: {error}"),
+    ("export.markdown_title", "Workouts {from} – {to}"),
+    ("export.markdown_exercises", "Exercises: {exercises}"),
+    ("export.markdown_time", "Time"),
+    ("export.markdown_exercise", "Exercise"),
+    ("export.markdown_amount", "Amount"),
+    ("export.markdown_weight", "Weight"),
+    ("data.vacuum_title", "Vacuum and optimize"),
+    (
+        "data.vacuum_confirm",
+        "{summary}\n\nRebuild the file to reclaim unused space? You can keep working meanwhile.\n[y] Vacuum  [any key] Cancel",
+    ),
+    ("data.vacuuming", "Vacuuming the database…"),
+    ("data.vacuumed", "Vacuum done: {before} → {after}, reclaimed {reclaimed}"),
+    ("data.totals_rebuilt", "Rebuilt the daily totals: {rows} days and exercises"),
+    ("data.totals_failed", "Could not rebuild the daily totals: {error}"),
+    ("data.vacuum_failed", "Vacuum failed: {error}"),
+    ("data.merge_title", "Merge another database"),
+    ("data.merge_field", "File: {input}\n\nSets missing here are copied in; the other file isn't changed."),
+    ("data.merged", "Merged {path}: {added} sets added, {skipped} already present"),
+    ("data.merge_failed", "Merge failed, nothing changed: {error}"),
+    ("setup_file.export_title", "Export configuration"),
+    ("setup_file.export_field", "File: {input}\n\nExercises, goals, tags and settings; no sets. End the name in .json for JSON."),
+    ("setup_file.import_title", "Import configuration"),
+    ("setup_file.import_field", "File: {input}\n\nAnything missing here is added; where both differ, this side is kept."),
+    ("setup_file.exported", "Configuration written to {path}"),
+    ("setup_file.failed", "Nothing changed: {error}"),
+    (
+        "setup_file.imported",
+        "Added {exercises} exercises, {aliases} aliases, {goals} goals, {tags} tags and {settings} settings, filled in {filled} blanks; {conflicts} differences kept as they are here",
+    ),
+    ("setup_file.conflict_unit", "{exercise} is counted in {here} here but {file} in the file"),
+    ("setup_file.conflict_field", "{exercise} has a different {field} here"),
+    ("setup_file.conflict_alias", "alias {alias} means {here} here but {file} in the file"),
+    ("setup_file.conflict_goal", "the daily goal for {exercise} is {here} here but {file} in the file"),
+    ("setup_file.conflict_setting", "{setting} is {here} here but {file} in the file"),
+    ("setup_file.field_category", "category"),
+    ("setup_file.field_color", "color"),
+    ("setup_file.field_default_count", "default count"),
+    ("setup_file.field_template", "set scheme"),
+    ("setup_file.field_progression", "progression plan"),
+    ("data.passphrase_title", "Encrypt database"),
+    ("data.passphrase_change_title", "Change passphrase"),
+    ("data.passphrase_field", "New passphrase: {new}\nRepeat:         {repeat}\n\n[Enter] Next/Save  [Esc] Cancel"),
+    ("data.passphrase_mismatch", "The passphrases don't match; type them again"),
+    ("data.encrypted", "Database encrypted. Backups taken before now aren't; delete them if they shouldn't stay readable"),
+    ("data.passphrase_changed", "Passphrase changed. Backups taken before now still use the old one"),
+    ("data.passphrase_failed", "Could not set the passphrase, nothing changed: {error}"),
+    ("data.archive_title", "Archive old sets"),
+    ("data.archive_field", "Archive sets before: {input}\n\nThey move to {path}; merge that file to bring them back."),
+    ("data.archive_bad_date", "The day must be a date like 2024-01-01"),
+    ("data.archive_nothing", "No sets before {date}, nothing to archive"),
+    (
+        "data.archive_confirm",
+        "Move {count} sets logged before {date} to {path}?\nThey leave the live database; a backup is taken first.\n[y] Archive  [any key] Cancel",
+    ),
+    ("data.archived", "Archived {moved} sets to {path}. Backup from before: {backup}"),
+    ("data.archive_failed", "Archive failed, no sets moved: {error}"),
+    ("data.bulk_title", "Delete sets in a range"),
+    (
+        "data.bulk_field",
+        "From:     {from}\nTo:       {to}\nExercise: {exercise}\n\nLeave the exercise empty for all of them.\n[Tab] Next field  [Enter] Next/Find  [Esc] Cancel",
+    ),
+    ("data.bulk_bad_date", "From and To must be dates like 2024-05-01"),
+    ("data.bulk_backwards", "From must not be after To"),
+    ("data.bulk_nothing", "No sets match"),
+    ("data.bulk_all_exercises", "every exercise"),
+    (
+        "data.bulk_confirm",
+        "Move {count} sets of {exercise} from {from} to {to} to the trash?\nUndo with [u] here until you quit.\n[y] Delete  [any key] Cancel",
+    ),
+    ("data.bulk_deleted", "Moved {count} sets to the trash; [u] undoes it"),
+    ("data.bulk_undone", "Restored {count} sets"),
+    ("data.bulk_failed", "Nothing changed: {error}"),
+    ("data.wipe_title", "Delete everything"),
+    (
+        "data.wipe_field",
+        "Every set, exercise, goal, measurement and setting of this profile will be deleted.\nA last backup is taken first; earlier backups and the archive stay.\n\nType {phrase} to confirm: {input}\n\n[Enter] Delete  [Esc] Cancel",
+    ),
+    ("data.wipe_mismatch", "Nothing deleted: type {phrase} exactly to confirm"),
+    ("data.wiped", "Everything deleted. The last backup is {backup}"),
+    ("data.wipe_failed", "Delete failed: {error}"),
+    // Profiles
+    ("profiles.title", "Profiles"),
+    ("profiles.open", "{name} (open)"),
+    ("profiles.pick_title", "Who's training?"),
+    ("profiles.pick_hint", " [Enter] Open  [Esc] Quit"),
+    ("db_picker.title", "Open a data file"),
+    ("db_picker.hint", " [↑/↓] Choose  [Enter] Open  [n] Other file  [Esc] Quit"),
+    ("db_picker.sets", "{count} sets"),
+    ("db_picker.missing", "missing"),
+    ("db_picker.encrypted", "encrypted"),
+    ("db_picker.unreadable", "unreadable"),
+    ("db_picker.failed", "Can't open {path}: {error}"),
+    ("db_picker.other", "Other data file"),
+    ("db_picker.field", "File: {input}\n\nA file that doesn't exist yet is created.\n{error}"),
+    // Data file watch
+    ("db_watch.changed", "The data file was changed outside the app  [F5] Reload"),
+    ("db_watch.confirm", "Reloading throws away what's being typed  [F5] Reload anyway"),
+    ("db_watch.missing", "{path} is gone — changes can't be saved until it's back  [F5] Retry"),
+    ("db_watch.reloaded", "Reloaded the data file"),
+    ("db_watch.reload_failed", "Could not reload the data file: {error}"),
+    // Instance lock
+    ("lock.title", "Data file in use"),
+    ("lock.held", "{path} is open in another instance: PID {pid} on {host}, since {started}."),
+    ("lock.unreadable", "{path} has a lock file that can't be read yet. If no other instance is running, delete {lock} and start again."),
+    ("lock.options", "[r] Open read-only  [q] Quit"),
+    ("lock.stale_options", "That instance is no longer running.\n\n[t] Take over  [r] Open read-only  [q] Quit"),
+    ("lock.read_only", "Read-only — another instance has this data file open"),
+    ("lock.write_refused", "Read-only, nothing was saved: {error}"),
+    ("profiles.new_title", "New profile"),
+    ("profiles.rename_title", "Rename {name}"),
+    ("profiles.name_field", "Name: {input}\n\nLetters, digits, '-' and '_'"),
+    ("profiles.delete_title", "Delete profile"),
+    ("profiles.delete_confirm", "Delete {name} and all of its data? Its backups are kept. [y/n]"),
+    ("profiles.saved", "Saved profile {name}"),
+    ("profiles.deleted", "Deleted profile {name}"),
+    ("profiles.switched", "Now showing {name}'s data"),
+    ("profiles.failed", "Profile not changed: {error}"),
+    ("profiles.active", "That profile is open; switch to another one first"),
+    ("profiles.default", "The default profile can't be renamed or deleted"),
+    ("profiles.compare_self", "Pick another profile to compare with the open one"),
+    ("profiles.encrypted", "{name}'s data is encrypted; start with --profile {name} to unlock it"),
+    ("profiles.locked", "{name}'s data is open in another instance (PID {pid} on {host})"),
+    // Profile comparison
+    ("compare.title", "{a} vs {b} — last {weeks} weeks"),
+    ("compare.sets", "Sets"),
+    ("compare.active_days", "Active days"),
+    ("compare.current_streak", "Current streak"),
+    ("compare.longest_streak", "Longest streak"),
+    ("compare.goals", "Goal days met"),
+    ("compare.weekly", "Sets per week"),
+    ("compare.no_sets", "Neither profile logged any sets in this range"),
+    ("compare.shared", "Exercises in common"),
+    ("compare.nothing_shared", "No exercises in common"),
+    ("compare.not_shared", "Not in common"),
+    ("compare.only", "Only {name}: {exercises}"),
+    // Config reload
+    ("config.reloaded", "Config reloaded"),
+    ("config.restart_needed", "Config reloaded; the new data file is used after a restart"),
+    ("config.reload_failed", "Config not reloaded, keeping the previous one: {error}"),
+    // Startup
+    ("startup.invalid_screen", "Unknown startup screen \"{screen}\" — opened the main screen"),
+    // Settings
+    ("settings.title", "Settings"),
+    ("settings.setting", "Setting"),
+    ("settings.value", "Value"),
+    ("settings.source", "From"),
+    ("settings.saved_here", "saved here"),
+    ("settings.from_config", "config.toml"),
+    ("settings.none", "(first in list)"),
+    ("settings.language", "Language"),
+    ("settings.ui.monochrome", "Theme"),
+    ("settings.week_starts_on", "Week starts on"),
+    ("settings.ui.date_format", "Date format"),
+    ("settings.ui.time_format", "Time format"),
+    ("settings.units.distance", "Distance unit"),
+    ("settings.units.weight", "Weight unit"),
+    ("settings.ui.default_exercise", "Default exercise"),
+    ("settings.ui.startup_screen", "Startup screen"),
+    ("settings.ui.restore_state", "Reopen where I left off"),
+    ("settings.ui.exit_summary", "Summary after quitting"),
+    ("settings.timer.rest_seconds", "Rest timer (seconds)"),
+    ("settings.notify.enabled", "Alerts"),
+    ("settings.mood.prompt", "Mood check-in"),
+    ("settings.goals.deadline_in_status", "Deadline in status bar"),
+    ("settings.saved", "Saved {setting}"),
+    ("settings.invalid", "Not a valid value — nothing changed"),
+    ("settings.reset", "{setting} is back to its config.toml value"),
+    ("settings.reset_all", "All settings are back to their config.toml values"),
+    ("settings.reset_title", "Reset settings"),
+    ("settings.reset_confirm", "Forget every setting changed here and go back to config.toml? [y/n]"),
+    // Webhook
+    ("webhook.no_url", "Set [webhook] url in config.toml to test the webhook"),
+    ("webhook.testing", "Sending a sample payload to {url}…"),
+    ("webhook.test_ok", "Webhook test sent"),
+    ("webhook.test_failed", "Webhook test failed: {error}"),
+    ("webhook.failed", "Webhook call failed; see {log}"),
+    // About
+    ("about.title", "About"),
+    ("about.version", "Version"),
+    ("about.data_file", "Data file"),
+    ("about.in_memory", "in memory only"),
+    ("about.storage", "Storage"),
+    ("about.encrypted", "encrypted"),
+    ("about.file_size", "File size"),
+    ("about.missing", "not written yet"),
+    ("about.rows", "Rows"),
+    ("about.rows_of", "{total} in all tables, {sets} of them sets"),
+    ("about.first_set", "First set"),
+    ("about.latest_set", "Latest set"),
+    ("about.none", "none yet"),
+    ("about.profile", "Profile"),
+    ("about.schema", "Schema version"),
+    ("about.schema_of", "{version} of {latest}"),
+    ("about.config_file", "Config file"),
+    ("about.no_config", "{path} (not found, using defaults)"),
+    // First-run setup
+    ("onboarding.title", "Welcome — step {step} of 4: {title}"),
+    ("onboarding.location_title", "Where to keep your data"),
+    ("onboarding.location_help", "Your sets are saved to this file. Keep it, or type another path to move it there; an existing file at that path is opened instead."),
+    ("onboarding.file", "File:"),
+    ("onboarding.exercises_title", "Exercises"),
+    ("onboarding.exercises_help", "Untick the exercises you don't do; they can be brought back from the exercises screen."),
+    ("onboarding.custom_title", "Your own exercise"),
+    ("onboarding.custom_help", "Add an exercise that isn't in the list, or leave the name empty to go on."),
+    ("onboarding.name", "Name:"),
+    ("onboarding.goal_title", "Daily goal"),
+    ("onboarding.goal_help", "Set a daily target for one exercise, or leave it empty to go without one."),
+    ("onboarding.target", "Target:"),
+    ("onboarding.no_exercises", "No exercises are enabled, so there's nothing to set a goal for."),
+    ("onboarding.keys", " [Enter] Next  [Esc] Skip"),
+    ("onboarding.exercises_keys", " [↑/↓] Move  [Space] Toggle  [Enter] Next  [Esc] Skip"),
+    ("onboarding.goal_keys", " [←/→] Exercise  [Enter] Finish  [Esc] Skip"),
+    // Unlocking an encrypted database
+    ("unlock.title", "Encrypted database"),
+    ("unlock.field", "{path}\nPassphrase: {input}\n\n{hint}"),
+    ("unlock.hint", "[Enter] Unlock  [Esc] Quit"),
+    ("unlock.wrong", "Wrong passphrase ({count}), try again  [Esc] Quit"),
+    // Trash
+    ("trash.title", "Trash ({count})"),
+    ("trash.logged", "Logged"),
+    ("trash.amount", "Amount"),
+    ("trash.deleted", "Deleted"),
+    ("trash.empty", "The trash is empty."),
+    ("trash.auto_purge", "Sets are purged for good {days} days after deletion."),
+    ("trash.restored", "Restored the set from {date}"),
+    ("trash.purged", "Purged permanently"),
+    ("trash.purge_title", "Purge permanently"),
+    ("trash.purge_confirm", "Delete {amount} {exercise} from {when} for good?\nThis can't be undone.\n[y] Purge  [any key] Cancel"),
+    // Health check
+    ("health.title", "Database health"),
+    ("health.check", "Check"),
+    ("health.found", "Found"),
+    ("health.ok", "OK"),
+    ("health.integrity", "SQLite integrity check"),
+    ("health.foreign_keys", "Foreign key violations"),
+    ("health.non_positive", "Sets with a zero or negative count"),
+    ("health.bad_timestamps", "Sets with an unreadable timestamp"),
+    ("health.unknown_exercises", "Sets for exercises missing from the list"),
+    ("health.duplicates", "Sets sharing exercise and timestamp"),
+    ("health.fix_hint", "Press [f] to add the missing names to the exercise list."),
+    ("health.fix_title", "Fix missing exercises"),
+    (
+        "health.fix_confirm",
+        "{count} sets use exercise names that aren't in the exercise list.\nAdd those names as exercises? Logged sets stay as they are.\n[y] Add  [any key] Cancel",
+    ),
+    ("health.fixed", "Added {count} exercises"),
+    ("health.clear_log_title", "Clear audit log"),
+    (
+        "health.clear_log_confirm",
+        "Delete all {count} recorded changes? Workouts are not affected.\n[y] Clear  [any key] Cancel",
+    ),
+    ("health.log_cleared", "Cleared {count} audit log entries"),
+    // Weekly report
+    ("report.title", "Weekly report {week}"),
+    ("report.hint", "[Enter] saves this as {path}"),
+    ("report.saved", "Report saved to {path}"),
+    ("report.failed", "Could not save the report: {error}"),
+    ("report.heading", "Week {week}: {start} – {end}"),
+    ("report.daily", "Daily totals"),
+    ("report.totals", "Week totals"),
+    ("report.this_week", "This week"),
+    ("report.previous_week", "Previous week"),
+    ("report.change", "Change"),
+    ("report.records", "Personal records"),
+    ("report.record", "New best set of {exercise}: {amount}"),
+    ("report.goals", "Goals met"),
+    ("report.goal_met", "Daily goal of {target} {exercise} reached"),
+    ("report.none", "Nothing this week."),
+    // Week view
+    ("week.title", "Week {start} – {end}"),
+    // Month view
+    ("month.title", "Calendar {month}"),
+    // Quick stats
+    ("quick_stats.title", "Quick stats"),
+    ("quick_stats.week", "This week"),
+    ("quick_stats.nothing", "  Nothing logged this week"),
+    ("quick_stats.streak", "Streak: {days} days"),
+    ("quick_stats.record", "Last record: {exercise} {amount} on {date}"),
+    ("quick_stats.no_record", "Last record: none yet"),
+    ("quick_stats.goals", "Goals today"),
+    ("quick_stats.dismiss", "Any key to close"),
+    // Command palette
+    ("palette.title", "Commands"),
+    ("palette.no_match", " No matching command"),
+    ("palette.date", "Date (YYYY-MM-DD): {input}"),
+    ("palette.rest_started", "Rest timer started for {exercise}"),
+    ("palette.rest_off", "The rest timer is off; set timer.rest_seconds to use it"),
+    ("action.add", "Add workout"),
+    ("action.history", "History"),
+    ("action.goto_date", "Go to date"),
+    ("action.search", "Search sets"),
+    ("action.measurements", "Measurements"),
+    ("action.stats", "Stats"),
+    ("action.report", "Weekly report"),
+    ("action.goals", "Goals"),
+    ("action.exercises", "Exercises"),
+    ("action.data", "Data and backups"),
+    ("action.export", "Export"),
+    ("action.export_csv", "Export CSV"),
+    ("action.export_config", "Export configuration"),
+    ("action.import_config", "Import configuration"),
+    ("action.health", "Database health"),
+    ("action.trash", "Trash"),
+    ("action.profiles", "Switch profile"),
+    ("action.settings", "Settings"),
+    ("action.about", "About"),
+    ("action.stopwatch", "Stopwatch"),
+    ("action.rest_timer", "Start rest timer"),
+    ("action.quick_stats", "Quick stats"),
+    ("action.week", "Week view"),
+    ("action.calendar", "Calendar"),
+    ("action.copy_today", "Copy today's summary"),
+    ("action.clear_plan", "Clear today's plan"),
+    ("action.quit", "Quit"),
+    // Tags
+    ("tags.title", "Tags"),
+    ("tags.none", "Tags — none yet; add them when logging a set"),
+    ("tags.tag", "Tag"),
+    ("tags.used", "Used on"),
+    ("tags.sets", "{count} sets"),
+    ("tags.rename_title", "Rename tag"),
+    ("tags.rename_field", "New name: {input}▏\n\n[Enter] Save  [Esc] Cancel"),
+    ("tags.renamed", "Renamed #{old} to #{new}"),
+    ("tags.invalid", "A tag needs a name without commas — nothing changed"),
+    ("tags.delete_title", "Delete tag"),
+    ("tags.delete_confirm", "Remove #{tag} from {count} sets and delete it? The sets stay.\n\n[y] Delete  [any other key] Cancel"),
+    ("tags.deleted", "Deleted #{tag}"),
+    // Search
+    ("search.title", "Search exercises and notes"),
+    ("search.hint", "Type part of an exercise name or a note"),
+    ("search.matches", "{count} matching sets"),
+    // Picking several sets
+    ("selection.count", " · {count} selected"),
+    ("selection.reassign_title", "Move {count} sets to"),
+    ("selection.confirm_title", "Reassign sets"),
+    ("selection.confirm", "Move {count} sets to {exercise}? Each change goes in the audit log.\n[y] Move  [any key] Cancel"),
+    ("selection.reassigned", "Moved {count} sets to {exercise}"),
+    ("selection.deleted", "Deleted {count} sets — undo from the data screen"),
+    // HTML report
+    ("html.title", "Workout report {from} – {to}"),
+    ("html.summary", "Summary"),
+    ("html.active_days", "Active days"),
+    ("html.empty", "No sets in this range."),
+    ("html.daily_chart", "Daily totals"),
+    ("html.weekly_chart", "Weekly totals"),
+    ("html.by_category", "Reps by category"),
+    ("html.category", "Category"),
+    ("html.weekly", "By week"),
+    ("html.daily", "By day"),
+    // Calendar export
+    ("ics.summary", "Workout: {totals}"),
+    // Copying a day's summary
+    ("clipboard.copied", "Copied: {text}"),
+    ("clipboard.sent", "Sent to the terminal's clipboard: {text}"),
+    ("clipboard.saved", "No clipboard available; summary saved to {path}"),
+    ("clipboard.failed", "Could not copy the summary: {error}"),
+    ("clipboard.nothing", "Nothing logged to copy"),
+    // Copying a day as today's plan
+    ("day_plan.copied", "Planned {sets} sets from {date} for today — [x] on the main screen clears it"),
+    ("day_plan.cleared", "Cleared today's plan"),
+    ("day_plan.complete", "Today's plan is done — all {sets} sets logged"),
+    // Planned against done
+    ("checklist.percent", "{percent}%"),
+    ("checklist.extra", " +{count} extra"),
+    // Journal
+    ("journal.title", "Journal"),
+    ("journal.confirm", "The journal has {count} sets missing from the database. Replay them? [y/n]"),
+    ("journal.replayed", "Replayed {count} sets from the journal"),
+    ("journal.failed", "Could not write the journal: {error}"),
+    // Audit log
+    ("audit.title", "Changes  [any key] Close"),
+    ("audit.empty", "No changes recorded for this set"),
+    ("audit.times", "Performed {performed}  ·  recorded {recorded}"),
+    ("audit.created", "logged"),
+    ("audit.count", "amount"),
+    ("audit.deleted_at", "trashed"),
+    ("audit.exercise", "exercise"),
+    ("audit.none", "—"),
+    ("timer.rest_over", "Rest over — time for the next set of {exercise}"),
+    ("timer.paused", "PAUSED"),
+    ("timer.cancel_title", "Cancel Rest?"),
+    ("timer.cancel", "{time} of rest left after {exercise}.\n\n[y] Cancel Timer  [n] Keep Resting"),
+    ("reminder.none_yet", "No {exercise} yet today — goal is {target}"),
+    ("reminder.behind", "{exercise}: {total} of {target} so far today"),
+    ("reminder.idle", "Nothing logged since {time} — time for a set?"),
+    ("reminder.dismiss", "[F3] dismiss"),
+    ("reminder.goal_left", "{exercise}: {left} to go today"),
+    ("reminder.goal_left_one_set", "{exercise}: {left} to go today — about one more set"),
+    ("reminder.goal_left_sets", "{exercise}: {left} to go today — about {sets} sets"),
+    // Mood check-in
+    ("mood.title", "Daily check-in"),
+    ("mood.question", "How do you feel today?"),
+    ("mood.skip", "[Esc] Skip"),
+    // Exercise management
+    ("exercises.title", "Exercises"),
+    ("exercises.title_archived", "Exercises (including archived)"),
+    ("exercises.unit", "Unit"),
+    ("exercises.category", "Category"),
+    ("exercises.color", "Color"),
+    ("exercises.default", "Default"),
+    ("exercises.archived", "Archived"),
+    ("exercises.aliases", "Aliases"),
+    ("exercises.auto_color", "auto"),
+    ("exercises.yes", "yes"),
+    ("exercises.new", "New exercise"),
+    ("exercises.name_field", "Name: {input}"),
+    ("exercises.default_for", "Default count for {exercise}"),
+    ("exercises.default_field", "Count: {input}\n\nLeave empty to clear"),
+    ("exercises.rename", "Rename {exercise}"),
+    ("exercises.new_name_field", "New name: {input}"),
+    ("exercises.alias_for", "Add alias for {exercise}"),
+    ("exercises.alias_field", "Alias: {input}"),
+    ("exercises.merge_into", "Merge {exercise} into…"),
+    ("exercises.confirm", "Confirm"),
+    (
+        "exercises.confirm_rename",
+        "Rename {from} to {to}?\n{rows} entries will be updated.\n\n[y] Confirm  [any other key] Cancel",
+    ),
+    (
+        "exercises.confirm_merge",
+        "Merge {from} into {into}?\n{rows} entries will be moved and {from} removed.\n\n[y] Confirm  [any other key] Cancel",
+    ),
+    ("exercises.confirm_delete", "Delete {exercise}?\nIt has no logged entries.\n\n[y] Confirm  [any other key] Cancel"),
+    ("exercises.alias_empty", "Alias can't be empty"),
+    ("exercises.alias_taken", "'{alias}' already refers to {exercise}"),
+    ("exercises.alias_added", "'{alias}' now maps to {exercise}"),
+    ("exercises.exists", "'{name}' already exists as {exercise}"),
+    ("exercises.created", "Created {exercise}"),
+    ("exercises.rename_taken", "'{name}' already exists — merge into it with [m] instead"),
+    ("exercises.renamed", "Renamed {from} to {to} ({rows} entries updated)"),
+    ("exercises.merged", "Merged {from} into {into} ({rows} entries moved)"),
+    ("exercises.deleted", "Deleted {exercise}"),
+    ("exercises.restored", "Restored {exercise} to the picker"),
+    ("exercises.starred", "{exercise} is a favorite and comes first in the picker"),
+    ("exercises.unstarred", "{exercise} is no longer a favorite"),
+    ("exercises.archived_msg", "Archived {exercise} — its history is kept"),
+    ("exercises.delete_refused", "Can't delete {exercise}: {rows} entries are logged. Archive [x] or merge [m] it instead."),
+    ("exercises.plan_for", "Weekly plan for {exercise}"),
+    (
+        "exercises.plan_field",
+        "Rule: {input}   (now: {current})\n\n+2 adds two per set each week\n+5% adds 5% to the weekly total\nLeave empty to clear",
+    ),
+    ("exercises.plan_none", "no plan"),
+    ("exercises.plan_started", "Started a weekly plan for {exercise}"),
+    ("exercises.plan_cleared", "Removed the weekly plan for {exercise}"),
+    ("exercises.plan_no_history", "Log some {exercise} first — the plan starts from the last four weeks"),
+    ("exercises.plan_invalid", "'{rule}' isn't a rule — try +2 or +5%"),
+    ("exercises.template_for", "Sets for {exercise}"),
+    ("exercises.template_field", "Sets: {input}\n\n3x20 is three sets of 20\n3x60s for timed exercises\nLeave empty to clear"),
+    ("exercises.template_set", "{exercise}: {template}, [g] on the add screen walks through it"),
+    ("exercises.template_cleared", "Removed the sets for {exercise}"),
+    ("exercises.template_invalid", "'{template}' isn't a set scheme — try 3x20"),
+    ("exercises.template_line", "Sets: {template}"),
+    // Exercise detail
+    ("exercise_detail.none", "Nothing logged yet"),
+    ("exercise_detail.total", "Total: {amount} in {sets} sets"),
+    ("exercise_detail.best_day", "Best day: {amount} on {date}"),
+    ("exercise_detail.best_set", "Best set: {amount} on {date}"),
+    ("exercise_detail.streak", "Streak: {days} days"),
+    ("exercise_detail.last_days", "Last {days} days: "),
+    ("exercise_detail.settings", "Settings"),
+    ("exercise_detail.unset", "—"),
+    ("exercise_detail.default", "Default: {amount}"),
+    ("exercise_detail.goal", "Daily goal: {amount}"),
+    ("exercise_detail.color", "Color: "),
+    ("exercise_detail.recent", "Recent sets"),
+    // Estimated one-rep max
+    ("strength.current", "Estimated 1RM:"),
+    ("strength.best", "Best:"),
+    ("strength.from_set", "{estimate} ({weight} × {reps}, {date})"),
+    ("strength.trend", "Last {weeks} weeks: {spark}{change}"),
+    ("strength.no_sets", "No sets with weight yet"),
+    ("strength.reps_only", "Estimated 1RM is only worked out for exercises counted in reps"),
+    ("strength.unreliable", "Sets left out as unreliable (over {reps} reps): {sets}"),
+    ("strength.new_best", "New best for {exercise}: {estimate} estimated 1RM"),
+    // Progression plans
+    ("progression.rule_reps", "+{step} per set weekly"),
+    ("progression.rule_volume", "+{percent}% volume weekly"),
+    ("progression.per_set", "{target} per set this week"),
+    ("progression.per_set_so_far", "{target} per set this week (averaging {average})"),
+    ("progression.volume", "{done} of {target} this week"),
+    ("progression.advanced", "up after last week"),
+    ("progression.held", "held after last week"),
+    ("progression.deloaded", "deloaded after last week"),
+    ("progression.rested", "held, nothing logged last week"),
+    // Stopwatch
+    ("stopwatch.title", "Stopwatch — {status}"),
+    ("stopwatch.running", "running"),
+    ("stopwatch.stopped", "stopped"),
+    ("stopwatch.laps", "Laps"),
+    ("stopwatch.lap", "Lap {number}   {lap}   ({total})"),
+    ("stopwatch.review", "Save Session"),
+    ("stopwatch.save_to", "Save to: {exercise}"),
+    ("stopwatch.as_laps", "A set for each lap:"),
+    ("stopwatch.as_total", "One set for the total:"),
+    ("stopwatch.key_exercise", "Exercise"),
+    ("stopwatch.key_split", "Laps/Total"),
+    ("stopwatch.key_save", "Save"),
+    ("stopwatch.key_back", "Keep Timing"),
+    ("stopwatch.saved", "Saved {amount} of {exercise} from the stopwatch"),
+    ("stopwatch.too_short", "Nothing to save; every set would be under a second"),
+    ("stopwatch.no_exercises", "No exercises are timed in seconds — add one with [e] on the main screen"),
+    ("stopwatch.discard_title", "Discard Session?"),
+    ("stopwatch.discard", "The time on the stopwatch hasn't been saved.\n\n[y] Discard  [n] Keep"),
+    // AMRAP countdown
+    ("amrap.title", "AMRAP — {exercise}"),
+    ("amrap.minutes", "Minutes"),
+    ("amrap.per_tap", "Reps per mini-set"),
+    ("amrap.setup_keys", "Press Enter after each mini-set once the countdown starts."),
+    ("amrap.invalid", "Minutes and reps per mini-set must be whole numbers above zero"),
+    ("amrap.reps_only", "AMRAP only works for exercises counted in reps"),
+    ("amrap.clock", "{remaining} left   ({elapsed} gone)"),
+    ("amrap.tally", "{reps} reps   mini-sets: {taps}   {pace} reps/min"),
+    ("amrap.done", "Time! {amount} {exercise} in {time} — Enter to save"),
+    ("amrap.abort_title", "Abort AMRAP?"),
+    ("amrap.abort", "Stop the countdown without logging anything?\n\n[y] Abort  [n] Keep Going"),
+    // Recap after quitting
+    ("recap.added", "This session: {sets} sets added"),
+    ("recap.exercise", "  {exercise}: {amount} in {sets} sets"),
+    ("recap.today", "Today: {totals}"),
+    ("recap.goals", "Goals: {met} of {count} met"),
+    ("recap.goal_left", "{exercise} {left} to go"),
+    ("recap.best", "New best: {exercise} {amount} (was {before})"),
+];
+
+const UK: Catalog = &[
+    ("common.status", "Стан"),
+    ("common.date", "Дата"),
+    ("common.exercise", "Вправа"),
+    ("common.total", "Разом"),
+    ("date.today", "Сьогодні"),
+    ("date.yesterday", "Вчора"),
+    ("date.today_at", "сьогодні о {time}"),
+    ("date.yesterday_at", "вчора о {time}"),
+    ("date.on_at", "{date} о {time}"),
+    ("weekday.0", "Понеділок"),
+    ("weekday.1", "Вівторок"),
+    ("weekday.2", "Середа"),
+    ("weekday.3", "Четвер"),
+    ("weekday.4", "Пʼятниця"),
+    ("weekday.5", "Субота"),
+    ("weekday.6", "Неділя"),
+    ("help.main", "[a] Додати  [h] Історія  [m] Виміри  [t] Статистика  [g] Цілі  [e] Вправи  [d] Дані  [p] Профілі  [o] Налаштування  [i] Про програму  [s] Секундомір  [S] Коротко  [w] Тиждень  [l] Календар  [c] Копіювати  [x] Скинути план  [:] Команди  [q] Вихід"),
+    ("help.main_rest", "[Space] Пауза  [+] +15 с  [-] -15 с  [Esc] Скасувати відпочинок  [a] Додати  [h] Історія  [m] Виміри  [t] Статистика  [g] Цілі  [e] Вправи  [d] Дані  [p] Профілі  [o] Налаштування  [i] Про програму  [s] Секундомір  [S] Коротко  [w] Тиждень  [l] Календар  [c] Копіювати  [x] Скинути план  [:] Команди  [q] Вихід"),
+    ("help.add", "[Tab] Вправа  [↑/↓] Кількість/Вага/RPE/Теги  [g] За планом  [i] Деталі  [*] Улюблена  [p] Диски  [a] AMRAP  [Enter] Зберегти  [Esc] Назад"),
+    ("help.add_rest", "[Space] Пауза  [+] +15 с  [-] -15 с  [Tab] Вправа  [↑/↓] Кількість/Вага/RPE/Теги  [g] За планом  [i] Деталі  [*] Улюблена  [p] Диски  [a] AMRAP  [Enter] Зберегти  [Esc] Назад"),
+    ("help.history", "[↑/↓] Навігація  [Enter] Обрати  [m] Позначити  [c] Порівняти  [f] Фільтр  [/] Пошук  [Esc] Назад"),
+    ("help.measurements", "[←/→] Показник  [Tab] Значення/Дата  [Enter] Зберегти  [Esc] Назад"),
+    ("help.stats", "[c] Графік/Таблиця  [a] Середнє  [w] Дні тижня  [n] Тижні  [i] Архів  [f] Тег  [r] Звіт за тиждень  [Esc] Назад"),
+    ("help.goals", "[↑/↓] Навігація  [Enter] Задати ціль  [s] Порадити  [n] Ціль до дати  [x] Прибрати  [Esc] Назад"),
+    ("help.data", "[↑/↓] Навігація  [b] Створити копію  [r] Відновити  [x] Експорт  [m] Об'єднати  [e] Експорт налаштувань  [i] Імпорт налаштувань  [c] Перевірка  [v] Стиснути  [s] Перерахувати підсумки  [d] Видалити за період  [u] Скасувати  [a] Архівувати  [w] Видалити все  [k] Пароль  [t] Кошик  [Esc] Назад"),
+    ("status.rest", "відпочинок {time}"),
+    ("status.update", "вийшла v{version} [F4]"),
+    ("goals.title", "Щоденні цілі"),
+    ("goals.daily_goal", "Ціль"),
+    ("goals.progress", "Прогрес"),
+    ("goals.reached", "Ціль досягнута: {target} {exercise} сьогодні!"),
+    ("timer.rest_over", "Відпочинок завершено — час для наступного підходу {exercise}"),
+    ("timer.paused", "ПАУЗА"),
+    ("timer.cancel_title", "Скасувати відпочинок?"),
+    ("timer.cancel", "Залишилось {time} відпочинку після {exercise}.\n\n[y] Скасувати  [n] Відпочивати далі"),
+    ("reminder.none_yet", "Сьогодні ще немає {exercise} — ціль {target}"),
+    ("reminder.idle", "Нічого не записано з {time} — час для підходу?"),
+    ("reminder.dismiss", "[F3] сховати"),
+    ("db_watch.changed", "Файл даних змінено поза програмою  [F5] Перечитати"),
+    ("db_watch.confirm", "Перечитування скасує введене  [F5] Все одно перечитати"),
+    ("db_watch.missing", "{path} зник — зміни не збережуться, доки він не повернеться  [F5] Ще раз"),
+    ("db_watch.reloaded", "Файл даних перечитано"),
+    ("lock.title", "Файл даних зайнятий"),
+    ("lock.options", "[r] Відкрити лише для читання  [q] Вихід"),
+    ("lock.stale_options", "Той екземпляр уже не працює.\n\n[t] Перехопити  [r] Відкрити лише для читання  [q] Вихід"),
+    ("lock.read_only", "Лише читання — файл даних відкритий в іншому екземплярі"),
+    ("reminder.goal_left", "{exercise}: сьогодні лишилося {left}"),
+    ("reminder.goal_left_one_set", "{exercise}: сьогодні лишилося {left} — приблизно ще один підхід"),
+    ("reminder.goal_left_sets", "{exercise}: сьогодні лишилося {left} — приблизно {sets} підходів"),
+    ("ui.too_small", "Вікно замале ({size}) — потрібно щонайменше {min}"),
+    ("status.mouse_on", "[F2] миша увімк."),
+    ("status.mouse_off", "[F2] миша вимк."),
+    ("main.welcome", "Вітаємо"),
+    ("main.title", "🏋️  Фітнес-трекер"),
+    ("main.last_set", "останній підхід {when}"),
+    ("main.summary", "Підсумок тренувань"),
+    ("main.empty", "Ще немає тренувань! Натисніть 'a', щоб додати перше."),
+    ("main.today_row", "{exercise} сьогодні"),
+    ("main.today_planned_row", "{exercise} сьогодні {progress}"),
+    ("main.last_row", "{exercise} минулого разу"),
+    ("add.exercise_type", "Вправа"),
+    ("add.switch", "{exercise} (Tab — змінити)"),
+    ("add.no_exercises", "Немає вправ — натисніть [e] на головному екрані, щоб додати"),
+    ("add.distance", "Відстань у {unit} (Enter — зберегти)"),
+    ("add.count_in", "Кількість у {unit} (Enter — зберегти)"),
+    ("add.count", "Кількість (Enter — зберегти)"),
+    ("add.weight", "Вага в {unit} (необовʼязково)"),
+    ("add.rpe", "RPE 1-10 (необовʼязково)"),
+    ("add.rpe_range", "RPE має бути від 1 до 10"),
+    ("add.added", "Додано {amount} {exercise}!"),
+    ("history.day", "Тренування {date}"),
+    ("history.title", "Історія тренувань (Enter — переглянути) — фільтр: {filter}"),
+    ("history.filter_all", "усі"),
+    ("history.filter_category", "категорія: {category}"),
+    ("history.filter_exercise", "вправа: {exercise}"),
+    ("history.filter_tag", "тег: #{tag}"),
+    ("export.title", "Експорт"),
+    ("export.format", "Формат"),
+    ("export.range", "Період"),
+    ("export.today", "Сьогодні"),
+    ("export.this_week", "Цей тиждень"),
+    ("export.this_month", "Цей місяць"),
+    ("export.all", "Усе"),
+    ("export.custom", "Власний"),
+    ("export.from", "Від"),
+    ("export.to", "До"),
+    ("export.path", "Зберегти в"),
+    ("export.preview", "Буде експортовано підходів: {count}"),
+    ("export.exercises", "Вправи"),
+    ("export.all_exercises", "усі"),
+    ("export.anonymize", "Анонімізувати"),
+    ("export.anonymize_off", "ні"),
+    ("export.anonymize_truncate", "лише дати, без часу"),
+    ("export.anonymize_shift", "дати випадково зсунуті"),
+    ("export.anonymize_rename", "{dates}, загальні назви вправ"),
+    ("export.delimiter", "Роздільник"),
+    ("export.quote", "Лапки"),
+    ("export.header", "Рядок заголовків"),
+    ("export.header_on", "так"),
+    ("export.header_off", "ні"),
+    ("export.decimal", "Десятковий роздільник"),
+    ("export.timestamp_format", "Формат часу"),
+    ("day_compare.mark", "◆ позначено"),
+    ("day_compare.marked", "Позначено {date} — оберіть іншу дату й натисніть c"),
+    ("day_compare.no_mark", "Спершу позначте дату клавішею m"),
+    ("day_compare.title", "{first} проти {second}"),
+    ("day_compare.exercise", "Вправа"),
+    ("day_compare.change", "Зміна"),
+    ("measurements.metric", "Показник (←/→ — змінити)"),
+    ("measurements.value", "Значення ({unit})"),
+    ("measurements.value_column", "Значення"),
+    ("measurements.change", "Зміна"),
+    ("measurements.summary", "Підсумок"),
+    ("measurements.none", "Ще немає вимірів {metric}"),
+    ("measurements.recorded", "Записано {metric} {value}"),
+    ("measurements.bad_date", "Дата має бути у форматі РРРР-ММ-ДД"),
+    ("measurements.bad_value", "Введіть додатне значення"),
+    ("stats.daily", "Щодня (останні 14 днів)"),
+    ("stats.weekly", "Щотижня (останні 8 тижнів)"),
+    ("stats.week_of", "Тиждень"),
+    ("stats.sets", "Підходи"),
+    ("stats.reps", "Повтори"),
+    ("stats.time", "Час"),
+    ("stats.distance", "Відстань"),
+    ("stats.mood", "Настрій"),
+    ("stats.volume", "Обсяг ({unit})"),
+    ("stats.weighted_only", "* обсяг лише з підходів з вагою"),
+    ("stats.monthly", "Щомісяця (останні 6 місяців)"),
+    ("stats.month", "Місяць"),
+    ("stats.deload", "Обсяг росте {weeks} тижнів поспіль — варто зробити легший тиждень"),
+    ("chart.title", "Повтори за день (останні 30 днів)"),
+    ("chart.reps", "повтори"),
+    ("chart.average", "середнє за {days} днів"),
+    ("weekdays.title", "Дні тижня (останні {weeks} тижнів)"),
+    ("weekdays.title_all", "Дні тижня (весь час)"),
+    ("weekdays.day", "День"),
+    ("weekdays.trained", "Тренувань"),
+    ("weekdays.average", "Сер. повт."),
+    ("rest.title", "Відпочинок (останні 8 тижнів)"),
+    ("rest.average", "Сер."),
+    ("rest.before", "відпочинок {rest}"),
+    ("mood.title", "Щоденна перевірка"),
+    ("mood.question", "Як ви почуваєтеся сьогодні?"),
+    ("mood.skip", "[Esc] Пропустити"),
+    ("exercises.title", "Вправи"),
+    ("exercises.confirm", "Підтвердження"),
+    ("exercises.created", "Створено {exercise}"),
+    ("exercises.deleted", "Видалено {exercise}"),
+    ("main.plan", "План на тиждень"),
+    ("add.plan", "План: {plan}"),
+    ("plates.title", "Калькулятор дисків"),
+    ("plates.per_side", "На кожен бік: {plates}"),
+    ("plates.bar_only", "нічого, лише гриф"),
+    ("plates.keys", "[Enter] Взяти цю вагу  [Esc] Закрити"),
+    ("progression.per_set", "{target} за підхід цього тижня"),
+    ("progression.volume", "{done} з {target} цього тижня"),
+];
+
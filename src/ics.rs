@@ -0,0 +1,260 @@
+use anyhow::Result;
+use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
+use std::io::Write;
+
+use crate::export::ExportOptions;
+use crate::i18n::Messages;
+use crate::units::{self, DistanceUnit};
+use crate::{dates, Database};
+
+/// Longest content line allowed by RFC 5545, in octets, before folding.
+const MAX_LINE: usize = 75;
+
+/// One logged set as it goes into an event description.
+#[derive(Debug, Clone)]
+pub struct IcsSet {
+    pub exercise: String,
+    pub unit: String,
+    pub count: i64,
+    pub time: String,
+}
+
+impl Database {
+    /// Sets between `from` and `to`, grouped by day, oldest first.
+    pub fn ics_days(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<(NaiveDate, Vec<IcsSet>)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT w.timestamp, w.exercise_type, COALESCE(e.unit, 'reps'), w.count
+             FROM workouts w
+             LEFT JOIN exercises e ON e.name = w.exercise_type
+             WHERE substr(w.timestamp, 1, 10) BETWEEN ?1 AND ?2 AND w.deleted_at IS NULL
+             ORDER BY w.timestamp ASC, w.id ASC",
+        )?;
+        let rows = stmt
+            .query_map([from.to_string(), to.to_string()], |row| {
+                Ok((row.get::<_, String>(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })?
+            .collect::<Result<Vec<(String, String, String, i64)>, _>>()?;
+
+        let mut days: Vec<(NaiveDate, Vec<IcsSet>)> = Vec::new();
+        for (timestamp, exercise, unit, count) in rows {
+            let Some(at) = dates::parse_timestamp(&timestamp) else {
+                continue;
+            };
+            let set = IcsSet {
+                exercise,
+                unit,
+                count,
+                time: at.format("%H:%M").to_string(),
+            };
+            match days.last_mut() {
+                Some((date, sets)) if *date == at.date() => sets.push(set),
+                _ => days.push((at.date(), vec![set])),
+            }
+        }
+        Ok(days)
+    }
+
+    pub fn write_ics(&self, out: &mut impl Write, options: &ExportOptions) -> Result<()> {
+        let (from, to) = options.range();
+        let days = self.ics_days(from, to)?;
+        let ics = render_ics(&days, Utc::now().naive_utc(), &options.text, options.distance);
+        out.write_all(ics.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Escapes a TEXT value: backslashes, separators and newlines.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Appends a content line, folded into CRLF-space continuations so no
+/// physical line exceeds 75 octets. Never splits a UTF-8 character.
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > MAX_LINE {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+/// An iCalendar file with one all-day event per workout day. UIDs are
+/// derived from the date, so importing a newer export updates the old
+/// events instead of duplicating them.
+pub fn render_ics(days: &[(NaiveDate, Vec<IcsSet>)], stamp: NaiveDateTime, text: &Messages, distance: DistanceUnit) -> String {
+    let mut out = String::new();
+    for line in ["BEGIN:VCALENDAR", "VERSION:2.0", "PRODID:-//fitness-tracker-tui//EN", "CALSCALE:GREGORIAN"] {
+        push_line(&mut out, line);
+    }
+    for (date, sets) in days {
+        // Totals in the order the exercises were first done that day
+        let mut totals: Vec<(&str, &str, i64)> = Vec::new();
+        for set in sets {
+            match totals.iter_mut().find(|(exercise, ..)| *exercise == set.exercise) {
+                Some((_, _, total)) => *total += set.count,
+                None => totals.push((&set.exercise, &set.unit, set.count)),
+            }
+        }
+        let totals: Vec<String> = totals
+            .iter()
+            .map(|(exercise, unit, total)| format!("{} {exercise}", units::format_amount(*total, unit, distance)))
+            .collect();
+        let summary = text.format("ics.summary", &[("totals", &totals.join(", "))]);
+        let description: Vec<String> = sets
+            .iter()
+            .map(|set| format!("{} {} ×{}", set.time, set.exercise, units::format_amount(set.count, &set.unit, distance)))
+            .collect();
+
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:{}-workout@fitness-tracker-tui", date.format("%Y%m%d")));
+        push_line(&mut out, &format!("DTSTAMP:{}", stamp.format("%Y%m%dT%H%M%SZ")));
+        push_line(&mut out, &format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")));
+        push_line(&mut out, &format!("DTEND;VALUE=DATE:{}", (*date + Duration::days(1)).format("%Y%m%d")));
+        push_line(&mut out, &format!("SUMMARY:{}", escape(&summary)));
+        push_line(&mut out, &format!("DESCRIPTION:{}", escape(&description.join("\n"))));
+        push_line(&mut out, "TRANSP:TRANSPARENT");
+        push_line(&mut out, "END:VEVENT");
+    }
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::params;
+
+    fn date(s: &str) -> NaiveDate {
+        dates::parse_date(s).unwrap()
+    }
+
+    /// A minimal reader: unfolds continuation lines, then splits each into
+    /// name (parameters dropped) and unescaped value.
+    fn parse(ics: &str) -> Vec<(String, String)> {
+        assert!(ics.ends_with("\r\n"));
+        for line in ics.split("\r\n") {
+            assert!(line.len() <= MAX_LINE, "line over 75 octets: {line:?}");
+            assert!(!line.contains('\n') && !line.contains('\r'));
+        }
+        ics.replace("\r\n ", "")
+            .split("\r\n")
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let (name, value) = line.split_once(':').expect("content line without a colon");
+                let name = name.split(';').next().unwrap().to_string();
+                let mut unescaped = String::new();
+                let mut chars = value.chars();
+                while let Some(c) = chars.next() {
+                    match (c, c == '\\') {
+                        (_, true) => match chars.next() {
+                            Some('n') | Some('N') => unescaped.push('\n'),
+                            Some(c @ ('\\' | ';' | ',')) => unescaped.push(c),
+                            other => panic!("bad escape \\{other:?}"),
+                        },
+                        (';' | ',', false) if name != "DESCRIPTION" && name != "SUMMARY" => unescaped.push(c),
+                        (';' | ',', false) => panic!("unescaped {c:?} in {name}"),
+                        _ => unescaped.push(c),
+                    }
+                }
+                (name, unescaped)
+            })
+            .collect()
+    }
+
+    fn seeded() -> Database {
+        let db = Database::new(":memory:").unwrap();
+        let sets = [
+            ("squats", 60, "2024-05-01 08:00:00"),
+            ("push-ups", 30, "2024-05-01 08:10:00"),
+            ("squats", 60, "2024-05-01 18:00:00"),
+            ("push-ups", 30, "2024-05-01 18:05:00"),
+            ("plank; hold, long", 90, "2024-05-03 07:00:00"),
+            ("squats", 10, "2024-06-01 07:00:00"),
+        ];
+        for (exercise, count, timestamp) in sets {
+            db.conn
+                .execute(
+                    "INSERT INTO workouts (exercise_type, count, timestamp) VALUES (?1, ?2, ?3)",
+                    params![exercise, count, timestamp],
+                )
+                .unwrap();
+        }
+        db.conn
+            .execute("INSERT INTO exercises (name, position, unit) VALUES ('plank; hold, long', 5, 'seconds')", [])
+            .unwrap();
+        db
+    }
+
+    fn ics() -> String {
+        let days = seeded().ics_days(date("2024-05-01"), date("2024-05-31")).unwrap();
+        let stamp = dates::parse_timestamp("2024-06-02 10:00:00").unwrap();
+        render_ics(&days, stamp, &Messages::default(), DistanceUnit::Km)
+    }
+
+    #[test]
+    fn calendar_structure_is_valid() {
+        let props = parse(&ics());
+        assert_eq!(props.first().unwrap(), &("BEGIN".to_string(), "VCALENDAR".to_string()));
+        assert_eq!(props.last().unwrap(), &("END".to_string(), "VCALENDAR".to_string()));
+        let mut depth = Vec::new();
+        for (name, value) in &props {
+            match name.as_str() {
+                "BEGIN" => depth.push(value.clone()),
+                "END" => assert_eq!(depth.pop().as_ref(), Some(value)),
+                _ => assert!(!depth.is_empty()),
+            }
+        }
+        assert!(depth.is_empty());
+        assert_eq!(props.iter().filter(|(n, v)| n == "BEGIN" && v == "VEVENT").count(), 2);
+        for required in ["VERSION", "PRODID"] {
+            assert_eq!(props.iter().filter(|(n, _)| n == required).count(), 1);
+        }
+        for required in ["UID", "DTSTAMP", "DTSTART", "DTEND", "SUMMARY"] {
+            assert_eq!(props.iter().filter(|(n, _)| n == required).count(), 2);
+        }
+    }
+
+    #[test]
+    fn one_all_day_event_per_workout_day() {
+        let props = parse(&ics());
+        let get = |name: &str| props.iter().filter(|(n, _)| n == name).map(|(_, v)| v.as_str()).collect::<Vec<_>>();
+        assert_eq!(get("DTSTART"), ["20240501", "20240503"]);
+        assert_eq!(get("DTEND"), ["20240502", "20240504"]);
+        assert_eq!(get("UID")[0], "20240501-workout@fitness-tracker-tui");
+        assert_eq!(get("DTSTAMP")[0], "20240602T100000Z");
+        assert!(ics().contains("DTSTART;VALUE=DATE:20240501\r\n"));
+    }
+
+    #[test]
+    fn summary_and_description_round_trip() {
+        let props = parse(&ics());
+        let get = |name: &str| props.iter().filter(|(n, _)| n == name).map(|(_, v)| v.clone()).collect::<Vec<_>>();
+        assert_eq!(get("SUMMARY"), ["Workout: 120 squats, 60 push-ups", "Workout: 90s plank; hold, long"]);
+        assert_eq!(
+            get("DESCRIPTION")[0],
+            "08:00 squats ×60\n08:10 push-ups ×30\n18:00 squats ×60\n18:05 push-ups ×30"
+        );
+    }
+
+    #[test]
+    fn long_lines_fold_without_splitting_characters() {
+        let mut out = String::new();
+        let line = format!("DESCRIPTION:{}", "×".repeat(100));
+        push_line(&mut out, &line);
+        assert!(out.split("\r\n").all(|l| l.len() <= MAX_LINE));
+        assert_eq!(out.replace("\r\n ", "").trim_end(), line);
+    }
+
+    #[test]
+    fn text_escaping() {
+        assert_eq!(escape("a,b;c\\d\ne"), "a\\,b\\;c\\\\d\\ne");
+    }
+}
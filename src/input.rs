@@ -0,0 +1,132 @@
+use chrono::{Local, NaiveDate};
+use crossterm::event::KeyCode;
+
+/// Text field that only accepts numbers, optionally with a decimal point.
+#[derive(Debug, Clone, Default)]
+pub struct NumericInput {
+    text: String,
+    allow_decimal: bool,
+}
+
+impl NumericInput {
+    pub fn decimal() -> Self {
+        Self {
+            text: String::new(),
+            allow_decimal: true,
+        }
+    }
+
+    /// Returns true when the key was consumed by the field.
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Char(c) if c.is_ascii_digit() => self.text.push(c),
+            KeyCode::Char('.') if self.allow_decimal && !self.text.contains('.') => {
+                self.text.push('.')
+            }
+            KeyCode::Backspace => {
+                self.text.pop();
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+    }
+
+    pub fn set(&mut self, value: impl ToString) {
+        self.text = value.to_string();
+    }
+
+    /// Switches decimal entry on or off, dropping any fraction already typed.
+    pub fn set_decimal(&mut self, allow_decimal: bool) {
+        self.allow_decimal = allow_decimal;
+        if !allow_decimal && let Some(dot) = self.text.find('.') {
+            self.text.truncate(dot);
+        }
+    }
+
+    pub fn positive_f64(&self) -> Option<f64> {
+        self.text
+            .parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite() && *n > 0.0)
+    }
+}
+
+/// `YYYY-MM-DD` entry field, pre-filled with today's date.
+#[derive(Debug, Clone)]
+pub struct DateInput {
+    text: String,
+}
+
+impl Default for DateInput {
+    fn default() -> Self {
+        Self {
+            text: Local::now().format("%Y-%m-%d").to_string(),
+        }
+    }
+}
+
+impl DateInput {
+    pub fn with_date(date: NaiveDate) -> Self {
+        Self {
+            text: date.format("%Y-%m-%d").to_string(),
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Char(c) if (c.is_ascii_digit() || c == '-') && self.text.len() < 10 => {
+                self.text.push(c)
+            }
+            KeyCode::Backspace => {
+                self.text.pop();
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn value(&self) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(&self.text, "%Y-%m-%d").ok()
+    }
+}
+
+/// Free-form single-line text field.
+#[derive(Debug, Clone, Default)]
+pub struct TextInput {
+    text: String,
+}
+
+impl TextInput {
+    pub fn with_text(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Char(c) => self.text.push(c),
+            KeyCode::Backspace => {
+                self.text.pop();
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+}
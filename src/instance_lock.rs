@@ -0,0 +1,221 @@
+//! Keeps two instances of the app from writing to the same data file. The
+//! UI takes a lock file next to the data file, `<file>.lock`, holding its
+//! PID, host and start time, and removes it again when it closes. Finding
+//! the lock taken asks whether to quit or open the file read-only; a lock
+//! left behind by a crash, whose process is gone, can be taken over.
+//! Commands that write take the lock too, without asking anything: they
+//! take over a stale lock and stop at one that's held.
+
+use anyhow::{bail, Context, Result};
+use chrono::Local;
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::Line,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::exercises::render_dialog;
+use crate::i18n::Messages;
+use crate::{before_ui, App, Database};
+
+/// Who holds a lock, as written in the lock file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Holder {
+    pub pid: u32,
+    pub host: String,
+    /// `YYYY-MM-DD HH:MM:SS`, local time.
+    pub started: String,
+}
+
+impl Holder {
+    fn current() -> Self {
+        Self {
+            pid: std::process::id(),
+            host: hostname(),
+            started: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        }
+    }
+
+    /// Whether the holder certainly stopped without removing the lock. A
+    /// process on another machine can't be checked, so it never is, and
+    /// neither is a holder the lock file doesn't name.
+    pub fn is_stale(&self) -> bool {
+        self.pid != 0 && self.host == hostname() && !process_running(self.pid)
+    }
+}
+
+/// The lock on a data file, removed when dropped, which happens on every
+/// way out of `main` short of the process being killed.
+#[derive(Debug)]
+pub struct InstanceLock {
+    path: PathBuf,
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        // A lock taken over from this instance is no longer its to remove
+        if read_holder(&self.path).is_some_and(|holder| holder.pid == std::process::id()) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+pub enum Acquired {
+    Locked(InstanceLock),
+    /// Another instance has the file; how it should be opened is up to the user.
+    Held(Holder),
+}
+
+fn lock_path(data: &Path) -> PathBuf {
+    let mut name = data.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    data.with_file_name(name)
+}
+
+/// The holder named in a lock file. One that can't be parsed, like a lock
+/// another instance created but hasn't written yet, names nobody (PID 0),
+/// which is never taken for stale.
+fn read_holder(path: &Path) -> Option<Holder> {
+    fs::read_to_string(path).ok().map(|text| serde_json::from_str(&text).unwrap_or_default())
+}
+
+/// Takes the lock on the data file at `data`, unless another instance has it.
+pub fn acquire(data: &Path) -> Result<Acquired> {
+    let path = lock_path(data);
+    let mut file = match OpenOptions::new().write(true).create_new(true).open(&path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+            return Ok(Acquired::Held(read_holder(&path).unwrap_or_default()));
+        }
+        Err(e) => return Err(e).with_context(|| format!("failed to create {}", path.display())),
+    };
+    file.write_all(serde_json::to_string(&Holder::current())?.as_bytes())?;
+    Ok(Acquired::Locked(InstanceLock { path }))
+}
+
+/// Replaces a stale lock on the data file at `data` with this instance's.
+pub fn take_over(data: &Path) -> Result<InstanceLock> {
+    let path = lock_path(data);
+    fs::remove_file(&path).with_context(|| format!("failed to remove {}", path.display()))?;
+    match acquire(data)? {
+        Acquired::Locked(lock) => Ok(lock),
+        Acquired::Held(holder) => bail!("PID {} on {} took the lock first", holder.pid, holder.host),
+    }
+}
+
+fn hostname() -> String {
+    std::env::var("COMPUTERNAME")
+        .ok()
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .or_else(|| Command::new("hostname").output().ok().and_then(|out| String::from_utf8(out.stdout).ok()))
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(unix)]
+fn process_running(pid: u32) -> bool {
+    // Signal 0 only checks that the process exists
+    Command::new("kill").args(["-0", &pid.to_string()]).output().is_ok_and(|out| out.status.success())
+}
+
+#[cfg(not(unix))]
+fn process_running(pid: u32) -> bool {
+    let filter = format!("PID eq {pid}");
+    Command::new("tasklist")
+        .args(["/FI", &filter, "/NH"])
+        .output()
+        // Can't tell, so assume it's there
+        .map_or(true, |out| String::from_utf8_lossy(&out.stdout).contains(&pid.to_string()))
+}
+
+/// Takes the lock on the data file at `data` for a command that writes,
+/// taking over a stale one. A lock held by a running instance is an error.
+pub fn acquire_for_command(data: &Path) -> Result<InstanceLock> {
+    match acquire(data)? {
+        Acquired::Locked(lock) => Ok(lock),
+        Acquired::Held(holder) if holder.is_stale() => take_over(data),
+        Acquired::Held(holder) if holder.pid == 0 => bail!(
+            "{} has a lock file that can't be read yet; if no other instance is running, delete {}",
+            data.display(),
+            lock_path(data).display()
+        ),
+        Acquired::Held(holder) => {
+            bail!("{} is open in another instance (PID {} on {}); close it first", data.display(), holder.pid, holder.host)
+        }
+    }
+}
+
+/// What to do about a data file another instance has open.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Choice {
+    ReadOnly,
+    TakeOver,
+}
+
+/// Asks what to do about the lock `holder` has on `data`, on a screen
+/// shown before the main UI. Quitting is an error.
+pub fn prompt_locked(data: &Path, holder: &Holder, text: &Messages) -> Result<Choice> {
+    let stale = holder.is_stale();
+    let mut body = if holder.pid == 0 {
+        text.format("lock.unreadable", &[("path", &data.display()), ("lock", &lock_path(data).display())])
+    } else {
+        text.format(
+            "lock.held",
+            &[("path", &data.display()), ("pid", &holder.pid), ("host", &holder.host), ("started", &holder.started)],
+        )
+    };
+    body.push_str("\n\n");
+    body.push_str(text.get(if stale { "lock.stale_options" } else { "lock.options" }));
+    before_ui(|terminal| loop {
+        terminal.draw(|f| render_dialog(f, text.get("lock.title"), &body))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => bail!("{} is open in another instance", data.display()),
+            KeyCode::Char('r') => return Ok(Choice::ReadOnly),
+            KeyCode::Char('t') if stale => return Ok(Choice::TakeOver),
+            _ => {}
+        }
+    })
+}
+
+impl App {
+    /// Passes `result` on, except that a write refused in read-only mode
+    /// becomes a warning instead of ending the app.
+    pub(crate) fn allow_read_only<T: Default>(&mut self, result: Result<T>) -> Result<T> {
+        match result {
+            Err(e) if self.read_only => {
+                self.show_warning(self.text.format("lock.write_refused", &[("error", &format!("{e:#}"))]));
+                Ok(T::default())
+            }
+            result => result,
+        }
+    }
+}
+
+/// The banner line while the data file is open read-only.
+pub fn banner_line(app: &App) -> Option<Line<'static>> {
+    app.read_only.then(|| {
+        Line::styled(
+            app.text.get("lock.read_only").to_string(),
+            Style::default().fg(Color::Black).bg(Color::Gray).add_modifier(Modifier::BOLD),
+        )
+    })
+}
+
+impl Database {
+    /// Refuses every write from here on, for a file another instance has open.
+    pub fn set_read_only(&self) -> Result<()> {
+        self.conn.pragma_update(None, "query_only", true)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,303 @@
+//! Optional append-only CSV journal of logged sets, kept next to the
+//! database as a plain-text safety net. Lines are written by a worker
+//! thread that reopens the file for every line, so a deleted or rotated
+//! journal is simply started again and a slow disk never holds up the UI.
+
+use anyhow::Result;
+use chrono::Local;
+use crossterm::event::KeyCode;
+use rusqlite::{params, OptionalExtension};
+use std::{
+    collections::HashSet,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, Sender},
+    thread::{self, JoinHandle},
+};
+
+use crate::config::JournalConfig;
+use crate::csv_import::{self, csv_field};
+use crate::{dates, App, Database};
+
+const HEADER: &str = "action,uuid,exercise,count,timestamp,rpe,weight,recorded_at";
+const INSERT: &str = "insert";
+const PURGE: &str = "purge";
+
+/// A set as the journal recorded it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+    pub uuid: String,
+    pub exercise: String,
+    pub count: i64,
+    pub timestamp: String,
+    pub rpe: Option<i64>,
+    pub weight: Option<f64>,
+    pub recorded_at: Option<String>,
+}
+
+impl JournalEntry {
+    fn line(&self) -> String {
+        format!(
+            "{INSERT},{},{},{},{},{},{},{}",
+            self.uuid,
+            csv_field(&self.exercise),
+            self.count,
+            self.timestamp,
+            self.rpe.map(|r| r.to_string()).unwrap_or_default(),
+            self.weight.map(|w| w.to_string()).unwrap_or_default(),
+            self.recorded_at.as_deref().unwrap_or_default()
+        )
+    }
+}
+
+/// The writer thread. Dropping the journal lets it finish the lines still
+/// queued before the app exits.
+#[derive(Debug)]
+pub struct Journal {
+    lines: Option<Sender<String>>,
+    failures: Receiver<String>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl Journal {
+    pub fn start(path: PathBuf) -> Self {
+        let (lines, queued) = mpsc::channel::<String>();
+        let (failed, failures) = mpsc::channel();
+        let writer = thread::spawn(move || {
+            for line in queued {
+                if let Err(e) = append(&path, &line) {
+                    let _ = failed.send(format!("{}: {e}", path.display()));
+                }
+            }
+        });
+        Self {
+            lines: Some(lines),
+            failures,
+            writer: Some(writer),
+        }
+    }
+
+    fn send(&self, line: String) {
+        if let Some(lines) = &self.lines {
+            let _ = lines.send(line);
+        }
+    }
+}
+
+impl Drop for Journal {
+    fn drop(&mut self) {
+        self.lines = None;
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+/// Appends one line, starting the file with the header when it's new or
+/// was removed, and syncs it to disk before returning.
+fn append(path: &Path, line: &str) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    if file.metadata()?.len() == 0 {
+        writeln!(file, "{HEADER}")?;
+    }
+    writeln!(file, "{line}")?;
+    file.sync_data()
+}
+
+/// Sets inserted by the journal, minus those it says were later purged.
+pub fn parse_journal(text: &str) -> Vec<JournalEntry> {
+    let Some(table) = csv_import::read_csv(text) else {
+        return Vec::new();
+    };
+    let column = |name: &str| table.columns.iter().position(|c| c == name);
+    let (Some(action), Some(uuid)) = (column("action"), column("uuid")) else {
+        return Vec::new();
+    };
+    let (exercise, count, timestamp, rpe, weight, recorded_at) = (
+        column("exercise"),
+        column("count"),
+        column("timestamp"),
+        column("rpe"),
+        column("weight"),
+        column("recorded_at"),
+    );
+
+    let mut entries: Vec<JournalEntry> = Vec::new();
+    let mut purged = HashSet::new();
+    for (_, _, fields) in &table.records {
+        let field = |index: Option<usize>| index.and_then(|i| fields.get(i)).map_or("", |f| f.as_str());
+        let id = field(Some(uuid)).to_string();
+        match field(Some(action)) {
+            PURGE => {
+                purged.insert(id);
+            }
+            INSERT => {
+                let (Ok(amount), Some(_)) = (field(count).parse(), dates::parse_timestamp(field(timestamp))) else {
+                    continue;
+                };
+                if id.is_empty() || field(exercise).is_empty() {
+                    continue;
+                }
+                entries.push(JournalEntry {
+                    uuid: id,
+                    exercise: field(exercise).to_string(),
+                    count: amount,
+                    timestamp: field(timestamp).to_string(),
+                    rpe: field(rpe).parse().ok(),
+                    weight: field(weight).parse().ok(),
+                    recorded_at: Some(field(recorded_at)).filter(|r| !r.is_empty()).map(str::to_string),
+                });
+            }
+            _ => {}
+        }
+    }
+    entries.retain(|entry| !purged.contains(&entry.uuid));
+    entries
+}
+
+impl Database {
+    /// Where the journal goes; relative paths are resolved next to the
+    /// database file.
+    pub fn journal_path(&self, config: &JournalConfig) -> PathBuf {
+        let db_path = self.file_path();
+        db_path.parent().unwrap_or(Path::new("")).join(&config.path)
+    }
+
+    pub fn start_journal(&mut self, path: PathBuf) {
+        self.journal = Some(Journal::start(path));
+    }
+
+    /// Highest workout id so far; sets inserted afterwards have larger ids.
+    pub fn last_workout_id(&self) -> Result<i64> {
+        Ok(self.conn.query_row("SELECT COALESCE(MAX(id), 0) FROM workouts", [], |row| row.get(0))?)
+    }
+
+    /// Journals the sets inserted since `last_workout_id` returned `after`.
+    /// Call it once the inserting transaction has committed.
+    pub fn journal_inserted_after(&self, after: i64) -> Result<()> {
+        let Some(journal) = &self.journal else {
+            return Ok(());
+        };
+        let mut stmt = self.conn.prepare(
+            "SELECT uuid, exercise_type, count, timestamp, rpe, weight, recorded_at FROM workouts
+             WHERE id > ?1 AND deleted_at IS NULL AND uuid IS NOT NULL
+             ORDER BY id",
+        )?;
+        let entries = stmt
+            .query_map([after], |row| {
+                Ok(JournalEntry {
+                    uuid: row.get(0)?,
+                    exercise: row.get(1)?,
+                    count: row.get(2)?,
+                    timestamp: row.get(3)?,
+                    rpe: row.get(4)?,
+                    weight: row.get(5)?,
+                    recorded_at: row.get(6)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        for entry in entries {
+            journal.send(entry.line());
+        }
+        Ok(())
+    }
+
+    /// Journals permanently removed sets so they aren't offered for replay.
+    pub fn journal_purged(&self, uuids: &[String]) {
+        if let Some(journal) = &self.journal {
+            for uuid in uuids {
+                journal.send(format!("{PURGE},{uuid},,,,,,"));
+            }
+        }
+    }
+
+    /// Whether the journal is being written, and so needs to hear about purges.
+    pub fn journaling(&self) -> bool {
+        self.journal.is_some()
+    }
+
+    /// Sets in the journal at `path` that this database doesn't have, such
+    /// as after restoring an older backup.
+    pub fn journal_missing(&self, path: &Path) -> Result<Vec<JournalEntry>> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut seen = HashSet::new();
+        let mut missing = Vec::new();
+        for entry in parse_journal(&text) {
+            if !seen.insert(entry.uuid.clone()) {
+                continue;
+            }
+            let known = self
+                .conn
+                .query_row("SELECT 1 FROM workouts WHERE uuid = ?1", [&entry.uuid], |_| Ok(()))
+                .optional()?
+                .is_some();
+            if !known {
+                missing.push(entry);
+            }
+        }
+        Ok(missing)
+    }
+
+    /// Adds the journal's missing sets back with their original UUIDs.
+    pub fn replay_journal(&self, entries: &[JournalEntry]) -> Result<usize> {
+        let now = Local::now().format(dates::TIMESTAMP_FORMAT).to_string();
+        let tx = self.conn.unchecked_transaction()?;
+        let mut added = 0;
+        for entry in entries {
+            added += tx.execute(
+                "INSERT OR IGNORE INTO workouts (exercise_type, count, timestamp, rpe, weight, uuid, recorded_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    entry.exercise,
+                    entry.count,
+                    entry.timestamp,
+                    entry.rpe,
+                    entry.weight,
+                    entry.uuid,
+                    entry.recorded_at.as_deref().unwrap_or(&now)
+                ],
+            )?;
+        }
+        if added > 0 {
+            self.log_change(None, "journal", None, Some(&format!("{added} sets replayed")))?;
+        }
+        tx.commit()?;
+        Ok(added)
+    }
+
+    /// A failed journal write since the last check, if any.
+    fn journal_failure(&self) -> Option<String> {
+        let journal = self.journal.as_ref()?;
+        let mut last = None;
+        while let Ok(failure) = journal.failures.try_recv() {
+            last = Some(failure);
+        }
+        last
+    }
+}
+
+impl App {
+    pub(crate) fn check_journal(&mut self) {
+        if let Some(error) = self.db.journal_failure() {
+            self.message = Some(self.text.format("journal.failed", &[("error", &error)]));
+        }
+    }
+
+    pub(crate) fn handle_journal_input(&mut self, key: KeyCode) -> Result<bool> {
+        match key {
+            KeyCode::Char('y') | KeyCode::Enter => {
+                let entries = std::mem::take(&mut self.journal_replay);
+                let added = self.db.replay_journal(&entries)?;
+                self.message = Some(self.text.format("journal.replayed", &[("count", &added)]));
+            }
+            KeyCode::Char('n') | KeyCode::Esc => self.journal_replay.clear(),
+            _ => {}
+        }
+        Ok(false)
+    }
+}
@@ -0,0 +1,159 @@
+//! The app's JSON-lines backend: every row of every table as one JSON
+//! object per line, tagged with its table. The file is imported into an
+//! in-memory SQLite database at startup, which every feature then queries
+//! as usual, and the whole database is exported back over the file
+//! whenever the data changed. Rows are written in table and id order with
+//! a fixed column order, so an unchanged row is an unchanged line and diffs
+//! stay small. `JsonlStore` works on the same files without SQLite.
+
+use anyhow::{bail, Context, Result};
+use rusqlite::types::{Value, ValueRef};
+use serde_json::{Map, Number};
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{migrations, Database};
+
+/// Key naming a row's table, written first on every line.
+pub(crate) const TABLE_KEY: &str = "table";
+
+impl Database {
+    /// An in-memory database filled from the JSON-lines file at `path`,
+    /// which is written back by `save`. A missing file starts empty.
+    pub fn load_jsonl(path: &Path) -> Result<Self> {
+        let mut db = Database::new(":memory:")?;
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e).with_context(|| format!("failed to read {}", path.display())),
+        };
+        let tables = db.table_columns()?;
+        let tx = db.conn.unchecked_transaction()?;
+        // Seeded rows (like the default exercises) are replaced by the file's
+        if !text.trim().is_empty() {
+            for (table, _) in &tables {
+                tx.execute(&format!("DELETE FROM \"{table}\""), [])?;
+            }
+        }
+        for (number, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let at = || format!("{}:{}", path.display(), number + 1);
+            let mut row: Map<String, serde_json::Value> = serde_json::from_str(line).with_context(at)?;
+            let Some(serde_json::Value::String(table)) = row.remove(TABLE_KEY) else {
+                bail!("{}: no \"{TABLE_KEY}\" key", at());
+            };
+            let Some((_, columns)) = tables.iter().find(|(name, _)| *name == table) else {
+                bail!("{}: unknown table {table:?}", at());
+            };
+            let mut names = Vec::new();
+            let mut values = Vec::new();
+            for (column, value) in row {
+                if !columns.contains(&column) {
+                    bail!("{}: unknown column {column:?} in {table}", at());
+                }
+                names.push(format!("\"{column}\""));
+                values.push(match value {
+                    serde_json::Value::Null => Value::Null,
+                    serde_json::Value::Bool(b) => Value::Integer(b as i64),
+                    serde_json::Value::Number(n) => match n.as_i64() {
+                        Some(i) => Value::Integer(i),
+                        None => Value::Real(n.as_f64().unwrap_or_default()),
+                    },
+                    serde_json::Value::String(s) => Value::Text(s),
+                    other => bail!("{}: unsupported value {other} in {table}.{column}", at()),
+                });
+            }
+            let placeholders = vec!["?"; values.len()].join(", ");
+            tx.execute(
+                &format!("INSERT INTO \"{table}\" ({}) VALUES ({placeholders})", names.join(", ")),
+                rusqlite::params_from_iter(values),
+            )
+            .with_context(at)?;
+        }
+        tx.commit()?;
+        db.jsonl = Some(path.to_path_buf());
+        db.saved_changes.set(db.total_changes()?);
+        Ok(db)
+    }
+
+    /// Every table and its columns, in the order they're written.
+    fn table_columns(&self) -> Result<Vec<(String, Vec<String>)>> {
+        let mut stmt = self.conn.prepare(
+            // The daily totals and the notes index are worked out again by
+            // the triggers on load
+            "SELECT name FROM sqlite_master
+             WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND name != 'daily_totals'
+             ORDER BY name",
+        )?;
+        let mut tables = stmt.query_map([], |row| row.get::<_, String>(0))?.collect::<Result<Vec<_>, _>>()?;
+        tables.retain(|name| !migrations::is_notes_index(name));
+        tables
+            .into_iter()
+            .map(|table| {
+                let mut stmt = self.conn.prepare("SELECT name FROM pragma_table_info(?1) ORDER BY cid")?;
+                let columns = stmt.query_map([&table], |row| row.get(0))?.collect::<Result<Vec<String>, _>>()?;
+                Ok((table, columns))
+            })
+            .collect()
+    }
+
+    /// The whole database as JSON lines.
+    pub fn to_jsonl(&self) -> Result<String> {
+        let mut out = String::new();
+        for (table, columns) in self.table_columns()? {
+            let mut stmt = self.conn.prepare(&format!("SELECT * FROM \"{table}\" ORDER BY rowid"))?;
+            let mut rows = stmt.query([])?;
+            while let Some(row) = rows.next()? {
+                out.push_str(&format!("{{\"{TABLE_KEY}\":{}", serde_json::to_string(&table)?));
+                for (i, column) in columns.iter().enumerate() {
+                    let value = match row.get_ref(i)? {
+                        ValueRef::Null => serde_json::Value::Null,
+                        ValueRef::Integer(n) => n.into(),
+                        ValueRef::Real(f) => Number::from_f64(f).map_or(serde_json::Value::Null, Into::into),
+                        ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned().into(),
+                        ValueRef::Blob(_) => bail!("{table}.{column} holds binary data"),
+                    };
+                    out.push_str(&format!(",{}:{value}", serde_json::to_string(column)?));
+                }
+                out.push_str("}\n");
+            }
+        }
+        Ok(out)
+    }
+
+    /// Writes the data back to its JSON-lines file when anything changed
+    /// since it was loaded or last saved. Does nothing for SQLite, which
+    /// is saved as it goes. The new file replaces the old one only once
+    /// it's complete.
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.jsonl else {
+            return Ok(());
+        };
+        let changes = self.total_changes()?;
+        if changes == self.saved_changes.get() && !self.unsaved.get() {
+            return Ok(());
+        }
+        let temp = path.with_extension("jsonl.tmp");
+        let write = || -> io::Result<()> {
+            let mut file = fs::File::create(&temp)?;
+            file.write_all(self.to_jsonl().map_err(io::Error::other)?.as_bytes())?;
+            file.sync_all()?;
+            fs::rename(&temp, path)
+        };
+        write().with_context(|| format!("failed to save {}", path.display()))?;
+        self.saved_changes.set(changes);
+        self.unsaved.set(false);
+        Ok(())
+    }
+
+    /// Makes the next `save` write the file even if `total_changes` didn't
+    /// move.
+    pub(crate) fn mark_unsaved(&self) {
+        self.unsaved.set(true);
+    }
+}
@@ -0,0 +1,425 @@
+//! A `WorkoutStore` kept straight in a JSON-lines file, without SQLite. The
+//! file is read into memory once, with an index from day to sets for the
+//! date queries, and rewritten whole after every change, replacing the old
+//! one only once it's complete. It's the same format `jsonl` writes, one
+//! object per row tagged with its table, so the app's JSON-lines backend
+//! opens these files and the other way round. Rows of other tables are
+//! written back as they were read; only the exercises and tags among them
+//! are looked at, for the history filters, and the audit log is appended to.
+
+use anyhow::{bail, Context, Result};
+use chrono::Local;
+use serde_json::{Map, Value};
+use std::{
+    cell::RefCell,
+    collections::{hash_map::RandomState, BTreeMap, HashMap},
+    fs,
+    hash::{BuildHasher, Hasher},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::audit::{self, describe_sets};
+use crate::exercises::{normalize_name, OTHER_CATEGORY};
+use crate::jsonl::TABLE_KEY;
+use crate::store::{NewWorkout, WorkoutStore};
+use crate::{dates, HistoryFilter, WorkoutRecord};
+
+const WORKOUTS: &str = "workouts";
+/// The columns of `workouts`, in the order the database has them.
+pub(crate) const WORKOUT_COLUMNS: [&str; 10] =
+    ["id", "exercise_type", "count", "timestamp", "rpe", "uuid", "deleted_at", "recorded_at", "weight", "notes"];
+const AUDIT_COLUMNS: [&str; 6] = ["id", "row_uuid", "field", "old_value", "new_value", "at"];
+
+/// A row as read from the file, and the line it's written back as.
+#[derive(Debug, Clone)]
+struct Row {
+    table: String,
+    columns: Map<String, Value>,
+    line: String,
+}
+
+impl Row {
+    /// A row whose `columns` are written in the order of `order`, with any
+    /// others after them.
+    fn new(table: &str, order: &[&str], columns: Map<String, Value>) -> Self {
+        let mut row = Row { table: table.to_string(), columns, line: String::new() };
+        row.render(order);
+        row
+    }
+
+    fn render(&mut self, order: &[&str]) {
+        let known = order.iter().filter_map(|column| self.columns.get_key_value(*column));
+        let rest = self.columns.iter().filter(|(column, _)| !order.contains(&column.as_str()));
+        let mut line = format!("{{\"{TABLE_KEY}\":{}", Value::from(self.table.as_str()));
+        for (column, value) in known.chain(rest) {
+            line.push_str(&format!(",{}:{value}", Value::from(column.as_str())));
+        }
+        line.push('}');
+        self.line = line;
+    }
+
+    /// Changes one column of a set and the line with it.
+    fn set(&mut self, column: &str, value: Value) {
+        self.columns.insert(column.to_string(), value);
+        self.render(&WORKOUT_COLUMNS);
+    }
+
+    fn int(&self, column: &str) -> Option<i64> {
+        self.columns.get(column).and_then(Value::as_i64)
+    }
+
+    fn text(&self, column: &str) -> Option<&str> {
+        self.columns.get(column).and_then(Value::as_str)
+    }
+
+    fn id(&self) -> i64 {
+        self.int("id").unwrap_or_default()
+    }
+
+    fn day(&self) -> &str {
+        self.text("timestamp").and_then(|timestamp| timestamp.get(..10)).unwrap_or_default()
+    }
+
+    fn is_live(&self) -> bool {
+        self.text("deleted_at").is_none()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct State {
+    sets: Vec<Row>,
+    /// Positions in `sets` of each day's sets, in file order.
+    by_day: BTreeMap<String, Vec<usize>>,
+    /// Rows of every other table, in file order.
+    others: Vec<Row>,
+}
+
+impl State {
+    fn push_set(&mut self, row: Row) {
+        self.by_day.entry(row.day().to_string()).or_default().push(self.sets.len());
+        self.sets.push(row);
+    }
+
+    fn set_mut(&mut self, id: i64) -> Result<&mut Row> {
+        match self.sets.iter_mut().find(|set| set.id() == id) {
+            Some(set) => Ok(set),
+            None => bail!("no set with id {id}"),
+        }
+    }
+
+    fn rows<'a>(&'a self, table: &'a str) -> impl Iterator<Item = &'a Row> {
+        self.others.iter().filter(move |row| row.table == table)
+    }
+
+    /// Tag names per set id, sorted like `tags::split` sorts them.
+    fn tags(&self) -> HashMap<i64, Vec<String>> {
+        let names: HashMap<i64, &str> = self.rows("tags").filter_map(|tag| Some((tag.int("id")?, tag.text("name")?))).collect();
+        let mut tags: HashMap<i64, Vec<String>> = HashMap::new();
+        for link in self.rows("workout_tags") {
+            if let (Some(set), Some(name)) = (link.int("workout_id"), link.int("tag_id").and_then(|id| names.get(&id))) {
+                tags.entry(set).or_default().push(name.to_string());
+            }
+        }
+        for names in tags.values_mut() {
+            names.sort();
+        }
+        tags
+    }
+
+    fn record(&self, set: &Row, tags: &HashMap<i64, Vec<String>>) -> WorkoutRecord {
+        WorkoutRecord {
+            id: set.id(),
+            exercise_type: set.text("exercise_type").unwrap_or_default().to_string(),
+            count: set.int("count").unwrap_or_default() as i32,
+            timestamp: set.text("timestamp").unwrap_or_default().to_string(),
+            rpe: set.int("rpe").map(|rpe| rpe as i32),
+            weight: set.columns.get("weight").and_then(Value::as_f64),
+            note: set.text("notes").map(str::to_string),
+            tags: tags.get(&set.id()).cloned().unwrap_or_default(),
+        }
+    }
+
+    /// The live sets of `day`, in time order.
+    fn records_on(&self, day: &str) -> Vec<WorkoutRecord> {
+        let tags = self.tags();
+        let mut records: Vec<WorkoutRecord> = self
+            .by_day
+            .get(day)
+            .into_iter()
+            .flatten()
+            .map(|&i| &self.sets[i])
+            .filter(|set| set.is_live())
+            .map(|set| self.record(set, &tags))
+            .collect();
+        records.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        records
+    }
+
+    /// Appends an entry to the audit log, like `Database::log_change`.
+    fn log_change(&mut self, uuid: Option<&str>, field: &str, old: Option<&str>, new: Option<&str>) {
+        let id = self.rows("audit_log").filter_map(|entry| entry.int("id")).max().unwrap_or_default() + 1;
+        let columns = Map::from_iter([
+            ("id".to_string(), Value::from(id)),
+            ("row_uuid".to_string(), Value::from(uuid)),
+            ("field".to_string(), Value::from(field)),
+            ("old_value".to_string(), Value::from(old)),
+            ("new_value".to_string(), Value::from(new)),
+            ("at".to_string(), Value::from(now())),
+        ]);
+        self.others.push(Row::new("audit_log", &AUDIT_COLUMNS, columns));
+        self.others.retain(|row| row.table != "audit_log" || row.int("id").is_some_and(|old| old > id - audit::MAX_ENTRIES));
+    }
+
+    /// The whole file: every table in name order like `to_jsonl`, each in
+    /// the order its rows were read or added.
+    fn to_text(&self) -> String {
+        let mut others: Vec<&Row> = self.others.iter().collect();
+        others.sort_by(|a, b| a.table.cmp(&b.table));
+        let (before, after): (Vec<&Row>, Vec<&Row>) = others.into_iter().partition(|row| row.table.as_str() < WORKOUTS);
+        let mut text = String::new();
+        for row in before.into_iter().chain(&self.sets).chain(after) {
+            text.push_str(&row.line);
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// Sets kept in a JSON-lines file.
+#[derive(Debug)]
+pub struct JsonlStore {
+    path: PathBuf,
+    state: RefCell<State>,
+}
+
+impl JsonlStore {
+    /// Reads the JSON-lines file at `path`; a missing file starts empty and
+    /// is created on the first change.
+    pub fn open(path: &Path) -> Result<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e).with_context(|| format!("failed to read {}", path.display())),
+        };
+        let mut state = State::default();
+        for (number, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let at = || format!("{}:{}", path.display(), number + 1);
+            let mut columns: Map<String, Value> = serde_json::from_str(line).with_context(at)?;
+            let Some(Value::String(table)) = columns.remove(TABLE_KEY) else {
+                bail!("{}: no \"{TABLE_KEY}\" key", at());
+            };
+            let row = Row { table, columns, line: line.to_string() };
+            if row.table == WORKOUTS {
+                state.push_set(row);
+            } else {
+                state.others.push(row);
+            }
+        }
+        Ok(Self { path: path.to_path_buf(), state: RefCell::new(state) })
+    }
+
+    /// Makes `change` to a copy of the data and writes that to the file,
+    /// keeping it only once it's written, so a failure changes nothing.
+    fn change<T>(&self, change: impl FnOnce(&mut State) -> Result<T>) -> Result<T> {
+        let mut next = self.state.borrow().clone();
+        let result = change(&mut next)?;
+        let temp = self.path.with_extension("jsonl.tmp");
+        let write = || -> io::Result<()> {
+            let mut file = fs::File::create(&temp)?;
+            file.write_all(next.to_text().as_bytes())?;
+            file.sync_all()?;
+            fs::rename(&temp, &self.path)
+        };
+        write().with_context(|| format!("failed to save {}", self.path.display()))?;
+        *self.state.borrow_mut() = next;
+        Ok(result)
+    }
+
+    /// Maps a typed name to its canonical exercise, like
+    /// `Database::resolve_exercise`.
+    pub(crate) fn resolve_exercise(&self, name: &str) -> Option<String> {
+        let name = normalize_name(name);
+        let state = self.state.borrow();
+        let exercise = state.rows("exercises").find(|exercise| exercise.text("name") == Some(&name)).map(|_| name.clone());
+        exercise.or_else(|| {
+            let alias = state.rows("exercise_aliases").find(|alias| alias.text("alias") == Some(&name))?;
+            alias.text("exercise").map(str::to_string)
+        })
+    }
+
+    /// What an exercise's amounts count, `reps` unless it says otherwise.
+    pub(crate) fn unit(&self, exercise: &str) -> String {
+        let state = self.state.borrow();
+        let unit = state.rows("exercises").find(|row| row.text("name") == Some(exercise)).and_then(|row| row.text("unit"));
+        unit.unwrap_or("reps").to_string()
+    }
+
+    /// The live sets from `from` to `to` (both `YYYY-MM-DD`, either open),
+    /// oldest first, each with its UUID.
+    pub(crate) fn records_between(&self, from: Option<&str>, to: Option<&str>) -> Vec<(WorkoutRecord, Option<String>)> {
+        let state = self.state.borrow();
+        let tags = state.tags();
+        let mut sets: Vec<&Row> = state
+            .by_day
+            .iter()
+            .filter(|(day, _)| from.is_none_or(|from| day.as_str() >= from) && to.is_none_or(|to| day.as_str() <= to))
+            .flat_map(|(_, sets)| sets.iter().map(|&i| &state.sets[i]))
+            .filter(|set| set.is_live())
+            .collect();
+        sets.sort_by(|a, b| a.text("timestamp").cmp(&b.text("timestamp")).then(a.id().cmp(&b.id())));
+        sets.into_iter().map(|set| (state.record(set, &tags), set.text("uuid").map(str::to_string))).collect()
+    }
+}
+
+impl WorkoutStore for JsonlStore {
+    /// Writes the file once for all of `sets`, with the same audit entries
+    /// the database makes.
+    fn add_workouts(&self, sets: &[NewWorkout]) -> Result<usize> {
+        self.change(|state| {
+            let recorded_at = now();
+            let mut id = state.sets.iter().map(Row::id).max().unwrap_or_default();
+            let mut uuid = None;
+            for set in sets {
+                id += 1;
+                let set_uuid = new_uuid();
+                let columns = Map::from_iter([
+                    ("id".to_string(), Value::from(id)),
+                    ("exercise_type".to_string(), Value::from(set.exercise_type.as_str())),
+                    ("count".to_string(), Value::from(set.count)),
+                    ("timestamp".to_string(), Value::from(set.timestamp.as_str())),
+                    ("rpe".to_string(), Value::from(set.rpe)),
+                    ("uuid".to_string(), Value::from(set_uuid.as_str())),
+                    ("deleted_at".to_string(), Value::Null),
+                    ("recorded_at".to_string(), Value::from(recorded_at.as_str())),
+                    ("weight".to_string(), Value::from(set.weight)),
+                    ("notes".to_string(), Value::Null),
+                ]);
+                state.push_set(Row::new(WORKOUTS, &WORKOUT_COLUMNS, columns));
+                uuid = Some(set_uuid);
+            }
+            match sets {
+                [] => {}
+                [set] => state.log_change(uuid.as_deref(), "created", None, Some(&set.describe())),
+                _ => {
+                    let summary = describe_sets(sets.iter().map(|set| set.timestamp.as_str()));
+                    state.log_change(None, "batch", None, Some(&summary));
+                }
+            }
+            Ok(sets.len())
+        })
+    }
+
+    fn get_today_workouts(&self) -> Result<Vec<WorkoutRecord>> {
+        Ok(self.state.borrow().records_on(&Local::now().format("%Y-%m-%d").to_string()))
+    }
+
+    fn get_last_workout_date(&self) -> Result<Option<String>> {
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        let state = self.state.borrow();
+        let last = state
+            .by_day
+            .range(..today)
+            .rev()
+            .find(|(_, sets)| sets.iter().any(|&i| state.sets[i].is_live()))
+            .map(|(day, _)| day.clone());
+        Ok(last)
+    }
+
+    fn get_workouts_by_date(&self, date: &str) -> Result<Vec<WorkoutRecord>> {
+        Ok(self.state.borrow().records_on(date))
+    }
+
+    fn get_unique_dates(&self, filter: &HistoryFilter) -> Result<Vec<String>> {
+        let state = self.state.borrow();
+        let categories: HashMap<&str, &str> =
+            state.rows("exercises").filter_map(|row| Some((row.text("name")?, row.text("category")?))).collect();
+        let tags = match filter {
+            HistoryFilter::Tag(_) => state.tags(),
+            _ => HashMap::new(),
+        };
+        let matches = |set: &Row| {
+            let exercise = set.text("exercise_type").unwrap_or_default();
+            match filter {
+                HistoryFilter::All => true,
+                HistoryFilter::Category(category) => categories.get(exercise).copied().unwrap_or(OTHER_CATEGORY) == category,
+                HistoryFilter::Exercise(name) => exercise == name,
+                HistoryFilter::Tag(tag) => tags.get(&set.id()).is_some_and(|names| names.contains(tag)),
+            }
+        };
+        let dates = state
+            .by_day
+            .iter()
+            .rev()
+            .filter(|(_, sets)| sets.iter().map(|&i| &state.sets[i]).any(|set| set.is_live() && matches(set)))
+            .map(|(day, _)| day.clone())
+            .collect();
+        Ok(dates)
+    }
+
+    fn delete_workout(&self, id: i64) -> Result<()> {
+        if !self.state.borrow().sets.iter().any(|set| set.id() == id && set.is_live()) {
+            return Ok(());
+        }
+        self.change(|state| {
+            let now = now();
+            let set = state.set_mut(id)?;
+            set.set("deleted_at", Value::from(now.as_str()));
+            let uuid = set.text("uuid").map(str::to_string);
+            state.log_change(uuid.as_deref(), "deleted_at", None, Some(&now));
+            Ok(())
+        })
+    }
+
+    fn restore_workout(&self, id: i64) -> Result<()> {
+        self.change(|state| {
+            let set = state.set_mut(id)?;
+            let deleted_at = set.text("deleted_at").map(str::to_string);
+            set.set("deleted_at", Value::Null);
+            let uuid = set.text("uuid").map(str::to_string);
+            state.log_change(uuid.as_deref(), "deleted_at", deleted_at.as_deref(), None);
+            Ok(())
+        })
+    }
+
+    fn update_workout_count(&self, id: i64, count: i32) -> Result<()> {
+        let old = self.state.borrow_mut().set_mut(id)?.int("count").unwrap_or_default();
+        if old == count as i64 {
+            return Ok(());
+        }
+        self.change(|state| {
+            let set = state.set_mut(id)?;
+            set.set("count", Value::from(count));
+            let uuid = set.text("uuid").map(str::to_string);
+            state.log_change(uuid.as_deref(), "count", Some(&old.to_string()), Some(&count.to_string()));
+            Ok(())
+        })
+    }
+}
+
+fn now() -> String {
+    Local::now().format(dates::TIMESTAMP_FORMAT).to_string()
+}
+
+/// A random v4 UUID, in the form the database's trigger gives its sets.
+fn new_uuid() -> String {
+    let random = || {
+        // Every RandomState is seeded differently
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos());
+        hasher.finish()
+    };
+    let (high, low) = (random(), random());
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        high >> 32,
+        (high >> 16) & 0xffff,
+        (high & 0x0fff) | 0x4000,
+        ((low >> 48) & 0x3fff) | 0x8000,
+        low & 0xffff_ffff_ffff
+    )
+}
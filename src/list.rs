@@ -0,0 +1,164 @@
+//! `fitness-tracker list`: sets for scripts, written to stdout as they're
+//! read, one JSON object or TSV row per set, so a long history never sits in
+//! memory. Amounts are kept as stored, like the flat exports: reps, seconds
+//! or meters, and kg.
+
+use anyhow::{bail, Result};
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::io::{self, ErrorKind, Write};
+
+use crate::jsonl_store::JsonlStore;
+use crate::store::TAGS;
+use crate::{tags, Database};
+
+const TSV_COLUMNS: [&str; 9] = ["id", "uuid", "timestamp", "exercise", "count", "unit", "rpe", "weight_kg", "tags"];
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ListFormat {
+    /// A JSON object per line
+    Json,
+    /// Tab-separated values under a header row
+    Tsv,
+}
+
+/// Which sets to list; every filter given has to match.
+#[derive(Debug, Default)]
+pub struct ListFilter {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    /// Exercise names as stored; empty for all of them.
+    pub exercises: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ListRow {
+    id: i64,
+    uuid: Option<String>,
+    timestamp: String,
+    exercise: String,
+    count: i64,
+    unit: String,
+    rpe: Option<i64>,
+    weight_kg: Option<f64>,
+    tags: Vec<String>,
+}
+
+impl ListRow {
+    fn tsv(&self) -> String {
+        // Tabs and line breaks would break the row apart
+        let field = |text: &str| text.replace(['\t', '\n', '\r'], " ");
+        let optional = |value: Option<String>| value.unwrap_or_default();
+        [
+            self.id.to_string(),
+            optional(self.uuid.clone()),
+            field(&self.timestamp),
+            field(&self.exercise),
+            self.count.to_string(),
+            field(&self.unit),
+            optional(self.rpe.map(|rpe| rpe.to_string())),
+            optional(self.weight_kg.map(|weight| weight.to_string())),
+            field(&self.tags.join(",")),
+        ]
+        .join("\t")
+    }
+}
+
+/// Parses a `--from` or `--to` day, saying what was expected when it isn't one.
+pub fn parse_day(text: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").map_err(|e| format!("{e}; expected a day like 2024-05-01"))
+}
+
+impl ListFilter {
+    fn check(&self) -> Result<()> {
+        if let (Some(from), Some(to)) = (self.from, self.to)
+            && from > to
+        {
+            bail!("--from {from} is after --to {to}");
+        }
+        Ok(())
+    }
+}
+
+fn day(date: Option<NaiveDate>) -> Option<String> {
+    date.map(|date| date.format("%Y-%m-%d").to_string())
+}
+
+impl Database {
+    /// Writes the sets `filter` matches to `out`, oldest first, skipping
+    /// the trash. A reader that stops early, like `head`, just ends the list.
+    pub fn write_set_list(&self, out: &mut impl Write, filter: &ListFilter, format: ListFormat) -> Result<()> {
+        filter.check()?;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, uuid, timestamp, exercise_type, count, COALESCE(e.unit, 'reps'), rpe, weight, {TAGS}
+             FROM workouts
+             LEFT JOIN exercises e ON e.name = workouts.exercise_type
+             WHERE deleted_at IS NULL
+               AND (?1 IS NULL OR substr(timestamp, 1, 10) >= ?1)
+               AND (?2 IS NULL OR substr(timestamp, 1, 10) <= ?2)
+             ORDER BY timestamp ASC, id ASC"
+        ))?;
+        let rows = stmt.query_map(rusqlite::params![day(filter.from), day(filter.to)], |row| {
+            Ok(ListRow {
+                id: row.get(0)?,
+                uuid: row.get(1)?,
+                timestamp: row.get(2)?,
+                exercise: row.get(3)?,
+                count: row.get(4)?,
+                unit: row.get(5)?,
+                rpe: row.get(6)?,
+                weight_kg: row.get(7)?,
+                tags: tags::split(row.get(8)?),
+            })
+        })?;
+        write_list(out, rows.map(|row| Ok(row?)), filter, format)
+    }
+}
+
+impl JsonlStore {
+    /// `Database::write_set_list` for a JSON-lines file read as it is.
+    pub fn write_set_list(&self, out: &mut impl Write, filter: &ListFilter, format: ListFormat) -> Result<()> {
+        filter.check()?;
+        let rows = self.records_between(day(filter.from).as_deref(), day(filter.to).as_deref()).into_iter().map(|(set, uuid)| {
+            Ok(ListRow {
+                unit: self.unit(&set.exercise_type),
+                id: set.id,
+                uuid,
+                timestamp: set.timestamp,
+                exercise: set.exercise_type,
+                count: set.count.into(),
+                rpe: set.rpe.map(Into::into),
+                weight_kg: set.weight,
+                tags: set.tags,
+            })
+        });
+        write_list(out, rows, filter, format)
+    }
+}
+
+/// Writes `rows`, ending quietly when the reader stops early.
+fn write_list(out: &mut impl Write, rows: impl Iterator<Item = Result<ListRow>>, filter: &ListFilter, format: ListFormat) -> Result<()> {
+    match write_rows(out, rows, filter, format) {
+        Err(e) if e.downcast_ref::<io::Error>().is_some_and(|e| e.kind() == ErrorKind::BrokenPipe) => Ok(()),
+        result => result,
+    }
+}
+
+fn write_rows(out: &mut impl Write, rows: impl Iterator<Item = Result<ListRow>>, filter: &ListFilter, format: ListFormat) -> Result<()> {
+    if format == ListFormat::Tsv {
+        writeln!(out, "{}", TSV_COLUMNS.join("\t"))?;
+    }
+    for set in rows {
+        let set = set?;
+        if !filter.exercises.is_empty() && !filter.exercises.contains(&set.exercise) {
+            continue;
+        }
+        match format {
+            ListFormat::Json => writeln!(out, "{}", serde_json::to_string(&set)?)?,
+            ListFormat::Tsv => writeln!(out, "{}", set.tsv())?,
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
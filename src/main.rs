@@ -1,34 +1,211 @@
-use anyhow::Result;
-use chrono::Local;
+use anyhow::{bail, Context, Result};
+use chrono::{Local, NaiveDate};
+use clap::Parser;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{self, Event, KeyCode, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{
     backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Row, Table, Wrap},
+    text::{Line, Span},
+    widgets::{
+        block::{Position, Title},
+        Block, Borders, Cell as TableCell, List, ListItem, ListState, Paragraph, Row, Scrollbar, ScrollbarOrientation,
+        ScrollbarState, Table, Wrap,
+    },
     Frame, Terminal,
 };
-use rusqlite::{params, Connection};
-use std::io;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    io,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+mod about;
+mod amrap;
+mod anonymize;
+mod apple_health;
+mod archive;
+mod audit;
+mod backup;
+mod bulk_delete;
+mod chart;
+mod cli;
+mod checklist;
+mod clipboard;
+mod compare;
+mod config;
+mod csv_import;
+mod daily_totals;
+mod data;
+mod dates;
+mod day_plan;
+mod day_compare;
+mod day_view;
+mod db_picker;
+mod db_watch;
+mod deadline_goals;
+mod encryption;
+mod exercise_detail;
+mod exercises;
+mod export;
+mod export_dialog;
+mod fitnotes;
+mod generic_csv;
+mod goals;
+mod health;
+mod heat;
+mod html;
+mod i18n;
+mod ics;
+mod input;
+mod instance_lock;
+mod journal;
+mod jsonl;
+mod jsonl_store;
+mod list;
+mod maintenance;
+mod measurements;
+mod merge;
+mod migrations;
+mod month_view;
+mod mood;
+mod mouse;
+mod notes;
+mod notify;
+mod onboarding;
+mod palette;
+mod plates;
+mod profiles;
+mod progression;
+mod quick_stats;
+mod recap;
+mod reload;
+mod reminders;
+mod report;
+mod rest;
+mod search;
+mod sets_export;
+mod selection;
+mod settings;
+mod setup_file;
+mod startup;
+mod stats;
+mod stopwatch;
+mod store;
+mod strength;
+mod strong;
+mod tags;
+mod template;
+mod timer;
+mod today;
+mod trash;
+mod trend;
+mod units;
+mod updates;
+mod weekdays;
+mod webhook;
+mod week_view;
+
+use cli::{Cli, Command};
+use checklist::{ChecklistItem, ItemState};
+use clipboard::Clipboard;
+use amrap::AmrapState;
+use anonymize::Anonymize;
+use compare::CompareState;
+use config::Config;
+use data::DataState;
+use encryption::Passphrase;
+use exercises::{Exercise, ExercisesState};
+use export::ExportOptions;
+use export_dialog::ExportDialog;
+use goals::GoalsState;
+use health::HealthState;
+use i18n::Messages;
+use input::{NumericInput, TextInput};
+use instance_lock::{Acquired, Choice};
+use journal::{Journal, JournalEntry};
+use jsonl_store::JsonlStore;
+use list::ListFilter;
+use maintenance::VacuumJob;
+use measurements::MeasurementsState;
+use mouse::Hitboxes;
+use notify::Notifier;
+use day_view::DayRow;
+use month_view::MonthState;
+use onboarding::Onboarding;
+use profiles::{Profiles, ProfilesState, DEFAULT_PROFILE};
+use recap::SessionStart;
+use reload::{ConfigWatch, Toast};
+use reminders::Reminder;
+use stopwatch::StopwatchState;
+use store::{Backend, WorkoutStore};
+use report::ReportState;
+use search::SearchState;
+use exercise_detail::ExerciseDetailState;
+use selection::Selection;
+use settings::SettingsState;
+use template::Guided;
+use timer::RestTimer;
+use updates::UpdateCheck;
+use trash::TrashState;
+use webhook::WebhookState;
+use week_view::WeekState;
 
 #[derive(Debug, Clone)]
 struct WorkoutRecord {
+    id: i64,
     exercise_type: String,
     count: i32,
     timestamp: String,
+    rpe: Option<i32>,
+    /// Kilograms, for sets imported from apps that track weight.
+    weight: Option<f64>,
+    tags: Vec<String>,
+    note: Option<String>,
 }
 
 struct Database {
     conn: Connection,
+    journal: Option<Journal>,
+    /// JSON-lines file the in-memory database is saved to, for the jsonl backend.
+    jsonl: Option<PathBuf>,
+    /// `total_changes` when the JSON-lines file was last written.
+    saved_changes: Cell<i64>,
+    /// Set by changes `total_changes` doesn't count, like a restore through
+    /// the backup API, so the next save writes the file anyway.
+    unsaved: Cell<bool>,
+    /// Passphrase of an encrypted database, kept for the connections
+    /// opened to back it up, vacuum it or merge into it.
+    passphrase: Option<Passphrase>,
 }
 
 impl Database {
     fn new(path: &str) -> Result<Self> {
-        let conn = Connection::open(path)?;
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Creates any missing tables on an open (and, if encrypted, unlocked)
+    /// connection and migrates it to the current schema.
+    fn with_connection(mut conn: Connection) -> Result<Self> {
+        Self::create_tables(&mut conn)?;
+        Ok(Self {
+            conn,
+            journal: None,
+            jsonl: None,
+            saved_changes: Cell::new(0),
+            unsaved: Cell::new(false),
+            passphrase: None,
+        })
+    }
+
+    fn create_tables(conn: &mut Connection) -> Result<()> {
         conn.execute(
             "CREATE TABLE IF NOT EXISTS workouts (
                 id INTEGER PRIMARY KEY,
@@ -38,147 +215,487 @@ impl Database {
             )",
             [],
         )?;
-        Ok(Self { conn })
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS measurements (
+                id INTEGER PRIMARY KEY,
+                date TEXT NOT NULL,
+                metric TEXT NOT NULL,
+                value REAL NOT NULL,
+                unit TEXT NOT NULL
+            )",
+            [],
+        )?;
+        migrations::migrate(conn)
+    }
+
+    fn get_state(&self, key: &str) -> Result<Option<String>> {
+        let value = self
+            .conn
+            .query_row("SELECT value FROM app_state WHERE key = ?1", [key], |row| row.get(0))
+            .optional()?;
+        Ok(value)
     }
 
-    fn add_workout(&self, exercise_type: &str, count: i32) -> Result<()> {
-        let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    fn set_state(&self, key: &str, value: &str) -> Result<()> {
         self.conn.execute(
-            "INSERT INTO workouts (exercise_type, count, timestamp) VALUES (?1, ?2, ?3)",
-            params![exercise_type, count, timestamp],
+            "INSERT INTO app_state (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![key, value],
         )?;
         Ok(())
     }
+}
 
-    fn get_today_workouts(&self) -> Result<Vec<WorkoutRecord>> {
-        let today = Local::now().format("%Y-%m-%d").to_string();
-        let mut stmt = self.conn.prepare(
-            "SELECT exercise_type, count, timestamp FROM workouts
-             WHERE substr(timestamp, 1, 10) = ?1
-             ORDER BY timestamp ASC",
-        )?;
+enum Screen {
+    Main,
+    AddWorkout,
+    History,
+    Measurements,
+    Stats,
+    Exercises,
+    Goals,
+    Data,
+    Health,
+    Trash,
+    Report,
+    Profiles,
+    Compare,
+    Settings,
+    About,
+    Stopwatch,
+    Amrap,
+    Export,
+    Week,
+    Month,
+    Search,
+    ExerciseDetail,
+}
 
-        let records = stmt
-            .query_map([today], |row| {
-                Ok(WorkoutRecord {
-                    exercise_type: row.get(0)?,
-                    count: row.get(1)?,
-                    timestamp: row.get(2)?,
-                })
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
-        
-        Ok(records)
-    }
+#[derive(Clone, PartialEq)]
+enum HistoryFilter {
+    All,
+    Category(String),
+    Exercise(String),
+    Tag(String),
+}
 
-    fn get_last_workout_date(&self) -> Result<Option<String>> {
-        let today = Local::now().format("%Y-%m-%d").to_string();
-        let mut stmt = self.conn.prepare(
-            "SELECT DISTINCT substr(timestamp, 1, 10) as workout_date
-             FROM workouts
-             WHERE substr(timestamp, 1, 10) < ?1
-             ORDER BY workout_date DESC
-             LIMIT 1",
-        )?;
-        
-        let mut rows = stmt.query([today])?;
-        if let Some(row) = rows.next()? {
-            Ok(Some(row.get(0)?))
-        } else {
-            Ok(None)
-        }
+impl HistoryFilter {
+    /// Cycles all -> each category -> each exercise -> each tag -> all.
+    fn next(&self, exercises: &[Exercise], tags: &[String]) -> Self {
+        let mut options = vec![HistoryFilter::All];
+        options.extend(
+            exercises::CATEGORIES
+                .iter()
+                .chain([&exercises::OTHER_CATEGORY])
+                .map(|c| HistoryFilter::Category(c.to_string())),
+        );
+        options.extend(exercises.iter().map(|e| HistoryFilter::Exercise(e.name.clone())));
+        options.extend(tags.iter().map(|tag| HistoryFilter::Tag(tag.clone())));
+        let current = options.iter().position(|o| o == self).unwrap_or(0);
+        options.swap_remove((current + 1) % options.len())
     }
 
-    fn get_workouts_by_date(&self, date: &str) -> Result<Vec<WorkoutRecord>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT exercise_type, count, timestamp FROM workouts
-             WHERE substr(timestamp, 1, 10) = ?1
-             ORDER BY timestamp ASC",
-        )?;
-
-        let records = stmt
-            .query_map([date], |row| {
-                Ok(WorkoutRecord {
-                    exercise_type: row.get(0)?,
-                    count: row.get(1)?,
-                    timestamp: row.get(2)?,
-                })
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
-        
-        Ok(records)
+    fn matches(&self, record: &WorkoutRecord, exercises: &[Exercise]) -> bool {
+        match self {
+            HistoryFilter::All => true,
+            HistoryFilter::Category(category) => {
+                let actual = exercises
+                    .iter()
+                    .find(|e| e.name == record.exercise_type)
+                    .map(|e| e.category())
+                    .unwrap_or(exercises::OTHER_CATEGORY);
+                actual == category
+            }
+            HistoryFilter::Exercise(name) => record.exercise_type == *name,
+            HistoryFilter::Tag(tag) => record.tags.contains(tag),
+        }
     }
 
-    fn get_unique_dates(&self) -> Result<Vec<String>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT DISTINCT date(timestamp) as workout_date 
-             FROM workouts 
-             ORDER BY workout_date DESC",
-        )?;
-        
-        let dates = stmt
-            .query_map([], |row| row.get(0))?
-            .collect::<Result<Vec<_>, _>>()?;
-        
-        Ok(dates)
+    fn label(&self, text: &Messages) -> String {
+        match self {
+            HistoryFilter::All => text.get("history.filter_all").to_string(),
+            HistoryFilter::Category(category) => text.format("history.filter_category", &[("category", category)]),
+            HistoryFilter::Exercise(name) => text.format("history.filter_exercise", &[("exercise", name)]),
+            HistoryFilter::Tag(tag) => text.format("history.filter_tag", &[("tag", tag)]),
+        }
     }
 }
 
-enum Screen {
-    Main,
-    AddWorkout,
-    History,
-}
-
-enum ExerciseType {
-    Squats,
-    PushUps,
+#[derive(PartialEq)]
+enum AddField {
+    Count,
+    Weight,
+    Rpe,
+    Tags,
 }
 
 struct App {
     db: Database,
+    config: Config,
+    /// config.toml as loaded, before the settings stored in the database.
+    file_config: Config,
+    config_watch: ConfigWatch,
+    /// Changes made to the data file by anything but this session.
+    db_watch: db_watch::DbWatch,
+    /// This instance's claim on the data file; `None` when another has it.
+    instance_lock: Option<instance_lock::InstanceLock>,
+    /// Whether the data file is open read-only, because another instance has it.
+    read_only: bool,
+    text: Messages,
     screen: Screen,
-    selected_exercise: ExerciseType,
-    input_count: String,
+    exercises: Vec<Exercise>,
+    /// Latest set per exercise, read once per session so the picker doesn't
+    /// reorder itself while sets are logged.
+    last_used: HashMap<String, String>,
+    selected_exercise: usize,
+    input_count: NumericInput,
+    input_rpe: NumericInput,
+    /// In the configured weight unit; kept between sets of the same exercise.
+    input_weight: NumericInput,
+    /// Comma-separated; kept between sets like the weight.
+    input_tags: TextInput,
+    /// The exercise's planned sets being walked through, if any.
+    guided: Option<Guided>,
+    add_field: AddField,
+    /// Target weight typed into the plate calculator while it's open.
+    plates: Option<NumericInput>,
     history_selected: usize,
+    history_filter: HistoryFilter,
     selected_date: Option<String>,
+    history_entry: usize,
+    history_offset: Cell<usize>,
+    /// Heat bucket per day for the date list, worked out on first use.
+    history_heat: RefCell<Option<HashMap<String, u8>>>,
+    /// Set amounts per day for the date list's bars, read on first use.
+    history_sets: RefCell<Option<HashMap<String, Vec<i64>>>>,
+    /// New amount being typed for the highlighted entry of the day view.
+    history_edit: Option<NumericInput>,
+    /// Note being typed for the highlighted entry of the day view.
+    history_note: Option<TextInput>,
+    /// Show the change log of the highlighted entry.
+    history_log: bool,
+    /// The day view groups sets by exercise instead of listing them by time.
+    history_grouped: bool,
+    /// Screen the day view was opened from, which Esc goes back to.
+    history_back: Option<Screen>,
+    /// Sets picked in the day view or the search results.
+    selection: Selection,
+    /// Screen the export was opened from, when not the data screen.
+    export_back: Option<Screen>,
+    /// Date marked in the list to compare another one with.
+    history_mark: Option<String>,
+    /// Marked and chosen dates while they're shown side by side.
+    history_compare: Option<(String, String)>,
+    exercises_screen: ExercisesState,
+    measurements: MeasurementsState,
+    goals_screen: GoalsState,
+    data_screen: DataState,
+    health: HealthState,
+    trash: TrashState,
+    report: ReportState,
+    week: WeekState,
+    month: MonthState,
+    search: SearchState,
+    exercise_detail: ExerciseDetailState,
+    settings_screen: SettingsState,
+    /// Whether Esc on the about screen goes back to settings.
+    about_from_settings: bool,
+    /// The stats screen shows the daily chart instead of the daily table.
+    stats_chart: bool,
+    /// The daily chart has its moving average line.
+    stats_average: bool,
+    /// The stats screen shows weekday patterns instead of categories.
+    stats_weekdays: bool,
+    /// Index into `weekdays::RANGES`.
+    stats_weekday_range: usize,
+    /// Tag the stats are limited to.
+    stats_tag: Option<String>,
+    profiles: ProfilesState,
+    compare: Option<CompareState>,
+    rest_timer: Option<RestTimer>,
+    stopwatch: StopwatchState,
+    amrap: Option<AmrapState>,
+    notifier: Notifier,
+    clipboard: Clipboard,
+    reminder: Option<Reminder>,
+    /// What's left of an unmet daily goal late in the day.
+    goal_nag: Option<String>,
+    vacuum: Option<VacuumJob>,
+    update_check: Option<UpdateCheck>,
+    /// Newer release to mention in the status bar.
+    update_notice: Option<String>,
+    webhook: WebhookState,
+    /// Export screen choices, kept for the rest of the session.
+    export_dialog: ExportDialog,
+    mood_prompt: bool,
+    /// The quick-stats popup over the main screen while it is open.
+    quick_stats: Option<quick_stats::QuickStats>,
+    /// The command palette while it is open.
+    palette: Option<palette::PaletteState>,
+    /// The last screen worth reopening next time, if `ui.restore_state` is on.
+    last_screen: startup::StartScreen,
+    /// Whether the first frame is up but the startup work left for the
+    /// first tick isn't done; the main screen shows placeholders meanwhile.
+    starting: bool,
+    onboarding: Option<Onboarding>,
+    /// Journaled sets missing from the database, offered for replay at startup.
+    journal_replay: Vec<JournalEntry>,
     message: Option<String>,
+    /// Note about the reloaded config, shown on every screen for a moment.
+    toast: Option<Toast>,
+    hitboxes: RefCell<Hitboxes>,
+    last_click: Option<(Instant, usize)>,
+    mouse_capture: bool,
 }
 
 impl App {
-    fn new(db: Database) -> Self {
-        Self {
+    fn new(db: Database, config: Config) -> Result<Self> {
+        let exercises = db.get_exercises()?;
+        let last_used = db.last_used_exercises()?;
+        let backend = if db.jsonl.is_some() { Backend::Jsonl } else { Backend::Sqlite };
+        let profiles = Profiles {
+            base: db.file_path(),
+            backend,
+        };
+        let file_config = config;
+        let mut config = file_config.clone();
+        settings::apply(&mut config, &db.get_settings()?);
+        let export_dialog = ExportDialog::new(&config.csv);
+        let db_watch = db_watch::DbWatch::new(&db);
+        let mut app = Self {
+            profiles: ProfilesState::new(profiles, DEFAULT_PROFILE),
+            compare: None,
             db,
+            text: Messages::for_language(&config.language),
+            config,
+            file_config,
+            config_watch: ConfigWatch::new(config::CONFIG_PATH),
+            db_watch,
+            instance_lock: None,
+            read_only: false,
             screen: Screen::Main,
-            selected_exercise: ExerciseType::Squats,
-            input_count: String::new(),
+            exercises,
+            last_used,
+            selected_exercise: 0,
+            input_count: NumericInput::default(),
+            input_rpe: NumericInput::default(),
+            input_weight: NumericInput::decimal(),
+            input_tags: TextInput::default(),
+            guided: None,
+            add_field: AddField::Count,
+            plates: None,
             history_selected: 0,
+            history_filter: HistoryFilter::All,
             selected_date: None,
+            history_entry: 0,
+            history_offset: Cell::new(0),
+            history_heat: RefCell::default(),
+            history_sets: RefCell::default(),
+            history_edit: None,
+            history_note: None,
+            history_log: false,
+            history_grouped: false,
+            history_back: None,
+            selection: Selection::default(),
+            export_back: None,
+            history_mark: None,
+            history_compare: None,
+            exercises_screen: ExercisesState::default(),
+            measurements: MeasurementsState::default(),
+            goals_screen: GoalsState::default(),
+            data_screen: DataState::default(),
+            health: HealthState::default(),
+            trash: TrashState::default(),
+            report: ReportState {
+                start: Local::now().date_naive(),
+            },
+            week: WeekState {
+                start: Local::now().date_naive(),
+                day: 0,
+            },
+            month: MonthState {
+                cursor: Local::now().date_naive(),
+            },
+            search: SearchState::default(),
+            exercise_detail: ExerciseDetailState::default(),
+            settings_screen: SettingsState::default(),
+            about_from_settings: false,
+            stats_chart: false,
+            stats_average: true,
+            stats_weekdays: false,
+            stats_weekday_range: 0,
+            stats_tag: None,
+            rest_timer: None,
+            stopwatch: StopwatchState::default(),
+            amrap: None,
+            notifier: Notifier::default(),
+            clipboard: Clipboard::default(),
+            reminder: None,
+            goal_nag: None,
+            vacuum: None,
+            update_check: None,
+            update_notice: None,
+            webhook: WebhookState::default(),
+            export_dialog,
+            mood_prompt: false,
+            quick_stats: None,
+            palette: None,
+            last_screen: startup::StartScreen::Main,
+            starting: false,
+            onboarding: None,
+            journal_replay: Vec::new(),
             message: None,
-        }
+            toast: None,
+            hitboxes: RefCell::default(),
+            last_click: None,
+            mouse_capture: false,
+        };
+        app.select_default_exercise();
+        Ok(app)
     }
 
     fn handle_input(&mut self, key: KeyCode) -> Result<bool> {
-        match &self.screen {
+        if key == KeyCode::F(2) {
+            self.set_mouse_capture(!self.mouse_capture)?;
+            return Ok(false);
+        }
+        if key == KeyCode::F(3) && self.reminder.is_some() {
+            self.reminder = None;
+            return Ok(false);
+        }
+        if key == KeyCode::F(3) && self.goal_nag.is_some() {
+            self.dismiss_goal_nag()?;
+            return Ok(false);
+        }
+        if key == KeyCode::F(4) && self.update_notice.is_some() {
+            self.dismiss_update()?;
+            return Ok(false);
+        }
+        if key == KeyCode::F(5) && self.db_watch.change.is_some() {
+            self.handle_reload_key();
+            return Ok(false);
+        }
+        if !self.journal_replay.is_empty() {
+            return self.handle_journal_input(key);
+        }
+        if self.onboarding.is_some() {
+            return self.handle_onboarding_input(key);
+        }
+        if self.mood_prompt {
+            return self.handle_mood_prompt_input(key);
+        }
+        if self.quick_stats.take().is_some() {
+            return Ok(false);
+        }
+        let was_main = matches!(self.screen, Screen::Main);
+        let was_history = matches!(self.screen, Screen::History);
+        let quit = match &self.screen {
+            _ if self.palette.is_some() => self.handle_palette_input(key),
             Screen::Main => self.handle_main_input(key),
             Screen::AddWorkout => self.handle_add_workout_input(key),
             Screen::History => self.handle_history_input(key),
+            Screen::Measurements => self.handle_measurements_input(key),
+            Screen::Stats => self.handle_stats_input(key),
+            Screen::Exercises => self.handle_exercises_input(key),
+            Screen::Goals => self.handle_goals_input(key),
+            Screen::Data => self.handle_data_input(key),
+            Screen::Health => self.handle_health_input(key),
+            Screen::Trash => self.handle_trash_input(key),
+            Screen::Report => self.handle_report_input(key),
+            Screen::Profiles => self.handle_profiles_input(key),
+            Screen::Compare => self.handle_compare_input(key),
+            Screen::Stopwatch => self.handle_stopwatch_input(key),
+            Screen::Amrap => self.handle_amrap_input(key),
+            Screen::Export => self.handle_export_input(key),
+            Screen::Settings => self.handle_settings_input(key),
+            Screen::About => self.handle_about_input(key),
+            Screen::Week => self.handle_week_input(key),
+            Screen::Month => self.handle_month_input(key),
+            Screen::Search => self.handle_search_input(key),
+            Screen::ExerciseDetail => self.handle_exercise_detail_input(key),
+        }?;
+        // Statuses from other screens are stale by the time the main screen
+        // shows them
+        if !was_main && matches!(self.screen, Screen::Main) {
+            self.message = None;
         }
+        if !was_history && matches!(self.screen, Screen::History) {
+            self.forget_history_days();
+        }
+        self.note_place();
+        Ok(quit)
+    }
+
+    fn open_add_workout(&mut self) {
+        self.screen = Screen::AddWorkout;
+        self.prefill_count();
+        self.input_rpe.clear();
+        self.add_field = AddField::Count;
+        self.message = None;
+    }
+
+    fn open_history(&mut self) {
+        self.screen = Screen::History;
+        self.history_selected = 0;
+        self.selected_date = None;
+        self.history_back = None;
+        self.message = None;
+    }
+
+    fn open_measurements(&mut self) {
+        self.screen = Screen::Measurements;
+        self.measurements = MeasurementsState::default();
+        self.message = None;
+    }
+
+    fn open_stats(&mut self) {
+        self.screen = Screen::Stats;
+        self.message = None;
+    }
+
+    fn open_exercises(&mut self) {
+        self.screen = Screen::Exercises;
+        self.exercises_screen = ExercisesState::default();
+        self.message = None;
     }
 
+    fn open_data(&mut self) {
+        self.screen = Screen::Data;
+        self.data_screen = DataState::default();
+        self.message = None;
+    }
+
+    fn open_profiles(&mut self) -> Result<()> {
+        self.screen = Screen::Profiles;
+        let names = self.profiles.profiles.list()?;
+        self.profiles.selected = names.iter().position(|n| *n == self.profiles.active).unwrap_or(0);
+        Ok(())
+    }
+
+    fn open_settings(&mut self) {
+        self.screen = Screen::Settings;
+        self.settings_screen = SettingsState::default();
+    }
+
+    fn copy_today(&mut self) -> Result<()> {
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        let records = self.db.get_today_workouts()?;
+        self.copy_day_summary(&today, &records);
+        Ok(())
+    }
+
+    /// The main screen's keys are the ones bound in the palette's registry.
     fn handle_main_input(&mut self, key: KeyCode) -> Result<bool> {
+        self.message = None;
+        if self.handle_rest_timer_key(key) {
+            return Ok(false);
+        }
         match key {
-            KeyCode::Char('q') => return Ok(true),
-            KeyCode::Char('a') => {
-                self.screen = Screen::AddWorkout;
-                self.input_count.clear();
-                self.message = None;
-            }
-            KeyCode::Char('h') => {
-                self.screen = Screen::History;
-                self.history_selected = 0;
-                self.selected_date = None;
-                self.message = None;
+            KeyCode::Char(':') => self.open_palette(),
+            KeyCode::Char(c) => {
+                if let Some(action) = palette::bound_to(c) {
+                    return self.run_action(action);
+                }
             }
             _ => {}
         }
@@ -186,106 +703,534 @@ impl App {
     }
 
     fn handle_add_workout_input(&mut self, key: KeyCode) -> Result<bool> {
+        if self.plates.is_some() {
+            return self.handle_plates_input(key);
+        }
+        let typing_tags = self.add_field == AddField::Tags;
+        if !typing_tags && self.handle_rest_timer_key(key) {
+            return Ok(false);
+        }
         match key {
+            KeyCode::Char('p') if !typing_tags => self.open_plates(),
+            KeyCode::Char('a') if !typing_tags => self.open_amrap(),
+            KeyCode::Tab if typing_tags => self.complete_tag()?,
+            KeyCode::Char('g') if !typing_tags && self.guided.is_none() => self.start_guided(),
+            KeyCode::Esc if self.guided.is_some() => self.stop_guided(),
+            // The guided exercise stays until its sets are done or it's stopped
+            KeyCode::Tab if self.guided.is_some() => {}
+            KeyCode::Char('i') if !typing_tags => {
+                if let Some(exercise) = self.picker_exercises().get(self.selected_exercise).map(|e| e.name.clone()) {
+                    self.open_exercise_detail(&exercise, Screen::AddWorkout)?;
+                }
+            }
+            KeyCode::Char('*') if !typing_tags => {
+                if let Some(exercise) = self.picker_exercises().get(self.selected_exercise).map(|e| (*e).clone()) {
+                    self.toggle_favorite(&exercise)?;
+                }
+            }
             KeyCode::Esc => {
                 self.screen = Screen::Main;
                 self.input_count.clear();
             }
-            KeyCode::Tab => {
-                self.selected_exercise = match self.selected_exercise {
-                    ExerciseType::Squats => ExerciseType::PushUps,
-                    ExerciseType::PushUps => ExerciseType::Squats,
-                };
+            KeyCode::Tab if !self.picker_exercises().is_empty() => {
+                self.selected_exercise = (self.selected_exercise + 1) % self.picker_exercises().len();
+                self.prefill_count();
+                self.input_weight.clear();
             }
-            KeyCode::Char(c) if c.is_ascii_digit() => {
-                self.input_count.push(c);
+            KeyCode::Down => {
+                self.add_field = match self.add_field {
+                    AddField::Count => AddField::Weight,
+                    AddField::Weight => AddField::Rpe,
+                    AddField::Rpe => AddField::Tags,
+                    AddField::Tags => AddField::Count,
+                };
             }
-            KeyCode::Backspace => {
-                self.input_count.pop();
+            KeyCode::Up => {
+                self.add_field = match self.add_field {
+                    AddField::Count => AddField::Tags,
+                    AddField::Weight => AddField::Count,
+                    AddField::Rpe => AddField::Weight,
+                    AddField::Tags => AddField::Rpe,
+                };
             }
             KeyCode::Enter => {
-                if let Ok(count) = self.input_count.parse::<i32>() {
-                    if count > 0 {
-                        let exercise = match self.selected_exercise {
-                            ExerciseType::Squats => "squats",
-                            ExerciseType::PushUps => "push-ups",
-                        };
-                        self.db.add_workout(exercise, count)?;
-                        self.message = Some(format!("Added {} {}!", count, exercise));
-                        self.input_count.clear();
+                let rpe = match self.input_rpe.as_str() {
+                    "" => None,
+                    text => match text.parse::<i32>() {
+                        Ok(rpe) if (1..=10).contains(&rpe) => Some(rpe),
+                        _ => {
+                            self.message = Some(self.text.get("add.rpe_range").to_string());
+                            return Ok(false);
+                        }
+                    },
+                };
+                let weight = match self.input_weight.as_str() {
+                    "" => None,
+                    _ => match self.input_weight.positive_f64() {
+                        Some(weight) => Some(weight * self.config.units.weight.kg_per_unit()),
+                        None => {
+                            self.message = Some(self.text.get("add.weight_invalid").to_string());
+                            return Ok(false);
+                        }
+                    },
+                };
+                let Some(exercise) = self.picker_exercises().get(self.selected_exercise).map(|e| (*e).clone())
+                else {
+                    return Ok(false);
+                };
+                if let Some(count) =
+                    units::parse_amount(self.input_count.as_str(), &exercise.unit, self.config.units.distance)
+                {
+                    let best_before = match weight {
+                        Some(_) => self.db.best_one_rep_max(&exercise.name)?,
+                        None => None,
+                    };
+                    self.db.add_workout(&exercise.name, count, rpe, weight)?;
+                    let tags = tags::parse_tags(self.input_tags.as_str());
+                    if !tags.is_empty() {
+                        self.db.tag_workout(self.db.last_workout_id()?, &tags)?;
                     }
+                    self.notify_webhook(&exercise.name, count as i64)?;
+                    self.start_rest_timer(&exercise.name);
+                    let amount = self.format_count(&exercise.name, count as i64);
+                    self.message = Some(
+                        self.text.format("add.added", &[("amount", &amount), ("exercise", &exercise.name)]),
+                    );
+                    self.check_goal(&exercise.name, count as i64)?;
+                    if let Some(weight) = weight {
+                        self.check_one_rep_max(&exercise, count, weight, best_before);
+                    }
+                    self.check_day_plan()?;
+                    self.guided_set_logged(&exercise.name);
+                    self.prefill_count();
+                    self.input_rpe.clear();
+                    self.add_field = AddField::Count;
                 }
             }
-            _ => {}
+            _ => {
+                match self.add_field {
+                    AddField::Count => self.input_count.handle_key(key),
+                    AddField::Weight => self.input_weight.handle_key(key),
+                    AddField::Rpe => self.input_rpe.handle_key(key),
+                    AddField::Tags => self.input_tags.handle_key(key),
+                };
+            }
         }
         Ok(false)
     }
 
+    /// Drops the date list's per-day heat and set bars, so they're worked
+    /// out again when next drawn.
+    pub(crate) fn forget_history_days(&self) {
+        self.history_heat.replace(None);
+        self.history_sets.replace(None);
+    }
+
+    /// Drops selections and screen state that point into the database, for
+    /// when its contents were replaced wholesale.
+    pub(crate) fn reset_cached_state(&mut self) {
+        self.last_used = self.db.last_used_exercises().unwrap_or_default();
+        self.selected_exercise = 0;
+        self.select_default_exercise();
+        self.history_selected = 0;
+        self.history_filter = HistoryFilter::All;
+        self.selected_date = None;
+        self.history_entry = 0;
+        self.history_offset.set(0);
+        self.history_mark = None;
+        self.history_compare = None;
+        self.selection = Selection::default();
+        self.quick_stats = None;
+        self.forget_history_days();
+        self.exercises_screen = ExercisesState::default();
+        self.measurements = MeasurementsState::default();
+        self.goals_screen = GoalsState::default();
+        self.rest_timer = None;
+        self.reminder = None;
+        self.guided = None;
+        self.prefill_count();
+    }
+
+    /// Resets the count field to the planned amount while guiding, else to
+    /// the selected exercise's default, if any.
+    fn prefill_count(&mut self) {
+        let distance = self.config.units.distance;
+        let Some(exercise) = self.picker_exercises().get(self.selected_exercise).map(|e| (*e).clone()) else {
+            self.input_count.clear();
+            return;
+        };
+        self.input_count.set_decimal(units::is_decimal(&exercise.unit));
+        let guided = self.guided.as_ref().filter(|g| g.exercise == exercise.name).map(|g| g.template.count);
+        match guided.or(exercise.default_count) {
+            Some(count) => self.input_count.set(units::input_text(count as i64, &exercise.unit, distance)),
+            None => self.input_count.clear(),
+        }
+    }
+
+    /// Whether colors are off, via config or NO_COLOR.
+    pub(crate) fn monochrome(&self) -> bool {
+        self.config.ui.monochrome || std::env::var_os("NO_COLOR").is_some()
+    }
+
+    /// A date in the configured display style.
+    pub(crate) fn display_date(&self, date: NaiveDate) -> String {
+        dates::format_date(date, self.config.ui.date_format, Local::now().date_naive(), &self.text)
+    }
+
+    /// Same as `display_date` for an ISO date string as stored in the database.
+    pub(crate) fn display_date_str(&self, date: &str) -> String {
+        dates::parse_date(date)
+            .map(|d| self.display_date(d))
+            .unwrap_or_else(|| date.to_string())
+    }
+
+    /// Entries of the open history day that pass the current filter, in
+    /// the order the day view shows them.
+    fn history_entries(&self) -> Result<Vec<WorkoutRecord>> {
+        let Some(date) = &self.selected_date else {
+            return Ok(Vec::new());
+        };
+        let records: Vec<WorkoutRecord> = self
+            .db
+            .get_workouts_by_date(date)?
+            .into_iter()
+            .filter(|w| self.history_filter.matches(w, &self.exercises))
+            .collect();
+        Ok(if self.history_grouped { day_view::grouped(records) } else { records })
+    }
+
+    /// Opens the day with sets before (or after) the open one, keeping the
+    /// date list's selection on it so Esc lands there.
+    fn step_history_day(&mut self, earlier: bool) -> Result<()> {
+        let dates = self.db.get_unique_dates(&self.history_filter)?;
+        let Some(current) = self.selected_date.as_ref().and_then(|date| dates.iter().position(|d| d == date)) else {
+            return Ok(());
+        };
+        // Newest first
+        let next = if earlier { current.checked_add(1) } else { current.checked_sub(1) };
+        match next.and_then(|i| dates.get(i).map(|date| (i, date))) {
+            Some((i, date)) => {
+                self.selected_date = Some(date.clone());
+                self.history_selected = i;
+                self.history_entry = 0;
+                self.history_offset.set(0);
+                self.message = None;
+            }
+            None => self.show_toast(self.text.get(if earlier { "history.no_earlier" } else { "history.no_later" }).to_string()),
+        }
+        Ok(())
+    }
+
+    /// Shows `date` in the history's day view, unfiltered, with Esc going
+    /// back to `back`; only a toast when the day has no sets.
+    pub(crate) fn open_history_day(&mut self, date: NaiveDate, back: Screen) -> Result<()> {
+        let date = date.to_string();
+        let dates = self.db.get_unique_dates(&HistoryFilter::All)?;
+        let Some(index) = dates.iter().position(|d| *d == date) else {
+            self.show_toast(self.text.get("history.no_sets").to_string());
+            return Ok(());
+        };
+        self.history_filter = HistoryFilter::All;
+        self.history_selected = index;
+        self.selected_date = Some(date);
+        self.history_entry = 0;
+        self.history_offset.set(0);
+        self.history_back = Some(back);
+        self.screen = Screen::History;
+        Ok(())
+    }
+
     fn handle_history_input(&mut self, key: KeyCode) -> Result<bool> {
+        if self.history_log {
+            self.history_log = false;
+            return Ok(false);
+        }
+        if self.history_compare.is_some() {
+            return self.handle_day_compare_input(key);
+        }
+        if self.selection.reassign.is_some() {
+            return self.handle_reassign_input(key);
+        }
+        if let Some(input) = &mut self.history_edit {
+            match key {
+                KeyCode::Esc => self.history_edit = None,
+                KeyCode::Enter => {
+                    let text = input.as_str().to_string();
+                    self.history_edit = None;
+                    if let Some(entry) = self.history_entries()?.get(self.history_entry) {
+                        let unit = self.exercise_unit(&entry.exercise_type).to_string();
+                        match units::parse_amount(&text, &unit, self.config.units.distance) {
+                            Some(count) => {
+                                self.db.update_workout_count(entry.id, count)?;
+                                self.forget_history_days();
+                            }
+                            None => self.message = Some(self.text.get("history.invalid_amount").to_string()),
+                        }
+                    }
+                }
+                _ => {
+                    input.handle_key(key);
+                }
+            }
+            return Ok(false);
+        }
+        if self.history_note.is_some() {
+            return self.handle_history_note_input(key);
+        }
+        if self.selected_date.is_some() && self.handle_selection_key(key)? {
+            return Ok(false);
+        }
         match key {
             KeyCode::Esc => {
-                if self.selected_date.is_some() {
+                if self.selected_date.is_some()
+                    && let Some(screen) = self.history_back.take()
+                {
+                    // The selection stays while going back and forth with the search
+                    if !matches!(screen, Screen::Search) {
+                        self.selection = Selection::default();
+                    }
+                    self.selected_date = None;
+                    self.screen = screen;
+                } else if self.selected_date.is_some() {
                     self.selected_date = None;
+                    self.history_offset.set(0);
                 } else {
+                    self.selection = Selection::default();
                     self.screen = Screen::Main;
                 }
             }
-            KeyCode::Up => {
-                if self.selected_date.is_none() && self.history_selected > 0 {
-                    self.history_selected -= 1;
+            KeyCode::Up if self.selected_date.is_none() && self.history_selected > 0 => {
+                self.history_selected -= 1;
+            }
+            KeyCode::Down if self.selected_date.is_none() => {
+                let dates = self.db.get_unique_dates(&self.history_filter)?;
+                if self.history_selected < dates.len().saturating_sub(1) {
+                    self.history_selected += 1;
                 }
             }
+            KeyCode::Up if self.history_entry > 0 => {
+                self.history_entry -= 1;
+            }
             KeyCode::Down => {
-                if self.selected_date.is_none() {
-                    let dates = self.db.get_unique_dates()?;
-                    if self.history_selected < dates.len().saturating_sub(1) {
-                        self.history_selected += 1;
-                    }
+                let entries = self.history_entries()?.len();
+                self.history_entry = (self.history_entry + 1).min(entries.saturating_sub(1));
+            }
+            KeyCode::Enter if self.selected_date.is_none() => {
+                let dates = self.db.get_unique_dates(&self.history_filter)?;
+                if let Some(date) = dates.get(self.history_selected) {
+                    self.selected_date = Some(date.clone());
+                    self.message = None;
+                    self.history_entry = 0;
+                    self.history_offset.set(0);
                 }
             }
-            KeyCode::Enter => {
-                if self.selected_date.is_none() {
-                    let dates = self.db.get_unique_dates()?;
-                    if let Some(date) = dates.get(self.history_selected) {
-                        self.selected_date = Some(date.clone());
+            KeyCode::Char('e') if self.selected_date.is_some() => {
+                if let Some(entry) = self.history_entries()?.get(self.history_entry) {
+                    let unit = self.exercise_unit(&entry.exercise_type);
+                    let mut input = NumericInput::default();
+                    input.set_decimal(units::is_decimal(unit));
+                    input.set(units::input_text(entry.count as i64, unit, self.config.units.distance));
+                    self.history_edit = Some(input);
+                }
+            }
+            KeyCode::Char('N') if self.selected_date.is_some() => self.edit_history_note()?,
+            KeyCode::Left | KeyCode::Char('p') if self.selected_date.is_some() => self.step_history_day(true)?,
+            KeyCode::Right | KeyCode::Char('n') if self.selected_date.is_some() => self.step_history_day(false)?,
+            KeyCode::Char('g') if self.selected_date.is_some() => {
+                // Keep the same set highlighted in the new order
+                let id = self.history_entries()?.get(self.history_entry).map(|entry| entry.id);
+                self.history_grouped = !self.history_grouped;
+                self.history_entry = self.history_entries()?.iter().position(|entry| Some(entry.id) == id).unwrap_or(0);
+            }
+            KeyCode::Char('/') => self.open_search(),
+            KeyCode::Char('l') if self.selected_date.is_some() => {
+                self.history_log = !self.history_entries()?.is_empty();
+            }
+            KeyCode::Char('y') if let Some(date) = self.selected_date.clone() => self.plan_from_day(&date)?,
+            KeyCode::Char('c') if let Some(date) = self.selected_date.clone() => {
+                let records = self.history_entries()?;
+                self.copy_day_summary(&date, &records);
+            }
+            KeyCode::Char('m') if self.selected_date.is_none() => {
+                if let Some(date) = self.db.get_unique_dates(&self.history_filter)?.get(self.history_selected) {
+                    self.toggle_history_mark(date.clone());
+                }
+            }
+            KeyCode::Char('c') => {
+                if let Some(date) = self.db.get_unique_dates(&self.history_filter)?.get(self.history_selected) {
+                    self.open_day_compare(date.clone());
+                }
+            }
+            KeyCode::Delete if self.selected_date.is_some() => {
+                let entries = self.history_entries()?;
+                if let Some(entry) = entries.get(self.history_entry) {
+                    self.db.delete_workout(entry.id)?;
+                    self.forget_history_days();
+                    self.message = Some(self.text.get("history.deleted").to_string());
+                    self.history_entry = self.history_entry.min(entries.len().saturating_sub(2));
+                    if entries.len() == 1 {
+                        self.selected_date = None;
+                        self.history_selected = 0;
+                        self.history_offset.set(0);
                     }
                 }
             }
+            KeyCode::Char('f') => {
+                self.history_filter = self.history_filter.next(&self.exercises, &self.db.tag_names()?);
+                self.history_selected = 0;
+                self.history_entry = 0;
+                self.history_offset.set(0);
+            }
             _ => {}
         }
         Ok(false)
     }
 }
 
+/// Smallest terminal the real layout is drawn in.
+const MIN_WIDTH: u16 = 40;
+const MIN_HEIGHT: u16 = 12;
+
 fn ui(f: &mut Frame, app: &App) {
+    *app.hitboxes.borrow_mut() = Hitboxes::default();
+
+    let size = f.size();
+    if size.width < MIN_WIDTH || size.height < MIN_HEIGHT {
+        let text = app.text.format(
+            "ui.too_small",
+            &[("size", &format!("{}×{}", size.width, size.height)), ("min", &format!("{}×{}", MIN_WIDTH, MIN_HEIGHT))],
+        );
+        let area = centered_rect(size.width, 2.min(size.height), size);
+        f.render_widget(Paragraph::new(text).alignment(Alignment::Center).wrap(Wrap { trim: true }), area);
+        return;
+    }
+
+    let banner = reminders::banner_height(app);
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Min(0), Constraint::Length(3)])
+        .constraints([Constraint::Length(banner), Constraint::Min(0), Constraint::Length(3)])
         .split(f.size());
+    reminders::render_reminder_banner(f, chunks[0], app);
+    let chunks = &chunks[1..];
 
     match app.screen {
         Screen::Main => render_main_screen(f, chunks[0], app),
         Screen::AddWorkout => render_add_workout_screen(f, chunks[0], app),
         Screen::History => render_history_screen(f, chunks[0], app),
+        Screen::Measurements => measurements::render_measurements_screen(f, chunks[0], app),
+        Screen::Stats => stats::render_stats_screen(f, chunks[0], app),
+        Screen::Exercises => exercises::render_exercises_screen(f, chunks[0], app),
+        Screen::Goals => goals::render_goals_screen(f, chunks[0], app),
+        Screen::Data => data::render_data_screen(f, chunks[0], app),
+        Screen::Health => health::render_health_screen(f, chunks[0], app),
+        Screen::Trash => trash::render_trash_screen(f, chunks[0], app),
+        Screen::Report => report::render_report_screen(f, chunks[0], app),
+        Screen::Profiles => profiles::render_profiles_screen(f, chunks[0], app),
+        Screen::Compare => compare::render_compare_screen(f, chunks[0], app),
+        Screen::Stopwatch => stopwatch::render_stopwatch_screen(f, chunks[0], app),
+        Screen::Amrap => amrap::render_amrap_screen(f, chunks[0], app),
+        Screen::Export => export_dialog::render_export_screen(f, chunks[0], app),
+        Screen::Settings => settings::render_settings_screen(f, chunks[0], app),
+        Screen::About => about::render_about_screen(f, chunks[0], app),
+        Screen::Week => week_view::render_week_screen(f, chunks[0], app),
+        Screen::Month => month_view::render_month_screen(f, chunks[0], app),
+        Screen::Search => search::render_search_screen(f, chunks[0], app),
+        Screen::ExerciseDetail => exercise_detail::render_exercise_detail_screen(f, chunks[0], app),
+    }
+
+    render_help(f, chunks[1], app);
+
+    if app.onboarding.is_some() {
+        onboarding::render_onboarding(f, chunks[0], app);
+    }
+    if app.mood_prompt {
+        mood::render_mood_prompt(f, app);
+    }
+    quick_stats::render_quick_stats(f, app);
+    timer::render_rest_cancel(f, app);
+    palette::render_palette(f, app);
+    if !app.journal_replay.is_empty() {
+        exercises::render_dialog(
+            f,
+            app.text.get("journal.title"),
+            &app.text.format("journal.confirm", &[("count", &app.journal_replay.len())]),
+        );
+    }
+    reload::render_toast(f, app);
+
+    // Monochrome strips every color after the fact, so individual render
+    // functions never need to check for it.
+    if app.monochrome() {
+        let area = f.size();
+        let buffer = f.buffer_mut();
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                buffer.get_mut(x, y).set_fg(Color::Reset).set_bg(Color::Reset);
+            }
+        }
+    }
+
+    if app.flashing() {
+        notify::flash(f);
     }
+}
 
-    render_help(f, chunks[1], &app.screen);
+/// A `width` x `height` rectangle centered in `area`, clamped to fit.
+fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
 }
 
 fn render_main_screen(f: &mut Frame, area: Rect, app: &App) {
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Length(3), Constraint::Min(0)])
-        .split(area);
+    if app.starting {
+        startup::render_main_skeleton(f, area, app);
+        return;
+    }
 
-    // Title
-    let title = Paragraph::new("🏋️  Fitness Tracker")
-        .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
-        .block(Block::default().borders(Borders::ALL).title("Welcome"));
-    f.render_widget(title, chunks[0]);
+    // This week's prescription for every exercise with a plan
+    let plans: Vec<Line> = app
+        .exercises
+        .iter()
+        .filter(|e| !e.archived)
+        .filter_map(|e| {
+            let line = app.plan_line(e)?;
+            Some(Line::from(vec![
+                Span::styled(format!("{}: ", e.display_name()), Style::default().fg(app.exercise_color(&e.name))),
+                Span::raw(line),
+            ]))
+        })
+        .collect();
+    let plans_height = if plans.is_empty() { 0 } else { plans.len().min(3) as u16 + 2 };
 
     // Workout summary table
     let today_workouts = app.db.get_today_workouts().unwrap_or_default();
+
+    // Today's plan copied from an earlier day, if any
+    let day_plan = app.day_checklist(&today_workouts);
+    let checklist_height = day_plan.as_ref().map_or(0, |checklist| checklist::height(checklist, 4));
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(checklist_height),
+            Constraint::Length(plans_height),
+            Constraint::Min(0),
+        ])
+        .split(area);
+    if let Some(checklist) = &day_plan {
+        checklist::render_checklist(f, chunks[1], app, app.text.get("main.day_plan"), checklist);
+    }
+    if !plans.is_empty() {
+        let block = Block::default().borders(Borders::ALL).title(app.text.get("main.plan"));
+        f.render_widget(Paragraph::new(plans).block(block), chunks[2]);
+    }
     
     // Get last workout date and its workouts
     let last_date = app.db.get_last_workout_date().unwrap_or(None);
@@ -294,150 +1239,141 @@ fn render_main_screen(f: &mut Frame, area: Rect, app: &App) {
     } else {
         Vec::new()
     };
+
+    // Title, with when the most recent set was logged
+    let mut title_text = app.text.get("main.title").to_string();
+    if let Some(at) = today_workouts
+        .last()
+        .or(last_workouts.last())
+        .and_then(|w| dates::parse_timestamp(&w.timestamp))
+    {
+        let ui = &app.config.ui;
+        let when = dates::format_relative(at, Local::now().naive_local(), ui.date_format, ui.time_format, &app.text);
+        title_text.push_str(&format!("   ·   {}", app.text.format("main.last_set", &[("when", &when)])));
+    }
+    let title = Paragraph::new(title_text)
+        .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("main.welcome")));
+    f.render_widget(title, chunks[0]);
     
-    // Organize workouts by exercise type
-    let mut today_squats = Vec::new();
-    let mut today_pushups = Vec::new();
-    let mut last_squats = Vec::new();
-    let mut last_pushups = Vec::new();
-
-    for workout in &today_workouts {
-        match workout.exercise_type.as_str() {
-            "squats" => today_squats.push(workout.count),
-            "push-ups" => today_pushups.push(workout.count),
-            _ => {}
+    // Organize workouts by exercise type, in picker order, followed by any
+    // logged or planned names that have no exercise entry
+    let mut names: Vec<&str> = app.exercises.iter().map(|e| e.name.as_str()).collect();
+    let planned_names = day_plan.iter().flat_map(|checklist| &checklist.items).map(|item| item.exercise.as_str());
+    for name in today_workouts.iter().chain(&last_workouts).map(|w| w.exercise_type.as_str()).chain(planned_names) {
+        if !names.contains(&name) {
+            names.push(name);
         }
     }
 
-    for workout in &last_workouts {
-        match workout.exercise_type.as_str() {
-            "squats" => last_squats.push(workout.count),
-            "push-ups" => last_pushups.push(workout.count),
-            _ => {}
-        }
-    }
+    let counts_for = |workouts: &[WorkoutRecord], name: &str| -> Vec<i32> {
+        workouts
+            .iter()
+            .filter(|w| w.exercise_type == name)
+            .map(|w| w.count)
+            .collect()
+    };
+    let grouped: Vec<(&str, Vec<i32>, Vec<i32>)> = names
+        .iter()
+        .map(|name| (*name, counts_for(&today_workouts, name), counts_for(&last_workouts, name)))
+        .collect();
+    // Sets planned for an exercise and the amounts still open
+    let plan_for = |name: &str| -> Option<(usize, Vec<i32>)> {
+        let items: Vec<&ChecklistItem> = day_plan.iter().flat_map(|checklist| checklist.planned(name)).collect();
+        let open = items.iter().filter(|item| item.state() == ItemState::Pending).map(|item| item.planned).collect();
+        (!items.is_empty()).then_some((items.len(), open))
+    };
 
     // Build table rows
     let mut table_rows = Vec::new();
 
     // Calculate max number of columns needed first
-    let max_workouts = [
-        today_squats.len(),
-        today_pushups.len(),
-        last_squats.len(),
-        last_pushups.len(),
-    ].iter().max().copied().unwrap_or(0);
-
-    // Squats today
-    if !today_squats.is_empty() {
-        let sum: i32 = today_squats.iter().sum();
-        let mut cells = vec!["Squats Today".to_string()];
-        for count in &today_squats {
-            cells.push(count.to_string());
-        }
-        // Pad with empty cells if needed
-        for _ in today_squats.len()..max_workouts {
-            cells.push("".to_string());
-        }
-        cells.push(sum.to_string());
-        
-        let table_row = Row::new(cells)
-            .style(Style::default().fg(Color::Green))
-            .height(1);
-        table_rows.push(table_row);
-    }
+    let max_workouts = grouped
+        .iter()
+        .flat_map(|(name, today, last)| [today.len() + plan_for(name).map_or(0, |(_, open)| open.len()), last.len()])
+        .max()
+        .unwrap_or(0);
 
-    // Squats last workout
-    if !last_squats.is_empty() {
-        let sum: i32 = last_squats.iter().sum();
-        let label = if let Some(ref date) = last_date {
-            format!("Squats ({})", date)
-        } else {
-            "Squats Last".to_string()
-        };
-        let mut cells = vec![label];
-        for count in &last_squats {
-            cells.push(count.to_string());
+    // Planned sets still open follow the logged ones, grayed
+    let summary_row = |name: &str, label: String, counts: &[i32], open: &[i32], style: Style| {
+        let sum: i64 = counts.iter().map(|c| *c as i64).sum();
+        let mut cells = vec![TableCell::from(label)];
+        for count in counts {
+            cells.push(TableCell::from(app.format_count(name, *count as i64)));
+        }
+        for count in open {
+            cells.push(TableCell::from(app.format_count(name, *count as i64)).style(Style::default().fg(Color::DarkGray)));
         }
         // Pad with empty cells if needed
-        for _ in last_squats.len()..max_workouts {
-            cells.push("".to_string());
+        for _ in counts.len() + open.len()..max_workouts {
+            cells.push(TableCell::from(""));
         }
-        cells.push(sum.to_string());
-        
-        let table_row = Row::new(cells)
-            .style(Style::default().fg(Color::DarkGray))
-            .height(1);
-        table_rows.push(table_row);
-    }
+        cells.push(TableCell::from(app.format_count(name, sum)));
+        Row::new(cells).style(style).height(1)
+    };
 
-    // Push-ups today
-    if !today_pushups.is_empty() {
-        let sum: i32 = today_pushups.iter().sum();
-        let mut cells = vec!["Push-ups Today".to_string()];
-        for count in &today_pushups {
-            cells.push(count.to_string());
+    for (name, today, last) in &grouped {
+        let color = app.exercise_color(name);
+        let display = exercises::display_name(name);
+
+        // Today, with what's left of the plan
+        let planned = plan_for(name);
+        let open = planned.as_ref().map_or(&[][..], |(_, open)| open.as_slice());
+        if !today.is_empty() || !open.is_empty() {
+            let label = match &planned {
+                Some((sets, open)) => {
+                    let progress = format!("{}/{sets}", sets - open.len());
+                    app.text.format("main.today_planned_row", &[("exercise", &display), ("progress", &progress)])
+                }
+                None => app.text.format("main.today_row", &[("exercise", &display)]),
+            };
+            table_rows.push(summary_row(name, label, today, open, Style::default().fg(color)));
         }
-        // Pad with empty cells if needed
-        for _ in today_pushups.len()..max_workouts {
-            cells.push("".to_string());
+
+        // Last workout
+        if !last.is_empty() {
+            let label = if let Some(ref date) = last_date {
+                let date = app.display_date_str(date);
+                app.text.format("main.dated_row", &[("exercise", &display), ("date", &date)])
+            } else {
+                app.text.format("main.last_row", &[("exercise", &display)])
+            };
+            let style = Style::default().fg(color).add_modifier(Modifier::DIM);
+            table_rows.push(summary_row(name, label, last, &[], style));
         }
-        cells.push(sum.to_string());
-        
-        let table_row = Row::new(cells)
-            .style(Style::default().fg(Color::Green))
-            .height(1);
-        table_rows.push(table_row);
     }
 
-    // Push-ups last workout
-    if !last_pushups.is_empty() {
-        let sum: i32 = last_pushups.iter().sum();
-        let label = if let Some(ref date) = last_date {
-            format!("Push-ups ({})", date)
-        } else {
-            "Push-ups Last".to_string()
-        };
-        let mut cells = vec![label];
-        for count in &last_pushups {
-            cells.push(count.to_string());
-        }
-        // Pad with empty cells if needed
-        for _ in last_pushups.len()..max_workouts {
-            cells.push("".to_string());
-        }
-        cells.push(sum.to_string());
-        
-        let table_row = Row::new(cells)
-            .style(Style::default().fg(Color::DarkGray))
-            .height(1);
-        table_rows.push(table_row);
+    let mut block = Block::default().borders(Borders::ALL).title(app.text.get("main.summary"));
+    if let Some(message) = &app.message {
+        block = block.title(Title::from(message.as_str()).position(Position::Bottom));
     }
 
     // If no workouts, show a message
     if table_rows.is_empty() {
-        let empty_msg = Paragraph::new("No workouts yet! Press 'a' to add your first workout.")
+        let empty_msg = Paragraph::new(app.text.get("main.empty"))
             .style(Style::default().fg(Color::Yellow))
-            .block(Block::default().borders(Borders::ALL).title("Workout Summary"))
+            .block(block)
             .wrap(Wrap { trim: true });
-        f.render_widget(empty_msg, chunks[1]);
+        f.render_widget(empty_msg, chunks[3]);
     } else {
         // Create column constraints: Exercise name + workout counts + total
+        // Every column keeps at least 1% so nothing collapses to zero width
+        let share = (70 / (max_workouts + 1)).max(1) as u16;
         let mut constraints = vec![Constraint::Percentage(30)]; // Exercise name column
         for _ in 0..max_workouts {
-            constraints.push(Constraint::Percentage(70 / (max_workouts + 1) as u16));
+            constraints.push(Constraint::Percentage(share));
         }
-        constraints.push(Constraint::Percentage(70 / (max_workouts + 1) as u16)); // Total column
+        constraints.push(Constraint::Percentage(share)); // Total column
 
         // Build header dynamically
-        let mut header_cells = vec!["Exercise".to_string()];
+        let mut header_cells = vec![app.text.get("common.exercise").to_string()];
         for i in 1..=max_workouts {
             header_cells.push(format!("#{}", i));
         }
-        header_cells.push("Total".to_string());
+        header_cells.push(app.text.get("common.total").to_string());
 
         let workout_table = Table::new(table_rows, constraints)
-            .block(Block::default().borders(Borders::ALL).title("Workout Summary"))
+            .block(block)
             .header(
                 Row::new(header_cells)
                     .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
@@ -445,7 +1381,7 @@ fn render_main_screen(f: &mut Frame, area: Rect, app: &App) {
             )
             .column_spacing(1);
 
-        f.render_widget(workout_table, chunks[1]);
+        f.render_widget(workout_table, chunks[3]);
     }
 }
 
@@ -455,124 +1391,744 @@ fn render_add_workout_screen(f: &mut Frame, area: Rect, app: &App) {
         .constraints([
             Constraint::Length(5),
             Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(3),
             Constraint::Min(0),
         ])
         .split(area);
 
     // Exercise type selector
-    let exercise_text = match app.selected_exercise {
-        ExerciseType::Squats => "Squats (Tab to switch)",
-        ExerciseType::PushUps => "Push-ups (Tab to switch)",
+    let selected = app.picker_exercises().get(app.selected_exercise).copied();
+    let mut exercise_text = match selected {
+        Some(exercise) => app.text.format("add.switch", &[("exercise", &exercises::favorite_name(exercise))]),
+        None => app.text.get("add.no_exercises").to_string(),
+    };
+    if let Some(plan) = selected.and_then(|e| app.plan_line(e)) {
+        exercise_text.push('\n');
+        exercise_text.push_str(&app.text.format("add.plan", &[("plan", &plan)]));
+    }
+    if let Some(line) = app.guided_line() {
+        exercise_text.push('\n');
+        exercise_text.push_str(&line);
+    } else if let Some(exercise) = selected
+        && let Some(template) = exercise.template
+    {
+        exercise_text.push('\n');
+        let scheme = template.text(&exercise.unit, app.config.units.distance);
+        exercise_text.push_str(&app.text.format("add.template", &[("template", &scheme)]));
+    }
+    let count_title = match selected.map(|e| e.unit.as_str()) {
+        Some(units::DISTANCE) => app.text.format("add.distance", &[("unit", &app.config.units.distance.label())]),
+        Some(unit) => app.text.format("add.count_in", &[("unit", &unit)]),
+        None => app.text.get("add.count").to_string(),
     };
     
     let exercise = Paragraph::new(exercise_text)
         .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
-        .block(Block::default().borders(Borders::ALL).title("Exercise Type"));
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("add.exercise_type")));
     f.render_widget(exercise, chunks[0]);
 
+    let field_style = |field| {
+        if app.add_field == field {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        }
+    };
+
     // Count input
     let input = Paragraph::new(app.input_count.as_str())
-        .style(Style::default().fg(Color::Yellow))
-        .block(Block::default().borders(Borders::ALL).title("Count (Enter to save)"));
+        .style(field_style(AddField::Count))
+        .block(Block::default().borders(Borders::ALL).title(count_title));
     f.render_widget(input, chunks[1]);
 
+    // Weight input
+    let weight = Paragraph::new(app.input_weight.as_str())
+        .style(field_style(AddField::Weight))
+        .block(Block::default().borders(Borders::ALL).title(app.text.format("add.weight", &[("unit", &app.config.units.weight.label())])));
+    f.render_widget(weight, chunks[2]);
+
+    // RPE input
+    let rpe = Paragraph::new(app.input_rpe.as_str())
+        .style(field_style(AddField::Rpe))
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("add.rpe")));
+    f.render_widget(rpe, chunks[3]);
+
+    // Tags input, offering the completion Tab would take
+    let known = app.db.tag_names().unwrap_or_default();
+    let tags_title = match tags::completion(app.input_tags.as_str(), &known) {
+        Some(tag) if app.add_field == AddField::Tags => app.text.format("add.tags_complete", &[("tag", &tag)]),
+        _ => app.text.get("add.tags").to_string(),
+    };
+    let tags = Paragraph::new(app.input_tags.as_str())
+        .style(field_style(AddField::Tags))
+        .block(Block::default().borders(Borders::ALL).title(tags_title));
+    f.render_widget(tags, chunks[4]);
+
     // Message
     if let Some(msg) = &app.message {
         let message = Paragraph::new(msg.as_str())
             .style(Style::default().fg(Color::Green))
-            .block(Block::default().borders(Borders::ALL).title("Status"));
-        f.render_widget(message, chunks[2]);
+            .block(Block::default().borders(Borders::ALL).title(app.text.get("common.status")));
+        f.render_widget(message, chunks[5]);
     }
+    plates::render_plates(f, app);
 }
 
 fn render_history_screen(f: &mut Frame, area: Rect, app: &App) {
-    if let Some(date) = &app.selected_date {
+    if let Some((first, second)) = &app.history_compare {
+        day_compare::render_day_compare(f, area, app, first, second);
+    } else if let Some(date) = &app.selected_date {
         // Show workouts for selected date
-        if let Ok(workouts) = app.db.get_workouts_by_date(date) {
-            let items: Vec<ListItem> = workouts
+        if let Ok(workouts) = app.history_entries() {
+            let rests = app.day_rests(date).unwrap_or_default();
+            // Sets sit under their exercise's header when grouped
+            let indent = if app.history_grouped { "  " } else { "" };
+            let set_item = |i: usize, w: &WorkoutRecord| {
+                let time = dates::parse_timestamp(&w.timestamp)
+                    .map(|at| dates::format_time(at.time(), app.config.ui.time_format, true))
+                    .unwrap_or_default();
+                let amount = app.format_count(&w.exercise_type, w.count as i64);
+                let mark = app.selection_mark(w.id);
+                let mut content = if app.history_grouped {
+                    format!("{indent}{mark}{} - {}", time, amount)
+                } else {
+                    format!("{mark}{} - {} {}", time, amount, w.exercise_type)
+                };
+                if let Some(weight) = w.weight {
+                    content.push_str(&format!(" ({})", units::format_weight(weight, app.config.units.weight)));
+                }
+                if let Some(rpe) = w.rpe {
+                    content.push_str(&format!(" @{}", rpe));
+                }
+                for tag in &w.tags {
+                    content.push_str(&format!(" #{tag}"));
+                }
+                let mut style = Style::default().fg(app.exercise_color(&w.exercise_type));
+                if i == app.history_entry {
+                    style = style.add_modifier(Modifier::BOLD | Modifier::REVERSED);
+                }
+                let mut spans = vec![Span::raw(content)];
+                if let Some(note) = &w.note {
+                    spans.push(Span::styled(format!("  {note}"), Style::default().fg(Color::Gray)));
+                }
+                if let Some(rest) = rests.get(&w.id) {
+                    let rest_style = if app.rest_too_long(*rest) { Style::default().fg(Color::Red) } else { Style::default().fg(Color::DarkGray) };
+                    let rest = app.text.format("rest.before", &[("rest", &stats::format_duration(*rest))]);
+                    spans.push(Span::styled(format!("  {rest}"), rest_style));
+                }
+                ListItem::new(Line::from(spans)).style(style)
+            };
+            let rows = day_view::rows(&workouts, app.history_grouped);
+            let items: Vec<ListItem> = rows
                 .iter()
-                .map(|w| {
-                    let time = w.timestamp.split(' ').nth(1).unwrap_or("");
-                    let content = format!("{} - {} {}", time, w.count, w.exercise_type);
-                    ListItem::new(content)
+                .map(|row| match row {
+                    DayRow::Header(exercise) => {
+                        ListItem::new(exercise.as_str()).style(Style::default().fg(app.exercise_color(exercise)).add_modifier(Modifier::BOLD))
+                    }
+                    DayRow::Set(i) => set_item(*i, &workouts[*i]),
+                    DayRow::Subtotal(sets) => {
+                        ListItem::new(format!("{indent}{}", app.day_total("history.subtotal", &workouts[sets.clone()])))
+                            .style(Style::default().fg(Color::DarkGray))
+                    }
+                    DayRow::Total => ListItem::new(app.day_total("history.total", &workouts)).style(Style::default().fg(Color::Yellow)),
                 })
                 .collect();
 
-            let list = List::new(items)
-                .block(Block::default().borders(Borders::ALL).title(format!("Workouts on {}", date)))
-                .style(Style::default().fg(Color::White));
-            f.render_widget(list, area);
+            let mut title = app.text.format("history.day", &[("date", &app.display_date_str(date))]);
+            if !app.selection.ids.is_empty() {
+                title.push_str(&app.text.format("selection.count", &[("count", &app.selection.ids.len())]));
+            }
+            let mut block = Block::default().borders(Borders::ALL).title(title);
+            if let Some(message) = &app.message {
+                block = block.title(Title::from(message.as_str()).position(Position::Bottom));
+            }
+            let selected = day_view::row_of(&rows, app.history_entry).unwrap_or(0);
+            render_history_list(f, area, app, List::new(items).block(block), selected);
+
+            if let Some(entry) = workouts.get(app.history_entry) {
+                if app.history_log {
+                    audit::render_audit_popup(f, app, entry.id);
+                }
+                if let Some(input) = &app.history_edit {
+                    exercises::render_dialog(
+                        f,
+                        &app.text.format("history.edit", &[("exercise", &entry.exercise_type)]),
+                        &app.text.format("history.edit_field", &[("input", &input.as_str())]),
+                    );
+                }
+                if let Some(input) = &app.history_note {
+                    exercises::render_dialog(
+                        f,
+                        &app.text.format("history.note", &[("exercise", &entry.exercise_type)]),
+                        &app.text.format("history.note_field", &[("input", &input.as_str())]),
+                    );
+                }
+            }
+            selection::render_reassign_dialog(f, app);
         }
     } else {
         // Show date list
-        if let Ok(dates) = app.db.get_unique_dates() {
+        if let Ok(dates) = app.db.get_unique_dates(&app.history_filter) {
+            let moods = app.db.get_moods().unwrap_or_default();
             let items: Vec<ListItem> = dates
                 .iter()
                 .enumerate()
                 .map(|(i, date)| {
+                    let bucket = app.heat_bucket(date);
                     let style = if i == app.history_selected {
                         Style::default()
                             .fg(Color::Yellow)
                             .add_modifier(Modifier::BOLD)
                     } else {
-                        Style::default()
+                        bucket.map_or(Style::default(), heat::style)
+                    };
+                    let mut label = match moods.get(date) {
+                        Some(mood) => format!("{} {}", app.display_date_str(date), mood::mood_icon(*mood)),
+                        None => app.display_date_str(date),
                     };
-                    ListItem::new(date.as_str()).style(style)
+                    if app.monochrome()
+                        && let Some(bucket) = bucket
+                    {
+                        label = format!("{} {label}", heat::glyph(bucket));
+                    }
+                    if app.history_mark.as_ref() == Some(date) {
+                        label.push_str(&format!("  {}", app.text.get("day_compare.mark")));
+                    }
+                    // Borders, the scrollbar and the gap before the bars
+                    let room = (area.width as usize).saturating_sub(label.chars().count() + 5);
+                    let bars = chart::set_bars(&app.day_sets(date), room.min(day_view::MAX_SET_BARS));
+                    ListItem::new(format!("{label}  {bars}")).style(style)
                 })
                 .collect();
 
-            let list = List::new(items)
-                .block(Block::default().borders(Borders::ALL).title("Workout History (Enter to view)"))
-                .style(Style::default().fg(Color::White));
-            f.render_widget(list, area);
+            let mut title = app.text.format("history.title", &[("filter", &app.history_filter.label(&app.text))]);
+            if !app.selection.ids.is_empty() {
+                title.push_str(&app.text.format("selection.count", &[("count", &app.selection.ids.len())]));
+            }
+            let mut block = Block::default().borders(Borders::ALL).title(title);
+            if let Some(message) = &app.message {
+                block = block.title(Title::from(message.as_str()).position(Position::Bottom));
+            }
+            render_history_list(f, area, app, List::new(items).block(block), app.history_selected);
         }
     }
 }
 
-fn render_help(f: &mut Frame, area: Rect, screen: &Screen) {
-    let help_text = match screen {
-        Screen::Main => "[a] Add Workout  [h] History  [q] Quit",
-        Screen::AddWorkout => "[Tab] Switch Exercise  [Enter] Save  [Esc] Back",
-        Screen::History => "[↑/↓] Navigate  [Enter] Select  [Esc] Back",
-    };
+/// Draws a history list scrolled to keep `selected` visible, with a
+/// scrollbar, and records where its rows landed for the mouse.
+fn render_history_list(f: &mut Frame, area: Rect, app: &App, list: List, selected: usize) {
+    let len = list.len();
+    let mut state = ListState::default()
+        .with_offset(app.history_offset.get())
+        .with_selected(Some(selected));
+    f.render_stateful_widget(list.style(Style::default().fg(Color::White)), area, &mut state);
+    app.history_offset.set(state.offset());
 
-    let help = Paragraph::new(help_text)
-        .style(Style::default().fg(Color::DarkGray))
-        .block(Block::default().borders(Borders::ALL));
-    f.render_widget(help, area);
+    let rows = Block::default().borders(Borders::ALL).inner(area);
+    if len > rows.height as usize {
+        let mut scrollbar = ScrollbarState::new(len.saturating_sub(rows.height as usize)).position(state.offset());
+        let track = Rect { y: rows.y, height: rows.height, ..area };
+        f.render_stateful_widget(Scrollbar::new(ScrollbarOrientation::VerticalRight), track, &mut scrollbar);
+    }
+    app.hitboxes.borrow_mut().history_list = Some((rows, state.offset()));
+}
+
+fn render_help(f: &mut Frame, area: Rect, app: &App) {
+    let help_text = app.text.get(match app.screen {
+        Screen::Main if app.rest_timer.is_some() => "help.main_rest",
+        Screen::Main => "help.main",
+        Screen::AddWorkout if app.rest_timer.is_some() => "help.add_rest",
+        Screen::AddWorkout => "help.add",
+        Screen::History if app.history_compare.is_some() => "help.history_compare",
+        Screen::History if app.selected_date.is_some() => "help.history_day",
+        Screen::History => "help.history",
+        Screen::Measurements => "help.measurements",
+        Screen::Stats => "help.stats",
+        Screen::Exercises => "help.exercises",
+        Screen::Goals => "help.goals",
+        Screen::Data => "help.data",
+        Screen::Health => "help.health",
+        Screen::Trash => "help.trash",
+        Screen::Report => "help.report",
+        Screen::Profiles => "help.profiles",
+        Screen::Compare => "help.compare",
+        Screen::Stopwatch => "help.stopwatch",
+        Screen::Amrap if app.amrap.as_ref().is_some_and(|a| matches!(a.mode, amrap::Amrap::Setup { .. })) => "help.amrap_setup",
+        Screen::Amrap => "help.amrap",
+        Screen::Export => "help.export",
+        Screen::Settings if app.settings_screen.tags.is_some() => "help.tags",
+        Screen::Settings => "help.settings",
+        Screen::About => "help.about",
+        Screen::Week => "help.week",
+        Screen::Month => "help.month",
+        Screen::Search if app.search.in_results => "help.search_results",
+        Screen::Search => "help.search",
+        Screen::ExerciseDetail => "help.exercise_detail",
+    });
+
+    let block = Block::default().borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let mouse = app.text.get(if app.mouse_capture { "status.mouse_on" } else { "status.mouse_off" });
+    let clock = format!(
+        "{}  {}  {}",
+        app.text.format("status.profile", &[("name", &app.profiles.active)]),
+        mouse,
+        dates::format_time(Local::now().time(), app.config.ui.time_format, false)
+    );
+    // The rest countdown goes in a span of its own, to stand out while paused
+    let mut rest = None;
+    if let Some(timer) = &app.rest_timer {
+        let remaining = timer::format_remaining(timer.remaining());
+        let mut text = app.text.format("status.rest", &[("time", &remaining)]);
+        let mut style = Style::default();
+        if timer.clock.is_paused() {
+            text = format!("{text} {}", app.text.get("timer.paused"));
+            style = Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD);
+        }
+        rest = Some(Span::styled(text, style));
+    }
+    let mut notices = String::new();
+    if let Some(deadline) = app.deadline_status() {
+        notices = format!("{deadline}  ");
+    }
+    if let Some(version) = &app.update_notice {
+        notices = format!("{notices}{}  ", app.text.format("status.update", &[("version", version)]));
+    }
+    let mut spans = vec![Span::raw(notices)];
+    if let Some(rest) = rest {
+        spans.extend([rest, Span::raw("  ")]);
+    }
+    spans.push(Span::raw(clock));
+    let clock = Line::from(spans);
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(clock.width() as u16 + 1)])
+        .split(inner);
+
+    let help = Paragraph::new(help_text).style(Style::default().fg(Color::DarkGray));
+    f.render_widget(help, chunks[0]);
+    app.hitboxes.borrow_mut().add_hints(help_text, chunks[0]);
+    let clock = Paragraph::new(clock)
+        .alignment(Alignment::Right)
+        .style(Style::default().fg(Color::Cyan));
+    f.render_widget(clock, chunks[1]);
+}
+
+/// Runs a screen of its own, like the passphrase prompt, in the terminal
+/// before the main UI takes over, restoring the terminal whatever happens.
+fn before_ui<T>(run: impl FnOnce(&mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<T>) -> Result<T> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+    let result = run(&mut terminal);
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    result
 }
 
 fn main() -> Result<()> {
+    let cli = Cli::parse();
+
     // Setup database
-    let db = Database::new("fitness_tracker.db")?;
-    let mut app = App::new(db);
+    let config = Config::load(config::CONFIG_PATH)?;
+    let mouse = config.ui.mouse && !cli.no_mouse;
+    let mut storage = cli.backend.unwrap_or(config.storage.backend);
+    let mut path = match (&cli.db, cli.backend) {
+        (Some(path), _) => path.clone(),
+        // A backend picked on the command line gets its own default file
+        (None, Some(backend)) => PathBuf::from(backend.default_path()),
+        (None, None) => config.storage.path.clone().unwrap_or_else(|| storage.default_path().into()),
+    };
+    let default_missing = cli.db.is_none() && !path.exists() && !db_picker::recent().is_empty();
+    if cli.command.is_none() && !cli.today && (cli.choose_db || default_missing) {
+        (path, storage) = db_picker::prompt_database(&path, storage, &Messages::for_language(&config.language))?;
+    }
+    let profiles = Profiles {
+        base: path,
+        backend: storage,
+    };
+    let profile = match cli.profile {
+        Some(name) => {
+            anyhow::ensure!(profiles.list()?.contains(&name), "no profile named {name}; create it on the profiles screen");
+            name
+        }
+        // Commands run on the default profile unless told otherwise
+        None if cli.command.is_some() || cli.today => DEFAULT_PROFILE.to_string(),
+        None => match profiles.list()? {
+            names if names.len() > 1 => profiles::prompt_profile(&names, &Messages::for_language(&config.language))?,
+            _ => DEFAULT_PROFILE.to_string(),
+        },
+    };
+    let path = profiles.path(&profile);
+    if cli.today {
+        return today::print(storage, &path, cli.format, config.units.distance);
+    }
+    // Listing reads a JSON-lines file as it is, without importing it first
+    if storage == Backend::Jsonl
+        && let Some(Command::List { from, to, exercises, format }) = &cli.command
+    {
+        let store = JsonlStore::open(&path)?;
+        let exercises = exercises
+            .iter()
+            .map(|name| store.resolve_exercise(name).with_context(|| format!("no exercise named {name:?}")))
+            .collect::<Result<Vec<_>>>()?;
+        let filter = ListFilter { from: *from, to: *to, exercises };
+        return store.write_set_list(&mut io::stdout().lock(), &filter, *format);
+    }
+    let text = Messages::for_language(&config.language);
+    let mut read_only = false;
+    let lock = match &cli.command {
+        Some(command) if command.writes() => Some(instance_lock::acquire_for_command(&path)?),
+        Some(_) => None,
+        None => match instance_lock::acquire(&path)? {
+            Acquired::Locked(lock) => Some(lock),
+            Acquired::Held(holder) => match instance_lock::prompt_locked(&path, &holder, &text)? {
+                Choice::TakeOver => Some(instance_lock::take_over(&path)?),
+                Choice::ReadOnly => {
+                    read_only = true;
+                    None
+                }
+            },
+        },
+    };
+    let mut db = if storage == Backend::Sqlite && encryption::is_encrypted(&path) {
+        encryption::prompt_unlock(&path, read_only, &Messages::for_language(&config.language))?
+    } else if read_only {
+        Database::open_read_only(storage, &path)?
+    } else {
+        Database::open(storage, &path)?
+    };
+    // The instance with the file open keeps the journal
+    let journal = (config.journal.enabled && !read_only).then(|| profiles.journal_path(&profile, &db, &config.journal));
+    // Read before anything new is appended this run
+    let journal_missing = match &journal {
+        Some(path) => db.journal_missing(path)?,
+        None => Vec::new(),
+    };
+    if let Some(path) = journal {
+        db.start_journal(path);
+    }
+    if config.trash.purge_after_days > 0 && !read_only {
+        db.purge_trash_older_than(config.trash.purge_after_days)?;
+    }
+    if let Some(command) = cli.command {
+        match command {
+            Command::Export { format, output, include_deleted, from, to, exercises, anonymize, rename_exercises, csv } => {
+                if !format.filters_exercises() {
+                    if !exercises.is_empty() {
+                        bail!("--exercise only applies to the csv, json and markdown formats");
+                    }
+                    if anonymize.is_some() {
+                        bail!("--anonymize only applies to the csv, json and markdown formats");
+                    }
+                }
+                let exercises = exercises
+                    .iter()
+                    .map(|name| db.resolve_exercise(name)?.with_context(|| format!("no exercise named {name:?}")))
+                    .collect::<Result<Vec<_>>>()?;
+                let named = if rename_exercises { &[] } else { exercises.as_slice() };
+                let path = output.unwrap_or_else(|| format.default_path(named));
+                let options = ExportOptions {
+                    include_deleted,
+                    from,
+                    to,
+                    csv: csv.apply(&config.csv),
+                    exercises,
+                    anonymize: anonymize.map(|dates| Anonymize::new(dates, rename_exercises)),
+                    ..ExportOptions::from_config(&config)
+                };
+                let summary = db.export(format, &path, &options)?;
+                println!("Exported to {}", path.display());
+                if let Some(measurements) = &summary.measurements {
+                    println!("Measurements exported to {}", measurements.display());
+                }
+                if summary.skipped > 0 {
+                    eprintln!(
+                        "Warning: skipped {} sets whose exercise has no activity type; map them under [apple_health.activities] in {}",
+                        summary.skipped,
+                        config::CONFIG_PATH
+                    );
+                }
+            }
+            Command::List { from, to, exercises, format } => {
+                let exercises = exercises
+                    .iter()
+                    .map(|name| db.resolve_exercise(name)?.with_context(|| format!("no exercise named {name:?}")))
+                    .collect::<Result<Vec<_>>>()?;
+                db.write_set_list(&mut io::stdout().lock(), &ListFilter { from, to, exercises }, format)?;
+            }
+            Command::Report { week, out } => {
+                let start = match week {
+                    Some(week) => report::parse_iso_week(&week, config.week_starts_on)
+                        .with_context(|| format!("not an ISO week like 2024-W18: {week}"))?,
+                    None => dates::week_start(Local::now().date_naive(), config.week_starts_on),
+                };
+                let path = out.unwrap_or_else(|| report::default_path(start));
+                let text = Messages::for_language(&config.language);
+                db.write_week_report(start, &path, &text, config.units.distance)?;
+                println!("Report written to {}", path.display());
+            }
+            Command::ImportStrong { path, rejects, yes } => {
+                let file = strong::read_strong_csv(&path, config.strong_import.weight_unit)?;
+                csv_import::run(&db, &file, &config.strong_import, config.units.distance, "Strong", &path, rejects, yes)?;
+            }
+            Command::ImportFitnotes { path, rejects, yes } => {
+                let file = fitnotes::read_fitnotes_csv(&path, config.fitnotes_import.weight_unit, config.units.distance)?;
+                csv_import::run(&db, &file, &config.fitnotes_import, config.units.distance, "FitNotes", &path, rejects, yes)?;
+            }
+            Command::ImportJson { path, rejects, yes } => {
+                let file = sets_export::read_sets_json(&path, config.units.distance)?;
+                csv_import::run(&db, &file, &config.csv_import, config.units.distance, "JSON", &path, rejects, yes)?;
+            }
+            Command::ImportCsv { path, mapping, csv, rejects, yes } => {
+                let dialect = csv.apply(&config.csv);
+                let file = generic_csv::read_mapped_csv(
+                    &db,
+                    &path,
+                    mapping.as_deref(),
+                    &dialect,
+                    csv.timestamp_format.as_deref(),
+                    config.csv_import.weight_unit,
+                    config.units.distance,
+                )?;
+                csv_import::run(&db, &file, &config.csv_import, config.units.distance, "CSV", &path, rejects, yes)?;
+            }
+            Command::Merge { path } => {
+                let report = db.merge_from(&path)?;
+                println!("Merged {}: {} sets added, {} already present", path.display(), report.added, report.skipped);
+            }
+            Command::ExportConfig { output } => {
+                let path = output.unwrap_or_else(setup_file::default_path);
+                db.write_setup_file(&path)?;
+                println!("Setup written to {}", path.display());
+            }
+            Command::ImportConfig { path } => {
+                let report = db.import_setup_file(&path)?;
+                let text = Messages::for_language(&config.language);
+                println!("{}", report.summary(&text));
+                for conflict in &report.conflicts {
+                    println!("  {}", conflict.describe(&text));
+                }
+            }
+            Command::Archive { before } => {
+                let report = db.archive_before(before, &config.backup)?;
+                println!("Archived {} sets to {}; backup from before: {}", report.moved, report.path.display(), report.backup.display());
+            }
+        }
+        db.save()?;
+        return Ok(());
+    }
+    // Only a convenience for the picker, so a folder that can't be written
+    // to doesn't stop the app
+    let _ = db_picker::remember(&db.file_path(), storage);
+    let mut app = App::new(db, config)?;
+    app.profiles = ProfilesState::new(profiles, &profile);
+    app.instance_lock = lock;
+    app.read_only = read_only;
+    app.mood_prompt = !read_only && app.should_prompt_mood()?;
+    app.open_startup_screen(cli.screen.as_deref())?;
+    if !read_only {
+        app.start_onboarding(cli.onboarding)?;
+    }
+    if app.config.updates.check && !cli.no_update_check {
+        app.start_update_check()?;
+    }
+    app.journal_replay = journal_missing;
+    app.starting = true;
+    let session = SessionStart::new(&app.db)?;
 
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(stdout, EnterAlternateScreen)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
+    app.set_mouse_capture(mouse)?;
+    // Up before anything slow runs, so a slow machine shows the app at once
+    terminal.draw(|f| ui(f, &app))?;
 
-    // Main loop
+    // Main loop; redraw a few times a second so the clock, timers and
+    // flashes keep moving without input
     loop {
+        let ticked = app.tick();
+        app.allow_read_only(ticked)?;
         terminal.draw(|f| ui(f, &app))?;
 
-        if let Event::Key(key) = event::read()? {
-            if app.handle_input(key.code)? {
-                break;
+        if !event::poll(Duration::from_millis(250))? {
+            continue;
+        }
+        let quit = match event::read()? {
+            Event::Key(key) if key.code == KeyCode::Char('p') && key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.open_palette();
+                false
+            }
+            Event::Key(key) => {
+                let quit = app.handle_input(key.code);
+                app.allow_read_only(quit)?
+            }
+            Event::Mouse(mouse) => {
+                let quit = app.handle_mouse(mouse);
+                app.allow_read_only(quit)?
             }
+            Event::Resize(_, _) => {
+                terminal.autoresize()?;
+                false
+            }
+            _ => false,
+        };
+        app.db.save()?;
+        if quit {
+            break;
         }
     }
 
     // Restore terminal
+    app.set_mouse_capture(false)?;
     disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
     terminal.show_cursor()?;
 
+    // Printed now the alternate screen is gone, so it stays in the scrollback
+    for line in app.session_recap(&session)? {
+        println!("{line}");
+    }
+    if app.config.backup.on_exit && app.db.total_changes()? != session.changes {
+        app.db.backup(&app.config.backup)?;
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+
+    const SIZES: [(u16, u16); 6] = [
+        (1, 1),
+        (200, 3),
+        (10, 50),
+        (MIN_WIDTH, MIN_HEIGHT),
+        (MIN_WIDTH, 200),
+        (200, MIN_HEIGHT),
+    ];
+
+    fn test_app() -> App {
+        let db = Database::new(":memory:").unwrap();
+        for count in [10, 12, 15] {
+            db.add_workout("push-ups", count, Some(8), None).unwrap();
+        }
+        db.add_workout("squats", 20, None, None).unwrap();
+        App::new(db, Config::default()).unwrap()
+    }
+
+    fn draw(app: &App, width: u16, height: u16) -> Terminal<TestBackend> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|f| ui(f, app)).unwrap();
+        terminal
+    }
+
+    #[test]
+    fn every_screen_survives_pathological_sizes() {
+        let mut app = test_app();
+        app.compare = Some(CompareState {
+            other: "other".to_string(),
+            other_db: Database::new(":memory:").unwrap(),
+            range: 0,
+        });
+        app.open_quick_stats().unwrap();
+        app.open_palette();
+        app.start_rest_timer("squats");
+        if let Some(rest) = &mut app.rest_timer {
+            rest.clock.toggle();
+            rest.confirm_cancel = true;
+        }
+        for (width, height) in SIZES {
+            for screen in [
+                Screen::Main,
+                Screen::AddWorkout,
+                Screen::History,
+                Screen::Measurements,
+                Screen::Stats,
+                Screen::Exercises,
+                Screen::Goals,
+                Screen::Data,
+                Screen::Health,
+                Screen::Trash,
+                Screen::Report,
+                Screen::Profiles,
+                Screen::Compare,
+                Screen::Settings,
+                Screen::About,
+                Screen::Stopwatch,
+                Screen::Amrap,
+                Screen::Export,
+                Screen::Week,
+                Screen::Month,
+                Screen::Search,
+                Screen::ExerciseDetail,
+            ] {
+                app.screen = screen;
+                draw(&app, width, height);
+            }
+            app.screen = Screen::Main;
+            app.starting = true;
+            draw(&app, width, height);
+            app.starting = false;
+        }
+    }
+
+    #[test]
+    fn history_detail_and_mood_prompt_survive_pathological_sizes() {
+        let mut app = test_app();
+        app.screen = Screen::History;
+        app.handle_input(KeyCode::Enter).unwrap();
+        assert!(app.selected_date.is_some());
+        app.mood_prompt = true;
+        for (width, height) in SIZES {
+            draw(&app, width, height);
+        }
+    }
+
+    #[test]
+    fn tiny_window_shows_only_the_size_warning() {
+        let app = test_app();
+        let terminal = draw(&app, 30, 8);
+        let text: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(text.contains("Window too small"));
+        assert!(!text.contains("Fitness Tracker"));
+    }
+
+    #[test]
+    fn picker_lists_favorites_then_most_recent() {
+        let db = Database::new(":memory:").unwrap();
+        for name in ["plank", "lunges", "dips"] {
+            db.add_exercise(name).unwrap();
+        }
+        for (exercise, timestamp) in [("squats", "2024-05-01 08:00:00"), ("dips", "2024-05-03 08:00:00"), ("push-ups", "2024-05-02 08:00:00")] {
+            db.conn
+                .execute("INSERT INTO workouts (exercise_type, count, timestamp) VALUES (?1, 10, ?2)", params![exercise, timestamp])
+                .unwrap();
+        }
+        let mut app = App::new(db, Config::default()).unwrap();
+        let order = |app: &App| app.picker_exercises().iter().map(|e| e.name.clone()).collect::<Vec<_>>();
+        assert_eq!(order(&app), ["dips", "push-ups", "squats", "plank", "lunges"]);
+
+        let lunges = app.exercises.iter().find(|e| e.name == "lunges").unwrap().clone();
+        app.selected_exercise = 1;
+        app.toggle_favorite(&lunges).unwrap();
+        assert_eq!(order(&app), ["lunges", "dips", "push-ups", "squats", "plank"]);
+        // Still on push-ups, which moved down a row
+        assert_eq!(app.selected_exercise, 2);
+
+        // A new set doesn't reorder the picker until the next session
+        app.db.add_workout("plank", 30, None, None).unwrap();
+        assert_eq!(order(&app), ["lunges", "dips", "push-ups", "squats", "plank"]);
+    }
+}
@@ -0,0 +1,113 @@
+use anyhow::{Context, Result};
+use rusqlite::OpenFlags;
+use std::{
+    fs,
+    path::PathBuf,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+
+use crate::data::format_size;
+use crate::encryption;
+use crate::{migrations, App, Database};
+
+/// VACUUM running on a worker thread with its own connection, so a large
+/// file doesn't freeze the UI.
+#[derive(Debug)]
+pub struct VacuumJob {
+    size_before: u64,
+    done: Receiver<Result<()>>,
+}
+
+impl Database {
+    /// Size of the database file on disk; zero for an in-memory database.
+    pub fn file_size(&self) -> u64 {
+        self.conn
+            .path()
+            .and_then(|path| fs::metadata(path).ok())
+            .map_or(0, |meta| meta.len())
+    }
+
+    /// Row count of every table, by table name. The notes index isn't one
+    /// anybody filled in, so it's left out.
+    pub fn row_counts(&self) -> Result<Vec<(String, i64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT name FROM sqlite_master
+             WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+             ORDER BY name",
+        )?;
+        let mut tables = stmt.query_map([], |row| row.get::<_, String>(0))?.collect::<Result<Vec<_>, _>>()?;
+        tables.retain(|name| !migrations::is_notes_index(name));
+        tables
+            .into_iter()
+            .map(|table| {
+                let sql = format!("SELECT COUNT(*) FROM \"{}\"", table.replace('"', "\"\""));
+                let count = self.conn.query_row(&sql, [], |row| row.get(0))?;
+                Ok((table, count))
+            })
+            .collect()
+    }
+}
+
+impl App {
+    /// "12.3 KB — workouts 120, measurements 8, …" for the vacuum prompt.
+    pub(crate) fn database_summary(&self) -> Result<String> {
+        let counts: Vec<String> = self
+            .db
+            .row_counts()?
+            .iter()
+            .map(|(table, count)| format!("{table} {count}"))
+            .collect();
+        Ok(format!("{} — {}", format_size(self.db.file_size()), counts.join(", ")))
+    }
+
+    pub(crate) fn start_vacuum(&mut self) -> Result<()> {
+        if self.vacuum.is_some() {
+            return Ok(());
+        }
+        let path = PathBuf::from(self.db.conn.path().unwrap_or_default());
+        let passphrase = self.db.passphrase.clone();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let result = encryption::open_connection(&path, OpenFlags::default(), passphrase.as_ref())
+                .and_then(|conn| Ok(conn.execute_batch("VACUUM; PRAGMA optimize;")?))
+                .context("VACUUM failed");
+            let _ = tx.send(result);
+        });
+        self.vacuum = Some(VacuumJob {
+            size_before: self.db.file_size(),
+            done: rx,
+        });
+        self.message = Some(self.text.get("data.vacuuming").to_string());
+        Ok(())
+    }
+
+    /// Reports a finished vacuum in the status line. Called from `tick`.
+    pub(crate) fn check_vacuum(&mut self) {
+        let Some(job) = &self.vacuum else {
+            return;
+        };
+        let result = match job.done.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => Err(anyhow::anyhow!("VACUUM thread stopped")),
+        };
+        let before = job.size_before;
+        self.vacuum = None;
+        self.watch_database();
+        self.message = Some(match result {
+            Ok(()) => {
+                let after = self.db.file_size();
+                self.text.format(
+                    "data.vacuumed",
+                    &[
+                        ("before", &format_size(before)),
+                        ("after", &format_size(after)),
+                        ("reclaimed", &format_size(before.saturating_sub(after))),
+                    ],
+                )
+            }
+            Err(e) => self.text.format("data.vacuum_failed", &[("error", &format!("{e:#}"))]),
+        });
+    }
+}
@@ -0,0 +1,239 @@
+use anyhow::Result;
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Paragraph, Row, Table, Tabs},
+    Frame,
+};
+use rusqlite::params;
+
+use crate::input::{DateInput, NumericInput};
+use crate::{App, Database, Screen};
+
+#[derive(Debug, Clone)]
+pub struct MeasurementRecord {
+    pub date: String,
+    pub value: f64,
+    pub unit: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MeasurementField {
+    Value,
+    Date,
+}
+
+pub struct MeasurementsState {
+    pub metric_selected: usize,
+    pub focus: MeasurementField,
+    pub date: DateInput,
+    pub value: NumericInput,
+}
+
+impl Default for MeasurementsState {
+    fn default() -> Self {
+        Self {
+            metric_selected: 0,
+            focus: MeasurementField::Value,
+            date: DateInput::default(),
+            value: NumericInput::decimal(),
+        }
+    }
+}
+
+impl Database {
+    pub fn add_measurement(&self, date: &str, metric: &str, value: f64, unit: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO measurements (date, metric, value, unit) VALUES (?1, ?2, ?3, ?4)",
+            params![date, metric, value, unit],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_measurements(&self, metric: &str) -> Result<Vec<MeasurementRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT date, value, unit FROM measurements
+             WHERE metric = ?1
+             ORDER BY date ASC, id ASC",
+        )?;
+
+        let records = stmt
+            .query_map([metric], |row| {
+                Ok(MeasurementRecord {
+                    date: row.get(0)?,
+                    value: row.get(1)?,
+                    unit: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(records)
+    }
+}
+
+impl App {
+    pub(crate) fn handle_measurements_input(&mut self, key: KeyCode) -> Result<bool> {
+        let metric_count = self.config.measurements.metrics.len();
+        let state = &mut self.measurements;
+        match key {
+            KeyCode::Esc => {
+                self.screen = Screen::Main;
+                self.message = None;
+            }
+            KeyCode::Left => {
+                state.metric_selected = state.metric_selected.saturating_sub(1);
+            }
+            KeyCode::Right => {
+                if state.metric_selected + 1 < metric_count {
+                    state.metric_selected += 1;
+                }
+            }
+            KeyCode::Tab => {
+                state.focus = match state.focus {
+                    MeasurementField::Value => MeasurementField::Date,
+                    MeasurementField::Date => MeasurementField::Value,
+                };
+            }
+            KeyCode::Enter => {
+                let Some(metric) = self.config.measurements.metrics.get(state.metric_selected) else {
+                    return Ok(false);
+                };
+                match (state.date.value(), state.value.positive_f64()) {
+                    (Some(date), Some(value)) => {
+                        let date = date.format("%Y-%m-%d").to_string();
+                        self.db.add_measurement(&date, &metric.name, value, &metric.unit)?;
+                        let amount = format!("{:.1} {}", value, metric.unit);
+                        self.message = Some(
+                            self.text.format("measurements.recorded", &[("metric", &metric.name), ("value", &amount)]),
+                        );
+                        state.value.clear();
+                    }
+                    (None, _) => self.message = Some(self.text.get("measurements.bad_date").to_string()),
+                    (_, None) => self.message = Some(self.text.get("measurements.bad_value").to_string()),
+                }
+            }
+            _ => {
+                match state.focus {
+                    MeasurementField::Value => state.value.handle_key(key),
+                    MeasurementField::Date => state.date.handle_key(key),
+                };
+            }
+        }
+        Ok(false)
+    }
+}
+
+pub fn render_measurements_screen(f: &mut Frame, area: Rect, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(0),
+        ])
+        .split(area);
+
+    let metrics = &app.config.measurements.metrics;
+    let state = &app.measurements;
+
+    // Metric selector
+    let titles: Vec<String> = metrics.iter().map(|m| m.name.clone()).collect();
+    let tabs = Tabs::new(titles)
+        .select(state.metric_selected)
+        .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("measurements.metric")));
+    f.render_widget(tabs, chunks[0]);
+
+    let Some(metric) = metrics.get(state.metric_selected) else {
+        let empty = Paragraph::new(app.text.get("measurements.no_metrics"))
+            .style(Style::default().fg(Color::Yellow))
+            .block(Block::default().borders(Borders::ALL));
+        f.render_widget(empty, chunks[1]);
+        return;
+    };
+
+    // Entry form
+    let form = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[1]);
+    let field_style = |field| {
+        if state.focus == field {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        }
+    };
+    let value = Paragraph::new(state.value.as_str())
+        .style(field_style(MeasurementField::Value))
+        .block(Block::default().borders(Borders::ALL).title(app.text.format("measurements.value", &[("unit", &metric.unit)])));
+    f.render_widget(value, form[0]);
+    let date = Paragraph::new(state.date.as_str())
+        .style(field_style(MeasurementField::Date))
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("common.date")));
+    f.render_widget(date, form[1]);
+
+    let records = app.db.get_measurements(&metric.name).unwrap_or_default();
+
+    // Summary: first, latest and the change between them
+    let summary = match (records.first(), records.last()) {
+        (Some(first), Some(latest)) => app.text.format(
+            "measurements.first_latest",
+            &[
+                ("first", &format!("{:.1} {}", first.value, first.unit)),
+                ("first_date", &app.display_date_str(&first.date)),
+                ("latest", &format!("{:.1} {}", latest.value, latest.unit)),
+                ("latest_date", &app.display_date_str(&latest.date)),
+                ("change", &format!("{:+.1} {}", latest.value - first.value, latest.unit)),
+            ],
+        ),
+        _ => app.text.format("measurements.none", &[("metric", &metric.name)]),
+    };
+    let summary_text = match &app.message {
+        Some(msg) => format!("{}  —  {}", msg, summary),
+        None => summary,
+    };
+    let summary = Paragraph::new(summary_text)
+        .style(Style::default().fg(Color::Green))
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("measurements.summary")));
+    f.render_widget(summary, chunks[2]);
+
+    // History, newest first, with the change from the previous entry
+    let rows: Vec<Row> = records
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(i, record)| {
+            let change = if i > 0 {
+                format!("{:+.1}", record.value - records[i - 1].value)
+            } else {
+                String::new()
+            };
+            Row::new(vec![
+                app.display_date_str(&record.date),
+                format!("{:.1} {}", record.value, record.unit),
+                change,
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(12),
+            Constraint::Length(14),
+            Constraint::Min(0),
+        ],
+    )
+    .header(
+        Row::new(vec![
+            app.text.get("common.date"),
+            app.text.get("measurements.value_column"),
+            app.text.get("measurements.change"),
+        ])
+            .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+    )
+    .block(Block::default().borders(Borders::ALL).title(app.text.format("measurements.history", &[("metric", &metric.name)])));
+    f.render_widget(table, chunks[3]);
+}
@@ -0,0 +1,98 @@
+use anyhow::{Context, Result};
+use rusqlite::OpenFlags;
+use std::{fs, path::Path, process};
+
+use crate::backup::check_database_file;
+use crate::encryption;
+use crate::{migrations, Database};
+
+/// Outcome of merging another database's workouts into this one.
+#[derive(Debug, Clone, Copy)]
+pub struct MergeReport {
+    pub added: usize,
+    /// Sets that already existed here.
+    pub skipped: usize,
+}
+
+impl Database {
+    /// Copies workouts from the database at `path` that don't exist here
+    /// yet, plus any exercise definitions this database lacks. The other
+    /// file is never written to: a temporary copy of it is migrated to the
+    /// current schema first, so older versions merge the same way.
+    ///
+    /// Sets are matched by UUID. Files from before UUIDs existed get fresh
+    /// ones in every copy, so their sets are matched on exercise, count and
+    /// timestamp instead. An encrypted file must share this database's
+    /// passphrase.
+    pub fn merge_from(&mut self, path: &Path) -> Result<MergeReport> {
+        check_database_file(path, self.passphrase.as_ref())?;
+
+        let temp = std::env::temp_dir().join(format!("fitness-tracker-merge-{}.db", process::id()));
+        let _ = fs::remove_file(&temp);
+        let last_id = self.last_workout_id()?;
+        let temp_str = temp.to_str().context("temporary path is not valid UTF-8")?;
+        let merged = (|| {
+            let other = encryption::open_connection(path, OpenFlags::SQLITE_OPEN_READ_ONLY, self.passphrase.as_ref())?;
+            let has_uuids = migrations::schema_version(&other)? >= migrations::WORKOUT_UUIDS;
+            other
+                .execute("VACUUM INTO ?1", [temp_str])
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            // Opening the copy brings it up to this version's schema
+            Database::with_connection(encryption::open_connection(&temp, OpenFlags::default(), self.passphrase.as_ref())?)?;
+            self.merge_attached(temp_str, has_uuids)
+        })();
+        let _ = fs::remove_file(&temp);
+        let report = merged?;
+        if report.added > 0 {
+            let summary = format!("{} sets from {}", report.added, path.display());
+            self.log_change(None, "merge", None, Some(&summary))?;
+            self.journal_inserted_after(last_id)?;
+        }
+        Ok(report)
+    }
+
+    fn merge_attached(&mut self, path: &str, has_uuids: bool) -> Result<MergeReport> {
+        let new_row = if has_uuids {
+            "o.uuid NOT IN (SELECT uuid FROM main.workouts)"
+        } else {
+            "NOT EXISTS (
+                SELECT 1 FROM main.workouts w
+                WHERE w.exercise_type = o.exercise_type AND w.count = o.count AND w.timestamp = o.timestamp
+            )"
+        };
+        // An attached file shares the main database's key unless told otherwise
+        let attach = if self.is_encrypted() && !encryption::is_encrypted(Path::new(path)) {
+            "ATTACH DATABASE ?1 AS other KEY ''"
+        } else {
+            "ATTACH DATABASE ?1 AS other"
+        };
+        self.conn.execute(attach, [path])?;
+        let merged = (|| {
+            let tx = self.conn.transaction()?;
+            let total: i64 = tx.query_row("SELECT COUNT(*) FROM other.workouts", [], |row| row.get(0))?;
+            tx.execute(
+                "INSERT OR IGNORE INTO exercises (name, category, position, color, archived, unit, default_count,
+                     progression, progression_base, progression_start)
+                 SELECT name, category, position, color, archived, unit, default_count,
+                     progression, progression_base, progression_start FROM other.exercises",
+                [],
+            )?;
+            let added = tx.execute(
+                &format!(
+                    "INSERT INTO workouts (exercise_type, count, timestamp, rpe, uuid, deleted_at, recorded_at, weight, notes)
+                     SELECT o.exercise_type, o.count, o.timestamp, o.rpe, o.uuid, o.deleted_at, o.recorded_at, o.weight, o.notes
+                     FROM other.workouts o
+                     WHERE {new_row}"
+                ),
+                [],
+            )?;
+            tx.commit()?;
+            Ok(MergeReport {
+                added,
+                skipped: (total as usize).saturating_sub(added),
+            })
+        })();
+        self.conn.execute("DETACH DATABASE other", [])?;
+        merged
+    }
+}
@@ -0,0 +1,315 @@
+use anyhow::Result;
+use rusqlite::Connection;
+
+/// Schema changes applied in order on top of the base tables. The index of
+/// the last applied entry (plus one) is stored in `PRAGMA user_version`, so
+/// entries must never be edited or reordered once released — only appended.
+const MIGRATIONS: &[&str] = &[
+    // 1: optional perceived exertion per set
+    "ALTER TABLE workouts ADD COLUMN rpe INTEGER",
+    // 2: daily mood check-in and small persisted app flags
+    "CREATE TABLE IF NOT EXISTS moods (
+        date TEXT PRIMARY KEY,
+        mood INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS app_state (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );",
+    // 3: exercise metadata, seeded with the built-ins and anything already logged
+    "CREATE TABLE IF NOT EXISTS exercises (
+        name TEXT PRIMARY KEY,
+        category TEXT,
+        position INTEGER NOT NULL DEFAULT 0
+    );
+    INSERT OR IGNORE INTO exercises (name, category, position)
+        VALUES ('squats', 'legs', 0), ('push-ups', 'push', 1);
+    INSERT OR IGNORE INTO exercises (name, position)
+        SELECT DISTINCT exercise_type, 100 FROM workouts;",
+    // 4: per-exercise display color
+    "ALTER TABLE exercises ADD COLUMN color TEXT",
+    // 5: alternate names that resolve to a canonical exercise
+    "CREATE TABLE IF NOT EXISTS exercise_aliases (
+        alias TEXT PRIMARY KEY,
+        exercise TEXT NOT NULL
+    )",
+    // 6: hide exercises from the picker without touching their history
+    "ALTER TABLE exercises ADD COLUMN archived INTEGER NOT NULL DEFAULT 0",
+    // 7: measurement unit and the count pre-filled on the add screen
+    "ALTER TABLE exercises ADD COLUMN unit TEXT NOT NULL DEFAULT 'reps';
+    ALTER TABLE exercises ADD COLUMN default_count INTEGER;",
+    // 8: daily targets per exercise
+    "CREATE TABLE IF NOT EXISTS goals (
+        exercise TEXT PRIMARY KEY,
+        daily_target INTEGER NOT NULL
+    )",
+    // 9: random v4 UUID per set, so merges match rows exactly. The trigger
+    // fills it in for every insert that doesn't bring its own.
+    "ALTER TABLE workouts ADD COLUMN uuid TEXT;
+    UPDATE workouts SET uuid =
+        lower(hex(randomblob(4))) || '-' || lower(hex(randomblob(2))) || '-4' ||
+        substr(lower(hex(randomblob(2))), 2) || '-' || substr('89ab', 1 + abs(random() % 4), 1) ||
+        substr(lower(hex(randomblob(2))), 2) || '-' || lower(hex(randomblob(6)));
+    CREATE UNIQUE INDEX IF NOT EXISTS workouts_uuid ON workouts (uuid);
+    CREATE TRIGGER IF NOT EXISTS workouts_uuid_default AFTER INSERT ON workouts
+    WHEN NEW.uuid IS NULL
+    BEGIN
+        UPDATE workouts SET uuid =
+            lower(hex(randomblob(4))) || '-' || lower(hex(randomblob(2))) || '-4' ||
+            substr(lower(hex(randomblob(2))), 2) || '-' || substr('89ab', 1 + abs(random() % 4), 1) ||
+            substr(lower(hex(randomblob(2))), 2) || '-' || lower(hex(randomblob(6)))
+        WHERE id = NEW.id;
+    END;",
+    // 10: soft delete; rows with a deletion time sit in the trash
+    "ALTER TABLE workouts ADD COLUMN deleted_at TEXT",
+    // 11: history of changes to sets, keyed by their UUID
+    "CREATE TABLE IF NOT EXISTS audit_log (
+        id INTEGER PRIMARY KEY,
+        row_uuid TEXT,
+        field TEXT NOT NULL,
+        old_value TEXT,
+        new_value TEXT,
+        at TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS audit_log_row ON audit_log (row_uuid);",
+    // 12: when a set was typed in, as opposed to when it was performed
+    "ALTER TABLE workouts ADD COLUMN recorded_at TEXT;
+    UPDATE workouts SET recorded_at = timestamp;",
+    // 13: weight lifted per set, in kg
+    "ALTER TABLE workouts ADD COLUMN weight REAL",
+    // 14: options changed on the settings screen, overriding config.toml
+    "CREATE TABLE IF NOT EXISTS settings (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    )",
+    // 15: weekly progression plan per exercise
+    "ALTER TABLE exercises ADD COLUMN progression TEXT;
+    ALTER TABLE exercises ADD COLUMN progression_base INTEGER;
+    ALTER TABLE exercises ADD COLUMN progression_start TEXT;",
+    // 16: column mappings saved by the generic CSV import, as JSON
+    "CREATE TABLE IF NOT EXISTS csv_mappings (
+        name TEXT PRIMARY KEY,
+        mapping TEXT NOT NULL
+    )",
+    // 17: tags across exercises, linked to sets; a purged set loses its links
+    "CREATE TABLE IF NOT EXISTS tags (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE
+    );
+    CREATE TABLE IF NOT EXISTS workout_tags (
+        workout_id INTEGER NOT NULL,
+        tag_id INTEGER NOT NULL,
+        PRIMARY KEY (workout_id, tag_id)
+    );
+    CREATE TRIGGER IF NOT EXISTS workout_tags_purged AFTER DELETE ON workouts
+    BEGIN
+        DELETE FROM workout_tags WHERE workout_id = OLD.id;
+    END;",
+    // 18: favorites, listed first in the picker
+    "ALTER TABLE exercises ADD COLUMN favorite INTEGER NOT NULL DEFAULT 0",
+    // 19: set schemes for guided adds
+    "ALTER TABLE exercises ADD COLUMN template_sets INTEGER;
+    ALTER TABLE exercises ADD COLUMN template_count INTEGER;",
+    // 20: a past day's sets copied as today's plan
+    "CREATE TABLE IF NOT EXISTS day_plan (
+        date TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        exercise TEXT NOT NULL,
+        count INTEGER NOT NULL
+    );",
+    // 21: totals to reach by a deadline, kept with their outcome once it passes
+    "CREATE TABLE IF NOT EXISTS deadline_goals (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        exercise TEXT NOT NULL,
+        target INTEGER NOT NULL,
+        start_date TEXT NOT NULL,
+        deadline TEXT NOT NULL,
+        outcome TEXT,
+        final_total INTEGER
+    );",
+    // 22: per day and exercise totals the stats read instead of every set,
+    // kept current by triggers; see `daily_totals`
+    "CREATE TABLE IF NOT EXISTS daily_totals (
+        day TEXT NOT NULL,
+        exercise_type TEXT NOT NULL,
+        sets INTEGER NOT NULL,
+        total INTEGER NOT NULL,
+        best INTEGER NOT NULL,
+        first_at TEXT NOT NULL,
+        volume REAL NOT NULL,
+        weighted_reps INTEGER NOT NULL,
+        rpe_sum INTEGER NOT NULL,
+        rpe_sets INTEGER NOT NULL,
+        PRIMARY KEY (day, exercise_type)
+    );
+    CREATE INDEX IF NOT EXISTS workouts_exercise_time ON workouts (exercise_type, timestamp);
+    INSERT INTO daily_totals
+        SELECT substr(timestamp, 1, 10) AS day, exercise_type, COUNT(*), SUM(count), MAX(count), MIN(timestamp),
+               COALESCE(SUM(weight * count), 0), SUM(CASE WHEN weight IS NULL THEN 0 ELSE count END),
+               COALESCE(SUM(rpe), 0), COUNT(rpe)
+        FROM workouts WHERE deleted_at IS NULL
+        GROUP BY day, exercise_type;
+    CREATE TRIGGER IF NOT EXISTS daily_totals_inserted AFTER INSERT ON workouts
+    WHEN NEW.deleted_at IS NULL
+    BEGIN
+        INSERT INTO daily_totals VALUES (
+            substr(NEW.timestamp, 1, 10), NEW.exercise_type, 1, NEW.count, NEW.count, NEW.timestamp,
+            COALESCE(NEW.weight * NEW.count, 0), CASE WHEN NEW.weight IS NULL THEN 0 ELSE NEW.count END,
+            COALESCE(NEW.rpe, 0), NEW.rpe IS NOT NULL)
+        ON CONFLICT (day, exercise_type) DO UPDATE SET
+            sets = sets + 1, total = total + excluded.total, best = MAX(best, excluded.best),
+            first_at = MIN(first_at, excluded.first_at), volume = volume + excluded.volume,
+            weighted_reps = weighted_reps + excluded.weighted_reps, rpe_sum = rpe_sum + excluded.rpe_sum,
+            rpe_sets = rpe_sets + excluded.rpe_sets;
+    END;
+    CREATE TRIGGER IF NOT EXISTS daily_totals_deleted AFTER DELETE ON workouts
+    WHEN OLD.deleted_at IS NULL
+    BEGIN
+        UPDATE daily_totals SET
+            sets = sets - 1, total = total - OLD.count, volume = volume - COALESCE(OLD.weight * OLD.count, 0),
+            weighted_reps = weighted_reps - CASE WHEN OLD.weight IS NULL THEN 0 ELSE OLD.count END,
+            rpe_sum = rpe_sum - COALESCE(OLD.rpe, 0), rpe_sets = rpe_sets - (OLD.rpe IS NOT NULL)
+        WHERE day = substr(OLD.timestamp, 1, 10) AND exercise_type = OLD.exercise_type;
+        DELETE FROM daily_totals
+        WHERE day = substr(OLD.timestamp, 1, 10) AND exercise_type = OLD.exercise_type AND sets <= 0;
+        UPDATE daily_totals SET
+            best = (SELECT MAX(w.count) FROM workouts w
+                    WHERE w.exercise_type = daily_totals.exercise_type AND w.deleted_at IS NULL
+                      AND w.timestamp >= daily_totals.day AND w.timestamp < date(daily_totals.day, '+1 day')),
+            first_at = (SELECT MIN(w.timestamp) FROM workouts w
+                        WHERE w.exercise_type = daily_totals.exercise_type AND w.deleted_at IS NULL
+                          AND w.timestamp >= daily_totals.day AND w.timestamp < date(daily_totals.day, '+1 day'))
+        WHERE day = substr(OLD.timestamp, 1, 10) AND exercise_type = OLD.exercise_type
+          AND (best = OLD.count OR first_at = OLD.timestamp);
+    END;
+    CREATE TRIGGER IF NOT EXISTS daily_totals_updated_from
+    AFTER UPDATE OF exercise_type, count, timestamp, rpe, weight, deleted_at ON workouts
+    WHEN OLD.deleted_at IS NULL
+    BEGIN
+        UPDATE daily_totals SET
+            sets = sets - 1, total = total - OLD.count, volume = volume - COALESCE(OLD.weight * OLD.count, 0),
+            weighted_reps = weighted_reps - CASE WHEN OLD.weight IS NULL THEN 0 ELSE OLD.count END,
+            rpe_sum = rpe_sum - COALESCE(OLD.rpe, 0), rpe_sets = rpe_sets - (OLD.rpe IS NOT NULL)
+        WHERE day = substr(OLD.timestamp, 1, 10) AND exercise_type = OLD.exercise_type;
+        DELETE FROM daily_totals
+        WHERE day = substr(OLD.timestamp, 1, 10) AND exercise_type = OLD.exercise_type AND sets <= 0;
+        UPDATE daily_totals SET
+            best = (SELECT MAX(w.count) FROM workouts w
+                    WHERE w.exercise_type = daily_totals.exercise_type AND w.deleted_at IS NULL
+                      AND w.timestamp >= daily_totals.day AND w.timestamp < date(daily_totals.day, '+1 day')),
+            first_at = (SELECT MIN(w.timestamp) FROM workouts w
+                        WHERE w.exercise_type = daily_totals.exercise_type AND w.deleted_at IS NULL
+                          AND w.timestamp >= daily_totals.day AND w.timestamp < date(daily_totals.day, '+1 day'))
+        WHERE day = substr(OLD.timestamp, 1, 10) AND exercise_type = OLD.exercise_type
+          AND (best = OLD.count OR first_at = OLD.timestamp);
+    END;
+    CREATE TRIGGER IF NOT EXISTS daily_totals_updated_to
+    AFTER UPDATE OF exercise_type, count, timestamp, rpe, weight, deleted_at ON workouts
+    WHEN NEW.deleted_at IS NULL
+    BEGIN
+        INSERT INTO daily_totals VALUES (
+            substr(NEW.timestamp, 1, 10), NEW.exercise_type, 1, NEW.count, NEW.count, NEW.timestamp,
+            COALESCE(NEW.weight * NEW.count, 0), CASE WHEN NEW.weight IS NULL THEN 0 ELSE NEW.count END,
+            COALESCE(NEW.rpe, 0), NEW.rpe IS NOT NULL)
+        ON CONFLICT (day, exercise_type) DO UPDATE SET
+            sets = sets + 1, total = total + excluded.total, best = MAX(best, excluded.best),
+            first_at = MIN(first_at, excluded.first_at), volume = volume + excluded.volume,
+            weighted_reps = weighted_reps + excluded.weighted_reps, rpe_sum = rpe_sum + excluded.rpe_sum,
+            rpe_sets = rpe_sets + excluded.rpe_sets;
+    END;",
+    // 23: free-text note per set, searchable through `NOTES_INDEX` where
+    // SQLite has FTS5
+    "ALTER TABLE workouts ADD COLUMN notes TEXT",
+];
+
+/// First schema version whose sets carry a UUID.
+pub const WORKOUT_UUIDS: usize = 9;
+
+/// First schema version whose sets carry a note.
+pub const NOTES: usize = 23;
+
+/// Full-text index over the notes. It only holds what the triggers in
+/// `NOTES_INDEX_SQL` put there, so it's left out of dumps and exports and
+/// built again from the notes wherever it's missing.
+pub const NOTES_INDEX: &str = "workout_notes";
+
+/// Trigram tokens so any substring of three characters or more matches,
+/// like the LIKE used without the index does.
+const NOTES_INDEX_SQL: &str = "
+    CREATE VIRTUAL TABLE workout_notes USING fts5(
+        notes, content = 'workouts', content_rowid = 'id', tokenize = 'trigram'
+    );
+    INSERT INTO workout_notes (workout_notes) VALUES ('rebuild');
+    CREATE TRIGGER workout_notes_inserted AFTER INSERT ON workouts
+    WHEN NEW.notes IS NOT NULL
+    BEGIN
+        INSERT INTO workout_notes (rowid, notes) VALUES (NEW.id, NEW.notes);
+    END;
+    CREATE TRIGGER workout_notes_deleted AFTER DELETE ON workouts
+    WHEN OLD.notes IS NOT NULL
+    BEGIN
+        INSERT INTO workout_notes (workout_notes, rowid, notes) VALUES ('delete', OLD.id, OLD.notes);
+    END;
+    CREATE TRIGGER workout_notes_updated AFTER UPDATE OF notes ON workouts
+    BEGIN
+        INSERT INTO workout_notes (workout_notes, rowid, notes)
+            SELECT 'delete', OLD.id, OLD.notes WHERE OLD.notes IS NOT NULL;
+        INSERT INTO workout_notes (rowid, notes)
+            SELECT NEW.id, NEW.notes WHERE NEW.notes IS NOT NULL;
+    END;";
+
+/// Whether `name` is the notes index or one of the tables and triggers
+/// that belong to it.
+pub fn is_notes_index(name: &str) -> bool {
+    name.strip_prefix(NOTES_INDEX).is_some_and(|rest| rest.is_empty() || rest.starts_with('_'))
+}
+
+/// Whether the notes index is there to search.
+pub fn has_notes_index(conn: &Connection) -> Result<bool> {
+    Ok(conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+        [NOTES_INDEX],
+        |row| row.get(0),
+    )?)
+}
+
+/// Builds the notes index over the notes already there, unless it exists
+/// or this SQLite was built without FTS5; searches use LIKE then.
+fn create_notes_index(conn: &Connection) -> Result<()> {
+    let fts5: bool = conn.query_row("SELECT sqlite_compileoption_used('ENABLE_FTS5')", [], |row| row.get(0))?;
+    if fts5 && !has_notes_index(conn)? {
+        conn.execute_batch(NOTES_INDEX_SQL)?;
+    }
+    Ok(())
+}
+
+/// Schema version a fully migrated database has.
+pub fn latest_version() -> usize {
+    MIGRATIONS.len()
+}
+
+pub fn schema_version(conn: &Connection) -> Result<usize> {
+    let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    Ok(version as usize)
+}
+
+pub fn migrate(conn: &mut Connection) -> Result<()> {
+    let current = schema_version(conn)?;
+    for (i, sql) in MIGRATIONS.iter().enumerate().skip(current) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(sql)?;
+        if i + 1 == NOTES {
+            create_notes_index(&tx)?;
+        }
+        tx.pragma_update(None, "user_version", (i + 1) as i64)?;
+        tx.commit()?;
+    }
+    // Restoring a dump, or opening with a build that lacked FTS5, leaves
+    // the notes unindexed
+    if current >= NOTES {
+        let tx = conn.transaction()?;
+        create_notes_index(&tx)?;
+        tx.commit()?;
+    }
+    Ok(())
+}
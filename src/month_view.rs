@@ -0,0 +1,146 @@
+//! A month as a calendar: a row per week, each day showing how many sets
+//! it had, with dots for how busy it was next to the month's busiest day.
+//! The whole grid's numbers come from one query, and Enter opens the day
+//! under the cursor in the history.
+
+use anyhow::Result;
+use chrono::{Datelike, Duration, Local, Months, NaiveDate};
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use std::collections::HashMap;
+
+use crate::dates::{self, week_start};
+use crate::{App, Database, Screen};
+
+/// Most dots a day gets, for the busiest day of the month.
+const MAX_DOTS: usize = 4;
+
+#[derive(Debug)]
+pub struct MonthState {
+    /// Day under the cursor; its month is the one shown.
+    pub cursor: NaiveDate,
+}
+
+impl Database {
+    /// Sets per day from `from` to `to`, days without any left out.
+    pub fn sets_per_day(&self, from: NaiveDate, to: NaiveDate) -> Result<HashMap<NaiveDate, i64>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT day, SUM(sets)
+             FROM daily_totals
+             WHERE day >= ?1 AND day <= ?2
+             GROUP BY day",
+        )?;
+        let mut sets = HashMap::new();
+        let rows = stmt.query_map([from.to_string(), to.to_string()], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?;
+        for row in rows {
+            let (day, count) = row?;
+            if let Some(day) = dates::parse_date(&day) {
+                sets.insert(day, count);
+            }
+        }
+        Ok(sets)
+    }
+}
+
+/// First and last day of the weeks covering `day`'s month.
+fn grid(day: NaiveDate, app: &App) -> (NaiveDate, NaiveDate) {
+    let first = day.with_day(1).unwrap_or(day);
+    let last = first + Months::new(1) - Duration::days(1);
+    let start = week_start(first, app.config.week_starts_on);
+    (start, week_start(last, app.config.week_starts_on) + Duration::days(6))
+}
+
+impl App {
+    pub(crate) fn open_month(&mut self) {
+        self.month = MonthState { cursor: Local::now().date_naive() };
+        self.screen = Screen::Month;
+        self.message = None;
+    }
+
+    pub(crate) fn handle_month_input(&mut self, key: KeyCode) -> Result<bool> {
+        let cursor = self.month.cursor;
+        match key {
+            KeyCode::Esc => self.screen = Screen::Main,
+            KeyCode::Left => self.month.cursor = cursor - Duration::days(1),
+            KeyCode::Right => self.month.cursor = cursor + Duration::days(1),
+            KeyCode::Up => self.month.cursor = cursor - Duration::weeks(1),
+            KeyCode::Down => self.month.cursor = cursor + Duration::weeks(1),
+            // Days past the end of the shorter month land on its last day
+            KeyCode::PageUp => self.month.cursor = cursor - Months::new(1),
+            KeyCode::PageDown => self.month.cursor = cursor + Months::new(1),
+            KeyCode::Enter => self.open_history_day(cursor, Screen::Month)?,
+            _ => {}
+        }
+        Ok(false)
+    }
+}
+
+/// The dots for `sets` out of the month's busiest day's `max`.
+fn dots(sets: i64, max: i64) -> String {
+    let dots = ((sets as f64 / max.max(1) as f64) * MAX_DOTS as f64).ceil() as usize;
+    "●".repeat(dots.clamp(1, MAX_DOTS))
+}
+
+pub fn render_month_screen(f: &mut Frame, area: Rect, app: &App) {
+    let today = Local::now().date_naive();
+    let cursor = app.month.cursor;
+    let (start, end) = grid(cursor, app);
+    let sets = app.db.sets_per_day(start, end).unwrap_or_default();
+    let max = sets.iter().filter(|(day, _)| day.month() == cursor.month()).map(|(_, count)| *count).max().unwrap_or(0);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(app.text.format("month.title", &[("month", &cursor.format("%Y-%m"))]));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let weeks = ((end - start).num_days() as u32 + 1) / 7;
+    let mut constraints = vec![Constraint::Length(1)];
+    constraints.extend((0..weeks).map(|_| Constraint::Ratio(1, weeks)));
+    let rows = Layout::default().direction(Direction::Vertical).constraints(constraints).split(inner);
+    let columns = |row: Rect| Layout::default().direction(Direction::Horizontal).constraints([Constraint::Ratio(1, 7); 7]).split(row);
+
+    let header = Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+    for (i, cell) in columns(rows[0]).iter().enumerate() {
+        let day = start + Duration::days(i as i64);
+        let name: String = dates::weekday_name(day.weekday(), &app.text).chars().take(3).collect();
+        f.render_widget(Paragraph::new(name).style(header), *cell);
+    }
+
+    for (week, row) in rows.iter().skip(1).enumerate() {
+        for (i, cell) in columns(*row).iter().enumerate() {
+            let day = start + Duration::days((week * 7 + i) as i64);
+            let count = sets.get(&day).copied().unwrap_or(0);
+            let mut style = Style::default();
+            if day.month() != cursor.month() {
+                style = style.fg(Color::DarkGray);
+            } else if day == today {
+                style = style.fg(Color::Yellow).add_modifier(Modifier::BOLD);
+            }
+            if day == cursor {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            let number = Span::raw(format!("{:>2}", day.day()));
+            let mut lines = vec![Line::from(number.clone())];
+            if count > 0 {
+                let marker_style = Style::default().fg(if day.month() == cursor.month() { Color::Green } else { Color::DarkGray });
+                let marker = dots(count, max);
+                if cell.height < 2 {
+                    // Too short for a second line, so the dots follow the day
+                    lines = vec![Line::from(vec![number, Span::styled(format!(" {marker}"), marker_style)])];
+                } else {
+                    // The count only when the dots leave room for it
+                    let full = format!("{marker} {count}");
+                    let marker = if full.chars().count() <= cell.width as usize { full } else { marker };
+                    lines.push(Line::styled(marker, marker_style));
+                }
+            }
+            f.render_widget(Paragraph::new(lines).style(style), *cell);
+        }
+    }
+}
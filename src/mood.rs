@@ -0,0 +1,120 @@
+use anyhow::Result;
+use chrono::{Local, NaiveDate};
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::Alignment,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use rusqlite::{params, OptionalExtension};
+use std::collections::HashMap;
+
+use crate::{centered_rect, App, Database};
+
+const DISMISSED_KEY: &str = "mood_prompt_dismissed";
+
+pub fn mood_icon(mood: i32) -> &'static str {
+    match mood {
+        1 => "😞",
+        2 => "🙁",
+        3 => "😐",
+        4 => "🙂",
+        _ => "😄",
+    }
+}
+
+impl Database {
+    pub fn set_mood(&self, date: &str, mood: i32) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO moods (date, mood) VALUES (?1, ?2)
+             ON CONFLICT(date) DO UPDATE SET mood = excluded.mood",
+            params![date, mood],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_mood(&self, date: &str) -> Result<Option<i32>> {
+        let mood = self
+            .conn
+            .query_row("SELECT mood FROM moods WHERE date = ?1", [date], |row| row.get(0))
+            .optional()?;
+        Ok(mood)
+    }
+
+    pub fn get_moods(&self) -> Result<HashMap<String, i32>> {
+        let mut stmt = self.conn.prepare("SELECT date, mood FROM moods")?;
+        let moods = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        Ok(moods)
+    }
+
+    pub fn get_moods_since(&self, since: NaiveDate) -> Result<Vec<(NaiveDate, i32)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT date, mood FROM moods WHERE date >= ?1 ORDER BY date ASC",
+        )?;
+        let rows = stmt
+            .query_map([since.format("%Y-%m-%d").to_string()], |row| {
+                Ok((row.get::<_, String>(0)?, row.get(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(date, mood)| {
+                Some((NaiveDate::parse_from_str(&date, "%Y-%m-%d").ok()?, mood))
+            })
+            .collect())
+    }
+}
+
+impl App {
+    /// The check-in is shown once per day: answering or dismissing it both
+    /// count as having been asked.
+    pub(crate) fn should_prompt_mood(&self) -> Result<bool> {
+        if !self.config.mood.prompt {
+            return Ok(false);
+        }
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        let dismissed = self.db.get_state(DISMISSED_KEY)?;
+        Ok(self.db.get_mood(&today)?.is_none() && dismissed.as_deref() != Some(today.as_str()))
+    }
+
+    pub(crate) fn handle_mood_prompt_input(&mut self, key: KeyCode) -> Result<bool> {
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        match key {
+            KeyCode::Char(c @ '1'..='5') => {
+                let mood = c.to_digit(10).unwrap_or(3) as i32;
+                self.db.set_mood(&today, mood)?;
+                self.mood_prompt = false;
+            }
+            KeyCode::Esc => {
+                self.db.set_state(DISMISSED_KEY, &today)?;
+                self.mood_prompt = false;
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+}
+
+pub fn render_mood_prompt(f: &mut Frame, app: &App) {
+    let area = centered_rect(44, 7, f.size());
+    let text = format!(
+        "{}\n\n1 {}  2 {}  3 {}  4 {}  5 {}\n\n{}",
+        app.text.get("mood.question"),
+        mood_icon(1),
+        mood_icon(2),
+        mood_icon(3),
+        mood_icon(4),
+        mood_icon(5),
+        app.text.get("mood.skip")
+    );
+    let prompt = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("mood.title")));
+    f.render_widget(Clear, area);
+    f.render_widget(prompt, area);
+}
+
@@ -0,0 +1,189 @@
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture, KeyCode, MouseButton, MouseEvent, MouseEventKind},
+    execute,
+};
+use ratatui::layout::Rect;
+
+use crate::day_view::{self, DayRow};
+use crate::store::WorkoutStore;
+use crate::{App, Screen};
+
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+const WHEEL_ROWS: usize = 3;
+
+/// Screen regions recorded while drawing so mouse events can be mapped back
+/// to whatever was under the pointer. Rebuilt on every frame.
+#[derive(Debug, Default)]
+pub struct Hitboxes {
+    /// Rows of the history list (dates or entries) and the index of the first
+    /// visible row.
+    pub history_list: Option<(Rect, usize)>,
+    /// Footer hotkey hints and the key each one stands for.
+    pub hints: Vec<(Rect, KeyCode)>,
+}
+
+impl Hitboxes {
+    /// Records the `[key] Label` hints of a footer line drawn at `area`.
+    pub fn add_hints(&mut self, text: &str, area: Rect) {
+        let chars: Vec<char> = text.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            if chars[i] != '[' {
+                i += 1;
+                continue;
+            }
+            let Some(close) = chars[i..].iter().position(|c| *c == ']').map(|p| i + p) else {
+                break;
+            };
+            // The hint runs until the next one starts
+            let end = chars[close..].iter().position(|c| *c == '[').map_or(chars.len(), |p| close + p);
+            let key: String = chars[i + 1..close].iter().collect();
+            if let Some(key) = hint_key(&key) {
+                let x = area.x.saturating_add(i as u16);
+                let width = (end - i) as u16;
+                let right = area.right();
+                if x < right {
+                    self.hints.push((Rect::new(x, area.y, width.min(right - x), 1), key));
+                }
+            }
+            i = end;
+        }
+    }
+}
+
+/// Key a hint label stands for; combined hints like "↑/↓" aren't clickable.
+fn hint_key(label: &str) -> Option<KeyCode> {
+    let mut chars = label.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(KeyCode::Char(c)),
+        _ => match label {
+            "Esc" => Some(KeyCode::Esc),
+            "Enter" => Some(KeyCode::Enter),
+            "Tab" => Some(KeyCode::Tab),
+            "Del" => Some(KeyCode::Delete),
+            "Space" => Some(KeyCode::Char(' ')),
+            _ => None,
+        },
+    }
+}
+
+fn contains(area: Rect, column: u16, row: u16) -> bool {
+    column >= area.x && column < area.right() && row >= area.y && row < area.bottom()
+}
+
+impl App {
+    /// Turns terminal mouse capture on or off. Only sends the escape sequence
+    /// when the state actually changes, so shutdown never disables a capture
+    /// that was never enabled.
+    pub(crate) fn set_mouse_capture(&mut self, enabled: bool) -> Result<()> {
+        if enabled == self.mouse_capture {
+            return Ok(());
+        }
+        if enabled {
+            execute!(io::stdout(), EnableMouseCapture)?;
+        } else {
+            execute!(io::stdout(), DisableMouseCapture)?;
+        }
+        self.mouse_capture = enabled;
+        Ok(())
+    }
+
+    pub(crate) fn handle_mouse(&mut self, event: MouseEvent) -> Result<bool> {
+        if self.mood_prompt {
+            return Ok(false);
+        }
+        let (column, row) = (event.column, event.row);
+        let over_history = matches!(self.screen, Screen::History)
+            && self
+                .hitboxes
+                .borrow()
+                .history_list
+                .is_some_and(|(area, _)| contains(area, column, row));
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {}
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown if over_history => {
+                self.scroll_history(event.kind == MouseEventKind::ScrollUp)?;
+                return Ok(false);
+            }
+            _ => return Ok(false),
+        }
+
+        let hint = self
+            .hitboxes
+            .borrow()
+            .hints
+            .iter()
+            .find(|(area, _)| contains(*area, column, row))
+            .map(|(_, key)| *key);
+        if let Some(key) = hint {
+            return self.handle_input(key);
+        }
+
+        if matches!(self.screen, Screen::History) {
+            let clicked = self.hitboxes.borrow().history_list.and_then(|(area, offset)| {
+                contains(area, column, row).then(|| offset + (row - area.y) as usize)
+            });
+            if let Some(index) = clicked {
+                self.click_history_row(index)?;
+            }
+        }
+        Ok(false)
+    }
+
+    /// Moves the history view by a few rows. The selection only moves when
+    /// it would otherwise scroll out of sight.
+    fn scroll_history(&mut self, up: bool) -> Result<()> {
+        let Some((area, offset)) = self.hitboxes.borrow().history_list else {
+            return Ok(());
+        };
+        let day_rows = day_view::rows(&self.history_entries()?, self.history_grouped);
+        let len = if self.selected_date.is_some() {
+            day_rows.len()
+        } else {
+            self.db.get_unique_dates(&self.history_filter)?.len()
+        };
+        let rows = area.height as usize;
+        let offset = if up {
+            offset.saturating_sub(WHEEL_ROWS)
+        } else {
+            (offset + WHEEL_ROWS).min(len.saturating_sub(rows))
+        };
+        self.history_offset.set(offset);
+
+        let last_visible = (offset + rows).min(len).saturating_sub(1);
+        if self.selected_date.is_some() {
+            // Headers and totals can't be selected, so pick the nearest set
+            let row = day_view::row_of(&day_rows, self.history_entry).unwrap_or(0).clamp(offset, last_visible.max(offset));
+            self.history_entry = day_view::entry_near(&day_rows, row).unwrap_or(0);
+        } else {
+            self.history_selected = self.history_selected.clamp(offset, last_visible.max(offset));
+        }
+        Ok(())
+    }
+
+    fn click_history_row(&mut self, index: usize) -> Result<()> {
+        let double = self
+            .last_click
+            .is_some_and(|(at, last)| last == index && at.elapsed() < DOUBLE_CLICK);
+        self.last_click = Some((Instant::now(), index));
+
+        if self.selected_date.is_some() {
+            if let Some(DayRow::Set(entry)) = day_view::rows(&self.history_entries()?, self.history_grouped).get(index) {
+                self.history_entry = *entry;
+            }
+        } else if index < self.db.get_unique_dates(&self.history_filter)?.len() {
+            self.history_selected = index;
+            if double {
+                self.last_click = None;
+                self.handle_input(KeyCode::Enter)?;
+            }
+        }
+        Ok(())
+    }
+}
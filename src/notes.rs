@@ -0,0 +1,57 @@
+//! A free-text note per set, like "felt easy" or "left knee sore", typed
+//! with 'N' in the history's day view and shown after the set there. The
+//! search screen matches notes as well as exercise names.
+
+use anyhow::Result;
+use crossterm::event::KeyCode;
+use rusqlite::params;
+
+use crate::input::TextInput;
+use crate::{App, Database};
+
+impl Database {
+    /// Replaces the note of set `id`; `None` removes it.
+    pub fn set_workout_note(&self, id: i64, note: Option<&str>) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        let (old, uuid): (Option<String>, Option<String>) =
+            tx.query_row("SELECT notes, uuid FROM workouts WHERE id = ?1", [id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        if old.as_deref() != note {
+            tx.execute("UPDATE workouts SET notes = ?2 WHERE id = ?1", params![id, note])?;
+            self.log_change(uuid.as_deref(), "notes", old.as_deref(), note)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+impl App {
+    /// Opens the note of the highlighted set of the day view for editing.
+    pub(crate) fn edit_history_note(&mut self) -> Result<()> {
+        if let Some(entry) = self.history_entries()?.get(self.history_entry) {
+            self.history_note = Some(TextInput::with_text(entry.note.as_deref().unwrap_or_default()));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn handle_history_note_input(&mut self, key: KeyCode) -> Result<bool> {
+        let Some(input) = &mut self.history_note else {
+            return Ok(false);
+        };
+        match key {
+            KeyCode::Esc => self.history_note = None,
+            KeyCode::Enter => {
+                let text = input.as_str().trim().to_string();
+                self.history_note = None;
+                if let Some(entry) = self.history_entries()?.get(self.history_entry) {
+                    // Saving an empty note removes it
+                    self.db.set_workout_note(entry.id, Some(text.as_str()).filter(|text| !text.is_empty()))?;
+                    self.forget_history_days();
+                }
+            }
+            _ => {
+                input.handle_key(key);
+            }
+        }
+        Ok(false)
+    }
+}
@@ -0,0 +1,143 @@
+use std::io::{self, Write};
+#[cfg(feature = "desktop-notify")]
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, Instant};
+
+use ratatui::{style::Modifier, Frame};
+use serde::Deserialize;
+
+use crate::App;
+
+/// Bells closer together than this are dropped so a burst of events
+/// doesn't turn into a bell storm.
+const MIN_BELL_GAP: Duration = Duration::from_secs(3);
+const FLASH_LENGTH: Duration = Duration::from_millis(300);
+
+/// How an event gets the user's attention.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Alert {
+    Off,
+    Bell,
+    Flash,
+    Both,
+}
+
+impl Alert {
+    fn bell(self) -> bool {
+        matches!(self, Alert::Bell | Alert::Both)
+    }
+
+    fn flash(self) -> bool {
+        matches!(self, Alert::Flash | Alert::Both)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum NotifyEvent {
+    TimerDone,
+    GoalReached,
+}
+
+#[derive(Debug, Default)]
+pub struct Notifier {
+    last_bell: Option<Instant>,
+    flash_until: Option<Instant>,
+    #[cfg(feature = "desktop-notify")]
+    desktop_failed: DesktopFailures,
+}
+
+/// Desktop notifications are sent from a worker thread, which reports
+/// failures back here so the main loop can ring the bell instead.
+#[cfg(feature = "desktop-notify")]
+#[derive(Debug)]
+struct DesktopFailures {
+    tx: Sender<()>,
+    rx: Receiver<()>,
+}
+
+#[cfg(feature = "desktop-notify")]
+impl Default for DesktopFailures {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self { tx, rx }
+    }
+}
+
+impl App {
+    /// Alerts the user to `event`; `body` is the in-app message, reused for
+    /// the desktop notification when that feature is built in.
+    pub(crate) fn notify(&mut self, event: NotifyEvent, body: &str) {
+        let config = &self.config.notify;
+        if !config.enabled {
+            return;
+        }
+        let alert = match event {
+            NotifyEvent::TimerDone => config.timer,
+            NotifyEvent::GoalReached => config.goal,
+        };
+        #[cfg(feature = "desktop-notify")]
+        if config.desktop && alert != Alert::Off {
+            self.send_desktop_notification(body);
+        }
+        #[cfg(not(feature = "desktop-notify"))]
+        let _ = body;
+
+        if alert.bell() {
+            self.ring_bell();
+        }
+        if alert.flash() {
+            self.notifier.flash_until = Some(Instant::now() + FLASH_LENGTH);
+        }
+    }
+
+    fn ring_bell(&mut self) {
+        let now = Instant::now();
+        if self.notifier.last_bell.is_some_and(|at| now - at < MIN_BELL_GAP) {
+            return;
+        }
+        // A missing bell is not worth failing over
+        let mut stdout = io::stdout();
+        let _ = stdout.write_all(b"\x07").and_then(|_| stdout.flush());
+        self.notifier.last_bell = Some(now);
+    }
+
+    /// Hands the notification to a worker thread so a slow or missing
+    /// notification daemon never blocks the UI.
+    #[cfg(feature = "desktop-notify")]
+    fn send_desktop_notification(&self, body: &str) {
+        let failed = self.notifier.desktop_failed.tx.clone();
+        let summary = self.text.get("main.title").to_string();
+        let body = body.to_string();
+        std::thread::spawn(move || {
+            let sent = notify_rust::Notification::new().summary(&summary).body(&body).show();
+            if sent.is_err() {
+                let _ = failed.send(());
+            }
+        });
+    }
+
+    /// Falls back to the bell for desktop notifications that didn't make it.
+    pub(crate) fn check_notifications(&mut self) {
+        #[cfg(feature = "desktop-notify")]
+        if self.notifier.desktop_failed.rx.try_recv().is_ok() {
+            while self.notifier.desktop_failed.rx.try_recv().is_ok() {}
+            self.ring_bell();
+        }
+    }
+
+    pub(crate) fn flashing(&self) -> bool {
+        self.notifier.flash_until.is_some_and(|until| Instant::now() < until)
+    }
+}
+
+/// Inverts every cell of the frame for the visual flash.
+pub fn flash(f: &mut Frame) {
+    let area = f.size();
+    let buffer = f.buffer_mut();
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            buffer.get_mut(x, y).modifier.toggle(Modifier::REVERSED);
+        }
+    }
+}
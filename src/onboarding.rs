@@ -0,0 +1,324 @@
+//! The short setup shown on first run: where the data lives, which exercises
+//! to track, and an optional daily goal, ending on the add screen. Every
+//! step can be skipped with Esc, and once the flow is over it doesn't come
+//! back unless asked for with `--onboarding`.
+
+use anyhow::{bail, ensure, Result};
+use chrono::Local;
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::config;
+use crate::exercises::Exercise;
+use crate::input::{NumericInput, TextInput};
+use crate::instance_lock::{self, Acquired};
+use crate::profiles::DEFAULT_PROFILE;
+use crate::reload::ConfigWatch;
+use crate::units;
+use crate::{centered_rect, AddField, App, Database, Screen};
+
+/// `app_state` key recording when the flow was finished or skipped.
+const DONE_KEY: &str = "onboarding_done";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Step {
+    Location,
+    Exercises,
+    Custom,
+    Goal,
+}
+
+#[derive(Debug)]
+pub struct Onboarding {
+    pub step: Step,
+    pub location: TextInput,
+    /// Highlighted row on the exercises step, as an index into all exercises.
+    pub selected: usize,
+    pub custom: TextInput,
+    /// Exercise the goal is for, as an index into the picker's exercises.
+    pub goal_exercise: usize,
+    pub goal: NumericInput,
+    /// Why the last step couldn't be completed.
+    pub error: Option<String>,
+}
+
+impl Onboarding {
+    /// Starts at the data location, which only the default profile offers:
+    /// other profiles' files live where the profiles screen puts them.
+    fn new(location: &Path, profile: &str) -> Self {
+        Self {
+            step: if profile == DEFAULT_PROFILE { Step::Location } else { Step::Exercises },
+            location: TextInput::with_text(&location.to_string_lossy()),
+            selected: 0,
+            custom: TextInput::default(),
+            goal_exercise: 0,
+            goal: NumericInput::default(),
+            error: None,
+        }
+    }
+}
+
+impl Database {
+    /// Whether nothing has been logged or remembered yet, which is the case
+    /// until the first run is over.
+    pub fn is_first_run(&self) -> Result<bool> {
+        Ok(self.conn.query_row(
+            "SELECT NOT EXISTS(SELECT 1 FROM workouts) AND NOT EXISTS(SELECT 1 FROM app_state)",
+            [],
+            |row| row.get(0),
+        )?)
+    }
+}
+
+impl App {
+    /// Shows the flow if this is the first run, or always with `force`.
+    pub(crate) fn start_onboarding(&mut self, force: bool) -> Result<()> {
+        if force || self.db.is_first_run()? {
+            self.onboarding = Some(Onboarding::new(&self.db.file_path(), &self.profiles.active));
+            // The mood check-in can wait for the next start
+            self.mood_prompt = false;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn handle_onboarding_input(&mut self, key: KeyCode) -> Result<bool> {
+        let exercises: Vec<Exercise> = self.picker_exercises().into_iter().cloned().collect();
+        let Some(flow) = &mut self.onboarding else {
+            return Ok(false);
+        };
+        flow.error = None;
+        let skip = key == KeyCode::Esc;
+        match flow.step {
+            Step::Location => {
+                if skip {
+                    flow.step = Step::Exercises;
+                } else if key == KeyCode::Enter {
+                    let to = PathBuf::from(flow.location.as_str().trim());
+                    match self.move_database(&to) {
+                        Ok(()) => self.next_onboarding_step(Step::Exercises),
+                        Err(e) => self.onboarding_error(format!("{e:#}")),
+                    }
+                } else {
+                    flow.location.handle_key(key);
+                }
+            }
+            Step::Exercises => match key {
+                KeyCode::Esc | KeyCode::Enter => flow.step = Step::Custom,
+                KeyCode::Up => flow.selected = flow.selected.saturating_sub(1),
+                KeyCode::Down if flow.selected + 1 < self.exercises.len() => flow.selected += 1,
+                KeyCode::Char(' ') => {
+                    if let Some(exercise) = self.exercises.get(flow.selected) {
+                        self.db.set_exercise_archived(&exercise.name, !exercise.archived)?;
+                        self.reload_exercises()?;
+                    }
+                }
+                _ => {}
+            },
+            Step::Custom => {
+                if skip || (key == KeyCode::Enter && flow.custom.as_str().trim().is_empty()) {
+                    flow.step = Step::Goal;
+                } else if key == KeyCode::Enter {
+                    let name = flow.custom.as_str().to_string();
+                    let before = self.exercises.len();
+                    self.create_exercise(&name)?;
+                    if self.exercises.len() > before {
+                        self.next_onboarding_step(Step::Goal);
+                    } else if let Some(message) = self.message.take() {
+                        self.onboarding_error(message);
+                    }
+                } else {
+                    flow.custom.handle_key(key);
+                }
+            }
+            Step::Goal => {
+                let count = exercises.len().max(1);
+                match key {
+                    KeyCode::Esc => self.finish_onboarding()?,
+                    KeyCode::Left => {
+                        flow.goal_exercise = (flow.goal_exercise + count - 1) % count;
+                        flow.goal.clear();
+                    }
+                    KeyCode::Right => {
+                        flow.goal_exercise = (flow.goal_exercise + 1) % count;
+                        flow.goal.clear();
+                    }
+                    KeyCode::Enter => {
+                        let text = flow.goal.as_str().to_string();
+                        if let Some(exercise) = exercises.get(flow.goal_exercise)
+                            && !text.is_empty()
+                        {
+                            match units::parse_amount(&text, &exercise.unit, self.config.units.distance) {
+                                Some(target) => self.db.set_goal(&exercise.name, Some(target))?,
+                                None => {
+                                    self.onboarding_error(self.text.get("history.invalid_amount").to_string());
+                                    return Ok(false);
+                                }
+                            }
+                        }
+                        self.finish_onboarding()?;
+                    }
+                    _ => {
+                        let unit = exercises.get(flow.goal_exercise).map_or("", |e| e.unit.as_str());
+                        flow.goal.set_decimal(units::is_decimal(unit));
+                        flow.goal.handle_key(key);
+                    }
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    fn next_onboarding_step(&mut self, step: Step) {
+        if let Some(flow) = &mut self.onboarding {
+            flow.step = step;
+        }
+    }
+
+    fn onboarding_error(&mut self, error: String) {
+        if let Some(flow) = &mut self.onboarding {
+            flow.error = Some(error);
+        }
+    }
+
+    /// Remembers that the flow is done and opens the add screen.
+    fn finish_onboarding(&mut self) -> Result<()> {
+        self.db.set_state(DONE_KEY, &Local::now().format("%Y-%m-%d").to_string())?;
+        self.onboarding = None;
+        self.screen = Screen::AddWorkout;
+        self.selected_exercise = 0;
+        self.prefill_count();
+        self.input_rpe.clear();
+        self.add_field = AddField::Count;
+        self.message = None;
+        Ok(())
+    }
+
+    /// Moves the (still empty) data file to `to`, or opens the file already
+    /// there, and points the config at it for the next start.
+    fn move_database(&mut self, to: &Path) -> Result<()> {
+        let from = self.db.file_path();
+        ensure!(!to.as_os_str().is_empty(), "the location can't be empty");
+        if to == from {
+            return Ok(());
+        }
+        let lock = match instance_lock::acquire(to)? {
+            Acquired::Locked(lock) => lock,
+            Acquired::Held(holder) => bail!("{} is open in another instance (PID {} on {})", to.display(), holder.pid, holder.host),
+        };
+        let backend = self.profiles.profiles.backend;
+        if !to.exists() {
+            if let Some(dir) = to.parent()
+                && !dir.as_os_str().is_empty()
+            {
+                fs::create_dir_all(dir)?;
+            }
+            self.db.save()?;
+            if from.exists() {
+                fs::rename(&from, to)?;
+            }
+        }
+        let mut db = Database::open(backend, to)?;
+        if self.config.journal.enabled {
+            let journal = self.profiles.profiles.journal_path(DEFAULT_PROFILE, &db, &self.config.journal);
+            db.start_journal(journal);
+        }
+        config::save_storage_path(config::CONFIG_PATH, to)?;
+        // Already in effect, so the reload shouldn't ask for a restart
+        self.file_config.storage.path = Some(to.to_path_buf());
+        self.config_watch = ConfigWatch::new(config::CONFIG_PATH);
+        self.db = db;
+        self.instance_lock = Some(lock);
+        self.profiles.profiles.base = to.to_path_buf();
+        self.reload_exercises()?;
+        self.apply_settings()?;
+        Ok(())
+    }
+}
+
+pub fn render_onboarding(f: &mut Frame, area: Rect, app: &App) {
+    let Some(flow) = &app.onboarding else {
+        return;
+    };
+    let area = centered_rect(70, 20, area);
+    let (number, title) = match flow.step {
+        Step::Location => (1, "onboarding.location_title"),
+        Step::Exercises => (2, "onboarding.exercises_title"),
+        Step::Custom => (3, "onboarding.custom_title"),
+        Step::Goal => (4, "onboarding.goal_title"),
+    };
+    let title = app.text.format("onboarding.title", &[("step", &number), ("title", &app.text.get(title))]);
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let inner = block.inner(area);
+    f.render_widget(ratatui::widgets::Clear, area);
+    f.render_widget(block, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(2)])
+        .split(inner);
+
+    let mut lines: Vec<Line> = Vec::new();
+    let field = |label: &str, value: String| Line::from(vec![Span::raw(format!("{label} ")), Span::styled(value, Style::default().fg(Color::Cyan))]);
+    match flow.step {
+        Step::Location => {
+            lines.push(Line::from(app.text.get("onboarding.location_help")));
+            lines.push(Line::from(""));
+            lines.push(field(app.text.get("onboarding.file"), flow.location.as_str().to_string()));
+        }
+        Step::Exercises => {
+            lines.push(Line::from(app.text.get("onboarding.exercises_help")));
+            lines.push(Line::from(""));
+            // Keep the highlighted row in view
+            let rows = chunks[0].height.saturating_sub(2) as usize;
+            let skip = (flow.selected + 1).saturating_sub(rows);
+            for (i, exercise) in app.exercises.iter().enumerate().skip(skip).take(rows) {
+                let mark = if exercise.archived { "[ ]" } else { "[x]" };
+                let mut style = Style::default().fg(app.exercise_color(&exercise.name));
+                if i == flow.selected {
+                    style = style.add_modifier(Modifier::BOLD | Modifier::REVERSED);
+                }
+                lines.push(Line::from(Span::styled(format!("{mark} {}", exercise.display_name()), style)));
+            }
+        }
+        Step::Custom => {
+            lines.push(Line::from(app.text.get("onboarding.custom_help")));
+            lines.push(Line::from(""));
+            lines.push(field(app.text.get("onboarding.name"), flow.custom.as_str().to_string()));
+        }
+        Step::Goal => {
+            lines.push(Line::from(app.text.get("onboarding.goal_help")));
+            lines.push(Line::from(""));
+            let exercises = app.picker_exercises();
+            match exercises.get(flow.goal_exercise) {
+                Some(exercise) => {
+                    lines.push(field(app.text.get("common.exercise"), format!("◀ {} ▶", exercise.display_name())));
+                    let unit = units::format_amount(0, &exercise.unit, app.config.units.distance);
+                    let unit = unit.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.' || c == ':');
+                    lines.push(field(app.text.get("onboarding.target"), format!("{}{unit}", flow.goal.as_str())));
+                }
+                None => lines.push(Line::from(app.text.get("onboarding.no_exercises"))),
+            }
+        }
+    }
+    if let Some(error) = &flow.error {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(error.clone(), Style::default().fg(Color::Red))));
+    }
+    f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), chunks[0]);
+
+    let keys = match flow.step {
+        Step::Exercises => "onboarding.exercises_keys",
+        Step::Goal => "onboarding.goal_keys",
+        _ => "onboarding.keys",
+    };
+    f.render_widget(Paragraph::new(app.text.get(keys)).style(Style::default().fg(Color::DarkGray)), chunks[1]);
+}
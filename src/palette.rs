@@ -0,0 +1,431 @@
+//! The command palette, opened with ':' on the main screen or Ctrl+P
+//! anywhere: type part of an action's name, pick it with Up/Down and run
+//! it with Enter. `ACTIONS` is also the main screen's keymap, so anything
+//! added there shows up in both. Actions that need a date ask for it in a
+//! prompt of their own before running.
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::exercises::{display_name, render_dialog};
+use crate::export::ExportFormat;
+use crate::i18n::Messages;
+use crate::input::{DateInput, TextInput};
+use crate::setup_file;
+use crate::{centered_rect, App, Screen};
+
+/// Matches listed at once; the rest are reached by typing more.
+const MAX_SHOWN: usize = 12;
+
+pub enum Run {
+    /// Runs straight away; true quits the app.
+    Now(fn(&mut App) -> Result<bool>),
+    /// Asks for a date first.
+    Date(fn(&mut App, NaiveDate) -> Result<()>),
+}
+
+/// How an action is reached without the palette.
+pub enum Binding {
+    /// This key on the main screen.
+    Main(char),
+    /// A sequence of keys through other screens, shown as a reminder only.
+    Via(&'static str),
+    None,
+}
+
+pub struct Action {
+    /// i18n key of its name.
+    pub name: &'static str,
+    pub binding: Binding,
+    pub run: Run,
+}
+
+pub const ACTIONS: &[Action] = &[
+    Action {
+        name: "action.add",
+        binding: Binding::Main('a'),
+        run: Run::Now(|app| {
+            app.open_add_workout();
+            Ok(false)
+        }),
+    },
+    Action {
+        name: "action.history",
+        binding: Binding::Main('h'),
+        run: Run::Now(|app| {
+            app.open_history();
+            Ok(false)
+        }),
+    },
+    Action {
+        name: "action.goto_date",
+        binding: Binding::None,
+        run: Run::Date(|app, date| app.open_history_day(date, Screen::Main)),
+    },
+    Action {
+        name: "action.search",
+        binding: Binding::Via("h /"),
+        run: Run::Now(|app| {
+            app.open_search();
+            Ok(false)
+        }),
+    },
+    Action {
+        name: "action.measurements",
+        binding: Binding::Main('m'),
+        run: Run::Now(|app| {
+            app.open_measurements();
+            Ok(false)
+        }),
+    },
+    Action {
+        name: "action.stats",
+        binding: Binding::Main('t'),
+        run: Run::Now(|app| {
+            app.open_stats();
+            Ok(false)
+        }),
+    },
+    Action {
+        name: "action.report",
+        binding: Binding::Via("t r"),
+        run: Run::Now(|app| {
+            app.open_report_screen();
+            Ok(false)
+        }),
+    },
+    Action {
+        name: "action.goals",
+        binding: Binding::Main('g'),
+        run: Run::Now(|app| app.open_goals().map(|_| false)),
+    },
+    Action {
+        name: "action.exercises",
+        binding: Binding::Main('e'),
+        run: Run::Now(|app| {
+            app.open_exercises();
+            Ok(false)
+        }),
+    },
+    Action {
+        name: "action.data",
+        binding: Binding::Main('d'),
+        run: Run::Now(|app| {
+            app.open_data();
+            Ok(false)
+        }),
+    },
+    Action {
+        name: "action.export",
+        binding: Binding::Via("d x"),
+        run: Run::Now(|app| {
+            app.open_export();
+            Ok(false)
+        }),
+    },
+    Action {
+        name: "action.export_csv",
+        binding: Binding::Via("d x"),
+        run: Run::Now(|app| {
+            app.export_dialog.format = ExportFormat::ALL.iter().position(|f| *f == ExportFormat::Csv).unwrap_or(0);
+            app.open_export();
+            Ok(false)
+        }),
+    },
+    Action {
+        name: "action.export_config",
+        binding: Binding::Via("d e"),
+        run: Run::Now(|app| {
+            app.open_data();
+            app.data_screen.setup_export = Some(TextInput::with_text(&setup_file::default_path().to_string_lossy()));
+            Ok(false)
+        }),
+    },
+    Action {
+        name: "action.import_config",
+        binding: Binding::Via("d i"),
+        run: Run::Now(|app| {
+            app.open_data();
+            app.data_screen.setup_import = Some(TextInput::default());
+            Ok(false)
+        }),
+    },
+    Action {
+        name: "action.health",
+        binding: Binding::Via("d c"),
+        run: Run::Now(|app| app.open_health_screen().map(|_| false)),
+    },
+    Action {
+        name: "action.trash",
+        binding: Binding::Via("d t"),
+        run: Run::Now(|app| {
+            app.open_trash();
+            Ok(false)
+        }),
+    },
+    Action {
+        name: "action.profiles",
+        binding: Binding::Main('p'),
+        run: Run::Now(|app| app.open_profiles().map(|_| false)),
+    },
+    Action {
+        name: "action.settings",
+        binding: Binding::Main('o'),
+        run: Run::Now(|app| {
+            app.open_settings();
+            Ok(false)
+        }),
+    },
+    Action {
+        name: "action.about",
+        binding: Binding::Main('i'),
+        run: Run::Now(|app| {
+            app.open_about(false);
+            Ok(false)
+        }),
+    },
+    Action {
+        name: "action.stopwatch",
+        binding: Binding::Main('s'),
+        run: Run::Now(|app| {
+            app.open_stopwatch();
+            Ok(false)
+        }),
+    },
+    Action {
+        name: "action.rest_timer",
+        binding: Binding::None,
+        run: Run::Now(|app| {
+            app.start_picked_rest_timer();
+            Ok(false)
+        }),
+    },
+    Action {
+        name: "action.quick_stats",
+        binding: Binding::Main('S'),
+        run: Run::Now(|app| app.open_quick_stats().map(|_| false)),
+    },
+    Action {
+        name: "action.week",
+        binding: Binding::Main('w'),
+        run: Run::Now(|app| {
+            app.open_week();
+            Ok(false)
+        }),
+    },
+    Action {
+        name: "action.calendar",
+        binding: Binding::Main('l'),
+        run: Run::Now(|app| {
+            app.open_month();
+            Ok(false)
+        }),
+    },
+    Action {
+        name: "action.copy_today",
+        binding: Binding::Main('c'),
+        run: Run::Now(|app| app.copy_today().map(|_| false)),
+    },
+    Action {
+        name: "action.clear_plan",
+        binding: Binding::Main('x'),
+        run: Run::Now(|app| app.clear_day_plan().map(|_| false)),
+    },
+    Action {
+        name: "action.quit",
+        binding: Binding::Main('q'),
+        run: Run::Now(|app| {
+            app.save_place()?;
+            Ok(true)
+        }),
+    },
+];
+
+/// The action run by `key` on the main screen.
+pub fn bound_to(key: char) -> Option<&'static Action> {
+    ACTIONS.iter().find(|action| matches!(action.binding, Binding::Main(c) if c == key))
+}
+
+/// How well `text` matches a fuzzy `query`, or None when it doesn't: the
+/// query's letters have to appear in order, and runs of them and letters
+/// starting a word count for more. Spaces in the query are ignored.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut from = 0;
+    let mut previous: Option<usize> = None;
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let at = (from..text.len()).find(|&i| text[i] == wanted)?;
+        score += 1;
+        if at > 0 && previous == Some(at - 1) {
+            score += 3;
+        }
+        if at == 0 || !text[at - 1].is_alphanumeric() {
+            score += 2;
+        }
+        previous = Some(at);
+        from = at + 1;
+    }
+    Some(score)
+}
+
+#[derive(Default)]
+pub struct PaletteState {
+    pub query: TextInput,
+    /// Highlighted row of the matches.
+    pub selected: usize,
+    /// The action waiting for its date, and the date being typed.
+    pub prompt: Option<(&'static Action, DateInput)>,
+}
+
+impl PaletteState {
+    /// Actions matching the query, best first; ties keep the registry's order.
+    pub fn matches(&self, text: &Messages) -> Vec<&'static Action> {
+        let mut scored: Vec<(i32, &'static Action)> = ACTIONS
+            .iter()
+            .filter_map(|action| fuzzy_score(self.query.as_str(), text.get(action.name)).map(|score| (score, action)))
+            .collect();
+        scored.sort_by_key(|(score, _)| -score);
+        scored.into_iter().map(|(_, action)| action).collect()
+    }
+}
+
+impl Binding {
+    fn reminder(&self) -> String {
+        match self {
+            Binding::Main(key) => key.to_string(),
+            Binding::Via(keys) => keys.to_string(),
+            Binding::None => String::new(),
+        }
+    }
+}
+
+impl App {
+    pub(crate) fn open_palette(&mut self) {
+        if self.onboarding.is_some() || self.mood_prompt || !self.journal_replay.is_empty() {
+            return;
+        }
+        self.quick_stats = None;
+        self.palette = Some(PaletteState::default());
+    }
+
+    pub(crate) fn run_action(&mut self, action: &'static Action) -> Result<bool> {
+        match action.run {
+            Run::Now(run) => run(self),
+            Run::Date(_) => {
+                self.palette = Some(PaletteState { prompt: Some((action, DateInput::default())), ..PaletteState::default() });
+                Ok(false)
+            }
+        }
+    }
+
+    pub(crate) fn handle_palette_input(&mut self, key: KeyCode) -> Result<bool> {
+        let Some(mut palette) = self.palette.take() else {
+            return Ok(false);
+        };
+        if let Some((action, input)) = &mut palette.prompt {
+            match key {
+                KeyCode::Esc => return Ok(false),
+                KeyCode::Enter => {
+                    if let (Run::Date(run), Some(date)) = (&action.run, input.value()) {
+                        run(self, date)?;
+                        return Ok(false);
+                    }
+                }
+                _ => {
+                    input.handle_key(key);
+                }
+            }
+            self.palette = Some(palette);
+            return Ok(false);
+        }
+        match key {
+            KeyCode::Esc => return Ok(false),
+            KeyCode::Enter => {
+                if let Some(action) = palette.matches(&self.text).get(palette.selected) {
+                    return self.run_action(action);
+                }
+            }
+            KeyCode::Up => palette.selected = palette.selected.saturating_sub(1),
+            KeyCode::Down if palette.selected + 1 < palette.matches(&self.text).len().min(MAX_SHOWN) => {
+                palette.selected += 1
+            }
+            _ => {
+                if palette.query.handle_key(key) {
+                    palette.selected = 0;
+                }
+            }
+        }
+        self.palette = Some(palette);
+        Ok(false)
+    }
+
+    /// Starts the rest timer for the exercise picked on the add screen.
+    fn start_picked_rest_timer(&mut self) {
+        let Some(exercise) = self.picker_exercises().get(self.selected_exercise).map(|e| e.name.clone()) else {
+            return;
+        };
+        if self.config.timer.rest_seconds == 0 {
+            self.show_toast(self.text.get("palette.rest_off").to_string());
+            return;
+        }
+        self.start_rest_timer(&exercise);
+        self.show_toast(self.text.format("palette.rest_started", &[("exercise", &display_name(&exercise))]));
+    }
+}
+
+pub fn render_palette(f: &mut Frame, app: &App) {
+    let Some(palette) = &app.palette else {
+        return;
+    };
+    if let Some((action, input)) = &palette.prompt {
+        render_dialog(f, app.text.get(action.name), &app.text.format("palette.date", &[("input", &input.as_str())]));
+        return;
+    }
+    let matches = palette.matches(&app.text);
+    let shown = matches.len().min(MAX_SHOWN);
+    let area = centered_rect(56, shown.max(1) as u16 + 4, f.size());
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(app.text.get("palette.title"))
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Length(1), Constraint::Min(0)])
+        .split(inner);
+    f.render_widget(Paragraph::new(format!("> {}▏", palette.query.as_str())), chunks[0]);
+
+    if matches.is_empty() {
+        let none = Paragraph::new(app.text.get("palette.no_match")).style(Style::default().fg(Color::DarkGray));
+        f.render_widget(none, chunks[2]);
+        return;
+    }
+    let width = chunks[2].width as usize;
+    let items: Vec<ListItem> = matches
+        .iter()
+        .take(shown)
+        .map(|action| {
+            let name = app.text.get(action.name);
+            let reminder = action.binding.reminder();
+            let gap = width.saturating_sub(name.chars().count() + reminder.chars().count() + 1).max(1);
+            ListItem::new(Line::from(vec![
+                Span::raw(format!(" {name}{}", " ".repeat(gap))),
+                Span::styled(reminder, Style::default().fg(Color::DarkGray)),
+            ]))
+        })
+        .collect();
+    let list = List::new(items).highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected(Some(palette.selected));
+    f.render_stateful_widget(list, chunks[2], &mut state);
+}
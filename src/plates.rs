@@ -0,0 +1,246 @@
+//! Plate calculator on the add screen: which plates go on each side of the
+//! bar for a target weight, from the bar and plate sizes in `[plates]`.
+
+use anyhow::Result;
+use crossterm::event::KeyCode;
+use ratatui::Frame;
+
+use crate::exercises::render_dialog;
+use crate::input::NumericInput;
+use crate::{AddField, App};
+
+/// Weights are matched in thousandths so sizes like 1.25 add up exactly.
+const SCALE: f64 = 1000.0;
+/// Largest search per side, in multiples of the smallest plate step, which
+/// keeps absurd targets from taking long.
+const MAX_STEPS: i64 = 100_000;
+
+/// Plates for one side of the bar, heaviest first, and the weight they make
+/// with the bar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Loading {
+    pub total: f64,
+    pub per_side: Vec<f64>,
+    /// Whether `total` is the target itself rather than the nearest weight
+    /// the plates can make.
+    pub exact: bool,
+}
+
+fn scaled(weight: f64) -> i64 {
+    (weight * SCALE).round() as i64
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// Works out the fewest plates per side that load the bar to `target`, or
+/// to the nearest weight they can make (the lighter one on a tie). A target
+/// at or below the bar gets the empty bar. `None` for weights that aren't
+/// positive numbers.
+pub fn load_bar(target: f64, bar: f64, sizes: &[f64]) -> Option<Loading> {
+    if !target.is_finite() || target <= 0.0 || !bar.is_finite() || bar < 0.0 {
+        return None;
+    }
+    let mut sizes: Vec<i64> = sizes.iter().filter(|s| s.is_finite()).map(|s| scaled(*s)).filter(|s| *s > 0).collect();
+    sizes.sort_unstable_by(|a, b| b.cmp(a));
+    sizes.dedup();
+    // Both sides together, since plates go on in pairs
+    let wanted = scaled(target) - scaled(bar);
+    let empty = Loading {
+        total: bar,
+        per_side: Vec::new(),
+        exact: wanted == 0,
+    };
+    if wanted <= 0 || sizes.is_empty() {
+        return Some(empty);
+    }
+
+    // Search in steps of the largest weight every size is a multiple of
+    let step = sizes.iter().fold(0, |g, s| gcd(g, *s));
+    let steps: Vec<usize> = sizes.iter().map(|s| (s / step) as usize).collect();
+    let limit = (wanted / 2 + sizes[0]) / step;
+    if limit > MAX_STEPS {
+        return None;
+    }
+    let limit = limit as usize;
+    // Fewest plates making each per-side weight
+    let mut fewest: Vec<Option<usize>> = vec![None; limit + 1];
+    fewest[0] = Some(0);
+    for side in 1..=limit {
+        fewest[side] = steps
+            .iter()
+            .filter(|plate| **plate <= side)
+            .filter_map(|plate| fewest[side - plate])
+            .min()
+            .map(|count| count + 1);
+    }
+    let distance = |side: usize| (2 * side as i64 * step - wanted).abs();
+    let best = (0..=limit)
+        .filter(|side| fewest[*side].is_some())
+        .min_by_key(|side| (distance(*side), *side))
+        .unwrap_or(0);
+
+    let mut per_side = Vec::new();
+    let mut side = best;
+    while side > 0 {
+        let Some(plate) = steps
+            .iter()
+            .find(|plate| **plate <= side && fewest[side - **plate].zip(fewest[side]).is_some_and(|(a, b)| a + 1 == b))
+        else {
+            break;
+        };
+        per_side.push((*plate as i64 * step) as f64 / SCALE);
+        side -= plate;
+    }
+    Some(Loading {
+        total: bar + 2.0 * (best as i64 * step) as f64 / SCALE,
+        per_side,
+        exact: distance(best) == 0,
+    })
+}
+
+/// A weight without trailing zeros, like "2.5" or "20".
+fn number(weight: f64) -> String {
+    let text = format!("{weight:.3}");
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+impl App {
+    /// Opens the calculator for the weight already typed in, if any.
+    pub(crate) fn open_plates(&mut self) {
+        let mut input = NumericInput::decimal();
+        input.set(self.input_weight.as_str());
+        self.plates = Some(input);
+    }
+
+    pub(crate) fn handle_plates_input(&mut self, key: KeyCode) -> Result<bool> {
+        let Some(input) = &mut self.plates else {
+            return Ok(false);
+        };
+        match key {
+            KeyCode::Esc => self.plates = None,
+            KeyCode::Enter => {
+                let plates = &self.config.plates;
+                if let Some(loading) = input.positive_f64().and_then(|target| load_bar(target, plates.bar, &plates.sizes)) {
+                    self.input_weight.set(number(loading.total));
+                    self.add_field = AddField::Weight;
+                    self.plates = None;
+                }
+            }
+            _ => {
+                input.handle_key(key);
+            }
+        }
+        Ok(false)
+    }
+}
+
+pub fn render_plates(f: &mut Frame, app: &App) {
+    let Some(input) = &app.plates else {
+        return;
+    };
+    let plates = &app.config.plates;
+    let unit = app.config.units.weight.label();
+    let mut lines = vec![
+        app.text.format("plates.target", &[("input", &input.as_str()), ("unit", &unit), ("bar", &number(plates.bar))]),
+        String::new(),
+    ];
+    match input.positive_f64().and_then(|target| load_bar(target, plates.bar, &plates.sizes).map(|l| (target, l))) {
+        Some((target, loading)) => {
+            let per_side = if loading.per_side.is_empty() {
+                app.text.get("plates.bar_only").to_string()
+            } else {
+                loading.per_side.iter().map(|w| number(*w)).collect::<Vec<_>>().join(" + ")
+            };
+            lines.push(app.text.format("plates.per_side", &[("plates", &per_side)]));
+            let total = number(loading.total);
+            lines.push(if loading.exact {
+                app.text.format("plates.exact", &[("total", &total), ("unit", &unit)])
+            } else if target < plates.bar {
+                app.text.format("plates.below_bar", &[("total", &total), ("unit", &unit)])
+            } else if plates.sizes.is_empty() {
+                app.text.get("plates.no_sizes").to_string()
+            } else {
+                app.text.format("plates.nearest", &[("total", &total), ("unit", &unit)])
+            });
+        }
+        None => lines.push(app.text.get("plates.enter_target").to_string()),
+    }
+    lines.push(app.text.get("plates.keys").to_string());
+    render_dialog(f, app.text.get("plates.title"), &lines.join("\n"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KG: [f64; 7] = [25.0, 20.0, 15.0, 10.0, 5.0, 2.5, 1.25];
+
+    fn loading(total: f64, per_side: &[f64], exact: bool) -> Option<Loading> {
+        Some(Loading {
+            total,
+            per_side: per_side.to_vec(),
+            exact,
+        })
+    }
+
+    #[test]
+    fn exact_targets_use_the_fewest_plates() {
+        assert_eq!(load_bar(72.5, 20.0, &KG), loading(72.5, &[25.0, 1.25], true));
+        assert_eq!(load_bar(100.0, 20.0, &KG), loading(100.0, &[25.0, 15.0], true));
+        assert_eq!(load_bar(142.5, 20.0, &KG), loading(142.5, &[25.0, 25.0, 10.0, 1.25], true));
+    }
+
+    #[test]
+    fn finds_loadings_a_greedy_pick_would_miss() {
+        // Taking 15 first leaves 5, which no plate makes
+        assert_eq!(load_bar(60.0, 20.0, &[15.0, 10.0]), loading(60.0, &[10.0, 10.0], true));
+    }
+
+    #[test]
+    fn odd_targets_get_the_nearest_loadable_weight() {
+        assert_eq!(load_bar(73.0, 20.0, &KG), loading(72.5, &[25.0, 1.25], false));
+        assert_eq!(load_bar(74.5, 20.0, &KG), loading(75.0, &[25.0, 2.5], false));
+        // Halfway between two loadings goes to the lighter one
+        assert_eq!(load_bar(25.0, 20.0, &[5.0]), loading(20.0, &[], false));
+        assert_eq!(load_bar(37.0, 20.0, &[5.0]), loading(40.0, &[5.0, 5.0], false));
+    }
+
+    #[test]
+    fn targets_at_or_below_the_bar_get_the_empty_bar() {
+        assert_eq!(load_bar(20.0, 20.0, &KG), loading(20.0, &[], true));
+        assert_eq!(load_bar(15.0, 20.0, &KG), loading(20.0, &[], false));
+    }
+
+    #[test]
+    fn no_plates_configured_leaves_the_bar() {
+        assert_eq!(load_bar(60.0, 20.0, &[]), loading(20.0, &[], false));
+        // Sizes that aren't positive numbers don't count
+        assert_eq!(load_bar(60.0, 20.0, &[0.0, -5.0, f64::NAN]), loading(20.0, &[], false));
+    }
+
+    #[test]
+    fn pounds_and_repeated_sizes() {
+        let lbs = [45.0, 35.0, 25.0, 10.0, 5.0, 2.5, 45.0];
+        assert_eq!(load_bar(225.0, 45.0, &lbs), loading(225.0, &[45.0, 45.0], true));
+        assert_eq!(load_bar(190.0, 45.0, &lbs), loading(190.0, &[45.0, 25.0, 2.5], true));
+    }
+
+    #[test]
+    fn rejects_targets_that_are_not_weights() {
+        assert_eq!(load_bar(0.0, 20.0, &KG), None);
+        assert_eq!(load_bar(-10.0, 20.0, &KG), None);
+        assert_eq!(load_bar(f64::NAN, 20.0, &KG), None);
+        assert_eq!(load_bar(60.0, f64::INFINITY, &KG), None);
+        // Far beyond anything a bar holds
+        assert_eq!(load_bar(1e9, 20.0, &KG), None);
+    }
+
+    #[test]
+    fn weights_print_without_trailing_zeros() {
+        assert_eq!(number(20.0), "20");
+        assert_eq!(number(2.5), "2.5");
+        assert_eq!(number(1.25), "1.25");
+    }
+}
@@ -0,0 +1,370 @@
+//! Separate data for everyone sharing the machine. The configured data file
+//! is the default profile; every other profile is a file of its own in a
+//! `profiles` folder next to it, so stats, goals and streaks never mix, and
+//! backups and the journal are kept per profile too.
+
+use anyhow::{bail, ensure, Context, Result};
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::config::JournalConfig;
+use crate::encryption;
+use crate::exercises::render_dialog;
+use crate::i18n::Messages;
+use crate::input::TextInput;
+use crate::instance_lock::{self, Acquired};
+use crate::store::Backend;
+use crate::{before_ui, centered_rect, App, Database};
+
+/// The profile living in the configured data file.
+pub const DEFAULT_PROFILE: &str = "default";
+const PROFILE_DIR: &str = "profiles";
+
+/// Where the profiles' data files are.
+#[derive(Debug, Clone)]
+pub struct Profiles {
+    /// The default profile's file; the others are kept next to it.
+    pub base: PathBuf,
+    pub backend: Backend,
+}
+
+impl Profiles {
+    fn dir(&self) -> PathBuf {
+        self.base.parent().unwrap_or(Path::new("")).join(PROFILE_DIR)
+    }
+
+    fn extension(&self) -> &'static str {
+        match self.backend {
+            Backend::Sqlite => "db",
+            Backend::Jsonl => "jsonl",
+        }
+    }
+
+    pub fn path(&self, name: &str) -> PathBuf {
+        if name == DEFAULT_PROFILE {
+            return self.base.clone();
+        }
+        self.dir().join(format!("{name}.{}", self.extension()))
+    }
+
+    /// Where a profile's journal goes. Profiles share a folder, so each
+    /// but the default gets a journal named after it.
+    pub fn journal_path(&self, name: &str, db: &Database, config: &JournalConfig) -> PathBuf {
+        if name == DEFAULT_PROFILE {
+            return db.journal_path(config);
+        }
+        self.dir().join(format!("{name}.journal.csv"))
+    }
+
+    /// Every profile: the default first, then the rest by name.
+    pub fn list(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        let entries = match fs::read_dir(self.dir()) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![DEFAULT_PROFILE.to_string()]),
+            Err(e) => return Err(e).with_context(|| format!("failed to read {}", self.dir().display())),
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(self.extension()) {
+                continue;
+            }
+            if let Some(name) = path.file_stem().and_then(|s| s.to_str())
+                && check_name(name).is_ok()
+            {
+                names.push(name.to_string());
+            }
+        }
+        names.sort_by_key(|name| name.to_lowercase());
+        names.insert(0, DEFAULT_PROFILE.to_string());
+        Ok(names)
+    }
+
+    pub fn create(&self, name: &str) -> Result<()> {
+        check_name(name)?;
+        let path = self.path(name);
+        ensure!(!path.exists(), "there's already a profile named {name}");
+        fs::create_dir_all(self.dir()).with_context(|| format!("failed to create {}", self.dir().display()))?;
+        let db = Database::open(self.backend, &path)?;
+        if self.backend == Backend::Jsonl {
+            // Nothing changed yet, so saving wouldn't write the file
+            fs::write(&path, db.to_jsonl()?).with_context(|| format!("failed to write {}", path.display()))?;
+        }
+        Ok(())
+    }
+
+    pub fn rename(&self, from: &str, to: &str) -> Result<()> {
+        ensure!(from != DEFAULT_PROFILE, "the default profile can't be renamed");
+        check_name(to)?;
+        let target = self.path(to);
+        ensure!(!target.exists(), "there's already a profile named {to}");
+        fs::rename(self.path(from), &target).with_context(|| format!("failed to rename {from}"))?;
+        let journal = self.dir().join(format!("{from}.journal.csv"));
+        if journal.exists() {
+            fs::rename(&journal, self.dir().join(format!("{to}.journal.csv")))?;
+        }
+        Ok(())
+    }
+
+    /// Removes a profile's data file. Its backups and journal are left alone.
+    pub fn delete(&self, name: &str) -> Result<()> {
+        ensure!(name != DEFAULT_PROFILE, "the default profile can't be deleted");
+        let path = self.path(name);
+        fs::remove_file(&path).with_context(|| format!("failed to remove {}", path.display()))
+    }
+}
+
+/// Profile names become file names, so they're kept to letters, digits,
+/// `-` and `_`.
+fn check_name(name: &str) -> Result<()> {
+    ensure!(!name.is_empty(), "the name can't be empty");
+    ensure!(
+        name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_'),
+        "profile names can only use letters, digits, '-' and '_'"
+    );
+    ensure!(name != DEFAULT_PROFILE, "\"{DEFAULT_PROFILE}\" is the profile in the main data file");
+    Ok(())
+}
+
+/// Asks whose data to open, on a screen shown before the main UI. Esc quits.
+pub fn prompt_profile(names: &[String], text: &Messages) -> Result<String> {
+    before_ui(|terminal| {
+        let mut selected = 0;
+        loop {
+            terminal.draw(|f| render_picker(f, names, selected, text))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => bail!("no profile picked"),
+                KeyCode::Up => selected = selected.saturating_sub(1),
+                KeyCode::Down if selected + 1 < names.len() => selected += 1,
+                KeyCode::Enter => return Ok(names[selected].clone()),
+                _ => {}
+            }
+        }
+    })
+}
+
+fn render_picker(f: &mut Frame, names: &[String], selected: usize, text: &Messages) {
+    let area = centered_rect(40, names.len() as u16 + 4, f.size());
+    let items: Vec<ListItem> = names.iter().map(|name| ListItem::new(name.as_str())).collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(text.get("profiles.pick_title")))
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED));
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(area);
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, chunks[0], &mut ListState::default().with_selected(Some(selected)));
+    let hint = Paragraph::new(text.get("profiles.pick_hint")).style(Style::default().fg(Color::DarkGray));
+    f.render_widget(hint, chunks[1]);
+}
+
+/// A profile name being typed on the profiles screen.
+#[derive(Debug)]
+pub enum NameEdit {
+    New(TextInput),
+    /// Renaming the named profile.
+    Rename(String, TextInput),
+}
+
+#[derive(Debug)]
+pub struct ProfilesState {
+    pub profiles: Profiles,
+    /// The profile whose data is open.
+    pub active: String,
+    pub selected: usize,
+    pub editing: Option<NameEdit>,
+    pub confirm_delete: bool,
+}
+
+impl ProfilesState {
+    pub fn new(profiles: Profiles, active: &str) -> Self {
+        Self {
+            profiles,
+            active: active.to_string(),
+            selected: 0,
+            editing: None,
+            confirm_delete: false,
+        }
+    }
+}
+
+impl App {
+    pub(crate) fn handle_profiles_input(&mut self, key: KeyCode) -> Result<bool> {
+        let names = self.profiles.profiles.list()?;
+        let state = &mut self.profiles;
+        let selected = names.get(state.selected).cloned();
+
+        if state.confirm_delete {
+            state.confirm_delete = false;
+            if key == KeyCode::Char('y')
+                && let Some(name) = selected
+            {
+                self.message = Some(match state.profiles.delete(&name) {
+                    Ok(()) => self.text.format("profiles.deleted", &[("name", &name)]),
+                    Err(e) => self.text.format("profiles.failed", &[("error", &format!("{e:#}"))]),
+                });
+                self.profiles.selected = self.profiles.selected.min(names.len().saturating_sub(2));
+            }
+            return Ok(false);
+        }
+
+        if let Some(edit) = &mut state.editing {
+            let input = match edit {
+                NameEdit::New(input) | NameEdit::Rename(_, input) => input,
+            };
+            match key {
+                KeyCode::Esc => state.editing = None,
+                KeyCode::Enter => {
+                    let name = input.as_str().trim().to_string();
+                    let result = match state.editing.take() {
+                        Some(NameEdit::Rename(from, _)) => state.profiles.rename(&from, &name),
+                        _ => state.profiles.create(&name),
+                    };
+                    self.message = Some(match result {
+                        Ok(()) => {
+                            let names = self.profiles.profiles.list()?;
+                            self.profiles.selected = names.iter().position(|n| *n == name).unwrap_or(0);
+                            self.text.format("profiles.saved", &[("name", &name)])
+                        }
+                        Err(e) => self.text.format("profiles.failed", &[("error", &format!("{e:#}"))]),
+                    });
+                }
+                _ => {
+                    input.handle_key(key);
+                }
+            }
+            return Ok(false);
+        }
+
+        self.message = None;
+        match key {
+            KeyCode::Esc => self.screen = crate::Screen::Main,
+            KeyCode::Up => state.selected = state.selected.saturating_sub(1),
+            KeyCode::Down if state.selected + 1 < names.len() => state.selected += 1,
+            KeyCode::Char('n') => state.editing = Some(NameEdit::New(TextInput::default())),
+            KeyCode::Enter if let Some(name) = selected => self.switch_profile(&name)?,
+            KeyCode::Char('c') if let Some(name) = selected => {
+                if name == state.active {
+                    self.message = Some(self.text.get("profiles.compare_self").to_string());
+                } else {
+                    self.open_compare_screen(&name)?;
+                }
+            }
+            KeyCode::Char('r') | KeyCode::Delete if let Some(name) = selected => {
+                if name == state.active {
+                    self.message = Some(self.text.get("profiles.active").to_string());
+                } else if name == DEFAULT_PROFILE {
+                    self.message = Some(self.text.get("profiles.default").to_string());
+                } else if key == KeyCode::Delete {
+                    state.confirm_delete = true;
+                } else {
+                    state.editing = Some(NameEdit::Rename(name.clone(), TextInput::with_text(&name)));
+                }
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    /// Closes the open profile's data and opens `name`'s instead.
+    fn switch_profile(&mut self, name: &str) -> Result<()> {
+        if name == self.profiles.active {
+            return Ok(());
+        }
+        let profiles = self.profiles.profiles.clone();
+        let path = profiles.path(name);
+        if profiles.backend == Backend::Sqlite && encryption::is_encrypted(&path) {
+            self.message = Some(self.text.format("profiles.encrypted", &[("name", &name)]));
+            return Ok(());
+        }
+        let lock = match instance_lock::acquire(&path)? {
+            Acquired::Locked(lock) => lock,
+            Acquired::Held(holder) => {
+                self.message = Some(self.text.format("profiles.locked", &[("name", &name), ("pid", &holder.pid), ("host", &holder.host)]));
+                return Ok(());
+            }
+        };
+        self.db.save()?;
+        let mut db = Database::open(profiles.backend, &path)?;
+        if self.config.journal.enabled {
+            let journal = profiles.journal_path(name, &db, &self.config.journal);
+            db.start_journal(journal);
+        }
+        self.db = db;
+        // Taking the new profile's lock lets go of the old one
+        self.instance_lock = Some(lock);
+        self.read_only = false;
+        self.exercises = self.db.get_exercises()?;
+        // Each profile keeps its own settings
+        self.apply_settings()?;
+        self.reset_cached_state();
+        self.mood_prompt = self.should_prompt_mood()?;
+        self.profiles.active = name.to_string();
+        self.message = Some(self.text.format("profiles.switched", &[("name", &name)]));
+        Ok(())
+    }
+}
+
+pub fn render_profiles_screen(f: &mut Frame, area: Rect, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(3)])
+        .split(area);
+
+    let names = app.profiles.profiles.list().unwrap_or_default();
+    let items: Vec<ListItem> = names
+        .iter()
+        .map(|name| {
+            if *name == app.profiles.active {
+                ListItem::new(app.text.format("profiles.open", &[("name", name)])).style(Style::default().fg(Color::Green))
+            } else {
+                ListItem::new(name.as_str())
+            }
+        })
+        .collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("profiles.title")))
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED));
+    f.render_stateful_widget(list, chunks[0], &mut ListState::default().with_selected(Some(app.profiles.selected)));
+
+    let status = Paragraph::new(app.message.as_deref().unwrap_or_default())
+        .style(Style::default().fg(Color::Green))
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("common.status")));
+    f.render_widget(status, chunks[1]);
+
+    match &app.profiles.editing {
+        Some(NameEdit::New(input)) => render_dialog(
+            f,
+            app.text.get("profiles.new_title"),
+            &app.text.format("profiles.name_field", &[("input", &input.as_str())]),
+        ),
+        Some(NameEdit::Rename(from, input)) => render_dialog(
+            f,
+            &app.text.format("profiles.rename_title", &[("name", from)]),
+            &app.text.format("profiles.name_field", &[("input", &input.as_str())]),
+        ),
+        None => {}
+    }
+    if app.profiles.confirm_delete
+        && let Some(name) = names.get(app.profiles.selected)
+    {
+        render_dialog(
+            f,
+            app.text.get("profiles.delete_title"),
+            &app.text.format("profiles.delete_confirm", &[("name", name)]),
+        );
+    }
+}
@@ -0,0 +1,422 @@
+//! Weekly progressive overload plans. An exercise can carry a rule, either
+//! a few more per set or a percentage more total volume each week, and the
+//! prescription for the current week is worked out from the weeks since the
+//! plan started: a week that reaches its target moves the next one up, a
+//! bad miss deloads, and anything else holds.
+
+use anyhow::Result;
+use chrono::{Duration, Local, NaiveDate};
+use rusqlite::params;
+use std::collections::BTreeMap;
+
+use crate::config::ProgressionConfig;
+use crate::dates::{self, week_start, WeekStart};
+use crate::exercises::Exercise;
+use crate::{App, Database};
+
+/// Weeks before the plan starts that its first target is based on.
+const BASELINE_WEEKS: i64 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rule {
+    /// This many more per set every week.
+    Reps(u32),
+    /// This many percent more in total every week.
+    Volume(u32),
+}
+
+impl Rule {
+    /// Reads "+2" (per set) or "+5%" (weekly volume); the plus is optional.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let text = text.strip_prefix('+').unwrap_or(text).trim();
+        let rule = match text.strip_suffix('%') {
+            Some(percent) => Rule::Volume(percent.trim().parse().ok()?),
+            None => Rule::Reps(text.parse().ok()?),
+        };
+        match rule {
+            Rule::Reps(0) | Rule::Volume(0) => None,
+            rule => Some(rule),
+        }
+    }
+
+    /// The rule as typed and stored, the inverse of `parse`.
+    pub fn text(self) -> String {
+        match self {
+            Rule::Reps(step) => format!("+{step}"),
+            Rule::Volume(percent) => format!("+{percent}%"),
+        }
+    }
+
+    /// The week's result this rule is judged on: the average set, or the
+    /// total. `None` for a week without sets.
+    fn measure(self, log: &WeekLog) -> Option<f64> {
+        if log.sets == 0 {
+            return None;
+        }
+        Some(match self {
+            Rule::Reps(_) => log.total as f64 / log.sets as f64,
+            Rule::Volume(_) => log.total as f64,
+        })
+    }
+
+    /// The target a week after this one was reached.
+    fn advance(self, target: i64) -> i64 {
+        match self {
+            Rule::Reps(step) => target + step as i64,
+            Rule::Volume(percent) => target + ((target as f64 * percent as f64 / 100.0).round() as i64).max(1),
+        }
+    }
+}
+
+/// A rule with the target of the week it started in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    pub rule: Rule,
+    pub base: i64,
+    /// First day of the week the plan started in.
+    pub start: NaiveDate,
+}
+
+impl Plan {
+    /// A plan from the exercise's columns; any missing or unreadable one
+    /// means there's no plan.
+    pub fn from_columns(rule: Option<String>, base: Option<i64>, start: Option<String>) -> Option<Self> {
+        Some(Self {
+            rule: Rule::parse(&rule?)?,
+            base: base?,
+            start: dates::parse_date(&start?)?,
+        })
+    }
+}
+
+/// Sets logged in one week.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct WeekLog {
+    pub sets: i64,
+    pub total: i64,
+}
+
+/// How the week before the current one went.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    Advanced,
+    Held,
+    Deloaded,
+    /// Nothing was logged, so the target stays.
+    Rested,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Prescription {
+    pub target: i64,
+    /// `None` in the plan's first week.
+    pub last: Option<Outcome>,
+}
+
+/// Per-day set counts and totals added up by the week they fall in.
+pub fn weekly_logs(days: &[(NaiveDate, i64, i64)], week: WeekStart) -> BTreeMap<NaiveDate, WeekLog> {
+    let mut weeks: BTreeMap<NaiveDate, WeekLog> = BTreeMap::new();
+    for (day, sets, total) in days {
+        let log = weeks.entry(week_start(*day, week)).or_default();
+        log.sets += sets;
+        log.total += total;
+    }
+    weeks
+}
+
+/// The rule's measure averaged over the weeks with sets, as a starting
+/// point for a new plan.
+pub fn baseline(rule: Rule, weeks: &BTreeMap<NaiveDate, WeekLog>) -> Option<i64> {
+    let measures: Vec<f64> = weeks.values().filter_map(|log| rule.measure(log)).collect();
+    if measures.is_empty() {
+        return None;
+    }
+    Some(((measures.iter().sum::<f64>() / measures.len() as f64).round() as i64).max(1))
+}
+
+/// Works out the target for `this_week` by replaying every finished week
+/// since the plan started. Reaching the target advances it; falling short by
+/// more than `miss_percent` takes `deload_percent` off (at least one, never
+/// below one); a smaller miss or an empty week holds it.
+pub fn prescribe(plan: &Plan, weeks: &BTreeMap<NaiveDate, WeekLog>, this_week: NaiveDate, config: &ProgressionConfig) -> Prescription {
+    let mut target = plan.base;
+    let mut last = None;
+    let mut week = plan.start;
+    while week < this_week {
+        let outcome = match weeks.get(&week).and_then(|log| plan.rule.measure(log)) {
+            None => Outcome::Rested,
+            Some(measure) if measure >= target as f64 => Outcome::Advanced,
+            Some(measure) if measure < target as f64 * (100 - config.miss_percent.min(100)) as f64 / 100.0 => {
+                if config.deload_percent == 0 {
+                    Outcome::Held
+                } else {
+                    Outcome::Deloaded
+                }
+            }
+            Some(_) => Outcome::Held,
+        };
+        target = match outcome {
+            Outcome::Advanced => plan.rule.advance(target),
+            Outcome::Deloaded => {
+                let cut = ((target as f64 * config.deload_percent as f64 / 100.0).round() as i64).max(1);
+                (target - cut).max(1)
+            }
+            Outcome::Held | Outcome::Rested => target,
+        };
+        last = Some(outcome);
+        week += Duration::weeks(1);
+    }
+    Prescription { target, last }
+}
+
+impl Database {
+    /// Number of sets and total per day for one exercise from `since` onwards.
+    pub fn exercise_day_logs(&self, exercise: &str, since: NaiveDate) -> Result<Vec<(NaiveDate, i64, i64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT day, sets, total FROM daily_totals
+             WHERE exercise_type = ?1 AND day >= ?2",
+        )?;
+        let rows = stmt
+            .query_map(params![exercise, since.format("%Y-%m-%d").to_string()], |row| {
+                Ok((row.get::<_, String>(0)?, row.get(1)?, row.get(2)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(day, sets, total)| Some((dates::parse_date(&day)?, sets, total)))
+            .collect())
+    }
+
+    /// Stores or, with `None`, removes an exercise's plan.
+    pub fn set_exercise_plan(&self, name: &str, plan: Option<&Plan>) -> Result<()> {
+        self.conn.execute(
+            "UPDATE exercises SET progression = ?2, progression_base = ?3, progression_start = ?4 WHERE name = ?1",
+            params![
+                name,
+                plan.map(|p| p.rule.text()),
+                plan.map(|p| p.base),
+                plan.map(|p| p.start.format("%Y-%m-%d").to_string()),
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+impl App {
+    fn this_week(&self) -> NaiveDate {
+        week_start(Local::now().date_naive(), self.config.week_starts_on)
+    }
+
+    /// Starts a plan this week, one step up from the average of the weeks
+    /// before. Returns `false` when there's no recent history to start from.
+    pub(crate) fn start_plan(&mut self, exercise: &str, rule: Rule) -> Result<bool> {
+        let this_week = self.this_week();
+        let days = self.db.exercise_day_logs(exercise, this_week - Duration::weeks(BASELINE_WEEKS))?;
+        let days: Vec<_> = days.into_iter().filter(|(day, _, _)| *day < this_week).collect();
+        let Some(base) = baseline(rule, &weekly_logs(&days, self.config.week_starts_on)) else {
+            return Ok(false);
+        };
+        let plan = Plan {
+            rule,
+            base: rule.advance(base),
+            start: this_week,
+        };
+        self.db.set_exercise_plan(exercise, Some(&plan))?;
+        self.reload_exercises()?;
+        Ok(true)
+    }
+
+    /// This week's target for an exercise with a plan, and the amount that
+    /// counts towards it so far.
+    pub(crate) fn prescription(&self, exercise: &Exercise) -> Result<Option<(Prescription, WeekLog)>> {
+        let Some(plan) = &exercise.plan else {
+            return Ok(None);
+        };
+        let week = self.config.week_starts_on;
+        // The week setting may have changed since the plan started
+        let plan = Plan {
+            start: week_start(plan.start, week),
+            ..plan.clone()
+        };
+        let weeks = weekly_logs(&self.db.exercise_day_logs(&exercise.name, plan.start)?, week);
+        let this_week = self.this_week();
+        let prescription = prescribe(&plan, &weeks, this_week, &self.config.progression);
+        Ok(Some((prescription, weeks.get(&this_week).copied().unwrap_or_default())))
+    }
+
+    /// One line describing this week's prescription, like "14 per set this
+    /// week (12 so far)".
+    pub(crate) fn plan_line(&self, exercise: &Exercise) -> Option<String> {
+        let (prescription, log) = self.prescription(exercise).ok()??;
+        let target = self.format_count(&exercise.name, prescription.target);
+        let line = match exercise.plan.as_ref()?.rule {
+            Rule::Reps(_) if log.sets > 0 => {
+                let average = self.format_count(&exercise.name, (log.total as f64 / log.sets as f64).round() as i64);
+                self.text.format("progression.per_set_so_far", &[("target", &target), ("average", &average)])
+            }
+            Rule::Reps(_) => self.text.format("progression.per_set", &[("target", &target)]),
+            Rule::Volume(_) => {
+                let done = self.format_count(&exercise.name, log.total);
+                self.text.format("progression.volume", &[("target", &target), ("done", &done)])
+            }
+        };
+        Some(match prescription.last {
+            Some(outcome) => {
+                let key = match outcome {
+                    Outcome::Advanced => "progression.advanced",
+                    Outcome::Held => "progression.held",
+                    Outcome::Deloaded => "progression.deloaded",
+                    Outcome::Rested => "progression.rested",
+                };
+                format!("{line} · {}", self.text.get(key))
+            }
+            None => line,
+        })
+    }
+
+    /// The rule in words, for the exercises screen.
+    pub(crate) fn rule_label(&self, rule: Rule) -> String {
+        match rule {
+            Rule::Reps(step) => self.text.format("progression.rule_reps", &[("step", &step)]),
+            Rule::Volume(percent) => self.text.format("progression.rule_volume", &[("percent", &percent)]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    /// Weeks starting Monday 2024-05-06, one `(sets, total)` per week.
+    fn weeks(logs: &[(i64, i64)]) -> BTreeMap<NaiveDate, WeekLog> {
+        logs.iter()
+            .enumerate()
+            .map(|(i, (sets, total))| (date("2024-05-06") + Duration::weeks(i as i64), WeekLog { sets: *sets, total: *total }))
+            .collect()
+    }
+
+    fn plan(rule: Rule, base: i64) -> Plan {
+        Plan {
+            rule,
+            base,
+            start: date("2024-05-06"),
+        }
+    }
+
+    /// Target in the week after the given ones.
+    fn target_after(plan: &Plan, logs: &[(i64, i64)], config: &ProgressionConfig) -> Prescription {
+        prescribe(plan, &weeks(logs), date("2024-05-06") + Duration::weeks(logs.len() as i64), config)
+    }
+
+    #[test]
+    fn rules_parse_and_print() {
+        assert_eq!(Rule::parse("+2"), Some(Rule::Reps(2)));
+        assert_eq!(Rule::parse(" 3 "), Some(Rule::Reps(3)));
+        assert_eq!(Rule::parse("+5%"), Some(Rule::Volume(5)));
+        assert_eq!(Rule::parse("+ 5 %"), Some(Rule::Volume(5)));
+        assert_eq!(Rule::parse("+0"), None);
+        assert_eq!(Rule::parse("-2"), None);
+        assert_eq!(Rule::parse("two"), None);
+        assert_eq!(Rule::parse(""), None);
+        for rule in [Rule::Reps(2), Rule::Volume(5)] {
+            assert_eq!(Rule::parse(&rule.text()), Some(rule));
+        }
+    }
+
+    #[test]
+    fn first_week_is_the_base() {
+        let prescription = target_after(&plan(Rule::Reps(2), 10), &[], &ProgressionConfig::default());
+        assert_eq!(prescription, Prescription { target: 10, last: None });
+    }
+
+    #[test]
+    fn rep_targets_advance_and_hold_across_weeks() {
+        let config = ProgressionConfig::default();
+        let plan = plan(Rule::Reps(2), 10);
+        // Averages of 10, 12, 13 (short of 14 but within 20%), then 14
+        let logs = [(3, 30), (3, 36), (3, 39), (4, 56)];
+        let targets: Vec<i64> = (0..=logs.len()).map(|n| target_after(&plan, &logs[..n], &config).target).collect();
+        assert_eq!(targets, [10, 12, 14, 14, 16]);
+        assert_eq!(target_after(&plan, &logs[..3], &config).last, Some(Outcome::Held));
+        assert_eq!(target_after(&plan, &logs, &config).last, Some(Outcome::Advanced));
+    }
+
+    #[test]
+    fn a_bad_miss_deloads_and_the_plan_climbs_back() {
+        let config = ProgressionConfig::default();
+        let plan = plan(Rule::Reps(2), 20);
+        // 20 hit, then an average of 15 against 22 is more than 20% short
+        let logs = [(2, 40), (2, 30), (2, 40)];
+        let targets: Vec<i64> = (0..=logs.len()).map(|n| target_after(&plan, &logs[..n], &config).target).collect();
+        assert_eq!(targets, [20, 22, 20, 22]);
+        assert_eq!(target_after(&plan, &logs[..2], &config).last, Some(Outcome::Deloaded));
+    }
+
+    #[test]
+    fn no_deload_step_holds_after_a_bad_miss() {
+        let config = ProgressionConfig {
+            deload_percent: 0,
+            ..ProgressionConfig::default()
+        };
+        let prescription = target_after(&plan(Rule::Reps(2), 20), &[(2, 10)], &config);
+        assert_eq!(prescription, Prescription { target: 20, last: Some(Outcome::Held) });
+    }
+
+    #[test]
+    fn deloads_take_at_least_one_and_never_go_below_one() {
+        let config = ProgressionConfig::default();
+        assert_eq!(target_after(&plan(Rule::Reps(1), 3), &[(1, 1)], &config).target, 2);
+        assert_eq!(target_after(&plan(Rule::Reps(1), 1), &[(1, 0)], &config).target, 1);
+    }
+
+    #[test]
+    fn weeks_without_sets_hold_the_target() {
+        let config = ProgressionConfig::default();
+        let plan = plan(Rule::Reps(2), 10);
+        let prescription = target_after(&plan, &[(3, 30), (0, 0), (3, 36)], &config);
+        assert_eq!(prescription.target, 14);
+        // A week missing from the logs altogether counts the same
+        let mut logs = weeks(&[(3, 30)]);
+        logs.insert(date("2024-05-20"), WeekLog { sets: 3, total: 36 });
+        assert_eq!(prescribe(&plan, &logs, date("2024-05-27"), &config).target, 14);
+        assert_eq!(prescribe(&plan, &logs, date("2024-05-20"), &config).last, Some(Outcome::Rested));
+    }
+
+    #[test]
+    fn volume_targets_grow_by_percent_of_the_weekly_total() {
+        let config = ProgressionConfig::default();
+        let plan = plan(Rule::Volume(5), 400);
+        // 400 and 420 hit, 400 against 441 holds, 441 hits, 300 deloads
+        let logs = [(8, 400), (8, 420), (8, 400), (9, 441), (6, 300)];
+        let targets: Vec<i64> = (0..=logs.len()).map(|n| target_after(&plan, &logs[..n], &config).target).collect();
+        assert_eq!(targets, [400, 420, 441, 441, 463, 417]);
+        // Small targets still move by one
+        assert_eq!(target_after(&Plan { base: 4, ..plan }, &[(1, 4)], &config).target, 5);
+    }
+
+    #[test]
+    fn baseline_averages_the_weeks_with_sets() {
+        let logs = weeks(&[(2, 20), (0, 0), (4, 48)]);
+        assert_eq!(baseline(Rule::Reps(2), &logs), Some(11));
+        assert_eq!(baseline(Rule::Volume(5), &logs), Some(34));
+        assert_eq!(baseline(Rule::Reps(2), &weeks(&[(0, 0)])), None);
+        assert_eq!(baseline(Rule::Reps(2), &BTreeMap::new()), None);
+    }
+
+    #[test]
+    fn days_are_grouped_by_the_configured_week() {
+        // Sunday 2024-05-12 ends a Monday week but starts a Sunday one
+        let days = [(date("2024-05-06"), 2, 20), (date("2024-05-12"), 1, 15), (date("2024-05-13"), 3, 30)];
+        let monday = weekly_logs(&days, WeekStart::Monday);
+        assert_eq!(monday[&date("2024-05-06")], WeekLog { sets: 3, total: 35 });
+        assert_eq!(monday[&date("2024-05-13")], WeekLog { sets: 3, total: 30 });
+        let sunday = weekly_logs(&days, WeekStart::Sunday);
+        assert_eq!(sunday[&date("2024-05-05")], WeekLog { sets: 2, total: 20 });
+        assert_eq!(sunday[&date("2024-05-12")], WeekLog { sets: 4, total: 45 });
+    }
+}
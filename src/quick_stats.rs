@@ -0,0 +1,152 @@
+//! A glance at the week without leaving the main screen: 'S' opens a
+//! popup with this week's totals, the current streak, the latest personal
+//! record and today's goal progress, and any key closes it. Everything is
+//! gathered once when it opens, so drawing it never touches the database.
+
+use anyhow::Result;
+use chrono::{Local, NaiveDate};
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use std::collections::HashMap;
+
+use crate::compare::streaks;
+use crate::dates::{self, week_start};
+use crate::{centered_rect, exercises, App, Database};
+
+#[derive(Debug, Clone, Default)]
+pub struct QuickStats {
+    /// This week's total per exercise, in the order first logged.
+    pub week: Vec<(String, i64)>,
+    pub current_streak: usize,
+    /// The latest set that beat every earlier set of its exercise:
+    /// (exercise, amount, day).
+    pub last_record: Option<(String, i64, NaiveDate)>,
+    /// Today's amount against each daily target: (exercise, total, target).
+    pub goals: Vec<(String, i64, i64)>,
+}
+
+impl Database {
+    pub fn quick_stats(&self, week: NaiveDate, today: NaiveDate) -> Result<QuickStats> {
+        let mut stats = QuickStats::default();
+        for (_, exercise, total) in self.week_totals(week, today)? {
+            match stats.week.iter_mut().find(|(name, _)| *name == exercise) {
+                Some((_, amount)) => *amount += total,
+                None => stats.week.push((exercise, total)),
+            }
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT substr(timestamp, 1, 10) AS day FROM workouts
+             WHERE deleted_at IS NULL ORDER BY day",
+        )?;
+        let days: Vec<NaiveDate> = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?
+            .iter()
+            .filter_map(|day| dates::parse_date(day))
+            .collect();
+        stats.current_streak = streaks(&days, today).0;
+
+        // A first set is nothing to beat, so like the weekly report's records
+        // only sets with an earlier one of their exercise count
+        let mut stmt = self.conn.prepare(
+            "SELECT exercise_type, count, substr(timestamp, 1, 10) FROM (
+                 SELECT exercise_type, count, timestamp,
+                        MAX(count) OVER (PARTITION BY exercise_type ORDER BY timestamp
+                                         ROWS BETWEEN UNBOUNDED PRECEDING AND 1 PRECEDING) AS best_before
+                 FROM workouts WHERE deleted_at IS NULL)
+             WHERE count > best_before
+             ORDER BY timestamp DESC LIMIT 1",
+        )?;
+        let record = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, String>(2)?)))?
+            .next()
+            .transpose()?;
+        stats.last_record =
+            record.and_then(|(exercise, best, day)| dates::parse_date(&day).map(|day| (exercise, best, day)));
+
+        let totals: HashMap<String, i64> = self.get_today_totals()?;
+        let mut goals: Vec<(String, i64, i64)> = self
+            .get_goals()?
+            .into_iter()
+            .map(|(exercise, target)| {
+                let total = totals.get(&exercise).copied().unwrap_or(0);
+                (exercise, total, target)
+            })
+            .collect();
+        goals.sort();
+        stats.goals = goals;
+        Ok(stats)
+    }
+}
+
+impl App {
+    pub(crate) fn open_quick_stats(&mut self) -> Result<()> {
+        let today = Local::now().date_naive();
+        let week = week_start(today, self.config.week_starts_on);
+        self.quick_stats = Some(self.db.quick_stats(week, today)?);
+        Ok(())
+    }
+}
+
+pub fn render_quick_stats(f: &mut Frame, app: &App) {
+    let Some(stats) = &app.quick_stats else {
+        return;
+    };
+    let heading = |text: &str| Line::from(Span::styled(text.to_string(), Style::default().add_modifier(Modifier::BOLD)));
+    let mut lines = vec![heading(app.text.get("quick_stats.week"))];
+    if stats.week.is_empty() {
+        lines.push(Line::from(app.text.get("quick_stats.nothing")));
+    }
+    for (exercise, total) in &stats.week {
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {}: ", exercises::display_name(exercise)), Style::default().fg(app.exercise_color(exercise))),
+            Span::raw(app.format_count(exercise, *total)),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(app.text.format("quick_stats.streak", &[("days", &stats.current_streak)])));
+    lines.push(Line::from(match &stats.last_record {
+        Some((exercise, best, day)) => app.text.format(
+            "quick_stats.record",
+            &[
+                ("exercise", &exercises::display_name(exercise)),
+                ("amount", &app.format_count(exercise, *best)),
+                ("date", &app.display_date(*day)),
+            ],
+        ),
+        None => app.text.get("quick_stats.no_record").to_string(),
+    }));
+    if !stats.goals.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(heading(app.text.get("quick_stats.goals")));
+    }
+    for (exercise, total, target) in &stats.goals {
+        let color = if total >= target { Color::Green } else { Color::Yellow };
+        lines.push(Line::from(Span::styled(
+            format!(
+                "  {}: {} / {}",
+                exercises::display_name(exercise),
+                app.format_count(exercise, *total),
+                app.format_count(exercise, *target)
+            ),
+            Style::default().fg(color),
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(app.text.get("quick_stats.dismiss"), Style::default().fg(Color::DarkGray))));
+
+    let area = centered_rect(48, lines.len() as u16 + 2, f.size());
+    let popup = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(app.text.get("quick_stats.title"))
+            .border_style(Style::default().fg(Color::Cyan)),
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(popup, area);
+}
@@ -0,0 +1,114 @@
+//! A recap printed after quitting, once the alternate screen is gone so it
+//! stays in the scrollback: the sets added this session, today's totals
+//! against the goals, and any new bests. Nothing is printed when the
+//! session changed no data, or with `ui.exit_summary` off.
+
+use anyhow::Result;
+use chrono::Local;
+use std::path::PathBuf;
+
+use crate::units;
+use crate::{App, Database};
+
+/// Where the data stood when the UI came up.
+#[derive(Debug)]
+pub struct SessionStart {
+    path: PathBuf,
+    last_id: i64,
+    /// `total_changes`, which the backup on exit goes by too.
+    pub changes: i64,
+}
+
+impl SessionStart {
+    pub fn new(db: &Database) -> Result<Self> {
+        Ok(Self { path: db.file_path(), last_id: db.last_workout_id()?, changes: db.total_changes()? })
+    }
+}
+
+impl Database {
+    /// Sets added after `after` and not trashed again, per exercise in the
+    /// order first added: (exercise, sets, total).
+    fn sets_added_after(&self, after: i64) -> Result<Vec<(String, i64, i64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT exercise_type, COUNT(*), SUM(count) FROM workouts
+             WHERE id > ?1 AND deleted_at IS NULL
+             GROUP BY exercise_type ORDER BY MIN(id)",
+        )?;
+        let rows = stmt.query_map([after], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?.collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// The biggest set added after `after` of each exercise where it beat
+    /// every set from before: (exercise, amount, best before). A first set
+    /// is nothing to beat, so it never counts.
+    fn bests_after(&self, after: i64) -> Result<Vec<(String, i64, i64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT exercise_type, MAX(count), before FROM (
+                 SELECT w.exercise_type, w.count, w.id,
+                        (SELECT MAX(o.count) FROM workouts o
+                         WHERE o.exercise_type = w.exercise_type AND o.id <= ?1 AND o.deleted_at IS NULL) AS before
+                 FROM workouts w
+                 WHERE w.id > ?1 AND w.deleted_at IS NULL
+             )
+             WHERE count > before
+             GROUP BY exercise_type ORDER BY MIN(id)",
+        )?;
+        let rows = stmt.query_map([after], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?.collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+}
+
+impl App {
+    /// The recap's lines; none when there's nothing to tell.
+    pub(crate) fn session_recap(&self, start: &SessionStart) -> Result<Vec<String>> {
+        // Another profile's ids say nothing about this session
+        if !self.config.ui.exit_summary || self.db.file_path() != start.path || self.db.total_changes()? == start.changes {
+            return Ok(Vec::new());
+        }
+        let added = self.db.sets_added_after(start.last_id)?;
+        let today = self.db.today_summary(Local::now().date_naive())?;
+        let bests = self.db.bests_after(start.last_id)?;
+        if added.is_empty() && today.exercises.iter().all(|e| e.total == 0) {
+            return Ok(Vec::new());
+        }
+
+        let mut lines = Vec::new();
+        // Only edits or deletions leave just today's totals to tell
+        if !added.is_empty() {
+            let sets: i64 = added.iter().map(|(_, sets, _)| sets).sum();
+            lines.push(self.text.format("recap.added", &[("sets", &sets)]));
+        }
+        for (exercise, sets, total) in &added {
+            let amount = self.format_count(exercise, *total);
+            lines.push(self.text.format("recap.exercise", &[("exercise", exercise), ("sets", sets), ("amount", &amount)]));
+        }
+
+        let distance = self.config.units.distance;
+        let totals: Vec<String> = today.exercises.iter().filter(|e| e.total > 0).map(|e| e.describe(distance)).collect();
+        lines.push(self.text.format("recap.today", &[("totals", &totals.join(" · "))]));
+
+        let goals: Vec<_> = today.exercises.iter().filter_map(|e| Some((e, e.target?))).collect();
+        if !goals.is_empty() {
+            let met = goals.iter().filter(|(e, target)| e.total >= *target).count();
+            let mut line = self.text.format("recap.goals", &[("met", &met), ("count", &goals.len())]);
+            let left: Vec<String> = goals
+                .iter()
+                .filter(|(e, target)| e.total < *target)
+                .map(|(e, target)| {
+                    let left = units::format_amount(target - e.total, &e.unit, distance);
+                    self.text.format("recap.goal_left", &[("exercise", &e.exercise), ("left", &left)])
+                })
+                .collect();
+            if !left.is_empty() {
+                line = format!("{line} — {}", left.join(", "));
+            }
+            lines.push(line);
+        }
+
+        for (exercise, amount, before) in &bests {
+            let (amount, before) = (self.format_count(exercise, *amount), self.format_count(exercise, *before));
+            lines.push(self.text.format("recap.best", &[("exercise", exercise), ("amount", &amount), ("before", &before)]));
+        }
+        Ok(lines)
+    }
+}
@@ -0,0 +1,151 @@
+//! Picks up edits to config.toml while the app runs. The file's modification
+//! time is polled from the tick loop; a change is parsed and applied on the
+//! next frame, except for the data file, which only changes on restart.
+
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::config::Config;
+use crate::App;
+
+/// How often the file's modification time is looked at.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// How long a toast stays on screen.
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+
+#[derive(Debug)]
+pub struct ConfigWatch {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    checked: Instant,
+}
+
+impl ConfigWatch {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self {
+            modified: modified(&path),
+            path,
+            checked: Instant::now(),
+        }
+    }
+
+    /// Whether the file changed since the last call that said so. A file
+    /// that appears or disappears counts as a change too.
+    fn changed(&mut self) -> bool {
+        if self.checked.elapsed() < POLL_INTERVAL {
+            return false;
+        }
+        self.checked = Instant::now();
+        let modified = modified(&self.path);
+        if modified == self.modified {
+            return false;
+        }
+        self.modified = modified;
+        true
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// A short note drawn over the corner of any screen.
+#[derive(Debug)]
+pub struct Toast {
+    pub text: String,
+    pub error: bool,
+    shown: Instant,
+}
+
+impl App {
+    pub(crate) fn check_config(&mut self) {
+        if self.toast.as_ref().is_some_and(|toast| toast.shown.elapsed() >= TOAST_DURATION) {
+            self.toast = None;
+        }
+        if !self.config_watch.changed() {
+            return;
+        }
+        let (text, error) = match Config::load(&self.config_watch.path) {
+            Ok(config) => {
+                let storage_changed = config.storage.backend != self.file_config.storage.backend
+                    || config.storage.path != self.file_config.storage.path;
+                // The open data file stays the one the app started with
+                let mut config = config;
+                config.storage = self.file_config.storage.clone();
+                self.file_config = config;
+                match self.apply_settings() {
+                    Ok(()) if storage_changed => (self.text.get("config.restart_needed").to_string(), false),
+                    Ok(()) => (self.text.get("config.reloaded").to_string(), false),
+                    Err(e) => (self.reload_failed(e), true),
+                }
+            }
+            Err(e) => (self.reload_failed(e), true),
+        };
+        self.toast = Some(Toast {
+            text,
+            error,
+            shown: Instant::now(),
+        });
+    }
+
+    /// Shows a short note in the corner for a few seconds.
+    pub(crate) fn show_toast(&mut self, text: String) {
+        self.toast = Some(Toast {
+            text,
+            error: false,
+            shown: Instant::now(),
+        });
+    }
+
+    /// A note about something that didn't go as asked, in the error color.
+    pub(crate) fn show_warning(&mut self, text: String) {
+        self.toast = Some(Toast {
+            text,
+            error: true,
+            shown: Instant::now(),
+        });
+    }
+
+    /// TOML errors draw the offending line under the message; the toast
+    /// only has room for the message.
+    fn reload_failed(&self, error: anyhow::Error) -> String {
+        let error = format!("{error:#}");
+        let error = error.lines().next().unwrap_or_default();
+        self.text.format("config.reload_failed", &[("error", &error)])
+    }
+}
+
+pub fn render_toast(f: &mut Frame, app: &App) {
+    let Some(toast) = &app.toast else {
+        return;
+    };
+    let area = f.size();
+    let len = toast.text.chars().count() as u16;
+    let width = (len + 4).min((area.width * 2 / 3).max(1));
+    let lines = len.div_ceil(width.saturating_sub(4).max(1)).max(1);
+    let rect = Rect {
+        x: area.right().saturating_sub(width),
+        y: area.y,
+        width,
+        height: (lines + 2).min(area.height),
+    };
+    let color = if toast.error { Color::Red } else { Color::Green };
+    f.render_widget(Clear, rect);
+    f.render_widget(
+        Paragraph::new(toast.text.as_str())
+            .wrap(Wrap { trim: true })
+            .style(Style::default().fg(color))
+            .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(color))),
+        rect,
+    );
+}
@@ -0,0 +1,198 @@
+use anyhow::Result;
+use chrono::{Local, NaiveDateTime, NaiveTime};
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::Paragraph,
+    Frame,
+};
+
+use crate::{db_watch, instance_lock, App, Database};
+
+/// Last reminder slot that fired, as "YYYY-MM-DD HH:MM", so a restart
+/// doesn't nag again for the same slot.
+const REMINDED_KEY: &str = "reminded_at";
+
+/// Day the unmet-goal banner was dismissed, so it stays away until tomorrow.
+const GOAL_DISMISSED_KEY: &str = "goal_nag_dismissed";
+
+/// How many recent sets the "about N sets" estimate averages.
+const TYPICAL_SETS: i64 = 10;
+
+/// Banner shown on every screen until dismissed or a set is logged.
+#[derive(Debug, Clone)]
+pub struct Reminder {
+    pub text: String,
+    /// Start of the window the reminder is about.
+    since: NaiveDateTime,
+}
+
+impl Database {
+    fn has_workouts_since(&self, since: NaiveDateTime) -> Result<bool> {
+        let since = since.format(crate::dates::TIMESTAMP_FORMAT).to_string();
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM workouts WHERE timestamp >= ?1 AND deleted_at IS NULL",
+            [since],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
+    /// Average amount of the exercise's last few sets.
+    fn typical_set(&self, exercise: &str) -> Result<Option<f64>> {
+        Ok(self.conn.query_row(
+            "SELECT AVG(count) FROM (
+                SELECT count FROM workouts WHERE exercise_type = ?1 AND deleted_at IS NULL
+                ORDER BY timestamp DESC LIMIT ?2
+            )",
+            rusqlite::params![exercise, TYPICAL_SETS],
+            |row| row.get(0),
+        )?)
+    }
+}
+
+/// Latest configured time at or before `now`, with the start of its window
+/// (the previous reminder time, or midnight for the first one).
+fn due_slot(times: &[NaiveTime], now: NaiveDateTime) -> Option<(NaiveDateTime, NaiveDateTime)> {
+    let mut times: Vec<NaiveTime> = times.to_vec();
+    times.sort();
+    let due = times.iter().rposition(|t| *t <= now.time())?;
+    let start = match due {
+        0 => NaiveTime::MIN,
+        i => times[i - 1],
+    };
+    Some((now.date().and_time(times[due]), now.date().and_time(start)))
+}
+
+impl App {
+    /// Fires the reminder for the current slot if nothing was logged in its
+    /// window, and clears the banner once something is.
+    pub(crate) fn check_reminders(&mut self) -> Result<()> {
+        if !self.config.reminders.enabled {
+            self.reminder = None;
+            return Ok(());
+        }
+        if let Some(reminder) = &self.reminder
+            && self.db.has_workouts_since(reminder.since)?
+        {
+            self.reminder = None;
+        }
+
+        let times: Vec<NaiveTime> = self
+            .config
+            .reminders
+            .times
+            .iter()
+            .filter_map(|t| NaiveTime::parse_from_str(t, "%H:%M").ok())
+            .collect();
+        let now = Local::now().naive_local();
+        let Some((due, since)) = due_slot(&times, now) else {
+            return Ok(());
+        };
+        let slot = due.format("%Y-%m-%d %H:%M").to_string();
+        if self.db.get_state(REMINDED_KEY)?.is_some_and(|last| last >= slot) {
+            return Ok(());
+        }
+        self.db.set_state(REMINDED_KEY, &slot)?;
+        if !self.db.has_workouts_since(since)? {
+            self.reminder = Some(Reminder {
+                text: self.reminder_text(since)?,
+                since,
+            });
+        }
+        Ok(())
+    }
+
+    /// The first exercise still short of its daily goal, with today's total
+    /// and the target.
+    fn goal_behind(&self) -> Result<Option<(String, i64, i64)>> {
+        let goals = self.db.get_goals()?;
+        let totals = self.db.get_today_totals()?;
+        Ok(self.picker_exercises().into_iter().find_map(|e| {
+            let target = *goals.get(&e.name)?;
+            let total = totals.get(&e.name).copied().unwrap_or(0);
+            (total < target).then(|| (e.name.clone(), total, target))
+        }))
+    }
+
+    /// Names the first exercise still short of its goal, if any has one.
+    fn reminder_text(&self, since: NaiveDateTime) -> Result<String> {
+        Ok(match self.goal_behind()? {
+            Some((name, 0, target)) => self.text.format(
+                "reminder.none_yet",
+                &[("exercise", &name), ("target", &self.format_count(&name, target))],
+            ),
+            Some((name, total, target)) => self.text.format(
+                "reminder.behind",
+                &[
+                    ("exercise", &name),
+                    ("total", &self.format_count(&name, total)),
+                    ("target", &self.format_count(&name, target)),
+                ],
+            ),
+            None => {
+                let time = crate::dates::format_time(since.time(), self.config.ui.time_format, false);
+                self.text.format("reminder.idle", &[("time", &time)])
+            }
+        })
+    }
+
+    /// Shows what's left of an unmet goal once it's late in the day. The
+    /// text is rebuilt on every tick, so it counts down as sets are logged
+    /// and goes away when the goal is met.
+    pub(crate) fn check_goal_nag(&mut self) -> Result<()> {
+        self.goal_nag = None;
+        let config = &self.config.reminders;
+        let Ok(after) = NaiveTime::parse_from_str(&config.goal_after, "%H:%M") else {
+            return Ok(());
+        };
+        let now = Local::now().naive_local();
+        let today = now.date().format("%Y-%m-%d").to_string();
+        if !config.enabled || now.time() < after || self.db.get_state(GOAL_DISMISSED_KEY)?.as_deref() == Some(&today) {
+            return Ok(());
+        }
+        let Some((name, total, target)) = self.goal_behind()? else {
+            return Ok(());
+        };
+        let left = target - total;
+        let args: [(&str, &dyn std::fmt::Display); 2] = [("exercise", &name), ("left", &self.format_count(&name, left))];
+        let sets = self.db.typical_set(&name)?.filter(|typical| *typical > 0.0).map(|typical| (left as f64 / typical).ceil() as i64);
+        self.goal_nag = Some(match sets {
+            Some(1) => self.text.format("reminder.goal_left_one_set", &args),
+            Some(sets) => self.text.format("reminder.goal_left_sets", &[args[0], args[1], ("sets", &sets)]),
+            None => self.text.format("reminder.goal_left", &args),
+        });
+        Ok(())
+    }
+
+    /// Hides the unmet-goal banner for the rest of the day.
+    pub(crate) fn dismiss_goal_nag(&mut self) -> Result<()> {
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        self.db.set_state(GOAL_DISMISSED_KEY, &today)?;
+        self.goal_nag = None;
+        Ok(())
+    }
+}
+
+/// Lines of the banner above every screen; its height is their number.
+pub fn banner_height(app: &App) -> u16 {
+    app.read_only as u16 + app.db_watch.change.is_some() as u16 + app.reminder.is_some() as u16 + app.goal_nag.is_some() as u16
+}
+
+pub fn render_reminder_banner(f: &mut Frame, area: Rect, app: &App) {
+    let dismiss = app.text.get("reminder.dismiss");
+    let mut lines: Vec<Line> = instance_lock::banner_line(app).into_iter().chain(db_watch::banner_line(app)).collect();
+    if let Some(reminder) = &app.reminder {
+        lines.push(Line::styled(
+            format!("🔔 {}   {dismiss}", reminder.text),
+            Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD),
+        ));
+    }
+    if let Some(nag) = &app.goal_nag {
+        // F3 takes the reminder away first
+        let text = if app.reminder.is_some() { format!("🎯 {nag}") } else { format!("🎯 {nag}   {dismiss}") };
+        lines.push(Line::styled(text, Style::default().fg(Color::Black).bg(Color::Cyan)));
+    }
+    f.render_widget(Paragraph::new(lines), area);
+}
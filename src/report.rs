@@ -0,0 +1,426 @@
+use anyhow::{Context, Result};
+use chrono::{Datelike, Duration, Local, NaiveDate, Weekday};
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::dates::{self, week_start, WeekStart};
+use crate::i18n::Messages;
+use crate::units::{self, DistanceUnit};
+use crate::{App, Database, Screen};
+
+/// Everything the weekly Markdown report shows, gathered up front so the
+/// formatting itself never touches the database.
+#[derive(Debug, Clone)]
+pub struct WeekReport {
+    pub start: NaiveDate,
+    /// Exercises logged this week or the one before, with their unit, in
+    /// exercise-list order.
+    pub exercises: Vec<(String, String)>,
+    /// Amount per exercise for each of the seven days.
+    pub daily: HashMap<String, [i64; 7]>,
+    /// Week total per exercise for the week before.
+    pub previous: HashMap<String, i64>,
+    /// Best single set of the week per exercise that beat every earlier set:
+    /// (exercise, amount, day).
+    pub records: Vec<(String, i64, NaiveDate)>,
+    /// Current daily targets.
+    pub goals: HashMap<String, i64>,
+}
+
+impl WeekReport {
+    fn total(&self, exercise: &str) -> i64 {
+        self.daily.get(exercise).map_or(0, |days| days.iter().sum())
+    }
+}
+
+#[derive(Debug)]
+pub struct ReportState {
+    /// First day of the week being previewed.
+    pub start: NaiveDate,
+}
+
+/// First day of ISO week `week` ("2024-W18") under the configured week
+/// start. With Sunday weeks that is the Sunday before the ISO Monday.
+pub fn parse_iso_week(week: &str, start: WeekStart) -> Option<NaiveDate> {
+    let (year, number) = week.split_once("-W").or_else(|| week.split_once("-w"))?;
+    let monday = NaiveDate::from_isoywd_opt(year.parse().ok()?, number.parse().ok()?, Weekday::Mon)?;
+    Some(week_start(monday, start))
+}
+
+/// "2024-W18" for the week starting on `start`, named after the ISO week
+/// its Thursday falls in so Sunday weeks get the same label.
+pub fn iso_week_label(start: NaiveDate) -> String {
+    let week = (start + Duration::days(3)).iso_week();
+    format!("{}-W{:02}", week.year(), week.week())
+}
+
+/// Output file used when no path is given.
+pub fn default_path(start: NaiveDate) -> PathBuf {
+    PathBuf::from(format!("fitness_tracker-report-{}.md", iso_week_label(start)))
+}
+
+impl Database {
+    pub fn week_report(&self, start: NaiveDate) -> Result<WeekReport> {
+        let previous_start = start - Duration::weeks(1);
+        let end = start + Duration::weeks(1);
+        let day = |date: NaiveDate| date.format("%Y-%m-%d").to_string();
+
+        let mut stmt = self.conn.prepare(
+            "SELECT d.exercise_type, COALESCE(e.unit, ?3), d.day, d.total
+             FROM daily_totals d
+             LEFT JOIN exercises e ON e.name = d.exercise_type
+             WHERE d.day >= ?1 AND d.day < ?2
+             ORDER BY COALESCE(e.position, 1000), d.exercise_type",
+        )?;
+        let rows = stmt
+            .query_map(rusqlite::params![day(previous_start), day(end), "reps"], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i64>(3)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut report = WeekReport {
+            start,
+            exercises: Vec::new(),
+            daily: HashMap::new(),
+            previous: HashMap::new(),
+            records: Vec::new(),
+            goals: self.get_goals()?,
+        };
+        for (exercise, unit, date, total) in rows {
+            let Some(date) = dates::parse_date(&date) else {
+                continue;
+            };
+            if !report.exercises.iter().any(|(name, _)| *name == exercise) {
+                report.exercises.push((exercise.clone(), unit));
+            }
+            if date < start {
+                *report.previous.entry(exercise).or_default() += total;
+            } else {
+                report.daily.entry(exercise).or_default()[(date - start).num_days() as usize] += total;
+            }
+        }
+
+        // SQLite takes the bare day column from the row holding the MAX
+        let mut stmt = self.conn.prepare(
+            "SELECT d.exercise_type, MAX(d.best), d.day,
+                    (SELECT MAX(p.best) FROM daily_totals p
+                     WHERE p.exercise_type = d.exercise_type AND p.day < ?1) AS best_before
+             FROM daily_totals d
+             WHERE d.day >= ?1 AND d.day < ?2
+             GROUP BY d.exercise_type",
+        )?;
+        let records = stmt
+            .query_map([day(start), day(end)], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<i64>>(3)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        for (exercise, best, date, best_before) in records {
+            if let (Some(before), Some(date)) = (best_before, dates::parse_date(&date))
+                && best > before
+            {
+                report.records.push((exercise, best, date));
+            }
+        }
+        report.records.sort_by_key(|(exercise, _, date)| (*date, exercise.clone()));
+        Ok(report)
+    }
+
+    /// Writes the Markdown report for the week starting on `start` to `path`.
+    pub fn write_week_report(&self, start: NaiveDate, path: &Path, text: &Messages, distance: DistanceUnit) -> Result<()> {
+        let markdown = render_markdown(&self.week_report(start)?, text, distance);
+        fs::write(path, markdown).with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+/// "+20", "-1.50 km" or "±0", in the exercise's unit.
+fn format_delta(delta: i64, unit: &str, distance: DistanceUnit) -> String {
+    let amount = units::format_amount(delta.abs(), unit, distance);
+    match delta {
+        0 => "±0".to_string(),
+        d if d > 0 => format!("+{amount}"),
+        _ => format!("-{amount}"),
+    }
+}
+
+/// The report as Markdown. Dates are always ISO, like every other export.
+pub fn render_markdown(report: &WeekReport, text: &Messages, distance: DistanceUnit) -> String {
+    let start = report.start;
+    let end = start + Duration::days(6);
+    let days: Vec<NaiveDate> = (0..7).map(|i| start + Duration::days(i)).collect();
+    let unit_of = |exercise: &str| {
+        report
+            .exercises
+            .iter()
+            .find(|(name, _)| name == exercise)
+            .map_or("reps", |(_, unit)| unit.as_str())
+    };
+    let none = format!("_{}_\n", text.get("report.none"));
+    let mut out = String::new();
+
+    let _ = writeln!(
+        out,
+        "# {}\n",
+        text.format(
+            "report.heading",
+            &[("week", &iso_week_label(start)), ("start", &start), ("end", &end)]
+        )
+    );
+
+    let _ = writeln!(out, "## {}\n", text.get("report.daily"));
+    let logged: Vec<&(String, String)> = report
+        .exercises
+        .iter()
+        .filter(|(name, _)| report.total(name) > 0)
+        .collect();
+    if logged.is_empty() {
+        out.push_str(&none);
+    } else {
+        let mut header = vec![text.get("common.exercise").to_string()];
+        header.extend(days.iter().map(|day| day.format("%m-%d").to_string()));
+        header.push(text.get("common.total").to_string());
+        let _ = writeln!(out, "| {} |", header.join(" | "));
+        let _ = writeln!(out, "|---|{}", "---:|".repeat(8));
+        for (exercise, unit) in &logged {
+            let mut cells = vec![exercise.clone()];
+            cells.extend(report.daily[exercise].iter().map(|&amount| match amount {
+                0 => "–".to_string(),
+                amount => units::format_amount(amount, unit, distance),
+            }));
+            cells.push(units::format_amount(report.total(exercise), unit, distance));
+            let _ = writeln!(out, "| {} |", cells.join(" | "));
+        }
+    }
+
+    let _ = writeln!(out, "\n## {}\n", text.get("report.totals"));
+    if report.exercises.is_empty() {
+        out.push_str(&none);
+    } else {
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} |",
+            text.get("common.exercise"),
+            text.get("report.this_week"),
+            text.get("report.previous_week"),
+            text.get("report.change")
+        );
+        let _ = writeln!(out, "|---|---:|---:|---:|");
+        for (exercise, unit) in &report.exercises {
+            let total = report.total(exercise);
+            let previous = report.previous.get(exercise).copied().unwrap_or(0);
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                exercise,
+                units::format_amount(total, unit, distance),
+                units::format_amount(previous, unit, distance),
+                format_delta(total - previous, unit, distance)
+            );
+        }
+    }
+
+    let _ = writeln!(out, "\n## {}\n", text.get("report.records"));
+    if report.records.is_empty() {
+        out.push_str(&none);
+    }
+    for (exercise, best, date) in &report.records {
+        let amount = units::format_amount(*best, unit_of(exercise), distance);
+        let _ = writeln!(
+            out,
+            "- {date}: {}",
+            text.format("report.record", &[("exercise", exercise), ("amount", &amount)])
+        );
+    }
+
+    let _ = writeln!(out, "\n## {}\n", text.get("report.goals"));
+    let mut goals_met = 0;
+    for (i, day) in days.iter().enumerate() {
+        for (exercise, unit) in &report.exercises {
+            let Some(&target) = report.goals.get(exercise) else {
+                continue;
+            };
+            let amount = report.daily.get(exercise).map_or(0, |d| d[i]);
+            if target > 0 && amount >= target {
+                let target = units::format_amount(target, unit, distance);
+                let _ = writeln!(
+                    out,
+                    "- {day}: {}",
+                    text.format("report.goal_met", &[("exercise", exercise), ("target", &target)])
+                );
+                goals_met += 1;
+            }
+        }
+    }
+    if goals_met == 0 {
+        out.push_str(&none);
+    }
+    out
+}
+
+impl App {
+    pub(crate) fn open_report_screen(&mut self) {
+        let today = Local::now().date_naive();
+        self.report.start = week_start(today, self.config.week_starts_on);
+        self.screen = Screen::Report;
+        self.message = None;
+    }
+
+    fn report_markdown(&self) -> Result<String> {
+        let report = self.db.week_report(self.report.start)?;
+        Ok(render_markdown(&report, &self.text, self.config.units.distance))
+    }
+
+    pub(crate) fn handle_report_input(&mut self, key: KeyCode) -> Result<bool> {
+        match key {
+            KeyCode::Esc => self.screen = Screen::Stats,
+            KeyCode::Left => {
+                self.report.start -= Duration::weeks(1);
+                self.message = None;
+            }
+            KeyCode::Right => {
+                self.report.start += Duration::weeks(1);
+                self.message = None;
+            }
+            KeyCode::Enter => {
+                let path = default_path(self.report.start);
+                let written = self.db.write_week_report(self.report.start, &path, &self.text, self.config.units.distance);
+                self.message = Some(match written {
+                    Ok(()) => self.text.format("report.saved", &[("path", &path.display())]),
+                    Err(e) => self.text.format("report.failed", &[("error", &format!("{e:#}"))]),
+                });
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+}
+
+pub fn render_report_screen(f: &mut Frame, area: Rect, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(3)])
+        .split(area);
+
+    let markdown = app.report_markdown().unwrap_or_else(|e| format!("{e:#}"));
+    let title = app.text.format("report.title", &[("week", &iso_week_label(app.report.start))]);
+    let preview = Paragraph::new(markdown).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(preview, chunks[0]);
+
+    let status = app
+        .message
+        .clone()
+        .unwrap_or_else(|| app.text.format("report.hint", &[("path", &default_path(app.report.start).display())]));
+    let status = Paragraph::new(status)
+        .style(Style::default().fg(Color::Green))
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("common.status")));
+    f.render_widget(status, chunks[1]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::params;
+
+    fn date(s: &str) -> NaiveDate {
+        dates::parse_date(s).unwrap()
+    }
+
+    /// In-memory database with two weeks of sets around 2024-W18
+    /// (2024-04-29 to 2024-05-05).
+    fn seeded() -> Database {
+        let db = Database::new(":memory:").unwrap();
+        let sets = [
+            ("push-ups", 20, "2024-04-23 08:00:00"),
+            ("push-ups", 25, "2024-04-25 08:00:00"),
+            ("squats", 30, "2024-04-26 08:00:00"),
+            ("push-ups", 20, "2024-04-29 08:00:00"),
+            ("push-ups", 30, "2024-04-29 18:00:00"),
+            ("push-ups", 15, "2024-05-01 08:00:00"),
+            ("squats", 10, "2024-05-05 21:00:00"),
+            // next week, outside the report
+            ("push-ups", 99, "2024-05-06 08:00:00"),
+        ];
+        for (exercise, count, timestamp) in sets {
+            db.conn
+                .execute(
+                    "INSERT INTO workouts (exercise_type, count, timestamp) VALUES (?1, ?2, ?3)",
+                    params![exercise, count, timestamp],
+                )
+                .unwrap();
+        }
+        db.set_goal("push-ups", Some(50)).unwrap();
+        db
+    }
+
+    fn markdown() -> String {
+        let report = seeded().week_report(date("2024-04-29")).unwrap();
+        render_markdown(&report, &Messages::default(), DistanceUnit::Km)
+    }
+
+    #[test]
+    fn iso_weeks_parse_for_both_week_starts() {
+        assert_eq!(parse_iso_week("2024-W18", WeekStart::Monday), Some(date("2024-04-29")));
+        assert_eq!(parse_iso_week("2024-W18", WeekStart::Sunday), Some(date("2024-04-28")));
+        assert_eq!(parse_iso_week("2020-W53", WeekStart::Monday), Some(date("2020-12-28")));
+        assert_eq!(parse_iso_week("2024-W54", WeekStart::Monday), None);
+        assert_eq!(parse_iso_week("2024-18", WeekStart::Monday), None);
+        assert_eq!(iso_week_label(date("2024-04-28")), "2024-W18");
+        assert_eq!(iso_week_label(date("2024-12-30")), "2025-W01");
+    }
+
+    #[test]
+    fn heading_names_the_week_and_its_dates() {
+        assert!(markdown().starts_with("# Week 2024-W18: 2024-04-29 – 2024-05-05\n"));
+    }
+
+    #[test]
+    fn daily_table_has_a_column_per_day_and_a_total() {
+        let markdown = markdown();
+        assert!(markdown.contains("| Exercise | 04-29 | 04-30 | 05-01 | 05-02 | 05-03 | 05-04 | 05-05 | Total |"));
+        assert!(markdown.contains("| push-ups | 50 | – | 15 | – | – | – | – | 65 |"));
+        assert!(markdown.contains("| squats | – | – | – | – | – | – | 10 | 10 |"));
+    }
+
+    #[test]
+    fn week_totals_compare_with_the_week_before() {
+        let markdown = markdown();
+        assert!(markdown.contains("| push-ups | 65 | 45 | +20 |"));
+        assert!(markdown.contains("| squats | 10 | 30 | -20 |"));
+    }
+
+    #[test]
+    fn records_and_goals_list_the_day_they_happened() {
+        let markdown = markdown();
+        assert!(markdown.contains("- 2024-04-29: New best set of push-ups: 30"));
+        assert!(!markdown.contains("New best set of squats"));
+        assert!(markdown.contains("- 2024-04-29: Daily goal of 50 push-ups reached"));
+        assert!(!markdown.contains("- 2024-05-01: Daily goal"));
+    }
+
+    #[test]
+    fn empty_week_says_so_in_every_section() {
+        let report = seeded().week_report(date("2023-01-02")).unwrap();
+        let markdown = render_markdown(&report, &Messages::default(), DistanceUnit::Km);
+        assert_eq!(markdown.matches("_Nothing this week._").count(), 4);
+    }
+}
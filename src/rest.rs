@@ -0,0 +1,196 @@
+//! Rest between sets, read from when each set was logged. A set's rest is
+//! the time since the set before it, whatever exercise that was; gaps longer
+//! than `SESSION_BREAK` mean a new session rather than a long rest, so they
+//! don't count at all.
+
+use anyhow::Result;
+use chrono::{NaiveDate, NaiveDateTime};
+use ratatui::{
+    layout::Constraint,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Cell, Row, Table},
+};
+use std::collections::HashMap;
+
+use crate::dates;
+use crate::stats::format_duration;
+use crate::store::WorkoutStore;
+use crate::{App, Database};
+
+/// Longest gap, in seconds, still taken as rest within one session.
+pub const SESSION_BREAK: i64 = 2 * 60 * 60;
+
+/// The rest in seconds before each of `times`, which must be in order;
+/// `None` for the first set and for sets starting a new session.
+pub fn rest_gaps(times: &[NaiveDateTime]) -> Vec<Option<i64>> {
+    let mut previous: Option<NaiveDateTime> = None;
+    times
+        .iter()
+        .map(|time| {
+            let gap = previous.map(|before| (*time - before).num_seconds()).filter(|gap| (0..=SESSION_BREAK).contains(gap));
+            previous = Some(*time);
+            gap
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RestAverage {
+    pub exercise: String,
+    /// Mean rest before the exercise's sets, in seconds.
+    pub seconds: i64,
+    /// How many rests the mean is over.
+    pub rests: usize,
+}
+
+/// Mean rest before the sets of each exercise, by exercise name. `sets`
+/// must be in the order they were logged.
+pub fn average_rest(sets: &[(String, NaiveDateTime)]) -> Vec<RestAverage> {
+    let times: Vec<NaiveDateTime> = sets.iter().map(|(_, time)| *time).collect();
+    let mut totals: HashMap<&str, (i64, usize)> = HashMap::new();
+    for ((exercise, _), gap) in sets.iter().zip(rest_gaps(&times)) {
+        if let Some(gap) = gap {
+            let total = totals.entry(exercise).or_default();
+            total.0 += gap;
+            total.1 += 1;
+        }
+    }
+    let mut averages: Vec<RestAverage> = totals
+        .into_iter()
+        .map(|(exercise, (sum, rests))| RestAverage {
+            exercise: exercise.to_string(),
+            seconds: sum / rests as i64,
+            rests,
+        })
+        .collect();
+    averages.sort_by(|a, b| a.exercise.cmp(&b.exercise));
+    averages
+}
+
+impl Database {
+    /// Exercise and time of every set since `since`, in the order logged.
+    pub fn set_times_since(&self, since: NaiveDate) -> Result<Vec<(String, NaiveDateTime)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT exercise_type, timestamp FROM workouts
+             WHERE timestamp >= ?1 AND deleted_at IS NULL
+             ORDER BY timestamp, id",
+        )?;
+        let rows = stmt
+            .query_map([since.format("%Y-%m-%d").to_string()], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(exercise, timestamp)| Some((exercise, dates::parse_timestamp(&timestamp)?)))
+            .collect())
+    }
+}
+
+impl App {
+    /// Whether a rest went past the rest timer's length, when it's on.
+    pub(crate) fn rest_too_long(&self, seconds: i64) -> bool {
+        let target = self.config.timer.rest_seconds as i64;
+        target > 0 && seconds > target
+    }
+
+    /// The rest before each set of `date` by set id, over every set of the
+    /// day rather than just the ones the history filter shows.
+    pub(crate) fn day_rests(&self, date: &str) -> Result<HashMap<i64, i64>> {
+        let sets: Vec<(i64, NaiveDateTime)> = self
+            .db
+            .get_workouts_by_date(date)?
+            .into_iter()
+            .filter_map(|w| Some((w.id, dates::parse_timestamp(&w.timestamp)?)))
+            .collect();
+        let times: Vec<NaiveDateTime> = sets.iter().map(|(_, time)| *time).collect();
+        Ok(sets.iter().zip(rest_gaps(&times)).filter_map(|((id, _), gap)| Some((*id, gap?))).collect())
+    }
+}
+
+/// Mean rest per exercise since `since`, in the warning color when it's
+/// longer than the rest timer.
+pub fn rest_table(since: NaiveDate, app: &App) -> Table<'_> {
+    let rows: Vec<Row> = average_rest(&app.db.set_times_since(since).unwrap_or_default())
+        .into_iter()
+        .map(|average| {
+            let style = if app.rest_too_long(average.seconds) { Style::default().fg(Color::Red) } else { Style::default() };
+            Row::new(vec![
+                Cell::from(average.exercise),
+                Cell::from(format_duration(average.seconds)).style(style),
+                Cell::from(average.rests.to_string()),
+            ])
+        })
+        .collect();
+    Table::new(rows, [Constraint::Min(8), Constraint::Length(7), Constraint::Length(5)])
+        .header(
+            Row::new(vec![app.text.get("common.exercise"), app.text.get("rest.average"), app.text.get("rest.count")])
+                .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("rest.title")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> NaiveDateTime {
+        dates::parse_timestamp(&format!("2024-05-06 {time}")).unwrap()
+    }
+
+    fn set(exercise: &str, time: &str) -> (String, NaiveDateTime) {
+        (exercise.to_string(), at(time))
+    }
+
+    #[test]
+    fn rest_is_the_time_since_the_previous_set() {
+        let times = [at("08:00:00"), at("08:01:45"), at("08:04:00")];
+        assert_eq!(rest_gaps(&times), vec![None, Some(105), Some(135)]);
+        assert_eq!(rest_gaps(&[]), Vec::<Option<i64>>::new());
+    }
+
+    #[test]
+    fn gaps_past_the_session_break_start_a_new_session() {
+        let times = [at("08:00:00"), at("10:00:00"), at("18:00:00"), at("18:02:00")];
+        // Exactly two hours still counts, the afternoon doesn't
+        assert_eq!(rest_gaps(&times), vec![None, Some(SESSION_BREAK), None, Some(120)]);
+    }
+
+    #[test]
+    fn averages_are_per_exercise_and_skip_session_breaks() {
+        let sets = [
+            set("squats", "08:00:00"),
+            set("squats", "08:02:00"),
+            set("squats", "08:05:00"),
+            set("push-ups", "08:06:00"),
+            // A new session, so no rest before it
+            set("push-ups", "19:00:00"),
+            set("push-ups", "19:01:30"),
+        ];
+        assert_eq!(
+            average_rest(&sets),
+            vec![
+                RestAverage { exercise: "push-ups".to_string(), seconds: 75, rests: 2 },
+                RestAverage { exercise: "squats".to_string(), seconds: 150, rests: 2 },
+            ]
+        );
+    }
+
+    #[test]
+    fn a_single_set_has_no_average() {
+        assert_eq!(average_rest(&[set("squats", "08:00:00")]), vec![]);
+    }
+
+    #[test]
+    fn set_times_skip_the_trash_and_older_days() {
+        let db = Database::new(":memory:").unwrap();
+        for (timestamp, deleted) in [("2024-05-05 08:00:00", false), ("2024-05-06 08:00:00", false), ("2024-05-06 08:02:00", true)] {
+            db.conn
+                .execute(
+                    "INSERT INTO workouts (exercise_type, count, timestamp, deleted_at) VALUES ('squats', 5, ?1, ?2)",
+                    rusqlite::params![timestamp, deleted.then_some("2024-05-07 08:00:00")],
+                )
+                .unwrap();
+        }
+        let since = dates::parse_date("2024-05-06").unwrap();
+        assert_eq!(db.set_times_since(since).unwrap(), vec![set("squats", "08:00:00")]);
+    }
+}
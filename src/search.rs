@@ -0,0 +1,298 @@
+//! Searching the whole history from the history screen: every set whose
+//! exercise name or note contains the typed text, newest first, with the
+//! part of the note that matched, and Enter opens the set's day with it
+//! highlighted. Tab moves the keys to the results, where sets can be
+//! picked for a bulk action. Notes are looked up in their full-text index
+//! where SQLite has one (see `migrations::NOTES_INDEX`) and with LIKE
+//! otherwise; names are few and short, so a substring test does for them.
+
+use anyhow::Result;
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        block::{Position, Title},
+        Block, Borders, List, ListItem, ListState, Paragraph,
+    },
+    Frame,
+};
+
+use crate::input::TextInput;
+use crate::selection;
+use crate::{dates, migrations, App, Database, Screen};
+
+/// Most matches listed; a longer list wouldn't be scrolled through anyway.
+const MAX_HITS: usize = 200;
+
+/// Characters of a note shown either side of the match.
+const SNIPPET_CONTEXT: usize = 24;
+
+/// Shortest query the trigram index can look up; shorter ones use LIKE.
+const MIN_INDEXED_QUERY: usize = 3;
+
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub id: i64,
+    pub timestamp: String,
+    pub exercise: String,
+    pub count: i64,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct SearchState {
+    pub query: TextInput,
+    pub hits: Vec<SearchHit>,
+    pub selected: usize,
+    /// Keys go to the results, for picking sets, instead of the query.
+    pub in_results: bool,
+}
+
+impl Database {
+    /// Sets outside the trash whose exercise or note contains `query`,
+    /// ignoring case, newest first.
+    pub fn search_sets(&self, query: &str) -> Result<Vec<SearchHit>> {
+        let (notes_match, pattern) =
+            if query.chars().count() >= MIN_INDEXED_QUERY && migrations::has_notes_index(&self.conn)? {
+                // One phrase, so the query's own quotes and operators are just text
+                (
+                    format!("id IN (SELECT rowid FROM {} WHERE {0} MATCH ?3)", migrations::NOTES_INDEX),
+                    format!("\"{}\"", query.replace('"', "\"\"")),
+                )
+            } else {
+                let escaped = query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+                ("notes LIKE '%' || ?3 || '%' ESCAPE '\\'".to_string(), escaped)
+            };
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, timestamp, exercise_type, count, notes FROM workouts
+             WHERE deleted_at IS NULL AND (instr(lower(exercise_type), lower(?1)) > 0 OR {notes_match})
+             ORDER BY timestamp DESC, id DESC
+             LIMIT ?2"
+        ))?;
+        let hits = stmt
+            .query_map(rusqlite::params![query, MAX_HITS as i64, pattern], |row| {
+                Ok(SearchHit {
+                    id: row.get(0)?,
+                    timestamp: row.get(1)?,
+                    exercise: row.get(2)?,
+                    count: row.get(3)?,
+                    note: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(hits)
+    }
+}
+
+impl App {
+    pub(crate) fn open_search(&mut self) {
+        self.search = SearchState::default();
+        self.screen = Screen::Search;
+        self.message = None;
+    }
+
+    pub(crate) fn handle_search_input(&mut self, key: KeyCode) -> Result<bool> {
+        if self.selection.reassign.is_some() {
+            return self.handle_reassign_input(key);
+        }
+        if self.search.in_results && self.handle_selection_key(key)? {
+            return Ok(false);
+        }
+        match key {
+            KeyCode::Esc => self.screen = Screen::History,
+            KeyCode::Tab => self.search.in_results = !self.search.in_results,
+            KeyCode::Up => self.search.selected = self.search.selected.saturating_sub(1),
+            KeyCode::Down => self.search.selected = (self.search.selected + 1).min(self.search.hits.len().saturating_sub(1)),
+            KeyCode::Enter => self.open_search_hit()?,
+            _ if self.search.in_results => {}
+            _ => {
+                if self.search.query.handle_key(key) {
+                    let query = self.search.query.as_str().trim();
+                    self.search.hits = if query.is_empty() { Vec::new() } else { self.db.search_sets(query)? };
+                    self.search.selected = 0;
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    /// Opens the day of the highlighted match with its set highlighted.
+    fn open_search_hit(&mut self) -> Result<()> {
+        let Some(hit) = self.search.hits.get(self.search.selected).cloned() else {
+            return Ok(());
+        };
+        let Some(date) = dates::parse_timestamp(&hit.timestamp).map(|at| at.date()) else {
+            return Ok(());
+        };
+        self.open_history_day(date, Screen::Search)?;
+        if let Some(entry) = self.history_entries()?.iter().position(|entry| entry.id == hit.id) {
+            self.history_entry = entry;
+        }
+        Ok(())
+    }
+}
+
+/// Byte offset of the first case-insensitive match of `query` in `text`.
+fn find_match(text: &str, query: &str) -> Option<usize> {
+    let lower = text.to_lowercase();
+    // Lowercasing can change lengths outside ASCII, and then the offsets
+    // found wouldn't fit the original
+    let found = (!query.is_empty() && lower.len() == text.len()).then(|| lower.find(&query.to_lowercase())).flatten();
+    found.filter(|start| text.is_char_boundary(start + query.len()))
+}
+
+/// The part of `note` around the match of `query`, with an ellipsis where
+/// it was cut; the start of the note when the match can't be placed.
+fn snippet(note: &str, query: &str) -> String {
+    let (start, end) = find_match(note, query).map_or((0, 0), |start| (start, start + query.len()));
+    let from = note[..start].char_indices().rev().nth(SNIPPET_CONTEXT - 1).map_or(0, |(i, _)| i);
+    let to = note[end..].char_indices().nth(SNIPPET_CONTEXT).map_or(note.len(), |(i, _)| end + i);
+    let before = if from > 0 { "…" } else { "" };
+    let after = if to < note.len() { "…" } else { "" };
+    format!("{before}{}{after}", &note[from..to])
+}
+
+/// `text` split around the first case-insensitive match of `query`, with
+/// the match highlighted.
+fn highlighted<'a>(text: &'a str, query: &str, style: Style) -> Vec<Span<'a>> {
+    match find_match(text, query) {
+        Some(start) => {
+            let end = start + query.len();
+            vec![
+                Span::styled(&text[..start], style),
+                Span::styled(&text[start..end], style.fg(Color::Yellow).add_modifier(Modifier::BOLD | Modifier::UNDERLINED)),
+                Span::styled(&text[end..], style),
+            ]
+        }
+        _ => vec![Span::styled(text, style)],
+    }
+}
+
+pub fn render_search_screen(f: &mut Frame, area: Rect, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(area);
+
+    let focused = Style::default().fg(Color::Yellow);
+    let cursor = if app.search.in_results { "" } else { "▏" };
+    let input = Paragraph::new(format!("{}{cursor}", app.search.query.as_str())).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(if app.search.in_results { Style::default() } else { focused })
+            .title(app.text.get("search.title")),
+    );
+    f.render_widget(input, chunks[0]);
+
+    let query = app.search.query.as_str().trim();
+    let mut title = if query.is_empty() {
+        app.text.get("search.hint").to_string()
+    } else {
+        app.text.format("search.matches", &[("count", &app.search.hits.len())])
+    };
+    if !app.selection.ids.is_empty() {
+        title.push_str(&app.text.format("selection.count", &[("count", &app.selection.ids.len())]));
+    }
+    let snippets: Vec<Option<String>> = app.search.hits.iter().map(|hit| hit.note.as_deref().map(|note| snippet(note, query))).collect();
+    let items: Vec<ListItem> = app
+        .search
+        .hits
+        .iter()
+        .zip(&snippets)
+        .map(|(hit, snippet)| {
+            let when = dates::parse_timestamp(&hit.timestamp)
+                .map(|at| format!("{} {}", app.display_date(at.date()), dates::format_time(at.time(), app.config.ui.time_format, false)))
+                .unwrap_or_else(|| hit.timestamp.clone());
+            let mark = app.selection_mark(hit.id);
+            let mut spans = vec![Span::raw(format!("{mark}{when}  {}  ", app.format_count(&hit.exercise, hit.count)))];
+            spans.extend(highlighted(&hit.exercise, query, Style::default().fg(app.exercise_color(&hit.exercise))));
+            if let Some(snippet) = snippet {
+                spans.push(Span::raw("  "));
+                spans.extend(highlighted(snippet, query, Style::default().fg(Color::Gray)));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(if app.search.in_results { focused } else { Style::default() })
+        .title(title);
+    if let Some(message) = &app.message {
+        block = block.title(Title::from(message.as_str()).position(Position::Bottom));
+    }
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected((!app.search.hits.is_empty()).then_some(app.search.selected));
+    f.render_stateful_widget(list, chunks[1], &mut state);
+    selection::render_reassign_dialog(f, app);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::WorkoutStore;
+
+    /// A database with a note on a push-up set and a plain squat set.
+    fn noted() -> Database {
+        let db = Database::new(":memory:").unwrap();
+        db.add_workout("push-ups", 20, None, None).unwrap();
+        db.add_workout("squats", 30, None, None).unwrap();
+        let id: i64 = db.conn.query_row("SELECT id FROM workouts WHERE exercise_type = 'push-ups'", [], |row| row.get(0)).unwrap();
+        db.set_workout_note(id, Some("Left shoulder felt tight after the warm-up")).unwrap();
+        db
+    }
+
+    fn found(db: &Database, query: &str) -> Vec<String> {
+        db.search_sets(query).unwrap().into_iter().map(|hit| hit.exercise).collect()
+    }
+
+    #[test]
+    fn finds_sets_by_note_and_by_name() {
+        let db = noted();
+        assert!(migrations::has_notes_index(&db.conn).unwrap());
+        assert_eq!(found(&db, "SHOULDER"), ["push-ups"]);
+        assert_eq!(found(&db, "squ"), ["squats"]);
+        // Too short for the index, so LIKE looks it up
+        assert_eq!(found(&db, "ft"), ["push-ups"]);
+        assert!(found(&db, "\"knee").is_empty());
+
+        let id = db.search_sets("tight").unwrap()[0].id;
+        db.set_workout_note(id, None).unwrap();
+        assert!(found(&db, "tight").is_empty());
+    }
+
+    #[test]
+    fn existing_notes_are_indexed_when_the_index_is_built() {
+        let db = noted();
+        db.conn
+            .execute_batch(
+                "DROP TRIGGER workout_notes_inserted;
+                 DROP TRIGGER workout_notes_deleted;
+                 DROP TRIGGER workout_notes_updated;
+                 DROP TABLE workout_notes;",
+            )
+            .unwrap();
+        // Without the index the notes are still found, through LIKE
+        assert_eq!(found(&db, "50%"), Vec::<String>::new());
+        assert_eq!(found(&db, "warm-up"), ["push-ups"]);
+
+        let mut conn = db.conn;
+        migrations::migrate(&mut conn).unwrap();
+        assert!(migrations::has_notes_index(&conn).unwrap());
+        let indexed: i64 = conn
+            .query_row("SELECT COUNT(*) FROM workout_notes WHERE workout_notes MATCH '\"warm-up\"'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(indexed, 1);
+    }
+
+    #[test]
+    fn snippets_cut_long_notes_around_the_match() {
+        let note = "Slept badly, so the first two sets dragged, but the last one finally felt easy again";
+        assert_eq!(snippet(note, "FINALLY"), "…agged, but the last one finally felt easy again");
+        assert_eq!(snippet("short and sweet", "sweet"), "short and sweet");
+        assert_eq!(snippet(note, "push-ups"), "Slept badly, so the firs…");
+    }
+}
@@ -0,0 +1,244 @@
+//! Several sets picked at once in the history's day views and the search
+//! results: Space toggles the highlighted one and 'a' all those listed.
+//! The pick stays while moving between days and the search, until the
+//! history is left.
+//! The picked sets can then be moved to another exercise after a
+//! confirmation, logged as one entry for all of them, deleted together with
+//! Delete as one bulk delete that the data screen can undo, or exported.
+
+use anyhow::Result;
+use crossterm::event::KeyCode;
+use ratatui::{
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Clear, List, ListItem},
+    Frame,
+};
+use rusqlite::{params, OptionalExtension};
+use std::collections::BTreeSet;
+
+use crate::exercises::render_dialog;
+use crate::export_dialog::ExportSelection;
+use crate::{centered_rect, dates, App, Database, Screen};
+
+#[derive(Debug, Default)]
+pub struct Selection {
+    pub ids: BTreeSet<i64>,
+    /// Exercise being picked to move the selected sets to.
+    pub reassign: Option<Reassign>,
+}
+
+#[derive(Debug)]
+pub struct Reassign {
+    /// Index into the picker's exercises.
+    pub target: usize,
+    pub confirm: bool,
+}
+
+impl Selection {
+    pub fn toggle(&mut self, id: i64) {
+        if !self.ids.remove(&id) {
+            self.ids.insert(id);
+        }
+    }
+
+    /// Selects all of `ids`, or none of them when they all were already.
+    pub fn toggle_all(&mut self, ids: &[i64]) {
+        if ids.iter().all(|id| self.ids.contains(id)) {
+            for id in ids {
+                self.ids.remove(id);
+            }
+        } else {
+            self.ids.extend(ids);
+        }
+    }
+}
+
+impl Database {
+    /// Moves the sets to `exercise` in one transaction, logged as one entry
+    /// naming how many moved and the exercises they came from. Returns how
+    /// many changed.
+    pub fn reassign_workouts(&self, ids: &[i64], exercise: &str) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut moved = 0;
+        let mut from: Vec<String> = Vec::new();
+        {
+            let mut select = tx.prepare("SELECT exercise_type FROM workouts WHERE id = ?1 AND deleted_at IS NULL")?;
+            let mut update = tx.prepare("UPDATE workouts SET exercise_type = ?2 WHERE id = ?1")?;
+            for &id in ids {
+                let old: Option<String> = select.query_row([id], |row| row.get(0)).optional()?;
+                let Some(old) = old.filter(|old| old != exercise) else {
+                    continue;
+                };
+                update.execute(params![id, exercise])?;
+                if !from.contains(&old) {
+                    from.push(old);
+                }
+                moved += 1;
+            }
+        }
+        if moved > 0 {
+            let summary = format!("{moved} sets from {} to {exercise}", from.join(", "));
+            self.log_change(None, "bulk_exercise_type", None, Some(&summary))?;
+        }
+        tx.commit()?;
+        Ok(moved)
+    }
+}
+
+impl App {
+    /// The sets listed on the current screen, in order, and the highlighted
+    /// one.
+    fn listed_sets(&self) -> Result<(Vec<i64>, Option<i64>)> {
+        let (ids, highlighted) = match self.screen {
+            Screen::Search => (self.search.hits.iter().map(|hit| hit.id).collect::<Vec<_>>(), self.search.selected),
+            _ => (self.history_entries()?.iter().map(|entry| entry.id).collect(), self.history_entry),
+        };
+        let highlighted = ids.get(highlighted).copied();
+        Ok((ids, highlighted))
+    }
+
+    /// Space, 'a' and, with sets selected, 'r', 'x' and Delete; false for
+    /// any other key.
+    pub(crate) fn handle_selection_key(&mut self, key: KeyCode) -> Result<bool> {
+        let (listed, highlighted) = self.listed_sets()?;
+        match key {
+            KeyCode::Char(' ') => {
+                if let Some(id) = highlighted {
+                    self.selection.toggle(id);
+                }
+            }
+            KeyCode::Char('a') => self.selection.toggle_all(&listed),
+            KeyCode::Char('r') if !self.selection.ids.is_empty() => {
+                self.selection.reassign = Some(Reassign { target: 0, confirm: false });
+            }
+            KeyCode::Delete if !self.selection.ids.is_empty() => self.delete_selection()?,
+            KeyCode::Char('x') if !self.selection.ids.is_empty() => self.export_selection()?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    pub(crate) fn handle_reassign_input(&mut self, key: KeyCode) -> Result<bool> {
+        let names: Vec<String> = self.picker_exercises().iter().map(|e| e.name.clone()).collect();
+        let Some(reassign) = &mut self.selection.reassign else {
+            return Ok(false);
+        };
+        if reassign.confirm {
+            let target = names.get(reassign.target).cloned();
+            self.selection.reassign = None;
+            if key == KeyCode::Char('y')
+                && let Some(exercise) = target
+            {
+                self.reassign_selection(&exercise)?;
+            }
+            return Ok(false);
+        }
+        match key {
+            KeyCode::Esc => self.selection.reassign = None,
+            KeyCode::Up => reassign.target = reassign.target.saturating_sub(1),
+            KeyCode::Down if reassign.target + 1 < names.len() => reassign.target += 1,
+            KeyCode::Enter if !names.is_empty() => reassign.confirm = true,
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    fn reassign_selection(&mut self, exercise: &str) -> Result<()> {
+        let ids: Vec<i64> = std::mem::take(&mut self.selection.ids).into_iter().collect();
+        let moved = self.db.reassign_workouts(&ids, exercise)?;
+        self.last_used = self.db.last_used_exercises()?;
+        self.message = Some(self.text.format("selection.reassigned", &[("count", &moved), ("exercise", &exercise)]));
+        self.refresh_listed()
+    }
+
+    fn delete_selection(&mut self) -> Result<()> {
+        let ids: Vec<i64> = std::mem::take(&mut self.selection.ids).into_iter().collect();
+        let moved = self.db.trash_workouts(&ids)?;
+        self.data_screen.last_bulk_delete = Some(ids);
+        self.message = Some(self.text.format("selection.deleted", &[("count", &moved)]));
+        self.refresh_listed()
+    }
+
+    /// Opens the export screen for the selected sets.
+    fn export_selection(&mut self) -> Result<()> {
+        let sets: Vec<i64> = self.selection.ids.iter().copied().collect();
+        let mut days = Vec::new();
+        for &id in &sets {
+            if let Some((timestamp, _)) = self.db.workout_times(id)?
+                && let Some(at) = dates::parse_timestamp(&timestamp)
+            {
+                days.push(at.date());
+            }
+        }
+        let (Some(&from), Some(&to)) = (days.iter().min(), days.iter().max()) else {
+            return Ok(());
+        };
+        let back = if matches!(self.screen, Screen::Search) { Screen::Search } else { Screen::History };
+        self.open_export_selection(ExportSelection { sets, from, to }, back);
+        Ok(())
+    }
+
+    /// Keeps the list and its highlight in step after sets moved or went.
+    fn refresh_listed(&mut self) -> Result<()> {
+        self.forget_history_days();
+        if matches!(self.screen, Screen::Search) {
+            let query = self.search.query.as_str().trim();
+            self.search.hits = if query.is_empty() { Vec::new() } else { self.db.search_sets(query)? };
+            self.search.selected = self.search.selected.min(self.search.hits.len().saturating_sub(1));
+        } else {
+            let entries = self.history_entries()?.len();
+            self.history_entry = self.history_entry.min(entries.saturating_sub(1));
+            if entries == 0 {
+                self.selected_date = None;
+                self.history_selected = 0;
+                self.history_offset.set(0);
+            }
+        }
+        Ok(())
+    }
+
+    /// The mark before a listed set while any are selected.
+    pub(crate) fn selection_mark(&self, id: i64) -> &'static str {
+        match (self.selection.ids.is_empty(), self.selection.ids.contains(&id)) {
+            (true, _) => "",
+            (false, true) => "● ",
+            (false, false) => "○ ",
+        }
+    }
+}
+
+pub fn render_reassign_dialog(f: &mut Frame, app: &App) {
+    let Some(reassign) = &app.selection.reassign else {
+        return;
+    };
+    let exercises = app.picker_exercises();
+    let count = app.selection.ids.len();
+    if reassign.confirm {
+        if let Some(exercise) = exercises.get(reassign.target) {
+            render_dialog(
+                f,
+                app.text.get("selection.confirm_title"),
+                &app.text.format("selection.confirm", &[("count", &count), ("exercise", &exercise.display_name())]),
+            );
+        }
+        return;
+    }
+    let items: Vec<ListItem> = exercises
+        .iter()
+        .enumerate()
+        .map(|(i, exercise)| {
+            let style = if i == reassign.target {
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            ListItem::new(exercise.display_name()).style(style)
+        })
+        .collect();
+    let area = centered_rect(40, exercises.len() as u16 + 2, f.size());
+    let list = List::new(items).block(
+        Block::default().borders(Borders::ALL).title(app.text.format("selection.reassign_title", &[("count", &count)])),
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(list, area);
+}
@@ -0,0 +1,390 @@
+//! Every set in a date range as a flat list, for spreadsheets and scripts
+//! rather than reading: CSV and JSON keep the amounts as stored (reps,
+//! seconds or meters, and kg), the Markdown table shows them in the
+//! configured units. CSV is written in the configured dialect. When the
+//! export is limited to some exercises, each file says which near the top.
+//! Any of them can be anonymized (see `anonymize`). CSV and JSON carry each
+//! set's UUID, so importing them back (`import-csv`, `import-json`) finds
+//! the sets already here rather than adding them again. Their body
+//! measurements in the range go to a companion file next to them, in the
+//! same format, so the sets file keeps a single shape.
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::csv_import::{self, ImportFile, ImportSet, Reject};
+use crate::export::{ExportFormat, ExportOptions};
+use crate::units::{self, DistanceUnit};
+use crate::{dates, Database};
+
+const CSV_COLUMNS: [&str; 7] = ["timestamp", "exercise", "count", "unit", "rpe", "weight_kg", "uuid"];
+const MEASUREMENT_COLUMNS: [&str; 4] = ["date", "metric", "value", "unit"];
+
+/// A set as the flat exports write it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetRow {
+    pub timestamp: String,
+    pub exercise: String,
+    pub count: i64,
+    pub unit: String,
+    pub rpe: Option<i64>,
+    pub weight_kg: Option<f64>,
+    /// `None` once anonymized.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+}
+
+/// A body measurement as the flat exports write it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MeasurementRow {
+    pub date: String,
+    pub metric: String,
+    pub value: f64,
+    pub unit: String,
+}
+
+/// Where the measurements of a CSV or JSON export to `path` go:
+/// `workouts.csv` has them in `workouts.measurements.csv`.
+pub fn measurements_path(path: &Path) -> PathBuf {
+    let extension = path.extension().map_or(String::new(), |extension| extension.to_string_lossy().into_owned());
+    path.with_extension(format!("measurements.{extension}"))
+}
+
+/// The JSON export of some exercises' sets.
+#[derive(Serialize)]
+struct FilteredSets<'a> {
+    exercises: &'a [String],
+    from: String,
+    to: String,
+    sets: Vec<SetRow>,
+}
+
+/// Either form of the JSON export, read back.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SetsFile {
+    Sets(Vec<SetRow>),
+    Filtered { sets: Vec<SetRow> },
+}
+
+/// The sets a list export writes, with the range and the exercise filter
+/// as the file names them, anonymized when the options ask for it.
+struct SetList {
+    sets: Vec<SetRow>,
+    from: NaiveDate,
+    to: NaiveDate,
+    exercises: Vec<String>,
+}
+
+impl Database {
+    /// Sets between the option's dates, oldest first, of the options'
+    /// exercises and among its sets if given, with trashed ones only when
+    /// the options ask for them.
+    pub fn export_sets(&self, options: &ExportOptions) -> Result<Vec<SetRow>> {
+        let (from, to) = options.range();
+        let mut stmt = self.conn.prepare(
+            "SELECT w.timestamp, w.exercise_type, w.count, COALESCE(e.unit, 'reps'), w.rpe, w.weight, w.uuid, w.id
+             FROM workouts w
+             LEFT JOIN exercises e ON e.name = w.exercise_type
+             WHERE substr(w.timestamp, 1, 10) BETWEEN ?1 AND ?2 AND (?3 OR w.deleted_at IS NULL)
+             ORDER BY w.timestamp ASC, w.id ASC",
+        )?;
+        let rows = stmt
+            .query_map(rusqlite::params![from.to_string(), to.to_string(), options.include_deleted], |row| {
+                let set = SetRow {
+                    timestamp: row.get(0)?,
+                    exercise: row.get(1)?,
+                    count: row.get(2)?,
+                    unit: row.get(3)?,
+                    rpe: row.get(4)?,
+                    weight_kg: row.get(5)?,
+                    uuid: row.get(6)?,
+                };
+                Ok((row.get::<_, i64>(7)?, set))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows
+            .into_iter()
+            .filter(|(id, _)| options.sets.as_ref().is_none_or(|sets| sets.contains(id)))
+            .map(|(_, set)| set)
+            .filter(|set| options.exercises.is_empty() || options.exercises.contains(&set.exercise))
+            .collect())
+    }
+
+    /// How many sets an export in `format` would include; the SQL dump has
+    /// them all whatever the range.
+    pub fn count_export_sets(&self, format: ExportFormat, options: &ExportOptions) -> Result<i64> {
+        if format.filters_exercises() && (!options.exercises.is_empty() || options.sets.is_some()) {
+            return Ok(self.export_sets(options)?.len() as i64);
+        }
+        let (from, to) = if format.uses_range() {
+            let (from, to) = options.range();
+            (from.to_string(), to.to_string())
+        } else {
+            (String::new(), "9999-12-31".to_string())
+        };
+        Ok(self.conn.query_row(
+            "SELECT COUNT(*) FROM workouts
+             WHERE substr(timestamp, 1, 10) BETWEEN ?1 AND ?2 AND (?3 OR deleted_at IS NULL)",
+            rusqlite::params![from, to, options.include_deleted],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Measurements between the options' dates, oldest first.
+    pub fn export_measurements(&self, options: &ExportOptions) -> Result<Vec<MeasurementRow>> {
+        let (from, to) = options.range();
+        let mut stmt = self.conn.prepare(
+            "SELECT date, metric, value, unit FROM measurements
+             WHERE date BETWEEN ?1 AND ?2
+             ORDER BY date ASC, id ASC",
+        )?;
+        let rows = stmt
+            .query_map([from.to_string(), to.to_string()], |row| {
+                Ok(MeasurementRow {
+                    date: row.get(0)?,
+                    metric: row.get(1)?,
+                    value: row.get(2)?,
+                    unit: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Measurements as CSV in the options' dialect, with a header of their
+    /// own when the dialect has one.
+    pub fn write_measurements_csv(&self, out: &mut impl Write, measurements: &[MeasurementRow], options: &ExportOptions) -> Result<()> {
+        let dialect = &options.csv;
+        if dialect.header {
+            writeln!(out, "{}", dialect.join(&MEASUREMENT_COLUMNS.map(str::to_string)))?;
+        }
+        for measurement in measurements {
+            let fields = [
+                dialect.field(&measurement.date),
+                dialect.field(&measurement.metric),
+                dialect.number(measurement.value),
+                dialect.field(&measurement.unit),
+            ];
+            writeln!(out, "{}", dialect.join(&fields))?;
+        }
+        Ok(())
+    }
+
+    /// The sets and range `options` select, anonymized when they ask for
+    /// it, for the CSV, JSON and Markdown writers to format.
+    fn set_list(&self, options: &ExportOptions) -> Result<SetList> {
+        let mut sets = self.export_sets(options)?;
+        let (from, to) = options.range();
+        Ok(match &options.anonymize {
+            Some(anonymize) => {
+                let exercises = anonymize.apply(&mut sets, &options.exercises);
+                SetList { sets, from: anonymize.date(from), to: anonymize.date(to), exercises }
+            }
+            None => SetList { sets, from, to, exercises: options.exercises.clone() },
+        })
+    }
+
+    pub fn write_sets_csv(&self, out: &mut impl Write, options: &ExportOptions) -> Result<()> {
+        let dialect = &options.csv;
+        dialect.check()?;
+        let list = self.set_list(options)?;
+        if !list.exercises.is_empty() {
+            writeln!(out, "# exercises: {}; from {} to {}", list.exercises.join(", "), list.from, list.to)?;
+        }
+        if dialect.header {
+            writeln!(out, "{}", dialect.join(&CSV_COLUMNS.map(str::to_string)))?;
+        }
+        for set in list.sets {
+            let fields = [
+                dialect.field(&dialect.timestamp(&set.timestamp)),
+                dialect.field(&set.exercise),
+                set.count.to_string(),
+                dialect.field(&set.unit),
+                set.rpe.map(|rpe| rpe.to_string()).unwrap_or_default(),
+                set.weight_kg.map(|weight| dialect.number(weight)).unwrap_or_default(),
+                set.uuid.unwrap_or_default(),
+            ];
+            writeln!(out, "{}", dialect.join(&fields))?;
+        }
+        Ok(())
+    }
+
+    /// A JSON array of sets, or, when limited to some exercises, an object
+    /// naming them and the range with the array under `sets`.
+    pub fn write_sets_json(&self, out: &mut impl Write, options: &ExportOptions) -> Result<()> {
+        let list = self.set_list(options)?;
+        if list.exercises.is_empty() {
+            serde_json::to_writer_pretty(&mut *out, &list.sets)?;
+        } else {
+            let filtered = FilteredSets {
+                exercises: &list.exercises,
+                from: list.from.to_string(),
+                to: list.to.to_string(),
+                sets: list.sets,
+            };
+            serde_json::to_writer_pretty(&mut *out, &filtered)?;
+        }
+        writeln!(out)?;
+        Ok(())
+    }
+
+    pub fn write_sets_markdown(&self, out: &mut impl Write, options: &ExportOptions) -> Result<()> {
+        let list = self.set_list(options)?;
+        let text = &options.text;
+        writeln!(out, "# {}\n", text.format("export.markdown_title", &[("from", &list.from), ("to", &list.to)]))?;
+        if !list.exercises.is_empty() {
+            writeln!(out, "{}\n", text.format("export.markdown_exercises", &[("exercises", &list.exercises.join(", "))]))?;
+        }
+        writeln!(
+            out,
+            "| {} | {} | {} | {} | RPE |",
+            text.get("export.markdown_time"),
+            text.get("export.markdown_exercise"),
+            text.get("export.markdown_amount"),
+            text.get("export.markdown_weight")
+        )?;
+        writeln!(out, "| --- | --- | ---: | ---: | ---: |")?;
+        for set in list.sets {
+            writeln!(
+                out,
+                "| {} | {} | {} | {} | {} |",
+                set.timestamp,
+                set.exercise.replace('|', "\\|"),
+                units::format_amount(set.count, &set.unit, options.distance),
+                set.weight_kg.map_or("–".to_string(), |weight| units::format_weight(weight, options.weight)),
+                set.rpe.map_or("–".to_string(), |rpe| rpe.to_string())
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads a JSON export of sets back for `import-json`. Sets are numbered by
+/// their place in the file, and any that can't be read are rejected as
+/// their JSON. Amounts go in as the unit the file gives them in.
+pub fn read_sets_json(path: &Path, distance: DistanceUnit) -> Result<ImportFile> {
+    let text = csv_import::read_file(path)?;
+    let sets = match serde_json::from_str(&text).with_context(|| format!("{} is not a JSON export of sets", path.display()))? {
+        SetsFile::Sets(sets) | SetsFile::Filtered { sets } => sets,
+    };
+    let mut file = ImportFile {
+        header: "set".to_string(),
+        delimiter: ',',
+        sets: Vec::new(),
+        rejects: Vec::new(),
+    };
+    for (i, set) in sets.into_iter().enumerate() {
+        let raw = format!("\"{}\"", serde_json::to_string(&set)?.replace('"', "\"\""));
+        let parsed = (|| {
+            let at = dates::parse_timestamp(&set.timestamp).ok_or_else(|| format!("unreadable timestamp {:?}", set.timestamp))?;
+            let count = Some(set.count);
+            Ok(ImportSet {
+                line: i + 1,
+                raw: raw.clone(),
+                at,
+                exercise: set.exercise.clone(),
+                category: None,
+                reps: count.filter(|_| set.unit != units::SECONDS && set.unit != units::DISTANCE),
+                weight: set.weight_kg,
+                seconds: count.filter(|_| set.unit == units::SECONDS),
+                distance: count.filter(|_| set.unit == units::DISTANCE).map(|meters| meters as f64 / distance.meters_per_unit()),
+                rpe: set.rpe.map(|rpe| rpe as f64),
+                uuid: csv_import::parse_uuid(set.uuid.as_deref().unwrap_or_default())?,
+            })
+        })();
+        match parsed {
+            Ok(set) => file.sets.push(set),
+            Err(reason) => file.rejects.push(Reject { line: i + 1, raw, reason }),
+        }
+    }
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn logged() -> Database {
+        let db = Database::new(":memory:").unwrap();
+        db.add_exercise("plank").unwrap();
+        db.set_exercise_unit("plank", units::SECONDS).unwrap();
+        for (timestamp, exercise, count, weight) in [
+            ("2024-05-01 08:00:00", "squats", 20, Some(62.5)),
+            ("2024-05-01 08:00:00", "squats", 20, Some(62.5)),
+            ("2024-05-02 09:30:00", "plank", 90, None),
+        ] {
+            db.conn
+                .execute(
+                    "INSERT INTO workouts (exercise_type, count, timestamp, weight) VALUES (?1, ?2, ?3, ?4)",
+                    rusqlite::params![exercise, count, timestamp, weight],
+                )
+                .unwrap();
+        }
+        db
+    }
+
+    fn options() -> ExportOptions {
+        ExportOptions {
+            from: NaiveDate::from_ymd_opt(2024, 5, 1),
+            to: NaiveDate::from_ymd_opt(2024, 5, 2),
+            ..ExportOptions::default()
+        }
+    }
+
+    #[test]
+    fn measurements_go_to_a_companion_file() {
+        let db = logged();
+        db.add_measurement("2024-04-30", "waist", 81.0, "cm").unwrap();
+        db.add_measurement("2024-05-01", "waist", 80.5, "cm").unwrap();
+        db.add_measurement("2024-05-02", "arm, left", 35.0, "cm").unwrap();
+        let dir = std::env::temp_dir().join(format!("fitness-tracker-measurements-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let summary = db.export(ExportFormat::Csv, &dir.join("sets.csv"), &options()).unwrap();
+        assert_eq!(summary.measurements, Some(dir.join("sets.measurements.csv")));
+        let csv = std::fs::read_to_string(dir.join("sets.measurements.csv")).unwrap();
+        assert_eq!(csv, "date,metric,value,unit\n2024-05-01,waist,80.5,cm\n2024-05-02,\"arm, left\",35,cm\n");
+        assert!(std::fs::read_to_string(dir.join("sets.csv")).unwrap().lines().all(|line| !line.contains("waist")));
+
+        db.export(ExportFormat::Json, &dir.join("sets.json"), &options()).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(dir.join("sets.measurements.json")).unwrap()).unwrap();
+        assert_eq!(json[0], serde_json::json!({"date": "2024-05-01", "metric": "waist", "value": 80.5, "unit": "cm"}));
+        assert_eq!(json.as_array().unwrap().len(), 2);
+
+        // Exports of some of the sets leave them out
+        let some = ExportOptions { exercises: vec!["squats".to_string()], ..options() };
+        assert_eq!(db.export(ExportFormat::Csv, &dir.join("squats.csv"), &some).unwrap().measurements, None);
+        assert!(!dir.join("squats.measurements.csv").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn json_round_trips_with_uuids() {
+        let source = logged();
+        let path = std::env::temp_dir().join(format!("fitness-tracker-sets-{}.json", std::process::id()));
+        for exercises in [vec![], vec!["squats".to_string(), "plank".to_string()]] {
+            let mut out = Vec::new();
+            source.write_sets_json(&mut out, &ExportOptions { exercises, ..options() }).unwrap();
+            std::fs::write(&path, out).unwrap();
+            let file = read_sets_json(&path, DistanceUnit::Km).unwrap();
+            assert!(file.rejects.is_empty(), "{:?}", file.rejects);
+
+            let target = Database::new(":memory:").unwrap();
+            target.add_exercise("plank").unwrap();
+            target.set_exercise_unit("plank", units::SECONDS).unwrap();
+            // Two equal sets in the same second are still two sets
+            assert_eq!(target.import_sets(&file.sets, &HashMap::new(), DistanceUnit::Km, "JSON").unwrap().added(), 3);
+            let exported = source.export_sets(&options()).unwrap();
+            assert!(exported.iter().all(|set| set.uuid.is_some()));
+            assert_eq!(target.export_sets(&options()).unwrap(), exported);
+            let again = target.import_sets(&file.sets, &HashMap::new(), DistanceUnit::Km, "JSON").unwrap();
+            assert_eq!((again.added(), again.duplicates), (0, 3));
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+}
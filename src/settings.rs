@@ -0,0 +1,386 @@
+//! The settings screen. Options changed here are stored in the database's
+//! `settings` table and override config.toml, which keeps acting as the
+//! defaults; resetting an option just forgets the stored value.
+
+use anyhow::Result;
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+    Frame,
+};
+use serde::de::{
+    value::{Error as ValueError, StrDeserializer},
+    DeserializeOwned, IntoDeserializer,
+};
+use std::collections::HashMap;
+
+use crate::config::Config;
+use crate::exercises::render_dialog;
+use crate::i18n::Messages;
+use crate::input::NumericInput;
+use crate::tags::{self, TagsState};
+use crate::{App, Database, Screen};
+
+/// How a setting's value is picked.
+pub enum Kind {
+    /// One of these values, as they're written in config.toml.
+    Choice(&'static [&'static str]),
+    /// A whole number up to this.
+    Number(u64),
+    /// One of the exercises in the picker, or none.
+    Exercise,
+}
+
+/// An option on the settings screen. Its key is the option's path in
+/// config.toml and the label's i18n key after `settings.`.
+pub struct Setting {
+    pub key: &'static str,
+    pub kind: Kind,
+    get: fn(&Config) -> String,
+    /// Takes a stored value; one that doesn't parse leaves the config as it is.
+    set: fn(&mut Config, &str),
+}
+
+const ON_OFF: &[&str] = &["on", "off"];
+
+pub const SETTINGS: &[Setting] = &[
+    Setting {
+        key: "language",
+        kind: Kind::Choice(&["en", "uk"]),
+        // Other codes fall back to English, like the catalogs do
+        get: |c| if matches!(c.language.to_lowercase().as_str(), "uk" | "ua") { "uk" } else { "en" }.to_string(),
+        set: |c, v| c.language = v.to_string(),
+    },
+    Setting {
+        key: "ui.monochrome",
+        kind: Kind::Choice(&["color", "monochrome"]),
+        get: |c| if c.ui.monochrome { "monochrome" } else { "color" }.to_string(),
+        set: |c, v| c.ui.monochrome = v == "monochrome",
+    },
+    Setting {
+        key: "week_starts_on",
+        kind: Kind::Choice(&["monday", "sunday"]),
+        get: |c| name_of(&["monday", "sunday"], &c.week_starts_on),
+        set: |c, v| c.week_starts_on = parse(v).unwrap_or(c.week_starts_on),
+    },
+    Setting {
+        key: "ui.date_format",
+        kind: Kind::Choice(&["iso", "dmy", "mdy", "short", "relative"]),
+        get: |c| name_of(&["iso", "dmy", "mdy", "short", "relative"], &c.ui.date_format),
+        set: |c, v| c.ui.date_format = parse(v).unwrap_or(c.ui.date_format),
+    },
+    Setting {
+        key: "ui.time_format",
+        kind: Kind::Choice(&["24h", "12h"]),
+        get: |c| name_of(&["24h", "12h"], &c.ui.time_format),
+        set: |c, v| c.ui.time_format = parse(v).unwrap_or(c.ui.time_format),
+    },
+    Setting {
+        key: "units.distance",
+        kind: Kind::Choice(&["km", "miles"]),
+        get: |c| name_of(&["km", "miles"], &c.units.distance),
+        set: |c, v| c.units.distance = parse(v).unwrap_or(c.units.distance),
+    },
+    Setting {
+        key: "units.weight",
+        kind: Kind::Choice(&["kg", "lbs"]),
+        get: |c| name_of(&["kg", "lbs"], &c.units.weight),
+        set: |c, v| c.units.weight = parse(v).unwrap_or(c.units.weight),
+    },
+    Setting {
+        key: "ui.default_exercise",
+        kind: Kind::Exercise,
+        get: |c| c.ui.default_exercise.clone().unwrap_or_default(),
+        set: |c, v| c.ui.default_exercise = (!v.is_empty()).then(|| v.to_string()),
+    },
+    Setting {
+        key: "ui.startup_screen",
+        kind: Kind::Choice(&["main", "add", "history", "stats"]),
+        get: |c| c.ui.startup_screen.clone(),
+        set: |c, v| c.ui.startup_screen = v.to_string(),
+    },
+    Setting {
+        key: "ui.restore_state",
+        kind: Kind::Choice(ON_OFF),
+        get: |c| on_off(c.ui.restore_state),
+        set: |c, v| c.ui.restore_state = v == "on",
+    },
+    Setting {
+        key: "ui.exit_summary",
+        kind: Kind::Choice(ON_OFF),
+        get: |c| on_off(c.ui.exit_summary),
+        set: |c, v| c.ui.exit_summary = v == "on",
+    },
+    Setting {
+        key: "timer.rest_seconds",
+        kind: Kind::Number(3600),
+        get: |c| c.timer.rest_seconds.to_string(),
+        set: |c, v| c.timer.rest_seconds = v.parse().unwrap_or(c.timer.rest_seconds),
+    },
+    Setting {
+        key: "notify.enabled",
+        kind: Kind::Choice(ON_OFF),
+        get: |c| on_off(c.notify.enabled),
+        set: |c, v| c.notify.enabled = v == "on",
+    },
+    Setting {
+        key: "mood.prompt",
+        kind: Kind::Choice(ON_OFF),
+        get: |c| on_off(c.mood.prompt),
+        set: |c, v| c.mood.prompt = v == "on",
+    },
+    Setting {
+        key: "goals.deadline_in_status",
+        kind: Kind::Choice(ON_OFF),
+        get: |c| on_off(c.goals.deadline_in_status),
+        set: |c, v| c.goals.deadline_in_status = v == "on",
+    },
+];
+
+/// Reads a config enum from the name it has in config.toml.
+fn parse<T: DeserializeOwned>(value: &str) -> Option<T> {
+    let de: StrDeserializer<ValueError> = value.into_deserializer();
+    T::deserialize(de).ok()
+}
+
+/// The config.toml name of an enum value, out of its possible `names`.
+fn name_of<T: DeserializeOwned + PartialEq>(names: &[&str], value: &T) -> String {
+    names
+        .iter()
+        .find(|name| parse::<T>(name).as_ref() == Some(value))
+        .map_or_else(String::new, |name| name.to_string())
+}
+
+fn on_off(value: bool) -> String {
+    ON_OFF[if value { 0 } else { 1 }].to_string()
+}
+
+/// Applies stored settings on top of `config`. Keys no longer offered are
+/// ignored.
+pub fn apply(config: &mut Config, saved: &HashMap<String, String>) {
+    for setting in SETTINGS {
+        if let Some(value) = saved.get(setting.key) {
+            (setting.set)(config, value);
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SettingsState {
+    pub selected: usize,
+    pub editing: Option<Edit>,
+    pub confirm_reset: bool,
+    /// The tag list shown in place of the settings.
+    pub tags: Option<TagsState>,
+}
+
+/// A value being changed in its row.
+#[derive(Debug)]
+pub enum Edit {
+    Pick { options: Vec<String>, index: usize },
+    Number(NumericInput),
+}
+
+impl Database {
+    pub fn get_settings(&self) -> Result<HashMap<String, String>> {
+        let mut stmt = self.conn.prepare("SELECT key, value FROM settings")?;
+        let settings = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        Ok(settings)
+    }
+
+    /// Stores a setting or, with `None`, goes back to the config's value.
+    pub fn save_setting(&self, key: &str, value: Option<&str>) -> Result<()> {
+        match value {
+            Some(value) => self
+                .conn
+                .execute("INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)", [key, value])?,
+            None => self.conn.execute("DELETE FROM settings WHERE key = ?1", [key])?,
+        };
+        Ok(())
+    }
+
+    pub fn clear_settings(&self) -> Result<()> {
+        self.conn.execute("DELETE FROM settings", [])?;
+        Ok(())
+    }
+}
+
+impl App {
+    /// Rebuilds the effective config from config.toml and the stored
+    /// settings, so a change shows on the next frame.
+    pub(crate) fn apply_settings(&mut self) -> Result<()> {
+        let mut config = self.file_config.clone();
+        apply(&mut config, &self.db.get_settings()?);
+        let default_changed = config.ui.default_exercise != self.config.ui.default_exercise;
+        self.text = Messages::for_language(&config.language);
+        self.config = config;
+        if default_changed {
+            self.select_default_exercise();
+        }
+        Ok(())
+    }
+
+    /// Preselects the configured default exercise on the add screen.
+    pub(crate) fn select_default_exercise(&mut self) {
+        if let Some(name) = &self.config.ui.default_exercise
+            && let Some(i) = self.picker_exercises().iter().position(|e| e.name == *name)
+        {
+            self.selected_exercise = i;
+            self.prefill_count();
+        }
+    }
+
+    pub(crate) fn handle_settings_input(&mut self, key: KeyCode) -> Result<bool> {
+        if self.settings_screen.tags.is_some() {
+            return self.handle_tags_input(key);
+        }
+        let state = &mut self.settings_screen;
+        let Some(setting) = SETTINGS.get(state.selected) else {
+            return Ok(false);
+        };
+
+        if state.confirm_reset {
+            state.confirm_reset = false;
+            if key == KeyCode::Char('y') {
+                self.db.clear_settings()?;
+                self.apply_settings()?;
+                self.message = Some(self.text.get("settings.reset_all").to_string());
+            }
+            return Ok(false);
+        }
+
+        if let Some(edit) = &mut state.editing {
+            match (edit, key) {
+                (_, KeyCode::Esc) => state.editing = None,
+                (Edit::Pick { options, index }, KeyCode::Left) => *index = (*index + options.len() - 1) % options.len(),
+                (Edit::Pick { options, index }, KeyCode::Right) => *index = (*index + 1) % options.len(),
+                (Edit::Pick { options, index }, KeyCode::Enter) => {
+                    let value = options[*index].clone();
+                    state.editing = None;
+                    self.change_setting(setting, &value)?;
+                }
+                (Edit::Number(input), KeyCode::Enter) => {
+                    let value = input.as_str().to_string();
+                    state.editing = None;
+                    match value.parse::<u64>() {
+                        Ok(number) if matches!(setting.kind, Kind::Number(max) if number <= max) => {
+                            self.change_setting(setting, &value)?
+                        }
+                        _ => self.message = Some(self.text.get("settings.invalid").to_string()),
+                    }
+                }
+                (Edit::Number(input), _) => {
+                    input.handle_key(key);
+                }
+                _ => {}
+            }
+            return Ok(false);
+        }
+
+        match key {
+            KeyCode::Esc => self.screen = Screen::Main,
+            KeyCode::Up => state.selected = state.selected.saturating_sub(1),
+            KeyCode::Down if state.selected + 1 < SETTINGS.len() => state.selected += 1,
+            KeyCode::Enter => {
+                let current = (setting.get)(&self.config);
+                let options: Vec<String> = match setting.kind {
+                    Kind::Choice(options) => options.iter().map(|o| o.to_string()).collect(),
+                    Kind::Exercise => std::iter::once(String::new())
+                        .chain(self.picker_exercises().iter().map(|e| e.name.clone()))
+                        .collect(),
+                    Kind::Number(_) => {
+                        let mut input = NumericInput::default();
+                        input.set(current);
+                        self.settings_screen.editing = Some(Edit::Number(input));
+                        return Ok(false);
+                    }
+                };
+                let index = options.iter().position(|o| *o == current).unwrap_or(0);
+                self.settings_screen.editing = Some(Edit::Pick { options, index });
+            }
+            KeyCode::Char('r') => {
+                self.db.save_setting(setting.key, None)?;
+                self.apply_settings()?;
+                self.message = Some(self.text.format("settings.reset", &[("setting", &self.text.get(&label(setting)))]));
+            }
+            KeyCode::Char('R') => state.confirm_reset = true,
+            KeyCode::Char('i') => self.open_about(true),
+            KeyCode::Char('w') => self.test_webhook(),
+            KeyCode::Char('t') => state.tags = Some(TagsState::default()),
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    fn change_setting(&mut self, setting: &Setting, value: &str) -> Result<()> {
+        self.db.save_setting(setting.key, Some(value))?;
+        self.apply_settings()?;
+        self.message = Some(self.text.format("settings.saved", &[("setting", &self.text.get(&label(setting)))]));
+        Ok(())
+    }
+}
+
+fn label(setting: &Setting) -> String {
+    format!("settings.{}", setting.key)
+}
+
+pub fn render_settings_screen(f: &mut Frame, area: Rect, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(3)])
+        .split(area);
+
+    let saved = app.db.get_settings().unwrap_or_default();
+    let state = &app.settings_screen;
+    let rows: Vec<Row> = SETTINGS
+        .iter()
+        .enumerate()
+        .map(|(i, setting)| {
+            let mut style = Style::default();
+            if i == state.selected {
+                style = style.add_modifier(Modifier::BOLD | Modifier::REVERSED);
+            }
+            let shown = |value: &str| match (&setting.kind, value) {
+                (Kind::Exercise, "") => app.text.get("settings.none").to_string(),
+                _ => value.to_string(),
+            };
+            let value = match &state.editing {
+                Some(Edit::Pick { options, index }) if i == state.selected => format!("◀ {} ▶", shown(&options[*index])),
+                Some(Edit::Number(input)) if i == state.selected => format!("{}▏", input.as_str()),
+                _ => shown(&(setting.get)(&app.config)),
+            };
+            let source = if saved.contains_key(setting.key) { "settings.saved_here" } else { "settings.from_config" };
+            Row::new(vec![
+                Cell::from(app.text.get(&label(setting)).to_string()),
+                Cell::from(value).style(Style::default().fg(Color::Cyan)),
+                Cell::from(app.text.get(source).to_string()).style(Style::default().fg(Color::DarkGray)),
+            ])
+            .style(style)
+        })
+        .collect();
+
+    let table = Table::new(rows, [Constraint::Percentage(40), Constraint::Percentage(35), Constraint::Min(0)])
+        .header(
+            Row::new(vec![app.text.get("settings.setting"), app.text.get("settings.value"), app.text.get("settings.source")])
+                .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("settings.title")));
+    if state.tags.is_some() {
+        tags::render_tags_panel(f, chunks[0], app);
+    } else {
+        f.render_widget(table, chunks[0]);
+    }
+
+    let status = Paragraph::new(app.message.clone().unwrap_or_default())
+        .style(Style::default().fg(Color::Green))
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("common.status")));
+    f.render_widget(status, chunks[1]);
+
+    if state.confirm_reset {
+        render_dialog(f, app.text.get("settings.reset_title"), app.text.get("settings.reset_confirm"));
+    }
+}
@@ -0,0 +1,371 @@
+//! Moving a setup to another installation without its workouts: exercise
+//! definitions with their aliases, colors, units, default counts and set
+//! schemes, daily goals, tags and the settings changed in the app go into
+//! one TOML file (JSON when the name ends in .json). Importing merges it
+//! in: anything missing here is added, and where both sides have a value
+//! that differs, this installation's is kept and the difference reported.
+//! Keys aren't rebindable, so there are none to carry over.
+
+use anyhow::{bail, Context, Result};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::i18n::Messages;
+use crate::settings::SETTINGS;
+use crate::{App, Database};
+
+/// Version written into new files; newer ones are refused.
+const VERSION: u32 = 1;
+
+pub fn default_path() -> PathBuf {
+    PathBuf::from("fitness_tracker-setup.toml")
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SetupFile {
+    pub version: u32,
+    /// Settings changed in the app, by their config.toml path.
+    pub settings: BTreeMap<String, String>,
+    /// Daily target per exercise.
+    pub goals: BTreeMap<String, i64>,
+    /// Alternate name -> exercise.
+    pub aliases: BTreeMap<String, String>,
+    pub tags: Vec<String>,
+    /// In picker order.
+    pub exercises: Vec<ExerciseDef>,
+}
+
+/// An exercise's row, columns as stored.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExerciseDef {
+    pub name: String,
+    pub category: Option<String>,
+    pub color: Option<String>,
+    pub unit: String,
+    pub default_count: Option<i64>,
+    pub archived: bool,
+    pub favorite: bool,
+    pub template_sets: Option<i64>,
+    pub template_count: Option<i64>,
+    pub progression: Option<String>,
+    pub progression_base: Option<i64>,
+    pub progression_start: Option<String>,
+}
+
+/// A value both sides have but disagree on; this installation's stays.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Conflict {
+    Unit { exercise: String, here: String, file: String },
+    /// Some other column of the exercise, by its i18n key.
+    Field { exercise: String, field: &'static str },
+    Alias { alias: String, here: String, file: String },
+    Goal { exercise: String, here: i64, file: i64 },
+    Setting { key: String, here: String, file: String },
+}
+
+impl Conflict {
+    pub fn describe(&self, text: &Messages) -> String {
+        match self {
+            Conflict::Unit { exercise, here, file } => {
+                text.format("setup_file.conflict_unit", &[("exercise", exercise), ("here", here), ("file", file)])
+            }
+            Conflict::Field { exercise, field } => {
+                text.format("setup_file.conflict_field", &[("exercise", exercise), ("field", &text.get(field))])
+            }
+            Conflict::Alias { alias, here, file } => {
+                text.format("setup_file.conflict_alias", &[("alias", alias), ("here", here), ("file", file)])
+            }
+            Conflict::Goal { exercise, here, file } => {
+                text.format("setup_file.conflict_goal", &[("exercise", exercise), ("here", here), ("file", file)])
+            }
+            Conflict::Setting { key, here, file } => {
+                text.format("setup_file.conflict_setting", &[("setting", key), ("here", here), ("file", file)])
+            }
+        }
+    }
+}
+
+/// What an import added, and what it left alone.
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub exercises: usize,
+    /// Blank columns of existing exercises filled in from the file.
+    pub filled: usize,
+    pub aliases: usize,
+    pub goals: usize,
+    pub tags: usize,
+    pub settings: usize,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl ImportReport {
+    pub fn summary(&self, text: &Messages) -> String {
+        text.format(
+            "setup_file.imported",
+            &[
+                ("exercises", &self.exercises),
+                ("filled", &self.filled),
+                ("aliases", &self.aliases),
+                ("goals", &self.goals),
+                ("tags", &self.tags),
+                ("settings", &self.settings),
+                ("conflicts", &self.conflicts.len()),
+            ],
+        )
+    }
+}
+
+/// Whether the settings screen still offers `key`.
+fn is_setting(key: &str) -> bool {
+    SETTINGS.iter().any(|setting| setting.key == key)
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+}
+
+impl Database {
+    pub fn setup_file(&self) -> Result<SetupFile> {
+        let mut stmt = self.conn.prepare(
+            "SELECT name, category, color, unit, default_count, archived, favorite, template_sets, template_count,
+                    progression, progression_base, progression_start
+             FROM exercises ORDER BY position ASC, name ASC",
+        )?;
+        let exercises = stmt
+            .query_map([], |row| {
+                Ok(ExerciseDef {
+                    name: row.get(0)?,
+                    category: row.get(1)?,
+                    color: row.get(2)?,
+                    unit: row.get(3)?,
+                    default_count: row.get(4)?,
+                    archived: row.get(5)?,
+                    favorite: row.get(6)?,
+                    template_sets: row.get(7)?,
+                    template_count: row.get(8)?,
+                    progression: row.get(9)?,
+                    progression_base: row.get(10)?,
+                    progression_start: row.get(11)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut stmt = self.conn.prepare("SELECT name FROM tags ORDER BY name")?;
+        let tags = stmt.query_map([], |row| row.get(0))?.collect::<Result<Vec<_>, _>>()?;
+        Ok(SetupFile {
+            version: VERSION,
+            settings: self.get_settings()?.into_iter().filter(|(key, _)| is_setting(key)).collect(),
+            goals: self.get_goals()?.into_iter().collect(),
+            aliases: self.get_exercise_aliases()?.into_iter().collect(),
+            tags,
+            exercises,
+        })
+    }
+
+    pub fn write_setup_file(&self, path: &Path) -> Result<()> {
+        let setup = self.setup_file()?;
+        let text = if is_json(path) { serde_json::to_string_pretty(&setup)? } else { toml::to_string(&setup)? };
+        fs::write(path, text).with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(())
+    }
+
+    pub fn import_setup_file(&self, path: &Path) -> Result<ImportReport> {
+        let text = fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+        let setup: SetupFile = if is_json(path) {
+            serde_json::from_str(&text).with_context(|| format!("{} is not a setup file", path.display()))?
+        } else {
+            toml::from_str(&text).with_context(|| format!("{} is not a setup file", path.display()))?
+        };
+        if setup.version > VERSION {
+            bail!("{} was written by a newer version of the tracker", path.display());
+        }
+        self.import_setup(&setup)
+    }
+
+    pub fn import_setup(&self, setup: &SetupFile) -> Result<ImportReport> {
+        let mut report = ImportReport::default();
+        let tx = self.conn.unchecked_transaction()?;
+
+        let here: BTreeMap<String, ExerciseDef> =
+            self.setup_file()?.exercises.into_iter().map(|e| (e.name.clone(), e)).collect();
+        let mut position: i64 = tx.query_row("SELECT COALESCE(MAX(position), 0) FROM exercises", [], |row| row.get(0))?;
+        for def in setup.exercises.iter().filter(|def| !def.name.is_empty()) {
+            let Some(existing) = here.get(&def.name) else {
+                position += 1;
+                tx.execute(
+                    "INSERT INTO exercises (name, category, position, color, unit, default_count, archived, favorite,
+                         template_sets, template_count, progression, progression_base, progression_start)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                    params![
+                        def.name,
+                        def.category,
+                        position,
+                        def.color,
+                        if def.unit.is_empty() { "reps" } else { def.unit.as_str() },
+                        def.default_count,
+                        def.archived,
+                        def.favorite,
+                        def.template_sets,
+                        def.template_count,
+                        def.progression,
+                        def.progression_base,
+                        def.progression_start,
+                    ],
+                )?;
+                report.exercises += 1;
+                continue;
+            };
+            if !def.unit.is_empty() && def.unit != existing.unit {
+                report.conflicts.push(Conflict::Unit {
+                    exercise: def.name.clone(),
+                    here: existing.unit.clone(),
+                    file: def.unit.clone(),
+                });
+                // A default count or scheme means something else in another unit
+                continue;
+            }
+            let mut fill = |field: &'static str, columns: &str, blank_here: bool, differs: bool, values: &[&dyn rusqlite::ToSql]| {
+                if blank_here {
+                    tx.execute(&format!("UPDATE exercises SET {columns} WHERE name = ?1"), values)?;
+                    report.filled += 1;
+                } else if differs {
+                    report.conflicts.push(Conflict::Field { exercise: def.name.clone(), field });
+                }
+                anyhow::Ok(())
+            };
+            if def.category.is_some() {
+                fill(
+                    "setup_file.field_category",
+                    "category = ?2",
+                    existing.category.is_none(),
+                    existing.category != def.category,
+                    params![def.name, def.category],
+                )?;
+            }
+            if def.color.is_some() {
+                fill(
+                    "setup_file.field_color",
+                    "color = ?2",
+                    existing.color.is_none(),
+                    existing.color != def.color,
+                    params![def.name, def.color],
+                )?;
+            }
+            if def.default_count.is_some() {
+                fill(
+                    "setup_file.field_default_count",
+                    "default_count = ?2",
+                    existing.default_count.is_none(),
+                    existing.default_count != def.default_count,
+                    params![def.name, def.default_count],
+                )?;
+            }
+            if def.template_sets.is_some() {
+                fill(
+                    "setup_file.field_template",
+                    "template_sets = ?2, template_count = ?3",
+                    existing.template_sets.is_none(),
+                    (existing.template_sets, existing.template_count) != (def.template_sets, def.template_count),
+                    params![def.name, def.template_sets, def.template_count],
+                )?;
+            }
+            if def.progression.is_some() {
+                fill(
+                    "setup_file.field_progression",
+                    "progression = ?2, progression_base = ?3, progression_start = ?4",
+                    existing.progression.is_none(),
+                    (&existing.progression, existing.progression_base, &existing.progression_start)
+                        != (&def.progression, def.progression_base, &def.progression_start),
+                    params![def.name, def.progression, def.progression_base, def.progression_start],
+                )?;
+            }
+        }
+
+        for (alias, exercise) in &setup.aliases {
+            let current: Option<String> = tx
+                .query_row("SELECT exercise FROM exercise_aliases WHERE alias = ?1", [alias], |row| row.get(0))
+                .optional()?;
+            match current {
+                None => {
+                    tx.execute("INSERT INTO exercise_aliases (alias, exercise) VALUES (?1, ?2)", [alias, exercise])?;
+                    report.aliases += 1;
+                }
+                Some(here) if here != *exercise => {
+                    report.conflicts.push(Conflict::Alias { alias: alias.clone(), here, file: exercise.clone() })
+                }
+                Some(_) => {}
+            }
+        }
+
+        let goals = self.get_goals()?;
+        for (exercise, target) in &setup.goals {
+            match goals.get(exercise) {
+                None => {
+                    tx.execute("INSERT INTO goals (exercise, daily_target) VALUES (?1, ?2)", params![exercise, target])?;
+                    report.goals += 1;
+                }
+                Some(here) if here != target => {
+                    report.conflicts.push(Conflict::Goal { exercise: exercise.clone(), here: *here, file: *target })
+                }
+                Some(_) => {}
+            }
+        }
+
+        for tag in &setup.tags {
+            report.tags += tx.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", [tag])?;
+        }
+
+        let settings = self.get_settings()?;
+        for (key, value) in setup.settings.iter().filter(|(key, _)| is_setting(key)) {
+            match settings.get(key) {
+                None => {
+                    tx.execute("INSERT INTO settings (key, value) VALUES (?1, ?2)", [key, value])?;
+                    report.settings += 1;
+                }
+                Some(here) if here != value => report.conflicts.push(Conflict::Setting {
+                    key: key.clone(),
+                    here: here.clone(),
+                    file: value.clone(),
+                }),
+                Some(_) => {}
+            }
+        }
+        tx.commit()?;
+        Ok(report)
+    }
+}
+
+impl App {
+    /// Writes the setup file; the outcome goes to the status line.
+    pub(crate) fn export_setup(&mut self, path: &Path) {
+        self.message = Some(match self.db.write_setup_file(path) {
+            Ok(()) => self.text.format("setup_file.exported", &[("path", &path.display())]),
+            Err(e) => self.text.format("setup_file.failed", &[("error", &format!("{e:#}"))]),
+        });
+    }
+
+    /// Merges a setup file in and lists what was kept in the status line.
+    pub(crate) fn import_setup(&mut self, path: &Path) -> Result<()> {
+        let report = match self.db.import_setup_file(path) {
+            Ok(report) => report,
+            Err(e) => {
+                self.message = Some(self.text.format("setup_file.failed", &[("error", &format!("{e:#}"))]));
+                return Ok(());
+            }
+        };
+        self.reload_exercises()?;
+        self.apply_settings()?;
+        let mut message = report.summary(&self.text);
+        if !report.conflicts.is_empty() {
+            let conflicts: Vec<String> = report.conflicts.iter().map(|c| c.describe(&self.text)).collect();
+            message.push_str(": ");
+            message.push_str(&conflicts.join("; "));
+        }
+        self.message = Some(message);
+        Ok(())
+    }
+}
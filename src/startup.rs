@@ -0,0 +1,183 @@
+//! The screen the app opens on. `--screen` wins, then the place the last
+//! session was quit from when `ui.restore_state` is on, then
+//! `ui.startup_screen`. Quitting always happens from the main screen, so
+//! the place kept is the last of the add, history and stats screens left
+//! for it; opening any other screen on the way resets it to the main one.
+//! The place is stored in the app-state table as JSON.
+//!
+//! The first frame is drawn before anything slow runs: the main screen
+//! shows its frame with placeholders, and the startup backup and the
+//! day's sets follow on the first tick.
+
+use anyhow::Result;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::store::WorkoutStore;
+use crate::{App, HistoryFilter, Screen};
+
+/// Stands in for anything not read yet.
+const PLACEHOLDER: &str = "…";
+
+const PLACE_KEY: &str = "last_place";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StartScreen {
+    #[default]
+    Main,
+    Add,
+    History,
+    Stats,
+}
+
+impl StartScreen {
+    /// Reads a screen from its name in config.toml or on the command line.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "main" => Some(StartScreen::Main),
+            "add" => Some(StartScreen::Add),
+            "history" => Some(StartScreen::History),
+            "stats" => Some(StartScreen::Stats),
+            _ => None,
+        }
+    }
+}
+
+/// Where a session was quit from.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Place {
+    pub screen: StartScreen,
+    /// The exercise picked on the add screen, or the highlighted day in
+    /// the history.
+    pub selection: Option<String>,
+    /// Whether that day's sets were open.
+    pub day_open: bool,
+}
+
+impl App {
+    pub(crate) fn open_startup_screen(&mut self, requested: Option<&str>) -> Result<()> {
+        if requested.is_none() && self.config.ui.restore_state {
+            let saved = self.db.get_state(PLACE_KEY)?;
+            if let Some(place) = saved.and_then(|json| serde_json::from_str::<Place>(&json).ok()) {
+                return self.restore_place(place);
+            }
+        }
+        let name = requested.unwrap_or(&self.config.ui.startup_screen).to_string();
+        let screen = StartScreen::parse(&name).unwrap_or_else(|| {
+            self.show_warning(self.text.format("startup.invalid_screen", &[("screen", &name)]));
+            StartScreen::Main
+        });
+        self.enter_start_screen(screen);
+        Ok(())
+    }
+
+    fn enter_start_screen(&mut self, screen: StartScreen) {
+        match screen {
+            StartScreen::Main => {}
+            StartScreen::Add => self.open_add_workout(),
+            StartScreen::History => self.open_history(),
+            StartScreen::Stats => self.screen = Screen::Stats,
+        }
+        self.last_screen = screen;
+    }
+
+    fn restore_place(&mut self, place: Place) -> Result<()> {
+        match place.screen {
+            StartScreen::Add => {
+                if let Some(name) = &place.selection
+                    && let Some(i) = self.picker_exercises().iter().position(|e| e.name == *name)
+                {
+                    self.selected_exercise = i;
+                }
+            }
+            StartScreen::History => {
+                self.enter_start_screen(StartScreen::History);
+                let dates = self.db.get_unique_dates(&HistoryFilter::All)?;
+                if let Some(date) = &place.selection
+                    && let Some(i) = dates.iter().position(|d| d == date)
+                {
+                    self.history_selected = i;
+                    if place.day_open {
+                        self.selected_date = Some(date.clone());
+                    }
+                }
+                return Ok(());
+            }
+            _ => {}
+        }
+        self.enter_start_screen(place.screen);
+        Ok(())
+    }
+
+    /// The startup work left for after the first frame, on the first tick.
+    pub(crate) fn finish_startup(&mut self) -> Result<()> {
+        if !self.starting {
+            return Ok(());
+        }
+        self.starting = false;
+        if self.config.backup.on_startup {
+            self.db.backup(&self.config.backup)?;
+        }
+        Ok(())
+    }
+
+    /// Keeps track of the last screen worth reopening, after every key.
+    pub(crate) fn note_place(&mut self) {
+        self.last_screen = match self.screen {
+            Screen::Main => return,
+            Screen::AddWorkout => StartScreen::Add,
+            Screen::History => StartScreen::History,
+            Screen::Stats => StartScreen::Stats,
+            _ => StartScreen::Main,
+        };
+    }
+
+    /// Stores where to open next time, on quitting.
+    pub(crate) fn save_place(&self) -> Result<()> {
+        if !self.config.ui.restore_state || self.read_only {
+            return Ok(());
+        }
+        let selection = match self.last_screen {
+            StartScreen::Add => self.picker_exercises().get(self.selected_exercise).map(|e| e.name.clone()),
+            StartScreen::History => match &self.selected_date {
+                Some(date) => Some(date.clone()),
+                None => self.db.get_unique_dates(&self.history_filter)?.get(self.history_selected).cloned(),
+            },
+            _ => None,
+        };
+        let place = Place {
+            screen: self.last_screen,
+            selection,
+            day_open: self.last_screen == StartScreen::History && self.selected_date.is_some(),
+        };
+        let json = serde_json::to_string(&place)?;
+        // Unchanged places aren't written, so they don't count as a change
+        // for the backup on exit
+        if self.db.get_state(PLACE_KEY)?.as_deref() != Some(json.as_str()) {
+            self.db.set_state(PLACE_KEY, &json)?;
+        }
+        Ok(())
+    }
+}
+
+/// The main screen's frame, with placeholders where the sets go.
+pub fn render_main_skeleton(f: &mut Frame, area: Rect, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(area);
+    let title = Paragraph::new(format!("{}   ·   {PLACEHOLDER}", app.text.get("main.title")))
+        .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("main.welcome")));
+    f.render_widget(title, chunks[0]);
+    let summary = Paragraph::new(PLACEHOLDER)
+        .style(Style::default().fg(Color::DarkGray))
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("main.summary")));
+    f.render_widget(summary, chunks[1]);
+}
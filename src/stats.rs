@@ -0,0 +1,533 @@
+use anyhow::Result;
+use chrono::{Datelike, Duration, Local, Months, NaiveDate};
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        block::{Position, Title},
+        Block, Borders, Cell, Row, Table,
+    },
+    Frame,
+};
+
+use crate::chart;
+use crate::dates::{week_start, WeekStart};
+use crate::exercises::{CATEGORIES, OTHER_CATEGORY};
+use crate::rest;
+use crate::trend::{self, Trend};
+use crate::units;
+use crate::weekdays;
+use crate::{App, Database, Screen};
+
+const DAILY_DAYS: i64 = 14;
+const WEEKLY_WEEKS: i64 = 8;
+const MONTHLY_MONTHS: u32 = 6;
+/// Week column, a column per category and "other", spacing and borders.
+const CATEGORY_TABLE_WIDTH: u16 = 12 + 9 * (CATEGORIES.len() as u16 + 1) + 2;
+const MONTHLY_TABLE_WIDTH: u16 = 42;
+
+/// Aggregated sets for a day, week or month. Amounts are split by exercise
+/// unit so reps, seconds and meters never get added together. RPE is kept
+/// as a sum and a count of rated sets so that unrated sets never drag the
+/// average towards zero. Volume load only covers sets counted in reps that
+/// have a weight, so `weighted_reps` tells whether it covers all of `reps`.
+#[derive(Debug, Clone)]
+pub struct PeriodStats {
+    pub start: NaiveDate,
+    pub sets: i64,
+    pub reps: i64,
+    pub seconds: i64,
+    pub meters: i64,
+    /// Weight × reps in kg.
+    pub volume: f64,
+    pub weighted_reps: i64,
+    pub rpe_sum: i64,
+    pub rpe_sets: i64,
+    pub mood_sum: i64,
+    pub mood_days: i64,
+}
+
+impl PeriodStats {
+    /// A period with nothing logged.
+    pub fn empty(start: NaiveDate) -> Self {
+        Self {
+            start,
+            sets: 0,
+            reps: 0,
+            seconds: 0,
+            meters: 0,
+            volume: 0.0,
+            weighted_reps: 0,
+            rpe_sum: 0,
+            rpe_sets: 0,
+            mood_sum: 0,
+            mood_days: 0,
+        }
+    }
+
+    pub fn avg_rpe(&self) -> Option<f64> {
+        (self.rpe_sets > 0).then(|| self.rpe_sum as f64 / self.rpe_sets as f64)
+    }
+
+    pub fn avg_mood(&self) -> Option<f64> {
+        (self.mood_days > 0).then(|| self.mood_sum as f64 / self.mood_days as f64)
+    }
+
+    /// Whether some reps were done without weight, so the volume load only
+    /// stands for part of the period.
+    pub fn partial_volume(&self) -> bool {
+        self.weighted_reps > 0 && self.weighted_reps < self.reps
+    }
+
+    /// Adds another period's sets to this one.
+    pub fn add(&mut self, other: &PeriodStats) {
+        self.sets += other.sets;
+        self.reps += other.reps;
+        self.seconds += other.seconds;
+        self.meters += other.meters;
+        self.volume += other.volume;
+        self.weighted_reps += other.weighted_reps;
+        self.rpe_sum += other.rpe_sum;
+        self.rpe_sets += other.rpe_sets;
+        self.mood_sum += other.mood_sum;
+        self.mood_days += other.mood_days;
+    }
+}
+
+impl Database {
+    /// Per-day totals from `since` onwards, oldest first, with the archive's
+    /// sets while it's attached, or only sets tagged `tag` when given.
+    pub fn get_daily_stats(&self, since: NaiveDate, tag: Option<&str>) -> Result<Vec<PeriodStats>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT d.day, SUM(d.sets),
+                    SUM(CASE WHEN e.unit IN (?3, ?4) THEN 0 ELSE d.total END),
+                    SUM(CASE WHEN e.unit = ?3 THEN d.total ELSE 0 END),
+                    SUM(CASE WHEN e.unit = ?4 THEN d.total ELSE 0 END),
+                    SUM(CASE WHEN e.unit IN (?3, ?4) THEN 0 ELSE d.volume END),
+                    SUM(CASE WHEN e.unit IN (?3, ?4) THEN 0 ELSE d.weighted_reps END),
+                    SUM(d.rpe_sum), SUM(d.rpe_sets)
+             FROM {} d
+             LEFT JOIN exercises e ON e.name = d.exercise_type
+             WHERE d.day >= ?2
+             GROUP BY d.day
+             ORDER BY d.day ASC",
+            self.stats_daily_totals(tag)
+        ))?;
+
+        let params = rusqlite::params![tag, since.format("%Y-%m-%d").to_string(), units::SECONDS, units::DISTANCE];
+        let rows = stmt
+            .query_map(params, |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get(1)?,
+                    (row.get(2)?, row.get(3)?, row.get(4)?),
+                    (row.get(5)?, row.get(6)?),
+                    row.get(7)?,
+                    row.get(8)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(day, sets, (reps, seconds, meters), (volume, weighted_reps), rpe_sum, rpe_sets)| {
+                let start = NaiveDate::parse_from_str(&day, "%Y-%m-%d").ok()?;
+                Some(PeriodStats {
+                    start,
+                    sets,
+                    reps,
+                    seconds,
+                    meters,
+                    volume,
+                    weighted_reps,
+                    rpe_sum,
+                    rpe_sets,
+                    mood_sum: 0,
+                    mood_days: 0,
+                })
+            })
+            .collect())
+    }
+}
+
+/// Folds per-day stats (oldest first) into longer periods, each starting
+/// on the day `period_start` gives for its days.
+fn fold_periods(daily: &[PeriodStats], period_start: impl Fn(NaiveDate) -> NaiveDate) -> Vec<PeriodStats> {
+    let mut periods: Vec<PeriodStats> = Vec::new();
+    for day in daily {
+        let start = period_start(day.start);
+        match periods.last_mut() {
+            Some(period) if period.start == start => period.add(day),
+            _ => periods.push(PeriodStats { start, ..day.clone() }),
+        }
+    }
+    periods
+}
+
+/// Folds per-day stats (oldest first) into per-week stats.
+pub fn weekly(daily: &[PeriodStats], week: WeekStart) -> Vec<PeriodStats> {
+    fold_periods(daily, |day| week_start(day, week))
+}
+
+/// Folds per-day stats (oldest first) into per-month stats.
+pub fn monthly(daily: &[PeriodStats]) -> Vec<PeriodStats> {
+    fold_periods(daily, |day| day.with_day(1).unwrap_or(day))
+}
+
+impl App {
+    pub(crate) fn handle_stats_input(&mut self, key: KeyCode) -> Result<bool> {
+        match key {
+            KeyCode::Esc => self.screen = Screen::Main,
+            KeyCode::Char('r') => self.open_report_screen(),
+            KeyCode::Char('c') => self.stats_chart = !self.stats_chart,
+            KeyCode::Char('a') if self.stats_chart => self.stats_average = !self.stats_average,
+            KeyCode::Char('w') => self.stats_weekdays = !self.stats_weekdays,
+            KeyCode::Char('n') if self.stats_weekdays => {
+                self.stats_weekday_range = (self.stats_weekday_range + 1) % weekdays::RANGES.len();
+            }
+            KeyCode::Char('i') if self.db.has_archive() => self.toggle_stats_archive(),
+            KeyCode::Char('f') => self.next_stats_tag()?,
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    /// Counts the archived sets in, or leaves them out again.
+    fn toggle_stats_archive(&mut self) {
+        let toggled = if self.db.archive_attached() { self.db.detach_archive() } else { self.db.attach_archive() };
+        if let Err(e) = toggled {
+            self.show_toast(self.text.format("stats.archive_failed", &[("error", &format!("{e:#}"))]));
+        }
+    }
+}
+
+/// A note on whether archived sets are counted, when there's an archive.
+fn archive_note(app: &App) -> Option<Title<'_>> {
+    if !app.db.has_archive() {
+        return None;
+    }
+    let key = if app.db.archive_attached() { "stats.archive_included" } else { "stats.archive_excluded" };
+    Some(
+        Title::from(Span::styled(app.text.get(key), Style::default().fg(Color::Cyan)))
+            .position(Position::Bottom)
+            .alignment(Alignment::Right),
+    )
+}
+
+/// Which tag the stats are limited to, when they are.
+fn tag_note(app: &App) -> Option<Title<'_>> {
+    let tag = app.stats_tag.as_ref()?;
+    Some(
+        Title::from(Span::styled(app.text.format("stats.tag", &[("tag", tag)]), Style::default().fg(Color::Cyan)))
+            .position(Position::Bottom)
+            .alignment(Alignment::Right),
+    )
+}
+
+pub fn render_stats_screen(f: &mut Frame, area: Rect, app: &App) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(area);
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[0]);
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(CATEGORY_TABLE_WIDTH), Constraint::Length(MONTHLY_TABLE_WIDTH), Constraint::Min(0)])
+        .split(rows[1]);
+
+    let today = Local::now().date_naive();
+    let week = app.config.week_starts_on;
+    let since = week_start(today, week) - Duration::weeks(WEEKLY_WEEKS - 1);
+    let first_month = today.with_day(1).unwrap_or(today) - Months::new(MONTHLY_MONTHS - 1);
+    let mut daily = app.db.get_daily_stats(first_month.min(since), app.stats_tag.as_deref()).unwrap_or_default();
+    for (date, mood) in app.db.get_moods_since(first_month.min(since)).unwrap_or_default() {
+        if let Some(day) = daily.iter_mut().find(|d| d.start == date) {
+            day.mood_sum += mood as i64;
+            day.mood_days += 1;
+        }
+    }
+    let months = monthly(&daily.iter().filter(|d| d.start >= first_month).cloned().collect::<Vec<_>>());
+    let weeks: Vec<PeriodStats> = weekly(&daily, week).into_iter().filter(|w| w.start >= since).collect();
+
+    let recent_days: Vec<PeriodStats> = daily
+        .iter()
+        .filter(|d| d.start > today - Duration::days(DAILY_DAYS))
+        .cloned()
+        .collect();
+
+    let covered_from = first_month.min(since);
+    let all_weeks = weekly(&daily, week);
+    let week_trends = Trends {
+        periods: &all_weeks,
+        previous: |start| start - Duration::weeks(1),
+        covered_from,
+        running: week_start(today, week),
+    };
+    let all_months = monthly(&daily);
+    let month_trends = Trends {
+        periods: &all_months,
+        previous: |start| start - Months::new(1),
+        covered_from,
+        running: today.with_day(1).unwrap_or(today),
+    };
+
+    let text = &app.text;
+    if app.stats_chart {
+        chart::render_daily_chart(f, chunks[0], &daily, covered_from, today, app);
+    } else {
+        let daily_table = stats_table(text.get("common.date"), &recent_days, None, app);
+        f.render_widget(daily_table.block(period_block(text.get("stats.daily"), &recent_days, app)), chunks[0]);
+    }
+    let mut weekly_block = period_block(text.get("stats.weekly"), &weeks, app);
+    if let Some(hint) = deload_hint(&week_trends, since, app) {
+        weekly_block = weekly_block.title(
+            Title::from(Span::styled(hint, Style::default().fg(Color::Cyan)))
+                .alignment(Alignment::Right),
+        );
+    }
+    if let Some(note) = tag_note(app) {
+        weekly_block = weekly_block.title(note);
+    }
+    f.render_widget(stats_table(text.get("stats.week_of"), &weeks, Some(&week_trends), app).block(weekly_block), chunks[1]);
+
+    if app.stats_weekdays {
+        f.render_widget(weekdays::weekday_table(app), bottom[0]);
+    } else {
+        let category_totals = app.db.get_category_totals_since(since, app.stats_tag.as_deref()).unwrap_or_default();
+        f.render_widget(category_table(&category_totals, app), bottom[0]);
+    }
+    f.render_widget(monthly_table(&months, &month_trends, app), bottom[1]);
+    f.render_widget(rest::rest_table(since, app), bottom[2]);
+}
+
+/// How a table compares each period with the one before it.
+struct Trends<'a> {
+    /// Every period the data covers, to look the one before a row up in.
+    periods: &'a [PeriodStats],
+    /// Start of the period before one starting on the given day.
+    previous: fn(NaiveDate) -> NaiveDate,
+    /// Where the data starts; periods starting earlier are only partly in it.
+    covered_from: NaiveDate,
+    /// Start of the period still running, which isn't compared until it's
+    /// over.
+    running: NaiveDate,
+}
+
+impl Trends<'_> {
+    /// The period before `period`, empty when nothing was logged in it.
+    fn previous_of(&self, period: &PeriodStats) -> Option<PeriodStats> {
+        let start = (self.previous)(period.start);
+        if period.start == self.running || start < self.covered_from {
+            return None;
+        }
+        Some(self.periods.iter().find(|p| p.start == start).cloned().unwrap_or_else(|| PeriodStats::empty(start)))
+    }
+}
+
+/// A figure with an arrow for how it moved since the previous period.
+fn trend_cell(text: String, current: f64, previous: Option<f64>, app: &App) -> Cell<'static> {
+    let Some(previous) = previous else {
+        return Cell::from(text);
+    };
+    let direction = trend::trend(previous, current, app.config.trends.tolerance_percent);
+    let color = match direction {
+        Trend::Up => Color::Green,
+        Trend::Down => Color::Yellow,
+        Trend::Steady => Color::DarkGray,
+    };
+    Cell::from(Line::from(vec![
+        Span::raw(text),
+        Span::styled(format!(" {}", direction.arrow()), Style::default().fg(color)),
+    ]))
+}
+
+/// Sets, reps and volume cells, with trend arrows when the table has them.
+fn figure_cells(period: &PeriodStats, trends: Option<&Trends>, app: &App) -> [Cell<'static>; 3] {
+    let previous = trends.and_then(|trends| trends.previous_of(period));
+    let previous = previous.as_ref();
+    [
+        trend_cell(period.sets.to_string(), period.sets as f64, previous.map(|p| p.sets as f64), app),
+        trend_cell(period.reps.to_string(), period.reps as f64, previous.map(|p| p.reps as f64), app),
+        trend_cell(volume_cell(period, app), period.volume, previous.map(|p| p.volume), app),
+    ]
+}
+
+/// The easier-week hint for the weekly table, judged on the finished weeks
+/// from `since`. Volume load is used when those weeks have any, reps
+/// otherwise.
+fn deload_hint(trends: &Trends, since: NaiveDate, app: &App) -> Option<String> {
+    let config = &app.config.trends;
+    let weeks: Vec<PeriodStats> = std::iter::successors(Some(since), |start| Some(*start + Duration::weeks(1)))
+        .take_while(|start| *start < trends.running)
+        .map(|start| trends.periods.iter().find(|p| p.start == start).cloned().unwrap_or_else(|| PeriodStats::empty(start)))
+        .collect();
+    let weighted = weeks.iter().any(|w| w.volume > 0.0);
+    let volumes: Vec<f64> = weeks.iter().map(|w| if weighted { w.volume } else { w.reps as f64 }).collect();
+    let rising = trend::deload_hint(&volumes, config.deload_weeks, config.deload_rise_percent)?;
+    Some(app.text.format("stats.deload", &[("weeks", &rising)]))
+}
+
+/// Volume load for a table cell, starred when only some reps had a weight.
+fn volume_cell(stats: &PeriodStats, app: &App) -> String {
+    if stats.volume <= 0.0 {
+        return "-".to_string();
+    }
+    let volume = units::format_volume(stats.volume, app.config.units.weight);
+    if stats.partial_volume() { format!("{volume}*") } else { volume }
+}
+
+/// A table block, with a note under it explaining the star when any row
+/// has one.
+fn period_block<'a>(title: &'a str, stats: &[PeriodStats], app: &'a App) -> Block<'a> {
+    let block = Block::default().borders(Borders::ALL).title(title);
+    if stats.iter().any(PeriodStats::partial_volume) {
+        block.title(Title::from(app.text.get("stats.weighted_only")).position(Position::Bottom))
+    } else {
+        block
+    }
+}
+
+fn volume_header(app: &App) -> String {
+    app.text.format("stats.volume", &[("unit", &app.config.units.weight.label())])
+}
+
+/// Sets, reps and volume load per month, newest first.
+fn monthly_table<'a>(months: &[PeriodStats], trends: &Trends, app: &'a App) -> Table<'a> {
+    let mut block = period_block(app.text.get("stats.monthly"), months, app);
+    if let Some(note) = archive_note(app) {
+        block = block.title(note);
+    }
+    let rows: Vec<Row> = months
+        .iter()
+        .rev()
+        .map(|m| {
+            let mut cells = vec![Cell::from(m.start.format("%Y-%m").to_string())];
+            cells.extend(figure_cells(m, Some(trends), app));
+            Row::new(cells)
+        })
+        .collect();
+    Table::new(rows, [Constraint::Length(9), Constraint::Length(8), Constraint::Length(9), Constraint::Min(11)])
+        .header(
+            Row::new(vec![
+                app.text.get("stats.month").to_string(),
+                app.text.get("stats.sets").to_string(),
+                app.text.get("stats.reps").to_string(),
+                volume_header(app),
+            ])
+            .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        )
+        .block(block)
+}
+
+/// Weekly rep volume with one column per category, newest week first.
+fn category_table(totals: &[(NaiveDate, String, i64)], app: &App) -> Table<'static> {
+    let week = app.config.week_starts_on;
+    let categories: Vec<&str> = CATEGORIES.iter().copied().chain([OTHER_CATEGORY]).collect();
+
+    let mut weeks: Vec<(NaiveDate, Vec<i64>)> = Vec::new();
+    for (day, category, total) in totals {
+        let start = week_start(*day, week);
+        if weeks.last().map(|(s, _)| *s) != Some(start) {
+            weeks.push((start, vec![0; categories.len()]));
+        }
+        let column = categories
+            .iter()
+            .position(|c| c == category)
+            .unwrap_or(categories.len() - 1);
+        if let Some((_, sums)) = weeks.last_mut() {
+            sums[column] += total;
+        }
+    }
+
+    let rows: Vec<Row> = weeks
+        .iter()
+        .rev()
+        .map(|(start, sums)| {
+            let mut cells = vec![app.display_date(*start)];
+            cells.extend(sums.iter().map(|s| s.to_string()));
+            Row::new(cells)
+        })
+        .collect();
+
+    let mut header = vec![app.text.get("stats.week_of").to_string()];
+    header.extend(categories.iter().map(|c| c.to_string()));
+    let mut widths = vec![Constraint::Length(12)];
+    widths.extend(categories.iter().map(|_| Constraint::Length(8)));
+
+    Table::new(rows, widths)
+        .header(Row::new(header).style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)))
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("stats.by_category")))
+}
+
+pub(crate) fn format_duration(seconds: i64) -> String {
+    if seconds >= 3600 {
+        format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+/// A row per period, newest first; the caller adds the block.
+fn stats_table<'a>(period: &'a str, stats: &[PeriodStats], trends: Option<&Trends>, app: &'a App) -> Table<'a> {
+    let distance = app.config.units.distance;
+    let rows: Vec<Row> = stats
+        .iter()
+        .rev()
+        .map(|s| {
+            let rpe = s
+                .avg_rpe()
+                .map(|avg| format!("{:.1}", avg))
+                .unwrap_or_else(|| "-".to_string());
+            let mood = s
+                .avg_mood()
+                .map(|avg| format!("{:.1}", avg))
+                .unwrap_or_else(|| "-".to_string());
+            let mut cells = vec![Cell::from(app.display_date(s.start))];
+            cells.extend(figure_cells(s, trends, app));
+            cells.extend([
+                if s.seconds > 0 { format_duration(s.seconds) } else { "-".to_string() },
+                if s.meters > 0 {
+                    units::format_amount(s.meters, units::DISTANCE, distance)
+                } else {
+                    "-".to_string()
+                },
+                rpe,
+                mood,
+            ]
+            .map(Cell::from));
+            Row::new(cells)
+        })
+        .collect();
+
+    Table::new(
+        rows,
+        [
+            Constraint::Length(11),
+            Constraint::Length(6),
+            Constraint::Length(8),
+            Constraint::Length(11),
+            Constraint::Length(8),
+            Constraint::Length(10),
+            Constraint::Length(8),
+            Constraint::Min(5),
+        ],
+    )
+    .header(
+        Row::new(vec![
+            period.to_string(),
+            app.text.get("stats.sets").to_string(),
+            app.text.get("stats.reps").to_string(),
+            volume_header(app),
+            app.text.get("stats.time").to_string(),
+            app.text.get("stats.distance").to_string(),
+            app.text.get("stats.avg_rpe").to_string(),
+            app.text.get("stats.mood").to_string(),
+        ])
+            .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+    )
+}
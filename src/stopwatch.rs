@@ -0,0 +1,269 @@
+//! Stopwatch screen for holds and timed circuits. Time is measured from
+//! `Instant`s taken on each key press, so it stays right however often the
+//! screen is drawn; `+` and `-` correct it by 15 seconds. Finishing leads to a review where the laps, or the
+//! total, are saved as sets of an exercise timed in seconds.
+
+use anyhow::Result;
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, List, ListItem, Paragraph},
+    Frame,
+};
+use std::time::Duration;
+
+use crate::exercises::{render_dialog, Exercise};
+use crate::store::WorkoutStore;
+use crate::timer::TimerState;
+use crate::units;
+use crate::{App, Screen};
+
+#[derive(Debug, Default)]
+pub struct Stopwatch {
+    clock: TimerState,
+    /// Lap lengths, oldest first.
+    laps: Vec<Duration>,
+    /// Elapsed time when the last lap was taken.
+    lap_mark: Duration,
+}
+
+impl Stopwatch {
+    pub fn elapsed(&self) -> Duration {
+        self.clock.elapsed()
+    }
+
+    pub fn running(&self) -> bool {
+        !self.clock.is_paused()
+    }
+
+    pub fn toggle(&mut self) {
+        self.clock.toggle();
+    }
+
+    pub fn lap(&mut self) {
+        let elapsed = self.elapsed();
+        if elapsed > self.lap_mark {
+            self.laps.push(elapsed - self.lap_mark);
+            self.lap_mark = elapsed;
+        }
+    }
+
+    /// Stops the clock, closing the last lap when there are laps at all.
+    fn finish(&mut self) {
+        if self.running() {
+            self.toggle();
+        }
+        if !self.laps.is_empty() {
+            self.lap();
+        }
+    }
+}
+
+/// `m:ss.t`, or `h:mm:ss.t` from an hour up.
+pub fn format_elapsed(elapsed: Duration) -> String {
+    let tenths = elapsed.as_millis() / 100;
+    let seconds = tenths / 10;
+    if seconds >= 3600 {
+        format!("{}:{:02}:{:02}.{}", seconds / 3600, seconds / 60 % 60, seconds % 60, tenths % 10)
+    } else {
+        format!("{}:{:02}.{}", seconds / 60, seconds % 60, tenths % 10)
+    }
+}
+
+/// Whole seconds for a set, rounded to the nearest.
+fn set_seconds(duration: Duration) -> i32 {
+    duration.as_secs_f64().round() as i32
+}
+
+/// Choices made after finishing, before anything is saved.
+#[derive(Debug, Default)]
+pub struct Review {
+    /// Index into the exercises timed in seconds.
+    pub exercise: usize,
+    /// Save the total as one set instead of a set per lap.
+    pub total: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct StopwatchState {
+    pub watch: Stopwatch,
+    pub review: Option<Review>,
+    /// Asking whether to throw away the time on Esc.
+    pub confirm_discard: bool,
+}
+
+impl StopwatchState {
+    /// The sets the review would save, in seconds.
+    fn entries(&self, total: bool) -> Vec<i32> {
+        let durations = if total || self.watch.laps.is_empty() {
+            vec![self.watch.elapsed()]
+        } else {
+            self.watch.laps.clone()
+        };
+        durations.into_iter().map(set_seconds).filter(|seconds| *seconds > 0).collect()
+    }
+}
+
+impl App {
+    pub(crate) fn open_stopwatch(&mut self) {
+        self.screen = Screen::Stopwatch;
+        self.message = None;
+    }
+
+    /// Exercises a session can be saved to.
+    fn timed_exercises(&self) -> Vec<&Exercise> {
+        self.picker_exercises().into_iter().filter(|e| e.unit == units::SECONDS).collect()
+    }
+
+    pub(crate) fn handle_stopwatch_input(&mut self, key: KeyCode) -> Result<bool> {
+        let state = &mut self.stopwatch;
+        if state.confirm_discard {
+            match key {
+                KeyCode::Char('y') => {
+                    self.stopwatch = StopwatchState::default();
+                    self.screen = Screen::Main;
+                }
+                KeyCode::Char('n') | KeyCode::Esc => state.confirm_discard = false,
+                _ => {}
+            }
+            return Ok(false);
+        }
+        if state.review.is_some() {
+            return self.handle_stopwatch_review_input(key);
+        }
+        match key {
+            KeyCode::Char('l') if state.watch.running() => state.watch.lap(),
+            KeyCode::Enter if state.watch.elapsed() > Duration::ZERO => {
+                if self.timed_exercises().is_empty() {
+                    self.message = Some(self.text.get("stopwatch.no_exercises").to_string());
+                } else {
+                    self.message = None;
+                    self.stopwatch.watch.finish();
+                    self.stopwatch.review = Some(Review::default());
+                }
+            }
+            KeyCode::Esc if state.watch.clock.asks_before_cancel() => state.confirm_discard = true,
+            KeyCode::Esc => {
+                self.stopwatch = StopwatchState::default();
+                self.screen = Screen::Main;
+            }
+            key => {
+                state.watch.clock.handle_key(key);
+            }
+        }
+        Ok(false)
+    }
+
+    fn handle_stopwatch_review_input(&mut self, key: KeyCode) -> Result<bool> {
+        let count = self.timed_exercises().len();
+        let Some(review) = &mut self.stopwatch.review else {
+            return Ok(false);
+        };
+        match key {
+            KeyCode::Esc => self.stopwatch.review = None,
+            KeyCode::Tab if count > 0 => review.exercise = (review.exercise + 1) % count,
+            KeyCode::Char(' ') if !self.stopwatch.watch.laps.is_empty() => review.total = !review.total,
+            KeyCode::Enter => {
+                let (index, total) = (review.exercise, review.total);
+                let entries = self.stopwatch.entries(total);
+                let Some(exercise) = self.timed_exercises().get(index % count.max(1)).map(|e| e.name.clone()) else {
+                    return Ok(false);
+                };
+                if entries.is_empty() {
+                    self.message = Some(self.text.get("stopwatch.too_short").to_string());
+                    return Ok(false);
+                }
+                for seconds in &entries {
+                    self.db.add_workout(&exercise, *seconds, None, None)?;
+                    self.notify_webhook(&exercise, *seconds as i64)?;
+                }
+                let total: i64 = entries.iter().map(|seconds| *seconds as i64).sum();
+                // Ready for the next round, with the saved total in the status
+                self.stopwatch = StopwatchState::default();
+                self.message = Some(self.text.format(
+                    "stopwatch.saved",
+                    &[("exercise", &exercise), ("amount", &self.format_count(&exercise, total))],
+                ));
+                self.check_goal(&exercise, total)?;
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+}
+
+pub fn render_stopwatch_screen(f: &mut Frame, area: Rect, app: &App) {
+    let state = &app.stopwatch;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(5), Constraint::Min(0), Constraint::Length(3)])
+        .split(area);
+
+    let elapsed = state.watch.elapsed();
+    let paused = !state.watch.running() && !elapsed.is_zero();
+    let status = app.text.get(if state.watch.running() { "stopwatch.running" } else { "stopwatch.stopped" });
+    let color = if state.watch.running() { Color::Green } else { Color::Yellow };
+    let mut time = format_elapsed(elapsed);
+    if paused {
+        time = format!("{time}  {}", app.text.get("timer.paused"));
+    }
+    let clock = Paragraph::new(format!("\n{time}"))
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(color).add_modifier(Modifier::BOLD))
+        .block(Block::default().borders(Borders::ALL).title(app.text.format("stopwatch.title", &[("status", &status)])));
+    f.render_widget(clock, chunks[0]);
+
+    match &state.review {
+        Some(review) => render_review(f, chunks[1], app, review),
+        None => {
+            let mut total = Duration::ZERO;
+            let mut items: Vec<ListItem> = Vec::new();
+            for (i, lap) in state.watch.laps.iter().enumerate() {
+                total += *lap;
+                items.push(ListItem::new(app.text.format(
+                    "stopwatch.lap",
+                    &[("number", &(i + 1)), ("lap", &format_elapsed(*lap)), ("total", &format_elapsed(total))],
+                )));
+            }
+            // Newest lap on top
+            items.reverse();
+            let laps = List::new(items).block(Block::default().borders(Borders::ALL).title(app.text.get("stopwatch.laps")));
+            f.render_widget(laps, chunks[1]);
+        }
+    }
+
+    if let Some(msg) = &app.message {
+        let message = Paragraph::new(msg.as_str())
+            .style(Style::default().fg(Color::Green))
+            .block(Block::default().borders(Borders::ALL).title(app.text.get("common.status")));
+        f.render_widget(message, chunks[2]);
+    }
+
+    if state.confirm_discard {
+        render_dialog(f, app.text.get("stopwatch.discard_title"), app.text.get("stopwatch.discard"));
+    }
+}
+
+fn render_review(f: &mut Frame, area: Rect, app: &App, review: &Review) {
+    let state = &app.stopwatch;
+    let exercises = app.timed_exercises();
+    let exercise = exercises.get(review.exercise % exercises.len().max(1)).map_or("", |e| e.name.as_str());
+    let entries = state.entries(review.total);
+    let mut lines = vec![
+        app.text.format("stopwatch.save_to", &[("exercise", &exercise)]),
+        app.text.get(if review.total || state.watch.laps.is_empty() { "stopwatch.as_total" } else { "stopwatch.as_laps" }).to_string(),
+        String::new(),
+    ];
+    lines.extend(entries.iter().map(|seconds| format!("  {}", app.format_count(exercise, *seconds as i64))));
+    let mut keys = vec!["[Tab] ", app.text.get("stopwatch.key_exercise")];
+    if !state.watch.laps.is_empty() {
+        keys.extend(["  [Space] ", app.text.get("stopwatch.key_split")]);
+    }
+    keys.extend(["  [Enter] ", app.text.get("stopwatch.key_save"), "  [Esc] ", app.text.get("stopwatch.key_back")]);
+    lines.push(String::new());
+    lines.push(keys.concat());
+    let review = Paragraph::new(lines.join("\n"))
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("stopwatch.review")));
+    f.render_widget(review, area);
+}
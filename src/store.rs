@@ -0,0 +1,612 @@
+//! Where workouts are kept. The workout queries the screens use are the
+//! `WorkoutStore` trait, implemented by `Database` over SQLite and by
+//! `JsonlStore` straight over a JSON-lines file. The app itself runs on
+//! `Database`, which for the jsonl backend is an in-memory database
+//! imported from the file at startup and exported back to it on save (see
+//! `jsonl`); both write the same file format.
+
+use anyhow::{bail, Result};
+use chrono::Local;
+use clap::ValueEnum;
+use rusqlite::{params, Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::audit::describe_sets;
+use crate::{dates, exercises, migrations, tags, Database, HistoryFilter, WorkoutRecord};
+
+/// A set's tags as one comma-separated column, for `tags::split`.
+pub(crate) const TAGS: &str = "(SELECT group_concat(t.name) FROM workout_tags wt JOIN tags t ON t.id = wt.tag_id WHERE wt.workout_id = workouts.id)";
+
+/// How long a read-only open waits out a write another instance is in the
+/// middle of.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How the data is stored on disk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    #[default]
+    Sqlite,
+    /// An in-memory SQLite database imported from a JSON-lines file, one
+    /// object per row, and exported back over it whenever the data changes.
+    Jsonl,
+}
+
+impl Backend {
+    pub fn default_path(self) -> &'static str {
+        match self {
+            Backend::Sqlite => "fitness_tracker.db",
+            Backend::Jsonl => "fitness_tracker.jsonl",
+        }
+    }
+}
+
+/// Logging and reading back sets.
+pub trait WorkoutStore {
+    /// Logs `sets` all at once, or none of them if any fails, and returns
+    /// how many were written.
+    fn add_workouts(&self, sets: &[NewWorkout]) -> Result<usize>;
+    /// Logs a set now; `weight` is in kg.
+    fn add_workout(&self, exercise_type: &str, count: i32, rpe: Option<i32>, weight: Option<f64>) -> Result<()> {
+        self.add_workouts(&[NewWorkout::now(exercise_type, count, rpe, weight)])?;
+        Ok(())
+    }
+    fn get_today_workouts(&self) -> Result<Vec<WorkoutRecord>>;
+    /// Most recent day before today with any sets.
+    fn get_last_workout_date(&self) -> Result<Option<String>>;
+    fn get_workouts_by_date(&self, date: &str) -> Result<Vec<WorkoutRecord>>;
+    /// Days with sets matching `filter`, newest first.
+    fn get_unique_dates(&self, filter: &HistoryFilter) -> Result<Vec<String>>;
+    /// Moves a set to the trash. Every other workout query skips it from
+    /// then on.
+    fn delete_workout(&self, id: i64) -> Result<()>;
+    fn restore_workout(&self, id: i64) -> Result<()>;
+    /// Changes a set's amount and records the old one.
+    fn update_workout_count(&self, id: i64, count: i32) -> Result<()>;
+}
+
+impl Database {
+    /// Opens the data at `path` with `backend`, creating it when missing.
+    pub fn open(backend: Backend, path: &Path) -> Result<Self> {
+        match backend {
+            Backend::Sqlite => Database::new(&path.to_string_lossy()),
+            Backend::Jsonl => Database::load_jsonl(path),
+        }
+    }
+
+    /// Opens the data at `path` for reading only, as it is, for a file
+    /// another instance has open. Nothing is created or migrated, so a file
+    /// from an older version is refused. Encrypted files go through
+    /// `unlock` instead.
+    pub fn open_read_only(backend: Backend, path: &Path) -> Result<Self> {
+        match backend {
+            Backend::Sqlite => Database::with_read_only_connection(
+                Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)?,
+                path,
+            ),
+            Backend::Jsonl => {
+                // Loaded into memory, so all that's left is never saving it
+                let db = Database::load_jsonl(path)?;
+                db.set_read_only()?;
+                Ok(db)
+            }
+        }
+    }
+
+    /// Wraps `conn`, opened read-only (and unlocked, if encrypted), without
+    /// touching its schema.
+    pub(crate) fn with_read_only_connection(conn: Connection, path: &Path) -> Result<Self> {
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        if migrations::schema_version(&conn)? < migrations::latest_version() {
+            bail!("{} is from an older version; open it in the app once first", path.display());
+        }
+        Ok(Self {
+            conn,
+            journal: None,
+            jsonl: None,
+            saved_changes: Cell::new(0),
+            unsaved: Cell::new(false),
+            passphrase: None,
+        })
+    }
+
+    /// The file the data lives in, which backups and the journal are kept
+    /// next to.
+    pub fn file_path(&self) -> PathBuf {
+        match &self.jsonl {
+            Some(path) => path.clone(),
+            None => PathBuf::from(self.conn.path().unwrap_or_default()),
+        }
+    }
+}
+
+/// A set for `add_workouts`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewWorkout {
+    pub exercise_type: String,
+    pub count: i32,
+    pub rpe: Option<i32>,
+    /// Kilograms.
+    pub weight: Option<f64>,
+    /// When the set was performed, `YYYY-MM-DD HH:MM:SS`.
+    pub timestamp: String,
+}
+
+impl NewWorkout {
+    /// A set performed now.
+    pub fn now(exercise_type: &str, count: i32, rpe: Option<i32>, weight: Option<f64>) -> Self {
+        Self {
+            exercise_type: exercise_type.to_string(),
+            count,
+            rpe,
+            weight,
+            timestamp: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        }
+    }
+
+    /// How the audit log names the set when it's logged on its own.
+    pub fn describe(&self) -> String {
+        match self.weight {
+            Some(weight) => format!("{} ×{} at {weight} kg", self.exercise_type, self.count),
+            None => format!("{} ×{}", self.exercise_type, self.count),
+        }
+    }
+}
+
+impl WorkoutStore for Database {
+    /// Logs `sets` in one transaction, reusing one prepared statement, and
+    /// returns how many were written. Any failure rolls back the whole
+    /// batch, so either all of them land or none do. A single set gets its
+    /// own audit entry; a batch gets one entry for all of them.
+    fn add_workouts(&self, sets: &[NewWorkout]) -> Result<usize> {
+        let recorded_at = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let last_id = self.last_workout_id()?;
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            // `timestamp` is when the set was performed and what everything
+            // aggregates on; `recorded_at` is always the wall-clock time of
+            // entry, which differs for sets logged after the fact
+            let mut insert = tx.prepare_cached(
+                "INSERT INTO workouts (exercise_type, count, timestamp, rpe, recorded_at, weight) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            for set in sets {
+                insert.execute(params![set.exercise_type, set.count, set.timestamp, set.rpe, recorded_at, set.weight])?;
+            }
+        }
+        match sets {
+            [] => {}
+            [set] => {
+                let uuid = self.workout_uuid(tx.last_insert_rowid())?;
+                self.log_change(uuid.as_deref(), "created", None, Some(&set.describe()))?;
+            }
+            _ => {
+                let summary = describe_sets(sets.iter().map(|set| set.timestamp.as_str()));
+                self.log_change(None, "batch", None, Some(&summary))?;
+            }
+        }
+        tx.commit()?;
+        self.journal_inserted_after(last_id)?;
+        Ok(sets.len())
+    }
+
+    fn get_today_workouts(&self) -> Result<Vec<WorkoutRecord>> {
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        let mut stmt = self.conn.prepare(
+            &format!(
+                "SELECT exercise_type, count, timestamp, rpe, id, weight, {TAGS}, notes FROM workouts
+                 WHERE substr(timestamp, 1, 10) = ?1 AND deleted_at IS NULL
+                 ORDER BY timestamp ASC"
+            ),
+        )?;
+
+        let records = stmt
+            .query_map([today], |row| {
+                Ok(WorkoutRecord {
+                    id: row.get(4)?,
+                    exercise_type: row.get(0)?,
+                    count: row.get(1)?,
+                    timestamp: row.get(2)?,
+                    rpe: row.get(3)?,
+                    weight: row.get(5)?,
+                    tags: tags::split(row.get(6)?),
+                    note: row.get(7)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        
+        Ok(records)
+    }
+
+    fn get_last_workout_date(&self) -> Result<Option<String>> {
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT substr(timestamp, 1, 10) as workout_date
+             FROM workouts
+             WHERE substr(timestamp, 1, 10) < ?1 AND deleted_at IS NULL
+             ORDER BY workout_date DESC
+             LIMIT 1",
+        )?;
+        
+        let mut rows = stmt.query([today])?;
+        if let Some(row) = rows.next()? {
+            Ok(Some(row.get(0)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn get_workouts_by_date(&self, date: &str) -> Result<Vec<WorkoutRecord>> {
+        let mut stmt = self.conn.prepare(
+            &format!(
+                "SELECT exercise_type, count, timestamp, rpe, id, weight, {TAGS}, notes FROM workouts
+                 WHERE substr(timestamp, 1, 10) = ?1 AND deleted_at IS NULL
+                 ORDER BY timestamp ASC"
+            ),
+        )?;
+
+        let records = stmt
+            .query_map([date], |row| {
+                Ok(WorkoutRecord {
+                    id: row.get(4)?,
+                    exercise_type: row.get(0)?,
+                    count: row.get(1)?,
+                    timestamp: row.get(2)?,
+                    rpe: row.get(3)?,
+                    weight: row.get(5)?,
+                    tags: tags::split(row.get(6)?),
+                    note: row.get(7)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        
+        Ok(records)
+    }
+
+    fn get_unique_dates(&self, filter: &HistoryFilter) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT date(w.timestamp) as workout_date
+             FROM workouts w
+             LEFT JOIN exercises e ON e.name = w.exercise_type
+             WHERE (?1 IS NULL OR COALESCE(e.category, ?3) = ?1)
+               AND (?2 IS NULL OR w.exercise_type = ?2)
+               AND (?4 IS NULL OR w.id IN (SELECT wt.workout_id FROM workout_tags wt JOIN tags t ON t.id = wt.tag_id WHERE t.name = ?4))
+               AND w.deleted_at IS NULL
+             ORDER BY workout_date DESC",
+        )?;
+
+        let (category, exercise, tag) = match filter {
+            HistoryFilter::All => (None, None, None),
+            HistoryFilter::Category(category) => (Some(category.as_str()), None, None),
+            HistoryFilter::Exercise(name) => (None, Some(name.as_str()), None),
+            HistoryFilter::Tag(tag) => (None, None, Some(tag.as_str())),
+        };
+        let dates = stmt
+            .query_map(params![category, exercise, exercises::OTHER_CATEGORY, tag], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        
+        Ok(dates)
+    }
+
+    fn delete_workout(&self, id: i64) -> Result<()> {
+        let now = Local::now().format(dates::TIMESTAMP_FORMAT).to_string();
+        let tx = self.conn.unchecked_transaction()?;
+        let changed = tx.execute(
+            "UPDATE workouts SET deleted_at = ?2 WHERE id = ?1 AND deleted_at IS NULL",
+            params![id, now],
+        )?;
+        if changed > 0 {
+            self.log_change(self.workout_uuid(id)?.as_deref(), "deleted_at", None, Some(&now))?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn restore_workout(&self, id: i64) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        let deleted_at: Option<String> =
+            tx.query_row("SELECT deleted_at FROM workouts WHERE id = ?1", [id], |row| row.get(0))?;
+        tx.execute("UPDATE workouts SET deleted_at = NULL WHERE id = ?1", [id])?;
+        self.log_change(self.workout_uuid(id)?.as_deref(), "deleted_at", deleted_at.as_deref(), None)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn update_workout_count(&self, id: i64, count: i32) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        let (old, uuid): (i64, Option<String>) =
+            tx.query_row("SELECT count, uuid FROM workouts WHERE id = ?1", [id], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?;
+        if old != count as i64 {
+            tx.execute("UPDATE workouts SET count = ?2 WHERE id = ?1", params![id, count])?;
+            self.log_change(uuid.as_deref(), "count", Some(&old.to_string()), Some(&count.to_string()))?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csv_import::ImportSet;
+    use crate::jsonl_store::{JsonlStore, WORKOUT_COLUMNS};
+    use crate::list::{ListFilter, ListFormat};
+    use chrono::{Duration, NaiveDate};
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("fitness-tracker-store-{}-{name}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    /// A `JsonlStore` over a new file holding a fresh database's rows, so
+    /// it knows the same exercises as `Database::new`.
+    fn jsonl_store(name: &str) -> (JsonlStore, PathBuf) {
+        let path = temp_path(name);
+        std::fs::write(&path, Database::new(":memory:").unwrap().to_jsonl().unwrap()).unwrap();
+        (JsonlStore::open(&path).unwrap(), path)
+    }
+
+    /// A fresh database for each way the app runs, both of them SQLite.
+    fn app_backends(name: &str) -> Vec<(Backend, Database)> {
+        vec![
+            (Backend::Sqlite, Database::new(":memory:").unwrap()),
+            (Backend::Jsonl, Database::load_jsonl(&temp_path(name)).unwrap()),
+        ]
+    }
+
+    fn days_ago(days: i64) -> String {
+        (Local::now().date_naive() - Duration::days(days)).format("%Y-%m-%d 08:00:00").to_string()
+    }
+
+    fn amounts(records: &[WorkoutRecord]) -> Vec<(String, i32)> {
+        records.iter().map(|r| (r.exercise_type.clone(), r.count)).collect()
+    }
+
+    fn logs_and_reads_back_by_day(store: &impl WorkoutStore, label: &str) {
+        store.add_workout("squats", 20, Some(7), None).unwrap();
+        let earlier = [
+            NewWorkout { timestamp: days_ago(2), ..NewWorkout::now("plank", 60, None, None) },
+            NewWorkout { timestamp: days_ago(1), ..NewWorkout::now("push-ups", 15, None, None) },
+        ];
+        assert_eq!(store.add_workouts(&earlier).unwrap(), 2, "{label}");
+        store.add_workout("squats", 25, None, None).unwrap();
+
+        let today = store.get_today_workouts().unwrap();
+        assert_eq!(amounts(&today), [("squats".to_string(), 20), ("squats".to_string(), 25)], "{label}");
+        assert_eq!(today[0].rpe, Some(7), "{label}");
+        let yesterday = (Local::now().date_naive() - Duration::days(1)).to_string();
+        assert_eq!(store.get_last_workout_date().unwrap(), Some(yesterday.clone()), "{label}");
+        assert_eq!(amounts(&store.get_workouts_by_date(&yesterday).unwrap()), [("push-ups".to_string(), 15)], "{label}");
+
+        let all = store.get_unique_dates(&HistoryFilter::All).unwrap();
+        assert_eq!(all.len(), 3, "{label}");
+        assert_eq!(all[1], yesterday, "newest first for {label}");
+        let legs = store.get_unique_dates(&HistoryFilter::Category("legs".to_string())).unwrap();
+        assert_eq!(legs, [all[0].clone()], "{label}");
+        let other = store.get_unique_dates(&HistoryFilter::Category(exercises::OTHER_CATEGORY.to_string())).unwrap();
+        let plank = store.get_unique_dates(&HistoryFilter::Exercise("plank".to_string())).unwrap();
+        assert_eq!(other, plank, "{label}");
+        assert_eq!(plank, [all[2].clone()], "{label}");
+    }
+
+    #[test]
+    fn sets_are_logged_and_read_back_by_day() {
+        logs_and_reads_back_by_day(&Database::new(":memory:").unwrap(), "sqlite");
+        let (store, path) = jsonl_store("days");
+        logs_and_reads_back_by_day(&store, "jsonl");
+        logs_and_reads_back_by_day(&Database::load_jsonl(&temp_path("days-app")).unwrap(), "jsonl app");
+        std::fs::remove_file(path).unwrap();
+    }
+
+    fn edits_and_trashes(store: &impl WorkoutStore, label: &str) {
+        store.add_workout("squats", 20, None, None).unwrap();
+        store.add_workout("squats", 30, None, None).unwrap();
+        let ids: Vec<i64> = store.get_today_workouts().unwrap().iter().map(|r| r.id).collect();
+
+        store.update_workout_count(ids[0], 22).unwrap();
+        store.delete_workout(ids[1]).unwrap();
+        assert_eq!(amounts(&store.get_today_workouts().unwrap()), [("squats".to_string(), 22)], "{label}");
+        store.restore_workout(ids[1]).unwrap();
+        assert_eq!(store.get_today_workouts().unwrap().len(), 2, "{label}");
+        assert!(store.update_workout_count(ids[1] + 1, 5).is_err(), "{label}");
+    }
+
+    #[test]
+    fn edits_and_the_trash() {
+        let db = Database::new(":memory:").unwrap();
+        edits_and_trashes(&db, "sqlite");
+        assert_eq!(db.count_audit_log().unwrap(), 5);
+
+        let (store, path) = jsonl_store("edits");
+        edits_and_trashes(&store, "jsonl");
+        // The file is what the app's JSON-lines backend would have written
+        let text = std::fs::read_to_string(&path).unwrap();
+        let loaded = Database::load_jsonl(&path).unwrap();
+        assert_eq!(loaded.count_audit_log().unwrap(), 5);
+        assert_eq!(loaded.to_jsonl().unwrap(), text);
+        assert_eq!(amounts(&loaded.get_today_workouts().unwrap()), amounts(&store.get_today_workouts().unwrap()));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn jsonl_store_reads_what_the_app_wrote() {
+        let path = temp_path("shared");
+        let db = Database::load_jsonl(&path).unwrap();
+        db.add_workout("squats", 20, None, Some(62.5)).unwrap();
+        db.tag_workout(db.last_workout_id().unwrap(), &["heavy".to_string()]).unwrap();
+        db.save().unwrap();
+
+        let store = JsonlStore::open(&path).unwrap();
+        let today = store.get_today_workouts().unwrap();
+        assert_eq!(amounts(&today), [("squats".to_string(), 20)]);
+        assert_eq!((today[0].weight, today[0].tags.clone()), (Some(62.5), vec!["heavy".to_string()]));
+        assert_eq!(store.get_unique_dates(&HistoryFilter::Tag("heavy".to_string())).unwrap().len(), 1);
+        let list = |write: &dyn Fn(&mut Vec<u8>, &ListFilter) -> Result<()>| {
+            let mut out = Vec::new();
+            write(&mut out, &ListFilter { exercises: vec!["squats".to_string()], ..ListFilter::default() }).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let listed = list(&|out, filter| store.write_set_list(out, filter, ListFormat::Tsv));
+        assert_eq!(listed, list(&|out, filter| db.write_set_list(out, filter, ListFormat::Tsv)));
+        assert_eq!(listed.lines().count(), 2);
+        assert_eq!(store.resolve_exercise(" Squats "), db.resolve_exercise("squats").unwrap());
+        // Rows it doesn't change are written back as they were
+        store.add_workout("plank", 60, None, None).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(db.to_jsonl().unwrap().lines().all(|line| text.contains(line)));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn jsonl_store_keeps_nothing_it_could_not_write() {
+        let path = temp_path("missing-dir").join("sets.jsonl");
+        let store = JsonlStore::open(&path).unwrap();
+        assert!(store.add_workout("squats", 20, None, None).is_err());
+        assert!(store.get_today_workouts().unwrap().is_empty());
+    }
+
+    #[test]
+    fn jsonl_store_knows_every_workout_column() {
+        let db = Database::new(":memory:").unwrap();
+        let mut stmt = db.conn.prepare("SELECT name FROM pragma_table_info('workouts') ORDER BY cid").unwrap();
+        let columns: Vec<String> = stmt.query_map([], |row| row.get(0)).unwrap().map(Result::unwrap).collect();
+        assert_eq!(columns, WORKOUT_COLUMNS);
+    }
+
+    #[test]
+    fn jsonl_survives_a_reload() {
+        let path = temp_path("reload");
+        let db = Database::load_jsonl(&path).unwrap();
+        db.add_workout("squats, deep", 20, Some(8), None).unwrap();
+        db.conn.execute("UPDATE workouts SET weight = 62.5", []).unwrap();
+        db.set_exercise_category("squats", Some("core")).unwrap();
+        db.save().unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.lines().all(|line| line.starts_with("{\"table\":")));
+
+        let reloaded = Database::load_jsonl(&path).unwrap();
+        assert_eq!(reloaded.to_jsonl().unwrap(), text);
+        let today = reloaded.get_today_workouts().unwrap();
+        assert_eq!(amounts(&today), [("squats, deep".to_string(), 20)]);
+        assert_eq!(today[0].weight, Some(62.5));
+        assert_eq!(format!("{:?}", reloaded.get_exercises().unwrap()), format!("{:?}", db.get_exercises().unwrap()));
+
+        // Nothing changed, so nothing is written
+        std::fs::remove_file(&path).unwrap();
+        reloaded.save().unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn jsonl_rejects_unknown_tables_and_columns() {
+        let path = temp_path("bad");
+        std::fs::write(&path, "{\"table\":\"nope\",\"id\":1}\n").unwrap();
+        assert!(Database::load_jsonl(&path).is_err());
+        std::fs::write(&path, "{\"table\":\"workouts\",\"id\":1,\"shoe\":\"left\"}\n").unwrap();
+        assert!(Database::load_jsonl(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn imports_and_exports_match_across_backends() {
+        let day = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let set = |exercise: &str, reps: i64, minute: i64| ImportSet {
+            line: 2,
+            raw: String::new(),
+            at: day.and_hms_opt(8, 0, 0).unwrap() + Duration::minutes(minute),
+            exercise: exercise.to_string(),
+            category: Some("legs".to_string()),
+            reps: Some(reps),
+            weight: None,
+            seconds: None,
+            distance: None,
+            rpe: None,
+            uuid: None,
+        };
+        let sets = [set("squats", 20, 0), set("lunges", 12, 5), set("squats", 15, 10)];
+        let results: Vec<String> = app_backends("io")
+            .into_iter()
+            .map(|(_, db)| {
+                let report = db.import_sets(&sets, &HashMap::new(), crate::units::DistanceUnit::Km, "test").unwrap();
+                assert_eq!(report.exercises.len(), 2);
+                format!("{:?} {:?}", report.exercises, db.ics_days(day, day).unwrap())
+            })
+            .collect();
+        assert_eq!(results[0], results[1]);
+    }
+
+    fn batch(size: i32) -> Vec<NewWorkout> {
+        (0..size)
+            .map(|i| NewWorkout {
+                exercise_type: "squats".to_string(),
+                count: i + 1,
+                rpe: None,
+                weight: None,
+                timestamp: format!("2024-05-{:02} 08:00:00", i % 28 + 1),
+            })
+            .collect()
+    }
+
+    fn set_count(db: &Database) -> i64 {
+        db.conn.query_row("SELECT COUNT(*) FROM workouts", [], |row| row.get(0)).unwrap()
+    }
+
+    fn writes_a_large_batch_quickly(store: &impl WorkoutStore, label: &str) {
+        let started = std::time::Instant::now();
+        assert_eq!(store.add_workouts(&batch(10_000)).unwrap(), 10_000, "{label}");
+        assert!(started.elapsed().as_secs() < 5, "{label} took {:?}", started.elapsed());
+        let days = store.get_unique_dates(&HistoryFilter::All).unwrap();
+        let sets: usize = days.iter().map(|day| store.get_workouts_by_date(day).unwrap().len()).sum();
+        assert_eq!((days.len(), sets), (28, 10_000), "{label}");
+    }
+
+    #[test]
+    fn a_large_batch_is_written_quickly_in_one_go() {
+        writes_a_large_batch_quickly(&Database::new(":memory:").unwrap(), "sqlite");
+        let (store, path) = jsonl_store("batch");
+        writes_a_large_batch_quickly(&store, "jsonl");
+        assert_eq!(set_count(&Database::load_jsonl(&path).unwrap()), 10_000);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn a_failure_mid_batch_writes_nothing() {
+        for (backend, db) in app_backends("batch-failure") {
+            db.conn
+                .execute_batch(
+                    "CREATE TEMP TRIGGER fail_midway BEFORE INSERT ON workouts WHEN NEW.count = 5000
+                     BEGIN SELECT RAISE(ABORT, 'injected failure'); END",
+                )
+                .unwrap();
+            let error = db.add_workouts(&batch(10_000)).unwrap_err();
+            assert!(format!("{error:#}").contains("injected failure"), "{backend:?}: {error:#}");
+            assert_eq!(set_count(&db), 0, "{backend:?}");
+            let audited: i64 = db.conn.query_row("SELECT COUNT(*) FROM audit_log", [], |row| row.get(0)).unwrap();
+            assert_eq!(audited, 0, "{backend:?}");
+        }
+    }
+
+    #[test]
+    fn a_large_batch_keeps_the_audit_history() {
+        for (backend, db) in app_backends("batch-audit") {
+            db.add_workout("push-ups", 12, None, None).unwrap();
+            let id = db.last_workout_id().unwrap();
+            db.update_workout_count(id, 15).unwrap();
+            db.add_workouts(&batch(10_000)).unwrap();
+            let fields: Vec<String> = db.get_audit_log(id).unwrap().into_iter().map(|entry| entry.field).collect();
+            assert_eq!(fields, ["created", "count"], "{backend:?}");
+            let summary: String = db
+                .conn
+                .query_row("SELECT new_value FROM audit_log WHERE row_uuid IS NULL AND field = 'batch'", [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(summary, "10000 sets from 2024-05-01 08:00:00 to 2024-05-28 08:00:00", "{backend:?}");
+        }
+    }
+}
@@ -0,0 +1,297 @@
+//! Estimated one-rep max for exercises counted in reps and done with
+//! weight, by the Epley formula. The estimate gets unreliable for long sets,
+//! so sets above `RELIABLE_REPS` are counted separately instead of used.
+
+use anyhow::Result;
+use chrono::{Duration, NaiveDate};
+
+use crate::chart;
+use crate::dates::{self, week_start, WeekStart};
+use crate::exercises::Exercise;
+use crate::units;
+use crate::{App, Database};
+
+/// Sets with more reps than this don't give a usable estimate.
+pub const RELIABLE_REPS: i32 = 15;
+/// Weeks shown in the trend, ending with the current one.
+const TREND_WEEKS: usize = 8;
+
+/// Epley's estimate of the most that could be lifted once.
+pub fn epley(weight: f64, reps: i32) -> f64 {
+    weight * (1.0 + reps as f64 / 30.0)
+}
+
+/// A set with its weight in kg.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeightedSet {
+    pub date: NaiveDate,
+    pub weight: f64,
+    pub reps: i32,
+}
+
+impl WeightedSet {
+    pub fn estimate(&self) -> f64 {
+        epley(self.weight, self.reps)
+    }
+
+    pub fn reliable(&self) -> bool {
+        (1..=RELIABLE_REPS).contains(&self.reps) && self.weight > 0.0
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Estimates {
+    /// The best set on the latest day with a reliable one.
+    pub current: Option<WeightedSet>,
+    /// The best set ever.
+    pub best: Option<WeightedSet>,
+    /// Best estimate per week up to `this_week`, oldest first; `None` for
+    /// weeks without reliable sets.
+    pub weekly: Vec<Option<f64>>,
+    /// Sets left out for having too many reps.
+    pub unreliable: usize,
+}
+
+fn better(a: Option<WeightedSet>, b: WeightedSet) -> Option<WeightedSet> {
+    match a {
+        Some(a) if a.estimate() >= b.estimate() => Some(a),
+        _ => Some(b),
+    }
+}
+
+pub fn estimates(sets: &[WeightedSet], this_week: NaiveDate, week: WeekStart) -> Estimates {
+    let first_week = this_week - Duration::weeks(TREND_WEEKS as i64 - 1);
+    let mut result = Estimates {
+        current: None,
+        best: None,
+        weekly: vec![None; TREND_WEEKS],
+        unreliable: 0,
+    };
+    let mut latest_day = None;
+    for set in sets {
+        if !set.reliable() {
+            result.unreliable += 1;
+            continue;
+        }
+        result.best = better(result.best, *set);
+        if latest_day.is_none_or(|day| set.date > day) {
+            latest_day = Some(set.date);
+            result.current = Some(*set);
+        } else if latest_day == Some(set.date) {
+            result.current = better(result.current, *set);
+        }
+        let start = week_start(set.date, week);
+        if (first_week..=this_week).contains(&start) {
+            let slot = &mut result.weekly[((start - first_week).num_days() / 7) as usize];
+            *slot = Some(slot.map_or(set.estimate(), |best: f64| best.max(set.estimate())));
+        }
+    }
+    result
+}
+
+/// A bar per week scaled between the lowest and highest estimate, with a
+/// dot for weeks without one.
+pub fn sparkline(weekly: &[Option<f64>]) -> String {
+    let values: Vec<f64> = weekly.iter().flatten().copied().collect();
+    let low = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let high = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    weekly
+        .iter()
+        .map(|value| match value {
+            None => '·',
+            Some(value) => chart::spark(*value, low, high),
+        })
+        .collect()
+}
+
+impl Database {
+    /// Every set of an exercise with a weight, oldest first.
+    pub fn weighted_sets(&self, exercise: &str) -> Result<Vec<WeightedSet>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT substr(timestamp, 1, 10), weight, count FROM workouts
+             WHERE exercise_type = ?1 AND weight IS NOT NULL AND deleted_at IS NULL
+             ORDER BY timestamp, id",
+        )?;
+        let rows = stmt
+            .query_map([exercise], |row| Ok((row.get::<_, String>(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(day, weight, reps)| Some(WeightedSet { date: dates::parse_date(&day)?, weight, reps }))
+            .collect())
+    }
+
+    /// The highest estimate from any reliable set so far.
+    pub fn best_one_rep_max(&self, exercise: &str) -> Result<Option<f64>> {
+        Ok(self
+            .weighted_sets(exercise)?
+            .iter()
+            .filter(|set| set.reliable())
+            .map(WeightedSet::estimate)
+            .reduce(f64::max))
+    }
+}
+
+impl App {
+    /// Called after a weighted set is saved, with the best estimate from
+    /// before it; a set that beats it gets a toast.
+    pub(crate) fn check_one_rep_max(&mut self, exercise: &Exercise, reps: i32, weight: f64, before: Option<f64>) {
+        let set = WeightedSet {
+            date: chrono::Local::now().date_naive(),
+            weight,
+            reps,
+        };
+        if exercise.unit != "reps" || !set.reliable() {
+            return;
+        }
+        if let Some(before) = before
+            && set.estimate() > before
+        {
+            let estimate = units::format_weight(set.estimate(), self.config.units.weight);
+            self.show_toast(self.text.format("strength.new_best", &[("exercise", &exercise.name), ("estimate", &estimate)]));
+        }
+    }
+
+    /// The estimate lines of an exercise's details.
+    pub(crate) fn one_rep_max_lines(&self, exercise: &Exercise) -> Result<Vec<String>> {
+        if exercise.unit != "reps" {
+            return Ok(vec![self.text.get("strength.reps_only").to_string()]);
+        }
+        let week = self.config.week_starts_on;
+        let this_week = week_start(chrono::Local::now().date_naive(), week);
+        let estimates = estimates(&self.db.weighted_sets(&exercise.name)?, this_week, week);
+        let unit = self.config.units.weight;
+        let set_text = |set: &WeightedSet| {
+            self.text.format(
+                "strength.from_set",
+                &[
+                    ("estimate", &units::format_weight(set.estimate(), unit)),
+                    ("weight", &units::format_weight(set.weight, unit)),
+                    ("reps", &set.reps),
+                    ("date", &self.display_date(set.date)),
+                ],
+            )
+        };
+        let mut lines = Vec::new();
+        match (&estimates.current, &estimates.best) {
+            (Some(current), Some(best)) => {
+                lines.push(format!("{} {}", self.text.get("strength.current"), set_text(current)));
+                lines.push(format!("{} {}", self.text.get("strength.best"), set_text(best)));
+                let values: Vec<f64> = estimates.weekly.iter().flatten().copied().collect();
+                let change = match (values.first(), values.last()) {
+                    (Some(first), Some(last)) if values.len() > 1 => {
+                        format!("  {:+.1} {}", (last - first) / unit.kg_per_unit(), unit.label())
+                    }
+                    _ => String::new(),
+                };
+                lines.push(self.text.format(
+                    "strength.trend",
+                    &[("weeks", &TREND_WEEKS), ("spark", &sparkline(&estimates.weekly)), ("change", &change)],
+                ));
+            }
+            _ => lines.push(self.text.get("strength.no_sets").to_string()),
+        }
+        if estimates.unreliable > 0 {
+            lines.push(self.text.format("strength.unreliable", &[("sets", &estimates.unreliable), ("reps", &RELIABLE_REPS)]));
+        }
+        Ok(lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn set(day: &str, weight: f64, reps: i32) -> WeightedSet {
+        WeightedSet { date: date(day), weight, reps }
+    }
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.expect("an estimate");
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn epley_estimates() {
+        assert_close(Some(epley(100.0, 0)), 100.0);
+        assert_close(Some(epley(100.0, 3)), 110.0);
+        assert_close(Some(epley(60.0, 15)), 90.0);
+    }
+
+    #[test]
+    fn long_and_weightless_sets_are_unreliable() {
+        assert!(set("2024-05-06", 60.0, 15).reliable());
+        assert!(!set("2024-05-06", 40.0, 16).reliable());
+        assert!(!set("2024-05-06", 0.0, 5).reliable());
+        assert!(!set("2024-05-06", 60.0, 0).reliable());
+    }
+
+    #[test]
+    fn current_is_the_latest_day_and_best_is_all_time() {
+        let sets = [
+            set("2024-05-06", 100.0, 3),
+            set("2024-05-13", 80.0, 5),
+            set("2024-05-13", 85.0, 5),
+            set("2024-05-13", 40.0, 30),
+        ];
+        let result = estimates(&sets, date("2024-05-13"), WeekStart::Monday);
+        assert_eq!(result.current, Some(sets[2]));
+        assert_eq!(result.best, Some(sets[0]));
+        assert_eq!(result.unreliable, 1);
+    }
+
+    #[test]
+    fn weekly_bests_fill_the_trend_window() {
+        let sets = [
+            // Before the window
+            set("2024-03-04", 200.0, 1),
+            set("2024-04-22", 90.0, 3),
+            set("2024-04-24", 90.0, 6),
+            set("2024-05-13", 100.0, 3),
+        ];
+        let result = estimates(&sets, date("2024-05-13"), WeekStart::Monday);
+        assert_eq!(result.weekly.len(), TREND_WEEKS);
+        assert_close(result.weekly[4], 108.0);
+        assert_close(result.weekly[7], 110.0);
+        assert_eq!(result.weekly.iter().flatten().count(), 2);
+    }
+
+    #[test]
+    fn no_reliable_sets_means_no_estimate() {
+        let result = estimates(&[set("2024-05-13", 20.0, 25)], date("2024-05-13"), WeekStart::Monday);
+        assert_eq!(result.current, None);
+        assert_eq!(result.best, None);
+        assert_eq!(result.unreliable, 1);
+    }
+
+    #[test]
+    fn sparkline_scales_between_the_lowest_and_highest_week() {
+        assert_eq!(sparkline(&[Some(100.0), None, Some(107.0), Some(114.0)]), "▁·▅█");
+        assert_eq!(sparkline(&[Some(100.0), Some(100.0)]), "▅▅");
+        assert_eq!(sparkline(&[None, None]), "··");
+    }
+
+    #[test]
+    fn best_estimate_skips_trashed_and_long_sets() {
+        let db = Database::new(":memory:").unwrap();
+        let add = |count: i32, weight: f64| {
+            db.conn
+                .execute(
+                    "INSERT INTO workouts (exercise_type, count, timestamp, weight) VALUES ('squats', ?1, '2024-05-13 08:00:00', ?2)",
+                    rusqlite::params![count, weight],
+                )
+                .unwrap();
+        };
+        assert_eq!(db.best_one_rep_max("squats").unwrap(), None);
+        add(5, 90.0);
+        add(20, 80.0);
+        add(1, 150.0);
+        db.conn.execute("UPDATE workouts SET deleted_at = '2024-05-14 08:00:00' WHERE count = 1", []).unwrap();
+        assert_close(db.best_one_rep_max("squats").unwrap(), 105.0);
+        assert_eq!(db.weighted_sets("squats").unwrap().len(), 2);
+    }
+}
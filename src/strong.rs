@@ -0,0 +1,188 @@
+use anyhow::{bail, Context, Result};
+use chrono::NaiveDateTime;
+use std::path::Path;
+
+use crate::csv_import::{self, ImportFile, ImportSet, Reject};
+use crate::dates;
+use crate::units::WeightUnit;
+
+fn parse_timestamp(text: &str) -> Option<NaiveDateTime> {
+    dates::parse_timestamp(text).or_else(|| NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").ok())
+}
+
+/// Parses a Strong CSV export. Both the comma and the semicolon flavors
+/// are accepted; columns are found by name. Weights are in `weight_unit`,
+/// as Strong doesn't say. Fails only when the header lacks the columns
+/// every row needs.
+pub fn parse_strong_csv(text: &str, weight_unit: WeightUnit) -> Result<ImportFile> {
+    let Some(table) = csv_import::read_csv(text) else {
+        bail!("the file is empty");
+    };
+    let column = |name: &str| table.columns.iter().position(|c| c.trim().eq_ignore_ascii_case(name));
+    let (Some(date), Some(exercise)) = (column("Date"), column("Exercise Name")) else {
+        bail!("not a Strong export: the header needs \"Date\" and \"Exercise Name\" columns");
+    };
+    let (reps, weight, seconds, distance, rpe) =
+        (column("Reps"), column("Weight"), column("Seconds"), column("Distance"), column("RPE"));
+    if reps.is_none() && seconds.is_none() && distance.is_none() {
+        bail!("not a Strong export: no Reps, Seconds or Distance column");
+    }
+
+    let mut file = ImportFile {
+        header: table.header,
+        delimiter: table.delimiter,
+        sets: Vec::new(),
+        rejects: Vec::new(),
+    };
+    for (line, raw, fields) in table.records {
+        let field = |index: Option<usize>| index.and_then(|i| fields.get(i)).map_or("", |f| f.as_str());
+        let number = |index: Option<usize>| csv_import::parse_number(field(index));
+        let parsed = (|| {
+            let at = parse_timestamp(field(Some(date)).trim())
+                .ok_or_else(|| format!("unreadable date {:?}", field(Some(date))))?;
+            let name = field(Some(exercise)).trim();
+            if name.is_empty() {
+                return Err("no exercise name".to_string());
+            }
+            Ok(ImportSet {
+                line,
+                raw: raw.clone(),
+                at,
+                exercise: name.to_string(),
+                category: None,
+                reps: number(reps)?.map(|r| r.round() as i64),
+                weight: number(weight)?.map(|w| csv_import::to_kg(w, weight_unit)),
+                seconds: number(seconds)?.map(|s| s.round() as i64),
+                distance: number(distance)?,
+                rpe: number(rpe)?,
+                uuid: None,
+            })
+        })();
+        match parsed {
+            Ok(set) => file.sets.push(set),
+            Err(reason) => file.rejects.push(Reject { line, raw, reason }),
+        }
+    }
+    Ok(file)
+}
+
+pub fn read_strong_csv(path: &Path, weight_unit: WeightUnit) -> Result<ImportFile> {
+    let text = csv_import::read_file(path)?;
+    parse_strong_csv(&text, weight_unit).with_context(|| format!("failed to import {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ImportConfig;
+    use crate::csv_import::known_exercise;
+    use crate::units::{self, DistanceUnit};
+    use crate::Database;
+    use std::collections::HashMap;
+
+    const EXPORT: &str = "\u{feff}Date,Workout Name,Duration,Exercise Name,Set Order,Weight,Reps,Distance,Seconds,Notes,Workout Notes,RPE
+2024-05-01 08:00:00,\"Morning, legs\",45m,Squat (Barbell),1,60,10,0,0,,,8
+2024-05-01 08:00:00,\"Morning, legs\",45m,Squat (Barbell),2,62.5,8,0,0,\"felt \"\"heavy\"\"
+second line\",,9
+2024-05-01 08:00:00,\"Morning, legs\",45m,Plank,1,0,0,0,60,,,
+not a date,Evening,10m,Push Up,1,0,15,0,0,,,
+2024-05-02 18:00:00,Evening,10m,Push Up,1,0,abc,0,0,,,
+2024-05-02 18:00:00,Evening,10m,Push Up,2,0,20,0,0,,,
+2024-05-02 18:00:00,Evening,10m,Running,1,0,0,5.2,1800,,,
+2024-05-02 18:00:00,Evening,10m,Squat (Barbell),3,0,0,0,30,,,
+";
+
+    fn config() -> ImportConfig {
+        let mut config = ImportConfig::default();
+        config.exercises.insert("Squat (Barbell)".to_string(), "squats".to_string());
+        config
+    }
+
+    #[test]
+    fn parses_rows_and_collects_unparseable_ones() {
+        let file = parse_strong_csv(EXPORT, WeightUnit::Kg).unwrap();
+        assert_eq!(file.delimiter, ',');
+        assert_eq!(file.sets.len(), 6);
+        assert_eq!(file.sets[1].weight, Some(62.5));
+        assert_eq!(file.sets[1].reps, Some(8));
+        assert_eq!(file.sets[1].rpe, Some(9.0));
+        assert_eq!(file.sets[2].seconds, Some(60));
+        assert_eq!(file.sets[2].reps, None);
+        assert_eq!(file.sets[3].line, 8);
+        let reasons: Vec<(usize, &str)> = file.rejects.iter().map(|r| (r.line, r.reason.as_str())).collect();
+        assert_eq!(reasons, [(6, "unreadable date \"not a date\""), (7, "not a number: \"abc\"")]);
+        assert!(file.sets[1].raw.contains("\"felt \"\"heavy\"\"\nsecond line\""));
+    }
+
+    #[test]
+    fn semicolon_exports_with_decimal_commas() {
+        let text = "Date;Exercise Name;Set Order;Weight;Reps\n2024-05-01 08:00;Squat (Barbell);1;62,5;8\n";
+        let file = parse_strong_csv(text, WeightUnit::Kg).unwrap();
+        assert_eq!(file.delimiter, ';');
+        assert_eq!(file.sets[0].weight, Some(62.5));
+        assert_eq!(file.sets[0].at, dates::parse_timestamp("2024-05-01 08:00:00").unwrap());
+    }
+
+    #[test]
+    fn other_csv_files_are_refused() {
+        assert!(parse_strong_csv("Start Date,End Date\n", WeightUnit::Kg).is_err());
+        assert!(parse_strong_csv("", WeightUnit::Kg).is_err());
+    }
+
+    #[test]
+    fn imports_with_mapping_units_and_weight() {
+        let db = Database::new(":memory:").unwrap();
+        let file = parse_strong_csv(EXPORT, WeightUnit::Kg).unwrap();
+        let exercises = db.get_exercises().unwrap();
+        let mapping: HashMap<String, String> = file
+            .sets
+            .iter()
+            .map(|s| (s.exercise.clone(), known_exercise(&s.exercise, &exercises, &config()).unwrap_or(s.exercise.clone())))
+            .collect();
+        let report = db.import_sets(&file.sets, &mapping, DistanceUnit::Km, "Strong").unwrap();
+
+        let summary: Vec<(&str, &str, usize, i64)> = report
+            .exercises
+            .iter()
+            .map(|e| (e.name.as_str(), e.unit.as_str(), e.sets, e.total))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("squats", "reps", 2, 18),
+                ("Plank", units::SECONDS, 1, 60),
+                ("Push Up", "reps", 1, 20),
+                ("Running", units::DISTANCE, 1, 5200),
+            ]
+        );
+        // squats counts reps, and the third set only has seconds
+        assert_eq!(report.rejects.len(), 1);
+        assert_eq!(report.rejects[0].reason, "no reps value for Squat (Barbell)");
+        assert_eq!(report.rejects[0].line, 10);
+
+        let weight: f64 = db
+            .conn
+            .query_row("SELECT weight FROM workouts WHERE exercise_type = 'squats' AND count = 8", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(weight, 62.5);
+        let unit: String = db
+            .conn
+            .query_row("SELECT unit FROM exercises WHERE name = 'Plank'", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(unit, units::SECONDS);
+
+        // Importing again finds every set already there
+        let again = db.import_sets(&file.sets, &mapping, DistanceUnit::Km, "Strong").unwrap();
+        assert!(again.exercises.is_empty());
+        assert_eq!(again.duplicates, 5);
+    }
+
+    #[test]
+    fn pounds_are_stored_as_kilograms() {
+        let db = Database::new(":memory:").unwrap();
+        let file = parse_strong_csv("Date,Exercise Name,Weight,Reps\n2024-05-01 08:00:00,squats,100,5\n", WeightUnit::Lbs).unwrap();
+        db.import_sets(&file.sets, &HashMap::new(), DistanceUnit::Km, "Strong").unwrap();
+        let weight: f64 = db.conn.query_row("SELECT weight FROM workouts", [], |r| r.get(0)).unwrap();
+        assert!((weight - 45.359237).abs() < 1e-9);
+    }
+}
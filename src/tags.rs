@@ -0,0 +1,255 @@
+//! Tags across exercises, like "morning", "gym" or "travel": typed on the
+//! add form as a comma-separated list, shown after each set in the history,
+//! and used to filter the history and the stats. Renaming and deleting
+//! them is a panel of the settings screen.
+
+use anyhow::Result;
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Cell, Row, Table},
+    Frame,
+};
+use rusqlite::params;
+
+use crate::exercises::render_dialog;
+use crate::input::TextInput;
+use crate::{App, Database, HistoryFilter};
+
+/// The tag list on the settings screen.
+#[derive(Debug, Default)]
+pub struct TagsState {
+    pub selected: usize,
+    /// New name being typed for the selected tag.
+    pub rename: Option<TextInput>,
+    pub confirm_delete: bool,
+}
+
+/// How a typed tag is stored: trimmed, without a leading '#', lowercase.
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim().trim_start_matches('#').trim().to_lowercase()
+}
+
+/// The tags in a comma-separated list, each once, in the order typed.
+pub fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in text.split(',').map(normalize_tag) {
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// Tags as read back from the database, comma-separated, sorted.
+pub fn split(stored: Option<String>) -> Vec<String> {
+    let mut tags: Vec<String> = stored.iter().flat_map(|tags| tags.split(',')).map(str::to_string).collect();
+    tags.sort();
+    tags
+}
+
+/// The first of `known` that the last tag typed in `text` is the start of,
+/// leaving out those already typed.
+pub fn completion<'a>(text: &str, known: &'a [String]) -> Option<&'a str> {
+    let (typed, last) = text.rsplit_once(',').unwrap_or(("", text));
+    let last = normalize_tag(last);
+    if last.is_empty() {
+        return None;
+    }
+    let typed = parse_tags(typed);
+    known.iter().map(String::as_str).find(|tag| tag.starts_with(&last) && *tag != last && !typed.iter().any(|t| t == tag))
+}
+
+/// `text` with its last tag replaced by `tag`, ready for the next one.
+pub fn complete(text: &str, tag: &str) -> String {
+    match text.rsplit_once(',') {
+        Some((typed, _)) => format!("{typed}, {tag}, "),
+        None => format!("{tag}, "),
+    }
+}
+
+impl Database {
+    /// Every tag with how many sets outside the trash carry it, by name.
+    pub fn tags(&self) -> Result<Vec<(String, i64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT t.name, COUNT(w.id) FROM tags t
+             LEFT JOIN workout_tags wt ON wt.tag_id = t.id
+             LEFT JOIN workouts w ON w.id = wt.workout_id AND w.deleted_at IS NULL
+             GROUP BY t.id
+             ORDER BY t.name",
+        )?;
+        let tags = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?.collect::<Result<Vec<_>, _>>()?;
+        Ok(tags)
+    }
+
+    pub fn tag_names(&self) -> Result<Vec<String>> {
+        Ok(self.tags()?.into_iter().map(|(name, _)| name).collect())
+    }
+
+    /// Attaches `tags` to the set, creating the ones that don't exist yet.
+    pub fn tag_workout(&self, workout_id: i64, tags: &[String]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        for tag in tags {
+            tx.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", [tag])?;
+            tx.execute(
+                "INSERT OR IGNORE INTO workout_tags (workout_id, tag_id) SELECT ?1, id FROM tags WHERE name = ?2",
+                params![workout_id, tag],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Renames a tag; renaming onto an existing tag merges the two.
+    pub fn rename_tag(&self, old: &str, new: &str) -> Result<()> {
+        if old == new {
+            return Ok(());
+        }
+        let tx = self.conn.unchecked_transaction()?;
+        let existing = tx.execute("UPDATE OR IGNORE tags SET name = ?2 WHERE name = ?1", [old, new])? == 0;
+        if existing {
+            tx.execute(
+                "INSERT OR IGNORE INTO workout_tags (workout_id, tag_id)
+                 SELECT wt.workout_id, (SELECT id FROM tags WHERE name = ?2)
+                 FROM workout_tags wt JOIN tags t ON t.id = wt.tag_id WHERE t.name = ?1",
+                [old, new],
+            )?;
+            self.delete_tag_in(&tx, old)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Deletes a tag, detaching it from its sets; the sets stay.
+    pub fn delete_tag(&self, name: &str) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        self.delete_tag_in(&tx, name)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn delete_tag_in(&self, tx: &rusqlite::Transaction, name: &str) -> Result<()> {
+        tx.execute("DELETE FROM workout_tags WHERE tag_id IN (SELECT id FROM tags WHERE name = ?1)", [name])?;
+        tx.execute("DELETE FROM tags WHERE name = ?1", [name])?;
+        Ok(())
+    }
+}
+
+impl App {
+    /// Tab on the tags field: completes the tag being typed.
+    pub(crate) fn complete_tag(&mut self) -> Result<()> {
+        let known = self.db.tag_names()?;
+        if let Some(tag) = completion(self.input_tags.as_str(), &known) {
+            self.input_tags = TextInput::with_text(&complete(self.input_tags.as_str(), tag));
+        }
+        Ok(())
+    }
+
+    /// Cycles the stats through no tag filter and each tag.
+    pub(crate) fn next_stats_tag(&mut self) -> Result<()> {
+        let tags = self.db.tag_names()?;
+        let next = match &self.stats_tag {
+            None => tags.first(),
+            Some(current) => tags.iter().skip_while(|tag| *tag != current).nth(1),
+        };
+        self.stats_tag = next.cloned();
+        Ok(())
+    }
+
+    pub(crate) fn handle_tags_input(&mut self, key: KeyCode) -> Result<bool> {
+        let Some(state) = &mut self.settings_screen.tags else {
+            return Ok(false);
+        };
+        let tags = self.db.tags()?;
+        let selected = tags.get(state.selected).map(|(name, _)| name.clone());
+        if state.confirm_delete {
+            state.confirm_delete = false;
+            if key == KeyCode::Char('y')
+                && let Some(name) = selected
+            {
+                self.db.delete_tag(&name)?;
+                state.selected = state.selected.min(tags.len().saturating_sub(2));
+                self.forget_tag(&name);
+                self.message = Some(self.text.format("tags.deleted", &[("tag", &name)]));
+            }
+            return Ok(false);
+        }
+        if let Some(input) = &mut state.rename {
+            match key {
+                KeyCode::Esc => state.rename = None,
+                KeyCode::Enter => {
+                    let new = normalize_tag(input.as_str());
+                    state.rename = None;
+                    if let Some(old) = selected {
+                        if new.is_empty() || new.contains(',') {
+                            self.message = Some(self.text.get("tags.invalid").to_string());
+                        } else {
+                            self.db.rename_tag(&old, &new)?;
+                            self.forget_tag(&old);
+                            self.message = Some(self.text.format("tags.renamed", &[("old", &old), ("new", &new)]));
+                        }
+                    }
+                }
+                _ => {
+                    input.handle_key(key);
+                }
+            }
+            return Ok(false);
+        }
+        match key {
+            KeyCode::Esc => self.settings_screen.tags = None,
+            KeyCode::Up => state.selected = state.selected.saturating_sub(1),
+            KeyCode::Down if state.selected + 1 < tags.len() => state.selected += 1,
+            KeyCode::Char('r') if let Some(name) = &selected => state.rename = Some(TextInput::with_text(name)),
+            KeyCode::Delete | KeyCode::Char('d') if selected.is_some() => state.confirm_delete = true,
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    /// Drops filters on a tag that was renamed or deleted.
+    fn forget_tag(&mut self, name: &str) {
+        if self.stats_tag.as_deref() == Some(name) {
+            self.stats_tag = None;
+        }
+        if self.history_filter == HistoryFilter::Tag(name.to_string()) {
+            self.history_filter = HistoryFilter::All;
+        }
+    }
+}
+
+pub fn render_tags_panel(f: &mut Frame, area: Rect, app: &App) {
+    let Some(state) = &app.settings_screen.tags else {
+        return;
+    };
+    let tags = app.db.tags().unwrap_or_default();
+    let rows: Vec<Row> = tags
+        .iter()
+        .enumerate()
+        .map(|(i, (name, count))| {
+            let mut style = Style::default();
+            if i == state.selected {
+                style = style.add_modifier(Modifier::BOLD | Modifier::REVERSED);
+            }
+            Row::new(vec![Cell::from(format!("#{name}")), Cell::from(app.text.format("tags.sets", &[("count", count)]))]).style(style)
+        })
+        .collect();
+    let title = if tags.is_empty() { app.text.get("tags.none") } else { app.text.get("tags.title") };
+    let table = Table::new(rows, [Constraint::Percentage(60), Constraint::Min(0)])
+        .header(
+            Row::new(vec![app.text.get("tags.tag"), app.text.get("tags.used")])
+                .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(table, area);
+
+    if let Some(input) = &state.rename {
+        render_dialog(f, app.text.get("tags.rename_title"), &app.text.format("tags.rename_field", &[("input", &input.as_str())]));
+    }
+    if state.confirm_delete
+        && let Some((name, count)) = tags.get(state.selected)
+    {
+        render_dialog(f, app.text.get("tags.delete_title"), &app.text.format("tags.delete_confirm", &[("tag", name), ("count", count)]));
+    }
+}
@@ -0,0 +1,107 @@
+//! Set schemes like "3×20" or "3×60s" stored with an exercise, and the
+//! guided add that walks through one: each planned set is pre-filled on
+//! the add screen, can be adjusted before it's saved, and counts towards
+//! "set 2/3". Stopping part-way keeps the sets already logged.
+
+use anyhow::Result;
+use rusqlite::params;
+
+use crate::units::{self, DistanceUnit};
+use crate::{App, Database};
+
+/// More sets than this is a typo rather than a scheme.
+const MAX_SETS: u32 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Template {
+    pub sets: u32,
+    /// Amount per set, stored like a set's count.
+    pub count: i32,
+}
+
+impl Template {
+    /// Reads "3x20", "3×60s" or "3 sets of 20", the amount in the
+    /// exercise's unit; a unit written after it is ignored.
+    pub fn parse(text: &str, unit: &str, distance: DistanceUnit) -> Option<Self> {
+        let text = text.trim().to_lowercase();
+        let (sets, amount) = text.split_once(" sets of ").or_else(|| text.split_once(['x', '×']))?;
+        let sets: u32 = sets.trim().parse().ok().filter(|sets| (1..=MAX_SETS).contains(sets))?;
+        let amount = amount.trim().trim_end_matches(|c: char| c.is_alphabetic()).trim();
+        Some(Self { sets, count: units::parse_amount(amount, unit, distance)? })
+    }
+
+    /// The scheme as typed, the inverse of `parse`.
+    pub fn text(self, unit: &str, distance: DistanceUnit) -> String {
+        let amount = units::input_text(self.count as i64, unit, distance);
+        if unit == units::SECONDS { format!("{}×{amount}s", self.sets) } else { format!("{}×{amount}", self.sets) }
+    }
+
+    pub fn from_columns(sets: Option<u32>, count: Option<i32>) -> Option<Self> {
+        Some(Self { sets: sets?, count: count? })
+    }
+}
+
+/// A guided add in progress on the add screen.
+#[derive(Debug, Clone)]
+pub struct Guided {
+    pub exercise: String,
+    pub template: Template,
+    /// Sets logged so far.
+    pub done: u32,
+}
+
+impl Database {
+    pub fn set_exercise_template(&self, name: &str, template: Option<Template>) -> Result<()> {
+        self.conn.execute(
+            "UPDATE exercises SET template_sets = ?2, template_count = ?3 WHERE name = ?1",
+            params![name, template.map(|t| t.sets), template.map(|t| t.count)],
+        )?;
+        Ok(())
+    }
+}
+
+impl App {
+    /// Starts walking through the selected exercise's scheme.
+    pub(crate) fn start_guided(&mut self) {
+        let Some(exercise) = self.picker_exercises().get(self.selected_exercise).map(|e| (*e).clone()) else {
+            return;
+        };
+        let Some(template) = exercise.template else {
+            self.message = Some(self.text.format("guided.no_template", &[("exercise", &exercise.name)]));
+            return;
+        };
+        self.guided = Some(Guided { exercise: exercise.name, template, done: 0 });
+        self.prefill_count();
+        self.message = None;
+    }
+
+    /// Counts a set just logged on the add screen towards the guided add,
+    /// finishing it after the last one.
+    pub(crate) fn guided_set_logged(&mut self, exercise: &str) {
+        let Some(guided) = self.guided.as_mut().filter(|g| g.exercise == exercise) else {
+            return;
+        };
+        guided.done += 1;
+        if guided.done >= guided.template.sets {
+            let sets = guided.template.sets;
+            self.guided = None;
+            self.message = Some(self.text.format("guided.finished", &[("sets", &sets), ("exercise", &exercise)]));
+        }
+    }
+
+    /// Ends a guided add early; the sets logged stay.
+    pub(crate) fn stop_guided(&mut self) {
+        if let Some(guided) = self.guided.take() {
+            let progress = format!("{}/{}", guided.done, guided.template.sets);
+            self.message = Some(self.text.format("guided.stopped", &[("progress", &progress), ("exercise", &guided.exercise)]));
+            self.prefill_count();
+        }
+    }
+
+    /// "Set 2/3" for the add screen while guiding.
+    pub(crate) fn guided_line(&self) -> Option<String> {
+        let guided = self.guided.as_ref()?;
+        let progress = format!("{}/{}", guided.done + 1, guided.template.sets);
+        Some(self.text.format("guided.progress", &[("progress", &progress)]))
+    }
+}
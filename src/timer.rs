@@ -0,0 +1,300 @@
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use crossterm::event::KeyCode;
+use ratatui::Frame;
+
+use crate::exercises::render_dialog;
+use crate::notify::NotifyEvent;
+use crate::{App, Screen};
+
+/// Time added or taken off by `+` and `-`.
+pub const ADJUST_STEP: Duration = Duration::from_secs(15);
+/// Cancelling a timer asks first once more than this is at stake.
+const CONFIRM_CANCEL_OVER: Duration = Duration::from_secs(5);
+
+/// The clock behind every timer: a countdown when it has a length, counting
+/// up otherwise. Elapsed time is the sum of the stretches it ran for rather
+/// than the time since it started, so a pause takes exactly nothing off.
+#[derive(Debug, Clone, Default)]
+pub struct TimerState {
+    length: Option<Duration>,
+    /// Running time from the stretches before the last pause.
+    banked: Duration,
+    /// When the current stretch began, unless paused.
+    running_since: Option<Instant>,
+}
+
+impl TimerState {
+    /// A countdown from `length`, already running.
+    pub fn countdown(length: Duration) -> Self {
+        Self::countdown_at(length, Instant::now())
+    }
+
+    fn countdown_at(length: Duration, now: Instant) -> Self {
+        Self { length: Some(length), banked: Duration::ZERO, running_since: Some(now) }
+    }
+
+    /// Time run so far; a countdown's stops at its length.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed_at(Instant::now())
+    }
+
+    fn elapsed_at(&self, now: Instant) -> Duration {
+        let elapsed = self.banked + self.running_since.map_or(Duration::ZERO, |since| now.saturating_duration_since(since));
+        self.length.map_or(elapsed, |length| elapsed.min(length))
+    }
+
+    /// Time left of a countdown; `None` when counting up.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.remaining_at(Instant::now())
+    }
+
+    fn remaining_at(&self, now: Instant) -> Option<Duration> {
+        Some(self.length? - self.elapsed_at(now))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.remaining().is_some_and(|remaining| remaining.is_zero())
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.running_since.is_none()
+    }
+
+    /// Pauses a running timer, or carries on with a paused one.
+    pub fn toggle(&mut self) {
+        self.toggle_at(Instant::now());
+    }
+
+    fn toggle_at(&mut self, now: Instant) {
+        match self.running_since.take() {
+            Some(since) => self.banked += now.saturating_duration_since(since),
+            None => self.running_since = Some(now),
+        }
+    }
+
+    /// Adds `step` to the time shown: more left of a countdown, more gone
+    /// when counting up.
+    pub fn add(&mut self, step: Duration) {
+        match &mut self.length {
+            Some(length) => *length += step,
+            None => self.banked += step,
+        }
+    }
+
+    /// Takes `step` off the time shown, down to zero; a countdown can run
+    /// out from it.
+    pub fn subtract(&mut self, step: Duration) {
+        self.subtract_at(step, Instant::now());
+    }
+
+    fn subtract_at(&mut self, step: Duration, now: Instant) {
+        let elapsed = self.elapsed_at(now);
+        match &mut self.length {
+            // Never below the time already gone, so there's nothing negative left
+            Some(length) => *length = length.saturating_sub(step).max(elapsed),
+            None => {
+                // The current stretch is banked first so the whole of it can go
+                self.banked = elapsed.saturating_sub(step);
+                self.running_since = self.running_since.map(|_| now);
+            }
+        }
+    }
+
+    /// The keys every timer shares: Space pauses and carries on, `+` and `-`
+    /// add or take off `ADJUST_STEP`. Returns whether `key` was one of them.
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Char(' ') => self.toggle(),
+            KeyCode::Char('+') => self.add(ADJUST_STEP),
+            KeyCode::Char('-') => self.subtract(ADJUST_STEP),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Whether cancelling should ask first: more than a few seconds are left
+    /// of a countdown, or gone when counting up.
+    pub fn asks_before_cancel(&self) -> bool {
+        self.remaining().unwrap_or_else(|| self.elapsed()) > CONFIRM_CANCEL_OVER
+    }
+}
+
+/// Countdown started after a set is logged.
+#[derive(Debug, Clone)]
+pub struct RestTimer {
+    pub exercise: String,
+    pub clock: TimerState,
+    /// Asking whether to stop it, after Esc.
+    pub confirm_cancel: bool,
+}
+
+impl RestTimer {
+    pub fn remaining(&self) -> Duration {
+        self.clock.remaining().unwrap_or_default()
+    }
+}
+
+/// `m:ss` for a countdown.
+pub fn format_remaining(remaining: Duration) -> String {
+    let seconds = remaining.as_secs_f64().ceil() as u64;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+impl App {
+    pub(crate) fn start_rest_timer(&mut self, exercise: &str) {
+        let seconds = self.config.timer.rest_seconds;
+        if seconds == 0 {
+            return;
+        }
+        self.rest_timer = Some(RestTimer {
+            exercise: exercise.to_string(),
+            clock: TimerState::countdown(Duration::from_secs(seconds)),
+            confirm_cancel: false,
+        });
+    }
+
+    /// The rest timer's keys, on the screens its countdown shows on; Esc
+    /// only cancels from the main screen, where it isn't "back". Returns
+    /// whether `key` went to the timer.
+    pub(crate) fn handle_rest_timer_key(&mut self, key: KeyCode) -> bool {
+        let Some(rest) = &mut self.rest_timer else {
+            return false;
+        };
+        if rest.confirm_cancel {
+            match key {
+                KeyCode::Char('y') => self.rest_timer = None,
+                KeyCode::Char('n') | KeyCode::Esc => rest.confirm_cancel = false,
+                _ => {}
+            }
+            return true;
+        }
+        match key {
+            KeyCode::Esc if matches!(self.screen, Screen::Main) => {
+                if rest.clock.asks_before_cancel() {
+                    rest.confirm_cancel = true;
+                } else {
+                    self.rest_timer = None;
+                }
+                true
+            }
+            key => rest.clock.handle_key(key),
+        }
+    }
+
+    /// Runs on every pass of the main loop, whether or not a key was pressed.
+    pub(crate) fn tick(&mut self) -> Result<()> {
+        self.finish_startup()?;
+        if let Some(timer) = self.rest_timer.take_if(|t| t.clock.is_finished()) {
+            let message = self.text.format("timer.rest_over", &[("exercise", &timer.exercise)]);
+            self.notify(NotifyEvent::TimerDone, &message);
+            self.message = Some(message);
+        }
+        self.check_amrap();
+        self.check_notifications();
+        self.check_journal();
+        self.check_webhook();
+        self.check_vacuum();
+        self.check_database();
+        self.check_config();
+        self.check_update()?;
+        self.check_goal_nag()?;
+        self.check_reminders()
+    }
+}
+
+/// Asks before the rest timer is cancelled with time still on it.
+pub fn render_rest_cancel(f: &mut Frame, app: &App) {
+    if let Some(rest) = app.rest_timer.as_ref().filter(|rest| rest.confirm_cancel) {
+        let body = app.text.format("timer.cancel", &[("time", &format_remaining(rest.remaining())), ("exercise", &rest.exercise)]);
+        render_dialog(f, app.text.get("timer.cancel_title"), &body);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn a_pause_takes_exactly_nothing_off() {
+        let start = Instant::now();
+        let mut timer = TimerState::countdown_at(secs(90), start);
+        timer.toggle_at(start + secs(20));
+        assert!(timer.is_paused());
+        // However long it stays paused
+        assert_eq!(timer.elapsed_at(start + secs(20)), secs(20));
+        assert_eq!(timer.elapsed_at(start + secs(500)), secs(20));
+        timer.toggle_at(start + secs(500));
+        assert_eq!(timer.elapsed_at(start + secs(510)), secs(30));
+        assert_eq!(timer.remaining_at(start + secs(510)), Some(secs(60)));
+    }
+
+    #[test]
+    fn running_stretches_add_up_over_many_pauses() {
+        let start = Instant::now();
+        let mut timer = TimerState::countdown_at(secs(600), start);
+        let mut now = start;
+        for _ in 0..10 {
+            // 7 seconds running, then 100 paused
+            now += secs(7);
+            timer.toggle_at(now);
+            now += secs(100);
+            timer.toggle_at(now);
+        }
+        assert_eq!(timer.elapsed_at(now), secs(70));
+        assert_eq!(timer.elapsed_at(now + Duration::from_millis(250)), Duration::from_millis(70_250));
+    }
+
+    #[test]
+    fn a_countdown_stops_at_its_length() {
+        let start = Instant::now();
+        let timer = TimerState::countdown_at(secs(60), start);
+        assert_eq!(timer.elapsed_at(start + secs(75)), secs(60));
+        assert_eq!(timer.remaining_at(start + secs(75)), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn adjusting_moves_the_time_left_of_a_countdown() {
+        let start = Instant::now();
+        let mut timer = TimerState::countdown_at(secs(60), start);
+        timer.add(ADJUST_STEP);
+        assert_eq!(timer.remaining_at(start + secs(10)), Some(secs(65)));
+        timer.subtract_at(ADJUST_STEP, start + secs(10));
+        assert_eq!(timer.remaining_at(start + secs(10)), Some(secs(50)));
+        // Taking off more than is left ends it rather than going below zero
+        timer.subtract_at(secs(120), start + secs(40));
+        assert_eq!(timer.remaining_at(start + secs(40)), Some(Duration::ZERO));
+        assert_eq!(timer.elapsed_at(start + secs(40)), secs(40));
+    }
+
+    #[test]
+    fn adjusting_moves_the_time_gone_when_counting_up() {
+        let start = Instant::now();
+        let mut timer = TimerState::default();
+        assert_eq!(timer.remaining_at(start), None);
+        timer.toggle_at(start);
+        timer.add(ADJUST_STEP);
+        assert_eq!(timer.elapsed_at(start + secs(10)), secs(25));
+        // The running stretch counts too, and the clock keeps going after
+        timer.subtract_at(secs(20), start + secs(10));
+        assert_eq!(timer.elapsed_at(start + secs(10)), secs(5));
+        assert_eq!(timer.elapsed_at(start + secs(12)), secs(7));
+        timer.subtract_at(secs(60), start + secs(12));
+        assert_eq!(timer.elapsed_at(start + secs(12)), Duration::ZERO);
+    }
+
+    #[test]
+    fn adjusting_while_paused_keeps_it_paused() {
+        let start = Instant::now();
+        let mut timer = TimerState::countdown_at(secs(60), start);
+        timer.toggle_at(start + secs(30));
+        timer.subtract_at(ADJUST_STEP, start + secs(40));
+        assert!(timer.is_paused());
+        assert_eq!(timer.remaining_at(start + secs(90)), Some(secs(15)));
+    }
+}
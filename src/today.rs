@@ -0,0 +1,118 @@
+//! `--today`: today's totals on one line, for a tmux status line or a shell
+//! prompt. The data file is opened read-only and never migrated, so it works
+//! while the UI has the file open, and the terminal is left alone.
+
+use anyhow::{bail, Result};
+use chrono::{Local, NaiveDate};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::path::Path;
+
+use crate::compare::streaks;
+use crate::store::Backend;
+use crate::units::{self, DistanceUnit};
+use crate::{dates, encryption, Database};
+
+const EMPTY: &str = "no workouts yet today";
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum TodayFormat {
+    /// One line like `squats 60/100 · push-ups 30 · streak 6d`
+    Text,
+    /// An object with the same totals, targets and streak
+    Json,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TodayExercise {
+    pub exercise: String,
+    pub unit: String,
+    /// In the exercise's stored unit.
+    pub total: i64,
+    /// The daily goal, if there is one.
+    pub target: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TodaySummary {
+    pub date: String,
+    /// In the order first done today, then goals not started yet.
+    pub exercises: Vec<TodayExercise>,
+    pub streak: usize,
+}
+
+impl TodayExercise {
+    /// `squats 60/100` with a goal, `push-ups 30` without.
+    pub fn describe(&self, distance: DistanceUnit) -> String {
+        let total = units::format_amount(self.total, &self.unit, distance);
+        match self.target {
+            Some(target) => format!("{} {total}/{}", self.exercise, units::format_amount(target, &self.unit, distance)),
+            None => format!("{} {total}", self.exercise),
+        }
+    }
+}
+
+impl TodaySummary {
+    pub fn to_line(&self, distance: DistanceUnit) -> String {
+        if self.exercises.iter().all(|e| e.total == 0) {
+            return EMPTY.to_string();
+        }
+        let mut parts: Vec<String> = self.exercises.iter().map(|e| e.describe(distance)).collect();
+        if self.streak > 0 {
+            parts.push(format!("streak {}d", self.streak));
+        }
+        parts.join(" · ")
+    }
+}
+
+impl Database {
+    pub fn today_summary(&self, today: NaiveDate) -> Result<TodaySummary> {
+        let units: Vec<(String, String)> =
+            self.get_exercises()?.into_iter().map(|exercise| (exercise.name, exercise.unit)).collect();
+        let unit = |name: &str| units.iter().find(|(n, _)| n == name).map_or("reps", |(_, unit)| unit.as_str()).to_string();
+        let mut goals = self.get_goals()?;
+
+        let day = today.format("%Y-%m-%d").to_string();
+        let mut stmt = self.conn.prepare("SELECT exercise_type, total FROM daily_totals WHERE day = ?1 ORDER BY first_at")?;
+        let done = stmt.query_map([&day], |row| Ok((row.get::<_, String>(0)?, row.get(1)?)))?.collect::<Result<Vec<_>, _>>()?;
+        let mut exercises: Vec<TodayExercise> = done
+            .into_iter()
+            .map(|(exercise, total)| TodayExercise { unit: unit(&exercise), target: goals.remove(&exercise), exercise, total })
+            .collect();
+        let mut waiting: Vec<(String, i64)> = goals.into_iter().collect();
+        waiting.sort();
+        exercises.extend(
+            waiting
+                .into_iter()
+                .map(|(exercise, target)| TodayExercise { unit: unit(&exercise), total: 0, target: Some(target), exercise }),
+        );
+
+        let mut stmt = self.conn.prepare("SELECT DISTINCT day FROM daily_totals ORDER BY day")?;
+        let days: Vec<NaiveDate> = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?
+            .iter()
+            .filter_map(|day| dates::parse_date(day))
+            .collect();
+        Ok(TodaySummary { date: day, exercises, streak: streaks(&days, today).0 })
+    }
+}
+
+/// Prints today's summary of the data file at `path` to stdout.
+pub fn print(backend: Backend, path: &Path, format: TodayFormat, distance: DistanceUnit) -> Result<()> {
+    let today = Local::now().date_naive();
+    // Nothing logged yet anywhere is still an answer, not an error
+    let summary = if path.exists() {
+        if backend == Backend::Sqlite && encryption::is_encrypted(path) {
+            bail!("{} is encrypted; --today can't unlock it", path.display());
+        }
+        Database::open_read_only(backend, path)?.today_summary(today)?
+    } else {
+        TodaySummary { date: today.format("%Y-%m-%d").to_string(), exercises: Vec::new(), streak: 0 }
+    };
+    match format {
+        TodayFormat::Text => println!("{}", summary.to_line(distance)),
+        TodayFormat::Json => println!("{}", serde_json::to_string(&summary)?),
+    }
+    Ok(())
+}
@@ -0,0 +1,220 @@
+use anyhow::Result;
+use chrono::{Duration, Local};
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Paragraph, Row, Table},
+    Frame,
+};
+
+use crate::exercises::render_dialog;
+use crate::store::WorkoutStore;
+use crate::{dates, App, Database, Screen};
+
+/// A soft-deleted set.
+#[derive(Debug, Clone)]
+pub struct TrashEntry {
+    pub id: i64,
+    pub exercise: String,
+    pub count: i64,
+    pub timestamp: String,
+    pub deleted_at: String,
+}
+
+#[derive(Debug, Default)]
+pub struct TrashState {
+    pub selected: usize,
+    pub confirm_purge: bool,
+}
+
+impl Database {
+    /// Removes a trashed set for good.
+    pub fn purge_workout(&self, id: i64) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        let uuid = self.workout_uuid(id)?;
+        let purged = tx.execute("DELETE FROM workouts WHERE id = ?1 AND deleted_at IS NOT NULL", [id])?;
+        if purged > 0 {
+            self.log_change(uuid.as_deref(), "purged", None, None)?;
+        }
+        tx.commit()?;
+        if purged > 0 {
+            self.journal_purged(&uuid.into_iter().collect::<Vec<_>>());
+        }
+        Ok(())
+    }
+
+    /// Permanently removes sets trashed more than `days` ago.
+    pub fn purge_trash_older_than(&self, days: u32) -> Result<usize> {
+        let cutoff = (Local::now() - Duration::days(days as i64))
+            .format(dates::TIMESTAMP_FORMAT)
+            .to_string();
+        let tx = self.conn.unchecked_transaction()?;
+        let uuids = if self.journaling() {
+            let mut stmt = tx.prepare("SELECT uuid FROM workouts WHERE deleted_at < ?1 AND uuid IS NOT NULL")?;
+            stmt.query_map([&cutoff], |row| row.get(0))?.collect::<Result<Vec<String>, _>>()?
+        } else {
+            Vec::new()
+        };
+        let purged = tx.execute("DELETE FROM workouts WHERE deleted_at < ?1", [&cutoff])?;
+        if purged > 0 {
+            self.log_change(None, "purged", None, Some(&format!("{purged} sets trashed before {cutoff}")))?;
+        }
+        tx.commit()?;
+        self.journal_purged(&uuids);
+        Ok(purged)
+    }
+
+    /// Trashed sets, most recently deleted first.
+    pub fn get_trash(&self) -> Result<Vec<TrashEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, exercise_type, count, timestamp, deleted_at FROM workouts
+             WHERE deleted_at IS NOT NULL
+             ORDER BY deleted_at DESC, id DESC",
+        )?;
+        let entries = stmt
+            .query_map([], |row| {
+                Ok(TrashEntry {
+                    id: row.get(0)?,
+                    exercise: row.get(1)?,
+                    count: row.get(2)?,
+                    timestamp: row.get(3)?,
+                    deleted_at: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+}
+
+impl App {
+    pub(crate) fn open_trash(&mut self) {
+        self.screen = Screen::Trash;
+        self.trash = TrashState::default();
+        self.message = None;
+    }
+
+    pub(crate) fn handle_trash_input(&mut self, key: KeyCode) -> Result<bool> {
+        let trash = self.db.get_trash()?;
+        let state = &mut self.trash;
+        let selected = trash.get(state.selected);
+
+        if state.confirm_purge {
+            state.confirm_purge = false;
+            if key == KeyCode::Char('y')
+                && let Some(entry) = selected
+            {
+                self.db.purge_workout(entry.id)?;
+                self.message = Some(self.text.get("trash.purged").to_string());
+                self.clamp_trash_selection()?;
+            }
+            return Ok(false);
+        }
+
+        match key {
+            KeyCode::Esc => self.screen = Screen::Data,
+            KeyCode::Up => state.selected = state.selected.saturating_sub(1),
+            KeyCode::Down if state.selected + 1 < trash.len() => state.selected += 1,
+            KeyCode::Char('r') => {
+                if let Some(entry) = selected {
+                    self.db.restore_workout(entry.id)?;
+                    let date = self.display_date_str(&entry.timestamp[..10.min(entry.timestamp.len())]);
+                    self.message = Some(self.text.format("trash.restored", &[("date", &date)]));
+                    self.clamp_trash_selection()?;
+                }
+            }
+            KeyCode::Char('p') if selected.is_some() => state.confirm_purge = true,
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    fn clamp_trash_selection(&mut self) -> Result<()> {
+        let len = self.db.get_trash()?.len();
+        self.trash.selected = self.trash.selected.min(len.saturating_sub(1));
+        Ok(())
+    }
+}
+
+pub fn render_trash_screen(f: &mut Frame, area: Rect, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(3)])
+        .split(area);
+
+    let trash = app.db.get_trash().unwrap_or_default();
+    let when = |timestamp: &str| {
+        dates::parse_timestamp(timestamp)
+            .map(|at| {
+                let time = dates::format_time(at.time(), app.config.ui.time_format, false);
+                format!("{} {}", app.display_date(at.date()), time)
+            })
+            .unwrap_or_else(|| timestamp.to_string())
+    };
+    let rows: Vec<Row> = trash
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let mut style = Style::default();
+            if i == app.trash.selected {
+                style = style.add_modifier(Modifier::BOLD | Modifier::REVERSED);
+            }
+            Row::new(vec![
+                when(&entry.timestamp),
+                entry.exercise.clone(),
+                app.format_count(&entry.exercise, entry.count),
+                when(&entry.deleted_at),
+            ])
+            .style(style)
+        })
+        .collect();
+
+    let title = app.text.format("trash.title", &[("count", &trash.len())]);
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(30),
+            Constraint::Percentage(25),
+            Constraint::Percentage(15),
+            Constraint::Min(0),
+        ],
+    )
+    .header(
+        Row::new(vec![
+            app.text.get("trash.logged"),
+            app.text.get("common.exercise"),
+            app.text.get("trash.amount"),
+            app.text.get("trash.deleted"),
+        ])
+        .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+    )
+    .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(table, chunks[0]);
+
+    let status = match &app.message {
+        Some(message) => message.clone(),
+        None if trash.is_empty() => app.text.get("trash.empty").to_string(),
+        None => match app.config.trash.purge_after_days {
+            0 => String::new(),
+            days => app.text.format("trash.auto_purge", &[("days", &days)]),
+        },
+    };
+    let status = Paragraph::new(status)
+        .style(Style::default().fg(Color::Green))
+        .block(Block::default().borders(Borders::ALL).title(app.text.get("common.status")));
+    f.render_widget(status, chunks[1]);
+
+    if app.trash.confirm_purge
+        && let Some(entry) = trash.get(app.trash.selected)
+    {
+        let amount = app.format_count(&entry.exercise, entry.count);
+        render_dialog(
+            f,
+            app.text.get("trash.purge_title"),
+            &app.text.format(
+                "trash.purge_confirm",
+                &[("amount", &amount), ("exercise", &entry.exercise), ("when", &when(&entry.timestamp))],
+            ),
+        );
+    }
+}
@@ -0,0 +1,108 @@
+//! Period-over-period comparisons for the stats screen: which way a figure
+//! moved since the period before, and whether volume has been climbing
+//! long enough that an easier week is worth suggesting.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trend {
+    Up,
+    Down,
+    Steady,
+}
+
+impl Trend {
+    pub fn arrow(self) -> char {
+        match self {
+            Trend::Up => '↑',
+            Trend::Down => '↓',
+            Trend::Steady => '→',
+        }
+    }
+}
+
+/// How `current` compares with `previous`; moves within `tolerance_percent`
+/// of `previous` are steady. Anything is up from nothing.
+pub fn trend(previous: f64, current: f64, tolerance_percent: u32) -> Trend {
+    let band = previous.abs() * tolerance_percent as f64 / 100.0;
+    if current > previous + band {
+        Trend::Up
+    } else if current < previous - band {
+        Trend::Down
+    } else {
+        Trend::Steady
+    }
+}
+
+/// How many of the last weeks in a row beat the week before them by more
+/// than `rise_percent`. `weekly` is oldest first, with a zero for every
+/// week without sets.
+pub fn rising_weeks(weekly: &[f64], rise_percent: u32) -> usize {
+    weekly
+        .windows(2)
+        .rev()
+        .take_while(|pair| pair[0] > 0.0 && pair[1] > pair[0] * (1.0 + rise_percent as f64 / 100.0))
+        .count()
+}
+
+/// The run of rising weeks when it's reached `weeks`, which is when an
+/// easier week is worth suggesting; 0 weeks never suggests one.
+pub fn deload_hint(weekly: &[f64], weeks: u32, rise_percent: u32) -> Option<usize> {
+    let rising = rising_weeks(weekly, rise_percent);
+    (weeks > 0 && rising >= weeks as usize).then_some(rising)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_wobbles_are_steady() {
+        assert_eq!(trend(100.0, 102.0, 5), Trend::Steady);
+        assert_eq!(trend(100.0, 98.0, 5), Trend::Steady);
+        assert_eq!(trend(100.0, 105.0, 5), Trend::Steady);
+        assert_eq!(trend(100.0, 100.0, 0), Trend::Steady);
+    }
+
+    #[test]
+    fn moves_past_the_band_go_up_or_down() {
+        assert_eq!(trend(100.0, 106.0, 5), Trend::Up);
+        assert_eq!(trend(100.0, 94.0, 5), Trend::Down);
+        assert_eq!(trend(100.0, 101.0, 0), Trend::Up);
+        assert_eq!(trend(100.0, 0.0, 5), Trend::Down);
+    }
+
+    #[test]
+    fn anything_is_up_from_nothing() {
+        assert_eq!(trend(0.0, 1.0, 5), Trend::Up);
+        assert_eq!(trend(0.0, 0.0, 5), Trend::Steady);
+    }
+
+    #[test]
+    fn arrows() {
+        assert_eq!([Trend::Up, Trend::Down, Trend::Steady].map(Trend::arrow), ['↑', '↓', '→']);
+    }
+
+    #[test]
+    fn rising_weeks_count_back_from_the_latest() {
+        assert_eq!(rising_weeks(&[100.0, 110.0, 121.0, 133.0], 5), 3);
+        // A flat week ends the run
+        assert_eq!(rising_weeks(&[100.0, 120.0, 122.0, 140.0, 160.0], 5), 2);
+        assert_eq!(rising_weeks(&[100.0, 90.0], 5), 0);
+        assert_eq!(rising_weeks(&[100.0], 5), 0);
+        assert_eq!(rising_weeks(&[], 5), 0);
+    }
+
+    #[test]
+    fn a_week_off_breaks_the_run() {
+        assert_eq!(rising_weeks(&[100.0, 120.0, 0.0, 50.0, 60.0], 5), 1);
+    }
+
+    #[test]
+    fn deload_hint_waits_for_enough_weeks() {
+        let weekly = [100.0, 110.0, 121.0, 133.0, 146.0];
+        assert_eq!(deload_hint(&weekly, 4, 5), Some(4));
+        assert_eq!(deload_hint(&weekly, 5, 5), None);
+        assert_eq!(deload_hint(&weekly, 0, 5), None);
+        // Rises under the threshold don't count
+        assert_eq!(deload_hint(&weekly, 4, 15), None);
+    }
+}
@@ -0,0 +1,106 @@
+use serde::Deserialize;
+
+pub const DISTANCE: &str = "distance";
+pub const SECONDS: &str = "seconds";
+
+const METERS_PER_MILE: f64 = 1609.344;
+const KG_PER_LB: f64 = 0.45359237;
+
+/// How distances are entered and displayed. They are always stored as whole
+/// meters in the `count` column so sums stay exact integers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DistanceUnit {
+    #[default]
+    Km,
+    Miles,
+}
+
+/// Unit weights are entered and displayed in, or given in by other apps.
+/// Stored weights are always kg.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WeightUnit {
+    #[default]
+    Kg,
+    Lbs,
+}
+
+impl WeightUnit {
+    pub fn label(self) -> &'static str {
+        match self {
+            WeightUnit::Kg => "kg",
+            WeightUnit::Lbs => "lb",
+        }
+    }
+
+    pub fn kg_per_unit(self) -> f64 {
+        match self {
+            WeightUnit::Kg => 1.0,
+            WeightUnit::Lbs => KG_PER_LB,
+        }
+    }
+}
+
+impl DistanceUnit {
+    pub fn label(self) -> &'static str {
+        match self {
+            DistanceUnit::Km => "km",
+            DistanceUnit::Miles => "mi",
+        }
+    }
+
+    pub fn meters_per_unit(self) -> f64 {
+        match self {
+            DistanceUnit::Km => 1000.0,
+            DistanceUnit::Miles => METERS_PER_MILE,
+        }
+    }
+}
+
+/// Whether the exercise's count field takes decimals.
+pub fn is_decimal(unit: &str) -> bool {
+    unit == DISTANCE
+}
+
+/// Parses what was typed into the count field into the stored amount.
+pub fn parse_amount(text: &str, unit: &str, distance: DistanceUnit) -> Option<i32> {
+    if is_decimal(unit) {
+        let value = text.parse::<f64>().ok().filter(|v| v.is_finite() && *v > 0.0)?;
+        let meters = (value * distance.meters_per_unit()).round();
+        (meters >= 1.0 && meters <= i32::MAX as f64).then_some(meters as i32)
+    } else {
+        text.parse::<i32>().ok().filter(|n| *n > 0)
+    }
+}
+
+/// The stored amount as it would be typed into the count field.
+pub fn input_text(count: i64, unit: &str, distance: DistanceUnit) -> String {
+    if is_decimal(unit) {
+        let value = count as f64 / distance.meters_per_unit();
+        let text = format!("{:.2}", value);
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        count.to_string()
+    }
+}
+
+/// A stored weight in kg for display in `unit`, to a tenth.
+pub fn format_weight(kg: f64, unit: WeightUnit) -> String {
+    format!("{:.1} {}", kg / unit.kg_per_unit(), unit.label())
+}
+
+/// A volume load (weight × reps) in kg for display in `unit`, rounded to a
+/// whole number.
+pub fn format_volume(kg: f64, unit: WeightUnit) -> String {
+    format!("{:.0}", kg / unit.kg_per_unit())
+}
+
+/// The stored amount for display, with a unit suffix where it isn't reps.
+pub fn format_amount(count: i64, unit: &str, distance: DistanceUnit) -> String {
+    match unit {
+        DISTANCE => format!("{:.2} {}", count as f64 / distance.meters_per_unit(), distance.label()),
+        SECONDS => format!("{}s", count),
+        _ => count.to_string(),
+    }
+}
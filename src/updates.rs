@@ -0,0 +1,110 @@
+//! Optional check for a newer release on GitHub, off unless `[updates]
+//! check` is on and the build has the `update-check` feature. It runs at
+//! most once a day on a worker thread with a short timeout, and any failure,
+//! like being offline, is simply dropped.
+
+use anyhow::Result;
+use chrono::{Duration, Local};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use crate::{dates, App};
+
+/// `app_state` key with the time of the last check.
+const CHECKED_KEY: &str = "update_checked";
+/// `app_state` key with the latest release found, reused until the next check.
+const LATEST_KEY: &str = "update_latest";
+/// `app_state` key with the release whose notice was dismissed.
+const DISMISSED_KEY: &str = "update_dismissed";
+
+/// The worker thread's answer, polled from the tick loop.
+pub type UpdateCheck = Receiver<Result<String>>;
+
+#[cfg(feature = "update-check")]
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/AndriiZhokh/fitness-tracker-tui-rust/releases/latest";
+
+/// The version of the latest release, like "0.2.0".
+#[cfg(feature = "update-check")]
+fn fetch_latest() -> Result<String> {
+    let agent = ureq::AgentBuilder::new().timeout(std::time::Duration::from_secs(5)).build();
+    let body = agent
+        .get(LATEST_RELEASE_URL)
+        .set("User-Agent", concat!("fitness-tracker-tui/", env!("CARGO_PKG_VERSION")))
+        .call()?
+        .into_string()?;
+    let body: serde_json::Value = serde_json::from_str(&body)?;
+    let tag = body["tag_name"].as_str().ok_or_else(|| anyhow::anyhow!("release without a tag"))?;
+    Ok(tag.trim_start_matches('v').to_string())
+}
+
+#[cfg(not(feature = "update-check"))]
+fn fetch_latest() -> Result<String> {
+    anyhow::bail!("built without the update-check feature")
+}
+
+/// Whether `latest` is a higher dotted version than `current`. Anything
+/// that isn't a plain number, like a "-beta" suffix, ends the comparison.
+fn is_newer(latest: &str, current: &str) -> bool {
+    let parts = |version: &str| -> Vec<u64> {
+        version
+            .split('.')
+            .map_while(|part| part.parse().ok())
+            .collect()
+    };
+    parts(latest) > parts(current)
+}
+
+impl App {
+    /// Starts the background check, or reuses the last answer when the
+    /// previous check was less than a day ago.
+    pub(crate) fn start_update_check(&mut self) -> Result<()> {
+        let now = Local::now().naive_local();
+        let checked = self.db.get_state(CHECKED_KEY)?.and_then(|at| dates::parse_timestamp(&at));
+        if checked.is_some_and(|at| now - at < Duration::days(1)) {
+            if let Some(latest) = self.db.get_state(LATEST_KEY)? {
+                self.offer_update(latest)?;
+            }
+            return Ok(());
+        }
+        // Recorded up front, so failing checks aren't retried every start
+        self.db.set_state(CHECKED_KEY, &now.format(dates::TIMESTAMP_FORMAT).to_string())?;
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = tx.send(fetch_latest());
+        });
+        self.update_check = Some(rx);
+        Ok(())
+    }
+
+    pub(crate) fn check_update(&mut self) -> Result<()> {
+        let Some(rx) = &self.update_check else {
+            return Ok(());
+        };
+        let latest = match rx.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return Ok(()),
+            Err(TryRecvError::Disconnected) => Err(anyhow::anyhow!("update check stopped")),
+        };
+        self.update_check = None;
+        if let Ok(latest) = latest {
+            self.db.set_state(LATEST_KEY, &latest)?;
+            self.offer_update(latest)?;
+        }
+        Ok(())
+    }
+
+    fn offer_update(&mut self, latest: String) -> Result<()> {
+        if is_newer(&latest, env!("CARGO_PKG_VERSION")) && self.db.get_state(DISMISSED_KEY)?.as_deref() != Some(&latest) {
+            self.update_notice = Some(latest);
+        }
+        Ok(())
+    }
+
+    /// Hides the notice until a release newer than this one comes out.
+    pub(crate) fn dismiss_update(&mut self) -> Result<()> {
+        if let Some(latest) = self.update_notice.take() {
+            self.db.set_state(DISMISSED_KEY, &latest)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+//! Optional webhook for home dashboards and the like: after every logged set
+//! a small JSON payload is POSTed to the configured URL. Each request runs on
+//! its own thread with a short timeout; failures go to a log file next to
+//! the database and show as a toast the first time only.
+
+use anyhow::Result;
+use chrono::Local;
+use serde_json::json;
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use crate::config::WebhookConfig;
+use crate::{dates, App};
+
+/// Failed requests are appended here, next to the database file.
+const LOG_FILE: &str = "fitness_tracker.webhook.log";
+
+/// How a request went, with whether it was the test from the settings screen.
+type Outcome = (bool, Result<(), String>);
+
+#[derive(Debug)]
+pub struct WebhookState {
+    sender: Sender<Outcome>,
+    outcomes: Receiver<Outcome>,
+    /// A failure was already shown this session.
+    warned: bool,
+}
+
+impl Default for WebhookState {
+    fn default() -> Self {
+        let (sender, outcomes) = mpsc::channel();
+        Self { sender, outcomes, warned: false }
+    }
+}
+
+/// What's sent for a set.
+pub fn payload(exercise: &str, count: i64, timestamp: &str, daily_total: i64) -> serde_json::Value {
+    json!({
+        "exercise": exercise,
+        "count": count,
+        "timestamp": timestamp,
+        "daily_total": daily_total,
+    })
+}
+
+#[cfg(feature = "webhook")]
+fn post(config: &WebhookConfig, body: &serde_json::Value) -> Result<()> {
+    let agent = ureq::AgentBuilder::new().timeout(std::time::Duration::from_secs(5)).build();
+    let mut request = agent
+        .post(&config.url)
+        .set("User-Agent", concat!("fitness-tracker-tui/", env!("CARGO_PKG_VERSION")));
+    if let Some(authorization) = &config.authorization {
+        request = request.set("Authorization", authorization);
+    }
+    request.set("Content-Type", "application/json").send_string(&body.to_string())?;
+    Ok(())
+}
+
+#[cfg(not(feature = "webhook"))]
+fn post(_config: &WebhookConfig, _body: &serde_json::Value) -> Result<()> {
+    anyhow::bail!("built without the webhook feature")
+}
+
+/// Appends a failed request to the log; a log that can't be written is
+/// left alone, since the toast says the same.
+fn log_failure(path: &Path, url: &str, error: &str) {
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+        let _ = writeln!(file, "{} POST {url} failed: {error}", Local::now().format(dates::TIMESTAMP_FORMAT));
+    }
+}
+
+impl App {
+    fn webhook_log_path(&self) -> PathBuf {
+        self.db.file_path().parent().unwrap_or(Path::new("")).join(LOG_FILE)
+    }
+
+    /// Sends `body` on a worker thread, so a slow or missing server never
+    /// holds up the UI.
+    fn send_webhook(&self, body: serde_json::Value, test: bool) {
+        let config = self.config.webhook.clone();
+        let sender = self.webhook.sender.clone();
+        let log = self.webhook_log_path();
+        thread::spawn(move || {
+            let outcome = post(&config, &body).map_err(|e| format!("{e:#}"));
+            if let Err(error) = &outcome {
+                log_failure(&log, &config.url, error);
+            }
+            let _ = sender.send((test, outcome));
+        });
+    }
+
+    /// Called after a set is saved.
+    pub(crate) fn notify_webhook(&self, exercise: &str, count: i64) -> Result<()> {
+        if !self.config.webhook.enabled || self.config.webhook.url.is_empty() {
+            return Ok(());
+        }
+        let total = self.db.get_today_totals()?.get(exercise).copied().unwrap_or(count);
+        let timestamp = Local::now().format(dates::TIMESTAMP_FORMAT).to_string();
+        self.send_webhook(payload(exercise, count, &timestamp, total), false);
+        Ok(())
+    }
+
+    /// Sends a sample payload from the settings screen, whether or not the
+    /// webhook is turned on.
+    pub(crate) fn test_webhook(&mut self) {
+        if self.config.webhook.url.is_empty() {
+            self.message = Some(self.text.get("webhook.no_url").to_string());
+            return;
+        }
+        let timestamp = Local::now().format(dates::TIMESTAMP_FORMAT).to_string();
+        self.send_webhook(payload("push-ups", 10, &timestamp, 30), true);
+        self.message = Some(self.text.format("webhook.testing", &[("url", &self.config.webhook.url)]));
+    }
+
+    /// Picks up finished requests on every tick.
+    pub(crate) fn check_webhook(&mut self) {
+        while let Ok((test, outcome)) = self.webhook.outcomes.try_recv() {
+            match (test, outcome) {
+                (true, Ok(())) => self.message = Some(self.text.get("webhook.test_ok").to_string()),
+                (true, Err(error)) => self.message = Some(self.text.format("webhook.test_failed", &[("error", &error)])),
+                (false, Ok(())) => {}
+                (false, Err(_)) if self.webhook.warned => {}
+                (false, Err(_)) => {
+                    self.webhook.warned = true;
+                    let log = self.webhook_log_path();
+                    self.show_toast(self.text.format("webhook.failed", &[("log", &log.display())]));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! The week at a glance: a column per day of a week, each listing the
+//! day's total per exercise, between the history's single days and the
+//! months on the stats screen. Enter opens the chosen day in the history,
+//! and Esc from there comes back to the week.
+
+use anyhow::Result;
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use crate::dates::{self, week_start};
+use crate::{App, Database, Screen};
+
+#[derive(Debug)]
+pub struct WeekState {
+    /// First day of the week shown.
+    pub start: NaiveDate,
+    /// Chosen column, 0 being the first day of the week.
+    pub day: usize,
+}
+
+impl Database {
+    /// Per day from `from` to `to` and exercise, the total amount, in the
+    /// order of each day's first set of the exercise.
+    pub fn week_totals(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<(NaiveDate, String, i64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT day, exercise_type, total
+             FROM daily_totals
+             WHERE day >= ?1 AND day <= ?2
+             ORDER BY day, first_at",
+        )?;
+        let rows = stmt
+            .query_map([from.to_string(), to.to_string()], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?))
+            })?
+            .filter_map(|row| match row {
+                Ok((day, exercise, total)) => dates::parse_date(&day).map(|day| Ok((day, exercise, total))),
+                Err(e) => Some(Err(e)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+}
+
+impl App {
+    pub(crate) fn open_week(&mut self) {
+        let today = Local::now().date_naive();
+        let start = week_start(today, self.config.week_starts_on);
+        self.week = WeekState { start, day: (today - start).num_days() as usize };
+        self.screen = Screen::Week;
+        self.message = None;
+    }
+
+    pub(crate) fn handle_week_input(&mut self, key: KeyCode) -> Result<bool> {
+        match key {
+            KeyCode::Esc => self.screen = Screen::Main,
+            KeyCode::Left => self.week.start -= Duration::weeks(1),
+            KeyCode::Right => self.week.start += Duration::weeks(1),
+            KeyCode::Tab => self.week.day = (self.week.day + 1) % 7,
+            KeyCode::BackTab => self.week.day = (self.week.day + 6) % 7,
+            KeyCode::Enter => self.open_history_day(self.week.start + Duration::days(self.week.day as i64), Screen::Week)?,
+            _ => {}
+        }
+        Ok(false)
+    }
+
+}
+
+/// `lines` cut to `height`, the last line saying how many didn't fit.
+fn fit_lines(mut lines: Vec<Line<'_>>, height: usize) -> Vec<Line<'_>> {
+    if lines.len() > height && height > 0 {
+        let hidden = lines.len() - (height - 1);
+        lines.truncate(height - 1);
+        lines.push(Line::from(Span::styled(format!("…+{hidden}"), Style::default().fg(Color::DarkGray))));
+    }
+    lines
+}
+
+/// `text` cut to `width` characters, ending in "…" when it was longer.
+fn fit_width(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        text.to_string()
+    } else {
+        let mut cut: String = text.chars().take(width.saturating_sub(1)).collect();
+        cut.push('…');
+        cut
+    }
+}
+
+pub fn render_week_screen(f: &mut Frame, area: Rect, app: &App) {
+    let today = Local::now().date_naive();
+    let start = app.week.start;
+    let end = start + Duration::days(6);
+    let totals = app.db.week_totals(start, end).unwrap_or_default();
+
+    let outer = Block::default()
+        .borders(Borders::ALL)
+        .title(app.text.format("week.title", &[("start", &app.display_date(start)), ("end", &app.display_date(end))]));
+    let inner = outer.inner(area);
+    f.render_widget(outer, area);
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Ratio(1, 7); 7])
+        .split(inner);
+
+    for (i, column) in columns.iter().enumerate() {
+        let day = start + Duration::days(i as i64);
+        let name: String = dates::weekday_name(day.weekday(), &app.text).chars().take(3).collect();
+        let mut border = Style::default();
+        if day == today {
+            border = border.fg(Color::Yellow).add_modifier(Modifier::BOLD);
+        }
+        let mut title = Style::default();
+        if i == app.week.day {
+            title = title.add_modifier(Modifier::REVERSED);
+        }
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(border)
+            .title(Span::styled(format!("{name} {}", day.day()), title));
+        let width = block.inner(*column).width as usize;
+        let height = block.inner(*column).height as usize;
+        let lines: Vec<Line> = totals
+            .iter()
+            .filter(|(date, _, _)| *date == day)
+            .map(|(_, exercise, total)| {
+                let amount = app.format_count(exercise, *total);
+                // The amount matters more than the whole name
+                let room = width.saturating_sub(amount.chars().count() + 1);
+                let text = if room == 0 { fit_width(&amount, width) } else { format!("{} {amount}", fit_width(exercise, room)) };
+                Line::from(Span::styled(text, Style::default().fg(app.exercise_color(exercise))))
+            })
+            .collect();
+        f.render_widget(Paragraph::new(fit_lines(lines, height)).block(block), *column);
+    }
+}
@@ -0,0 +1,146 @@
+//! Which weekdays get trained: how often each one had sets over a range of
+//! weeks and how many reps those days averaged, shown on the stats screen
+//! in place of the category table.
+
+use anyhow::Result;
+use chrono::{Datelike, Duration, Local, NaiveDate, Weekday};
+use ratatui::{
+    layout::Constraint,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Row, Table},
+};
+
+use crate::dates::{self, week_start, WeekStart};
+use crate::units;
+use crate::{App, Database};
+
+/// Ranges offered, in weeks up to and including this one; `None` is
+/// everything logged.
+pub const RANGES: [Option<i64>; 3] = [Some(4), Some(12), None];
+const BAR_WIDTH: usize = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeekdayPattern {
+    pub day: Weekday,
+    /// Days of this weekday with sets...
+    pub trained: i64,
+    /// ...out of how many there were in the range.
+    pub days: i64,
+    /// Reps on the trained days.
+    pub reps: i64,
+}
+
+impl WeekdayPattern {
+    pub fn share(&self) -> f64 {
+        if self.days > 0 { self.trained as f64 / self.days as f64 } else { 0.0 }
+    }
+
+    /// Average reps on the days this weekday was trained.
+    pub fn average(&self) -> Option<i64> {
+        (self.trained > 0).then(|| self.reps / self.trained)
+    }
+}
+
+/// How many `day`s fall between `from` and `to`, both included.
+fn count_weekday(from: NaiveDate, to: NaiveDate, day: Weekday) -> i64 {
+    if to < from {
+        return 0;
+    }
+    let first = from + Duration::days(day.days_since(from.weekday()) as i64);
+    if first > to { 0 } else { (to - first).num_days() / 7 + 1 }
+}
+
+/// A row per weekday starting from `week`'s first day, from the trained
+/// days and reps per weekday numbered as SQLite's `%w` (0 is Sunday).
+pub fn patterns(totals: &[(u32, i64, i64)], from: NaiveDate, to: NaiveDate, week: WeekStart) -> Vec<WeekdayPattern> {
+    let first = week_start(to, week).weekday();
+    (0..7)
+        .map(|offset| {
+            let day = (0..offset).fold(first, |day, _| day.succ());
+            let (trained, reps) = totals
+                .iter()
+                .find(|(number, _, _)| *number == day.num_days_from_sunday())
+                .map_or((0, 0), |(_, trained, reps)| (*trained, *reps));
+            WeekdayPattern { day, trained, days: count_weekday(from, to, day), reps }
+        })
+        .collect()
+}
+
+impl Database {
+    /// Per weekday (`%w`, 0 is Sunday), the days from `from` to `to` with
+    /// sets and the reps on them, archived sets included while the archive
+    /// is attached, or only sets tagged `tag` when given.
+    pub fn weekday_totals(&self, from: NaiveDate, to: NaiveDate, tag: Option<&str>) -> Result<Vec<(u32, i64, i64)>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT CAST(strftime('%w', day) AS INTEGER), COUNT(*), SUM(reps) FROM (
+                 SELECT d.day,
+                        SUM(CASE WHEN e.unit IN (?4, ?5) THEN 0 ELSE d.total END) AS reps
+                 FROM {} d
+                 LEFT JOIN exercises e ON e.name = d.exercise_type
+                 WHERE d.day >= ?2 AND d.day <= ?3
+                 GROUP BY d.day
+             )
+             GROUP BY strftime('%w', day)",
+            self.stats_daily_totals(tag)
+        ))?;
+        let day = |date: NaiveDate| date.format("%Y-%m-%d").to_string();
+        let rows = stmt
+            .query_map(rusqlite::params![tag, day(from), day(to), units::SECONDS, units::DISTANCE], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+}
+
+impl App {
+    /// The first and last day of the chosen range, starting no earlier than
+    /// the first set; `None` before any sets.
+    fn weekday_range(&self) -> Result<Option<(NaiveDate, NaiveDate)>> {
+        let today = Local::now().date_naive();
+        let Some(first) = self.db.first_stats_day(self.stats_tag.as_deref())? else {
+            return Ok(None);
+        };
+        let from = match RANGES[self.stats_weekday_range] {
+            Some(weeks) => (week_start(today, self.config.week_starts_on) - Duration::weeks(weeks - 1)).max(first),
+            None => first,
+        };
+        Ok(Some((from, today)))
+    }
+}
+
+pub fn weekday_table(app: &App) -> Table<'_> {
+    let text = &app.text;
+    let title = match RANGES[app.stats_weekday_range] {
+        Some(weeks) => text.format("weekdays.title", &[("weeks", &weeks)]),
+        None => text.get("weekdays.title_all").to_string(),
+    };
+    let range = app.weekday_range().ok().flatten();
+    let rows: Vec<Row> = match range {
+        Some((from, to)) => {
+            let totals = app.db.weekday_totals(from, to, app.stats_tag.as_deref()).unwrap_or_default();
+            // Under a week of data, a share would say 100% for a single day
+            let counts_only = (to - from).num_days() < 6;
+            patterns(&totals, from, to, app.config.week_starts_on)
+                .iter()
+                .map(|pattern| {
+                    let trained = if counts_only {
+                        text.format("weekdays.days", &[("days", &pattern.trained)])
+                    } else {
+                        format!("{:.0}%", pattern.share() * 100.0)
+                    };
+                    let bar = "█".repeat((pattern.share() * BAR_WIDTH as f64).round() as usize);
+                    let average = pattern.average().map_or("-".to_string(), |average| average.to_string());
+                    Row::new(vec![dates::weekday_name(pattern.day, text).to_string(), trained, average, bar])
+                })
+                .collect()
+        }
+        None => Vec::new(),
+    };
+    Table::new(rows, [Constraint::Length(10), Constraint::Length(7), Constraint::Length(8), Constraint::Min(BAR_WIDTH as u16)])
+        .header(
+            Row::new(vec![text.get("weekdays.day"), text.get("weekdays.trained"), text.get("weekdays.average"), ""])
+                .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::default().borders(Borders::ALL).title(title))
+}